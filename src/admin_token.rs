@@ -0,0 +1,226 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::notify::env_parse;
+use crate::secrets::Secrets;
+
+/// How long an admin UI token is valid unless `ADMIN_TOKEN_TTL_SECS` says otherwise
+pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Shortest accepted `ADMIN_TOKEN_SECRET`, in bytes
+const MIN_SECRET_LENGTH: usize = 32;
+
+/// A freshly issued admin UI token
+#[derive(Clone, Debug, Serialize)]
+pub struct IssuedToken {
+    pub token: String,
+    /// Must accompany state-changing requests made with the token, as `X-CSRF-Token`
+    pub csrf_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An admin UI token that passed verification
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedToken {
+    /// Name of the admin key holder the token was issued to
+    pub holder: String,
+    /// Id of the managed key the token was issued for, which must still be active
+    pub key_id: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Claims {
+    sub: String,
+    /// Id of the managed key that asked for the token; none for configured keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    exp: i64,
+    nonce: String,
+}
+
+/// Issues and verifies short-lived, HMAC-signed admin tokens, so no server-side sessions
+/// are needed
+///
+/// Tokens are `<claims>.<signature>`, both base64url. Replicas only accept each other's tokens
+/// when they share `ADMIN_TOKEN_SECRET`.
+pub struct TokenSigner {
+    key: hmac::Key,
+    ttl: Duration,
+}
+
+impl std::fmt::Debug for TokenSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenSigner")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TokenSigner {
+    fn default() -> Self {
+        Self::random(DEFAULT_TOKEN_TTL)
+    }
+}
+
+impl TokenSigner {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret),
+            ttl,
+        }
+    }
+
+    /// A signer with a random key; its tokens are invalidated by a restart
+    pub fn random(ttl: Duration) -> Self {
+        let mut secret = [0u8; MIN_SECRET_LENGTH];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .expect("system random number generator failed");
+        Self::new(&secret, ttl)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Issues a token for `holder`, valid for the configured time from now, and only while
+    /// managed key `key_id`, if given, is
+    pub fn issue(&self, holder: &str, key_id: Option<&str>) -> IssuedToken {
+        let mut nonce = [0u8; 16];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("system random number generator failed");
+        let expires_at =
+            Utc::now() + chrono::Duration::from_std(self.ttl).unwrap_or(chrono::Duration::MAX);
+        let claims = Claims {
+            sub: holder.to_string(),
+            kid: key_id.map(str::to_string),
+            exp: expires_at.timestamp(),
+            nonce: URL_SAFE_NO_PAD.encode(nonce),
+        };
+        let claims = URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&claims).expect("token claims always serialize"));
+        let signature = URL_SAFE_NO_PAD.encode(hmac::sign(&self.key, claims.as_bytes()));
+
+        IssuedToken {
+            csrf_token: self.csrf_token(&claims),
+            token: format!("{}.{}", claims, signature),
+            expires_at,
+        }
+    }
+
+    /// Checks the token's signature and expiry; whether its managed key is still active is up
+    /// to the caller
+    pub fn verify(&self, token: &str) -> Option<VerifiedToken> {
+        self.verify_at(token, Utc::now())
+    }
+
+    fn verify_at(&self, token: &str, now: DateTime<Utc>) -> Option<VerifiedToken> {
+        let (claims, signature) = token.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        hmac::verify(&self.key, claims.as_bytes(), &signature).ok()?;
+
+        let decoded: Claims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(claims).ok()?).ok()?;
+        if decoded.exp <= now.timestamp() {
+            return None;
+        }
+        Some(VerifiedToken {
+            holder: decoded.sub,
+            key_id: decoded.kid,
+            csrf_token: self.csrf_token(claims),
+        })
+    }
+
+    /// CSRF token bound to one admin token, so it cannot be reused with another
+    fn csrf_token(&self, claims: &str) -> String {
+        let tag = hmac::sign(&self.key, format!("csrf:{}", claims).as_bytes());
+        URL_SAFE_NO_PAD.encode(tag)
+    }
+}
+
+/// Build the admin token signer from environment variables
+///
+/// Optional: `ADMIN_TOKEN_SECRET` (a secret, at least 32 bytes; random per process when unset),
+/// `ADMIN_TOKEN_TTL_SECS` (default 900).
+pub fn load_token_signer_from_env(secrets: &Secrets) -> Result<TokenSigner, String> {
+    let ttl = env_parse("ADMIN_TOKEN_TTL_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TOKEN_TTL);
+    if ttl.is_zero() {
+        return Err("ADMIN_TOKEN_TTL_SECS must be greater than 0".to_string());
+    }
+
+    match secrets.get("ADMIN_TOKEN_SECRET") {
+        Some(secret) if secret.expose().len() < MIN_SECRET_LENGTH => Err(format!(
+            "ADMIN_TOKEN_SECRET must be at least {} bytes long",
+            MIN_SECRET_LENGTH
+        )),
+        Some(secret) => Ok(TokenSigner::new(secret.expose().as_bytes(), ttl)),
+        None => {
+            tracing::warn!(
+                "ADMIN_TOKEN_SECRET not set, admin UI tokens are only valid on this instance until it restarts"
+            );
+            Ok(TokenSigner::random(ttl))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_and_verify() {
+        let signer = TokenSigner::new(&[7; 32], Duration::from_secs(60));
+        let issued = signer.issue("alice", None);
+
+        let verified = signer.verify(&issued.token).unwrap();
+        assert_eq!(verified.holder, "alice");
+        assert_eq!(verified.key_id, None);
+        assert_eq!(verified.csrf_token, issued.csrf_token);
+        let managed = signer.issue("deploy-bot", Some("3f9c2a1b7d4e"));
+        assert_eq!(
+            signer.verify(&managed.token).unwrap().key_id.as_deref(),
+            Some("3f9c2a1b7d4e")
+        );
+
+        // Each token has its own CSRF token
+        assert_ne!(signer.issue("alice", None).csrf_token, issued.csrf_token);
+
+        // Expired tokens are rejected
+        let later = issued.expires_at + chrono::Duration::seconds(1);
+        assert_eq!(signer.verify_at(&issued.token, later), None);
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_foreign_tokens() {
+        let signer = TokenSigner::new(&[7; 32], Duration::from_secs(60));
+        let issued = signer.issue("alice", None);
+
+        let (claims, signature) = issued.token.split_once('.').unwrap();
+        let forged_claims = URL_SAFE_NO_PAD.encode(
+            serde_json::to_vec(&Claims {
+                sub: "mallory".to_string(),
+                kid: None,
+                exp: i64::MAX,
+                nonce: String::new(),
+            })
+            .unwrap(),
+        );
+        assert_eq!(
+            signer.verify(&format!("{}.{}", forged_claims, signature)),
+            None
+        );
+        assert_eq!(signer.verify(claims), None);
+        assert_eq!(signer.verify("not a token"), None);
+
+        let other = TokenSigner::new(&[8; 32], Duration::from_secs(60));
+        assert_eq!(other.verify(&issued.token), None);
+    }
+}
@@ -0,0 +1,500 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use ring::digest::{SHA256, digest};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::bindings::StorageError;
+use crate::error::AppError;
+
+/// Start of every managed API key, before its id
+pub const KEY_PREFIX: &str = "ark_";
+
+/// How often each instance reloads the managed keys from the store, to pick up keys created,
+/// rotated or expired by other instances
+pub const KEY_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest key holder name accepted
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Random bytes in a key's id, and in its secret
+const ID_BYTES: usize = 6;
+const SECRET_BYTES: usize = 32;
+
+/// What a managed key may be used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// The admin API, like a key from `ADMIN_API_KEYS`
+    Admin,
+    /// Resolving ARKs on `require_auth` shoulders, like a key from `RESOLUTION_API_KEYS`
+    Resolve,
+    /// Minting, registering, importing and shortening, like a key from `MINT_API_KEYS`
+    Mint,
+}
+
+/// A managed API key as stored; the key itself is only kept hashed
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ApiKey {
+    /// Public identifier, which the key starts with after [`KEY_PREFIX`]
+    pub id: String,
+    /// Name of the key holder, recorded as who made admin changes
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// SHA-256 of the key, as hex
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+    /// Name of the admin who created the key, or rotated the key it replaces
+    pub created_by: String,
+    /// When the key stops working; never when not set
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Id of the key this one was rotated from
+    pub rotated_from: Option<String>,
+}
+
+impl ApiKey {
+    /// What the key starts with, enough to tell which key a client holds
+    pub fn prefix(&self) -> String {
+        format!("{}{}", KEY_PREFIX, self.id)
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// A key as handed to its holder, the only time it is shown
+#[derive(Clone, Debug)]
+pub struct IssuedKey {
+    pub key: String,
+    pub record: ApiKey,
+}
+
+/// Backend that keeps the managed API keys, so they survive restarts and are shared by the
+/// instances using the same store
+pub trait KeyStore: Send + Sync {
+    /// Saves the key, replacing the one with the same id
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError>;
+    /// Every key, expired ones included
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError>;
+}
+
+/// Keys kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryKeyStore {
+    keys: RwLock<BTreeMap<String, ApiKey>>,
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        self.keys
+            .write()
+            .expect("api keys lock poisoned")
+            .insert(key.id.clone(), key.clone());
+        Ok(())
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        Ok(self
+            .keys
+            .read()
+            .expect("api keys lock poisoned")
+            .values()
+            .cloned()
+            .collect())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Hex SHA-256 of a key, as stored
+fn hash_key(key: &str) -> String {
+    hex(digest(&SHA256, key.as_bytes()).as_ref())
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator failed");
+    bytes
+}
+
+/// Checks a key holder name: 1 to 64 letters, digits, `.`, `_` or `-`
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || name.len() > MAX_NAME_LENGTH
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(format!(
+            "Key name must be 1 to {} letters, digits, '.', '_' or '-'",
+            MAX_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// API keys created, rotated and expired through the admin API rather than configured
+///
+/// Keys are read from the store at startup, after every change made on this instance, and
+/// every [`KEY_RELOAD_INTERVAL`], so requests are authenticated without a storage round trip.
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    /// Keys by id, as last loaded
+    keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl ApiKeys {
+    /// Replaces the keys held with those in `store`, returning how many there are
+    pub fn reload(&self, store: &dyn KeyStore) -> Result<usize, StorageError> {
+        let keys: HashMap<String, ApiKey> = store
+            .api_keys()?
+            .into_iter()
+            .map(|key| (key.id.clone(), key))
+            .collect();
+        let count = keys.len();
+        *self.keys.write().expect("api keys lock poisoned") = keys;
+        Ok(count)
+    }
+
+    /// The name of the holder of `token`, if it is an active key with `scope`
+    pub fn authenticate(&self, token: &str, scope: Scope) -> Option<String> {
+        self.identify(token)
+            .filter(|key| key.scopes.contains(&scope))
+            .map(|key| key.name)
+    }
+
+    /// The active key `token` is, whatever its scopes
+    pub fn identify(&self, token: &str) -> Option<ApiKey> {
+        let (id, _) = token.strip_prefix(KEY_PREFIX)?.split_once('_')?;
+        let keys = self.keys.read().expect("api keys lock poisoned");
+        let key = keys.get(id)?;
+        (constant_time_eq(hash_key(token).as_bytes(), key.hash.as_bytes())
+            && key.is_active(Utc::now()))
+        .then(|| key.clone())
+    }
+
+    /// Every key, oldest first
+    pub fn list(&self) -> Vec<ApiKey> {
+        let mut keys: Vec<ApiKey> = self
+            .keys
+            .read()
+            .expect("api keys lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        keys.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        keys
+    }
+
+    /// Whether key `id` is active and has `scope`
+    pub fn grants(&self, id: &str, scope: Scope) -> bool {
+        self.get(id)
+            .is_some_and(|key| key.is_active(Utc::now()) && key.scopes.contains(&scope))
+    }
+
+    pub fn get(&self, id: &str) -> Option<ApiKey> {
+        self.keys
+            .read()
+            .expect("api keys lock poisoned")
+            .get(id)
+            .cloned()
+    }
+
+    /// Whether a key, expired or not, is held by `name`
+    pub fn is_holder(&self, name: &str) -> bool {
+        self.keys
+            .read()
+            .expect("api keys lock poisoned")
+            .values()
+            .any(|key| key.name == name)
+    }
+
+    /// Whether some active key has `scope`
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        let now = Utc::now();
+        self.keys
+            .read()
+            .expect("api keys lock poisoned")
+            .values()
+            .any(|key| key.is_active(now) && key.scopes.contains(&scope))
+    }
+
+    /// Issues a key for `name` with `scopes`, until `expires_at` if given
+    pub fn create(
+        &self,
+        store: &dyn KeyStore,
+        name: &str,
+        scopes: &[Scope],
+        expires_at: Option<DateTime<Utc>>,
+        created_by: &str,
+    ) -> Result<IssuedKey, AppError> {
+        validate_name(name).map_err(AppError::InvalidRequest)?;
+        if scopes.is_empty() {
+            return Err(AppError::InvalidRequest(
+                "A key needs at least one scope".to_string(),
+            ));
+        }
+        let now = Utc::now();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(AppError::InvalidRequest(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+        let mut scopes = scopes.to_vec();
+        scopes.sort();
+        scopes.dedup();
+        self.issue(store, name, scopes, expires_at, created_by, None)
+    }
+
+    fn issue(
+        &self,
+        store: &dyn KeyStore,
+        name: &str,
+        scopes: Vec<Scope>,
+        expires_at: Option<DateTime<Utc>>,
+        created_by: &str,
+        rotated_from: Option<String>,
+    ) -> Result<IssuedKey, AppError> {
+        let id = hex(&random_bytes::<ID_BYTES>());
+        let key = format!(
+            "{}{}_{}",
+            KEY_PREFIX,
+            id,
+            URL_SAFE_NO_PAD.encode(random_bytes::<SECRET_BYTES>())
+        );
+        let record = ApiKey {
+            id,
+            name: name.to_string(),
+            scopes,
+            hash: hash_key(&key),
+            created_at: Utc::now(),
+            created_by: created_by.to_string(),
+            expires_at,
+            rotated_from,
+        };
+        self.save(store, &record)?;
+        Ok(IssuedKey { key, record })
+    }
+
+    /// Makes the key stop working at `at`, or now, unless it already expires before then
+    pub fn expire(
+        &self,
+        store: &dyn KeyStore,
+        id: &str,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKey, AppError> {
+        let mut key = self
+            .get(id)
+            .ok_or_else(|| AppError::NotFound(format!("No API key with id {}", id)))?;
+        let at = at.unwrap_or_else(Utc::now);
+        if key.expires_at.is_none_or(|expires_at| at < expires_at) {
+            key.expires_at = Some(at);
+            self.save(store, &key)?;
+        }
+        Ok(key)
+    }
+
+    /// Issues a new key with the holder, scopes and expiry of key `id`, which keeps working
+    /// for `grace` so clients can switch over
+    pub fn rotate(
+        &self,
+        store: &dyn KeyStore,
+        id: &str,
+        grace: Duration,
+        rotated_by: &str,
+    ) -> Result<IssuedKey, AppError> {
+        let old = self
+            .get(id)
+            .ok_or_else(|| AppError::NotFound(format!("No API key with id {}", id)))?;
+        let now = Utc::now();
+        if !old.is_active(now) {
+            return Err(AppError::InvalidRequest(format!(
+                "API key {} has expired and cannot be rotated",
+                old.prefix()
+            )));
+        }
+        // Checked before the new key is saved, so a rotation either happens in full or not at all
+        let grace_ends = chrono::Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| now.checked_add_signed(grace))
+            .ok_or_else(|| AppError::Unprocessable("grace_secs is too large".to_string()))?;
+        let issued = self.issue(
+            store,
+            &old.name,
+            old.scopes.clone(),
+            old.expires_at,
+            rotated_by,
+            Some(old.id.clone()),
+        )?;
+        self.expire(store, &old.id, Some(grace_ends))?;
+        Ok(issued)
+    }
+
+    fn save(&self, store: &dyn KeyStore, key: &ApiKey) -> Result<(), AppError> {
+        store.put_key(key)?;
+        self.keys
+            .write()
+            .expect("api keys lock poisoned")
+            .insert(key.id.clone(), key.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn test_created_keys_authenticate() {
+        let store = MemoryKeyStore::default();
+        let keys = ApiKeys::default();
+        let issued = keys
+            .create(&store, "deploy-bot", &[Scope::Admin], None, "alice")
+            .unwrap();
+        assert!(issued.key.starts_with(&issued.record.prefix()));
+        assert_ne!(issued.record.hash, issued.key);
+        assert_eq!(store.api_keys().unwrap(), vec![issued.record.clone()]);
+
+        assert_eq!(
+            keys.authenticate(&issued.key, Scope::Admin).as_deref(),
+            Some("deploy-bot")
+        );
+        assert_eq!(keys.authenticate(&issued.key, Scope::Resolve), None);
+        let mut forged = issued.key.clone();
+        forged.pop();
+        assert_eq!(keys.authenticate(&forged, Scope::Admin), None);
+        assert_eq!(keys.authenticate("not-a-managed-key", Scope::Admin), None);
+
+        // Another instance sees the key once it reloads
+        let other = ApiKeys::default();
+        assert_eq!(other.reload(&store).unwrap(), 1);
+        assert!(other.authenticate(&issued.key, Scope::Admin).is_some());
+
+        for name in ["", "with space", &"n".repeat(65)] {
+            assert!(
+                keys.create(&store, name, &[Scope::Admin], None, "alice")
+                    .is_err()
+            );
+        }
+        assert!(keys.create(&store, "bot", &[], None, "alice").is_err());
+        let past = Utc::now() - chrono::Duration::hours(1);
+        assert!(
+            keys.create(&store, "bot", &[Scope::Admin], Some(past), "alice")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_expired_keys_stop_working() {
+        let store = MemoryKeyStore::default();
+        let keys = ApiKeys::default();
+        let issued = keys
+            .create(&store, "reader", &[Scope::Resolve], None, "alice")
+            .unwrap();
+
+        let later = Utc::now() + chrono::Duration::days(1);
+        let expiring = keys.expire(&store, &issued.record.id, Some(later)).unwrap();
+        assert_eq!(expiring.expires_at, Some(later));
+        assert!(keys.authenticate(&issued.key, Scope::Resolve).is_some());
+
+        let expired = keys.expire(&store, &issued.record.id, None).unwrap();
+        assert!(!expired.is_active(Utc::now()));
+        assert_eq!(keys.authenticate(&issued.key, Scope::Resolve), None);
+        // An expiry is never pushed back
+        let again = keys.expire(&store, &issued.record.id, Some(later)).unwrap();
+        assert_eq!(again.expires_at, expired.expires_at);
+
+        assert!(matches!(
+            keys.expire(&store, "missing", None),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(!keys.has_scope(Scope::Resolve));
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_old_key_for_the_grace_period() {
+        let store = MemoryKeyStore::default();
+        let keys = ApiKeys::default();
+        let old = keys
+            .create(
+                &store,
+                "ingest",
+                &[Scope::Admin, Scope::Admin],
+                None,
+                "alice",
+            )
+            .unwrap();
+        assert_eq!(old.record.scopes, vec![Scope::Admin]);
+
+        let new = keys
+            .rotate(&store, &old.record.id, Duration::from_secs(3600), "bob")
+            .unwrap();
+        assert_eq!(new.record.name, "ingest");
+        assert_eq!(new.record.created_by, "bob");
+        assert_eq!(
+            new.record.rotated_from.as_deref(),
+            Some(old.record.id.as_str())
+        );
+        assert!(keys.authenticate(&new.key, Scope::Admin).is_some());
+        assert!(keys.authenticate(&old.key, Scope::Admin).is_some());
+        assert!(keys.get(&old.record.id).unwrap().expires_at.is_some());
+
+        let newer = keys
+            .rotate(&store, &new.record.id, Duration::ZERO, "bob")
+            .unwrap();
+        assert_eq!(keys.authenticate(&new.key, Scope::Admin), None);
+        assert!(keys.authenticate(&newer.key, Scope::Admin).is_some());
+        assert!(matches!(
+            keys.rotate(&store, &new.record.id, Duration::ZERO, "bob"),
+            Err(AppError::InvalidRequest(_))
+        ));
+        assert_eq!(keys.list().len(), 3);
+
+        // A grace period past the end of time is refused before a key is issued
+        assert!(matches!(
+            keys.rotate(
+                &store,
+                &newer.record.id,
+                Duration::from_secs(u64::MAX),
+                "bob"
+            ),
+            Err(AppError::Unprocessable(_))
+        ));
+        assert!(matches!(
+            keys.rotate(
+                &store,
+                &newer.record.id,
+                Duration::from_secs(i64::MAX as u64 / 1000),
+                "bob"
+            ),
+            Err(AppError::Unprocessable(_))
+        ));
+        assert_eq!(keys.list().len(), 3);
+    }
+}
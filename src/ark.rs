@@ -1,4 +1,8 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::AppError;
+use crate::names::{Naan, ShoulderName};
 
 /// An ARK identifier parsed into its components
 ///
@@ -7,12 +11,12 @@ use crate::AppError;
 /// normalized version used only for equality comparison per RFC specifications.
 #[derive(Debug, Clone)]
 pub struct Ark {
-    /// The original ARK string as received (only ark:/ normalized to ark)
+    /// The original ARK string as received (only the label lowercased and ark:/ normalized to ark)
     pub original: String,
     /// The NAAN (Name Assigning Authority Number) as received
-    pub naan: String,
+    pub naan: Naan,
     /// The shoulder (prefix) of the ARK as received
-    pub shoulder: String,
+    pub shoulder: ShoulderName,
     /// The blade (unique identifier) of the ARK as received
     pub blade: String,
     /// The qualifier (optional additional path) of the ARK as received. This includes any query
@@ -25,12 +29,94 @@ pub struct Ark {
 impl PartialEq for Ark {
     fn eq(&self, other: &Self) -> bool {
         // Equality is based solely on the normalized form per RFC
-        self.normalized_ark == other.normalized_ark
+        self.equivalent(other)
     }
 }
 
 impl Eq for Ark {}
 
+impl std::hash::Hash for Ark {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // Consistent with equality, so equivalent ARKs collapse in sets and maps
+        self.canonical().hash(state);
+    }
+}
+
+impl Ark {
+    /// The normalized form that decides equality, as [`normalize_ark_string`] returns it
+    pub fn canonical(&self) -> &str {
+        &self.normalized_ark
+    }
+
+    /// Whether both ARKs identify the same object; the same as `==`
+    ///
+    /// ```
+    /// use ark_service::ark::parse_ark;
+    ///
+    /// let ark = parse_ark("ark:12345/x6np1wh8k").unwrap();
+    /// assert!(ark.equivalent(&parse_ark("ark:/12345/x6-np1wh8k/").unwrap()));
+    /// ```
+    pub fn equivalent(&self, other: &Ark) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// The inflection (`?`, `??` or `?info`) ending this ARK, asking for metadata instead of
+    /// the object
+    pub fn inflection(&self) -> Option<&str> {
+        let start = self.qualifier.find('?')?;
+        let inflection = &self.qualifier[start..];
+        matches!(inflection, "?" | "??" | "?info").then_some(inflection)
+    }
+
+    /// This ARK without its inflection, if it has one
+    pub fn without_inflection(&self) -> Ark {
+        self.inflection()
+            .and_then(|inflection| {
+                parse_ark(&self.original[..self.original.len() - inflection.len()])
+            })
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// This ARK without its qualifier: the object a component is part of
+    pub fn base(&self) -> Ark {
+        parse_ark(&format!(
+            "ark:{}/{}{}",
+            self.naan, self.shoulder, self.blade
+        ))
+        .unwrap_or_else(|| self.clone())
+    }
+}
+
+/// Writes the canonical form; the spelling the ARK was parsed from is in [`Ark::original`]
+impl std::fmt::Display for Ark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.canonical())
+    }
+}
+
+/// Accepts any spelling [`parse_ark`] does
+impl std::str::FromStr for Ark {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+/// Serializes as the canonical string, so stored ARKs compare with plain string equality
+impl Serialize for Ark {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.canonical())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ark {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ark = String::deserialize(deserializer)?;
+        parse_ark(&ark).ok_or_else(|| serde::de::Error::custom(format!("invalid ARK: {}", ark)))
+    }
+}
+
 impl TryFrom<&str> for Ark {
     type Error = AppError;
 
@@ -47,6 +133,100 @@ impl TryFrom<String> for Ark {
     }
 }
 
+/// Policy for the spelling of ARKs this service emits (mint responses, links, examples)
+///
+/// Only identity-inert details are affected: the `ark:` vs `ark:/` label, NAAN case, and
+/// hyphens inside the blade. Every spelling compares equal under [`Ark`] equality.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct ArkFormat {
+    /// Emit the classic `ark:/NAAN/...` form instead of `ark:NAAN/...` (default: false)
+    #[serde(default)]
+    pub slash: bool,
+    /// Lowercase the NAAN (default: true)
+    #[serde(default = "default_lowercase_naan")]
+    pub lowercase_naan: bool,
+    /// Insert a hyphen after every N blade characters for readability (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub hyphen_group: Option<usize>,
+}
+
+fn default_lowercase_naan() -> bool {
+    true
+}
+
+impl Default for ArkFormat {
+    fn default() -> Self {
+        Self {
+            slash: false,
+            lowercase_naan: true,
+            hyphen_group: None,
+        }
+    }
+}
+
+impl ArkFormat {
+    /// Checks that the policy is usable
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hyphen_group == Some(0) {
+            return Err("hyphen_group must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Spell an ARK from its components according to this policy
+    pub fn format(&self, naan: &str, shoulder: &str, blade: &str, qualifier: &str) -> String {
+        let label = if self.slash { "ark:/" } else { "ark:" };
+        let naan = if self.lowercase_naan {
+            naan.to_lowercase()
+        } else {
+            naan.to_string()
+        };
+        let blade = match self.hyphen_group {
+            Some(group) if group > 0 => blade
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(group)
+                .map(|chunk| chunk.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-"),
+            _ => blade.to_string(),
+        };
+
+        if qualifier.is_empty() {
+            format!("{}{}/{}{}", label, naan, shoulder, blade)
+        } else if qualifier.starts_with('?') {
+            format!("{}{}/{}{}{}", label, naan, shoulder, blade, qualifier)
+        } else {
+            format!("{}{}/{}{}/{}", label, naan, shoulder, blade, qualifier)
+        }
+    }
+
+    /// Spell the normalized form of a parsed ARK according to this policy
+    pub fn format_canonical(&self, ark: &Ark) -> String {
+        // The normalized form always parses, since it is derived from a parsed ARK
+        match parse_ark(&ark.normalized_ark) {
+            Some(normalized) => self.format(
+                &normalized.naan,
+                &normalized.shoulder,
+                &normalized.blade,
+                &normalized.qualifier,
+            ),
+            None => ark.normalized_ark.clone(),
+        }
+    }
+}
+
+/// The scheme label every ARK starts with, in its canonical lowercase spelling
+const LABEL: &str = "ark:";
+
+/// The rest of `ark` after its `ark:` label, which may be spelled in any case (`ARK:`, `Ark:`)
+pub fn strip_label(ark: &str) -> Option<&str> {
+    ark.get(..LABEL.len())
+        .filter(|label| label.eq_ignore_ascii_case(LABEL))
+        .map(|_| &ark[LABEL.len()..])
+}
+
 /// Extract shoulder from ARK path (primordial shoulder: letters ending with first digit)
 pub fn extract_shoulder(path: &str) -> Option<&str> {
     for (byte_idx, ch) in path.char_indices() {
@@ -57,45 +237,106 @@ pub fn extract_shoulder(path: &str) -> Option<&str> {
     None
 }
 
+/// A normalization step that changed an ARK, in the order they are applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Everything from the first `?` was removed
+    QueryRemoved,
+    /// The `ark:` label, which is case-insensitive, was lowercased
+    LabelLowercased,
+    /// `ark:/` was rewritten to `ark:`
+    LabelSlashRemoved,
+    /// Whitespace, e.g. from text wrapping, was removed
+    WhitespaceRemoved,
+    /// Hyphens and hyphen-like characters (U+2010 to U+2015) were removed
+    HyphensRemoved,
+    /// The NAAN was lowercased
+    NaanLowercased,
+    /// Trailing `/` and `.` were removed
+    TrailingPunctuationRemoved,
+}
+
+/// Hyphen-like characters that are identity-inert, besides the ASCII hyphen
+const HYPHEN_LIKE: [char; 6] = [
+    '\u{2010}', // ‐ (HYPHEN)
+    '\u{2011}', // ‑ (NON-BREAKING HYPHEN)
+    '\u{2012}', // ‒ (FIGURE DASH)
+    '\u{2013}', // – (EN DASH)
+    '\u{2014}', // — (EM DASH)
+    '\u{2015}', // ― (HORIZONTAL BAR)
+];
+
 /// Normalize an ARK string according to RFC specifications
 /// Returns a fully normalized ARK suitable for comparison
-fn normalize_ark_string(ark: &str) -> String {
+///
+/// Stability: the steps and their order (see [`Normalization`]) only change in a major
+/// release, so normalized forms can be stored and compared across versions. Inputs that are
+/// not ARKs are normalized the same way without complaint; use [`parse_ark`] to check them.
+///
+/// ```
+/// use ark_service::ark::normalize_ark_string;
+///
+/// assert_eq!(normalize_ark_string("ark:/12345/x6-np1wh8k?foo=bar"), "ark:12345/x6np1wh8k");
+/// ```
+pub fn normalize_ark_string(ark: &str) -> String {
+    explain_normalization(ark).0
+}
+
+/// Normalize an ARK string like [`normalize_ark_string`], reporting which steps changed it
+pub fn explain_normalization(ark: &str) -> (String, Vec<Normalization>) {
+    let mut applied = Vec::new();
+    let mut step = |ark: String, normalized: String, kind: Normalization| {
+        if normalized != ark {
+            applied.push(kind);
+        }
+        normalized
+    };
+
     // Remove query string (everything from first '?' onwards)
-    let ark = ark.split('?').next().unwrap_or(ark);
+    let without_query = ark.split('?').next().unwrap_or(ark).to_string();
+    let ark = step(ark.to_string(), without_query, Normalization::QueryRemoved);
+
+    // The label is case-insensitive: ARK: and Ark: are ark:
+    let lowercased_label = match strip_label(&ark) {
+        Some(rest) if !ark.starts_with(LABEL) => format!("{}{}", LABEL, rest),
+        _ => ark.clone(),
+    };
+    let ark = step(ark, lowercased_label, Normalization::LabelLowercased);
 
     // Handle both ark: and ark:/ formats
-    let ark = ark.replace("ark:/", "ark:");
+    let without_slash = ark.replace("ark:/", "ark:");
+    let ark = step(ark, without_slash, Normalization::LabelSlashRemoved);
 
     // Remove whitespace (spaces, tabs, newlines, etc.) that may have been introduced
     // during text wrapping or copy-paste operations
-    let mut ark = ark
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .collect::<String>();
-
-    // Remove hyphens (standard ASCII hyphen)
-    ark = ark.replace("-", "");
+    let without_whitespace = ark.chars().filter(|c| !c.is_whitespace()).collect();
+    let ark = step(ark, without_whitespace, Normalization::WhitespaceRemoved);
 
-    // Remove hyphen-like characters
-    ark = ark.replace('\u{2010}', ""); // U+2010: ‐ (HYPHEN)
-    ark = ark.replace('\u{2011}', ""); // U+2011: ‑ (NON-BREAKING HYPHEN)
-    ark = ark.replace('\u{2012}', ""); // U+2012: ‒ (FIGURE DASH)
-    ark = ark.replace('\u{2013}', ""); // U+2013: – (EN DASH)
-    ark = ark.replace('\u{2014}', ""); // U+2014: — (EM DASH)
-    ark = ark.replace('\u{2015}', ""); // U+2015: ― (HORIZONTAL BAR)
+    // Remove hyphens (standard ASCII hyphen) and hyphen-like characters
+    let without_hyphens = ark
+        .chars()
+        .filter(|c| *c != '-' && !HYPHEN_LIKE.contains(c))
+        .collect();
+    let ark = step(ark, without_hyphens, Normalization::HyphensRemoved);
 
     // Lowercase the NAAN
-    if let Some(slash_pos) = ark.find('/').filter(|&pos| pos > 4) {
-        // Split into "ark:NAAN" and rest
-        let (prefix, rest) = ark.split_at(slash_pos);
-        let naan_part = &prefix[4..]; // Skip "ark:"
-        ark = format!("ark:{}{}", naan_part.to_lowercase(), rest);
-    }
+    let lowercased = match ark.find('/').filter(|&pos| pos > 4) {
+        Some(slash_pos) => {
+            // Split into "ark:NAAN" and rest
+            let (prefix, rest) = ark.split_at(slash_pos);
+            let naan_part = &prefix[4..]; // Skip "ark:"
+            format!("ark:{}{}", naan_part.to_lowercase(), rest)
+        }
+        None => ark.clone(),
+    };
+    let ark = step(ark, lowercased, Normalization::NaanLowercased);
 
     // Strip trailing structural characters (/ and .) from the end
-    ark = ark.trim_end_matches(&['/', '.'][..]).to_string();
+    let trimmed = ark.trim_end_matches(&['/', '.'][..]).to_string();
+    let ark = step(ark, trimmed, Normalization::TrailingPunctuationRemoved);
 
-    ark
+    (ark, applied)
 }
 
 /// Parse an ARK identifier into its components
@@ -104,22 +345,18 @@ fn normalize_ark_string(ark: &str) -> String {
 /// except for ark:/ -> ark: conversion. A fully normalized version is computed and stored internally
 /// for equality comparison (which removes query strings per RFC).
 pub fn parse_ark(ark: &str) -> Option<Ark> {
-    // Minimal normalization - ONLY normalize ark:/ to ark:
-    let original_form = ark.replace("ark:/", "ark:");
-
-    if !original_form.starts_with("ark:") {
-        return None;
-    }
+    // Minimal normalization - ONLY lowercase the label and normalize ark:/ to ark:
+    let original_form = format!("{}{}", LABEL, strip_label(ark)?).replace("ark:/", "ark:");
 
     // Parse components - query string becomes part of the qualifier
     let original_remainder = &original_form[4..]; // Skip "ark:"
     let mut original_parts = original_remainder.splitn(2, '/');
-    let naan = original_parts.next()?.to_string();
+    let naan = Naan::new(original_parts.next()?).ok()?;
     let rest = original_parts.next()?;
 
     // Extract shoulder from the part before query string
     let rest_without_query = rest.split('?').next().unwrap_or(rest);
-    let shoulder = extract_shoulder(rest_without_query)?.to_string();
+    let shoulder = ShoulderName::new(extract_shoulder(rest_without_query)?).ok()?;
 
     // Extract blade (without query string) and qualifier (with query string)
     let after_shoulder = &rest[shoulder.len()..];
@@ -215,6 +452,56 @@ mod tests {
         assert_eq!(with_em_dash, normal);
     }
 
+    #[test]
+    fn test_explain_normalization() {
+        let (normalized, applied) = explain_normalization("ark:/12345AB/x6-np1 wh8k/?foo=bar");
+        assert_eq!(normalized, "ark:12345ab/x6np1wh8k");
+        assert_eq!(
+            applied,
+            vec![
+                Normalization::QueryRemoved,
+                Normalization::LabelSlashRemoved,
+                Normalization::WhitespaceRemoved,
+                Normalization::HyphensRemoved,
+                Normalization::NaanLowercased,
+                Normalization::TrailingPunctuationRemoved,
+            ]
+        );
+
+        let (normalized, applied) = explain_normalization("ark:12345/x6np1wh8k");
+        assert_eq!(normalized, "ark:12345/x6np1wh8k");
+        assert!(applied.is_empty());
+    }
+
+    #[test]
+    fn test_equivalence_api() {
+        let classic = parse_ark("ark:/12345AB/x6-np1wh8k.").unwrap();
+        let modern = parse_ark("ark:12345ab/x6np1wh8k").unwrap();
+
+        assert_eq!(classic.canonical(), "ark:12345ab/x6np1wh8k");
+        assert_eq!(classic.canonical(), normalize_ark_string(&classic.original));
+        assert!(classic.equivalent(&modern));
+        assert!(!classic.equivalent(&parse_ark("ark:12345ab/b3np1wh8k").unwrap()));
+
+        let unique: std::collections::HashSet<Ark> = [classic, modern].into_iter().collect();
+        assert_eq!(unique.len(), 1);
+    }
+
+    #[test]
+    fn test_string_and_serde_round_trip() {
+        let ark: Ark = "ark:/12345/x6-np1wh8k/page2?foo=bar".parse().unwrap();
+        assert_eq!(ark.to_string(), "ark:12345/x6np1wh8k/page2");
+        assert!("not-an-ark".parse::<Ark>().is_err());
+
+        let json = serde_json::to_string(&ark).unwrap();
+        assert_eq!(json, r#""ark:12345/x6np1wh8k/page2""#);
+        let back: Ark = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, ark);
+        assert_eq!(back.original, "ark:12345/x6np1wh8k/page2");
+
+        assert!(serde_json::from_str::<Ark>(r#""not-an-ark""#).is_err());
+    }
+
     #[test]
     fn test_query_string_removal() {
         // Per RFC 3.2: query strings must be removed during normalization FOR COMPARISON ONLY
@@ -242,6 +529,7 @@ mod tests {
 
         // But original also includes query string
         assert_eq!(with_query.original, "ark:12345/x6np1wh8k/page2?foo=bar");
+        assert_eq!(with_query.base().original, "ark:12345/x6np1wh8k");
     }
 
     #[test]
@@ -264,6 +552,25 @@ mod tests {
         assert_eq!(without_query.qualifier, "");
     }
 
+    #[test]
+    fn test_inflection() {
+        for (ark, inflection) in [
+            ("ark:12345/x6np1wh8k?", Some("?")),
+            ("ark:12345/x6np1wh8k??", Some("??")),
+            ("ark:12345/x6np1wh8k/page2?info", Some("?info")),
+            ("ark:12345/x6np1wh8k?page=2", None),
+            ("ark:12345/x6np1wh8k", None),
+        ] {
+            assert_eq!(parse_ark(ark).unwrap().inflection(), inflection, "{}", ark);
+        }
+
+        let stripped = parse_ark("ark:12345/x6np1wh8k/page2?info")
+            .unwrap()
+            .without_inflection();
+        assert_eq!(stripped.original, "ark:12345/x6np1wh8k/page2");
+        assert_eq!(stripped.qualifier, "page2");
+    }
+
     #[test]
     fn test_trailing_slash_removal() {
         // Per RFC 3.2: trailing slashes should be removed
@@ -298,6 +605,27 @@ mod tests {
         assert_eq!(ark2, clean2);
     }
 
+    #[test]
+    fn test_label_is_case_insensitive() {
+        for spelling in [
+            "ARK:12345/x6np1wh8k",
+            "Ark:/12345/x6np1wh8k",
+            "aRk:12345/x6np1wh8k",
+        ] {
+            let ark = parse_ark(spelling).unwrap();
+            assert_eq!(ark.original, "ark:12345/x6np1wh8k");
+            assert_eq!(ark.canonical(), "ark:12345/x6np1wh8k");
+        }
+        assert_eq!(
+            explain_normalization("ARK:12345/x6np1wh8k"),
+            (
+                "ark:12345/x6np1wh8k".to_string(),
+                vec![Normalization::LabelLowercased]
+            )
+        );
+        assert!(parse_ark("arc:12345/x6np1wh8k").is_none());
+    }
+
     #[test]
     fn test_naan_lowercase_normalization() {
         // Per RFC 3.2: NAAN should be normalized to lowercase FOR COMPARISON
@@ -368,6 +696,56 @@ mod tests {
         assert_eq!(wrapped.qualifier, "\npage2.pdf"); // Original preserves newline
     }
 
+    #[test]
+    fn test_ark_format_default() {
+        let format = ArkFormat::default();
+        assert_eq!(
+            format.format("12345", "x6", "np1wh8k", ""),
+            "ark:12345/x6np1wh8k"
+        );
+        assert_eq!(
+            format.format("ABCDE", "x6", "np1wh8k", "page2.pdf"),
+            "ark:abcde/x6np1wh8k/page2.pdf"
+        );
+        assert_eq!(
+            format.format("12345", "x6", "np1wh8k", "?info"),
+            "ark:12345/x6np1wh8k?info"
+        );
+    }
+
+    #[test]
+    fn test_ark_format_policy() {
+        let format = ArkFormat {
+            slash: true,
+            lowercase_naan: false,
+            hyphen_group: Some(4),
+        };
+        let emitted = format.format("ABCDE", "x6", "np1wh8kq", "");
+        assert_eq!(emitted, "ark:/ABCDE/x6np1w-h8kq");
+
+        // Emitted forms are equivalent to the plain form
+        assert_eq!(
+            parse_ark(&emitted).unwrap(),
+            parse_ark("ark:abcde/x6np1wh8kq").unwrap()
+        );
+
+        assert!(format.validate().is_ok());
+        let invalid = ArkFormat {
+            hyphen_group: Some(0),
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_ark_format_canonical() {
+        let ark = parse_ark("ark:/ABCDE/x6-np-1wh8k/page2.pdf/?foo=bar").unwrap();
+        assert_eq!(
+            ArkFormat::default().format_canonical(&ark),
+            "ark:abcde/x6np1wh8k/page2.pdf"
+        );
+    }
+
     #[test]
     fn test_rfc_example_equivalence() {
         // Per RFC 3.1, these ARKs should be equivalent FOR COMPARISON:
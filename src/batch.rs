@@ -0,0 +1,368 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::Mutex;
+
+use crate::ark::{Ark, parse_ark};
+use crate::bindings::Binding;
+use crate::config::AppState;
+use crate::error::AppError;
+use crate::events::ArkChange;
+use crate::lifecycle::{ArkStatus, Lifecycle};
+use crate::shoulder::Shoulder;
+use crate::validation;
+
+/// Held while a batch is checked and written, so batches on this instance never interleave
+static BATCH_LOCK: Mutex<()> = Mutex::new(());
+
+/// One step of an admin batch, tagged by `op`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    /// Binds an ARK that has no binding yet, `active` unless `status` is `reserved`
+    Bind {
+        ark: String,
+        target: String,
+        #[serde(default)]
+        metadata: BTreeMap<String, String>,
+        #[serde(default)]
+        status: ArkStatus,
+    },
+    /// Points a bound ARK at a new target, keeping its metadata and status
+    Rebind { ark: String, target: String },
+    /// Withdraws an ARK for good; shorthand for `set_status` to `tombstoned`
+    Tombstone {
+        ark: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Moves an ARK to another status, if its lifecycle allows; an unbound ARK counts as an
+    /// `active` one without target
+    SetStatus {
+        ark: String,
+        status: ArkStatus,
+        /// Required for `alias`
+        #[serde(default)]
+        alias_of: Option<String>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Sets metadata keys of a bound ARK; keys set to `null` are removed
+    SetMetadata {
+        ark: String,
+        metadata: BTreeMap<String, Option<String>>,
+    },
+}
+
+impl BatchOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatchOperation::Bind { .. } => "bind",
+            BatchOperation::Rebind { .. } => "rebind",
+            BatchOperation::Tombstone { .. } => "tombstone",
+            BatchOperation::SetStatus { .. } => "set_status",
+            BatchOperation::SetMetadata { .. } => "set_metadata",
+        }
+    }
+
+    fn ark(&self) -> &str {
+        match self {
+            BatchOperation::Bind { ark, .. }
+            | BatchOperation::Rebind { ark, .. }
+            | BatchOperation::Tombstone { ark, .. }
+            | BatchOperation::SetStatus { ark, .. }
+            | BatchOperation::SetMetadata { ark, .. } => ark,
+        }
+    }
+}
+
+/// How one operation of a batch fared
+#[derive(Debug, Serialize)]
+pub struct OperationResult {
+    /// Position in the request's `operations`
+    pub index: usize,
+    pub op: &'static str,
+    /// Normalized form of the ARK, once it was found valid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ark: Option<String>,
+    pub ok: bool,
+    /// Status of the ARK after the operation, if it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ArkStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A batch's per-operation results, and the changes it made if every operation succeeded
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub applied: bool,
+    pub results: Vec<OperationResult>,
+    /// Normalized ARK and change of each operation, in order; empty unless applied
+    pub changes: Vec<(String, ArkChange)>,
+}
+
+/// Runs `operations` in order against the bindings and writes the result at once, or nothing
+/// if any operation fails
+///
+/// Later operations see the effect of earlier ones, so an ARK can be bound and described in the
+/// same batch. Every operation is checked even after one fails, so the results name all the
+/// problems.
+pub fn run_batch(
+    state: &AppState,
+    operations: &[BatchOperation],
+) -> Result<BatchOutcome, AppError> {
+    let _guard = BATCH_LOCK.lock().expect("batch lock poisoned");
+    let now = Utc::now();
+    let mut working: HashMap<String, (Ark, Option<Binding>)> = HashMap::new();
+    let mut results = Vec::with_capacity(operations.len());
+    let mut changes = Vec::with_capacity(operations.len());
+    for (index, operation) in operations.iter().enumerate() {
+        let mut result = OperationResult {
+            index,
+            op: operation.name(),
+            ark: None,
+            ok: false,
+            status: None,
+            error: None,
+        };
+        match checked_ark(state, operation.ark()) {
+            Err(reason) => result.error = Some(reason),
+            Ok(ark) => {
+                let shoulder = state
+                    .shoulders
+                    .get(&ark.shoulder)
+                    .expect("valid ARKs are on a configured shoulder");
+                let canonical = ark.canonical().to_string();
+                result.ark = Some(canonical.clone());
+                let related = related(state, &working, shoulder, &ark, operation)?;
+                let (_, binding) = match working.entry(canonical.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let binding = state.bindings.get(&ark)?;
+                        entry.insert((ark, binding))
+                    }
+                };
+                match apply(
+                    state, shoulder, &canonical, binding, operation, &related, now,
+                ) {
+                    Ok(change) => {
+                        result.ok = true;
+                        result.status = binding.as_ref().map(|b| b.lifecycle.status);
+                        changes.push((canonical, change));
+                    }
+                    Err(reason) => result.error = Some(reason),
+                }
+            }
+        }
+        results.push(result);
+    }
+
+    let applied = results.iter().all(|result| result.ok);
+    if applied {
+        state.bindings.bind_all(
+            working
+                .into_values()
+                .filter_map(|(ark, binding)| binding.map(|binding| (ark, binding))),
+        )?;
+    } else {
+        changes.clear();
+    }
+    Ok(BatchOutcome {
+        applied,
+        results,
+        changes,
+    })
+}
+
+/// What an operation needs to know about ARKs other than its own, as the batch left them so far
+#[derive(Default)]
+struct Related {
+    /// Metadata of the base ARK, which a component bound in the batch starts with
+    inherited: BTreeMap<String, String>,
+    /// Status of the ARK an alias is to stand in for, none if it is not bound
+    alias_target: Option<ArkStatus>,
+}
+
+/// Looks up the ARKs `operation` on `ark` refers to besides `ark` itself
+fn related(
+    state: &AppState,
+    working: &HashMap<String, (Ark, Option<Binding>)>,
+    shoulder: &Shoulder,
+    ark: &Ark,
+    operation: &BatchOperation,
+) -> Result<Related, AppError> {
+    let mut related = Related::default();
+    match operation {
+        BatchOperation::Bind { .. } if shoulder.is_component(ark) => {
+            if let Some(base) = current_binding(state, working, &ark.base())? {
+                related.inherited = base.metadata;
+            }
+        }
+        BatchOperation::SetStatus {
+            alias_of: Some(alias_of),
+            ..
+        } => {
+            // An unparsable target is reported when the operation is applied
+            if let Some(target) = parse_ark(alias_of) {
+                related.alias_target = current_binding(state, working, &target)?
+                    .map(|binding| binding.lifecycle.status);
+            }
+        }
+        _ => {}
+    }
+    Ok(related)
+}
+
+/// The binding of `ark` as the batch left it so far
+fn current_binding(
+    state: &AppState,
+    working: &HashMap<String, (Ark, Option<Binding>)>,
+    ark: &Ark,
+) -> Result<Option<Binding>, AppError> {
+    match working.get(ark.canonical()) {
+        Some((_, binding)) => Ok(binding.clone()),
+        None => Ok(state.bindings.get(ark)?),
+    }
+}
+
+/// Parses an operation's ARK, which must be valid on a configured shoulder, without a qualifier
+/// unless it names a component
+fn checked_ark(state: &AppState, input: &str) -> Result<Ark, String> {
+    let result = validation::validate_ark(state, input, None);
+    if !result.valid {
+        return Err(result
+            .error
+            .or_else(|| result.warnings.and_then(|w| w.into_iter().next()))
+            .unwrap_or_else(|| "not a valid ARK on a configured shoulder".to_string()));
+    }
+    let ark = parse_ark(input).expect("valid ARKs parse");
+    if !ark.qualifier.is_empty()
+        && !state
+            .shoulders
+            .get(&ark.shoulder)
+            .is_some_and(|shoulder| shoulder.is_component(&ark))
+    {
+        return Err(
+            "ARKs with a qualifier have no binding of their own, unless it names a component"
+                .to_string(),
+        );
+    }
+    Ok(ark)
+}
+
+/// Applies one operation to the binding of the ARK `canonical`, leaving it untouched if the
+/// operation fails
+///
+/// A new binding starts with the inherited metadata, overridden by the metadata given. An alias
+/// must stand in for an `active` ARK, so aliases never chain or loop.
+fn apply(
+    state: &AppState,
+    shoulder: &Shoulder,
+    canonical: &str,
+    binding: &mut Option<Binding>,
+    operation: &BatchOperation,
+    related: &Related,
+    now: DateTime<Utc>,
+) -> Result<ArkChange, String> {
+    match operation {
+        BatchOperation::Bind {
+            target,
+            metadata,
+            status,
+            ..
+        } => {
+            if binding.is_some() {
+                return Err("already bound; use rebind to change the target".to_string());
+            }
+            let lifecycle = Lifecycle::starting(*status)?;
+            shoulder.validate_target(target)?;
+            let mut metadata_with_inherited = related.inherited.clone();
+            metadata_with_inherited.extend(metadata.clone());
+            *binding = Some(Binding {
+                target: target.clone(),
+                metadata: metadata_with_inherited,
+                bound_at: now,
+                lifecycle,
+            });
+            Ok(ArkChange::Updated)
+        }
+        BatchOperation::Rebind { target, .. } => {
+            let current = binding.as_mut().ok_or("not bound; use bind")?;
+            if current.lifecycle.status == ArkStatus::Tombstoned {
+                return Err("tombstoned ARKs cannot be rebound".to_string());
+            }
+            shoulder.validate_target(target)?;
+            current.target = target.clone();
+            current.bound_at = now;
+            Ok(ArkChange::Updated)
+        }
+        BatchOperation::Tombstone { reason, .. } => {
+            transition(binding, ArkStatus::Tombstoned, None, reason, now)
+        }
+        BatchOperation::SetStatus {
+            status,
+            alias_of,
+            reason,
+            ..
+        } => {
+            let alias_of = alias_of
+                .as_deref()
+                .map(|alias_of| {
+                    let alias_of = checked_ark(state, alias_of)
+                        .map_err(|reason| format!("alias_of: {}", reason))?;
+                    if alias_of.canonical() == canonical {
+                        return Err("an ARK cannot be an alias of itself".to_string());
+                    }
+                    match related.alias_target {
+                        Some(ArkStatus::Active) => Ok(alias_of.canonical().to_string()),
+                        Some(status) => Err(format!(
+                            "alias_of: {} is {}; an alias must stand in for an active ARK",
+                            alias_of.canonical(),
+                            status
+                        )),
+                        None => Err(format!("alias_of: {} is not bound", alias_of.canonical())),
+                    }
+                })
+                .transpose()?;
+            transition(binding, *status, alias_of, reason, now)
+        }
+        BatchOperation::SetMetadata { metadata, .. } => {
+            let current = binding.as_mut().ok_or("not bound; use bind")?;
+            for (key, value) in metadata {
+                match value {
+                    Some(value) => current.metadata.insert(key.clone(), value.clone()),
+                    None => current.metadata.remove(key),
+                };
+            }
+            Ok(ArkChange::Updated)
+        }
+    }
+}
+
+/// Moves an ARK to `status`, giving an unbound ARK an `active` binding without target first
+fn transition(
+    binding: &mut Option<Binding>,
+    status: ArkStatus,
+    alias_of: Option<String>,
+    reason: &Option<String>,
+    now: DateTime<Utc>,
+) -> Result<ArkChange, String> {
+    let mut lifecycle = binding
+        .as_ref()
+        .map(|binding| binding.lifecycle.clone())
+        .unwrap_or_default();
+    lifecycle.transition(status, alias_of, reason.clone(), now)?;
+    let current = binding.get_or_insert_with(|| Binding {
+        target: String::new(),
+        metadata: BTreeMap::new(),
+        bound_at: now,
+        lifecycle: Lifecycle::default(),
+    });
+    current.lifecycle = lifecycle;
+    Ok(match status {
+        ArkStatus::Withdrawn | ArkStatus::Tombstoned => ArkChange::Deleted,
+        _ => ArkChange::Updated,
+    })
+}
@@ -0,0 +1,386 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Bound;
+use std::sync::{Arc, RwLock};
+
+use crate::ark::{Ark, normalize_ark_string};
+use crate::lifecycle::{ArkStatus, Lifecycle, StatusChange};
+use crate::names::Naan;
+
+/// Where an ARK resolves to, recorded when it was minted or by an admin batch
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "StoredBinding")]
+pub struct Binding {
+    pub target: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    pub bound_at: DateTime<Utc>,
+    /// Status of the ARK; only `active` ARKs resolve to `target`
+    #[serde(default, skip_serializing_if = "Lifecycle::is_default")]
+    pub lifecycle: Lifecycle,
+}
+
+/// A binding as stored, possibly by a release that marked withdrawn ARKs with a `tombstone`
+/// instead of a lifecycle
+#[derive(Deserialize)]
+struct StoredBinding {
+    target: String,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    bound_at: DateTime<Utc>,
+    #[serde(default)]
+    lifecycle: Option<Lifecycle>,
+    #[serde(default)]
+    tombstone: Option<LegacyTombstone>,
+}
+
+impl From<StoredBinding> for Binding {
+    fn from(stored: StoredBinding) -> Self {
+        let lifecycle = match (stored.lifecycle, stored.tombstone) {
+            (Some(lifecycle), _) => lifecycle,
+            (None, Some(tombstone)) => tombstone.into_lifecycle(),
+            (None, None) => Lifecycle::default(),
+        };
+        Binding {
+            target: stored.target,
+            metadata: stored.metadata,
+            bound_at: stored.bound_at,
+            lifecycle,
+        }
+    }
+}
+
+/// When and why an ARK was withdrawn, as bindings recorded it before ARKs had a lifecycle
+#[derive(Deserialize)]
+pub(crate) struct LegacyTombstone {
+    #[serde(default)]
+    reason: Option<String>,
+    withdrawn_at: DateTime<Utc>,
+}
+
+impl LegacyTombstone {
+    /// The lifecycle of an active ARK tombstoned at `withdrawn_at`, which it stands for
+    pub(crate) fn into_lifecycle(self) -> Lifecycle {
+        Lifecycle {
+            status: ArkStatus::Tombstoned,
+            alias_of: None,
+            history: vec![StatusChange {
+                from: ArkStatus::Active,
+                to: ArkStatus::Tombstoned,
+                at: self.withdrawn_at,
+                reason: self.reason,
+            }],
+        }
+    }
+}
+
+/// A store (bindings, the mint ledger) could not be reached
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageError(pub String);
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "storage unavailable: {}", self.0)
+    }
+}
+
+/// Backend that keeps bindings, keyed by canonical ARK
+pub trait BindingStore: Send + Sync {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError>;
+    /// Records all bindings at once, so readers see either none or all of them
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError>;
+    /// Records the binding unless the ARK has one, which is returned instead
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError>;
+    fn count(&self) -> Result<usize, StorageError>;
+    /// Number of bindings of canonical ARKs starting with `prefix`, or `None` where the backend
+    /// can only tell by scanning every binding
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError>;
+    /// About `limit` bindings, from where `cursor` (the `next` of the previous page) left off
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError>;
+}
+
+/// A page of bindings by canonical ARK; pages may be empty before the last one
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BindingPage {
+    pub bindings: Vec<(String, Binding)>,
+    /// Cursor of the next page, none after the last one
+    pub next: Option<String>,
+}
+
+impl BindingPage {
+    /// A page of bindings in ARK order, continuing after its last ARK unless it came up short
+    pub(crate) fn by_ark(bindings: Vec<(String, Binding)>, limit: usize) -> Self {
+        let next = (bindings.len() >= limit)
+            .then(|| bindings.last().map(|(ark, _)| ark.clone()))
+            .flatten();
+        Self { bindings, next }
+    }
+}
+
+/// Bindings kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryBindingStore {
+    bindings: RwLock<BTreeMap<String, Binding>>,
+}
+
+impl BindingStore for MemoryBindingStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        Ok(self
+            .bindings
+            .read()
+            .expect("bindings lock poisoned")
+            .get(canonical)
+            .cloned())
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        self.bindings
+            .write()
+            .expect("bindings lock poisoned")
+            .extend(bindings);
+        Ok(())
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        let mut bindings = self.bindings.write().expect("bindings lock poisoned");
+        match bindings.get(canonical) {
+            Some(existing) => Ok(Some(existing.clone())),
+            None => {
+                bindings.insert(canonical.to_string(), binding.clone());
+                Ok(None)
+            }
+        }
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        Ok(self.bindings.read().expect("bindings lock poisoned").len())
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        Ok(Some(
+            self.bindings
+                .read()
+                .expect("bindings lock poisoned")
+                .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+                .take_while(|(ark, _)| ark.starts_with(prefix))
+                .count(),
+        ))
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        let start = cursor.map_or(Bound::Unbounded, |cursor| {
+            Bound::Excluded(cursor.to_string())
+        });
+        let bindings = self
+            .bindings
+            .read()
+            .expect("bindings lock poisoned")
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|(ark, binding)| (ark.clone(), binding.clone()))
+            .collect();
+        Ok(BindingPage::by_ark(bindings, limit))
+    }
+}
+
+/// Targets of individual ARKs, which take precedence over their shoulder's route pattern
+///
+/// Tracks whether the store is reachable, so resolution can fall back to route patterns while
+/// it is down instead of failing.
+pub struct Bindings {
+    store: Arc<dyn BindingStore>,
+    /// When the store started failing; cleared by the next successful call
+    unavailable_since: RwLock<Option<DateTime<Utc>>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryBindingStore::default()))
+    }
+}
+
+impl Bindings {
+    pub fn new(store: Arc<dyn BindingStore>) -> Self {
+        Self {
+            store,
+            unavailable_since: RwLock::new(None),
+        }
+    }
+
+    /// Records all bindings at once, so readers see either none or all of them
+    pub fn bind_all(
+        &self,
+        bindings: impl IntoIterator<Item = (Ark, Binding)>,
+    ) -> Result<(), StorageError> {
+        let bindings: Vec<(String, Binding)> = bindings
+            .into_iter()
+            .map(|(ark, binding)| (ark.canonical().to_string(), binding))
+            .collect();
+        self.track(self.store.put_all(bindings))
+    }
+
+    /// Records the binding unless the ARK has one, which is returned instead
+    pub fn bind_new(&self, ark: &Ark, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        self.track(self.store.put_new(ark.canonical(), binding))
+    }
+
+    /// The binding of an ARK, in any spelling; ARKs with a qualifier have none of their own
+    pub fn get(&self, ark: &Ark) -> Result<Option<Binding>, StorageError> {
+        self.track(self.store.get(ark.canonical()))
+    }
+
+    pub fn len(&self) -> Result<usize, StorageError> {
+        self.track(self.store.count())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, StorageError> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Number of ARKs on `shoulder` with a binding, or `None` where the store can only tell by
+    /// scanning every binding
+    pub fn count_on(&self, naan: &Naan, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        let prefix = normalize_ark_string(&format!("ark:{}/{}", naan, shoulder));
+        self.track(self.store.count_prefixed(&prefix))
+    }
+
+    /// When the store started failing, if its last call failed
+    pub fn unavailable_since(&self) -> Option<DateTime<Utc>> {
+        *self
+            .unavailable_since
+            .read()
+            .expect("bindings health lock poisoned")
+    }
+
+    /// Notes whether the store answered, logging when it goes down or comes back
+    fn track<T>(&self, result: Result<T, StorageError>) -> Result<T, StorageError> {
+        let mut since = self
+            .unavailable_since
+            .write()
+            .expect("bindings health lock poisoned");
+        match (&result, *since) {
+            (Err(e), None) => {
+                tracing::error!(error = %e, "Binding storage unavailable");
+                *since = Some(Utc::now());
+            }
+            (Ok(_), Some(started)) => {
+                tracing::info!(%started, "Binding storage available again");
+                *since = None;
+            }
+            _ => {}
+        }
+        result
+    }
+}
+
+/// Bound ARKs per shoulder, as last counted in the background, so that requests never wait on
+/// a count of the bindings
+#[derive(Debug, Default)]
+pub struct BindingCounts {
+    counts: RwLock<HashMap<String, usize>>,
+}
+
+impl BindingCounts {
+    /// The last count for `shoulder`; none before the first, or where the store cannot count
+    pub fn get(&self, shoulder: &str) -> Option<usize> {
+        self.counts
+            .read()
+            .expect("binding counts lock poisoned")
+            .get(shoulder)
+            .copied()
+    }
+
+    /// Counts the bound ARKs of each shoulder again; a shoulder whose count fails keeps its
+    /// last one
+    pub fn refresh<'a>(
+        &self,
+        bindings: &Bindings,
+        naan: &Naan,
+        shoulders: impl IntoIterator<Item = &'a str>,
+    ) {
+        for shoulder in shoulders {
+            match bindings.count_on(naan, shoulder) {
+                Ok(Some(count)) => {
+                    self.counts
+                        .write()
+                        .expect("binding counts lock poisoned")
+                        .insert(shoulder.to_string(), count);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    tracing::warn!(shoulder = %shoulder, error = %error, "Could not count bound ARKs");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+    use crate::testing::SwitchableBindingStore;
+
+    #[test]
+    fn test_bindings_follow_ark_equivalence() {
+        let bindings = Bindings::default();
+        let binding = Binding {
+            target: "https://example.org/items/1".to_string(),
+            metadata: BTreeMap::from([("title".to_string(), "Item 1".to_string())]),
+            bound_at: Utc::now(),
+            lifecycle: Lifecycle::default(),
+        };
+        bindings
+            .bind_all([(parse_ark("ark:12345/x6np1wh8kq").unwrap(), binding.clone())])
+            .unwrap();
+
+        assert_eq!(bindings.len(), Ok(1));
+        assert_eq!(
+            bindings.get(&parse_ark("ark:/12345/x6-np1wh8kq").unwrap()),
+            Ok(Some(binding))
+        );
+        assert_eq!(
+            bindings.get(&parse_ark("ark:12345/x6other").unwrap()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_reads_tombstones_as_tombstoned_lifecycles() {
+        // As the Redis store and journals kept withdrawn ARKs before they had a lifecycle
+        let binding: Binding = serde_json::from_str(
+            r#"{"target": "https://example.org/items/1", "bound_at": "2025-01-15T00:00:00Z",
+                "tombstone": {"reason": "Duplicate", "withdrawn_at": "2025-02-01T00:00:00Z"}}"#,
+        )
+        .unwrap();
+        assert_eq!(binding.lifecycle.status, ArkStatus::Tombstoned);
+        assert_eq!(
+            binding.lifecycle.history[0].reason.as_deref(),
+            Some("Duplicate")
+        );
+
+        let json = serde_json::to_value(&binding).unwrap();
+        assert!(json.get("tombstone").is_none());
+        assert_eq!(serde_json::from_value::<Binding>(json).unwrap(), binding);
+    }
+
+    #[test]
+    fn test_tracks_storage_availability() {
+        let store = Arc::new(SwitchableBindingStore::default());
+        let bindings = Bindings::new(store.clone());
+        let ark = parse_ark("ark:12345/x6np1wh8kq").unwrap();
+
+        store.set_down(true);
+        assert!(bindings.get(&ark).is_err());
+        let since = bindings.unavailable_since().unwrap();
+        // Further failures keep the original outage start
+        assert!(bindings.get(&ark).is_err());
+        assert_eq!(bindings.unavailable_since(), Some(since));
+
+        store.set_down(false);
+        assert_eq!(bindings.get(&ark), Ok(None));
+        assert_eq!(bindings.unavailable_since(), None);
+    }
+}
@@ -0,0 +1,279 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::config::BETANUMERIC;
+use crate::config_document::{ConfigStore, Provenance};
+use crate::minting::ShoulderMintStats;
+use crate::notify::env_parse;
+
+/// When the advisor recommends a longer blade, and whether it switches to it by itself
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdvisorPolicy {
+    /// Identifiers a shoulder is expected to mint from now on (default: 100,000)
+    pub horizon: u64,
+    /// Highest acceptable chance of a collision over the horizon (default: 0.01)
+    pub threshold: f64,
+    /// Mint with the recommended blade length as soon as there is one (default: false)
+    pub auto_escalate: bool,
+}
+
+impl Default for AdvisorPolicy {
+    fn default() -> Self {
+        Self {
+            horizon: 100_000,
+            threshold: 0.01,
+            auto_escalate: false,
+        }
+    }
+}
+
+/// How crowded a shoulder's namespace is, and the blade length it should move to
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BladeLengthAdvice {
+    pub shoulder: String,
+    /// Blade length currently used for minting, excluding the check character
+    pub blade_length: usize,
+    pub namespace_size: f64,
+    /// Identifiers minted since the service started
+    pub minted: u64,
+    pub collision_rate: f64,
+    /// Chance that minting the next `horizon` identifiers draws at least one already issued
+    pub projected_collision_probability: f64,
+    /// Shortest longer blade that keeps the projection under the threshold, if one is needed
+    pub recommended_blade_length: Option<usize>,
+}
+
+/// Audit entry for a blade length the advisor switched to by itself
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Escalation {
+    pub shoulder: String,
+    pub from: usize,
+    pub to: usize,
+    pub projected_collision_probability: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// Recommends longer blades before shoulders get crowded, optionally applying them
+#[derive(Debug, Default)]
+pub struct BladeLengthAdvisor {
+    policy: AdvisorPolicy,
+    /// Blade lengths switched to at runtime, by shoulder
+    overrides: RwLock<HashMap<String, usize>>,
+    escalations: RwLock<Vec<Escalation>>,
+}
+
+/// Number of distinct blades of the given length
+pub fn namespace_size(blade_length: usize) -> f64 {
+    (BETANUMERIC.len() as f64).powi(blade_length as i32)
+}
+
+/// Birthday-bound chance that minting `upcoming` random blades, with `issued` already taken,
+/// draws at least one blade twice
+pub fn collision_probability(issued: u64, upcoming: u64, blade_length: usize) -> f64 {
+    collision_probability_in(issued, upcoming, namespace_size(blade_length))
+}
+
+/// [`collision_probability`] in a namespace of `namespace_size` blades
+pub fn collision_probability_in(issued: u64, upcoming: u64, namespace_size: f64) -> f64 {
+    let (issued, upcoming) = (issued as f64, upcoming as f64);
+    let pairs = upcoming * issued + upcoming * (upcoming - 1.0).max(0.0) / 2.0;
+    -(-pairs / namespace_size).exp_m1()
+}
+
+impl BladeLengthAdvisor {
+    pub fn new(policy: AdvisorPolicy) -> Self {
+        Self {
+            policy,
+            ..Default::default()
+        }
+    }
+
+    pub fn policy(&self) -> &AdvisorPolicy {
+        &self.policy
+    }
+
+    /// Blade length to mint with: the escalated one if the advisor switched, else `configured`
+    pub fn blade_length(&self, shoulder: &str, configured: usize) -> usize {
+        self.overrides
+            .read()
+            .expect("blade advisor lock poisoned")
+            .get(shoulder)
+            .copied()
+            .unwrap_or(configured)
+            .max(configured)
+    }
+
+    /// Advice for a shoulder minting with `blade_length` characters of an alphabet of
+    /// `alphabet_size`, given its mint counters
+    ///
+    /// A longer blade is recommended when the projection exceeds the threshold, or when the
+    /// observed collision rate already does.
+    pub fn advise(
+        &self,
+        blade_length: usize,
+        alphabet_size: usize,
+        stats: &ShoulderMintStats,
+    ) -> BladeLengthAdvice {
+        let horizon = self.policy.horizon;
+        let namespace_size = |length: usize| (alphabet_size as f64).powi(length as i32);
+        let probability =
+            |length| collision_probability_in(stats.issued(), horizon, namespace_size(length));
+        let projected = probability(blade_length);
+
+        let recommended_blade_length = (projected > self.policy.threshold
+            || stats.collision_rate > self.policy.threshold)
+            .then(|| {
+                (blade_length + 1..)
+                    .find(|&length| probability(length) <= self.policy.threshold)
+                    .expect("collision probability falls as the blade grows")
+            });
+
+        BladeLengthAdvice {
+            shoulder: stats.shoulder.clone(),
+            blade_length,
+            namespace_size: namespace_size(blade_length),
+            minted: stats.minted,
+            collision_rate: stats.collision_rate,
+            projected_collision_probability: projected,
+            recommended_blade_length,
+        }
+    }
+
+    /// Applies the advice if auto-escalation is enabled, recording an audit entry and the
+    /// shoulder's provenance. Returns the escalation, if any.
+    pub fn apply(&self, advice: &BladeLengthAdvice, store: &ConfigStore) -> Option<Escalation> {
+        if !self.policy.auto_escalate {
+            return None;
+        }
+        let to = advice.recommended_blade_length?;
+
+        let escalation = Escalation {
+            shoulder: advice.shoulder.clone(),
+            from: advice.blade_length,
+            to,
+            projected_collision_probability: advice.projected_collision_probability,
+            at: Utc::now(),
+        };
+        tracing::warn!(
+            shoulder = %escalation.shoulder,
+            from = escalation.from,
+            to = escalation.to,
+            projected_collision_probability = escalation.projected_collision_probability,
+            "Blade length escalated automatically"
+        );
+
+        self.overrides
+            .write()
+            .expect("blade advisor lock poisoned")
+            .insert(escalation.shoulder.clone(), to);
+        self.escalations
+            .write()
+            .expect("blade advisor lock poisoned")
+            .push(escalation.clone());
+        store.record(
+            &escalation.shoulder,
+            Provenance {
+                changed_by: "blade-length-advisor".to_string(),
+                changed_at: escalation.at,
+                source: "auto-escalation".to_string(),
+            },
+        );
+        Some(escalation)
+    }
+
+    /// Every automatic escalation since the service started, oldest first
+    pub fn escalations(&self) -> Vec<Escalation> {
+        self.escalations
+            .read()
+            .expect("blade advisor lock poisoned")
+            .clone()
+    }
+}
+
+/// Build the blade length advisor from environment variables
+///
+/// Optional: `BLADE_ADVISOR_HORIZON` (default 100000), `BLADE_ADVISOR_THRESHOLD` (default 0.01),
+/// `BLADE_AUTO_ESCALATE` (default false).
+pub fn load_blade_advisor_from_env() -> Result<BladeLengthAdvisor, String> {
+    let defaults = AdvisorPolicy::default();
+    let policy = AdvisorPolicy {
+        horizon: env_parse("BLADE_ADVISOR_HORIZON")?.unwrap_or(defaults.horizon),
+        threshold: env_parse("BLADE_ADVISOR_THRESHOLD")?.unwrap_or(defaults.threshold),
+        auto_escalate: env_parse("BLADE_AUTO_ESCALATE")?.unwrap_or(defaults.auto_escalate),
+    };
+    if !(policy.threshold > 0.0 && policy.threshold < 1.0) {
+        return Err("BLADE_ADVISOR_THRESHOLD must be between 0 and 1".to_string());
+    }
+    Ok(BladeLengthAdvisor::new(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(minted: u64, collision_rate: f64) -> ShoulderMintStats {
+        ShoulderMintStats {
+            shoulder: "x6".to_string(),
+            minted,
+            collision_rate,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collision_probability() {
+        assert_eq!(collision_probability(0, 1, 8), 0.0);
+        // The README's rule of thumb: 100,000 blades of length 8 stay under 1%
+        let p = collision_probability(0, 100_000, 8);
+        assert!(p > 0.009 && p < 0.01, "{p}");
+        // Identifiers already issued crowd the namespace
+        assert!(collision_probability(1_000_000, 100_000, 8) > 0.1);
+        assert!(collision_probability(0, 100_000, 12) < 1e-7);
+    }
+
+    #[test]
+    fn test_advise() {
+        let advisor = BladeLengthAdvisor::default();
+
+        let quiet = advisor.advise(8, BETANUMERIC.len(), &stats(10, 0.0));
+        assert_eq!(quiet.recommended_blade_length, None);
+
+        let crowded = advisor.advise(6, BETANUMERIC.len(), &stats(50_000, 0.0));
+        assert!(crowded.projected_collision_probability > 0.99);
+        assert_eq!(crowded.recommended_blade_length, Some(9));
+
+        // Observed collisions alone are enough to recommend a longer blade
+        let colliding = advisor.advise(8, BETANUMERIC.len(), &stats(10, 0.05));
+        assert_eq!(colliding.recommended_blade_length, Some(9));
+
+        // Smaller alphabets need longer blades
+        let digits = advisor.advise(8, 10, &stats(10, 0.0));
+        assert_eq!(digits.namespace_size, 1e8);
+        assert_eq!(digits.recommended_blade_length, Some(12));
+    }
+
+    #[test]
+    fn test_apply_escalates_only_when_enabled() {
+        let store = ConfigStore::default();
+        let advice =
+            BladeLengthAdvisor::default().advise(6, BETANUMERIC.len(), &stats(50_000, 0.0));
+
+        let advisor = BladeLengthAdvisor::default();
+        assert_eq!(advisor.apply(&advice, &store), None);
+        assert_eq!(advisor.blade_length("x6", 6), 6);
+
+        let advisor = BladeLengthAdvisor::new(AdvisorPolicy {
+            auto_escalate: true,
+            ..Default::default()
+        });
+        let escalation = advisor.apply(&advice, &store).unwrap();
+        assert_eq!((escalation.from, escalation.to), (6, 9));
+        assert_eq!(advisor.blade_length("x6", 6), 9);
+        // A configured length longer than the escalation still wins
+        assert_eq!(advisor.blade_length("x6", 10), 10);
+        assert_eq!(advisor.escalations(), vec![escalation]);
+        assert_eq!(store.provenance("x6").unwrap().source, "auto-escalation");
+    }
+}
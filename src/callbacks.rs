@@ -0,0 +1,527 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::bindings::StorageError;
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::http_client::{HttpClient, HttpClients};
+use crate::notify::env_parse;
+
+/// Delivers a JSON payload to a callback URL
+pub trait CallbackTransport: Send + Sync {
+    /// Posts `body`, sending any `idempotency_key` as the `Idempotency-Key` header
+    fn post(
+        &self,
+        url: &str,
+        idempotency_key: Option<&str>,
+        body: String,
+    ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>>;
+}
+
+/// Posts callbacks over HTTP(S)
+pub struct HttpTransport {
+    client: HttpClient,
+}
+
+impl HttpTransport {
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+}
+
+impl CallbackTransport for HttpTransport {
+    fn post(
+        &self,
+        url: &str,
+        idempotency_key: Option<&str>,
+        body: String,
+    ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(key) = idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+        let request = request.body(body);
+        Box::pin(async move {
+            let response = self.client.send(request).await?;
+            response
+                .error_for_status()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Why a resolution failed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The constructed target URL failed security validation
+    BlockedRedirect,
+}
+
+/// A failed resolution reported to the shoulder's callback
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResolutionFailure {
+    pub ark: String,
+    pub kind: FailureKind,
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Body posted to a callback URL
+#[derive(Debug, Serialize)]
+struct CallbackPayload<'a> {
+    shoulder: &'a str,
+    failures: &'a [ResolutionFailure],
+    /// Failures left out because the batch was full
+    dropped: usize,
+}
+
+/// Limits how much is posted to callback URLs, and how long failed posts are retried
+#[derive(Clone, Debug)]
+pub struct BatchPolicy {
+    /// How often pending failures are posted; at most one new batch per URL per interval
+    pub flush_interval: Duration,
+    /// Maximum number of failures in one request; further failures are only counted
+    pub max_batch: usize,
+    /// Attempts at a delivery before it is given up on
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt, doubled after each further one
+    pub initial_backoff: Duration,
+    /// Longest wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for BatchPolicy {
+    fn default() -> Self {
+        Self {
+            flush_interval: Duration::from_secs(60),
+            max_batch: 100,
+            max_attempts: 8,
+            initial_backoff: Duration::from_secs(60),
+            max_backoff: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl BatchPolicy {
+    /// Wait before the next attempt at a delivery that has failed `attempts` times
+    fn backoff(&self, attempts: u32) -> Duration {
+        let doublings = attempts.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff)
+    }
+}
+
+/// How long delivered deliveries are kept for inspection
+const DELIVERY_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+#[derive(Debug, Default)]
+struct Batch {
+    shoulder: String,
+    failures: Vec<ResolutionFailure>,
+    dropped: usize,
+}
+
+/// Collects resolution failures per callback URL and posts them in batches
+///
+/// Each batch becomes a [`Delivery`] in the store before it is first posted, and failed posts
+/// are retried with exponential backoff, so restarts neither drop nor re-send a delivered batch.
+#[derive(Default)]
+pub struct FailureCallbacks {
+    transport: Option<Arc<dyn CallbackTransport>>,
+    policy: BatchPolicy,
+    pending: Mutex<HashMap<String, Batch>>,
+    /// Ids of the deliveries being attempted, so a manual retry and a flush never post the same
+    /// delivery at once
+    in_flight: Mutex<HashSet<String>>,
+}
+
+impl FailureCallbacks {
+    pub fn new(transport: Arc<dyn CallbackTransport>, policy: BatchPolicy) -> Self {
+        Self {
+            transport: Some(transport),
+            policy,
+            pending: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn flush_interval(&self) -> Duration {
+        self.policy.flush_interval
+    }
+
+    /// Queues a failure for `url`, the callback configured on `shoulder`
+    pub fn report(&self, shoulder: &str, url: &str, failure: ResolutionFailure) {
+        if self.transport.is_none() {
+            return;
+        }
+
+        let mut pending = self.pending.lock().expect("callbacks lock poisoned");
+        let batch = pending.entry(url.to_string()).or_insert_with(|| Batch {
+            shoulder: shoulder.to_string(),
+            ..Default::default()
+        });
+        if batch.failures.len() < self.policy.max_batch {
+            batch.failures.push(failure);
+        } else {
+            batch.dropped += 1;
+        }
+    }
+
+    /// Stores every pending batch as a delivery and attempts the deliveries that are due,
+    /// returning how many were delivered
+    pub async fn flush(&self, store: &dyn DeliveryStore) -> usize {
+        if self.transport.is_none() {
+            return 0;
+        }
+        let batches = std::mem::take(&mut *self.pending.lock().expect("callbacks lock poisoned"));
+        for (url, batch) in batches {
+            let payload = serde_json::to_value(CallbackPayload {
+                shoulder: &batch.shoulder,
+                failures: &batch.failures,
+                dropped: batch.dropped,
+            })
+            .expect("callback payload serializes");
+            let delivery = Delivery::new(&url, &batch.shoulder, payload);
+            if let Err(e) = store.save_delivery(&delivery) {
+                tracing::warn!(
+                    shoulder = %batch.shoulder,
+                    error = %e,
+                    "Failed to store resolution failure callback, keeping it for the next flush"
+                );
+                self.requeue(url, batch);
+            }
+        }
+
+        let now = Utc::now();
+        let due = match store.deliveries(Some(DeliveryStatus::Pending)) {
+            Ok(pending) => pending
+                .into_iter()
+                .filter(|delivery| delivery.next_attempt_at <= now),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to read pending callback deliveries");
+                return 0;
+            }
+        };
+        let mut delivered = 0;
+        for delivery in due {
+            match self.attempt(store, delivery).await {
+                Ok(Some(delivery)) if delivery.status == DeliveryStatus::Delivered => {
+                    delivered += 1
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Failed to record callback delivery"),
+            }
+        }
+
+        let retention = chrono::Duration::from_std(DELIVERY_RETENTION).expect("retention fits");
+        if let Err(e) = store.prune_deliveries(now - retention) {
+            tracing::warn!(error = %e, "Failed to prune callback deliveries");
+        }
+        delivered
+    }
+
+    /// Attempts `delivery` now, whatever its schedule, and returns it updated
+    ///
+    /// `None` when callbacks are disabled, or the delivery is being attempted already or was
+    /// delivered meanwhile.
+    pub async fn retry(
+        &self,
+        store: &dyn DeliveryStore,
+        delivery: Delivery,
+    ) -> Result<Option<Delivery>, StorageError> {
+        self.attempt(store, delivery).await
+    }
+
+    async fn attempt(
+        &self,
+        store: &dyn DeliveryStore,
+        delivery: Delivery,
+    ) -> Result<Option<Delivery>, StorageError> {
+        let Some(transport) = &self.transport else {
+            return Ok(None);
+        };
+        let id = delivery.id.clone();
+        if !self
+            .in_flight
+            .lock()
+            .expect("callbacks lock poisoned")
+            .insert(id.clone())
+        {
+            return Ok(None);
+        }
+        let attempted = self.post(transport.as_ref(), store, delivery).await;
+        self.in_flight
+            .lock()
+            .expect("callbacks lock poisoned")
+            .remove(&id);
+        attempted
+    }
+
+    async fn post(
+        &self,
+        transport: &dyn CallbackTransport,
+        store: &dyn DeliveryStore,
+        delivery: Delivery,
+    ) -> Result<Option<Delivery>, StorageError> {
+        // Another attempt may have finished since the delivery was read
+        let mut delivery = store.delivery(&delivery.id)?.unwrap_or(delivery);
+        if delivery.status == DeliveryStatus::Delivered {
+            return Ok(None);
+        }
+
+        let body = delivery.payload.to_string();
+        let result = transport
+            .post(&delivery.url, Some(&delivery.id), body)
+            .await;
+        let now = Utc::now();
+        delivery.attempts += 1;
+        match result {
+            Ok(()) => {
+                tracing::info!(
+                    shoulder = %delivery.shoulder,
+                    delivery = %delivery.id,
+                    attempts = delivery.attempts,
+                    "Resolution failure callback delivered"
+                );
+                delivery.status = DeliveryStatus::Delivered;
+                delivery.delivered_at = Some(now);
+                delivery.last_error = None;
+            }
+            Err(e) => {
+                let backoff = self.policy.backoff(delivery.attempts);
+                let gave_up = delivery.attempts >= self.policy.max_attempts;
+                tracing::warn!(
+                    shoulder = %delivery.shoulder,
+                    delivery = %delivery.id,
+                    attempts = delivery.attempts,
+                    error = %e,
+                    gave_up = gave_up,
+                    "Resolution failure callback failed"
+                );
+                delivery.status = if gave_up {
+                    DeliveryStatus::Failed
+                } else {
+                    DeliveryStatus::Pending
+                };
+                delivery.next_attempt_at =
+                    now + chrono::Duration::from_std(backoff).unwrap_or(chrono::Duration::MAX);
+                delivery.last_error = Some(e);
+            }
+        }
+
+        store.save_delivery(&delivery)?;
+        Ok(Some(delivery))
+    }
+
+    /// Puts back a batch that could not be stored, merging it with any newer one for `url`
+    fn requeue(&self, url: String, batch: Batch) {
+        let mut pending = self.pending.lock().expect("callbacks lock poisoned");
+        let newer = pending.entry(url).or_insert_with(|| Batch {
+            shoulder: batch.shoulder.clone(),
+            ..Default::default()
+        });
+        let room = self.policy.max_batch.saturating_sub(batch.failures.len());
+        let kept = newer.failures.len().min(room);
+        let dropped = batch.dropped + newer.dropped + newer.failures.len() - kept;
+        let mut failures = batch.failures;
+        failures.extend(newer.failures.drain(..kept));
+        newer.failures = failures;
+        newer.dropped = dropped;
+    }
+}
+
+/// Build the failure callback dispatcher from environment variables
+///
+/// Optional: `CALLBACK_FLUSH_INTERVAL_SECS` (default 60), `CALLBACK_MAX_BATCH` (default 100),
+/// `CALLBACK_TIMEOUT_SECS` (default 10), `CALLBACK_MAX_ATTEMPTS` (default 8),
+/// `CALLBACK_RETRY_INITIAL_SECS` (default 60), `CALLBACK_RETRY_MAX_SECS` (default 3600).
+pub fn load_failure_callbacks_from_env(http: &HttpClients) -> Result<FailureCallbacks, String> {
+    let defaults = BatchPolicy::default();
+    let policy = BatchPolicy {
+        flush_interval: env_parse("CALLBACK_FLUSH_INTERVAL_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.flush_interval),
+        max_batch: env_parse("CALLBACK_MAX_BATCH")?.unwrap_or(defaults.max_batch),
+        max_attempts: env_parse("CALLBACK_MAX_ATTEMPTS")?.unwrap_or(defaults.max_attempts),
+        initial_backoff: env_parse("CALLBACK_RETRY_INITIAL_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.initial_backoff),
+        max_backoff: env_parse("CALLBACK_RETRY_MAX_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.max_backoff),
+    };
+    if policy.flush_interval.is_zero() {
+        return Err("CALLBACK_FLUSH_INTERVAL_SECS must be greater than 0".to_string());
+    }
+    if policy.max_attempts == 0 {
+        return Err("CALLBACK_MAX_ATTEMPTS must be greater than 0".to_string());
+    }
+    let timeout = Duration::from_secs(env_parse("CALLBACK_TIMEOUT_SECS")?.unwrap_or(10));
+
+    Ok(FailureCallbacks::new(
+        Arc::new(HttpTransport::new(http.client(timeout))),
+        policy,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deliveries::MemoryDeliveryStore;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        /// URL, idempotency key and body of every post
+        sent: Mutex<Vec<(String, String, serde_json::Value)>>,
+        down: AtomicBool,
+    }
+
+    impl CallbackTransport for RecordingTransport {
+        fn post(
+            &self,
+            url: &str,
+            idempotency_key: Option<&str>,
+            body: String,
+        ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+            let body = serde_json::from_str(&body).unwrap();
+            let key = idempotency_key.unwrap().to_string();
+            self.sent.lock().unwrap().push((url.to_string(), key, body));
+            let result = if self.down.load(Ordering::Relaxed) {
+                Err("503 Service Unavailable".to_string())
+            } else {
+                Ok(())
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn failure(ark: &str) -> ResolutionFailure {
+        ResolutionFailure {
+            ark: ark.to_string(),
+            kind: FailureKind::BlockedRedirect,
+            reason: "Redirect URL has invalid scheme".to_string(),
+            at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_failures_per_url() {
+        let transport = Arc::new(RecordingTransport::default());
+        let store = MemoryDeliveryStore::default();
+        let policy = BatchPolicy {
+            max_batch: 2,
+            ..Default::default()
+        };
+        let callbacks = FailureCallbacks::new(transport.clone(), policy);
+
+        for ark in ["ark:12345/x6a", "ark:12345/x6b", "ark:12345/x6c"] {
+            callbacks.report("x6", "https://hooks.example.org/x6", failure(ark));
+        }
+        assert_eq!(callbacks.flush(&store).await, 1);
+
+        let (url, key, body) = {
+            let sent = transport.sent.lock().unwrap();
+            assert_eq!(sent.len(), 1);
+            sent[0].clone()
+        };
+        assert_eq!(url, "https://hooks.example.org/x6");
+        assert_eq!(body["shoulder"], "x6");
+        assert_eq!(body["failures"].as_array().unwrap().len(), 2);
+        assert_eq!(body["failures"][0]["kind"], "blocked_redirect");
+        assert_eq!(body["dropped"], 1);
+        let delivery = store.delivery(&key).unwrap().unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+
+        // Nothing is left pending after a flush
+        assert_eq!(callbacks.flush(&store).await, 0);
+        assert_eq!(transport.sent.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_failed_deliveries_with_backoff() {
+        let transport = Arc::new(RecordingTransport::default());
+        transport.down.store(true, Ordering::Relaxed);
+        let store = MemoryDeliveryStore::default();
+        let policy = BatchPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            ..Default::default()
+        };
+        let callbacks = FailureCallbacks::new(transport.clone(), policy);
+
+        callbacks.report(
+            "x6",
+            "https://hooks.example.org/x6",
+            failure("ark:12345/x6a"),
+        );
+        assert_eq!(callbacks.flush(&store).await, 0);
+        let pending = store.deliveries(Some(DeliveryStatus::Pending)).unwrap();
+        assert_eq!(pending[0].attempts, 1);
+        assert_eq!(
+            pending[0].last_error.as_deref(),
+            Some("503 Service Unavailable")
+        );
+
+        // A restarted service picks the delivery up from the store, with the same key
+        let restarted = FailureCallbacks::new(transport.clone(), callbacks.policy.clone());
+        assert_eq!(restarted.flush(&store).await, 0);
+        let failed = store.deliveries(Some(DeliveryStatus::Failed)).unwrap();
+        assert_eq!(failed[0].attempts, 2);
+        assert_eq!(restarted.flush(&store).await, 0);
+        {
+            let sent = transport.sent.lock().unwrap();
+            assert_eq!(sent.len(), 2);
+            assert_eq!(sent[0].1, sent[1].1);
+        }
+
+        transport.down.store(false, Ordering::Relaxed);
+        let retried = restarted
+            .retry(&store, failed[0].clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retried.status, DeliveryStatus::Delivered);
+        assert_eq!(retried.attempts, 3);
+        // A stale copy of a delivered delivery is not sent again
+        assert!(
+            restarted
+                .retry(&store, failed[0].clone())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_maximum() {
+        let policy = BatchPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_secs(60));
+        assert_eq!(policy.backoff(3), Duration::from_secs(240));
+        assert_eq!(policy.backoff(100), Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_transport() {
+        let store = MemoryDeliveryStore::default();
+        let callbacks = FailureCallbacks::default();
+        callbacks.report(
+            "x6",
+            "https://hooks.example.org/x6",
+            failure("ark:12345/x6a"),
+        );
+        assert_eq!(callbacks.flush(&store).await, 0);
+        assert!(callbacks.pending.lock().unwrap().is_empty());
+        assert!(store.deliveries(None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,54 @@
+/// A deliberate difference between this service's resolution and N2T.net's
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Divergence {
+    /// Stable identifier, referenced by the N2T contract tests
+    pub id: &'static str,
+    /// What N2T.net does
+    pub n2t: &'static str,
+    /// What this service does instead
+    pub service: &'static str,
+    /// Why the difference is intended
+    pub rationale: &'static str,
+}
+
+/// Every known way resolution here differs from N2T.net
+///
+/// Anything not listed is expected to produce the same target as N2T; the contract tests in
+/// `tests/n2t_contract.rs` check both the equivalences and each divergence.
+pub const DIVERGENCES: &[Divergence] = &[
+    Divergence {
+        id: "inflections",
+        n2t: "Answers `?`, `??` and `?info` itself with a metadata record",
+        service: "Redirects to the shoulder's `metadata_route_pattern`, or forwards the \
+                  inflection to the target like any other query string when there is none. \
+                  Shoulders with `forward_inflections` relay the upstream answer instead, \
+                  falling back to a local ERC record",
+        rationale: "The service stores no metadata, so the project's own services are the only \
+                    ones that can describe the object",
+    },
+    Divergence {
+        id: "hyphens",
+        n2t: "Removes hyphens from the identifier before substituting it into the target",
+        service: "Substitutes the identifier as requested, hyphens included",
+        rationale: "Hyphens are only ignored when comparing ARKs; targets that rely on them \
+                    must receive them",
+    },
+    Divergence {
+        id: "unknown-shoulder",
+        n2t: "Falls back to the rule registered for the whole NAAN",
+        service: "Answers 404, since every rule belongs to a shoulder",
+        rationale: "A catch-all would hide mistyped or retired shoulders behind redirects to \
+                    the wrong place",
+    },
+    Divergence {
+        id: "other-naans",
+        n2t: "Resolves ARKs of every registered NAAN",
+        service: "Answers 400 for any NAAN other than its own",
+        rationale: "The service is the resolver for a single NAAN, not a global one",
+    },
+];
+
+/// The divergence with the given id
+pub fn divergence(id: &str) -> Option<&'static Divergence> {
+    DIVERGENCES.iter().find(|divergence| divergence.id == id)
+}
@@ -1,21 +1,315 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::admin_token::TokenSigner;
+use crate::api_keys::ApiKeys;
+use crate::ark::ArkFormat;
+use crate::bindings::{BindingCounts, Bindings};
+use crate::blade_advisor::BladeLengthAdvisor;
+use crate::callbacks::FailureCallbacks;
+use crate::config_document::ConfigStore;
+use crate::deadline::RequestTimeouts;
+use crate::events::EventLog;
+use crate::http_client::HttpMetrics;
+use crate::import::DEFAULT_MAX_IMPORT_BYTES;
+use crate::inflection::{InflectionForwarder, UnregisteredInflections};
+use crate::journal::MintJournal;
+use crate::key_usage::KeyUsage;
+use crate::ledger::LedgerCounts;
+use crate::limits::InputLimits;
+use crate::log_level::LogLevel;
+use crate::mint_pool::MintPools;
+use crate::mint_queue::MintQueue;
+use crate::mint_quota::MintQuotas;
+use crate::minting::{BladePool, CollisionPolicy, MintObserver, MintStats};
+use crate::names::{Naan, ShoulderName};
+use crate::notify::Notifier;
+use crate::partition::NodePartition;
+use crate::public_mint::PublicMint;
+use crate::quarantine::Quarantine;
+use crate::rate_limit::RateLimiter;
+use crate::read_only::ReadOnly;
+use crate::replication::Replication;
+use crate::server::{CanonicalHost, DEFAULT_ADMIN_API_PATH};
+use crate::shorten::Shortener;
 use crate::shoulder::Shoulder;
+use crate::sitemap::SitemapPages;
+use crate::snapshot::SnapshotDir;
+use crate::stats::ResolutionStats;
+use crate::status::StatusBoard;
+use crate::store::{ArkStore, MemoryArkStore};
+use crate::template::NoidTemplate;
+use crate::validation::ValidationPool;
+use crate::views::Views;
 
 /// The Betanumeric alphabet used for ARK blades.
 pub const BETANUMERIC: &[u8] = b"0123456789bcdfghjkmnpqrstvwxz";
 
 /// The application state shared across handlers.
-#[derive(Clone)]
 pub struct AppState {
     /// The NAAN (Name Assigning Authority Number) for this service.
-    pub naan: String,
+    pub naan: Naan,
     /// The default blade length for minted ARKs, excluding the check character.
     /// If a shoulder uses check characters, the final blade will be one character longer.
     /// Used when a shoulder doesn't specify its own blade_length.
     pub default_blade_length: usize,
     /// The maximum number of ARKs that can be minted in a single request.
     pub max_mint_count: usize,
+    /// The maximum number of ARKs that can be minted in a single streamed request.
+    pub max_stream_mint_count: usize,
+    /// The maximum number of ARKs validated in a single request; the rest are skipped.
+    pub max_validate_count: usize,
+    /// The largest NOID or EZID export accepted by an import request, in bytes.
+    pub max_import_bytes: usize,
+    /// Worker threads that validate large batches in parallel.
+    pub validation_pool: ValidationPool,
     /// The mapping of shoulders to their configurations.
-    pub shoulders: HashMap<String, Shoulder>,
+    pub shoulders: HashMap<ShoulderName, Shoulder>,
+    /// API keys accepted by the admin endpoints, mapped to the name of their holder.
+    /// The admin API is unusable while this is empty and no managed key has the admin scope.
+    pub admin_keys: HashMap<String, String>,
+    /// Path the admin API is mounted under (e.g. `/admin/api`); not served at all when unset.
+    pub admin_api_path: Option<String>,
+    /// Issues and verifies the short-lived admin UI tokens.
+    pub token_signer: TokenSigner,
+    /// API keys accepted when resolving ARKs on shoulders with `require_auth`, mapped to the
+    /// name of their holder. Kept apart from the admin keys so readers cannot administer.
+    pub resolution_keys: HashMap<String, String>,
+    /// API keys accepted by the endpoints that add ARKs (minting, registering, importing and
+    /// shortening), mapped to the name of their holder. Admin keys are accepted there too.
+    pub mint_keys: HashMap<String, String>,
+    /// API keys created through the admin API and kept hashed in the store, accepted alongside
+    /// the configured ones.
+    pub api_keys: ApiKeys,
+    /// Provenance of the live configuration and any staged configuration document.
+    pub config_store: ConfigStore,
+    /// Sends alert emails; disabled unless SMTP is configured.
+    pub notifier: Arc<Notifier>,
+    /// Uptime, incidents, and maintenance windows shown on the status page.
+    pub status_board: StatusBoard,
+    /// Public base URL of this resolver (e.g. `https://ark.example.org`), without trailing slash.
+    pub public_base_url: Option<String>,
+    /// Host that `GET` requests for any other host are redirected to; none by default.
+    pub canonical_host: Option<CanonicalHost>,
+    /// Relation used for the `Link` header pointing at the canonical ARK (`canonical` or
+    /// `cite-as`). No header is sent when unset or when `public_base_url` is unknown.
+    pub canonical_link_rel: Option<String>,
+    /// The service's persistence commitment, published in `/.well-known/ark-configuration`.
+    pub commitment: Option<String>,
+    /// How emitted ARKs are spelled, unless a shoulder overrides it.
+    pub ark_format: ArkFormat,
+    /// HTML templates for the status page, error pages, and other browser-facing pages.
+    pub views: Views,
+    /// Recent ARK changes, published as ResourceSync change lists.
+    pub event_log: EventLog,
+    /// Batches failed resolutions for shoulders with a `failure_callback`.
+    pub failure_callbacks: FailureCallbacks,
+    /// Redirects blocked by security validation, kept for review through the admin API.
+    pub quarantine: Quarantine,
+    /// Fetches inflection answers for shoulders with `forward_inflections`.
+    pub inflections: InflectionForwarder,
+    /// How inflections on ARKs of unregistered shoulders are answered; `404` by default.
+    pub unregistered_inflections: UnregisteredInflections,
+    /// Outbound request counters per destination, shared by every outbound HTTP client.
+    pub http_metrics: Arc<HttpMetrics>,
+    /// How minting retries when a generated identifier has already been issued.
+    pub collision_policy: CollisionPolicy,
+    /// Threads drawing the blades of large mints; blades are drawn serially by default.
+    pub blade_pool: BladePool,
+    /// This instance's share of every shoulder's namespace when several mint side by side.
+    pub node_partition: NodePartition,
+    /// Mint, collision, and escalation counters per shoulder.
+    pub mint_stats: MintStats,
+    /// ARKs minted this hour and day on shoulders with a `mint_quota`.
+    pub mint_quotas: MintQuotas,
+    /// ARKs minted ahead of requests, per shoulder; disabled unless configured.
+    pub mint_pools: MintPools,
+    /// The running log filter, adjustable through the admin API once logging is set up.
+    pub log_level: LogLevel,
+    /// Recommends, and optionally switches to, longer blades as shoulders get crowded.
+    pub blade_advisor: BladeLengthAdvisor,
+    /// Successful resolutions per shoulder over the last 30 days.
+    pub resolution_stats: ResolutionStats,
+    /// ARKs in the mint ledger per public shoulder, counted in the background.
+    pub ledger_counts: LedgerCounts,
+    /// Bound ARKs per public shoulder, counted in the background.
+    pub binding_counts: BindingCounts,
+    /// Where the sitemaps of each indexed shoulder start, found in the background.
+    pub sitemap_pages: SitemapPages,
+    /// Calls made with each API key over the last 30 days.
+    pub key_usage: KeyUsage,
+    /// Limits requests per client; disabled unless configured.
+    pub rate_limiter: RateLimiter,
+    /// Unauthenticated minting on a test shoulder; not served unless configured.
+    pub public_mint: Option<PublicMint>,
+    /// Short links derived from URLs on a service-managed shoulder; not served unless configured.
+    pub shortener: Option<Shortener>,
+    /// Pauses minting and changes to ARKs while set; off unless configured.
+    pub read_only: ReadOnly,
+    /// Deadlines of resolution requests; none unless configured or asked for by the client.
+    pub request_timeouts: RequestTimeouts,
+    /// Maximum lengths of incoming ARKs, blades and qualifiers, with rejection counts.
+    pub input_limits: InputLimits,
+    /// Told about every mint request by applications embedding the service; none by default.
+    pub mint_observer: Option<Arc<dyn MintObserver>>,
+    /// Mint ledger and bindings, in memory unless a persistent backend is configured.
+    pub store: Arc<dyn ArkStore>,
+    /// Targets recorded for individual ARKs through mint-and-bind, kept in `store`, with its
+    /// availability.
+    pub bindings: Bindings,
+    /// Keeps bindings made while the binding store is down, for replay; disabled unless configured.
+    pub mint_journal: Option<MintJournal>,
+    /// Mirrors changes to `store` to a standby instance; disabled unless configured.
+    pub replication: Arc<Replication>,
+    /// Snapshots of the registry to restore from; disabled unless configured.
+    pub snapshots: Option<SnapshotDir>,
+    /// Bounds the mint requests waiting on storage; requests mint inline by default.
+    pub mint_queue: MintQueue,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        let store: Arc<dyn ArkStore> = Arc::new(MemoryArkStore::default());
+        Self {
+            naan: Naan::new("12345").expect("valid NAAN"),
+            default_blade_length: 8,
+            max_mint_count: 1000,
+            max_stream_mint_count: 1_000_000,
+            max_validate_count: 10000,
+            max_import_bytes: DEFAULT_MAX_IMPORT_BYTES,
+            validation_pool: ValidationPool::default(),
+            shoulders: HashMap::new(),
+            admin_keys: HashMap::new(),
+            admin_api_path: Some(DEFAULT_ADMIN_API_PATH.to_string()),
+            token_signer: TokenSigner::default(),
+            resolution_keys: HashMap::new(),
+            mint_keys: HashMap::new(),
+            api_keys: ApiKeys::default(),
+            config_store: ConfigStore::default(),
+            notifier: Arc::new(Notifier::default()),
+            status_board: StatusBoard::default(),
+            public_base_url: None,
+            canonical_host: None,
+            canonical_link_rel: None,
+            commitment: None,
+            ark_format: ArkFormat::default(),
+            views: Views::default(),
+            event_log: EventLog::default(),
+            failure_callbacks: FailureCallbacks::default(),
+            quarantine: Quarantine::default(),
+            inflections: InflectionForwarder::default(),
+            unregistered_inflections: UnregisteredInflections::default(),
+            http_metrics: Arc::default(),
+            collision_policy: CollisionPolicy::default(),
+            blade_pool: BladePool::default(),
+            node_partition: NodePartition::default(),
+            mint_stats: MintStats::default(),
+            mint_quotas: MintQuotas::default(),
+            mint_pools: MintPools::default(),
+            log_level: LogLevel::default(),
+            blade_advisor: BladeLengthAdvisor::default(),
+            resolution_stats: ResolutionStats::default(),
+            ledger_counts: LedgerCounts::default(),
+            binding_counts: BindingCounts::default(),
+            sitemap_pages: SitemapPages::default(),
+            key_usage: KeyUsage::default(),
+            rate_limiter: RateLimiter::default(),
+            public_mint: None,
+            shortener: None,
+            read_only: ReadOnly::default(),
+            request_timeouts: RequestTimeouts::default(),
+            input_limits: InputLimits::default(),
+            mint_observer: None,
+            store: store.clone(),
+            bindings: Bindings::new(store),
+            mint_journal: None,
+            replication: Arc::default(),
+            snapshots: None,
+            mint_queue: MintQueue::default(),
+        }
+    }
+}
+
+impl AppState {
+    /// The emitted-ARK format for a shoulder: its own policy, or the global one
+    pub fn ark_format_for<'a>(&'a self, shoulder: &'a Shoulder) -> &'a ArkFormat {
+        shoulder.ark_format.as_ref().unwrap_or(&self.ark_format)
+    }
+
+    /// Whether a shoulder is advertised; ARKs on unknown shoulders are not hidden
+    pub fn is_public(&self, shoulder: &str) -> bool {
+        self.shoulders
+            .get(shoulder)
+            .is_none_or(|config| config.public)
+    }
+
+    /// The blade length a shoulder mints with: its template's, or else its own or the default
+    /// unless the blade advisor escalated it
+    pub fn blade_length_for(&self, name: &str, shoulder: &Shoulder) -> usize {
+        if let Some(template) = &shoulder.template {
+            return template.blade_length();
+        }
+        let configured = shoulder.blade_length.unwrap_or(self.default_blade_length);
+        self.blade_advisor.blade_length(name, configured)
+    }
+
+    /// The shape of the blades this instance mints on a shoulder, within its node partition
+    pub fn blade_template_for(&self, shoulder: &Shoulder, blade_length: usize) -> NoidTemplate {
+        shoulder
+            .blade_template(blade_length)
+            .with_partition(self.node_partition)
+    }
+
+    /// Whether ARKs on `shoulder` are derived by the URL shortener rather than minted
+    pub fn is_shortened(&self, shoulder: &str) -> bool {
+        self.shortener
+            .as_ref()
+            .is_some_and(|shortener| shortener.shoulder.as_str() == shoulder)
+    }
+}
+
+/// Parse admin API keys from the `ADMIN_API_KEYS` format
+///
+/// Format: `name:key,name:key,...`. The name identifies the key holder in provenance records
+/// and logs; the key is what clients send as a bearer token.
+pub fn parse_admin_keys(value: &str) -> Result<HashMap<String, String>, String> {
+    let mut keys = HashMap::new();
+
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, key) = entry.split_once(':').ok_or_else(|| {
+            format!(
+                "Invalid admin key entry (expected name:key): {}",
+                name_of(entry)
+            )
+        })?;
+        let (name, key) = (name.trim(), key.trim());
+        if name.is_empty() || key.is_empty() {
+            return Err(format!("Invalid admin key entry: {}", name_of(entry)));
+        }
+        keys.insert(key.to_string(), name.to_string());
+    }
+
+    Ok(keys)
+}
+
+/// Returns the part of an admin key entry that is safe to show in error messages
+fn name_of(entry: &str) -> &str {
+    entry.split(':').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_admin_keys() {
+        let keys = parse_admin_keys("alice:s3cret, bob:hunter2").unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys["s3cret"], "alice");
+        assert_eq!(keys["hunter2"], "bob");
+
+        assert!(parse_admin_keys("").unwrap().is_empty());
+        assert!(parse_admin_keys("no-separator").is_err());
+        assert!(parse_admin_keys("alice:").is_err());
+    }
 }
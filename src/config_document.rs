@@ -0,0 +1,353 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use crate::names::{Naan, ShoulderName};
+use crate::shoulder::{Shoulder, describe_shoulder_error};
+
+/// Canonical, exportable representation of the service configuration
+///
+/// Shoulders are stored in a `BTreeMap` so the serialized document has a stable key order,
+/// which keeps diffs meaningful when the export is committed to version control.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConfigDocument {
+    pub naan: Naan,
+    pub default_blade_length: usize,
+    pub max_mint_count: usize,
+    pub shoulders: BTreeMap<ShoulderName, ShoulderDocument>,
+}
+
+/// A single shoulder entry in a [`ConfigDocument`]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShoulderDocument {
+    #[serde(flatten)]
+    pub config: Shoulder,
+    /// Who last changed this shoulder and when. Ignored on import; the service records its own.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// Records who changed a piece of configuration, when, and through which channel
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Provenance {
+    pub changed_by: String,
+    pub changed_at: DateTime<Utc>,
+    pub source: String,
+}
+
+/// A change between the live configuration and a staged document
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ConfigChange {
+    Added { shoulder: ShoulderName },
+    Removed { shoulder: ShoulderName },
+    Modified { shoulder: ShoulderName },
+    Setting { name: String },
+}
+
+/// A configuration document imported for review, not yet applied to the running service
+#[derive(Clone, Debug, Serialize)]
+pub struct StagedConfig {
+    pub document: ConfigDocument,
+    pub staged_by: String,
+    pub staged_at: DateTime<Utc>,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Holds provenance for live shoulders and the currently staged configuration
+///
+/// The running service remains the source of truth: importing a document only stages it,
+/// so operators can review the computed changes before rolling them out.
+#[derive(Debug, Default)]
+pub struct ConfigStore {
+    provenance: RwLock<HashMap<String, Provenance>>,
+    staged: RwLock<Option<StagedConfig>>,
+}
+
+impl ConfigStore {
+    /// Creates a store that records every given shoulder as changed by `changed_by` via `source`
+    pub fn new<'a>(
+        shoulders: impl IntoIterator<Item = &'a ShoulderName>,
+        changed_by: &str,
+        source: &str,
+    ) -> Self {
+        let now = Utc::now();
+        let provenance = shoulders
+            .into_iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    Provenance {
+                        changed_by: changed_by.to_string(),
+                        changed_at: now,
+                        source: source.to_string(),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            provenance: RwLock::new(provenance),
+            staged: RwLock::new(None),
+        }
+    }
+
+    /// Returns the provenance recorded for a shoulder, if any
+    pub fn provenance(&self, shoulder: &str) -> Option<Provenance> {
+        self.provenance
+            .read()
+            .expect("provenance lock poisoned")
+            .get(shoulder)
+            .cloned()
+    }
+
+    /// Records who last changed a shoulder
+    pub fn record(&self, shoulder: &str, provenance: Provenance) {
+        self.provenance
+            .write()
+            .expect("provenance lock poisoned")
+            .insert(shoulder.to_string(), provenance);
+    }
+
+    /// Returns a copy of the currently staged configuration
+    pub fn staged(&self) -> Option<StagedConfig> {
+        self.staged.read().expect("staged lock poisoned").clone()
+    }
+
+    /// Replaces the staged configuration
+    pub fn stage(&self, staged: StagedConfig) {
+        *self.staged.write().expect("staged lock poisoned") = Some(staged);
+    }
+
+    /// Discards the staged configuration, returning whether one existed
+    pub fn discard_staged(&self) -> bool {
+        self.staged
+            .write()
+            .expect("staged lock poisoned")
+            .take()
+            .is_some()
+    }
+}
+
+impl ConfigDocument {
+    /// Builds the canonical document for the given live configuration
+    pub fn from_live(
+        naan: &Naan,
+        default_blade_length: usize,
+        max_mint_count: usize,
+        shoulders: &HashMap<ShoulderName, Shoulder>,
+        store: &ConfigStore,
+    ) -> Self {
+        let shoulders = shoulders
+            .iter()
+            .map(|(name, config)| {
+                (
+                    name.clone(),
+                    ShoulderDocument {
+                        config: config.clone(),
+                        provenance: store.provenance(name),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            naan: naan.clone(),
+            default_blade_length,
+            max_mint_count,
+            shoulders,
+        }
+    }
+
+    /// Validates every shoulder in the document, returning the first error found
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, shoulder) in &self.shoulders {
+            shoulder
+                .config
+                .validate_as(name)
+                .map_err(|e| describe_shoulder_error(name, &e))?;
+        }
+        Ok(())
+    }
+
+    /// Lists the changes needed to go from `self` to `other`
+    pub fn diff(&self, other: &ConfigDocument) -> Vec<ConfigChange> {
+        let mut changes = Vec::new();
+
+        if self.naan != other.naan {
+            changes.push(ConfigChange::Setting {
+                name: "naan".to_string(),
+            });
+        }
+        if self.default_blade_length != other.default_blade_length {
+            changes.push(ConfigChange::Setting {
+                name: "default_blade_length".to_string(),
+            });
+        }
+        if self.max_mint_count != other.max_mint_count {
+            changes.push(ConfigChange::Setting {
+                name: "max_mint_count".to_string(),
+            });
+        }
+
+        for (name, current) in &self.shoulders {
+            match other.shoulders.get(name) {
+                None => changes.push(ConfigChange::Removed {
+                    shoulder: name.clone(),
+                }),
+                Some(next) if next.config != current.config => {
+                    changes.push(ConfigChange::Modified {
+                        shoulder: name.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for name in other.shoulders.keys() {
+            if !self.shoulders.contains_key(name) {
+                changes.push(ConfigChange::Added {
+                    shoulder: name.clone(),
+                });
+            }
+        }
+
+        changes
+    }
+
+    /// Serializes the document in its canonical form (sorted keys, pretty-printed)
+    pub fn to_canonical_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("config document is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shoulder(route_pattern: &str) -> Shoulder {
+        Shoulder {
+            route_pattern: route_pattern.to_string(),
+            project_name: "Test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn live_document() -> ConfigDocument {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            shoulder("https://example.org/${value}"),
+        );
+        shoulders.insert("b3".parse().unwrap(), shoulder("https://beta.org/${value}"));
+        let store = ConfigStore::new(shoulders.keys(), "environment", "SHOULDERS");
+
+        ConfigDocument::from_live(&"12345".parse().unwrap(), 8, 1000, &shoulders, &store)
+    }
+
+    #[test]
+    fn test_export_is_canonical_and_includes_provenance() {
+        let document = live_document();
+        let json = document.to_canonical_json();
+
+        // Shoulders are sorted, so b3 always precedes x6
+        assert!(json.find("\"b3\"").unwrap() < json.find("\"x6\"").unwrap());
+        assert!(json.contains("\"changed_by\": \"environment\""));
+        assert!(json.contains("\"source\": \"SHOULDERS\""));
+
+        // Serializing the same document twice yields identical output
+        assert_eq!(json, document.to_canonical_json());
+    }
+
+    #[test]
+    fn test_import_ignores_provenance_and_keeps_comments() {
+        let json = r#"{
+            "naan": "12345",
+            "default_blade_length": 8,
+            "max_mint_count": 1000,
+            "shoulders": {
+                "x6": {
+                    "route_pattern": "https://example.org/${value}",
+                    "project_name": "Test",
+                    "comment": "Moved to the new storage cluster",
+                    "provenance": {"changed_by": "mallory", "changed_at": "2020-01-01T00:00:00Z", "source": "forged"}
+                }
+            }
+        }"#;
+
+        let document: ConfigDocument = serde_json::from_str(json).unwrap();
+        let x6 = &document.shoulders["x6"];
+        assert_eq!(
+            x6.config.comment.as_deref(),
+            Some("Moved to the new storage cluster")
+        );
+        assert!(x6.provenance.is_none());
+    }
+
+    #[test]
+    fn test_validate_rejects_unsafe_route_pattern() {
+        let mut document = live_document();
+        document
+            .shoulders
+            .get_mut("x6")
+            .unwrap()
+            .config
+            .route_pattern = "javascript:alert(1)".to_string();
+
+        assert!(document.validate().is_err());
+        assert!(live_document().validate().is_ok());
+    }
+
+    #[test]
+    fn test_diff_reports_changes() {
+        let live = live_document();
+        let mut staged = live.clone();
+        staged.shoulders.remove("b3");
+        staged.shoulders.get_mut("x6").unwrap().config.project_name = "Renamed".to_string();
+        staged.shoulders.insert(
+            "fk4".parse().unwrap(),
+            ShoulderDocument {
+                config: shoulder("https://fk.org/${value}"),
+                provenance: None,
+            },
+        );
+        staged.max_mint_count = 50;
+
+        let changes = live.diff(&staged);
+        assert_eq!(
+            changes,
+            vec![
+                ConfigChange::Setting {
+                    name: "max_mint_count".to_string()
+                },
+                ConfigChange::Removed {
+                    shoulder: "b3".parse().unwrap()
+                },
+                ConfigChange::Modified {
+                    shoulder: "x6".parse().unwrap()
+                },
+                ConfigChange::Added {
+                    shoulder: "fk4".parse().unwrap()
+                },
+            ]
+        );
+        assert!(live.diff(&live_document()).is_empty());
+    }
+
+    #[test]
+    fn test_stage_and_discard() {
+        let store = ConfigStore::default();
+        assert!(store.staged().is_none());
+        assert!(!store.discard_staged());
+
+        store.stage(StagedConfig {
+            document: live_document(),
+            staged_by: "alice".to_string(),
+            staged_at: Utc::now(),
+            changes: Vec::new(),
+        });
+        assert_eq!(store.staged().unwrap().staged_by, "alice");
+        assert!(store.discard_staged());
+        assert!(store.staged().is_none());
+    }
+}
@@ -0,0 +1,186 @@
+use axum::http::HeaderMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::notify::env_parse;
+
+/// Header a client can send to ask for a shorter deadline, in milliseconds
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout";
+
+/// Longest deadline a client can ask for
+pub const DEFAULT_MAX_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The point by which a request must be answered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deadline(Instant);
+
+/// The deadline passed before the work finished
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeadlineExceeded;
+
+impl Deadline {
+    /// A deadline `timeout` from now
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now() + timeout)
+    }
+
+    pub fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// Time left before the deadline, zero once it has passed
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+/// Runs blocking `work`, such as a storage query, giving up once `deadline` passes
+///
+/// Without a deadline the work runs inline. With one it runs on the blocking pool, where
+/// work that misses the deadline still finishes, but nobody waits for it.
+pub async fn run_blocking<T, F>(deadline: Option<Deadline>, work: F) -> Result<T, DeadlineExceeded>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let Some(deadline) = deadline else {
+        return Ok(work());
+    };
+    if deadline.is_expired() {
+        return Err(DeadlineExceeded);
+    }
+    match tokio::time::timeout_at(deadline.instant(), tokio::task::spawn_blocking(work)).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => std::panic::resume_unwind(e.into_panic()),
+        Err(_) => Err(DeadlineExceeded),
+    }
+}
+
+/// How long requests get before they are answered with `504 Gateway Timeout`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RequestTimeouts {
+    /// Deadline of requests that do not ask for one; none by default
+    pub default: Option<Duration>,
+    /// Cap on the deadline a client asks for with `X-Request-Timeout`, when there is no
+    /// default to cap it at
+    pub max: Duration,
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            default: None,
+            max: DEFAULT_MAX_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl RequestTimeouts {
+    /// The deadline of a request starting now: the one it asks for, capped at the default or
+    /// else the maximum, or the default
+    pub fn deadline_for(&self, headers: &HeaderMap) -> Result<Option<Deadline>, String> {
+        let requested = headers
+            .get(REQUEST_TIMEOUT_HEADER)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .filter(|millis| *millis > 0)
+                    .map(Duration::from_millis)
+                    .ok_or_else(|| {
+                        "X-Request-Timeout must be a positive number of milliseconds".to_string()
+                    })
+            })
+            .transpose()?;
+        Ok(requested
+            .map(|timeout| timeout.min(self.default.unwrap_or(self.max).min(self.max)))
+            .or(self.default)
+            .map(Deadline::after))
+    }
+}
+
+/// Build the request timeouts from environment variables
+///
+/// Optional: `REQUEST_TIMEOUT_MS` (no deadline unless set), `REQUEST_TIMEOUT_MAX_MS`
+/// (default 30000).
+pub fn load_request_timeouts_from_env() -> Result<RequestTimeouts, String> {
+    let timeouts = RequestTimeouts {
+        default: env_parse("REQUEST_TIMEOUT_MS")?.map(Duration::from_millis),
+        max: env_parse("REQUEST_TIMEOUT_MAX_MS")?
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_MAX_REQUEST_TIMEOUT),
+    };
+    if timeouts.default.is_some_and(|timeout| timeout.is_zero()) || timeouts.max.is_zero() {
+        return Err(
+            "REQUEST_TIMEOUT_MS and REQUEST_TIMEOUT_MAX_MS must be greater than 0".to_string(),
+        );
+    }
+    Ok(timeouts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_deadline_for() {
+        let timeouts = RequestTimeouts {
+            default: Some(Duration::from_secs(2)),
+            max: Duration::from_secs(5),
+        };
+        let remaining = |headers: &HeaderMap| {
+            timeouts
+                .deadline_for(headers)
+                .map(|deadline| deadline.map(|deadline| deadline.remaining()))
+        };
+        let mut headers = HeaderMap::new();
+        let default = remaining(&headers).unwrap().unwrap();
+        assert!(default <= Duration::from_secs(2) && default > Duration::from_secs(1));
+
+        headers.insert(REQUEST_TIMEOUT_HEADER, HeaderValue::from_static("250"));
+        assert!(remaining(&headers).unwrap().unwrap() <= Duration::from_millis(250));
+
+        // A client can only shorten the default
+        headers.insert(REQUEST_TIMEOUT_HEADER, HeaderValue::from_static("4000"));
+        assert!(remaining(&headers).unwrap().unwrap() <= Duration::from_secs(2));
+
+        let uncapped = RequestTimeouts {
+            default: None,
+            max: Duration::from_secs(5),
+        };
+        headers.insert(REQUEST_TIMEOUT_HEADER, HeaderValue::from_static("600000"));
+        let capped = uncapped
+            .deadline_for(&headers)
+            .unwrap()
+            .unwrap()
+            .remaining();
+        assert!(capped <= Duration::from_secs(5) && capped > Duration::from_secs(4));
+
+        for invalid in ["0", "-1", "soon"] {
+            headers.insert(REQUEST_TIMEOUT_HEADER, HeaderValue::from_static(invalid));
+            assert!(remaining(&headers).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(
+            RequestTimeouts::default().deadline_for(&HeaderMap::new()),
+            Ok(None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_blocking_gives_up_at_the_deadline() {
+        assert_eq!(run_blocking(None, || 1).await, Ok(1));
+        let deadline = Some(Deadline::after(Duration::from_secs(5)));
+        assert_eq!(run_blocking(deadline, || 2).await, Ok(2));
+
+        let deadline = Some(Deadline::after(Duration::from_millis(20)));
+        let slow = run_blocking(deadline, || std::thread::sleep(Duration::from_millis(500)));
+        assert_eq!(slow.await, Err(DeadlineExceeded));
+    }
+}
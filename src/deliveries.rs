@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::bindings::StorageError;
+
+/// Where a webhook delivery stands
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    Delivered,
+    /// Given up on after the last allowed attempt; only a manual retry sends it again
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One webhook payload and the state of its delivery
+///
+/// The id is sent as the `Idempotency-Key` header on every attempt, so receivers can drop
+/// repeats of a payload they already processed.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Delivery {
+    pub id: String,
+    pub url: String,
+    pub shoulder: String,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// When the next attempt is due, while pending
+    pub next_attempt_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl Delivery {
+    /// A pending delivery of `payload` to `url`, due now, with a fresh idempotency key
+    pub fn new(url: &str, shoulder: &str, payload: serde_json::Value) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!("{:032x}", rand::random::<u128>()),
+            url: url.to_string(),
+            shoulder: shoulder.to_string(),
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+}
+
+/// Backend that keeps webhook deliveries, so pending ones survive restarts and delivered ones
+/// are not sent again
+pub trait DeliveryStore: Send + Sync {
+    /// Inserts the delivery, or replaces the one with the same id
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError>;
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError>;
+    /// Deliveries with `status`, or all of them, newest first
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError>;
+    /// Removes delivered deliveries created before `before`, returning how many
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError>;
+}
+
+/// Deliveries kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryDeliveryStore {
+    deliveries: RwLock<HashMap<String, Delivery>>,
+}
+
+impl DeliveryStore for MemoryDeliveryStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        self.deliveries
+            .write()
+            .expect("deliveries lock poisoned")
+            .insert(delivery.id.clone(), delivery.clone());
+        Ok(())
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        Ok(self
+            .deliveries
+            .read()
+            .expect("deliveries lock poisoned")
+            .get(id)
+            .cloned())
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        let mut deliveries: Vec<Delivery> = self
+            .deliveries
+            .read()
+            .expect("deliveries lock poisoned")
+            .values()
+            .filter(|delivery| status.is_none_or(|status| delivery.status == status))
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|delivery| std::cmp::Reverse(delivery.created_at));
+        Ok(deliveries)
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        let mut deliveries = self.deliveries.write().expect("deliveries lock poisoned");
+        let count = deliveries.len();
+        deliveries.retain(|_, delivery| {
+            delivery.status != DeliveryStatus::Delivered || delivery.created_at >= before
+        });
+        Ok(count - deliveries.len())
+    }
+}
@@ -0,0 +1,140 @@
+use crate::ark::{Ark, ArkFormat};
+use crate::shoulder::Shoulder;
+
+/// A simple (unqualified) Dublin Core record; every element is repeatable
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DublinCore {
+    pub title: Vec<String>,
+    pub creator: Vec<String>,
+    pub description: Vec<String>,
+    pub publisher: Vec<String>,
+    pub date: Vec<String>,
+    pub identifier: Vec<String>,
+    pub relation: Vec<String>,
+}
+
+impl DublinCore {
+    /// Describes an ARK from what the service knows about it
+    ///
+    /// This is the single mapping from service data to Dublin Core, shared by every `oai_dc`
+    /// output. The record identifies the object (canonical ARK, resolver URL, and target URL)
+    /// and names the shoulder's owner as publisher.
+    pub fn describe(
+        ark: &Ark,
+        shoulder: &Shoulder,
+        format: &ArkFormat,
+        public_base_url: Option<&str>,
+        target_url: &str,
+    ) -> Self {
+        let canonical = format.format_canonical(ark);
+        let mut identifier = vec![canonical.clone()];
+        if let Some(base) = public_base_url {
+            identifier.push(format!("{}/{}", base, canonical));
+        }
+        identifier.push(target_url.to_string());
+
+        let publisher = shoulder
+            .contact
+            .as_ref()
+            .map(|contact| {
+                contact
+                    .organization
+                    .clone()
+                    .unwrap_or_else(|| contact.name.clone())
+            })
+            .into_iter()
+            .collect();
+
+        Self {
+            publisher,
+            identifier,
+            relation: vec![shoulder.project_name.clone()],
+            ..Default::default()
+        }
+    }
+
+    /// Serializes the record as an OAI-PMH `oai_dc` XML document
+    pub fn to_oai_dc_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <oai_dc:dc xmlns:oai_dc=\"http://www.openarchives.org/OAI/2.0/oai_dc/\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+             xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+             xsi:schemaLocation=\"http://www.openarchives.org/OAI/2.0/oai_dc/ \
+             http://www.openarchives.org/OAI/2.0/oai_dc.xsd\">\n",
+        );
+
+        let elements = [
+            ("title", &self.title),
+            ("creator", &self.creator),
+            ("description", &self.description),
+            ("publisher", &self.publisher),
+            ("date", &self.date),
+            ("identifier", &self.identifier),
+            ("relation", &self.relation),
+        ];
+        for (name, values) in elements {
+            for value in values {
+                xml.push_str(&format!(
+                    "  <dc:{0}>{1}</dc:{0}>\n",
+                    name,
+                    escape_xml(value)
+                ));
+            }
+        }
+
+        xml.push_str("</oai_dc:dc>\n");
+        xml
+    }
+}
+
+/// Escape text for inclusion in XML element content
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shoulder::Contact;
+
+    #[test]
+    fn test_describe_and_serialize() {
+        let ark = Ark::try_from("ark:/12345/x6np1wh8k/page2.pdf").unwrap();
+        let shoulder = Shoulder {
+            route_pattern: "https://example.org/${value}".to_string(),
+            project_name: "Letters & Diaries".to_string(),
+            contact: Some(Contact {
+                name: "Digital Collections".to_string(),
+                email: None,
+                organization: Some("Example Library".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let record = DublinCore::describe(
+            &ark,
+            &shoulder,
+            &ArkFormat::default(),
+            Some("https://ark.example.org"),
+            "https://example.org/x6np1wh8k/page2.pdf",
+        );
+        assert_eq!(
+            record.identifier,
+            vec![
+                "ark:12345/x6np1wh8k/page2.pdf",
+                "https://ark.example.org/ark:12345/x6np1wh8k/page2.pdf",
+                "https://example.org/x6np1wh8k/page2.pdf",
+            ]
+        );
+        assert_eq!(record.publisher, vec!["Example Library"]);
+
+        let xml = record.to_oai_dc_xml();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<dc:publisher>Example Library</dc:publisher>"));
+        assert!(xml.contains("<dc:relation>Letters &amp; Diaries</dc:relation>"));
+        assert!(xml.trim_end().ends_with("</oai_dc:dc>"));
+    }
+}
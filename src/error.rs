@@ -1,29 +1,202 @@
 use axum::{
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 
+use std::time::Duration;
+
+use crate::bindings::StorageError;
+use crate::i18n::Locale;
+use crate::limits::TooLong;
+
 #[derive(Debug)]
 pub enum AppError {
     ShoulderNotFound,
     InvalidArk,
+    /// The ARK in the request URI is longer than allowed
+    UriTooLong,
+    /// The blade or qualifier of an ARK is longer than allowed
+    InputTooLong,
     InvalidNaan,
+    Unauthorized,
+    CsrfRejected,
+    MethodNotAllowed,
+    BlockedRedirect,
+    MintExhausted,
+    /// The ARK was withdrawn or tombstoned
+    Withdrawn,
+    /// The binding store or mint ledger is down, so mints cannot be recorded
+    StorageUnavailable(String),
+    RateLimited {
+        retry_after: Duration,
+    },
+    /// Too many requests are already waiting for a mint worker
+    Overloaded {
+        retry_after: Duration,
+    },
+    /// The shoulder's mint quota for the current hour or day is used up
+    MintQuotaExceeded {
+        retry_after: Duration,
+    },
+    /// The request's deadline passed while waiting on storage or an upstream
+    DeadlineExceeded,
+    /// The service is read-only, so ARKs cannot be minted or changed
+    ReadOnly,
+    NoStagedConfig,
+    InvalidConfig(String),
+    InvalidRequest(String),
+    /// The request is well-formed but cannot be carried out
+    Unprocessable(String),
+    NotFound(String),
+    Internal(String),
 }
 
+/// Catalog key of a public-facing error message, left in the response extensions so the
+/// message can be localized once the client's language is known
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ErrorMessage(pub &'static str);
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             AppError::ShoulderNotFound => {
-                tracing::warn!(error_type = "ShoulderNotFound", "Request failed: shoulder not found");
-                (StatusCode::NOT_FOUND, "Shoulder not found")
+                tracing::warn!(
+                    error_type = "ShoulderNotFound",
+                    "Request failed: shoulder not found"
+                );
+                return public_error(StatusCode::NOT_FOUND, "shoulder_not_found");
             }
             AppError::InvalidArk => {
-                tracing::warn!(error_type = "InvalidArk", "Request failed: invalid ARK format");
-                (StatusCode::BAD_REQUEST, "Invalid ARK format")
+                tracing::warn!(
+                    error_type = "InvalidArk",
+                    "Request failed: invalid ARK format"
+                );
+                return public_error(StatusCode::BAD_REQUEST, "invalid_ark");
+            }
+            AppError::UriTooLong => {
+                tracing::warn!(error_type = "UriTooLong", "Request failed: ARK too long");
+                return public_error(StatusCode::URI_TOO_LONG, "uri_too_long");
+            }
+            AppError::InputTooLong => {
+                tracing::warn!(
+                    error_type = "InputTooLong",
+                    "Request failed: blade or qualifier too long"
+                );
+                return public_error(StatusCode::BAD_REQUEST, "input_too_long");
             }
             AppError::InvalidNaan => {
                 tracing::warn!(error_type = "InvalidNaan", "Request failed: NAAN mismatch");
-                (StatusCode::BAD_REQUEST, "NAAN does not match")
+                return public_error(StatusCode::BAD_REQUEST, "naan_mismatch");
+            }
+            AppError::Unauthorized => {
+                tracing::warn!(
+                    error_type = "Unauthorized",
+                    "Request failed: missing or invalid API key"
+                );
+                return public_error(StatusCode::UNAUTHORIZED, "unauthorized");
+            }
+            AppError::CsrfRejected => {
+                tracing::warn!(
+                    error_type = "CsrfRejected",
+                    "Request failed: missing or invalid CSRF token"
+                );
+                return public_error(StatusCode::FORBIDDEN, "csrf_rejected");
+            }
+            AppError::MethodNotAllowed => {
+                tracing::debug!(
+                    error_type = "MethodNotAllowed",
+                    "Request failed: method not allowed for this shoulder"
+                );
+                let mut response =
+                    public_error(StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed");
+                response
+                    .headers_mut()
+                    .insert(header::ALLOW, header::HeaderValue::from_static("GET, HEAD"));
+                return response;
+            }
+            AppError::BlockedRedirect => {
+                tracing::warn!(
+                    error_type = "BlockedRedirect",
+                    "Request failed: redirect target blocked"
+                );
+                return public_error(StatusCode::BAD_GATEWAY, "blocked_redirect");
+            }
+            AppError::MintExhausted => {
+                tracing::error!(
+                    error_type = "MintExhausted",
+                    "Request failed: no unused identifier found within the retry limit"
+                );
+                return public_error(StatusCode::SERVICE_UNAVAILABLE, "mint_exhausted");
+            }
+            AppError::Withdrawn => {
+                tracing::debug!(error_type = "Withdrawn", "Request failed: ARK withdrawn");
+                return public_error(StatusCode::GONE, "tombstone");
+            }
+            AppError::StorageUnavailable(reason) => {
+                tracing::error!(error_type = "StorageUnavailable", reason = %reason, "Request failed: storage unavailable");
+                return public_error(StatusCode::SERVICE_UNAVAILABLE, "storage_unavailable");
+            }
+            AppError::RateLimited { retry_after } => {
+                tracing::debug!(
+                    error_type = "RateLimited",
+                    "Request failed: rate limit exceeded"
+                );
+                let response = public_error(StatusCode::TOO_MANY_REQUESTS, "rate_limited");
+                return with_retry_after(response, retry_after);
+            }
+            AppError::Overloaded { retry_after } => {
+                tracing::warn!(error_type = "Overloaded", "Request failed: mint queue full");
+                let response = public_error(StatusCode::SERVICE_UNAVAILABLE, "overloaded");
+                return with_retry_after(response, retry_after);
+            }
+            AppError::MintQuotaExceeded { retry_after } => {
+                tracing::warn!(
+                    error_type = "MintQuotaExceeded",
+                    "Request failed: shoulder mint quota exceeded"
+                );
+                let response = public_error(StatusCode::TOO_MANY_REQUESTS, "mint_quota_exceeded");
+                return with_retry_after(response, retry_after);
+            }
+            AppError::DeadlineExceeded => {
+                tracing::warn!(
+                    error_type = "DeadlineExceeded",
+                    "Request failed: deadline exceeded"
+                );
+                return public_error(StatusCode::GATEWAY_TIMEOUT, "deadline_exceeded");
+            }
+            AppError::ReadOnly => {
+                tracing::debug!(error_type = "ReadOnly", "Request failed: read-only mode");
+                return public_error(StatusCode::SERVICE_UNAVAILABLE, "read_only");
+            }
+            AppError::NoStagedConfig => {
+                tracing::debug!(
+                    error_type = "NoStagedConfig",
+                    "Request failed: no staged configuration"
+                );
+                (StatusCode::NOT_FOUND, "No staged configuration".to_string())
+            }
+            AppError::InvalidConfig(reason) => {
+                tracing::warn!(error_type = "InvalidConfig", reason = %reason, "Request failed: invalid configuration");
+                (StatusCode::UNPROCESSABLE_ENTITY, reason)
+            }
+            AppError::InvalidRequest(reason) => {
+                tracing::warn!(error_type = "InvalidRequest", reason = %reason, "Request failed: invalid request");
+                (StatusCode::BAD_REQUEST, reason)
+            }
+            AppError::Unprocessable(reason) => {
+                tracing::warn!(error_type = "Unprocessable", reason = %reason, "Request failed: unprocessable");
+                (StatusCode::UNPROCESSABLE_ENTITY, reason)
+            }
+            AppError::NotFound(what) => {
+                tracing::debug!(error_type = "NotFound", what = %what, "Request failed: not found");
+                (StatusCode::NOT_FOUND, format!("{} not found", what))
+            }
+            AppError::Internal(reason) => {
+                tracing::error!(error_type = "Internal", reason = %reason, "Request failed: internal error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal server error".to_string(),
+                )
             }
         };
 
@@ -31,3 +204,34 @@ impl IntoResponse for AppError {
     }
 }
 
+impl From<TooLong> for AppError {
+    fn from(part: TooLong) -> Self {
+        match part {
+            TooLong::Ark => AppError::UriTooLong,
+            TooLong::Blade | TooLong::Qualifier => AppError::InputTooLong,
+        }
+    }
+}
+
+/// Adds `Retry-After` in whole seconds, rounded up so clients never retry too early
+fn with_retry_after(mut response: Response, retry_after: Duration) -> Response {
+    let seconds = retry_after.as_millis().div_ceil(1000).max(1);
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        header::HeaderValue::from(seconds as u64),
+    );
+    response
+}
+
+/// Builds an error response with the English message for `key`, marked for localization
+fn public_error(status: StatusCode, key: &'static str) -> Response {
+    let mut response = (status, Locale::En.message(key)).into_response();
+    response.extensions_mut().insert(ErrorMessage(key));
+    response
+}
+
+impl From<StorageError> for AppError {
+    fn from(error: StorageError) -> Self {
+        AppError::StorageUnavailable(error.0)
+    }
+}
@@ -0,0 +1,111 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::views::Views;
+
+/// Where an error page template comes from
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateSource {
+    /// Read from a file at startup
+    Path(PathBuf),
+    /// Given directly in the configuration
+    Inline(String),
+}
+
+/// Operator-supplied error page templates, as configured in `ERROR_PAGES`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorPagesConfig {
+    pub shoulder_not_found: Option<TemplateSource>,
+    pub tombstone: Option<TemplateSource>,
+    pub maintenance: Option<TemplateSource>,
+    pub invalid_ark: Option<TemplateSource>,
+}
+
+impl ErrorPagesConfig {
+    /// Adds every configured page to `views`, reading file sources from disk
+    pub fn load_into(self, views: &mut Views) -> Result<(), String> {
+        let pages = [
+            ("error/shoulder_not_found.html", self.shoulder_not_found),
+            ("error/tombstone.html", self.tombstone),
+            ("error/maintenance.html", self.maintenance),
+            ("error/invalid_ark.html", self.invalid_ark),
+        ];
+
+        for (name, source) in pages {
+            let source = match source {
+                None => continue,
+                Some(TemplateSource::Inline(template)) => template,
+                Some(TemplateSource::Path(path)) => std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read error page {}: {}", path.display(), e))?,
+            };
+            views.add_template(name, source)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The template for an error, identified by its message catalog key
+pub fn template_name(key: &str) -> Option<&'static str> {
+    match key {
+        "shoulder_not_found" => Some("error/shoulder_not_found.html"),
+        "tombstone" => Some("error/tombstone.html"),
+        "maintenance" => Some("error/maintenance.html"),
+        "invalid_ark" | "naan_mismatch" => Some("error/invalid_ark.html"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+
+    #[test]
+    fn test_load_inline_and_file_pages() {
+        let path = std::env::temp_dir().join(format!("ark-error-page-{}.html", std::process::id()));
+        std::fs::write(&path, "<p>{{ message }}</p>").unwrap();
+
+        let config: ErrorPagesConfig = serde_json::from_value(serde_json::json!({
+            "shoulder_not_found": {"inline": "<h1>{{ ark }}</h1>"},
+            "invalid_ark": {"path": path},
+        }))
+        .unwrap();
+        let mut views = Views::default();
+        let loaded = config.load_into(&mut views);
+        std::fs::remove_file(&path).unwrap();
+        loaded.unwrap();
+
+        assert_eq!(
+            views
+                .render(
+                    template_name("shoulder_not_found").unwrap(),
+                    context! { ark => "ark:12345/<b>" }
+                )
+                .unwrap(),
+            "<h1>ark:12345&#x2f;&lt;b&gt;</h1>"
+        );
+        // NAAN mismatches share the invalid ARK page
+        assert_eq!(
+            views
+                .render(
+                    template_name("naan_mismatch").unwrap(),
+                    context! { message => "bad" }
+                )
+                .unwrap(),
+            "<p>bad</p>"
+        );
+        assert!(!views.has(template_name("tombstone").unwrap()));
+    }
+
+    #[test]
+    fn test_missing_file_is_an_error() {
+        let config = ErrorPagesConfig {
+            tombstone: Some(TemplateSource::Path("/nonexistent/410.html".into())),
+            ..Default::default()
+        };
+        assert!(config.load_into(&mut Views::default()).is_err());
+    }
+}
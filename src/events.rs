@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::RwLock;
+
+/// Number of events kept when `EVENT_LOG_CAPACITY` is not set
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 100_000;
+
+/// What happened to an ARK, named after the ResourceSync change types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArkChange {
+    Created,
+    Updated,
+    /// The ARK was tombstoned
+    Deleted,
+}
+
+impl ArkChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ArkChange::Created => "created",
+            ArkChange::Updated => "updated",
+            ArkChange::Deleted => "deleted",
+        }
+    }
+}
+
+/// A single change to an ARK
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ArkEvent {
+    pub ark: String,
+    pub change: ArkChange,
+    pub at: DateTime<Utc>,
+}
+
+/// Chronological log of ARK changes, holding at most `capacity` events
+///
+/// Once full, the oldest events are dropped; [`EventLog::complete_since`] tells readers from
+/// when on the log is still complete.
+#[derive(Debug)]
+pub struct EventLog {
+    events: RwLock<VecDeque<ArkEvent>>,
+    capacity: usize,
+    /// Time of the newest dropped event, or when the log was created if none was dropped
+    complete_since: RwLock<DateTime<Utc>>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: RwLock::new(VecDeque::new()),
+            capacity,
+            complete_since: RwLock::new(Utc::now()),
+        }
+    }
+
+    /// Records that `ark` changed now
+    pub fn record(&self, ark: String, change: ArkChange) {
+        self.push(ArkEvent {
+            ark,
+            change,
+            at: Utc::now(),
+        });
+    }
+
+    /// Appends an event, dropping the oldest ones if the log is full
+    pub fn push(&self, event: ArkEvent) {
+        let mut events = self.events.write().expect("event log lock poisoned");
+        events.push_back(event);
+        while events.len() > self.capacity {
+            if let Some(dropped) = events.pop_front() {
+                *self
+                    .complete_since
+                    .write()
+                    .expect("event log lock poisoned") = dropped.at;
+            }
+        }
+    }
+
+    /// Events with `from <= at <= until`, oldest first
+    pub fn between(
+        &self,
+        from: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Vec<ArkEvent> {
+        self.events
+            .read()
+            .expect("event log lock poisoned")
+            .iter()
+            .filter(|event| from.is_none_or(|from| event.at >= from))
+            .filter(|event| until.is_none_or(|until| event.at <= until))
+            .cloned()
+            .collect()
+    }
+
+    /// The earliest time from which every change is still in the log
+    pub fn complete_since(&self) -> DateTime<Utc> {
+        *self.complete_since.read().expect("event log lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(ark: &str, at: &str) -> ArkEvent {
+        ArkEvent {
+            ark: ark.to_string(),
+            change: ArkChange::Created,
+            at: at.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_between_and_capacity() {
+        let log = EventLog::new(2);
+        log.push(event("ark:12345/x6a", "2025-01-01T00:00:00Z"));
+        log.push(event("ark:12345/x6b", "2025-01-02T00:00:00Z"));
+        log.push(event("ark:12345/x6c", "2025-01-03T00:00:00Z"));
+
+        // The oldest event was dropped, so the log is only complete from its time on
+        assert_eq!(
+            log.complete_since(),
+            "2025-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        let arks = |events: Vec<ArkEvent>| -> Vec<String> {
+            events.into_iter().map(|event| event.ark).collect()
+        };
+        assert_eq!(
+            arks(log.between(None, None)),
+            vec!["ark:12345/x6b", "ark:12345/x6c"]
+        );
+        assert_eq!(
+            arks(log.between(
+                Some("2025-01-02T00:00:00Z".parse().unwrap()),
+                Some("2025-01-02T12:00:00Z".parse().unwrap())
+            )),
+            vec!["ark:12345/x6b"]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::SecondsFormat;
+use serde::Deserialize;
+
+use crate::ledger::LedgerEntry;
+
+/// Ledger entries read from the store at a time while exporting
+pub const EXPORT_PAGE_SIZE: usize = 1000;
+
+/// Format of a mint ledger export
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `ark,shoulder,minted_at` rows after a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    /// What the export starts with, before any entry
+    pub fn header(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "ark,shoulder,minted_at\n",
+            ExportFormat::Jsonl => "",
+        }
+    }
+
+    /// An entry as one line of the export
+    pub fn line(self, entry: &LedgerEntry) -> String {
+        match self {
+            ExportFormat::Csv => {
+                // The same spelling as in JSON, e.g. `2025-03-31T09:12:44Z`
+                let minted_at = entry
+                    .minted_at
+                    .map(|at| at.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+                    .unwrap_or_default();
+                format!(
+                    "{},{},{}\n",
+                    csv_field(&entry.ark),
+                    csv_field(&entry.shoulder),
+                    minted_at
+                )
+            }
+            ExportFormat::Jsonl => {
+                serde_json::to_string(entry).expect("ledger entries serialize") + "\n"
+            }
+        }
+    }
+}
+
+/// Quotes a CSV field if it holds a comma, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_export_lines() {
+        let entry = LedgerEntry {
+            ark: "ark:12345/x6np1wh8kq".to_string(),
+            shoulder: "x6".to_string(),
+            minted_at: Some(Utc.with_ymd_and_hms(2025, 3, 31, 9, 12, 44).unwrap()),
+            metadata: Default::default(),
+        };
+        assert_eq!(
+            ExportFormat::Csv.line(&entry),
+            "ark:12345/x6np1wh8kq,x6,2025-03-31T09:12:44Z\n"
+        );
+        assert_eq!(
+            ExportFormat::Jsonl.line(&entry),
+            "{\"ark\":\"ark:12345/x6np1wh8kq\",\"shoulder\":\"x6\",\"minted_at\":\"2025-03-31T09:12:44Z\"}\n"
+        );
+
+        let unknown = LedgerEntry {
+            ark: "ark:12345/x6a,\"b\"".to_string(),
+            minted_at: None,
+            ..entry
+        };
+        assert_eq!(
+            ExportFormat::Csv.line(&unknown),
+            "\"ark:12345/x6a,\"\"b\"\"\",x6,\n"
+        );
+        assert_eq!(
+            ExportFormat::Jsonl.line(&unknown),
+            "{\"ark\":\"ark:12345/x6a,\\\"b\\\"\",\"shoulder\":\"x6\"}\n"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::callbacks::{CallbackTransport, HttpTransport};
+use crate::config::AppState;
+use crate::http_client::HttpClients;
+use crate::notify::env_parse;
+use crate::status::OverallStatus;
+
+/// Time between heartbeats unless `HEARTBEAT_INTERVAL_SECS` says otherwise
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What each heartbeat reports about the service
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HeartbeatStats {
+    pub naan: String,
+    pub status: OverallStatus,
+    pub uptime_seconds: i64,
+    pub shoulders: usize,
+    /// Identifiers minted since the service started
+    pub minted: u64,
+    /// Successful resolutions over the last 24 hours, across shoulders
+    pub resolutions_24h: u64,
+}
+
+impl HeartbeatStats {
+    pub fn collect(state: &AppState) -> Self {
+        let status = state.status_board.report(&state.naan, Vec::new());
+        Self {
+            naan: status.naan,
+            status: status.status,
+            uptime_seconds: status.uptime_seconds,
+            shoulders: state.shoulders.len(),
+            minted: state
+                .mint_stats
+                .snapshot()
+                .iter()
+                .map(|stats| stats.minted)
+                .sum(),
+            resolutions_24h: state
+                .shoulders
+                .keys()
+                .map(|shoulder| state.resolution_stats.recent(shoulder).last_24h)
+                .sum(),
+        }
+    }
+}
+
+/// Pings an uptime monitor (healthchecks.io-style) at a fixed interval, so operators are
+/// alerted when the pings stop, even if the host itself cannot report anything
+pub struct Heartbeat {
+    url: String,
+    interval: Duration,
+    transport: Arc<dyn CallbackTransport>,
+}
+
+impl Heartbeat {
+    pub fn new(url: String, interval: Duration, transport: Arc<dyn CallbackTransport>) -> Self {
+        Self {
+            url,
+            interval,
+            transport,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Posts the service's current stats to the monitor; failures are logged and otherwise
+    /// left to the monitor to notice
+    pub async fn ping(&self, state: &AppState) {
+        let stats = HeartbeatStats::collect(state);
+        let body = serde_json::to_string(&stats).expect("heartbeat stats serialize");
+        match self.transport.post(&self.url, None, body).await {
+            Ok(()) => tracing::debug!("Heartbeat sent"),
+            Err(e) => tracing::warn!(error = %e, "Heartbeat failed"),
+        }
+    }
+}
+
+/// Build the heartbeat from environment variables; `None` unless `HEARTBEAT_URL` is set
+///
+/// Optional: `HEARTBEAT_INTERVAL_SECS` (default 60), `HEARTBEAT_TIMEOUT_SECS` (default 10).
+pub fn load_heartbeat_from_env(http: &HttpClients) -> Result<Option<Heartbeat>, String> {
+    let Ok(url) = std::env::var("HEARTBEAT_URL") else {
+        return Ok(None);
+    };
+    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid HEARTBEAT_URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("HEARTBEAT_URL must use http or https: {}", url));
+    }
+    let interval = env_parse("HEARTBEAT_INTERVAL_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL);
+    if interval.is_zero() {
+        return Err("HEARTBEAT_INTERVAL_SECS must be greater than 0".to_string());
+    }
+    let timeout = Duration::from_secs(env_parse("HEARTBEAT_TIMEOUT_SECS")?.unwrap_or(10));
+
+    // A late heartbeat is worth less than the next one, so there are no retries
+    let client = http.client(timeout).without_retries();
+    Ok(Some(Heartbeat::new(
+        url,
+        interval,
+        Arc::new(HttpTransport::new(client)),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minting::mint_arks;
+    use crate::shoulder::Shoulder;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl CallbackTransport for RecordingTransport {
+        fn post(
+            &self,
+            url: &str,
+            _idempotency_key: Option<&str>,
+            body: String,
+        ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+            let body = serde_json::from_str(&body).unwrap();
+            self.sent.lock().unwrap().push((url.to_string(), body));
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_reports_stats() {
+        let state = AppState {
+            shoulders: [(
+                "x6".parse().unwrap(),
+                Shoulder {
+                    route_pattern: "https://example.org/${value}".to_string(),
+                    project_name: "Test".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            ..Default::default()
+        };
+        mint_arks(&state, "x6", 3).unwrap();
+        state.resolution_stats.record("x6");
+
+        let transport = Arc::new(RecordingTransport::default());
+        let heartbeat = Heartbeat::new(
+            "https://hc-ping.example.org/abc".to_string(),
+            DEFAULT_HEARTBEAT_INTERVAL,
+            transport.clone(),
+        );
+        heartbeat.ping(&state).await;
+
+        let sent = transport.sent.lock().unwrap();
+        let (url, body) = &sent[0];
+        assert_eq!(url, "https://hc-ping.example.org/abc");
+        assert_eq!(body["status"], "operational");
+        assert_eq!(body["shoulders"], 1);
+        assert_eq!(body["minted"], 3);
+        assert_eq!(body["resolutions_24h"], 1);
+    }
+}
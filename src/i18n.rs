@@ -0,0 +1,291 @@
+use axum::http::{HeaderMap, header};
+
+/// A language human-facing messages can be rendered in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+/// English messages, keyed by validation code or error key. Every key must exist here.
+const EN: &[(&str, &str)] = &[
+    ("E_PARSE", "Failed to parse ARK structure"),
+    (
+        "E_TOO_LONG",
+        "ARK, blade or qualifier exceeds the maximum length",
+    ),
+    (
+        "E_BETANUMERIC",
+        "Shoulder and blade must contain only betanumeric characters (0-9, b-z excluding vowels)",
+    ),
+    (
+        "E_ALPHABET",
+        "Blade contains characters outside its shoulder's alphabet",
+    ),
+    (
+        "E_NAAN_MISMATCH",
+        "NAAN {naan} does not match configured NAAN {configured}",
+    ),
+    (
+        "E_SHOULDER_UNKNOWN",
+        "Unknown shoulder. Please specify has_check_character parameter to validate unregistered shoulders.",
+    ),
+    (
+        "W_CHECKCHAR_MISMATCH",
+        "Check character validation failed. Either there's an error or this ARK has no check character.",
+    ),
+    (
+        "W_SHOULDER_UNREGISTERED",
+        "Shoulder is not registered in the system.",
+    ),
+    (
+        "W_BLADE_TOO_SHORT",
+        "Blade too short for check character validation",
+    ),
+    ("shoulder_not_found", "Shoulder not found"),
+    ("invalid_ark", "Invalid ARK format"),
+    ("uri_too_long", "ARK exceeds the maximum length"),
+    (
+        "input_too_long",
+        "Blade or qualifier exceeds the maximum length",
+    ),
+    ("naan_mismatch", "NAAN does not match"),
+    ("unauthorized", "Unauthorized"),
+    ("method_not_allowed", "Method not allowed"),
+    (
+        "blocked_redirect",
+        "The target for this ARK is misconfigured and has been reported",
+    ),
+    (
+        "mint_exhausted",
+        "No unused identifier could be minted on this shoulder; its blade length is too short",
+    ),
+    ("tombstone", "This ARK has been withdrawn"),
+    (
+        "storage_unavailable",
+        "Storage is temporarily unavailable, please retry later",
+    ),
+    ("rate_limited", "Too many requests, please retry later"),
+    (
+        "overloaded",
+        "The service is busy minting, please retry later",
+    ),
+    (
+        "mint_quota_exceeded",
+        "This shoulder's mint quota is used up, please retry later",
+    ),
+    (
+        "csrf_rejected",
+        "Missing or invalid CSRF token for this admin UI token",
+    ),
+    (
+        "deadline_exceeded",
+        "The request could not be answered in time, please retry later",
+    ),
+    (
+        "read_only",
+        "The service is read-only for maintenance: ARKs resolve and validate, but cannot be minted or changed until it ends",
+    ),
+];
+
+const FR: &[(&str, &str)] = &[
+    ("E_PARSE", "Impossible d'analyser la structure de l'ARK"),
+    (
+        "E_TOO_LONG",
+        "L'ARK, l'identifiant ou le qualificatif dépasse la longueur maximale",
+    ),
+    (
+        "E_BETANUMERIC",
+        "Le préfixe et l'identifiant ne doivent contenir que des caractères bêtanumériques (0-9, b-z sans les voyelles)",
+    ),
+    (
+        "E_ALPHABET",
+        "L'identifiant contient des caractères hors de l'alphabet de son préfixe",
+    ),
+    (
+        "E_NAAN_MISMATCH",
+        "Le NAAN {naan} ne correspond pas au NAAN configuré {configured}",
+    ),
+    (
+        "E_SHOULDER_UNKNOWN",
+        "Préfixe inconnu. Veuillez préciser le paramètre has_check_character pour valider les préfixes non enregistrés.",
+    ),
+    (
+        "W_CHECKCHAR_MISMATCH",
+        "Le caractère de contrôle est incorrect. Il s'agit d'une erreur, ou cet ARK n'a pas de caractère de contrôle.",
+    ),
+    (
+        "W_SHOULDER_UNREGISTERED",
+        "Ce préfixe n'est pas enregistré dans le système.",
+    ),
+    (
+        "W_BLADE_TOO_SHORT",
+        "Identifiant trop court pour vérifier le caractère de contrôle",
+    ),
+    ("shoulder_not_found", "Préfixe introuvable"),
+    ("invalid_ark", "Format d'ARK invalide"),
+    ("uri_too_long", "L'ARK dépasse la longueur maximale"),
+    (
+        "input_too_long",
+        "L'identifiant ou le qualificatif dépasse la longueur maximale",
+    ),
+    ("naan_mismatch", "Le NAAN ne correspond pas"),
+    ("unauthorized", "Non autorisé"),
+    ("method_not_allowed", "Méthode non autorisée"),
+    (
+        "blocked_redirect",
+        "La cible de cet ARK est mal configurée et a été signalée",
+    ),
+    (
+        "mint_exhausted",
+        "Aucun identifiant inutilisé n'a pu être créé sur ce préfixe ; son identifiant est trop court",
+    ),
+    ("tombstone", "Cet ARK a été retiré"),
+    (
+        "storage_unavailable",
+        "Le stockage est temporairement indisponible, veuillez réessayer plus tard",
+    ),
+    (
+        "rate_limited",
+        "Trop de requêtes, veuillez réessayer plus tard",
+    ),
+    (
+        "overloaded",
+        "Le service est occupé à créer des identifiants, veuillez réessayer plus tard",
+    ),
+    (
+        "mint_quota_exceeded",
+        "Le quota de création de ce préfixe est épuisé, veuillez réessayer plus tard",
+    ),
+    (
+        "csrf_rejected",
+        "Jeton CSRF manquant ou invalide pour ce jeton d'administration",
+    ),
+    (
+        "deadline_exceeded",
+        "La requête n'a pas pu aboutir à temps, veuillez réessayer plus tard",
+    ),
+    (
+        "read_only",
+        "Le service est en lecture seule pour maintenance : les ARK sont résolus et validés, mais ne peuvent être créés ni modifiés avant la fin de celle-ci",
+    ),
+];
+
+impl Locale {
+    /// The BCP 47 language tag, as sent in `Content-Language`
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Fr => FR,
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" | "*" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            _ => None,
+        }
+    }
+
+    /// Picks the supported locale the client prefers from an `Accept-Language` value
+    ///
+    /// Languages are matched on their primary subtag (`fr-CA` selects French). The highest
+    /// quality wins, ties go to the language listed first, and English is the fallback.
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut best: Option<(Locale, f32)> = None;
+
+        for range in accept_language.split(',') {
+            let mut params = range.split(';');
+            let tag = params.next().unwrap_or_default().trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if let Some(locale) = Self::from_tag(tag)
+                && quality > 0.0
+                && best.is_none_or(|(_, best_quality)| quality > best_quality)
+            {
+                best = Some((locale, quality));
+            }
+        }
+
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+
+    /// Negotiates the locale from a request's `Accept-Language` header
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(Self::negotiate)
+            .unwrap_or_default()
+    }
+
+    /// Looks up a message, falling back to English and then to the key itself
+    pub fn message(self, key: &'static str) -> &'static str {
+        let lookup = |catalog: &'static [(&'static str, &'static str)]| {
+            catalog
+                .iter()
+                .find(|(candidate, _)| *candidate == key)
+                .map(|(_, text)| *text)
+        };
+        lookup(self.catalog()).or_else(|| lookup(EN)).unwrap_or(key)
+    }
+
+    /// Looks up a message and fills in its `{name}` placeholders
+    pub fn format(self, key: &'static str, args: &[(&str, &str)]) -> String {
+        args.iter()
+            .fold(self.message(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(Locale::negotiate("fr"), Locale::Fr);
+        assert_eq!(Locale::negotiate("fr-CA,fr;q=0.9,en;q=0.8"), Locale::Fr);
+        assert_eq!(Locale::negotiate("en-GB,fr;q=0.5"), Locale::En);
+        assert_eq!(Locale::negotiate("de,fr;q=0.7,en;q=0.3"), Locale::Fr);
+        assert_eq!(Locale::negotiate("de"), Locale::En);
+        assert_eq!(Locale::negotiate("fr;q=0"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+    }
+
+    #[test]
+    fn test_every_message_is_translated() {
+        for (key, _) in EN {
+            let translated = FR.iter().find(|(candidate, _)| candidate == key);
+            assert!(translated.is_some(), "missing French message for {}", key);
+        }
+        assert_eq!(EN.len(), FR.len());
+    }
+
+    #[test]
+    fn test_format_fills_placeholders() {
+        let text = Locale::Fr.format(
+            "E_NAAN_MISMATCH",
+            &[("naan", "99999"), ("configured", "12345")],
+        );
+        assert_eq!(
+            text,
+            "Le NAAN 99999 ne correspond pas au NAAN configuré 12345"
+        );
+        assert_eq!(Locale::Fr.message("no_such_key"), "no_such_key");
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::bindings::StorageError;
+
+/// How long a mint request's idempotency key is remembered
+pub const IDEMPOTENCY_KEY_RETENTION: Duration = Duration::from_secs(24 * 3600);
+/// How often receipts past their retention are removed
+pub const RECEIPT_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Longest idempotency key accepted
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// The ARKs a mint request with an idempotency key was answered with, so a retry gets the same
+/// ones
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MintReceipt {
+    /// The client's idempotency key, scoped to the key holder by [`receipt_key`]
+    pub key: String,
+    pub shoulder: String,
+    /// Count the request asked for, before capping
+    pub count: usize,
+    /// Metadata the request asked to keep with the ARKs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    /// Whether the request asked for each ARK in detail
+    #[serde(default)]
+    pub detail: bool,
+    pub arks: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MintReceipt {
+    /// Whether the receipt is still within [`IDEMPOTENCY_KEY_RETENTION`] at `now`
+    pub fn is_current(&self, now: DateTime<Utc>) -> bool {
+        now - self.created_at < retention()
+    }
+}
+
+/// Checks a client-chosen idempotency key: 1 to 255 visible ASCII characters
+pub fn validate_idempotency_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(format!(
+            "Idempotency key must be 1 to {} characters long",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        ));
+    }
+    if !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err("Idempotency key must be visible ASCII characters".to_string());
+    }
+    Ok(())
+}
+
+/// The key a receipt is stored under: the client's idempotency key, then the key holder
+///
+/// Each holder has keys of their own, so one holder's receipts are never replayed to another.
+/// Idempotency keys have no spaces, so the first one ends the client's key.
+pub fn receipt_key(holder: &str, key: &str) -> String {
+    format!("{} {}", key, holder)
+}
+
+/// Earliest creation time of a receipt that is still current at `now`
+pub fn retention_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - retention()
+}
+
+fn retention() -> chrono::Duration {
+    chrono::Duration::from_std(IDEMPOTENCY_KEY_RETENTION).expect("retention fits")
+}
+
+/// Backend that keeps mint receipts by idempotency key, so retried mints are answered alike
+/// across restarts and instances
+pub trait ReceiptStore: Send + Sync {
+    /// Saves the receipt unless one with the same key exists, which is returned instead
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError>;
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError>;
+    /// Removes receipts created before `before`, returning how many
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError>;
+}
+
+/// Receipts kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryReceiptStore {
+    receipts: RwLock<HashMap<String, MintReceipt>>,
+}
+
+impl ReceiptStore for MemoryReceiptStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        let mut receipts = self.receipts.write().expect("receipts lock poisoned");
+        match receipts.get(&receipt.key) {
+            Some(existing) => Ok(Some(existing.clone())),
+            None => {
+                receipts.insert(receipt.key.clone(), receipt.clone());
+                Ok(None)
+            }
+        }
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        Ok(self
+            .receipts
+            .read()
+            .expect("receipts lock poisoned")
+            .get(key)
+            .cloned())
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        let mut receipts = self.receipts.write().expect("receipts lock poisoned");
+        let count = receipts.len();
+        receipts.retain(|_, receipt| receipt.created_at >= before);
+        Ok(count - receipts.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_idempotency_key() {
+        assert!(validate_idempotency_key("ingest-2025-01-15/batch-7").is_ok());
+        for invalid in ["", "with space", "caf\u{e9}", &"k".repeat(256)] {
+            assert!(validate_idempotency_key(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_memory_store_keeps_first_receipt() {
+        let store = MemoryReceiptStore::default();
+        let receipt = |arks: &[&str]| MintReceipt {
+            key: "batch-7".to_string(),
+            shoulder: "x6".to_string(),
+            count: 1,
+            metadata: BTreeMap::new(),
+            detail: false,
+            arks: arks.iter().map(|ark| ark.to_string()).collect(),
+            created_at: Utc::now(),
+        };
+        let first = receipt(&["ark:12345/x6bbbb"]);
+        assert_eq!(store.save_receipt(&first).unwrap(), None);
+        assert_eq!(
+            store.save_receipt(&receipt(&["ark:12345/x6cccc"])).unwrap(),
+            Some(first.clone())
+        );
+        assert_eq!(store.receipt("batch-7").unwrap(), Some(first.clone()));
+        assert!(first.is_current(Utc::now()));
+
+        assert_eq!(store.prune_receipts(first.created_at).unwrap(), 0);
+        let later = first.created_at + chrono::Duration::seconds(1);
+        assert_eq!(store.prune_receipts(later).unwrap(), 1);
+        assert_eq!(store.receipt("batch-7").unwrap(), None);
+    }
+}
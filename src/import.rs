@@ -0,0 +1,233 @@
+use serde::Deserialize;
+
+/// Default cap on the size of an import request body
+pub const DEFAULT_MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Most rejected identifiers listed in an import report; the rest are only counted
+pub const MAX_IMPORT_ERRORS: usize = 100;
+
+/// Identifiers recorded in the ledger at once while importing
+pub const IMPORT_CHUNK_SIZE: usize = 1000;
+
+/// Kind of export an import reads
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// A NOID minter database dumped as text, e.g. with `db_dump -p`
+    Noid,
+    /// An EZID CSV download, with the identifier in the `_id` column
+    Ezid,
+}
+
+/// An identifier found in an export, with the line it starts on
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedIdentifier {
+    pub line: usize,
+    pub identifier: String,
+}
+
+/// The identifiers of an export, as ARKs, in the order they appear
+///
+/// Other identifiers (DOIs in an EZID download, for instance) are returned as they are, to be
+/// rejected by validation like any malformed ARK.
+pub fn identifiers(
+    format: ImportFormat,
+    text: &str,
+    naan: &str,
+) -> Result<Vec<ImportedIdentifier>, String> {
+    match format {
+        ImportFormat::Noid => Ok(noid_identifiers(text, naan)),
+        ImportFormat::Ezid => ezid_identifiers(text),
+    }
+}
+
+/// Identifiers with a circulation record (`<id>\t:/c`), which NOID keeps for every identifier it
+/// issued or queued
+///
+/// Works on `db_dump -p` output, where keys and values are on lines of their own, as well as on
+/// dumps with one tab-separated record per line.
+fn noid_identifiers(text: &str, naan: &str) -> Vec<ImportedIdentifier> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let record = unescape_db_dump(line.strip_prefix(' ').unwrap_or(line));
+            let (id, _) = record.split_once("\t:/c")?;
+            let id = id.trim();
+            // Minter settings are kept under `:/` keys of their own
+            if id.is_empty() || id.starts_with(":/") {
+                return None;
+            }
+            Some(ImportedIdentifier {
+                line: index + 1,
+                identifier: noid_ark(id, naan),
+            })
+        })
+        .collect()
+}
+
+/// NOID identifiers are `NAAN/shoulder+blade`, or just `shoulder+blade` for minters set up
+/// without a NAAN
+fn noid_ark(id: &str, naan: &str) -> String {
+    if id.starts_with("ark:") {
+        id.to_string()
+    } else if id.contains('/') {
+        format!("ark:{}", id)
+    } else {
+        format!("ark:{}/{}", naan, id)
+    }
+}
+
+/// Reverses `db_dump -p` escaping: `\\` for a backslash, `\hh` for any other unprintable byte
+fn unescape_db_dump(line: &str) -> String {
+    if !line.contains('\\') {
+        return line.to_string();
+    }
+    let mut bytes = Vec::with_capacity(line.len());
+    let mut rest = line.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match rest {
+            [b'\\', tail @ ..] => {
+                bytes.push(b'\\');
+                rest = tail;
+            }
+            [high, low, tail @ ..] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                let hex = [*high, *low];
+                let hex = std::str::from_utf8(&hex).expect("hex digits are ASCII");
+                bytes.push(u8::from_str_radix(hex, 16).expect("two hex digits"));
+                rest = tail;
+            }
+            _ => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Identifiers in the `_id` column (or `id`, `identifier`) of an EZID download
+fn ezid_identifiers(text: &str) -> Result<Vec<ImportedIdentifier>, String> {
+    let mut records = csv_records(text).into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let column = ["_id", "id", "identifier"]
+        .iter()
+        .find_map(|name| {
+            header
+                .iter()
+                .position(|field| field.trim().eq_ignore_ascii_case(name))
+        })
+        .ok_or("EZID export has no _id column")?;
+
+    Ok(records
+        .filter_map(|(line, fields)| {
+            let identifier = fields.get(column)?.trim();
+            (!identifier.is_empty()).then(|| ImportedIdentifier {
+                line,
+                identifier: identifier.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// CSV records with the line each starts on; quoted fields may hold commas, quotes (doubled)
+/// and line breaks
+fn csv_records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|field| !field.is_empty()) {
+                    records.push((start, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    fields.push(field);
+    if fields.iter().any(|field| !field.is_empty()) {
+        records.push((start, fields));
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arks(found: Vec<ImportedIdentifier>) -> Vec<(usize, String)> {
+        found
+            .into_iter()
+            .map(|found| (found.line, found.identifier))
+            .collect()
+    }
+
+    #[test]
+    fn test_noid_identifiers() {
+        let dump = "VERSION=3\nformat=print\ntype=btree\nHEADER=END\n \
+                    :/template\n x6{eedk}\n \
+                    12345/x6np1wh8kq\\09:/c\n i|20190301120000|noid|0\n \
+                    x6k4t2\\09:/c\n q|20190302120000|noid|0\n \
+                    12345/x6np1wh8kq\\09where\n https://example.org/a\nDATA=END\n\
+                    ark:/12345/x6b3\t:/c\ti|20190303120000|noid|0\n";
+        assert_eq!(
+            arks(identifiers(ImportFormat::Noid, dump, "12345").unwrap()),
+            vec![
+                (7, "ark:12345/x6np1wh8kq".to_string()),
+                (9, "ark:12345/x6k4t2".to_string()),
+                (14, "ark:/12345/x6b3".to_string()),
+            ]
+        );
+        assert_eq!(unescape_db_dump("a\\\\b\\7e\\z"), "a\\b~\\z");
+    }
+
+    #[test]
+    fn test_ezid_identifiers() {
+        let export = "_id,_target,dc.title\r\n\
+                      ark:/12345/x6np1wh8kq,https://example.org/a,\"Report, \"\"final\"\"\"\r\n\
+                      doi:10.5072/FK2,https://example.org/b,\"Two\nlines\"\r\n\
+                      ,https://example.org/c,\r\n\
+                      \r\n\
+                      ark:/12345/x6k4t2,https://example.org/d,";
+        assert_eq!(
+            arks(identifiers(ImportFormat::Ezid, export, "12345").unwrap()),
+            vec![
+                (2, "ark:/12345/x6np1wh8kq".to_string()),
+                (3, "doi:10.5072/FK2".to_string()),
+                (7, "ark:/12345/x6k4t2".to_string()),
+            ]
+        );
+        assert!(identifiers(ImportFormat::Ezid, "target\nx\n", "12345").is_err());
+        assert!(
+            identifiers(ImportFormat::Ezid, "", "12345")
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
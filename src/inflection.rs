@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use crate::deadline::Deadline;
+use crate::http_client::{HttpClient, HttpClients};
+use crate::notify::env_parse;
+
+/// How long an upstream Name Mapping Authority gets to answer an inflection
+pub const DEFAULT_INFLECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest upstream answer relayed to the client; bigger ones count as failures
+const MAX_UPSTREAM_BYTES: usize = 1024 * 1024;
+
+/// An inflection answer relayed from the upstream Name Mapping Authority
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpstreamRecord {
+    pub content_type: String,
+    pub body: String,
+}
+
+/// Fetches inflection answers (`?`, `??`, `?info`) for shoulders with `forward_inflections`
+#[derive(Debug)]
+pub struct InflectionForwarder {
+    client: HttpClient,
+}
+
+impl Default for InflectionForwarder {
+    fn default() -> Self {
+        Self::new(HttpClients::default().client(DEFAULT_INFLECTION_TIMEOUT))
+    }
+}
+
+impl InflectionForwarder {
+    /// Answers are awaited by a client, so requests are not retried; the local record is
+    /// served instead
+    pub fn new(client: HttpClient) -> Self {
+        Self {
+            client: client.without_retries(),
+        }
+    }
+
+    /// Requests `url`, failing on errors, timeouts, non-2xx answers and oversized bodies
+    ///
+    /// With a `deadline`, the upstream gets no longer than the time left before it.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        deadline: Option<Deadline>,
+    ) -> Result<UpstreamRecord, String> {
+        let mut request = self.client.get(url);
+        if let Some(deadline) = deadline {
+            if deadline.is_expired() {
+                return Err("request deadline exceeded".to_string());
+            }
+            request = request.timeout(deadline.remaining().min(self.client.timeout()));
+        }
+        let response = self
+            .client
+            .send(request)
+            .await?
+            .error_for_status()
+            .map_err(|e| e.to_string())?;
+        if response
+            .content_length()
+            .is_some_and(|length| length > MAX_UPSTREAM_BYTES as u64)
+        {
+            return Err("upstream answer is too large".to_string());
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("text/plain; charset=utf-8")
+            .to_string();
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        if body.len() > MAX_UPSTREAM_BYTES {
+            return Err("upstream answer is too large".to_string());
+        }
+        Ok(UpstreamRecord { content_type, body })
+    }
+}
+
+/// Local answer to an inflection: an ERC record (who, what, when, where) as served by N2T
+pub fn erc_record(who: Option<&str>, what: &str, target: Option<&str>) -> String {
+    const UNKNOWN: &str = "(:unkn) unknown";
+    let what = if what.is_empty() { UNKNOWN } else { what };
+    format!(
+        "erc:\nwho: {}\nwhat: {}\nwhen: {}\nwhere: {}\n",
+        who.unwrap_or(UNKNOWN),
+        what,
+        UNKNOWN,
+        target.unwrap_or(UNKNOWN)
+    )
+}
+
+/// How inflections on ARKs of unregistered shoulders are answered
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum UnregisteredInflections {
+    /// `404 Not Found`, like any other request for the ARK
+    #[default]
+    NotFound,
+    /// A metadata stub with what the ARK's structure tells, marked as an unknown namespace
+    Stub,
+}
+
+impl std::str::FromStr for UnregisteredInflections {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "not_found" => Ok(Self::NotFound),
+            "stub" => Ok(Self::Stub),
+            other => Err(format!("expected not_found or stub, got {}", other)),
+        }
+    }
+}
+
+/// Read `UNREGISTERED_INFLECTIONS` (`not_found`, the default, or `stub`)
+pub fn load_unregistered_inflections_from_env() -> Result<UnregisteredInflections, String> {
+    Ok(env_parse("UNREGISTERED_INFLECTIONS")?.unwrap_or_default())
+}
+
+/// Build the inflection forwarder from environment variables
+///
+/// Optional: `INFLECTION_TIMEOUT_SECS` (default 5).
+pub fn load_inflection_forwarder_from_env(
+    http: &HttpClients,
+) -> Result<InflectionForwarder, String> {
+    let timeout = env_parse("INFLECTION_TIMEOUT_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_INFLECTION_TIMEOUT);
+    if timeout.is_zero() {
+        return Err("INFLECTION_TIMEOUT_SECS must be greater than 0".to_string());
+    }
+    Ok(InflectionForwarder::new(http.client(timeout)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erc_record() {
+        assert_eq!(
+            erc_record(
+                Some("Example Library"),
+                "Digital Collections",
+                Some("https://example.org/x6abc")
+            ),
+            "erc:\nwho: Example Library\nwhat: Digital Collections\nwhen: (:unkn) unknown\nwhere: https://example.org/x6abc\n"
+        );
+        assert_eq!(
+            erc_record(None, "", None),
+            "erc:\nwho: (:unkn) unknown\nwhat: (:unkn) unknown\nwhen: (:unkn) unknown\nwhere: (:unkn) unknown\n"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::ark::Ark;
+use crate::bindings::{Binding, Bindings};
+use crate::notify::env_parse;
+
+/// Time between attempts to replay the journal unless `MINT_JOURNAL_RECONCILE_SECS` says otherwise
+pub const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One journaled binding, as a line of JSON
+#[derive(Debug, Deserialize, Serialize)]
+struct JournalEntry {
+    ark: String,
+    #[serde(flatten)]
+    binding: Binding,
+}
+
+/// A journaled ARK that the store had meanwhile bound to another target
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalConflict {
+    pub ark: String,
+    pub journaled: String,
+    pub stored: String,
+}
+
+/// Outcome of replaying the journal into the binding store
+#[derive(Debug, Default, PartialEq)]
+pub struct ReconcileReport {
+    pub applied: usize,
+    pub conflicts: Vec<JournalConflict>,
+    /// Entries left for the next attempt because the store failed again
+    pub remaining: usize,
+}
+
+/// Bindings made while the binding store was down, appended to a local file and replayed into
+/// the store once it answers again
+///
+/// Each entry is synced to disk before the mint is acknowledged, so a crash loses nothing that
+/// a client was told about.
+#[derive(Debug)]
+pub struct MintJournal {
+    path: PathBuf,
+    reconcile_interval: Duration,
+    pending: AtomicUsize,
+    /// Held while appending or replaying, which both rewrite the file
+    lock: Mutex<()>,
+}
+
+impl MintJournal {
+    /// Opens the journal at `path`, picking up entries left over from a previous run
+    pub fn open(path: impl Into<PathBuf>, reconcile_interval: Duration) -> io::Result<Self> {
+        let path = path.into();
+        let pending = read_entries(&path)?.len();
+        Ok(Self {
+            path,
+            reconcile_interval,
+            pending: AtomicUsize::new(pending),
+            lock: Mutex::new(()),
+        })
+    }
+
+    pub fn reconcile_interval(&self) -> Duration {
+        self.reconcile_interval
+    }
+
+    /// Entries waiting to be replayed into the store
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Appends bindings to the journal and syncs them to disk
+    pub fn append(&self, bindings: &[(Ark, Binding)]) -> io::Result<()> {
+        let mut lines = String::new();
+        for (ark, binding) in bindings {
+            let entry = JournalEntry {
+                ark: ark.original.clone(),
+                binding: binding.clone(),
+            };
+            lines.push_str(&serde_json::to_string(&entry).expect("journal entry serializes"));
+            lines.push('\n');
+        }
+
+        let _guard = self.lock.lock().expect("journal lock poisoned");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+        self.pending.fetch_add(bindings.len(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Replays journaled bindings into the store, oldest first
+    ///
+    /// Stops at the first store failure, keeping the rest for the next attempt. An ARK the store
+    /// has meanwhile bound to another target keeps its stored binding; the journaled one is
+    /// moved to `{path}.conflicts` for an operator to review.
+    pub fn reconcile(&self, bindings: &Bindings) -> io::Result<ReconcileReport> {
+        let _guard = self.lock.lock().expect("journal lock poisoned");
+        let entries = read_entries(&self.path)?;
+        if entries.is_empty() {
+            return Ok(ReconcileReport::default());
+        }
+
+        let mut report = ReconcileReport::default();
+        let mut conflicts = Vec::new();
+        let mut remaining = Vec::new();
+        let mut entries = entries.into_iter();
+        for entry in entries.by_ref() {
+            let Ok(ark) = Ark::try_from(entry.ark.as_str()) else {
+                tracing::warn!(ark = %entry.ark, "Dropping unparseable journal entry");
+                continue;
+            };
+            // Bound only if the ARK is still unbound, so a binding made since is never replaced
+            let result = match bindings.bind_new(&ark, &entry.binding) {
+                Ok(None) => Ok(()),
+                Ok(Some(stored)) if stored.target == entry.binding.target => Ok(()),
+                Ok(Some(stored)) => {
+                    tracing::error!(
+                        ark = %entry.ark,
+                        journaled = %entry.binding.target,
+                        stored = %stored.target,
+                        "Journaled binding conflicts with stored binding"
+                    );
+                    report.conflicts.push(JournalConflict {
+                        ark: entry.ark.clone(),
+                        journaled: entry.binding.target.clone(),
+                        stored: stored.target,
+                    });
+                    conflicts.push(entry);
+                    continue;
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => report.applied += 1,
+                Err(_) => {
+                    remaining.push(entry);
+                    break;
+                }
+            }
+        }
+        remaining.extend(entries);
+
+        if !conflicts.is_empty() {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(conflicts_path(&self.path))?;
+            file.write_all(to_lines(&conflicts).as_bytes())?;
+            file.sync_data()?;
+        }
+        if remaining.is_empty() {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            // Replace the file in one step, so a crash leaves either the old or the new journal
+            let tmp = self.path.with_extension("tmp");
+            std::fs::write(&tmp, to_lines(&remaining))?;
+            std::fs::rename(&tmp, &self.path)?;
+        }
+        report.remaining = remaining.len();
+        self.pending.store(remaining.len(), Ordering::Relaxed);
+
+        if report.applied > 0 || !report.conflicts.is_empty() {
+            tracing::info!(
+                applied = report.applied,
+                conflicts = report.conflicts.len(),
+                remaining = report.remaining,
+                "Mint journal replayed"
+            );
+        }
+        Ok(report)
+    }
+}
+
+fn conflicts_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".conflicts");
+    PathBuf::from(name)
+}
+
+fn to_lines(entries: &[JournalEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| serde_json::to_string(entry).expect("journal entry serializes") + "\n")
+        .collect()
+}
+
+/// Reads the journal; a missing file is an empty journal
+///
+/// A torn last line, left by a crash mid-write, is skipped: its mint was never acknowledged.
+fn read_entries(path: &Path) -> io::Result<Vec<JournalEntry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!(error = %e, "Skipping unreadable mint journal line");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Open the mint journal from environment variables; `None` unless `MINT_JOURNAL_PATH` is set
+///
+/// Optional: `MINT_JOURNAL_RECONCILE_SECS` (default 30).
+pub fn load_mint_journal_from_env() -> Result<Option<MintJournal>, String> {
+    let Ok(path) = std::env::var("MINT_JOURNAL_PATH") else {
+        return Ok(None);
+    };
+    let interval = env_parse("MINT_JOURNAL_RECONCILE_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RECONCILE_INTERVAL);
+    if interval.is_zero() {
+        return Err("MINT_JOURNAL_RECONCILE_SECS must be greater than 0".to_string());
+    }
+    MintJournal::open(&path, interval)
+        .map(Some)
+        .map_err(|e| format!("Failed to open MINT_JOURNAL_PATH {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+    use crate::bindings::{BindingPage, BindingStore, MemoryBindingStore, StorageError};
+    use crate::testing::SwitchableBindingStore;
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    fn binding(target: &str) -> Binding {
+        Binding {
+            target: target.to_string(),
+            metadata: Default::default(),
+            bound_at: Utc::now(),
+            lifecycle: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_reconciles_journal_into_store() {
+        let path = std::env::temp_dir().join(format!("ark-journal-{}.jsonl", std::process::id()));
+        let store = Arc::new(SwitchableBindingStore::default());
+        let bindings = Bindings::new(store.clone());
+        let (a, b, c) = (
+            parse_ark("ark:12345/x6aaaa").unwrap(),
+            parse_ark("ark:12345/x6bbbb").unwrap(),
+            parse_ark("ark:12345/x6cccc").unwrap(),
+        );
+        bindings
+            .bind_all([(c.clone(), binding("https://example.org/elsewhere"))])
+            .unwrap();
+
+        let journal = MintJournal::open(&path, DEFAULT_RECONCILE_INTERVAL).unwrap();
+        journal
+            .append(&[
+                (a.clone(), binding("https://example.org/a")),
+                (b.clone(), binding("https://example.org/b")),
+                (c.clone(), binding("https://example.org/c")),
+            ])
+            .unwrap();
+        assert_eq!(journal.pending(), 3);
+
+        // Nothing is lost while the store is still down, and a reopened journal sees it all
+        store.set_down(true);
+        let report = journal.reconcile(&bindings).unwrap();
+        assert_eq!(report.remaining, 3);
+        let journal = MintJournal::open(&path, DEFAULT_RECONCILE_INTERVAL).unwrap();
+        assert_eq!(journal.pending(), 3);
+
+        store.set_down(false);
+        let report = journal.reconcile(&bindings).unwrap();
+        let conflicts = std::fs::read_to_string(conflicts_path(&path)).unwrap();
+        std::fs::remove_file(conflicts_path(&path)).unwrap();
+
+        assert_eq!(report.applied, 2);
+        assert_eq!(report.remaining, 0);
+        assert_eq!(
+            report.conflicts,
+            vec![JournalConflict {
+                ark: "ark:12345/x6cccc".to_string(),
+                journaled: "https://example.org/c".to_string(),
+                stored: "https://example.org/elsewhere".to_string(),
+            }]
+        );
+        assert!(conflicts.contains("https://example.org/c"));
+        assert_eq!(journal.pending(), 0);
+        assert!(!path.exists());
+        assert_eq!(
+            bindings.get(&b).unwrap().unwrap().target,
+            "https://example.org/b"
+        );
+        assert_eq!(
+            bindings.get(&c).unwrap().unwrap().target,
+            "https://example.org/elsewhere"
+        );
+    }
+
+    /// Binding store that never finds a binding on lookup, as if every binding had been made
+    /// just after it was looked up
+    #[derive(Default)]
+    struct LateBindings(MemoryBindingStore);
+
+    impl BindingStore for LateBindings {
+        fn get(&self, _canonical: &str) -> Result<Option<Binding>, StorageError> {
+            Ok(None)
+        }
+
+        fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+            self.0.put_all(bindings)
+        }
+
+        fn put_new(
+            &self,
+            canonical: &str,
+            binding: &Binding,
+        ) -> Result<Option<Binding>, StorageError> {
+            self.0.put_new(canonical, binding)
+        }
+
+        fn count(&self) -> Result<usize, StorageError> {
+            self.0.count()
+        }
+
+        fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+            self.0.count_prefixed(prefix)
+        }
+
+        fn bindings(
+            &self,
+            cursor: Option<&str>,
+            limit: usize,
+        ) -> Result<BindingPage, StorageError> {
+            self.0.bindings(cursor, limit)
+        }
+    }
+
+    #[test]
+    fn test_keeps_bindings_made_while_reconciling() {
+        let path =
+            std::env::temp_dir().join(format!("ark-journal-late-{}.jsonl", std::process::id()));
+        let store = Arc::new(LateBindings::default());
+        let bindings = Bindings::new(store.clone());
+        let ark = parse_ark("ark:12345/x6dddd").unwrap();
+        store
+            .put_all(vec![(
+                ark.canonical().to_string(),
+                binding("https://example.org/live"),
+            )])
+            .unwrap();
+
+        let journal = MintJournal::open(&path, DEFAULT_RECONCILE_INTERVAL).unwrap();
+        journal
+            .append(&[(ark.clone(), binding("https://example.org/journaled"))])
+            .unwrap();
+        let report = journal.reconcile(&bindings).unwrap();
+        std::fs::remove_file(conflicts_path(&path)).unwrap();
+
+        assert_eq!(report.applied, 0);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(
+            store.0.get(ark.canonical()).unwrap().unwrap().target,
+            "https://example.org/live"
+        );
+    }
+}
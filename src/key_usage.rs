@@ -0,0 +1,316 @@
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+
+use crate::public_mint::PUBLIC_MINT_PATH;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+
+/// Hours of calls kept per key: 30 days
+const RETAINED_HOURS: i64 = 30 * 24;
+
+/// Header row of the CSV usage report
+pub const USAGE_CSV_HEADER: &str = "start,mint,validate,resolve,admin,other,total\n";
+
+/// The kind of endpoint a call was made to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Any of the mint endpoints
+    Mint,
+    Validate,
+    /// Resolution of an ARK
+    Resolve,
+    /// Anything under the admin API
+    Admin,
+    Other,
+}
+
+impl Endpoint {
+    /// The kind of endpoint serving `path`, with the admin API mounted at `admin_api_path`
+    pub fn of(path: &str, admin_api_path: Option<&str>) -> Self {
+        if admin_api_path.is_some_and(|admin| {
+            path.strip_prefix(admin)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        }) {
+            return Endpoint::Admin;
+        }
+        match path {
+            "/api/v1/mint" | "/api/v1/mint/stream" | "/api/v1/mint-and-bind" | PUBLIC_MINT_PATH => {
+                Endpoint::Mint
+            }
+            "/api/v1/validate" => Endpoint::Validate,
+            path if path.starts_with("/ark:") => Endpoint::Resolve,
+            _ => Endpoint::Other,
+        }
+    }
+}
+
+/// Calls made with a key, by kind of endpoint
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct UsageCounts {
+    pub mint: u64,
+    pub validate: u64,
+    pub resolve: u64,
+    pub admin: u64,
+    pub other: u64,
+    pub total: u64,
+}
+
+impl UsageCounts {
+    fn count(&mut self, endpoint: Endpoint) {
+        let counter = match endpoint {
+            Endpoint::Mint => &mut self.mint,
+            Endpoint::Validate => &mut self.validate,
+            Endpoint::Resolve => &mut self.resolve,
+            Endpoint::Admin => &mut self.admin,
+            Endpoint::Other => &mut self.other,
+        };
+        *counter += 1;
+        self.total += 1;
+    }
+
+    /// Adds the calls counted in `other`
+    pub fn add(&mut self, other: &UsageCounts) {
+        self.mint += other.mint;
+        self.validate += other.validate;
+        self.resolve += other.resolve;
+        self.admin += other.admin;
+        self.other += other.other;
+        self.total += other.total;
+    }
+}
+
+/// Width of the buckets of a usage report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// The last 24 hours, an hour at a time
+    #[default]
+    Hour,
+    /// The last 30 days, a day at a time
+    Day,
+}
+
+impl Granularity {
+    fn hours(self) -> i64 {
+        match self {
+            Granularity::Hour => 1,
+            Granularity::Day => 24,
+        }
+    }
+
+    /// Buckets in a report, the current one included
+    fn buckets(self) -> i64 {
+        match self {
+            Granularity::Hour => 24,
+            Granularity::Day => RETAINED_HOURS / 24,
+        }
+    }
+}
+
+/// Format of a usage report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageFormat {
+    #[default]
+    Json,
+    /// One row per bucket after a header row
+    Csv,
+}
+
+/// Calls made with a key in one bucket of a usage report
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct UsageBucket {
+    pub start: DateTime<Utc>,
+    #[serde(flatten)]
+    pub calls: UsageCounts,
+}
+
+impl UsageBucket {
+    /// The bucket as a row of the CSV report
+    pub fn csv_line(&self) -> String {
+        let calls = &self.calls;
+        format!(
+            "{},{},{},{},{},{},{}\n",
+            self.start.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            calls.mint,
+            calls.validate,
+            calls.resolve,
+            calls.admin,
+            calls.other,
+            calls.total
+        )
+    }
+}
+
+/// An API key whose calls are counted
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UsageKey {
+    /// A managed key, by id, so a rotated key and its replacement are counted apart
+    Managed(String),
+    /// A key from `ADMIN_API_KEYS`, `MINT_API_KEYS` or `RESOLUTION_API_KEYS`, by the name it is
+    /// configured with
+    Configured(String),
+}
+
+/// Calls made with each API key, counted in hourly buckets over the last 30 days
+///
+/// Counts are kept in memory by each instance, and start over when it restarts.
+#[derive(Debug, Default)]
+pub struct KeyUsage {
+    /// `(hour since the epoch, calls)` of each key, oldest first
+    keys: RwLock<HashMap<UsageKey, VecDeque<(i64, UsageCounts)>>>,
+}
+
+fn hour_of(at: DateTime<Utc>) -> i64 {
+    at.timestamp().div_euclid(SECONDS_PER_HOUR)
+}
+
+impl KeyUsage {
+    /// Counts a call to `endpoint` made now with `key`
+    pub fn record(&self, key: &UsageKey, endpoint: Endpoint) {
+        self.record_at(key, endpoint, Utc::now());
+    }
+
+    fn record_at(&self, key: &UsageKey, endpoint: Endpoint, at: DateTime<Utc>) {
+        let hour = hour_of(at);
+        let mut keys = self.keys.write().expect("key usage lock poisoned");
+        let buckets = keys.entry(key.clone()).or_default();
+        match buckets.back_mut() {
+            Some((last, calls)) if *last == hour => calls.count(endpoint),
+            _ => {
+                let mut calls = UsageCounts::default();
+                calls.count(endpoint);
+                buckets.push_back((hour, calls));
+            }
+        }
+        while buckets
+            .front()
+            .is_some_and(|(first, _)| *first <= hour - RETAINED_HOURS)
+        {
+            buckets.pop_front();
+        }
+    }
+
+    /// Calls made with `key`, oldest bucket first, including the current one and buckets
+    /// without calls
+    pub fn report(&self, key: &UsageKey, granularity: Granularity) -> Vec<UsageBucket> {
+        self.report_at(key, granularity, Utc::now())
+    }
+
+    fn report_at(
+        &self,
+        key: &UsageKey,
+        granularity: Granularity,
+        now: DateTime<Utc>,
+    ) -> Vec<UsageBucket> {
+        let width = granularity.hours();
+        let current = hour_of(now).div_euclid(width);
+        let count = granularity.buckets();
+        let mut report: Vec<UsageBucket> = (current - count + 1..=current)
+            .map(|bucket| UsageBucket {
+                start: Utc
+                    .timestamp_opt(bucket * width * SECONDS_PER_HOUR, 0)
+                    .single()
+                    .expect("bucket starts are valid times"),
+                calls: UsageCounts::default(),
+            })
+            .collect();
+
+        let keys = self.keys.read().expect("key usage lock poisoned");
+        for (hour, calls) in keys.get(key).into_iter().flatten() {
+            let index = hour.div_euclid(width) - (current - count + 1);
+            if let Some(bucket) = usize::try_from(index)
+                .ok()
+                .and_then(|index| report.get_mut(index))
+            {
+                bucket.calls.add(calls);
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_endpoint_of() {
+        let admin = Some("/admin/api");
+        assert_eq!(Endpoint::of("/api/v1/mint", admin), Endpoint::Mint);
+        assert_eq!(Endpoint::of("/api/v1/mint/stream", admin), Endpoint::Mint);
+        assert_eq!(Endpoint::of("/api/v1/validate", admin), Endpoint::Validate);
+        assert_eq!(
+            Endpoint::of("/ark:12345/x6np1wh8k", admin),
+            Endpoint::Resolve
+        );
+        assert_eq!(Endpoint::of("/admin/api/batch", admin), Endpoint::Admin);
+        assert_eq!(Endpoint::of("/admin/apis", admin), Endpoint::Other);
+        assert_eq!(Endpoint::of("/admin/api/batch", None), Endpoint::Other);
+        assert_eq!(Endpoint::of("/api/v1/info", admin), Endpoint::Other);
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let usage = KeyUsage::default();
+        let now: DateTime<Utc> = "2025-03-31T12:30:00Z".parse().unwrap();
+        let alice = UsageKey::Configured("alice".to_string());
+
+        usage.record_at(&alice, Endpoint::Mint, now - TimeDelta::days(40));
+        usage.record_at(&alice, Endpoint::Mint, now - TimeDelta::days(10));
+        usage.record_at(&alice, Endpoint::Validate, now - TimeDelta::hours(2));
+        usage.record_at(&alice, Endpoint::Mint, now - TimeDelta::minutes(10));
+        usage.record_at(&alice, Endpoint::Admin, now);
+        usage.record_at(
+            &UsageKey::Managed("3f9c2a1b7d4e".to_string()),
+            Endpoint::Mint,
+            now,
+        );
+
+        let hourly = usage.report_at(&alice, Granularity::Hour, now);
+        assert_eq!(hourly.len(), 24);
+        assert_eq!(
+            hourly[0].start,
+            now - TimeDelta::minutes(30) - TimeDelta::hours(23)
+        );
+        let last = hourly.last().unwrap();
+        assert_eq!(
+            (last.calls.mint, last.calls.admin, last.calls.total),
+            (1, 1, 2)
+        );
+        assert_eq!(hourly[21].calls.validate, 1);
+        assert_eq!(
+            hourly.iter().map(|bucket| bucket.calls.total).sum::<u64>(),
+            3
+        );
+
+        let daily = usage.report_at(&alice, Granularity::Day, now);
+        assert_eq!(daily.len(), 30);
+        assert_eq!(
+            daily.last().unwrap().start,
+            "2025-03-31T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(daily.last().unwrap().calls.total, 3);
+        assert_eq!(daily[19].calls.mint, 1);
+        assert_eq!(
+            daily.last().unwrap().csv_line(),
+            "2025-03-31T00:00:00Z,1,1,0,1,0,3\n"
+        );
+
+        assert!(
+            usage
+                .report_at(
+                    &UsageKey::Configured("carol".to_string()),
+                    Granularity::Hour,
+                    now
+                )
+                .iter()
+                .all(|bucket| bucket.calls.total == 0)
+        );
+        // Buckets older than 30 days are dropped
+        assert_eq!(usage.keys.read().unwrap()[&alice].len(), 3);
+    }
+}
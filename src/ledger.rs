@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use crate::bindings::StorageError;
+
+/// Time between counts of the ARKs recorded per shoulder
+pub const LEDGER_COUNT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Record of every ARK the service has handed out, keyed by canonical ARK
+///
+/// Minting records each batch before it is returned, so an identifier that reached a client is
+/// known to every later mint. Persistent [`crate::store::ArkStore`] backends keep it across
+/// restarts.
+pub trait MintLedger: Send + Sync {
+    /// Records ARKs minted on `shoulder`, all or none, keeping `metadata` with each in the same
+    /// write
+    ///
+    /// ARKs that were already recorded, by an earlier or concurrent mint, are left as they are and
+    /// returned.
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError>;
+    /// Keeps `metadata`, such as who asked for them and why, with recorded ARKs, replacing
+    /// what they had; ARKs not recorded are skipped
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError>;
+    fn contains(&self, ark: &str) -> Result<bool, StorageError>;
+    /// Number of ARKs recorded
+    fn minted(&self) -> Result<usize, StorageError>;
+    /// Number of ARKs recorded on `shoulder`, or `None` where the backend can only tell by
+    /// scanning the whole ledger
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError>;
+    /// Reserves the next `count` positions of `shoulder`'s sequential counter, which starts at
+    /// 0, and returns the first
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError>;
+    /// Next position `shoulder`'s sequential counter will hand out
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError>;
+    /// About `limit` recorded ARKs, those of `shoulder` only if given, from where `cursor` (the
+    /// `next` of the previous page) left off
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError>;
+}
+
+/// An ARK in the ledger, with when it was recorded
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerEntry {
+    pub ark: String,
+    pub shoulder: String,
+    /// Unknown for ARKs a backend recorded before it kept mint times
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minted_at: Option<DateTime<Utc>>,
+    /// Kept with the ARK when it was minted, such as who asked for it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A page of ledger entries; pages may be empty before the last one
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LedgerPage {
+    pub entries: Vec<LedgerEntry>,
+    /// Cursor of the next page, none after the last one
+    pub next: Option<String>,
+}
+
+/// Shoulder, mint time and metadata of an ARK in the memory ledger
+type MemoryEntry = (String, DateTime<Utc>, BTreeMap<String, String>);
+
+/// Ledger kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryMintLedger {
+    /// Entry of each recorded ARK
+    arks: RwLock<BTreeMap<String, MemoryEntry>>,
+    /// Next position of each shoulder's sequential counter
+    sequences: Mutex<HashMap<String, u64>>,
+}
+
+impl MintLedger for MemoryMintLedger {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        let mut recorded = self.arks.write().expect("mint ledger lock poisoned");
+        let mut taken = Vec::new();
+        let minted_at = Utc::now();
+        for ark in arks {
+            match recorded.entry(ark.clone()) {
+                Entry::Occupied(_) => taken.push(ark.clone()),
+                Entry::Vacant(entry) => {
+                    entry.insert((shoulder.to_string(), minted_at, metadata.clone()));
+                }
+            }
+        }
+        Ok(taken)
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        let mut recorded = self.arks.write().expect("mint ledger lock poisoned");
+        for ark in arks {
+            if let Some((_, _, kept)) = recorded.get_mut(ark) {
+                kept.clone_from(metadata);
+            }
+        }
+        Ok(())
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        Ok(self
+            .arks
+            .read()
+            .expect("mint ledger lock poisoned")
+            .contains_key(ark))
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        Ok(self.arks.read().expect("mint ledger lock poisoned").len())
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        let recorded = self.arks.read().expect("mint ledger lock poisoned");
+        Ok(Some(
+            recorded
+                .values()
+                .filter(|(on, _, _)| on == shoulder)
+                .count(),
+        ))
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        let mut sequences = self.sequences.lock().expect("mint ledger lock poisoned");
+        let next = sequences.entry(shoulder.to_string()).or_default();
+        let first = *next;
+        *next += count;
+        Ok(first)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        let sequences = self.sequences.lock().expect("mint ledger lock poisoned");
+        Ok(sequences.get(shoulder).copied().unwrap_or(0))
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        let recorded = self.arks.read().expect("mint ledger lock poisoned");
+        let start = cursor.map_or(Bound::Unbounded, Bound::Excluded);
+        let entries: Vec<LedgerEntry> = recorded
+            .range::<str, _>((start, Bound::Unbounded))
+            .filter(|(_, (on, _, _))| shoulder.is_none_or(|shoulder| on == shoulder))
+            .take(limit)
+            .map(|(ark, (shoulder, minted_at, metadata))| LedgerEntry {
+                ark: ark.clone(),
+                shoulder: shoulder.clone(),
+                minted_at: Some(*minted_at),
+                metadata: metadata.clone(),
+            })
+            .collect();
+        Ok(next_page_by_ark(entries, limit))
+    }
+}
+
+/// ARKs recorded per shoulder, as last counted in the background, so that requests never wait
+/// on a count of the ledger
+#[derive(Debug, Default)]
+pub struct LedgerCounts {
+    counts: RwLock<HashMap<String, usize>>,
+}
+
+impl LedgerCounts {
+    /// The last count for `shoulder`; none before the first, or where the store cannot count
+    pub fn get(&self, shoulder: &str) -> Option<usize> {
+        self.counts
+            .read()
+            .expect("ledger counts lock poisoned")
+            .get(shoulder)
+            .copied()
+    }
+
+    /// Counts the ARKs of each shoulder again; a shoulder whose count fails keeps its last one
+    pub fn refresh<'a>(
+        &self,
+        ledger: &dyn MintLedger,
+        shoulders: impl IntoIterator<Item = &'a str>,
+    ) {
+        for shoulder in shoulders {
+            match ledger.minted_on(shoulder) {
+                Ok(Some(count)) => {
+                    self.counts
+                        .write()
+                        .expect("ledger counts lock poisoned")
+                        .insert(shoulder.to_string(), count);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    tracing::warn!(shoulder = %shoulder, error = %error, "Could not count recorded ARKs");
+                }
+            }
+        }
+    }
+}
+
+/// A page of entries ordered by ARK, continuing after its last ARK if it is full
+pub(crate) fn next_page_by_ark(entries: Vec<LedgerEntry>, limit: usize) -> LedgerPage {
+    let next = (entries.len() >= limit)
+        .then(|| entries.last().map(|entry| entry.ark.clone()))
+        .flatten();
+    LedgerPage { entries, next }
+}
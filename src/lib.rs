@@ -1,11 +1,72 @@
+pub mod admin_token;
+pub mod api_keys;
 pub mod ark;
+pub mod batch;
+pub mod bindings;
+pub mod blade_advisor;
+pub mod callbacks;
 pub mod check_character;
+pub mod compat;
 pub mod config;
+pub mod config_document;
+pub mod deadline;
+pub mod deliveries;
+pub mod dublin_core;
 pub mod error;
+pub mod error_pages;
+pub mod events;
+pub mod export;
+pub mod heartbeat;
+pub mod http_client;
+pub mod i18n;
+pub mod idempotency;
+pub mod import;
+pub mod inflection;
+pub mod journal;
+pub mod key_usage;
+pub mod ledger;
+pub mod lifecycle;
+pub mod limits;
+pub mod log_level;
+pub mod mint_pool;
+pub mod mint_queue;
+pub mod mint_quota;
 pub mod minting;
+pub mod names;
+pub mod notify;
+pub mod partition;
+pub mod platform;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod profile;
+pub mod public_mint;
+pub mod qualifier;
+pub mod quarantine;
+pub mod rate_limit;
+pub mod read_only;
+pub mod redaction;
+#[cfg(feature = "redis-store")]
+pub mod redis_store;
+pub mod replication;
+pub mod resourcesync;
+pub mod secrets;
 pub mod server;
+pub mod shorten;
 pub mod shoulder;
+pub mod sitemap;
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod stats;
+pub mod status;
+pub mod store;
+pub mod template;
+pub mod testing;
+pub mod turtle;
 pub mod validation;
+pub mod views;
+pub mod well_known;
 
+pub use ark::{Ark, normalize_ark_string, parse_ark};
 pub use config::AppState;
 pub use error::AppError;
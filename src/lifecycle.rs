@@ -0,0 +1,205 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where an ARK stands in its life, from reserved to tombstoned
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArkStatus {
+    /// Issued but not yet public; resolves as if unknown
+    Reserved,
+    /// Resolves to its target
+    #[default]
+    Active,
+    /// Taken out of service for now; answers 410 Gone until reinstated
+    Withdrawn,
+    /// Withdrawn for good; no status follows
+    Tombstoned,
+    /// Stands in for another ARK, to which it redirects
+    Alias,
+}
+
+impl ArkStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ArkStatus::Reserved => "reserved",
+            ArkStatus::Active => "active",
+            ArkStatus::Withdrawn => "withdrawn",
+            ArkStatus::Tombstoned => "tombstoned",
+            ArkStatus::Alias => "alias",
+        }
+    }
+
+    /// Whether an ARK in this status may move to `next`
+    ///
+    /// Nothing returns to `reserved`, and nothing leaves `tombstoned`. An alias may be pointed
+    /// at another ARK.
+    pub fn can_become(self, next: ArkStatus) -> bool {
+        use ArkStatus::*;
+        matches!(
+            (self, next),
+            (Reserved, Active | Alias | Tombstoned)
+                | (Active, Withdrawn | Alias | Tombstoned)
+                | (Withdrawn, Active | Tombstoned)
+                | (Alias, Active | Alias | Withdrawn | Tombstoned)
+        )
+    }
+
+    /// Whether a new binding may start in this status
+    pub fn is_initial(self) -> bool {
+        matches!(self, ArkStatus::Reserved | ArkStatus::Active)
+    }
+}
+
+impl fmt::Display for ArkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Audit entry for one status change
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct StatusChange {
+    pub from: ArkStatus,
+    pub to: ArkStatus,
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// An ARK's status, with every change that led to it
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Lifecycle {
+    #[serde(default)]
+    pub status: ArkStatus,
+    /// Normalized ARK this one redirects to while `status` is `alias`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
+    /// Status changes, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StatusChange>,
+}
+
+impl Lifecycle {
+    /// Lifecycle of a new binding, which must start out `reserved` or `active`
+    pub fn starting(status: ArkStatus) -> Result<Self, String> {
+        if !status.is_initial() {
+            return Err(format!("new bindings cannot start out {}", status));
+        }
+        Ok(Self {
+            status,
+            ..Self::default()
+        })
+    }
+
+    /// An active ARK that was never changed, which stores need not record
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Moves to `to` if the current status allows it, recording the change
+    ///
+    /// `alias_of` is required for `alias` and refused otherwise.
+    pub fn transition(
+        &mut self,
+        to: ArkStatus,
+        alias_of: Option<String>,
+        reason: Option<String>,
+        at: DateTime<Utc>,
+    ) -> Result<(), String> {
+        if !self.status.can_become(to) {
+            return Err(format!(
+                "cannot change status from {} to {}",
+                self.status, to
+            ));
+        }
+        match (to, &alias_of) {
+            (ArkStatus::Alias, None) => return Err("alias needs alias_of".to_string()),
+            (ArkStatus::Alias, Some(target)) if self.alias_of.as_ref() == Some(target) => {
+                return Err(format!("already an alias of {}", target));
+            }
+            (ArkStatus::Alias, Some(_)) => {}
+            (_, Some(_)) => return Err("alias_of is only allowed with status alias".to_string()),
+            (_, None) => {}
+        }
+        self.history.push(StatusChange {
+            from: self.status,
+            to,
+            at,
+            reason,
+        });
+        self.status = to;
+        self.alias_of = alias_of;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions() {
+        use ArkStatus::*;
+        let statuses = [Reserved, Active, Withdrawn, Tombstoned, Alias];
+        for from in statuses {
+            assert!(!from.can_become(Reserved), "{} -> reserved", from);
+            assert!(!Tombstoned.can_become(from), "tombstoned -> {}", from);
+        }
+        assert!(Reserved.can_become(Active));
+        assert!(Active.can_become(Withdrawn));
+        assert!(Withdrawn.can_become(Active));
+        assert!(Withdrawn.can_become(Tombstoned));
+        assert!(!Withdrawn.can_become(Alias));
+        assert!(!Active.can_become(Active));
+    }
+
+    #[test]
+    fn test_lifecycle_records_changes() {
+        assert!(Lifecycle::starting(ArkStatus::Withdrawn).is_err());
+        let mut lifecycle = Lifecycle::starting(ArkStatus::Reserved).unwrap();
+        let now = Utc::now();
+        lifecycle
+            .transition(ArkStatus::Active, None, None, now)
+            .unwrap();
+        assert!(
+            lifecycle
+                .transition(ArkStatus::Alias, None, None, now)
+                .is_err()
+        );
+        let target = "ark:12345/x6bbbb".to_string();
+        lifecycle
+            .transition(ArkStatus::Alias, Some(target.clone()), None, now)
+            .unwrap();
+        lifecycle
+            .transition(
+                ArkStatus::Tombstoned,
+                None,
+                Some("Duplicate".to_string()),
+                now,
+            )
+            .unwrap();
+        assert!(
+            lifecycle
+                .transition(ArkStatus::Reserved, None, None, now)
+                .is_err()
+        );
+
+        assert_eq!(lifecycle.status, ArkStatus::Tombstoned);
+        assert_eq!(lifecycle.alias_of, None);
+        let path: Vec<_> = lifecycle
+            .history
+            .iter()
+            .map(|change| (change.from, change.to))
+            .collect();
+        assert_eq!(
+            path,
+            [
+                (ArkStatus::Reserved, ArkStatus::Active),
+                (ArkStatus::Active, ArkStatus::Alias),
+                (ArkStatus::Alias, ArkStatus::Tombstoned),
+            ]
+        );
+        assert_eq!(lifecycle.history[2].reason.as_deref(), Some("Duplicate"));
+    }
+}
@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::ark::Ark;
+use crate::notify::env_parse;
+
+/// Longest ARK accepted unless `ARK_MAX_LENGTH` says otherwise, in bytes
+pub const DEFAULT_MAX_ARK_LENGTH: usize = 2048;
+
+/// Longest blade accepted unless `ARK_MAX_BLADE_LENGTH` says otherwise, in bytes
+pub const DEFAULT_MAX_BLADE_LENGTH: usize = 128;
+
+/// Longest qualifier accepted unless `ARK_MAX_QUALIFIER_LENGTH` says otherwise, in bytes
+pub const DEFAULT_MAX_QUALIFIER_LENGTH: usize = 1024;
+
+/// Characters of an oversized input kept in logs
+const LOGGED_PREFIX: usize = 64;
+
+/// The part of an ARK that was too long
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TooLong {
+    Ark,
+    Blade,
+    Qualifier,
+}
+
+impl TooLong {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TooLong::Ark => "ark",
+            TooLong::Blade => "blade",
+            TooLong::Qualifier => "qualifier",
+        }
+    }
+}
+
+/// Rejected inputs, per part that was too long
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct RejectionCounts {
+    pub ark: u64,
+    pub blade: u64,
+    pub qualifier: u64,
+}
+
+/// The configured limits and what they rejected, as reported to admins
+#[derive(Debug, Serialize)]
+pub struct InputLimitsReport {
+    pub max_ark_length: usize,
+    pub max_blade_length: usize,
+    pub max_qualifier_length: usize,
+    pub rejected: RejectionCounts,
+    /// ARKs resolved only after trailing punctuation from the surrounding prose was removed
+    pub punctuation_stripped: u64,
+}
+
+/// Maximum lengths of incoming ARKs, checked before they reach allocations, logs and templates
+#[derive(Debug)]
+pub struct InputLimits {
+    pub max_ark_length: usize,
+    pub max_blade_length: usize,
+    pub max_qualifier_length: usize,
+    rejected: [AtomicU64; 3],
+    punctuation_stripped: AtomicU64,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_ARK_LENGTH,
+            DEFAULT_MAX_BLADE_LENGTH,
+            DEFAULT_MAX_QUALIFIER_LENGTH,
+        )
+    }
+}
+
+impl InputLimits {
+    pub fn new(
+        max_ark_length: usize,
+        max_blade_length: usize,
+        max_qualifier_length: usize,
+    ) -> Self {
+        Self {
+            max_ark_length,
+            max_blade_length,
+            max_qualifier_length,
+            rejected: Default::default(),
+            punctuation_stripped: AtomicU64::new(0),
+        }
+    }
+
+    /// Checks a raw ARK before it is parsed
+    pub fn check_raw(&self, ark: &str) -> Result<(), TooLong> {
+        if ark.len() > self.max_ark_length {
+            return Err(self.reject(TooLong::Ark, ark));
+        }
+        Ok(())
+    }
+
+    /// Checks the blade and qualifier of a parsed ARK
+    pub fn check_parsed(&self, ark: &Ark) -> Result<(), TooLong> {
+        if ark.blade.len() > self.max_blade_length {
+            return Err(self.reject(TooLong::Blade, &ark.original));
+        }
+        if ark.qualifier.len() > self.max_qualifier_length {
+            return Err(self.reject(TooLong::Qualifier, &ark.original));
+        }
+        Ok(())
+    }
+
+    /// Checks a raw ARK, then its parts if it parses
+    pub fn check(&self, ark: &str, parsed: Option<&Ark>) -> Result<(), TooLong> {
+        self.check_raw(ark)?;
+        parsed.map_or(Ok(()), |parsed| self.check_parsed(parsed))
+    }
+
+    fn reject(&self, part: TooLong, ark: &str) -> TooLong {
+        self.rejected[part as usize].fetch_add(1, Ordering::Relaxed);
+        let prefix: String = ark.chars().take(LOGGED_PREFIX).collect();
+        tracing::warn!(
+            part = part.as_str(),
+            length = ark.len(),
+            prefix = %prefix,
+            "Rejected oversized ARK input"
+        );
+        part
+    }
+
+    /// Counts an ARK that resolved after its trailing punctuation was stripped
+    pub fn record_punctuation_stripped(&self) {
+        self.punctuation_stripped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn report(&self) -> InputLimitsReport {
+        let count = |part: TooLong| self.rejected[part as usize].load(Ordering::Relaxed);
+        InputLimitsReport {
+            max_ark_length: self.max_ark_length,
+            max_blade_length: self.max_blade_length,
+            max_qualifier_length: self.max_qualifier_length,
+            rejected: RejectionCounts {
+                ark: count(TooLong::Ark),
+                blade: count(TooLong::Blade),
+                qualifier: count(TooLong::Qualifier),
+            },
+            punctuation_stripped: self.punctuation_stripped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Build the input limits from environment variables
+///
+/// Optional: `ARK_MAX_LENGTH` (default 2048), `ARK_MAX_BLADE_LENGTH` (default 128),
+/// `ARK_MAX_QUALIFIER_LENGTH` (default 1024), all in bytes.
+pub fn load_input_limits_from_env() -> Result<InputLimits, String> {
+    let limits = InputLimits::new(
+        env_parse("ARK_MAX_LENGTH")?.unwrap_or(DEFAULT_MAX_ARK_LENGTH),
+        env_parse("ARK_MAX_BLADE_LENGTH")?.unwrap_or(DEFAULT_MAX_BLADE_LENGTH),
+        env_parse("ARK_MAX_QUALIFIER_LENGTH")?.unwrap_or(DEFAULT_MAX_QUALIFIER_LENGTH),
+    );
+    if limits.max_ark_length == 0 || limits.max_blade_length == 0 {
+        return Err("ARK_MAX_LENGTH and ARK_MAX_BLADE_LENGTH must be greater than 0".to_string());
+    }
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+
+    #[test]
+    fn test_limits_reject_and_count() {
+        let limits = InputLimits::new(40, 10, 8);
+
+        let ok = "ark:12345/x6np1wh8k/page2";
+        assert_eq!(limits.check(ok, parse_ark(ok).as_ref()), Ok(()));
+
+        let long = format!("ark:12345/x6{}", "b".repeat(40));
+        assert_eq!(limits.check_raw(&long), Err(TooLong::Ark));
+
+        let blade = format!("ark:12345/x6{}", "b".repeat(11));
+        assert_eq!(
+            limits.check(&blade, parse_ark(&blade).as_ref()),
+            Err(TooLong::Blade)
+        );
+
+        let qualifier = "ark:12345/x6np1wh8k/page2.pdf";
+        assert_eq!(
+            limits.check(qualifier, parse_ark(qualifier).as_ref()),
+            Err(TooLong::Qualifier)
+        );
+
+        assert_eq!(
+            limits.report().rejected,
+            RejectionCounts {
+                ark: 1,
+                blade: 1,
+                qualifier: 1,
+            }
+        );
+    }
+}
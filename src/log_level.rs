@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+/// Handle on the running log filter, for raising or lowering verbosity without a restart
+///
+/// Without a handle (tests, or applications with their own subscriber), the filter cannot be
+/// changed.
+#[derive(Debug, Default)]
+pub struct LogLevel {
+    handle: Option<reload::Handle<EnvFilter, Registry>>,
+    /// The filter the service started with, restored by [`LogLevel::reset`]
+    initial: String,
+}
+
+/// A log filter: a default level, and levels for individual modules such as
+/// `ark_service::shoulder`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct LogFilter {
+    pub level: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modules: BTreeMap<String, String>,
+}
+
+impl LogFilter {
+    /// The filter as `EnvFilter` directives, e.g. `info,ark_service::shoulder=debug`
+    pub fn directives(&self) -> Result<String, String> {
+        let level = parse_level(&self.level)?;
+        let mut directives = vec![level.to_string().to_lowercase()];
+        for (module, module_level) in &self.modules {
+            if module.is_empty()
+                || !module
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "_:".contains(c))
+            {
+                return Err(format!("Invalid module: {}", module));
+            }
+            let module_level = parse_level(module_level)?;
+            directives.push(format!(
+                "{}={}",
+                module,
+                module_level.to_string().to_lowercase()
+            ));
+        }
+        Ok(directives.join(","))
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level).map_err(|_| {
+        format!(
+            "Invalid log level {}: expected off, error, warn, info, debug, or trace",
+            level
+        )
+    })
+}
+
+impl LogLevel {
+    /// Changes the filter through `handle`, which the service started with `initial`
+    pub fn new(handle: reload::Handle<EnvFilter, Registry>, initial: String) -> Self {
+        Self {
+            handle: Some(handle),
+            initial,
+        }
+    }
+
+    /// The filter in effect, as `EnvFilter` directives
+    pub fn current(&self) -> Result<String, String> {
+        self.handle()?
+            .with_current(|filter| filter.to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Replaces the filter, returning the directives now in effect
+    pub fn set(&self, filter: &LogFilter) -> Result<String, String> {
+        self.apply(&filter.directives()?)
+    }
+
+    /// Goes back to the filter the service started with
+    pub fn reset(&self) -> Result<String, String> {
+        self.apply(&self.initial)
+    }
+
+    fn apply(&self, directives: &str) -> Result<String, String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+        self.handle()?.reload(filter).map_err(|e| e.to_string())?;
+        self.current()
+    }
+
+    fn handle(&self) -> Result<&reload::Handle<EnvFilter, Registry>, String> {
+        self.handle
+            .as_ref()
+            .ok_or_else(|| "The log level cannot be changed in this process".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(level: &str, modules: &[(&str, &str)]) -> LogFilter {
+        LogFilter {
+            level: level.to_string(),
+            modules: modules
+                .iter()
+                .map(|(module, level)| (module.to_string(), level.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(
+            filter("INFO", &[("ark_service::shoulder", "debug")]).directives(),
+            Ok("info,ark_service::shoulder=debug".to_string())
+        );
+        assert!(filter("verbose", &[]).directives().is_err());
+        assert!(
+            filter("info", &[("ark_service::shoulder", "loud")])
+                .directives()
+                .is_err()
+        );
+        assert!(
+            filter("info", &[("ark_service=trace,h2", "debug")])
+                .directives()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_set_and_reset() {
+        let (layer, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        let log_level = LogLevel::new(handle, "info".to_string());
+        assert_eq!(log_level.current(), Ok("info".to_string()));
+
+        let changed = log_level
+            .set(&filter("warn", &[("ark_service::shoulder", "trace")]))
+            .unwrap();
+        assert!(
+            changed.contains("ark_service::shoulder=trace"),
+            "{}",
+            changed
+        );
+        assert!(changed.contains("warn"), "{}", changed);
+        assert_eq!(log_level.reset(), Ok("info".to_string()));
+        drop(layer);
+
+        assert!(LogLevel::default().current().is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::notify::env_parse;
+
+/// How often pools are topped up unless `MINT_POOL_REFILL_INTERVAL_SECS` says otherwise
+pub const DEFAULT_POOL_REFILL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// ARKs minted ahead of requests, per shoulder
+///
+/// Pooled ARKs are already checked against and recorded in the mint ledger, so a request
+/// served from the pool does not wait on storage. The pools themselves are kept in memory:
+/// ARKs still pooled when the service stops stay in the ledger and are never issued.
+#[derive(Debug)]
+pub struct MintPools {
+    size: usize,
+    refill_interval: Duration,
+    pools: Mutex<HashMap<String, VecDeque<String>>>,
+}
+
+impl Default for MintPools {
+    /// No pools; every request mints its own ARKs
+    fn default() -> Self {
+        Self {
+            size: 0,
+            refill_interval: DEFAULT_POOL_REFILL_INTERVAL,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// ARKs waiting in one shoulder's pool, as shown by the admin API
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PoolLevel {
+    pub shoulder: String,
+    pub pooled: usize,
+    pub size: usize,
+}
+
+impl MintPools {
+    /// Pools of up to `size` ARKs per shoulder, topped up every `refill_interval`; a size of 0
+    /// disables them
+    pub fn new(size: usize, refill_interval: Duration) -> Result<Self, String> {
+        if refill_interval.is_zero() {
+            return Err("MINT_POOL_REFILL_INTERVAL_SECS must be greater than 0".to_string());
+        }
+        Ok(Self {
+            size,
+            refill_interval,
+            ..Default::default()
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.size > 0
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn refill_interval(&self) -> Duration {
+        self.refill_interval
+    }
+
+    /// The `count` oldest ARKs of the shoulder's pool, or none if it holds fewer
+    pub fn take(&self, shoulder: &str, count: usize) -> Option<Vec<String>> {
+        let mut pools = self.pools.lock().expect("mint pool lock poisoned");
+        let pool = pools.get_mut(shoulder)?;
+        if pool.len() < count {
+            return None;
+        }
+        Some(pool.drain(..count).collect())
+    }
+
+    /// ARKs missing from the shoulder's pool
+    pub fn shortfall(&self, shoulder: &str) -> usize {
+        let pools = self.pools.lock().expect("mint pool lock poisoned");
+        self.size
+            .saturating_sub(pools.get(shoulder).map_or(0, VecDeque::len))
+    }
+
+    /// Adds freshly minted ARKs to the shoulder's pool
+    pub fn add(&self, shoulder: &str, arks: Vec<String>) {
+        let mut pools = self.pools.lock().expect("mint pool lock poisoned");
+        pools.entry(shoulder.to_string()).or_default().extend(arks);
+    }
+
+    /// Level of each of `shoulders`' pools, in name order
+    pub fn levels<'a>(&self, shoulders: impl IntoIterator<Item = &'a str>) -> Vec<PoolLevel> {
+        let pools = self.pools.lock().expect("mint pool lock poisoned");
+        let mut levels: Vec<PoolLevel> = shoulders
+            .into_iter()
+            .map(|shoulder| PoolLevel {
+                shoulder: shoulder.to_string(),
+                pooled: pools.get(shoulder).map_or(0, VecDeque::len),
+                size: self.size,
+            })
+            .collect();
+        levels.sort_by(|a, b| a.shoulder.cmp(&b.shoulder));
+        levels
+    }
+}
+
+/// Configure mint pools from `MINT_POOL_SIZE` and `MINT_POOL_REFILL_INTERVAL_SECS`
+pub fn load_mint_pools_from_env() -> Result<MintPools, String> {
+    let size = env_parse("MINT_POOL_SIZE")?.unwrap_or(0);
+    let refill_interval = env_parse("MINT_POOL_REFILL_INTERVAL_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_POOL_REFILL_INTERVAL);
+    MintPools::new(size, refill_interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pools_hand_out_oldest_first() {
+        let pools = MintPools::new(3, Duration::from_secs(1)).unwrap();
+        assert!(pools.is_enabled());
+        assert_eq!(pools.take("x6", 1), None);
+        assert_eq!(pools.shortfall("x6"), 3);
+
+        pools.add("x6", vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(pools.shortfall("x6"), 1);
+        assert_eq!(pools.take("x6", 3), None);
+        assert_eq!(pools.take("x6", 1), Some(vec!["a".to_string()]));
+        assert_eq!(
+            pools.levels(["x6", "b3"]),
+            [
+                PoolLevel {
+                    shoulder: "b3".to_string(),
+                    pooled: 0,
+                    size: 3
+                },
+                PoolLevel {
+                    shoulder: "x6".to_string(),
+                    pooled: 1,
+                    size: 3
+                }
+            ]
+        );
+        assert_eq!(pools.shortfall("b3"), 3);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let pools = MintPools::default();
+        assert!(!pools.is_enabled());
+        assert_eq!(pools.shortfall("x6"), 0);
+        assert!(MintPools::new(10, Duration::ZERO).is_err());
+    }
+}
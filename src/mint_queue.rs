@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::notify::env_parse;
+
+/// Mint workers unless `MINT_WORKERS` says otherwise
+pub const DEFAULT_MINT_WORKERS: usize = 4;
+/// Mint requests allowed to wait for a worker unless `MINT_QUEUE_DEPTH` says otherwise
+pub const DEFAULT_MINT_QUEUE_DEPTH: usize = 64;
+/// Suggested wait for rejected requests unless `MINT_QUEUE_RETRY_AFTER_SECS` says otherwise
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Workers {
+    sender: SyncSender<Job>,
+    workers: usize,
+    capacity: usize,
+    waiting: Arc<AtomicUsize>,
+    busy: Arc<AtomicUsize>,
+}
+
+/// Mint queue counters, as shown by the admin API
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MintQueueStats {
+    pub enabled: bool,
+    pub workers: usize,
+    pub capacity: usize,
+    /// Requests waiting for a worker
+    pub waiting: usize,
+    /// Requests being minted
+    pub busy: usize,
+    /// Requests turned away because the queue was full, since the service started
+    pub rejected: u64,
+}
+
+/// Bounded queue in front of a fixed set of mint worker threads
+///
+/// Minting, and binding in particular, waits on storage. When storage slows down, requests
+/// beyond the queue's capacity are rejected right away with `503` and `Retry-After`, instead
+/// of piling up in memory.
+pub struct MintQueue {
+    workers: Option<Workers>,
+    retry_after: Duration,
+    rejected: AtomicU64,
+}
+
+impl Default for MintQueue {
+    /// Mints on the runtime's blocking threads, without a queue
+    fn default() -> Self {
+        Self {
+            workers: None,
+            retry_after: DEFAULT_RETRY_AFTER,
+            rejected: AtomicU64::new(0),
+        }
+    }
+}
+
+impl MintQueue {
+    /// A queue of at most `capacity` waiting requests served by `workers` threads; with no
+    /// workers, requests mint on the runtime's blocking threads without a queue
+    pub fn new(workers: usize, capacity: usize, retry_after: Duration) -> Result<Self, String> {
+        if workers == 0 {
+            return Ok(Self {
+                retry_after,
+                ..Default::default()
+            });
+        }
+        if capacity == 0 {
+            return Err("MINT_QUEUE_DEPTH must be greater than 0".to_string());
+        }
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Job>(capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let waiting = Arc::new(AtomicUsize::new(0));
+        let busy = Arc::new(AtomicUsize::new(0));
+        for i in 0..workers {
+            let (receiver, waiting, busy) = (receiver.clone(), waiting.clone(), busy.clone());
+            std::thread::Builder::new()
+                .name(format!("mint-{}", i))
+                .spawn(move || work(&receiver, &waiting, &busy))
+                .map_err(|e| format!("Failed to start mint workers: {}", e))?;
+        }
+
+        Ok(Self {
+            workers: Some(Workers {
+                sender,
+                workers,
+                capacity,
+                waiting,
+                busy,
+            }),
+            retry_after,
+            rejected: AtomicU64::new(0),
+        })
+    }
+
+    /// Runs `job` on a mint worker, or fails with [`AppError::Overloaded`] if the queue is full
+    pub async fn run<T, F>(&self, job: F) -> Result<T, AppError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, AppError> + Send + 'static,
+    {
+        // Large mints take long enough to hold up the runtime's other tasks
+        let Some(workers) = &self.workers else {
+            return tokio::task::spawn_blocking(job)
+                .await
+                .map_err(|_| AppError::Internal("mint job panicked".to_string()))?;
+        };
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let job: Job = Box::new(move || {
+            // The request may have been dropped in the meantime
+            let _ = sender.send(job());
+        });
+        workers.waiting.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = workers.sender.try_send(job) {
+            workers.waiting.fetch_sub(1, Ordering::Relaxed);
+            return Err(match e {
+                TrySendError::Full(_) => {
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        capacity = workers.capacity,
+                        "Mint queue full, rejecting request"
+                    );
+                    AppError::Overloaded {
+                        retry_after: self.retry_after,
+                    }
+                }
+                TrySendError::Disconnected(_) => {
+                    AppError::Internal("mint workers stopped".to_string())
+                }
+            });
+        }
+        receiver
+            .await
+            .map_err(|_| AppError::Internal("mint worker panicked".to_string()))?
+    }
+
+    pub fn stats(&self) -> MintQueueStats {
+        let rejected = self.rejected.load(Ordering::Relaxed);
+        match &self.workers {
+            Some(workers) => MintQueueStats {
+                enabled: true,
+                workers: workers.workers,
+                capacity: workers.capacity,
+                waiting: workers.waiting.load(Ordering::Relaxed),
+                busy: workers.busy.load(Ordering::Relaxed),
+                rejected,
+            },
+            None => MintQueueStats {
+                enabled: false,
+                workers: 0,
+                capacity: 0,
+                waiting: 0,
+                busy: 0,
+                rejected,
+            },
+        }
+    }
+}
+
+/// Worker loop: runs jobs until the queue is dropped
+fn work(receiver: &Mutex<Receiver<Job>>, waiting: &AtomicUsize, busy: &AtomicUsize) {
+    loop {
+        let job = match receiver.lock().expect("mint queue lock poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        waiting.fetch_sub(1, Ordering::Relaxed);
+        busy.fetch_add(1, Ordering::Relaxed);
+        // A panicking job fails its own request, not the worker
+        let _ = catch_unwind(AssertUnwindSafe(job));
+        busy.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Build the mint queue from environment variables
+///
+/// Optional: `MINT_WORKERS` (default 4; 0 mints without a queue), `MINT_QUEUE_DEPTH`
+/// (default 64), `MINT_QUEUE_RETRY_AFTER_SECS` (default 1).
+pub fn load_mint_queue_from_env() -> Result<MintQueue, String> {
+    let workers = env_parse("MINT_WORKERS")?.unwrap_or(DEFAULT_MINT_WORKERS);
+    let capacity = env_parse("MINT_QUEUE_DEPTH")?.unwrap_or(DEFAULT_MINT_QUEUE_DEPTH);
+    let retry_after = env_parse("MINT_QUEUE_RETRY_AFTER_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER);
+    MintQueue::new(workers, capacity, retry_after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, header};
+    use axum::response::IntoResponse;
+
+    #[tokio::test]
+    async fn test_rejects_requests_beyond_capacity() {
+        let queue = Arc::new(MintQueue::new(1, 1, Duration::from_secs(2)).unwrap());
+        let (release, blocked) = std::sync::mpsc::channel::<()>();
+
+        // Occupy the only worker, then fill the only queue slot
+        let busy = tokio::spawn({
+            let queue = queue.clone();
+            async move {
+                queue
+                    .run(move || {
+                        blocked.recv().unwrap();
+                        Ok(1)
+                    })
+                    .await
+            }
+        });
+        while queue.stats().busy == 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.run(|| Ok(2)).await }
+        });
+        while queue.stats().waiting == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let rejected = queue.run(|| Ok(3)).await.unwrap_err().into_response();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(rejected.headers()[header::RETRY_AFTER], "2");
+        assert_eq!(queue.stats().rejected, 1);
+
+        release.send(()).unwrap();
+        assert_eq!(busy.await.unwrap().unwrap(), 1);
+        assert_eq!(queued.await.unwrap().unwrap(), 2);
+        assert_eq!(queue.stats().waiting, 0);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_queue_runs_without_workers() {
+        let queue = MintQueue::default();
+        assert_eq!(queue.run(|| Ok("minted")).await.unwrap(), "minted");
+        assert!(!queue.stats().enabled);
+    }
+}
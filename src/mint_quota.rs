@@ -0,0 +1,230 @@
+use chrono::{DateTime, DurationRound, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Most ARKs a shoulder may mint per clock hour and per UTC day, over all clients
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct MintQuota {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub per_hour: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub per_day: Option<u64>,
+}
+
+impl MintQuota {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.per_hour.is_none() && self.per_day.is_none() {
+            return Err("mint_quota needs per_hour or per_day".to_string());
+        }
+        if self.per_hour == Some(0) || self.per_day == Some(0) {
+            return Err("mint_quota limits must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Most ARKs a single mint may ask for, the smallest of the limits
+    pub fn largest_mint(&self) -> u64 {
+        self.windows()
+            .map(|(_, limit)| limit)
+            .min()
+            .unwrap_or(u64::MAX)
+    }
+
+    /// The hourly and daily windows with a limit, with that limit
+    fn windows(&self) -> impl Iterator<Item = (Window, u64)> {
+        [(Window::Hour, self.per_hour), (Window::Day, self.per_day)]
+            .into_iter()
+            .filter_map(|(window, limit)| limit.map(|limit| (window, limit)))
+    }
+}
+
+/// Why a mint was refused by its shoulder's quota
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuotaExceeded {
+    /// The window has too little left; it resets after `retry_after`
+    UsedUp { retry_after: Duration },
+    /// More ARKs were asked for than a whole window allows
+    TooLarge { limit: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Window {
+    Hour,
+    Day,
+}
+
+impl Window {
+    fn length(self) -> chrono::Duration {
+        match self {
+            Window::Hour => chrono::Duration::hours(1),
+            Window::Day => chrono::Duration::days(1),
+        }
+    }
+
+    fn start(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now.duration_trunc(self.length())
+            .expect("hours and days truncate")
+    }
+}
+
+/// ARKs counted in one window of a shoulder
+#[derive(Debug)]
+struct WindowUsage {
+    start: DateTime<Utc>,
+    used: u64,
+}
+
+/// ARKs minted per shoulder in the current hour and day, on this instance
+#[derive(Debug, Default)]
+pub struct MintQuotas {
+    usage: Mutex<HashMap<(String, Window), WindowUsage>>,
+}
+
+impl MintQuotas {
+    /// Counts `count` ARKs against the shoulder's quota, or none if any window would overflow
+    pub fn take(
+        &self,
+        shoulder: &str,
+        quota: &MintQuota,
+        count: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(), QuotaExceeded> {
+        let mut usage = self.usage.lock().expect("mint quota lock poisoned");
+        let mut retry_after = None;
+        for (window, limit) in quota.windows() {
+            if count > limit {
+                return Err(QuotaExceeded::TooLarge { limit });
+            }
+            let start = window.start(now);
+            let used = match usage.get(&(shoulder.to_string(), window)) {
+                Some(counted) if counted.start == start => counted.used,
+                _ => 0,
+            };
+            if used + count > limit {
+                let reset = (start + window.length() - now).to_std().unwrap_or_default();
+                retry_after = retry_after.max(Some(reset));
+            }
+        }
+        if let Some(retry_after) = retry_after {
+            return Err(QuotaExceeded::UsedUp { retry_after });
+        }
+        for (window, _) in quota.windows() {
+            let start = window.start(now);
+            let counted = usage
+                .entry((shoulder.to_string(), window))
+                .or_insert(WindowUsage { start, used: 0 });
+            if counted.start != start {
+                *counted = WindowUsage { start, used: 0 };
+            }
+            counted.used += count;
+        }
+        Ok(())
+    }
+
+    /// Gives back ARKs taken for a mint that then failed
+    pub fn give_back(&self, shoulder: &str, count: u64, now: DateTime<Utc>) {
+        let mut usage = self.usage.lock().expect("mint quota lock poisoned");
+        for window in [Window::Hour, Window::Day] {
+            if let Some(counted) = usage.get_mut(&(shoulder.to_string(), window))
+                && counted.start == window.start(now)
+            {
+                counted.used = counted.used.saturating_sub(count);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_windows_reset() {
+        let quotas = MintQuotas::default();
+        let quota = MintQuota {
+            per_hour: Some(10),
+            per_day: Some(15),
+        };
+        let at = |time: &str| time.parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            quotas.take("x6", &quota, 11, at("2025-01-15T09:00:00Z")),
+            Err(QuotaExceeded::TooLarge { limit: 10 })
+        );
+        assert!(
+            quotas
+                .take("x6", &quota, 8, at("2025-01-15T09:10:00Z"))
+                .is_ok()
+        );
+        assert_eq!(
+            quotas.take("x6", &quota, 3, at("2025-01-15T09:40:00Z")),
+            Err(QuotaExceeded::UsedUp {
+                retry_after: Duration::from_secs(20 * 60)
+            })
+        );
+        // Other shoulders have their own count
+        assert!(
+            quotas
+                .take("b3", &quota, 3, at("2025-01-15T09:40:00Z"))
+                .is_ok()
+        );
+
+        // The hour resets, but the day's quota still holds
+        assert!(
+            quotas
+                .take("x6", &quota, 3, at("2025-01-15T10:00:00Z"))
+                .is_ok()
+        );
+        assert_eq!(
+            quotas.take("x6", &quota, 5, at("2025-01-15T10:30:00Z")),
+            Err(QuotaExceeded::UsedUp {
+                retry_after: Duration::from_secs(13 * 3600 + 30 * 60)
+            })
+        );
+        quotas.give_back("x6", 3, at("2025-01-15T10:30:00Z"));
+        assert!(
+            quotas
+                .take("x6", &quota, 7, at("2025-01-15T10:30:00Z"))
+                .is_ok()
+        );
+        assert!(
+            quotas
+                .take("x6", &quota, 8, at("2025-01-16T00:00:00Z"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(MintQuota::default().validate().is_err());
+        assert_eq!(
+            MintQuota {
+                per_hour: Some(100),
+                per_day: Some(40)
+            }
+            .largest_mint(),
+            40
+        );
+        assert!(
+            MintQuota {
+                per_hour: Some(0),
+                per_day: None
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            MintQuota {
+                per_hour: None,
+                per_day: Some(500)
+            }
+            .validate()
+            .is_ok()
+        );
+    }
+}
@@ -1,25 +1,317 @@
+use chrono::{DateTime, Utc};
 use rand::Rng;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::RwLock;
+use std::time::Duration;
 
+use crate::ark::{Ark, ArkFormat, parse_ark};
+use crate::bindings::StorageError;
 use crate::check_character::calculate_check_character;
-use crate::config::{AppState, BETANUMERIC};
+use crate::config::AppState;
 use crate::error::AppError;
+use crate::mint_quota::QuotaExceeded;
+use crate::names::ShoulderName;
+use crate::notify::{Alert, env_parse};
+use crate::shoulder::Shoulder;
+use crate::template::{BufferedRng, Generator, NoidTemplate};
 
-/// Mint a single new ARK with the given NAAN, shoulder, blade length, and check character option
-pub fn mint_ark(
+/// Largest mint metadata accepted, counting the bytes of its keys and values
+pub const MAX_MINT_METADATA_BYTES: usize = 4096;
+
+/// Smallest mint whose blades are drawn in parallel unless `MINT_PARALLEL_THRESHOLD` says
+/// otherwise
+pub const DEFAULT_PARALLEL_MINT_THRESHOLD: usize = 1024;
+
+/// How minting reacts when a generated identifier has already been issued
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionPolicy {
+    /// New candidates generated after a collision before giving up (default: 10)
+    pub max_retries: u32,
+    /// Once retries run out, try again with a blade one character longer instead of failing
+    /// (default: false)
+    pub escalate_blade_length: bool,
+}
+
+impl Default for CollisionPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            escalate_blade_length: false,
+        }
+    }
+}
+
+/// Worker threads shared by all requests for drawing the random blades of large mints
+///
+/// Drawing and check-summing blades is CPU-bound, so the candidates of a large mint are drawn
+/// on a fixed number of threads, each from its own buffered generator. Smaller mints draw
+/// them on the minting thread.
+pub struct BladePool {
+    pool: Option<rayon::ThreadPool>,
+    threshold: usize,
+}
+
+impl Default for BladePool {
+    /// Draws every mint's blades serially
+    fn default() -> Self {
+        Self {
+            pool: None,
+            threshold: DEFAULT_PARALLEL_MINT_THRESHOLD,
+        }
+    }
+}
+
+impl BladePool {
+    /// A pool of `threads` workers for mints of at least `threshold` ARKs; with fewer than two
+    /// threads, blades are drawn serially
+    pub fn new(threads: usize, threshold: usize) -> Result<Self, String> {
+        let pool = (threads > 1)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("blades-{}", i))
+                    .build()
+                    .map_err(|e| format!("Failed to start blade threads: {}", e))
+            })
+            .transpose()?;
+        Ok(Self { pool, threshold })
+    }
+
+    /// Number of worker threads, 0 when drawing serially
+    pub fn threads(&self) -> usize {
+        self.pool
+            .as_ref()
+            .map_or(0, |pool| pool.current_num_threads())
+    }
+
+    /// `count` random blades shaped by `template`, with their check characters if it has
+    /// them; neither checked against the ledger nor told apart from each other
+    pub fn random_blades(
+        &self,
+        shoulder: &str,
+        template: &NoidTemplate,
+        count: usize,
+    ) -> Vec<String> {
+        let blade = |rng: &mut BufferedRng| {
+            with_check_character(
+                shoulder,
+                template.random_blade_with(rng),
+                template.check_character(),
+            )
+        };
+        match &self.pool {
+            Some(pool) if count >= self.threshold => pool.install(|| {
+                (0..count)
+                    .into_par_iter()
+                    .map_init(BufferedRng::default, |rng, _| blade(rng))
+                    .collect()
+            }),
+            _ => {
+                let mut rng = BufferedRng::default();
+                (0..count).map(|_| blade(&mut rng)).collect()
+            }
+        }
+    }
+}
+
+/// Build the blade pool from environment variables
+///
+/// Optional: `MINT_THREADS` (default: the number of CPUs; 0 or 1 draws serially),
+/// `MINT_PARALLEL_THRESHOLD` (default 1024).
+pub fn load_blade_pool_from_env() -> Result<BladePool, String> {
+    let threads = match env_parse("MINT_THREADS")? {
+        Some(threads) => threads,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let threshold =
+        env_parse("MINT_PARALLEL_THRESHOLD")?.unwrap_or(DEFAULT_PARALLEL_MINT_THRESHOLD);
+    BladePool::new(threads, threshold)
+}
+
+/// Hooks for applications embedding the service, to persist or index minted identifiers in
+/// their own systems
+///
+/// Observers are called synchronously, before the mint response is sent, so they should be
+/// quick; anything slow belongs on a queue of the observer's own.
+pub trait MintObserver: Send + Sync {
+    /// ARKs minted on `shoulder` in one request, in the order they are returned
+    fn on_minted(&self, shoulder: &str, arks: &[String]);
+
+    /// A mint request on `shoulder` failed; nothing from it was issued
+    fn on_failed(&self, _shoulder: &str, _error: &AppError) {}
+}
+
+/// Mint counters for one shoulder since the service started
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ShoulderMintStats {
+    pub shoulder: String,
+    pub minted: u64,
+    /// Candidates that had already been issued and were regenerated
+    pub collisions: u64,
+    /// Identifiers minted with a longer blade after retries ran out
+    pub escalations: u64,
+    /// Identifiers that could not be minted at all
+    pub exhausted: u64,
+    /// Share of generated candidates that collided; a rising rate means the blade length is
+    /// becoming too short for the shoulder's volume
+    pub collision_rate: f64,
+    pub last_minted_at: Option<DateTime<Utc>>,
+    /// Identifiers created elsewhere and registered in the ledger
+    pub registered: u64,
+}
+
+impl ShoulderMintStats {
+    /// Identifiers taking up the shoulder's namespace, minted or registered
+    pub fn issued(&self) -> u64 {
+        self.minted + self.registered
+    }
+}
+
+/// Mint counters per shoulder
+#[derive(Debug, Default)]
+pub struct MintStats {
+    shoulders: RwLock<HashMap<String, ShoulderMintStats>>,
+}
+
+impl MintStats {
+    fn update(&self, shoulder: &str, update: impl FnOnce(&mut ShoulderMintStats)) {
+        let mut shoulders = self.shoulders.write().expect("mint stats lock poisoned");
+        let stats = shoulders
+            .entry(shoulder.to_string())
+            .or_insert_with(|| ShoulderMintStats {
+                shoulder: shoulder.to_string(),
+                ..Default::default()
+            });
+        update(stats);
+        let candidates = stats.minted + stats.collisions;
+        if candidates > 0 {
+            stats.collision_rate = stats.collisions as f64 / candidates as f64;
+        }
+    }
+
+    /// Counters for one shoulder, if it has been minted on
+    pub fn shoulder(&self, shoulder: &str) -> Option<ShoulderMintStats> {
+        self.shoulders
+            .read()
+            .expect("mint stats lock poisoned")
+            .get(shoulder)
+            .cloned()
+    }
+
+    /// Counters for every shoulder minted on so far, by shoulder
+    pub fn snapshot(&self) -> Vec<ShoulderMintStats> {
+        let mut stats: Vec<ShoulderMintStats> = self
+            .shoulders
+            .read()
+            .expect("mint stats lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        stats.sort_by(|a, b| a.shoulder.cmp(&b.shoulder));
+        stats
+    }
+}
+
+/// Mint a single new ARK with the given NAAN and shoulder, with a random blade shaped by
+/// `template`, spelled according to `format`
+pub fn mint_ark(naan: &str, shoulder: &str, template: &NoidTemplate, format: &ArkFormat) -> String {
+    mint_ark_with_rng(naan, shoulder, template, format, &mut rand::rng())
+}
+
+/// [`mint_ark`] with blades drawn from `rng` instead of the operating system's randomness
+///
+/// With a seeded generator such as `StdRng::seed_from_u64`, the same calls mint the same ARKs,
+/// for reproducible fixtures and tests. Nothing is checked against or recorded in a ledger.
+pub fn mint_ark_with_rng(
     naan: &str,
     shoulder: &str,
-    blade_length: usize,
-    uses_check_character: bool,
+    template: &NoidTemplate,
+    format: &ArkFormat,
+    rng: &mut impl Rng,
 ) -> String {
-    let blade = generate_random_blade(blade_length);
+    let blade = with_check_character(
+        shoulder,
+        template.random_blade_with(rng),
+        template.check_character(),
+    );
+    format.format(naan, shoulder, &blade, "")
+}
+
+/// Generate a random blade, with its check character if the template has one
+fn generate_blade(shoulder: &str, template: &NoidTemplate) -> String {
+    with_check_character(
+        shoulder,
+        template.random_blade(),
+        template.check_character(),
+    )
+}
 
+/// Append the blade's check character if the shoulder uses one
+pub(crate) fn with_check_character(
+    shoulder: &str,
+    mut blade: String,
+    uses_check_character: bool,
+) -> String {
     if uses_check_character {
         let identifier_for_check = format!("{}{}", shoulder, blade);
-        let check_character = calculate_check_character(&identifier_for_check);
-        format!("ark:{}/{}{}{}", naan, shoulder, blade, check_character)
-    } else {
-        format!("ark:{}/{}{}", naan, shoulder, blade)
+        blade.push(calculate_check_character(&identifier_for_check));
     }
+    blade
+}
+
+/// Generate a blade that `is_issued` does not know, following the collision policy
+///
+/// Collisions, escalations, and failures are counted in the state's mint stats. Failures to
+/// check a candidate fail the mint.
+fn generate_unique_blade(
+    state: &AppState,
+    shoulder: &str,
+    template: &NoidTemplate,
+    is_issued: impl Fn(&str) -> Result<bool, AppError>,
+) -> Result<String, AppError> {
+    let policy = &state.collision_policy;
+    let escalated = policy.escalate_blade_length.then(|| template.lengthened());
+
+    for (attempt, escalating) in
+        std::iter::once((template, false)).chain(escalated.iter().map(|longer| (longer, true)))
+    {
+        if escalating {
+            tracing::warn!(
+                shoulder = %shoulder,
+                blade_length = attempt.blade_length(),
+                "Mint retries exhausted, escalating blade length"
+            );
+        }
+        for _ in 0..=policy.max_retries {
+            let blade = generate_blade(shoulder, attempt);
+            if !is_issued(&blade)? {
+                state.mint_stats.update(shoulder, |stats| {
+                    stats.minted += 1;
+                    stats.last_minted_at = Some(Utc::now());
+                    if escalating {
+                        stats.escalations += 1;
+                    }
+                });
+                return Ok(blade);
+            }
+            state
+                .mint_stats
+                .update(shoulder, |stats| stats.collisions += 1);
+        }
+    }
+
+    tracing::error!(
+        shoulder = %shoulder,
+        blade_length = template.blade_length(),
+        max_retries = policy.max_retries,
+        "Mint failed: every candidate collided"
+    );
+    state
+        .mint_stats
+        .update(shoulder, |stats| stats.exhausted += 1);
+    Err(AppError::MintExhausted)
 }
 
 /// Mints multiple ARK identifiers for a given shoulder
@@ -31,19 +323,57 @@ pub fn mint_ark(
 ///
 /// # Returns
 /// * `Ok(Vec<String>)` - Vector of minted ARK identifiers
-/// * `Err(AppError)` - If the shoulder is not found
+/// * `Err(AppError)` - If the shoulder is not found, or no unused identifier could be generated
+///
+/// Minted ARKs are recorded in the state's mint ledger before they are returned. Candidates
+/// already in the ledger, or issued earlier in the same call, are regenerated according to the
+/// state's [`CollisionPolicy`]. The state's [`MintObserver`], if any, is told
+/// about the outcome before it is returned.
 pub fn mint_arks(state: &AppState, shoulder: &str, count: usize) -> Result<Vec<String>, AppError> {
+    mint_with_metadata(state, shoulder, count, &BTreeMap::new())
+}
+
+/// [`mint_arks`], keeping `metadata` with the minted ARKs in the ledger
+///
+/// Fresh ARKs are recorded together with their metadata, so a failed write hands out and burns
+/// none. Pooled ARKs, already recorded, are annotated, and go back to the pool if that fails.
+pub fn mint_with_metadata(
+    state: &AppState,
+    shoulder: &str,
+    count: usize,
+    metadata: &BTreeMap<String, String>,
+) -> Result<Vec<String>, AppError> {
+    let result = mint_unobserved(state, shoulder, count, metadata);
+    if let Some(observer) = &state.mint_observer {
+        match &result {
+            Ok(arks) => observer.on_minted(shoulder, arks),
+            Err(error) => observer.on_failed(shoulder, error),
+        }
+    }
+    result
+}
+
+fn mint_unobserved(
+    state: &AppState,
+    shoulder: &str,
+    count: usize,
+    metadata: &BTreeMap<String, String>,
+) -> Result<Vec<String>, AppError> {
     // Verify shoulder exists and get its configuration
-    let shoulder_config = state
-        .shoulders
-        .get(shoulder)
-        .ok_or_else(|| {
+    let (shoulder_name, shoulder_config) =
+        state.shoulders.get_key_value(shoulder).ok_or_else(|| {
             tracing::debug!(
                 shoulder = %shoulder,
                 "Mint failed: shoulder not found"
             );
             AppError::ShoulderNotFound
         })?;
+    if state.is_shortened(shoulder) {
+        return Err(AppError::InvalidRequest(format!(
+            "ARKs on {} are derived by the URL shortener and cannot be minted",
+            shoulder
+        )));
+    }
 
     // Limit count for safety
     let original_count = count;
@@ -59,55 +389,530 @@ pub fn mint_arks(state: &AppState, shoulder: &str, count: usize) -> Result<Vec<S
         );
     }
 
+    if let Some(quota) = &shoulder_config.mint_quota {
+        let now = Utc::now();
+        state
+            .mint_quotas
+            .take(shoulder, quota, count as u64, now)
+            .map_err(|exceeded| match exceeded {
+                QuotaExceeded::UsedUp { retry_after } => {
+                    tracing::warn!(
+                        shoulder = %shoulder,
+                        requested_count = count,
+                        "Mint refused: shoulder mint quota used up"
+                    );
+                    alert_mint_quota_exhausted(state, shoulder_name, retry_after);
+                    AppError::MintQuotaExceeded { retry_after }
+                }
+                QuotaExceeded::TooLarge { limit } => AppError::InvalidRequest(format!(
+                    "At most {} ARKs can be minted at once on this shoulder",
+                    limit
+                )),
+            })?;
+        let arks = mint_within_quota(state, shoulder, shoulder_config, count, metadata);
+        if arks.is_err() {
+            state.mint_quotas.give_back(shoulder, count as u64, now);
+        }
+        return arks;
+    }
+    mint_within_quota(state, shoulder, shoulder_config, count, metadata)
+}
+
+/// Emails the shoulder's contact about its used up mint quota without delaying the response
+///
+/// Outside a Tokio runtime, as in offline tools, there is no one to tell and nothing is sent.
+fn alert_mint_quota_exhausted(state: &AppState, shoulder: &ShoulderName, retry_after: Duration) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let alert = Alert::MintQuotaExhausted {
+        naan: state.naan.clone(),
+        shoulder: shoulder.clone(),
+        retry_after,
+    };
+    let notifier = state.notifier.clone();
+    let contact = state
+        .shoulders
+        .get(shoulder)
+        .and_then(|s| s.contact.as_ref())
+        .and_then(|c| c.email.clone());
+
+    runtime.spawn(async move {
+        notifier.notify(alert, contact.as_deref()).await;
+    });
+}
+
+/// Hands out `count` ARKs once the count is capped and allowed by the shoulder's quota, from
+/// the shoulder's pool if it holds enough
+fn mint_within_quota(
+    state: &AppState,
+    shoulder: &str,
+    shoulder_config: &Shoulder,
+    count: usize,
+    metadata: &BTreeMap<String, String>,
+) -> Result<Vec<String>, AppError> {
+    let Some(arks) = state.mint_pools.take(shoulder, count) else {
+        return mint_fresh(state, shoulder, shoulder_config, count, metadata);
+    };
+    tracing::debug!(
+        shoulder = %shoulder,
+        count = count,
+        "Handing out pooled ARKs"
+    );
+    if metadata.is_empty() {
+        return Ok(arks);
+    }
+    let canonicals: Vec<String> = arks
+        .iter()
+        .map(|ark| parse_ark(ark).map_or(ark.clone(), |parsed| parsed.canonical().to_string()))
+        .collect();
+    if let Err(e) = state.store.annotate(&canonicals, metadata) {
+        tracing::error!(shoulder = %shoulder, error = %e, "Mint failed: metadata not kept");
+        state.mint_pools.add(shoulder, arks);
+        return Err(AppError::from(e));
+    }
+    Ok(arks)
+}
+
+/// Checks metadata sent with a mint request: keys must not be empty, and keys and values
+/// together fit in [`MAX_MINT_METADATA_BYTES`]
+pub fn validate_mint_metadata(metadata: &BTreeMap<String, String>) -> Result<(), String> {
+    if metadata.keys().any(String::is_empty) {
+        return Err("metadata keys must not be empty".to_string());
+    }
+    let size: usize = metadata
+        .iter()
+        .map(|(key, value)| key.len() + value.len())
+        .sum();
+    if size > MAX_MINT_METADATA_BYTES {
+        return Err(format!(
+            "metadata must fit in {} bytes of keys and values",
+            MAX_MINT_METADATA_BYTES
+        ));
+    }
+    Ok(())
+}
+
+/// Tops up every shoulder's pool, if pools are enabled
+///
+/// A shoulder whose mint fails is left for the next round, without holding up the others.
+pub fn refill_pools(state: &AppState) {
+    // Nothing is added to the ledger while it may be migrating
+    let Some(_write) = state.read_only.begin_write() else {
+        return;
+    };
+    for (shoulder, shoulder_config) in &state.shoulders {
+        let shoulder = shoulder.as_str();
+        let shortfall = state.mint_pools.shortfall(shoulder);
+        if shortfall == 0 || state.is_shortened(shoulder) {
+            continue;
+        }
+        match mint_fresh(
+            state,
+            shoulder,
+            shoulder_config,
+            shortfall,
+            &BTreeMap::new(),
+        ) {
+            Ok(arks) => {
+                tracing::debug!(
+                    shoulder = %shoulder,
+                    count = arks.len(),
+                    "Mint pool refilled"
+                );
+                state.mint_pools.add(shoulder, arks);
+            }
+            Err(error) => tracing::warn!(
+                shoulder = %shoulder,
+                error = ?error,
+                "Failed to refill mint pool"
+            ),
+        }
+    }
+}
+
+/// Mints `count` new ARKs, recording them in the ledger with `metadata`
+fn mint_fresh(
+    state: &AppState,
+    shoulder: &str,
+    shoulder_config: &Shoulder,
+    count: usize,
+    metadata: &BTreeMap<String, String>,
+) -> Result<Vec<String>, AppError> {
     // Use shoulder-specific blade length if configured, otherwise use default
-    let blade_length = shoulder_config
-        .blade_length
-        .unwrap_or(state.default_blade_length);
+    let blade_length = state.blade_length_for(shoulder, shoulder_config);
+
+    let template = state.blade_template_for(shoulder_config, blade_length);
 
     tracing::debug!(
         shoulder = %shoulder,
         count = count,
-        blade_length = blade_length,
-        uses_check_character = shoulder_config.uses_check_character,
+        template = %template,
         "Minting ARKs"
     );
 
+    let mint = Mint {
+        state,
+        shoulder,
+        template: &template,
+        format: state.ark_format_for(shoulder_config),
+        metadata,
+    };
+
     // Generate ARKs with or without check characters based on shoulder config
-    let arks: Vec<String> = (0..count)
-        .map(|_| {
-            mint_ark(
-                &state.naan,
-                shoulder,
-                blade_length,
-                shoulder_config.uses_check_character,
-            )
-        })
-        .collect();
+    let mut issued = HashSet::with_capacity(count);
+    let arks = mint
+        .new_blades(count, &mut issued)
+        .and_then(|blades| mint.record(blades, &mut issued));
 
-    Ok(arks)
+    // Reconsider the blade length now that the shoulder's counters changed, also after failures;
+    // a template fixes it
+    if shoulder_config.template.is_none()
+        && let Some(stats) = state.mint_stats.shoulder(shoulder)
+    {
+        let alphabet_size = shoulder_config.alphabet.characters().len();
+        let advice = state
+            .blade_advisor
+            .advise(blade_length, alphabet_size, &stats);
+        state.blade_advisor.apply(&advice, &state.config_store);
+    }
+
+    arks
+}
+
+/// One mint request's shoulder settings
+struct Mint<'a> {
+    state: &'a AppState,
+    shoulder: &'a str,
+    template: &'a NoidTemplate,
+    format: &'a ArkFormat,
+    /// Kept with each ARK in the ledger
+    metadata: &'a BTreeMap<String, String>,
+}
+
+impl Mint<'_> {
+    fn ark(&self, blade: &str) -> String {
+        self.format
+            .format(&self.state.naan, self.shoulder, blade, "")
+    }
+
+    /// Key of the blade's ARK in the ledger
+    fn canonical(&self, blade: &str) -> String {
+        let ark = self.ark(blade);
+        parse_ark(&ark).map_or(ark, |parsed| parsed.canonical().to_string())
+    }
+
+    /// `count` blades that are neither among `issued` nor in the ledger, added to `issued`
+    fn new_blades(
+        &self,
+        count: usize,
+        issued: &mut HashSet<String>,
+    ) -> Result<Vec<String>, AppError> {
+        let blades = match self.template.generator() {
+            Generator::Random => self.random_blades(count, issued)?,
+            Generator::Sequential | Generator::Unbounded => {
+                let blades = self.sequential_blades(count)?;
+                issued.extend(blades.iter().cloned());
+                blades
+            }
+        };
+        Ok(blades)
+    }
+
+    /// The next `count` blades of the shoulder's sequential counter
+    ///
+    /// Positions whose ARK is already in the ledger, for instance imported from NOID, are
+    /// skipped. Past the last blade of the template, the mint fails. With a node partition, the
+    /// counter is this node's own, stepping over the other nodes' positions.
+    fn sequential_blades(&self, count: usize) -> Result<Vec<String>, AppError> {
+        let partition = self.state.node_partition;
+        let counter = partition.sequence_key(self.shoulder);
+        let mut blades = Vec::with_capacity(count);
+        while blades.len() < count {
+            let wanted = (count - blades.len()) as u64;
+            let first = self
+                .state
+                .store
+                .reserve_sequence(&counter, wanted)
+                .map_err(|e| self.storage_failed(e))?;
+            for local in first..first + wanted {
+                let next = partition.position(local).and_then(|position| {
+                    Some((position, self.template.sequential_blade(position)?))
+                });
+                let Some((position, blade)) = next else {
+                    tracing::error!(
+                        shoulder = %self.shoulder,
+                        template = %self.template,
+                        "Mint failed: sequential counter reached the end of the namespace"
+                    );
+                    self.state
+                        .mint_stats
+                        .update(self.shoulder, |stats| stats.exhausted += 1);
+                    return Err(AppError::MintExhausted);
+                };
+                let blade =
+                    with_check_character(self.shoulder, blade, self.template.check_character());
+                let issued = self
+                    .state
+                    .store
+                    .contains(&self.canonical(&blade))
+                    .map_err(|e| self.storage_failed(e))?;
+                if issued {
+                    tracing::debug!(
+                        shoulder = %self.shoulder,
+                        position = position,
+                        "Sequential blade already issued, skipping"
+                    );
+                    continue;
+                }
+                self.state.mint_stats.update(self.shoulder, |stats| {
+                    stats.minted += 1;
+                    stats.last_minted_at = Some(Utc::now());
+                });
+                blades.push(blade);
+            }
+        }
+        Ok(blades)
+    }
+
+    /// `count` random blades that are neither among `issued` nor in the ledger, added to
+    /// `issued`
+    ///
+    /// The candidates are drawn at once, on the blade pool for large mints. Each one already
+    /// issued is replaced following the collision policy.
+    fn random_blades(
+        &self,
+        count: usize,
+        issued: &mut HashSet<String>,
+    ) -> Result<Vec<String>, AppError> {
+        let candidates = self
+            .state
+            .blade_pool
+            .random_blades(self.shoulder, self.template, count);
+        let mut blades = Vec::with_capacity(count);
+        let mut fresh = 0;
+        for candidate in candidates {
+            let blade = if self.is_issued(&candidate, issued)? {
+                self.state
+                    .mint_stats
+                    .update(self.shoulder, |stats| stats.collisions += 1);
+                generate_unique_blade(self.state, self.shoulder, self.template, |blade| {
+                    self.is_issued(blade, issued)
+                })?
+            } else {
+                fresh += 1;
+                candidate
+            };
+            issued.insert(blade.clone());
+            blades.push(blade);
+        }
+        if fresh > 0 {
+            self.state.mint_stats.update(self.shoulder, |stats| {
+                stats.minted += fresh;
+                stats.last_minted_at = Some(Utc::now());
+            });
+        }
+        Ok(blades)
+    }
+
+    /// Whether the blade is among `issued` or its ARK in the ledger
+    fn is_issued(&self, blade: &str, issued: &HashSet<String>) -> Result<bool, AppError> {
+        if issued.contains(blade) {
+            return Ok(true);
+        }
+        self.state
+            .store
+            .contains(&self.canonical(blade))
+            .map_err(|e| self.storage_failed(e))
+    }
+
+    /// Records the blades' ARKs in the ledger and returns them
+    ///
+    /// ARKs that a concurrent mint recorded since they were checked are replaced, and the
+    /// replacements recorded in turn, for at most `max_retries` rounds. If recording fails,
+    /// none of the ARKs are handed out.
+    fn record(
+        &self,
+        mut blades: Vec<String>,
+        issued: &mut HashSet<String>,
+    ) -> Result<Vec<String>, AppError> {
+        let mut pending: Vec<usize> = (0..blades.len()).collect();
+        for _ in 0..=self.state.collision_policy.max_retries {
+            let arks: Vec<String> = pending
+                .iter()
+                .map(|&i| self.canonical(&blades[i]))
+                .collect();
+            let taken: HashSet<String> = self
+                .state
+                .store
+                .record(self.shoulder, &arks, self.metadata)
+                .map_err(|e| self.storage_failed(e))?
+                .into_iter()
+                .collect();
+            if taken.is_empty() {
+                return Ok(blades.iter().map(|blade| self.ark(blade)).collect());
+            }
+
+            pending.retain(|&i| taken.contains(&self.canonical(&blades[i])));
+            tracing::warn!(
+                shoulder = %self.shoulder,
+                taken = pending.len(),
+                "Minted ARKs were issued concurrently, regenerating"
+            );
+            for &i in &pending {
+                self.state.mint_stats.update(self.shoulder, |stats| {
+                    stats.minted -= 1;
+                    stats.collisions += 1;
+                });
+                blades[i] = self.new_blades(1, issued)?.remove(0);
+            }
+        }
+
+        tracing::error!(
+            shoulder = %self.shoulder,
+            "Mint failed: ledger kept reporting concurrent mints"
+        );
+        self.state
+            .mint_stats
+            .update(self.shoulder, |stats| stats.exhausted += 1);
+        Err(AppError::MintExhausted)
+    }
+
+    fn storage_failed(&self, error: StorageError) -> AppError {
+        tracing::error!(shoulder = %self.shoulder, error = %error, "Mint failed: ledger unavailable");
+        AppError::from(error)
+    }
+}
+
+/// Records ARKs created outside the service in the ledger, so minting avoids them and they count
+/// towards their shoulders' stats
+///
+/// The ARKs must already be validated against the configured shoulders. Returns the normalized
+/// forms of the ARKs newly recorded and of those the ledger already held, each in the order
+/// given; repeats within `arks` count as already held.
+pub fn register_arks(
+    state: &AppState,
+    arks: &[Ark],
+) -> Result<(Vec<String>, Vec<String>), AppError> {
+    let mut seen = HashSet::with_capacity(arks.len());
+    let mut by_shoulder: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for ark in arks {
+        if seen.insert(ark.canonical()) {
+            by_shoulder
+                .entry(ark.shoulder.as_str())
+                .or_default()
+                .push(ark.canonical().to_string());
+        }
+    }
+
+    let mut taken = HashSet::new();
+    for (shoulder, canonicals) in &by_shoulder {
+        let already = state.store.record(shoulder, canonicals, &BTreeMap::new()).map_err(|e| {
+            tracing::error!(shoulder = %shoulder, error = %e, "Register failed: ledger unavailable");
+            AppError::from(e)
+        })?;
+        let recorded = (canonicals.len() - already.len()) as u64;
+        state
+            .mint_stats
+            .update(shoulder, |stats| stats.registered += recorded);
+        taken.extend(already);
+    }
+
+    let mut registered = Vec::new();
+    let mut already_registered = Vec::new();
+    let mut first = HashSet::with_capacity(arks.len());
+    for ark in arks {
+        let canonical = ark.canonical().to_string();
+        if first.insert(ark.canonical()) && !taken.contains(&canonical) {
+            registered.push(canonical);
+        } else {
+            already_registered.push(canonical);
+        }
+    }
+    Ok((registered, already_registered))
 }
 
-/// Generate a random blade using betanumeric characters
-fn generate_random_blade(blade_length: usize) -> String {
-    let mut rng = rand::rng();
-    (0..blade_length)
-        .map(|_| {
-            let idx = rng.random_range(0..BETANUMERIC.len());
-            BETANUMERIC[idx] as char
+/// ARKs shaped like the ones `shoulder` mints, for trying out its route pattern without issuing
+/// anything
+///
+/// Nothing is counted or recorded. Candidates the mint ledger already holds are skipped, so
+/// examples do not name real identifiers while the ledger can be read. Sequential shoulders
+/// show the ARKs their counter would issue next, without reserving them.
+pub fn synthesize_arks(
+    state: &AppState,
+    shoulder: &str,
+    count: usize,
+) -> Result<Vec<String>, AppError> {
+    let shoulder_config = state
+        .shoulders
+        .get(shoulder)
+        .ok_or(AppError::ShoulderNotFound)?;
+    let blade_length = state.blade_length_for(shoulder, shoulder_config);
+    let template = state.blade_template_for(shoulder_config, blade_length);
+    let format = state.ark_format_for(shoulder_config);
+
+    if template.generator() != Generator::Random {
+        let partition = state.node_partition;
+        let next = state
+            .store
+            .sequence_position(&partition.sequence_key(shoulder))?;
+        return Ok((next..)
+            .map_while(|local| template.sequential_blade(partition.position(local)?))
+            .take(count)
+            .map(|blade| {
+                let blade = with_check_character(shoulder, blade, template.check_character());
+                format.format(&state.naan, shoulder, &blade, "")
+            })
+            .collect());
+    }
+
+    let is_issued = |ark: &str| {
+        parse_ark(ark).is_some_and(|ark| state.store.contains(ark.canonical()).unwrap_or(false))
+    };
+
+    Ok((0..count)
+        .filter_map(|_| {
+            (0..=state.collision_policy.max_retries)
+                .map(|_| {
+                    let blade = generate_blade(shoulder, &template);
+                    format.format(&state.naan, shoulder, &blade, "")
+                })
+                .find(|ark| !is_issued(ark))
         })
-        .collect()
+        .collect())
+}
+
+/// Build the collision policy from environment variables
+///
+/// Optional: `MINT_MAX_RETRIES` (default 10), `MINT_ESCALATE_BLADE_LENGTH` (default false).
+pub fn load_collision_policy_from_env() -> Result<CollisionPolicy, String> {
+    let defaults = CollisionPolicy::default();
+    Ok(CollisionPolicy {
+        max_retries: env_parse("MINT_MAX_RETRIES")?.unwrap_or(defaults.max_retries),
+        escalate_blade_length: env_parse("MINT_ESCALATE_BLADE_LENGTH")?
+            .unwrap_or(defaults.escalate_blade_length),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ark::parse_ark, config::BETANUMERIC, shoulder::Shoulder};
+    use crate::blade_advisor::{AdvisorPolicy, BladeLengthAdvisor};
+    use crate::ledger::MintLedger;
+    use crate::mint_pool::MintPools;
+    use crate::partition::NodePartition;
+    use crate::shoulder::Minter;
+    use crate::testing::InterceptedStore;
+    use crate::{ark::parse_ark, config::BETANUMERIC};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     fn create_test_state(uses_check_character: bool) -> AppState {
         let mut shoulders = HashMap::new();
         shoulders.insert(
-            "x6".to_string(),
+            "x6".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://example.org/${value}".to_string(),
                 project_name: "Test Project".to_string(),
@@ -117,10 +922,11 @@ mod tests {
         );
 
         AppState {
-            naan: "12345".to_string(),
+            naan: "12345".parse().unwrap(),
             default_blade_length: 8,
             max_mint_count: 1000,
             shoulders,
+            ..Default::default()
         }
     }
 
@@ -135,6 +941,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hands_out_pooled_arks() {
+        let state = AppState {
+            mint_pools: MintPools::new(5, std::time::Duration::from_secs(1)).unwrap(),
+            ..create_test_state(true)
+        };
+        refill_pools(&state);
+        assert_eq!(state.store.minted().unwrap(), 5);
+        assert_eq!(state.mint_pools.shortfall("x6"), 0);
+
+        // Pooled ARKs are already in the ledger, so handing them out records nothing new
+        let pooled = mint_arks(&state, "x6", 3).unwrap();
+        assert_eq!(state.store.minted().unwrap(), 5);
+        for ark in &pooled {
+            assert!(
+                state
+                    .store
+                    .contains(parse_ark(ark).unwrap().canonical())
+                    .unwrap()
+            );
+        }
+        // Requests larger than what is left are minted afresh
+        let fresh = mint_arks(&state, "x6", 4).unwrap();
+        assert_eq!(state.store.minted().unwrap(), 9);
+        assert!(fresh.iter().all(|ark| !pooled.contains(ark)));
+
+        refill_pools(&state);
+        assert_eq!(state.store.minted().unwrap(), 12);
+        let rest = mint_arks(&state, "x6", 5).unwrap();
+        assert!(rest.iter().all(|ark| !pooled.contains(ark)));
+        assert_eq!(state.store.minted().unwrap(), 12);
+    }
+
+    #[test]
+    fn mints_large_batches_in_parallel() {
+        let state = AppState {
+            blade_pool: BladePool::new(4, 64).unwrap(),
+            ..create_test_state(true)
+        };
+        let arks = mint_arks(&state, "x6", 1000).unwrap();
+        assert_eq!(arks.iter().collect::<HashSet<_>>().len(), 1000);
+        assert_eq!(state.store.minted().unwrap(), 1000);
+        assert_eq!(state.mint_stats.shoulder("x6").unwrap().minted, 1000);
+
+        // In a crowded namespace, candidates drawn together collide and are replaced
+        let crowded = AppState {
+            default_blade_length: 2,
+            collision_policy: CollisionPolicy {
+                max_retries: 1000,
+                escalate_blade_length: false,
+            },
+            ..state
+        };
+        let arks = mint_arks(&crowded, "x6", 200).unwrap();
+        assert_eq!(arks.iter().collect::<HashSet<_>>().len(), 200);
+        let stats = crowded.mint_stats.shoulder("x6").unwrap();
+        assert_eq!(stats.minted, 1200);
+        assert!(stats.collisions > 0);
+    }
+
+    #[test]
+    fn keeps_mint_metadata_in_the_ledger() {
+        let state = AppState {
+            mint_pools: MintPools::new(2, std::time::Duration::from_secs(1)).unwrap(),
+            ..create_test_state(true)
+        };
+        refill_pools(&state);
+        let metadata = BTreeMap::from([
+            ("creator".to_string(), "digitization".to_string()),
+            ("batch".to_string(), "2026-10 scans".to_string()),
+        ]);
+        // Pooled ARKs get the metadata when they are handed out, as do fresh ones
+        let pooled = mint_with_metadata(&state, "x6", 2, &metadata).unwrap();
+        let fresh = mint_with_metadata(&state, "x6", 3, &metadata).unwrap();
+        mint_arks(&state, "x6", 1).unwrap();
+
+        let entries = state.store.entries(None, None, 10).unwrap().entries;
+        let annotated: HashSet<&str> = entries
+            .iter()
+            .filter(|entry| entry.metadata == metadata)
+            .map(|entry| entry.ark.as_str())
+            .collect();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(annotated.len(), 5);
+        assert!(
+            pooled
+                .iter()
+                .chain(&fresh)
+                .all(|ark| annotated.contains(ark.as_str()))
+        );
+
+        assert!(validate_mint_metadata(&metadata).is_ok());
+        let empty_key = BTreeMap::from([(String::new(), "x".to_string())]);
+        assert!(validate_mint_metadata(&empty_key).is_err());
+        let large = BTreeMap::from([("note".to_string(), "x".repeat(MAX_MINT_METADATA_BYTES))]);
+        assert!(validate_mint_metadata(&large).is_err());
+    }
+
     #[test]
     fn enforces_maximum_count_limit() {
         let state = create_test_state(true);
@@ -151,9 +1055,51 @@ mod tests {
         assert!(matches!(result, Err(AppError::ShoulderNotFound)));
     }
 
+    #[derive(Default)]
+    struct RecordingObserver {
+        minted: std::sync::Mutex<Vec<(String, Vec<String>)>>,
+        failed: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl MintObserver for RecordingObserver {
+        fn on_minted(&self, shoulder: &str, arks: &[String]) {
+            self.minted
+                .lock()
+                .unwrap()
+                .push((shoulder.to_string(), arks.to_vec()));
+        }
+
+        fn on_failed(&self, shoulder: &str, _error: &AppError) {
+            self.failed.lock().unwrap().push(shoulder.to_string());
+        }
+    }
+
+    #[test]
+    fn notifies_mint_observer() {
+        let observer = std::sync::Arc::new(RecordingObserver::default());
+        let state = AppState {
+            mint_observer: Some(observer.clone()),
+            ..create_test_state(true)
+        };
+
+        let arks = mint_arks(&state, "x6", 3).unwrap();
+        assert!(mint_arks(&state, "z9", 1).is_err());
+
+        assert_eq!(
+            *observer.minted.lock().unwrap(),
+            vec![("x6".to_string(), arks)]
+        );
+        assert_eq!(*observer.failed.lock().unwrap(), vec!["z9".to_string()]);
+    }
+
     #[test]
     fn mints_ark_with_check_character() {
-        let ark = mint_ark("12345", "x6", 8, true);
+        let ark = mint_ark(
+            "12345",
+            "x6",
+            &NoidTemplate::flat(Generator::Random, 8, true),
+            &ArkFormat::default(),
+        );
 
         assert!(ark.starts_with("ark:12345/x6"));
         assert_eq!(ark.len(), "ark:12345/x6".len() + 9); // 8 blade + 1 check
@@ -164,9 +1110,39 @@ mod tests {
         assert_eq!(parsed.blade.len(), 9);
     }
 
+    #[test]
+    fn seeded_rng_mints_the_same_arks() {
+        let template = NoidTemplate::flat(Generator::Random, 8, true);
+        let mint = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..5)
+                .map(|_| {
+                    mint_ark_with_rng("12345", "x6", &template, &ArkFormat::default(), &mut rng)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let arks = mint(42);
+        assert_eq!(arks, mint(42));
+        assert_ne!(arks, mint(43));
+        for ark in &arks {
+            let parsed = parse_ark(ark).unwrap();
+            assert_eq!(parsed.blade.len(), 9);
+            assert!(crate::check_character::validate_check_character(&format!(
+                "x6{}",
+                parsed.blade
+            )));
+        }
+    }
+
     #[test]
     fn mints_ark_without_check_character() {
-        let ark = mint_ark("12345", "x6", 8, false);
+        let ark = mint_ark(
+            "12345",
+            "x6",
+            &NoidTemplate::flat(Generator::Random, 8, false),
+            &ArkFormat::default(),
+        );
 
         assert!(ark.starts_with("ark:12345/x6"));
         assert_eq!(ark.len(), "ark:12345/x6".len() + 8); // 8 blade only
@@ -177,10 +1153,39 @@ mod tests {
         assert_eq!(parsed.blade.len(), 8);
     }
 
+    #[test]
+    fn applies_shoulder_ark_format() {
+        let mut state = create_test_state(false);
+        state.shoulders.get_mut("x6").unwrap().ark_format = Some(ArkFormat {
+            slash: true,
+            hyphen_group: Some(4),
+            ..Default::default()
+        });
+
+        let arks = mint_arks(&state, "x6", 1).unwrap();
+        assert!(arks[0].starts_with("ark:/12345/x6"));
+        // 8 blade characters in two hyphenated groups
+        assert_eq!(arks[0].len(), "ark:/12345/x6".len() + 9);
+        assert_eq!(arks[0].matches('-').count(), 1);
+    }
+
+    #[test]
+    fn applies_global_ark_format() {
+        let mut state = create_test_state(true);
+        state.ark_format = ArkFormat {
+            slash: true,
+            ..Default::default()
+        };
+
+        let arks = mint_arks(&state, "x6", 1).unwrap();
+        assert!(arks[0].starts_with("ark:/12345/x6"));
+    }
+
     #[test]
     fn generates_random_betanumeric_blades() {
-        let blade1 = generate_random_blade(8);
-        let blade2 = generate_random_blade(8);
+        let template = NoidTemplate::flat(Generator::Random, 8, false);
+        let blade1 = template.random_blade();
+        let blade2 = template.random_blade();
 
         assert_eq!(blade1.len(), 8);
         assert_eq!(blade2.len(), 8);
@@ -196,17 +1201,18 @@ mod tests {
         let mut shoulders = HashMap::new();
         // Shoulder with custom blade length
         shoulders.insert(
-            "x6".to_string(),
+            "x6".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://example.org/${value}".to_string(),
                 project_name: "Custom Length Project".to_string(),
                 uses_check_character: false,
                 blade_length: Some(12),
+                ..Default::default()
             },
         );
         // Shoulder using default blade length
         shoulders.insert(
-            "b3".to_string(),
+            "b3".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://example.org/${value}".to_string(),
                 project_name: "Default Length Project".to_string(),
@@ -216,10 +1222,11 @@ mod tests {
         );
 
         let state = AppState {
-            naan: "12345".to_string(),
+            naan: "12345".parse().unwrap(),
             default_blade_length: 8,
             max_mint_count: 1000,
             shoulders,
+            ..Default::default()
         };
 
         // Test shoulder with custom blade length (12 characters)
@@ -239,7 +1246,7 @@ mod tests {
     fn uses_shoulder_blade_length_with_check_character() {
         let mut shoulders = HashMap::new();
         shoulders.insert(
-            "fk4".to_string(),
+            "fk4".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://example.org/${value}".to_string(),
                 project_name: "Custom Length with Check".to_string(),
@@ -249,10 +1256,11 @@ mod tests {
         );
 
         let state = AppState {
-            naan: "99999".to_string(),
+            naan: "99999".parse().unwrap(),
             default_blade_length: 8,
             max_mint_count: 1000,
             shoulders,
+            ..Default::default()
         };
 
         let arks = mint_arks(&state, "fk4", 1).unwrap();
@@ -263,4 +1271,276 @@ mod tests {
         assert_eq!(parsed.naan, "99999");
         assert_eq!(parsed.shoulder, "fk4");
     }
+
+    fn tiny_namespace_state(policy: CollisionPolicy) -> AppState {
+        // 29 possible one-character blades
+        let mut state = create_test_state(false);
+        state.default_blade_length = 1;
+        state.collision_policy = policy;
+        state
+    }
+
+    #[test]
+    fn regenerates_colliding_blades() {
+        let state = tiny_namespace_state(CollisionPolicy {
+            max_retries: 1000,
+            escalate_blade_length: false,
+        });
+        let arks = mint_arks(&state, "x6", BETANUMERIC.len()).unwrap();
+
+        let unique: HashSet<&String> = arks.iter().collect();
+        assert_eq!(unique.len(), BETANUMERIC.len());
+        let stats = &state.mint_stats.snapshot()[0];
+        assert_eq!(stats.minted, BETANUMERIC.len() as u64);
+        assert!(stats.collisions > 0);
+        assert!(stats.collision_rate > 0.0);
+    }
+
+    #[test]
+    fn fails_or_escalates_when_namespace_is_full() {
+        let count = BETANUMERIC.len() + 1;
+        let state = tiny_namespace_state(CollisionPolicy {
+            max_retries: 1000,
+            escalate_blade_length: false,
+        });
+        assert!(matches!(
+            mint_arks(&state, "x6", count),
+            Err(AppError::MintExhausted)
+        ));
+        assert_eq!(state.mint_stats.snapshot()[0].exhausted, 1);
+
+        let state = tiny_namespace_state(CollisionPolicy {
+            max_retries: 1000,
+            escalate_blade_length: true,
+        });
+        let arks = mint_arks(&state, "x6", count).unwrap();
+        let longer = arks
+            .iter()
+            .filter(|ark| parse_ark(ark).unwrap().blade.len() == 2)
+            .count();
+        assert_eq!(longer, 1);
+        assert_eq!(state.mint_stats.snapshot()[0].escalations, 1);
+    }
+
+    #[test]
+    fn switches_to_recommended_blade_length_when_auto_escalating() {
+        let mut state = tiny_namespace_state(CollisionPolicy::default());
+        state.blade_advisor = BladeLengthAdvisor::new(AdvisorPolicy {
+            auto_escalate: true,
+            ..Default::default()
+        });
+
+        let first = mint_arks(&state, "x6", 1).unwrap();
+        assert_eq!(parse_ark(&first[0]).unwrap().blade.len(), 1);
+        let second = mint_arks(&state, "x6", 1).unwrap();
+        assert_eq!(parse_ark(&second[0]).unwrap().blade.len(), 8);
+
+        let escalations = state.blade_advisor.escalations();
+        assert_eq!(escalations.len(), 1);
+        assert_eq!((escalations[0].from, escalations[0].to), (1, 8));
+    }
+
+    #[test]
+    fn encodes_sequential_positions_in_betanumeric() {
+        let sequential_blade = |position, blade_length| {
+            NoidTemplate::flat(Generator::Sequential, blade_length, false)
+                .sequential_blade(position)
+        };
+        assert_eq!(sequential_blade(0, 4).unwrap(), "0000");
+        assert_eq!(sequential_blade(10, 4).unwrap(), "000b");
+        assert_eq!(sequential_blade(29, 4).unwrap(), "0010");
+        assert_eq!(sequential_blade(28, 1).unwrap(), "z");
+        assert_eq!(sequential_blade(29, 1), None);
+        assert_eq!(sequential_blade(u64::MAX, 14).unwrap().len(), 14);
+    }
+
+    #[test]
+    fn mints_sequential_blades_in_order() {
+        let mut state = create_test_state(false);
+        state.default_blade_length = 2;
+        state.shoulders.get_mut("x6").unwrap().minter = Minter::Sequential;
+        // Issued by NOID before the migration
+        state
+            .store
+            .record("x6", &["ark:12345/x603".to_string()], &BTreeMap::new())
+            .unwrap();
+
+        let first = mint_arks(&state, "x6", 3).unwrap();
+        assert_eq!(
+            first,
+            ["ark:12345/x600", "ark:12345/x601", "ark:12345/x602"]
+        );
+        let second = mint_arks(&state, "x6", 2).unwrap();
+        assert_eq!(second, ["ark:12345/x604", "ark:12345/x605"]);
+        assert_eq!(state.store.sequence_position("x6").unwrap(), 6);
+        assert_eq!(state.mint_stats.snapshot()[0].collisions, 0);
+
+        state.store.reserve_sequence("x6", 29 * 29 - 7).unwrap();
+        assert_eq!(mint_arks(&state, "x6", 1).unwrap(), ["ark:12345/x6zz"]);
+        assert!(matches!(
+            mint_arks(&state, "x6", 1),
+            Err(AppError::MintExhausted)
+        ));
+    }
+
+    #[test]
+    fn partitioned_nodes_mint_disjoint_arks() {
+        let node = |node, minter| {
+            let mut state = AppState {
+                default_blade_length: 2,
+                node_partition: NodePartition::new(node, 2).unwrap(),
+                ..create_test_state(false)
+            };
+            state.shoulders.get_mut("x6").unwrap().minter = minter;
+            state
+        };
+
+        // Each node has a store of its own
+        let (first, second) = (node(0, Minter::Random), node(1, Minter::Random));
+        let first: HashSet<String> = mint_arks(&first, "x6", 100).unwrap().into_iter().collect();
+        let second: HashSet<String> = mint_arks(&second, "x6", 100).unwrap().into_iter().collect();
+        assert_eq!((first.len(), second.len()), (100, 100));
+        assert!(first.is_disjoint(&second));
+
+        let (first, second) = (node(0, Minter::Sequential), node(1, Minter::Sequential));
+        assert_eq!(
+            mint_arks(&first, "x6", 3).unwrap(),
+            ["ark:12345/x600", "ark:12345/x602", "ark:12345/x604"]
+        );
+        assert_eq!(
+            mint_arks(&second, "x6", 3).unwrap(),
+            ["ark:12345/x601", "ark:12345/x603", "ark:12345/x605"]
+        );
+        assert_eq!(second.store.sequence_position("x6#1").unwrap(), 3);
+    }
+
+    #[test]
+    fn appends_check_character_to_sequential_blades() {
+        let mut state = create_test_state(true);
+        state.shoulders.get_mut("x6").unwrap().minter = Minter::Sequential;
+
+        let arks = mint_arks(&state, "x6", 2).unwrap();
+        let first = parse_ark(&arks[0]).unwrap();
+        assert_eq!(&first.blade[..8], "00000000");
+        assert_eq!(first.blade.len(), 9);
+        assert!(arks[1].contains("/x600000001"));
+        assert_eq!(synthesize_arks(&state, "x6", 1).unwrap()[0], {
+            let blade = with_check_character("x6", "00000002".to_string(), true);
+            format!("ark:12345/x6{}", blade)
+        });
+    }
+
+    #[test]
+    fn mints_blades_shaped_by_template() {
+        let mut state = create_test_state(false);
+        state.shoulders.get_mut("x6").unwrap().template = Some("x6.rdedk".parse().unwrap());
+
+        for ark in mint_arks(&state, "x6", 20).unwrap() {
+            let blade = parse_ark(&ark).unwrap().blade;
+            let chars: Vec<char> = blade.chars().collect();
+            assert_eq!(chars.len(), 4);
+            assert!(chars[0].is_ascii_digit() && chars[2].is_ascii_digit());
+            assert_eq!(
+                with_check_character("x6", blade[..3].to_string(), true),
+                blade
+            );
+        }
+
+        state.shoulders.get_mut("x6").unwrap().template = Some(".zd".parse().unwrap());
+        state.store.reserve_sequence("x6", 9).unwrap();
+        assert_eq!(
+            mint_arks(&state, "x6", 2).unwrap(),
+            ["ark:12345/x69", "ark:12345/x610"]
+        );
+    }
+
+    #[test]
+    fn records_minted_arks_in_ledger() {
+        let state = create_test_state(true);
+        let arks = mint_arks(&state, "x6", 3).unwrap();
+        assert_eq!(state.store.minted().unwrap(), 3);
+        for ark in &arks {
+            let canonical = parse_ark(ark).unwrap().canonical().to_string();
+            assert!(state.store.contains(&canonical).unwrap());
+        }
+    }
+
+    #[test]
+    fn avoids_arks_already_in_ledger() {
+        let state = tiny_namespace_state(CollisionPolicy {
+            max_retries: 1000,
+            escalate_blade_length: false,
+        });
+        // Issued before a restart: all but the last blade
+        let issued: Vec<String> = BETANUMERIC[..BETANUMERIC.len() - 1]
+            .iter()
+            .map(|c| format!("ark:12345/x6{}", *c as char))
+            .collect();
+        state.store.record("x6", &issued, &BTreeMap::new()).unwrap();
+
+        let arks = mint_arks(&state, "x6", 1).unwrap();
+        let last = *BETANUMERIC.last().unwrap() as char;
+        assert_eq!(arks, [format!("ark:12345/x6{}", last)]);
+        assert!(matches!(
+            mint_arks(&state, "x6", 1),
+            Err(AppError::MintExhausted)
+        ));
+    }
+
+    #[test]
+    fn regenerates_arks_minted_concurrently() {
+        // Another mint records the first ARK of the first batch just before it
+        let raced = std::sync::OnceLock::new();
+        let store = InterceptedStore::on_record(move |inner, shoulder, arks, metadata| {
+            raced.get_or_init(|| {
+                inner.record(shoulder, &arks[..1], metadata).unwrap();
+            });
+            inner.record(shoulder, arks, metadata)
+        });
+        let state = AppState {
+            store: Arc::new(store),
+            ..create_test_state(true)
+        };
+        let arks = mint_arks(&state, "x6", 3).unwrap();
+
+        // The other mint's ARK plus the three returned
+        assert_eq!(state.store.minted().unwrap(), 4);
+        assert_eq!(arks.iter().collect::<HashSet<_>>().len(), 3);
+        let stats = &state.mint_stats.snapshot()[0];
+        assert_eq!((stats.minted, stats.collisions), (3, 1));
+    }
+
+    #[test]
+    fn records_mint_metadata_with_the_arks() {
+        // Every write to the ledger carries the metadata; nothing is annotated afterwards
+        let store = InterceptedStore::on_record(|inner, shoulder, arks, metadata| {
+            assert_eq!(metadata["creator"], "digitization");
+            inner.record(shoulder, arks, metadata)
+        });
+        let state = AppState {
+            store: Arc::new(store),
+            ..create_test_state(true)
+        };
+        let metadata = BTreeMap::from([("creator".to_string(), "digitization".to_string())]);
+        mint_with_metadata(&state, "x6", 3, &metadata).unwrap();
+
+        let entries = state.store.entries(None, None, 10).unwrap().entries;
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.metadata == metadata));
+    }
+
+    #[test]
+    fn fails_mint_when_ledger_is_down() {
+        // The ledger rejects every write
+        let store =
+            InterceptedStore::on_record(|_, _, _, _| Err(StorageError("disk full".to_string())));
+        let state = AppState {
+            store: Arc::new(store),
+            ..create_test_state(true)
+        };
+        assert!(matches!(
+            mint_arks(&state, "x6", 3),
+            Err(AppError::StorageUnavailable(_))
+        ));
+    }
 }
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::ark::extract_shoulder;
+
+/// A Name Assigning Authority Number, e.g. `12345`
+///
+/// Only ASCII letters and digits are accepted; case is kept as given.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Naan(String);
+
+impl Naan {
+    pub fn new(naan: impl Into<String>) -> Result<Self, String> {
+        let naan = naan.into();
+        if naan.is_empty() || !naan.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!(
+                "Invalid NAAN (expected ASCII letters and digits): {:?}",
+                naan
+            ));
+        }
+        Ok(Self(naan))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A shoulder in its primordial form: characters other than digits, ending with one digit,
+/// e.g. `x6` or `abc7`
+///
+/// Whether the characters are betanumeric is left to validation, which reports it separately.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ShoulderName(String);
+
+impl ShoulderName {
+    pub fn new(shoulder: impl Into<String>) -> Result<Self, String> {
+        let shoulder = shoulder.into();
+        let primordial = extract_shoulder(&shoulder) == Some(shoulder.as_str());
+        if !primordial || shoulder.contains(['/', '?']) || shoulder.chars().any(char::is_whitespace)
+        {
+            return Err(format!(
+                "Invalid shoulder (expected characters other than digits, ending with one digit): {:?}",
+                shoulder
+            ));
+        }
+        Ok(Self(shoulder))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! string_newtype {
+    ($name:ident) => {
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        /// Lets maps keyed by this type be queried with a `&str`
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::new(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = String;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+string_newtype!(Naan);
+string_newtype!(ShoulderName);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_naan() {
+        assert_eq!(Naan::new("12345").unwrap(), "12345");
+        assert!(Naan::new("b2345AB").is_ok());
+        assert!(Naan::new("").is_err());
+        assert!(Naan::new("123/45").is_err());
+        assert!(Naan::new("12 345").is_err());
+    }
+
+    #[test]
+    fn test_shoulder_name() {
+        assert!(ShoulderName::new("x6").is_ok());
+        assert!(ShoulderName::new("abc7").is_ok());
+        // A blade or a full ARK is not a shoulder
+        assert!(ShoulderName::new("x6np1wh8k").is_err());
+        assert!(ShoulderName::new("ark:12345/x6").is_err());
+        assert!(ShoulderName::new("xyz").is_err());
+        assert!(ShoulderName::new("").is_err());
+
+        // Deserialization validates, including as map keys
+        let shoulders: HashMap<ShoulderName, u32> = serde_json::from_str(r#"{"x6": 1}"#).unwrap();
+        assert_eq!(shoulders.get("x6"), Some(&1));
+        assert!(serde_json::from_str::<HashMap<ShoulderName, u32>>(r#"{"x6np": 1}"#).is_err());
+    }
+}
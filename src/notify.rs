@@ -0,0 +1,540 @@
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    transport::smtp::authentication::Credentials,
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::names::{Naan, ShoulderName};
+use crate::secrets::{Secret, Secrets};
+
+/// An email ready to be handed to a transport
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmailMessage {
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Something that can deliver emails (SMTP in production, a recorder in tests)
+pub trait EmailTransport: Send + Sync {
+    fn send(
+        &self,
+        from: &str,
+        message: EmailMessage,
+    ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>>;
+}
+
+/// SMTP connection settings loaded from the `SMTP_*` environment variables
+#[derive(Clone, Debug)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<Secret>,
+    /// `starttls` (default), `tls`, or `none`
+    pub security: String,
+}
+
+/// [`EmailTransport`] backed by lettre's async SMTP client
+pub struct SmtpTransport {
+    inner: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    pub fn new(config: &SmtpConfig) -> Result<Self, String> {
+        let builder = match config.security.as_str() {
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host),
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+            other => return Err(format!("Unknown SMTP_SECURITY value: {}", other)),
+        }
+        .map_err(|e| format!("Invalid SMTP configuration: {}", e))?;
+
+        let builder = builder.port(config.port);
+        let builder = match (&config.username, &config.password) {
+            (Some(username), Some(password)) => builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose().to_string(),
+            )),
+            _ => builder,
+        };
+
+        Ok(Self {
+            inner: builder.build(),
+        })
+    }
+}
+
+impl EmailTransport for SmtpTransport {
+    fn send(
+        &self,
+        from: &str,
+        message: EmailMessage,
+    ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+        let email = build_message(from, &message);
+        Box::pin(async move {
+            self.inner
+                .send(email?)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("SMTP delivery failed: {}", e))
+        })
+    }
+}
+
+fn build_message(from: &str, message: &EmailMessage) -> Result<Message, String> {
+    let parse = |address: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Invalid email address '{}': {}", address, e))
+    };
+
+    let mut builder = Message::builder()
+        .from(parse(from)?)
+        .subject(&message.subject);
+    for recipient in &message.to {
+        builder = builder.to(parse(recipient)?);
+    }
+
+    builder
+        .body(message.body.clone())
+        .map_err(|e| format!("Failed to build email: {}", e))
+}
+
+/// An operational event worth telling a human about
+#[derive(Clone, Debug)]
+pub enum Alert {
+    /// A resolution produced a redirect target that failed security validation
+    BlockedRedirect {
+        naan: Naan,
+        shoulder: ShoulderName,
+        ark: String,
+        reason: String,
+    },
+    /// A mint request was refused because the shoulder's mint quota is used up
+    MintQuotaExhausted {
+        naan: Naan,
+        shoulder: ShoulderName,
+        retry_after: Duration,
+    },
+}
+
+const BLOCKED_REDIRECT_SUBJECT: &str = "[ark:{naan}] Blocked redirect on shoulder {shoulder}";
+const BLOCKED_REDIRECT_BODY: &str = "\
+A resolution request for {ark} produced a redirect target that failed validation
+and was blocked.
+
+Reason: {reason}
+
+Please review the route_pattern configured for shoulder {shoulder}.
+";
+
+const MINT_QUOTA_EXHAUSTED_SUBJECT: &str = "[ark:{naan}] Mint quota used up on shoulder {shoulder}";
+const MINT_QUOTA_EXHAUSTED_BODY: &str = "\
+A mint request on shoulder {shoulder} was refused because the shoulder's mint quota
+is used up. Minting resumes in {retry_after} seconds.
+
+Please review the mint_quota configured for shoulder {shoulder} if the demand is expected.
+";
+
+impl Alert {
+    /// Key used to suppress repeats of the same alert
+    fn suppression_key(&self) -> String {
+        match self {
+            Alert::BlockedRedirect { shoulder, .. } => format!("blocked_redirect:{}", shoulder),
+            Alert::MintQuotaExhausted { shoulder, .. } => {
+                format!("mint_quota_exhausted:{}", shoulder)
+            }
+        }
+    }
+
+    /// Renders the alert into a subject and body
+    fn render(&self) -> (String, String) {
+        match self {
+            Alert::BlockedRedirect {
+                naan,
+                shoulder,
+                ark,
+                reason,
+            } => {
+                let vars = [
+                    ("naan", naan.as_str()),
+                    ("shoulder", shoulder.as_str()),
+                    ("ark", ark.as_str()),
+                    ("reason", reason.as_str()),
+                ];
+                (
+                    render_template(BLOCKED_REDIRECT_SUBJECT, &vars),
+                    render_template(BLOCKED_REDIRECT_BODY, &vars),
+                )
+            }
+            Alert::MintQuotaExhausted {
+                naan,
+                shoulder,
+                retry_after,
+            } => {
+                let retry_after = retry_after.as_secs().to_string();
+                let vars = [
+                    ("naan", naan.as_str()),
+                    ("shoulder", shoulder.as_str()),
+                    ("retry_after", retry_after.as_str()),
+                ];
+                (
+                    render_template(MINT_QUOTA_EXHAUSTED_SUBJECT, &vars),
+                    render_template(MINT_QUOTA_EXHAUSTED_BODY, &vars),
+                )
+            }
+        }
+    }
+}
+
+/// Substitute `{name}` placeholders in a message template
+fn render_template(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Limits how often alerts are emailed, so a misbehaving shoulder can't flood inboxes
+#[derive(Clone, Debug)]
+pub struct SuppressionPolicy {
+    /// Minimum time between two emails for the same alert (same kind and shoulder)
+    pub repeat_interval: Duration,
+    /// Maximum number of alert emails sent in any one-hour window
+    pub max_per_hour: usize,
+}
+
+impl Default for SuppressionPolicy {
+    fn default() -> Self {
+        Self {
+            repeat_interval: Duration::from_secs(3600),
+            max_per_hour: 20,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SuppressionState {
+    last_sent: HashMap<String, Instant>,
+    window_start: Option<Instant>,
+    sent_in_window: usize,
+}
+
+/// What happened to an alert handed to the [`Notifier`]
+#[derive(Debug, PartialEq)]
+pub enum NotifyOutcome {
+    Sent,
+    Suppressed,
+    Disabled,
+    Failed(String),
+}
+
+/// Sends alert emails to shoulder contacts and the operator address
+#[derive(Default)]
+pub struct Notifier {
+    transport: Option<Arc<dyn EmailTransport>>,
+    from: String,
+    operator_email: Option<String>,
+    policy: SuppressionPolicy,
+    state: Mutex<SuppressionState>,
+}
+
+impl Notifier {
+    pub fn new(
+        transport: Arc<dyn EmailTransport>,
+        from: String,
+        operator_email: Option<String>,
+        policy: SuppressionPolicy,
+    ) -> Self {
+        Self {
+            transport: Some(transport),
+            from,
+            operator_email,
+            policy,
+            state: Mutex::new(SuppressionState::default()),
+        }
+    }
+
+    /// Whether an SMTP transport is configured
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    /// Email an alert to `contact` (typically the shoulder's contact address) and the operator
+    pub async fn notify(&self, alert: Alert, contact: Option<&str>) -> NotifyOutcome {
+        let Some(transport) = &self.transport else {
+            return NotifyOutcome::Disabled;
+        };
+
+        let to: Vec<String> = contact
+            .into_iter()
+            .chain(self.operator_email.as_deref())
+            .map(str::to_string)
+            .collect();
+        if to.is_empty() {
+            return NotifyOutcome::Disabled;
+        }
+
+        let key = alert.suppression_key();
+        if !self.admit(&key) {
+            tracing::debug!(alert = %key, "Alert email suppressed");
+            return NotifyOutcome::Suppressed;
+        }
+
+        let (subject, body) = alert.render();
+        match transport
+            .send(&self.from, EmailMessage { to, subject, body })
+            .await
+        {
+            Ok(()) => {
+                tracing::info!(alert = %key, "Alert email sent");
+                NotifyOutcome::Sent
+            }
+            Err(e) => {
+                tracing::error!(alert = %key, error = %e, "Alert email failed");
+                NotifyOutcome::Failed(e)
+            }
+        }
+    }
+
+    /// Apply the suppression policy, recording the send if it is allowed
+    fn admit(&self, key: &str) -> bool {
+        let mut state = self.state.lock().expect("notifier lock poisoned");
+        let now = Instant::now();
+
+        if state
+            .last_sent
+            .get(key)
+            .is_some_and(|last| now.duration_since(*last) < self.policy.repeat_interval)
+        {
+            return false;
+        }
+
+        let window_expired = state
+            .window_start
+            .is_none_or(|start| now.duration_since(start) >= Duration::from_secs(3600));
+        if window_expired {
+            state.window_start = Some(now);
+            state.sent_in_window = 0;
+        }
+        if state.sent_in_window >= self.policy.max_per_hour {
+            return false;
+        }
+
+        state.sent_in_window += 1;
+        state.last_sent.insert(key.to_string(), now);
+        true
+    }
+}
+
+/// Build the notifier from environment variables
+///
+/// Email is enabled when `SMTP_HOST` is set, which then also requires `SMTP_FROM`.
+/// Optional: `SMTP_PORT` (default 587), `SMTP_USERNAME` and `SMTP_PASSWORD` (from `secrets`),
+/// `SMTP_SECURITY` (`starttls`, `tls`, or `none`), `ALERT_EMAIL` (operator address copied on
+/// every alert), `ALERT_REPEAT_INTERVAL_SECS` (default 3600), `ALERT_MAX_PER_HOUR` (default 20).
+pub fn load_notifier_from_env(secrets: &Secrets) -> Result<Notifier, String> {
+    let Ok(host) = std::env::var("SMTP_HOST") else {
+        return Ok(Notifier::default());
+    };
+
+    let from = std::env::var("SMTP_FROM").map_err(|_| "SMTP_FROM must be set with SMTP_HOST")?;
+    let config = SmtpConfig {
+        host,
+        port: env_parse("SMTP_PORT")?.unwrap_or(587),
+        username: secrets
+            .get("SMTP_USERNAME")
+            .map(|username| username.expose().to_string()),
+        password: secrets.get("SMTP_PASSWORD").cloned(),
+        security: std::env::var("SMTP_SECURITY").unwrap_or_else(|_| "starttls".to_string()),
+    };
+
+    let defaults = SuppressionPolicy::default();
+    let policy = SuppressionPolicy {
+        repeat_interval: env_parse("ALERT_REPEAT_INTERVAL_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.repeat_interval),
+        max_per_hour: env_parse("ALERT_MAX_PER_HOUR")?.unwrap_or(defaults.max_per_hour),
+    };
+
+    Ok(Notifier::new(
+        Arc::new(SmtpTransport::new(&config)?),
+        from,
+        std::env::var("ALERT_EMAIL").ok(),
+        policy,
+    ))
+}
+
+/// Parse an optional environment variable, failing if it is set but invalid
+pub(crate) fn env_parse<T: std::str::FromStr>(name: &str) -> Result<Option<T>, String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid {}: {}", name, value))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Keeps the emails it is asked to send
+    #[derive(Default)]
+    pub(crate) struct RecordingTransport {
+        pub(crate) sent: Mutex<Vec<EmailMessage>>,
+    }
+
+    impl EmailTransport for RecordingTransport {
+        fn send(
+            &self,
+            _from: &str,
+            message: EmailMessage,
+        ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+            self.sent.lock().unwrap().push(message);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    fn blocked_redirect(shoulder: &str) -> Alert {
+        Alert::BlockedRedirect {
+            naan: "12345".parse().unwrap(),
+            shoulder: shoulder.parse().unwrap(),
+            ark: format!("ark:12345/{}test", shoulder),
+            reason: "Redirect URL has invalid scheme".to_string(),
+        }
+    }
+
+    pub(crate) fn notifier(
+        transport: Arc<RecordingTransport>,
+        policy: SuppressionPolicy,
+    ) -> Notifier {
+        Notifier::new(
+            transport,
+            "ark@example.org".to_string(),
+            Some("ops@example.org".to_string()),
+            policy,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sends_rendered_alert_to_contact_and_operator() {
+        let transport = Arc::new(RecordingTransport::default());
+        let notifier = notifier(transport.clone(), SuppressionPolicy::default());
+
+        let outcome = notifier
+            .notify(blocked_redirect("x6"), Some("team@example.org"))
+            .await;
+        assert_eq!(outcome, NotifyOutcome::Sent);
+
+        let sent = transport.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, vec!["team@example.org", "ops@example.org"]);
+        assert_eq!(
+            sent[0].subject,
+            "[ark:12345] Blocked redirect on shoulder x6"
+        );
+        assert!(sent[0].body.contains("ark:12345/x6test"));
+        assert!(!sent[0].body.contains('{'));
+    }
+
+    #[tokio::test]
+    async fn test_suppresses_repeated_alerts() {
+        let transport = Arc::new(RecordingTransport::default());
+        let notifier = notifier(transport.clone(), SuppressionPolicy::default());
+
+        assert_eq!(
+            notifier.notify(blocked_redirect("x6"), None).await,
+            NotifyOutcome::Sent
+        );
+        assert_eq!(
+            notifier.notify(blocked_redirect("x6"), None).await,
+            NotifyOutcome::Suppressed
+        );
+        // A different shoulder is a different alert
+        assert_eq!(
+            notifier.notify(blocked_redirect("b3"), None).await,
+            NotifyOutcome::Sent
+        );
+        assert_eq!(transport.sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mint_quota_alert_is_suppressed_per_shoulder() {
+        let transport = Arc::new(RecordingTransport::default());
+        let notifier = notifier(transport.clone(), SuppressionPolicy::default());
+        let used_up = |shoulder: &str| Alert::MintQuotaExhausted {
+            naan: "12345".parse().unwrap(),
+            shoulder: shoulder.parse().unwrap(),
+            retry_after: Duration::from_secs(600),
+        };
+
+        assert_eq!(
+            notifier.notify(used_up("x6"), None).await,
+            NotifyOutcome::Sent
+        );
+        assert_eq!(
+            notifier.notify(used_up("x6"), None).await,
+            NotifyOutcome::Suppressed
+        );
+        // Blocked redirects on the same shoulder are a different alert
+        assert_eq!(
+            notifier.notify(blocked_redirect("x6"), None).await,
+            NotifyOutcome::Sent
+        );
+
+        let sent = transport.sent.lock().unwrap();
+        assert_eq!(
+            sent[0].subject,
+            "[ark:12345] Mint quota used up on shoulder x6"
+        );
+        assert!(sent[0].body.contains("resumes in 600 seconds"));
+        assert!(!sent[0].body.contains('{'));
+    }
+
+    #[tokio::test]
+    async fn test_enforces_hourly_cap() {
+        let transport = Arc::new(RecordingTransport::default());
+        let policy = SuppressionPolicy {
+            repeat_interval: Duration::ZERO,
+            max_per_hour: 2,
+        };
+        let notifier = notifier(transport.clone(), policy);
+
+        for shoulder in ["x6", "b3", "fk4"] {
+            notifier.notify(blocked_redirect(shoulder), None).await;
+        }
+        assert_eq!(transport.sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_transport() {
+        let notifier = Notifier::default();
+        assert!(!notifier.is_enabled());
+        assert_eq!(
+            notifier
+                .notify(blocked_redirect("x6"), Some("a@b.org"))
+                .await,
+            NotifyOutcome::Disabled
+        );
+    }
+
+    #[test]
+    fn test_build_message_rejects_invalid_address() {
+        let message = EmailMessage {
+            to: vec!["not an address".to_string()],
+            subject: "s".to_string(),
+            body: "b".to_string(),
+        };
+        assert!(build_message("ark@example.org", &message).is_err());
+    }
+}
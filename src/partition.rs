@@ -0,0 +1,158 @@
+use rand::Rng;
+use serde::Serialize;
+
+use crate::notify::env_parse;
+use crate::template::NoidTemplate;
+
+/// This instance's share of every shoulder's namespace, so several instances can mint without
+/// a shared store or any coordination and never issue the same identifier
+///
+/// Random blades of node `n` start with every `nodes`-th character of their first position's
+/// alphabet, from the `n`-th on. Sequential counters are interleaved: node `n` issues positions
+/// `n`, `n + nodes`, `n + 2 * nodes` and so on, from a counter of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct NodePartition {
+    /// This instance, from 0
+    pub node: u32,
+    /// Instances minting on the same shoulders
+    pub nodes: u32,
+}
+
+impl Default for NodePartition {
+    /// The whole namespace, for an instance minting on its own
+    fn default() -> Self {
+        Self { node: 0, nodes: 1 }
+    }
+}
+
+impl NodePartition {
+    pub fn new(node: u32, nodes: u32) -> Result<Self, String> {
+        if node >= nodes {
+            return Err(format!(
+                "NODE_ID must be below NODE_COUNT ({}), got {}",
+                nodes, node
+            ));
+        }
+        Ok(Self { node, nodes })
+    }
+
+    /// A character of `alphabet` that this node's blades may start with; the alphabet must
+    /// pass [`NodePartition::check`]
+    pub fn first_character(&self, alphabet: &[u8], rng: &mut impl Rng) -> u8 {
+        let (node, nodes) = (self.node as usize, self.nodes as usize);
+        let share = (alphabet.len() + nodes - 1 - node) / nodes;
+        alphabet[node + nodes * rng.random_range(0..share)]
+    }
+
+    /// Number of characters of an alphabet of `size` that this node's blades may start with
+    pub fn share(&self, size: usize) -> usize {
+        (size + self.nodes as usize - 1).saturating_sub(self.node as usize) / self.nodes as usize
+    }
+
+    /// Key of this node's sequential counter for `shoulder`
+    pub fn sequence_key(&self, shoulder: &str) -> String {
+        if self.nodes == 1 {
+            shoulder.to_string()
+        } else {
+            format!("{}#{}", shoulder, self.node)
+        }
+    }
+
+    /// Position in the shoulder's namespace of this node's counter position `local`
+    pub fn position(&self, local: u64) -> Option<u64> {
+        local
+            .checked_mul(self.nodes as u64)?
+            .checked_add(self.node as u64)
+    }
+
+    /// Checks that every node gets some of the characters `template`'s blades start with
+    pub fn check(&self, template: &NoidTemplate) -> Result<(), String> {
+        match template.first_alphabet_size() {
+            Some(size) if size < self.nodes as usize => Err(format!(
+                "NODE_COUNT ({}) exceeds the {} characters blades can start with",
+                self.nodes, size
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Trailing number of `NODE_ID`, so a StatefulSet pod name such as `ark-service-2` can be used
+/// as it is
+fn node_number(id: &str) -> Result<u32, String> {
+    let digits = id.len() - id.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    id[id.len() - digits..]
+        .parse()
+        .map_err(|_| format!("NODE_ID must end in a number: {}", id))
+}
+
+/// Build this instance's namespace partition from environment variables; the whole namespace
+/// unless `NODE_COUNT` is set
+///
+/// `NODE_COUNT` requires `NODE_ID`, counted from 0. A `NODE_ID` such as `ark-service-2` is read
+/// as its trailing number.
+pub fn load_node_partition_from_env() -> Result<NodePartition, String> {
+    let Some(nodes) = env_parse::<u32>("NODE_COUNT")? else {
+        return Ok(NodePartition::default());
+    };
+    if nodes == 0 {
+        return Err("NODE_COUNT must be greater than 0".to_string());
+    }
+    let id = std::env::var("NODE_ID").map_err(|_| "NODE_COUNT is set, but NODE_ID is not")?;
+    NodePartition::new(node_number(id.trim())?, nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BETANUMERIC;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_nodes_share_first_characters_and_counters() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let nodes: Vec<NodePartition> = (0..3).map(|n| NodePartition::new(n, 3).unwrap()).collect();
+
+        let firsts: Vec<HashSet<u8>> = nodes
+            .iter()
+            .map(|node| {
+                (0..500)
+                    .map(|_| node.first_character(BETANUMERIC, &mut rng))
+                    .collect()
+            })
+            .collect();
+        // 29 characters split 10, 10 and 9, without overlap
+        assert_eq!(
+            firsts.iter().map(HashSet::len).collect::<Vec<_>>(),
+            [10, 10, 9]
+        );
+        assert_eq!(
+            nodes.iter().map(|node| node.share(29)).collect::<Vec<_>>(),
+            [10, 10, 9]
+        );
+        assert!(firsts[0].is_disjoint(&firsts[1]) && firsts[1].is_disjoint(&firsts[2]));
+
+        let positions: Vec<Vec<u64>> = nodes
+            .iter()
+            .map(|node| (0..3).map(|local| node.position(local).unwrap()).collect())
+            .collect();
+        assert_eq!(positions, [[0, 3, 6], [1, 4, 7], [2, 5, 8]]);
+        assert_eq!(nodes[2].sequence_key("x6"), "x6#2");
+        assert_eq!(NodePartition::new(0, 1).unwrap().sequence_key("x6"), "x6");
+        assert_eq!(nodes[1].position(u64::MAX), None);
+
+        assert!(NodePartition::new(3, 3).is_err());
+        let digits: NoidTemplate = ".rdd".parse().unwrap();
+        assert!(NodePartition::new(0, 10).unwrap().check(&digits).is_ok());
+        assert!(NodePartition::new(0, 11).unwrap().check(&digits).is_err());
+    }
+
+    #[test]
+    fn test_node_number() {
+        assert_eq!(node_number("2"), Ok(2));
+        assert_eq!(node_number("ark-service-12"), Ok(12));
+        assert!(node_number("ark-service").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Repository platform whose URL layout a shoulder resolves into, in place of a hand-written
+/// route pattern
+///
+/// With a platform, the shoulder's `route_pattern` is the repository's base URL, such as
+/// `https://repository.example.edu`, and the platform adds the path to each ARK's record.
+/// Qualifiers follow the ARK onto that path, where the platforms address files and children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    /// DSpace, with the NAAN and blade as the item's handle: `/handle/12345/x6np1wh8k`
+    Dspace,
+    /// Omeka S or Classic with the Ark module, which serves ARKs itself:
+    /// `/ark:/12345/x6np1wh8k`
+    Omeka,
+    /// Fedora, directly or behind Islandora, with objects under the NAAN:
+    /// `/rest/12345/x6np1wh8k`
+    #[serde(alias = "islandora")]
+    Fedora,
+    /// Samvera Hyrax, with the blade as the work's ID: `/concern/works/x6np1wh8k`
+    Samvera,
+}
+
+impl Platform {
+    /// Path template appended to the repository's base URL
+    fn path(self) -> &'static str {
+        match self {
+            Platform::Dspace => "/handle/${prefix}/${value}",
+            Platform::Omeka => "/ark:/${prefix}/${value}",
+            Platform::Fedora => "/rest/${prefix}/${value}",
+            Platform::Samvera => "/concern/works/${value}",
+        }
+    }
+
+    /// The route pattern for a repository at `base_url`
+    pub fn route_pattern(self, base_url: &str) -> String {
+        format!("{}{}", base_url.trim_end_matches('/'), self.path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+    use crate::shoulder::Shoulder;
+    use axum::http::Method;
+
+    fn resolve(platform: Platform, ark: &str) -> String {
+        let shoulder = Shoulder::builder()
+            .route_pattern("https://repository.example.edu/")
+            .project_name("Repository")
+            .platform(platform)
+            .build()
+            .unwrap();
+        shoulder
+            .try_resolve(&parse_ark(ark).unwrap())
+            .unwrap_or_else(|blocked| panic!("{} blocked: {}", ark, blocked.reason))
+    }
+
+    #[test]
+    fn test_platform_presets() {
+        let cases = [
+            (Platform::Dspace, "/handle/12345/x6np1wh8k"),
+            (Platform::Omeka, "/ark:/12345/x6np1wh8k"),
+            (Platform::Fedora, "/rest/12345/x6np1wh8k"),
+            (Platform::Samvera, "/concern/works/x6np1wh8k"),
+        ];
+        for (platform, path) in cases {
+            let base = "https://repository.example.edu";
+            assert_eq!(
+                resolve(platform, "ark:12345/x6np1wh8k"),
+                format!("{}{}", base, path)
+            );
+            // Path qualifiers address parts of the record, query qualifiers are kept as such
+            assert_eq!(
+                resolve(platform, "ark:/12345/x6np1wh8k/files/page2.pdf"),
+                format!("{}{}/files/page2.pdf", base, path)
+            );
+            assert_eq!(
+                resolve(platform, "ark:12345/x6np1wh8k?locale=fr"),
+                format!("{}{}?locale=fr", base, path)
+            );
+        }
+    }
+
+    #[test]
+    fn test_platform_names() {
+        let platform: Platform = serde_json::from_str("\"islandora\"").unwrap();
+        assert_eq!(platform, Platform::Fedora);
+        assert_eq!(
+            serde_json::to_string(&Platform::Dspace).unwrap(),
+            "\"dspace\""
+        );
+    }
+
+    #[test]
+    fn test_platform_needs_a_base_url() {
+        let shoulder = |route_pattern: &str| {
+            Shoulder::builder()
+                .route_pattern(route_pattern)
+                .project_name("Repository")
+                .platform(Platform::Omeka)
+                .build()
+        };
+        assert!(shoulder("https://repository.example.edu/omeka").is_ok());
+        assert!(shoulder("https://repository.example.edu/${value}").is_err());
+        assert!(shoulder("https://repository.example.edu/?page=1").is_err());
+        // Routing with a platform resolves like the expanded pattern
+        let resolution = shoulder("https://repository.example.edu/omeka")
+            .unwrap()
+            .resolve(&parse_ark("ark:12345/x6np1wh8k").unwrap(), &Method::GET);
+        assert!(matches!(
+            resolution,
+            crate::shoulder::ResolutionOutcome::Redirect { url, .. }
+                if url == "https://repository.example.edu/omeka/ark:/12345/x6np1wh8k"
+        ));
+    }
+}
@@ -0,0 +1,687 @@
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::types::Json;
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::api_keys::{ApiKey, KeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, StorageError};
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::idempotency::{MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerEntry, LedgerPage, MintLedger, next_page_by_ark};
+use crate::lifecycle::Lifecycle;
+use crate::notify::env_parse;
+use crate::store::ArkStore;
+
+/// Pooled connections unless `DATABASE_MAX_CONNECTIONS` says otherwise
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+/// Wait for a free connection unless `DATABASE_ACQUIRE_TIMEOUT_SECS` says otherwise
+pub const DEFAULT_ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS ark_minted (
+        ark TEXT PRIMARY KEY,
+        shoulder TEXT NOT NULL,
+        minted_at TIMESTAMPTZ NOT NULL DEFAULT now()
+    );
+    CREATE TABLE IF NOT EXISTS ark_sequences (
+        shoulder TEXT PRIMARY KEY,
+        next_position BIGINT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS ark_bindings (
+        ark TEXT PRIMARY KEY,
+        target TEXT NOT NULL,
+        metadata JSONB NOT NULL DEFAULT '{}',
+        bound_at TIMESTAMPTZ NOT NULL
+    );
+    ALTER TABLE ark_bindings ADD COLUMN IF NOT EXISTS lifecycle JSONB;
+    DO $$
+    BEGIN
+        -- Bindings withdrawn before ARKs had a lifecycle carry a tombstone instead
+        IF EXISTS (
+            SELECT 1 FROM information_schema.columns
+            WHERE table_name = 'ark_bindings' AND column_name = 'tombstone'
+        ) THEN
+            UPDATE ark_bindings SET lifecycle = jsonb_build_object(
+                'status', 'tombstoned',
+                'history', jsonb_build_array(jsonb_strip_nulls(jsonb_build_object(
+                    'from', 'active',
+                    'to', 'tombstoned',
+                    'at', tombstone->'withdrawn_at',
+                    'reason', tombstone->'reason'
+                )))
+            )
+            WHERE tombstone IS NOT NULL AND lifecycle IS NULL;
+            ALTER TABLE ark_bindings DROP COLUMN tombstone;
+        END IF;
+    END
+    $$;
+    ALTER TABLE ark_minted ADD COLUMN IF NOT EXISTS metadata JSONB;
+    CREATE INDEX IF NOT EXISTS ark_minted_shoulder ON ark_minted (shoulder);
+    CREATE TABLE IF NOT EXISTS ark_deliveries (
+        id TEXT PRIMARY KEY,
+        status TEXT NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL,
+        delivery JSONB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS ark_mint_receipts (
+        key TEXT PRIMARY KEY,
+        created_at TIMESTAMPTZ NOT NULL,
+        receipt JSONB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS ark_api_keys (
+        id TEXT PRIMARY KEY,
+        api_key JSONB NOT NULL
+    );
+";
+
+/// Mint ledger, binding store, webhook deliveries, mint receipts and API keys in a PostgreSQL
+/// database, which replicas of the service can share
+///
+/// Queries run on a small runtime of their own, since the storage traits are synchronous and
+/// are called from both mint workers and request tasks.
+pub struct PostgresStore {
+    pool: PgPool,
+    /// Taken on drop, to shut it down without blocking
+    runtime: Option<Runtime>,
+}
+
+impl PostgresStore {
+    /// Connects to `url` and creates the tables if needed
+    pub fn connect(
+        url: &str,
+        max_connections: u32,
+        acquire_timeout: Duration,
+    ) -> Result<Self, StorageError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("postgres")
+            .enable_all()
+            .build()
+            .map_err(|e| StorageError(format!("postgres: {}", e)))?;
+        let options = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .acquire_timeout(acquire_timeout);
+        let url = url.to_string();
+        let pool = wait_for(&runtime, async move {
+            let pool = options.connect(&url).await?;
+            sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+            Ok(pool)
+        })?;
+        Ok(Self {
+            pool,
+            runtime: Some(runtime),
+        })
+    }
+
+    /// Runs a query on the store's runtime and waits for its result
+    fn run<T, F, Fut>(&self, query: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>> + Send + 'static,
+    {
+        let runtime = self.runtime.as_ref().expect("postgres runtime running");
+        wait_for(runtime, query(self.pool.clone()))
+    }
+}
+
+impl Drop for PostgresStore {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Spawns `future` on `runtime` and blocks until it finishes; unlike `Runtime::block_on`, this
+/// also works from inside another runtime
+fn wait_for<T, Fut>(runtime: &Runtime, future: Fut) -> Result<T, StorageError>
+where
+    T: Send + 'static,
+    Fut: Future<Output = Result<T, sqlx::Error>> + Send + 'static,
+{
+    let (sender, receiver) = std::sync::mpsc::channel();
+    runtime.spawn(async move {
+        let _ = sender.send(future.await);
+    });
+    receiver
+        .recv()
+        .map_err(|_| StorageError("postgres: query aborted".to_string()))?
+        .map_err(|e| StorageError(format!("postgres: {}", e)))
+}
+
+/// ARK, shoulder, mint time and metadata of a row of `ark_minted`
+type LedgerRow = (
+    String,
+    String,
+    DateTime<Utc>,
+    Option<Json<BTreeMap<String, String>>>,
+);
+
+impl MintLedger for PostgresStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        let (shoulder, arks) = (shoulder.to_string(), arks.to_vec());
+        let metadata = (!metadata.is_empty()).then(|| Json(metadata.clone()));
+        self.run(|pool| async move {
+            // One statement, so the batch is recorded all or none, with its metadata
+            let inserted: HashSet<String> = sqlx::query_scalar(
+                "INSERT INTO ark_minted (ark, shoulder, metadata)
+                 SELECT unnest($1::text[]), $2, $3
+                 ON CONFLICT (ark) DO NOTHING RETURNING ark",
+            )
+            .bind(&arks)
+            .bind(shoulder)
+            .bind(metadata)
+            .fetch_all(&pool)
+            .await?
+            .into_iter()
+            .collect();
+            Ok(arks
+                .into_iter()
+                .filter(|ark| !inserted.contains(ark))
+                .collect())
+        })
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        let arks = arks.to_vec();
+        let metadata = (!metadata.is_empty()).then(|| Json(metadata.clone()));
+        self.run(|pool| async move {
+            sqlx::query("UPDATE ark_minted SET metadata = $2 WHERE ark = ANY($1)")
+                .bind(arks)
+                .bind(metadata)
+                .execute(&pool)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        let ark = ark.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM ark_minted WHERE ark = $1)")
+                .bind(ark)
+                .fetch_one(&pool)
+                .await
+        })
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ark_minted")
+                .fetch_one(&pool)
+                .await
+                .map(|count| count as usize)
+        })
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        let shoulder = shoulder.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ark_minted WHERE shoulder = $1")
+                .bind(shoulder)
+                .fetch_one(&pool)
+                .await
+                .map(|count| Some(count as usize))
+        })
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        let shoulder = shoulder.to_string();
+        let count = count as i64;
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>(
+                "INSERT INTO ark_sequences (shoulder, next_position) VALUES ($1, $2)
+                 ON CONFLICT (shoulder)
+                 DO UPDATE SET next_position = ark_sequences.next_position + $2
+                 RETURNING next_position",
+            )
+            .bind(shoulder)
+            .bind(count)
+            .fetch_one(&pool)
+            .await
+            .map(|next| (next - count) as u64)
+        })
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        let shoulder = shoulder.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT next_position FROM ark_sequences WHERE shoulder = $1",
+            )
+            .bind(shoulder)
+            .fetch_optional(&pool)
+            .await
+            .map(|next| next.unwrap_or(0) as u64)
+        })
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        let shoulder = shoulder.map(str::to_string);
+        let cursor = cursor.unwrap_or_default().to_string();
+        let entries = self.run(|pool| async move {
+            let rows: Vec<LedgerRow> = sqlx::query_as(
+                "SELECT ark, shoulder, minted_at, metadata FROM ark_minted
+                 WHERE ark > $1 AND ($2::text IS NULL OR shoulder = $2)
+                 ORDER BY ark LIMIT $3",
+            )
+            .bind(cursor)
+            .bind(shoulder)
+            .bind(limit as i64)
+            .fetch_all(&pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(ark, shoulder, minted_at, metadata)| LedgerEntry {
+                    ark,
+                    shoulder,
+                    minted_at: Some(minted_at),
+                    metadata: metadata.map(|Json(metadata)| metadata).unwrap_or_default(),
+                })
+                .collect())
+        })?;
+        Ok(next_page_by_ark(entries, limit))
+    }
+}
+
+/// Target, metadata, binding time and lifecycle of a row of `ark_bindings`
+type BindingRow = (
+    String,
+    Json<BTreeMap<String, String>>,
+    DateTime<Utc>,
+    Option<Json<Lifecycle>>,
+);
+
+/// A [`BindingRow`] preceded by its ARK
+type ArkBindingRow = (
+    String,
+    String,
+    Json<BTreeMap<String, String>>,
+    DateTime<Utc>,
+    Option<Json<Lifecycle>>,
+);
+
+impl BindingStore for PostgresStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        let canonical = canonical.to_string();
+        self.run(|pool| async move {
+            let row: Option<BindingRow> = sqlx::query_as(
+                "SELECT target, metadata, bound_at, lifecycle FROM ark_bindings WHERE ark = $1",
+            )
+            .bind(canonical)
+            .fetch_optional(&pool)
+            .await?;
+            Ok(row.map(binding_from_row))
+        })
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        self.run(|pool| async move {
+            let mut transaction = pool.begin().await?;
+            for (canonical, binding) in bindings {
+                sqlx::query(
+                    "INSERT INTO ark_bindings (ark, target, metadata, bound_at, lifecycle)
+                     VALUES ($1, $2, $3, $4, $5)
+                     ON CONFLICT (ark) DO UPDATE SET target = EXCLUDED.target,
+                         metadata = EXCLUDED.metadata, bound_at = EXCLUDED.bound_at,
+                         lifecycle = EXCLUDED.lifecycle",
+                )
+                .bind(canonical)
+                .bind(binding.target)
+                .bind(Json(binding.metadata))
+                .bind(binding.bound_at)
+                .bind((!binding.lifecycle.is_default()).then_some(Json(binding.lifecycle)))
+                .execute(&mut *transaction)
+                .await?;
+            }
+            transaction.commit().await
+        })
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        let ark = canonical.to_string();
+        let binding = binding.clone();
+        let inserted = self.run(|pool| async move {
+            sqlx::query(
+                "INSERT INTO ark_bindings (ark, target, metadata, bound_at, lifecycle)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (ark) DO NOTHING",
+            )
+            .bind(ark)
+            .bind(binding.target)
+            .bind(Json(binding.metadata))
+            .bind(binding.bound_at)
+            .bind((!binding.lifecycle.is_default()).then_some(Json(binding.lifecycle)))
+            .execute(&pool)
+            .await
+            .map(|result| result.rows_affected() > 0)
+        })?;
+        if inserted {
+            return Ok(None);
+        }
+        self.get(canonical)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM ark_bindings")
+                .fetch_one(&pool)
+                .await
+                .map(|count| count as usize)
+        })
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        let prefix = prefix.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM ark_bindings WHERE left(ark, length($1)) = $1",
+            )
+            .bind(prefix)
+            .fetch_one(&pool)
+            .await
+            .map(|count| Some(count as usize))
+        })
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        let cursor = cursor.unwrap_or_default().to_string();
+        let bindings = self.run(|pool| async move {
+            let rows: Vec<ArkBindingRow> = sqlx::query_as(
+                "SELECT ark, target, metadata, bound_at, lifecycle FROM ark_bindings
+                 WHERE ark > $1 ORDER BY ark LIMIT $2",
+            )
+            .bind(cursor)
+            .bind(limit as i64)
+            .fetch_all(&pool)
+            .await?;
+            Ok(rows
+                .into_iter()
+                .map(|(ark, target, metadata, bound_at, lifecycle)| {
+                    (
+                        ark,
+                        binding_from_row((target, metadata, bound_at, lifecycle)),
+                    )
+                })
+                .collect())
+        })?;
+        Ok(BindingPage::by_ark(bindings, limit))
+    }
+}
+
+/// A binding from the columns of a [`BindingRow`]
+fn binding_from_row((target, Json(metadata), bound_at, lifecycle): BindingRow) -> Binding {
+    Binding {
+        target,
+        metadata,
+        bound_at,
+        lifecycle: lifecycle
+            .map(|Json(lifecycle)| lifecycle)
+            .unwrap_or_default(),
+    }
+}
+
+impl DeliveryStore for PostgresStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        let delivery = delivery.clone();
+        self.run(|pool| async move {
+            sqlx::query(
+                "INSERT INTO ark_deliveries (id, status, created_at, delivery)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (id) DO UPDATE SET status = EXCLUDED.status,
+                     delivery = EXCLUDED.delivery",
+            )
+            .bind(&delivery.id)
+            .bind(delivery.status.as_str())
+            .bind(delivery.created_at)
+            .bind(Json(&delivery))
+            .execute(&pool)
+            .await
+            .map(|_| ())
+        })
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        let id = id.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, Json<Delivery>>(
+                "SELECT delivery FROM ark_deliveries WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map(|delivery| delivery.map(|Json(delivery)| delivery))
+        })
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        let status = status.map(|status| status.as_str());
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, Json<Delivery>>(
+                "SELECT delivery FROM ark_deliveries WHERE $1::text IS NULL OR status = $1
+                 ORDER BY created_at DESC",
+            )
+            .bind(status)
+            .fetch_all(&pool)
+            .await
+            .map(|rows| rows.into_iter().map(|Json(delivery)| delivery).collect())
+        })
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.run(|pool| async move {
+            sqlx::query("DELETE FROM ark_deliveries WHERE status = $1 AND created_at < $2")
+                .bind(DeliveryStatus::Delivered.as_str())
+                .bind(before)
+                .execute(&pool)
+                .await
+                .map(|result| result.rows_affected() as usize)
+        })
+    }
+}
+
+impl ReceiptStore for PostgresStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        let key = receipt.key.clone();
+        let receipt = receipt.clone();
+        let inserted = self.run(|pool| async move {
+            sqlx::query(
+                "INSERT INTO ark_mint_receipts (key, created_at, receipt) VALUES ($1, $2, $3)
+                 ON CONFLICT (key) DO NOTHING",
+            )
+            .bind(&receipt.key)
+            .bind(receipt.created_at)
+            .bind(Json(&receipt))
+            .execute(&pool)
+            .await
+            .map(|result| result.rows_affected() > 0)
+        })?;
+        if inserted {
+            return Ok(None);
+        }
+        self.receipt(&key)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        let key = key.to_string();
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, Json<MintReceipt>>(
+                "SELECT receipt FROM ark_mint_receipts WHERE key = $1",
+            )
+            .bind(key)
+            .fetch_optional(&pool)
+            .await
+            .map(|receipt| receipt.map(|Json(receipt)| receipt))
+        })
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.run(|pool| async move {
+            sqlx::query("DELETE FROM ark_mint_receipts WHERE created_at < $1")
+                .bind(before)
+                .execute(&pool)
+                .await
+                .map(|result| result.rows_affected() as usize)
+        })
+    }
+}
+
+impl KeyStore for PostgresStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        let key = key.clone();
+        self.run(|pool| async move {
+            sqlx::query(
+                "INSERT INTO ark_api_keys (id, api_key) VALUES ($1, $2)
+                 ON CONFLICT (id) DO UPDATE SET api_key = EXCLUDED.api_key",
+            )
+            .bind(&key.id)
+            .bind(Json(&key))
+            .execute(&pool)
+            .await
+            .map(|_| ())
+        })
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        self.run(|pool| async move {
+            sqlx::query_scalar::<_, Json<ApiKey>>("SELECT api_key FROM ark_api_keys ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .map(|keys| keys.into_iter().map(|Json(key)| key).collect())
+        })
+    }
+}
+
+impl ArkStore for PostgresStore {
+    fn backend(&self) -> &'static str {
+        "postgres"
+    }
+}
+
+/// Connect to PostgreSQL from environment variables; `None` unless `DATABASE_URL` is set
+///
+/// Optional: `DATABASE_MAX_CONNECTIONS` (default 5), `DATABASE_ACQUIRE_TIMEOUT_SECS` (default 5).
+pub fn load_postgres_store_from_env() -> Result<Option<Arc<PostgresStore>>, String> {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        return Ok(None);
+    };
+    let max_connections = env_parse("DATABASE_MAX_CONNECTIONS")?.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+    if max_connections == 0 {
+        return Err("DATABASE_MAX_CONNECTIONS must be greater than 0".to_string());
+    }
+    let acquire_timeout = env_parse("DATABASE_ACQUIRE_TIMEOUT_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_ACQUIRE_TIMEOUT);
+    PostgresStore::connect(&url, max_connections, acquire_timeout)
+        .map(|store| Some(Arc::new(store)))
+        .map_err(|e| format!("Failed to connect to DATABASE_URL: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+    use crate::bindings::Bindings;
+    use crate::lifecycle::ArkStatus;
+
+    /// Runs against the database at `TEST_DATABASE_URL`, and is skipped when that is unset
+    #[tokio::test]
+    async fn test_postgres_store_shares_ledger_and_bindings() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            return;
+        };
+        let store = Arc::new(PostgresStore::connect(&url, 2, DEFAULT_ACQUIRE_TIMEOUT).unwrap());
+        let blade = format!("x6pg{}", Utc::now().timestamp_micros());
+        let ark = format!("ark:12345/{}", blade);
+
+        let minted = std::slice::from_ref(&ark);
+        assert!(
+            store
+                .record("x6", minted, &BTreeMap::new())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store.record("x6", minted, &BTreeMap::new()).unwrap(),
+            minted
+        );
+        assert!(store.contains(&ark).unwrap());
+        let first = store.reserve_sequence(&blade, 3).unwrap();
+        assert_eq!(first, 0);
+        let mut delivery = Delivery::new(
+            "https://hooks.example.org/x6",
+            "x6",
+            serde_json::json!({"shoulder": "x6"}),
+        );
+        store.save_delivery(&delivery).unwrap();
+        delivery.attempts = 1;
+        store.save_delivery(&delivery).unwrap();
+        let receipt = MintReceipt {
+            key: blade.clone(),
+            shoulder: "x6".to_string(),
+            count: 1,
+            metadata: Default::default(),
+            detail: false,
+            arks: minted.to_vec(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(store.save_receipt(&receipt).unwrap(), None);
+
+        let bindings = Bindings::new(store.clone());
+        let parsed = parse_ark(&ark).unwrap();
+        bindings
+            .bind_all([(
+                parsed.clone(),
+                Binding {
+                    target: "https://example.org/pg".to_string(),
+                    metadata: BTreeMap::from([("title".to_string(), "Postgres".to_string())]),
+                    bound_at: Utc::now(),
+                    lifecycle: Lifecycle::starting(ArkStatus::Reserved).unwrap(),
+                },
+            )])
+            .unwrap();
+
+        // A second connection, as another replica would open, sees both
+        let replica = PostgresStore::connect(&url, 1, DEFAULT_ACQUIRE_TIMEOUT).unwrap();
+        assert!(replica.contains(&ark).unwrap());
+        assert_eq!(replica.reserve_sequence(&blade, 1).unwrap(), 3);
+        assert_eq!(
+            replica.delivery(&delivery.id).unwrap(),
+            Some(delivery.clone())
+        );
+        let pending = replica.deliveries(Some(DeliveryStatus::Pending)).unwrap();
+        assert!(pending.contains(&delivery));
+        let retried = MintReceipt {
+            arks: Vec::new(),
+            ..receipt.clone()
+        };
+        assert_eq!(
+            replica.save_receipt(&retried).unwrap(),
+            Some(receipt.clone())
+        );
+        assert_eq!(replica.receipt(&blade).unwrap(), Some(receipt));
+        let binding = replica.get(parsed.canonical()).unwrap().unwrap();
+        assert_eq!(binding.target, "https://example.org/pg");
+        assert_eq!(binding.metadata["title"], "Postgres");
+        assert_eq!(binding.lifecycle.status, ArkStatus::Reserved);
+    }
+}
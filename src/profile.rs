@@ -0,0 +1,135 @@
+/// A set of defaults for a common kind of deployment, selected with `CONFIG_PROFILE`
+///
+/// A profile only fills in settings the environment leaves unset, so anything configured
+/// explicitly still wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// A single small instance: mints on the request's task, validates on one thread, and
+    /// serves no admin API
+    Minimal,
+    /// Identifiers meant to last: a persistent ARK store and mint journal, and longer blades
+    Archive,
+    /// Trying the service out: the `99999` test NAAN with an `fk4` test shoulder open to public
+    /// minting, and metadata stubs for inflections on other shoulders
+    Test,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(Self::Minimal),
+            "archive" => Ok(Self::Archive),
+            "test" => Ok(Self::Test),
+            other => Err(format!("expected minimal, archive, or test, got {}", other)),
+        }
+    }
+}
+
+impl Profile {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Minimal => "minimal",
+            Profile::Archive => "archive",
+            Profile::Test => "test",
+        }
+    }
+
+    /// The settings the profile fills in, as environment variables and their values
+    pub fn defaults(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Profile::Minimal => &[
+                ("MINT_WORKERS", "0"),
+                ("VALIDATE_THREADS", "1"),
+                ("MINT_THREADS", "1"),
+                ("ADMIN_API_ENABLED", "false"),
+            ],
+            Profile::Archive => &[
+                ("ARK_STORE_PATH", "ark-store.db"),
+                ("MINT_JOURNAL_PATH", "mint-journal.jsonl"),
+                ("DEFAULT_BLADE_LENGTH", "10"),
+            ],
+            Profile::Test => &[
+                ("NAAN", "99999"),
+                (
+                    "SHOULDERS",
+                    r#"{"fk4": {"route_pattern": "https://example.org/${value}", "project_name": "Test shoulder"}}"#,
+                ),
+                ("UNREGISTERED_INFLECTIONS", "stub"),
+                ("PUBLIC_MINT_SHOULDER", "fk4"),
+            ],
+        }
+    }
+
+    /// The profile's settings for which `is_set` is false
+    pub fn missing_defaults(
+        &self,
+        is_set: impl Fn(&str) -> bool,
+    ) -> Vec<(&'static str, &'static str)> {
+        self.defaults()
+            .iter()
+            .copied()
+            .filter(|(name, _)| !is_set(name))
+            .collect()
+    }
+}
+
+/// Read `CONFIG_PROFILE` and set each of the profile's settings that the environment leaves
+/// unset
+///
+/// Returns the profile with the names of the settings it filled in. This changes the process
+/// environment, so it must run before the rest of the configuration is loaded and while no
+/// other thread reads the environment.
+pub fn apply_profile_from_env() -> Result<Option<(Profile, Vec<&'static str>)>, String> {
+    let Ok(name) = std::env::var("CONFIG_PROFILE") else {
+        return Ok(None);
+    };
+    let profile: Profile = name
+        .parse()
+        .map_err(|e| format!("Invalid CONFIG_PROFILE: {}", e))?;
+    let missing = profile.missing_defaults(|name| std::env::var_os(name).is_some());
+    for (name, value) in &missing {
+        // SAFETY: called at startup, before the service spawns anything that reads the environment
+        unsafe {
+            std::env::set_var(name, value);
+        }
+    }
+    Ok(Some((
+        profile,
+        missing.into_iter().map(|(name, _)| name).collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_fills_only_unset_settings() {
+        let profile: Profile = "test".parse().unwrap();
+        let missing = profile.missing_defaults(|name| name == "NAAN");
+
+        let names: Vec<&str> = missing.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "SHOULDERS",
+                "UNREGISTERED_INFLECTIONS",
+                "PUBLIC_MINT_SHOULDER"
+            ]
+        );
+        assert!("production".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn test_test_profile_shoulders_load() {
+        let (_, shoulders) = Profile::Test
+            .defaults()
+            .iter()
+            .find(|(name, _)| *name == "SHOULDERS")
+            .unwrap();
+        let shoulders = crate::shoulder::parse_shoulders_json(shoulders).unwrap();
+        assert!(shoulders["fk4"].validate().is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::names::ShoulderName;
+use crate::notify::env_parse;
+use crate::rate_limit::{RateLimitPolicy, RateLimiter, limiter_from_env};
+use crate::secrets::Secrets;
+use crate::shoulder::Shoulder;
+
+/// Path of the public mint endpoint
+pub const PUBLIC_MINT_PATH: &str = "/api/v1/public/mint";
+
+/// ARKs one public mint request may ask for unless `PUBLIC_MINT_MAX_COUNT` says otherwise
+pub const DEFAULT_PUBLIC_MINT_MAX_COUNT: usize = 5;
+/// Public mint requests per client and minute unless `PUBLIC_MINT_PER_MINUTE` says otherwise
+pub const DEFAULT_PUBLIC_MINT_PER_MINUTE: u32 = 5;
+/// Public mint requests a client may make at once unless `PUBLIC_MINT_BURST` says otherwise
+pub const DEFAULT_PUBLIC_MINT_BURST: u32 = 2;
+
+/// Minting without credentials on a designated test shoulder, so prospective integrators can
+/// try the API
+///
+/// Requests may only mint a few ARKs, and are held to a rate limit of their own on top of the
+/// general one.
+#[derive(Debug)]
+pub struct PublicMint {
+    /// The only shoulder the endpoint mints on
+    pub shoulder: ShoulderName,
+    /// ARKs allowed per request
+    pub max_count: usize,
+    pub rate_limiter: RateLimiter,
+}
+
+impl PublicMint {
+    /// Rate limiter key of a client, apart from its general rate limit bucket
+    pub fn client_key(client: &str) -> String {
+        format!("public-mint:{}", client)
+    }
+}
+
+/// Set up the public mint endpoint from environment variables; `None` unless
+/// `PUBLIC_MINT_SHOULDER` names one of `shoulders`
+///
+/// Optional: `PUBLIC_MINT_MAX_COUNT` (default 5), `PUBLIC_MINT_PER_MINUTE` (default 5),
+/// `PUBLIC_MINT_BURST` (default 2). Like the general rate limit, the buckets are shared through
+/// `REDIS_URL` when it is set, and honor `RATE_LIMIT_TRUST_FORWARDED_FOR`.
+pub fn load_public_mint_from_env(
+    secrets: &Secrets,
+    shoulders: &HashMap<ShoulderName, Shoulder>,
+) -> Result<Option<PublicMint>, String> {
+    let Ok(shoulder) = std::env::var("PUBLIC_MINT_SHOULDER") else {
+        return Ok(None);
+    };
+    let shoulder: ShoulderName = shoulder
+        .parse()
+        .map_err(|e| format!("Invalid PUBLIC_MINT_SHOULDER: {}", e))?;
+    if !shoulders.contains_key(&shoulder) {
+        return Err(format!(
+            "PUBLIC_MINT_SHOULDER {} is not a configured shoulder",
+            shoulder
+        ));
+    }
+
+    let max_count = env_parse("PUBLIC_MINT_MAX_COUNT")?.unwrap_or(DEFAULT_PUBLIC_MINT_MAX_COUNT);
+    let requests_per_minute =
+        env_parse("PUBLIC_MINT_PER_MINUTE")?.unwrap_or(DEFAULT_PUBLIC_MINT_PER_MINUTE);
+    let burst = env_parse("PUBLIC_MINT_BURST")?.unwrap_or(DEFAULT_PUBLIC_MINT_BURST);
+    for (name, value) in [
+        ("PUBLIC_MINT_MAX_COUNT", max_count as u64),
+        ("PUBLIC_MINT_PER_MINUTE", requests_per_minute.into()),
+        ("PUBLIC_MINT_BURST", burst.into()),
+    ] {
+        if value == 0 {
+            return Err(format!("{} must be greater than 0", name));
+        }
+    }
+    let policy = RateLimitPolicy {
+        requests_per_minute,
+        burst,
+        trust_forwarded_for: env_parse("RATE_LIMIT_TRUST_FORWARDED_FOR")?.unwrap_or(false),
+    };
+
+    let rate_limiter = limiter_from_env(policy, secrets)?;
+    Ok(Some(PublicMint {
+        shoulder,
+        max_count,
+        rate_limiter,
+    }))
+}
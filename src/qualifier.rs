@@ -0,0 +1,156 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How a shoulder forwards the qualifier of an ARK (the part after the blade, including any
+/// query string) into its route pattern
+///
+/// Backends disagree on `+`, `%20` and already-encoded sequences, so this is chosen per
+/// shoulder rather than guessed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QualifierEncoding {
+    /// Forward the qualifier exactly as received (default)
+    #[default]
+    Verbatim,
+    /// Percent-decode the qualifier once, and read `+` in the query string as a space.
+    /// Encoded delimiters, dots, `%` and control characters stay encoded, so decoding never
+    /// changes how the target's path and query are split.
+    Decode,
+    /// Percent-encode what is not allowed in a URL, including `+`, keeping existing escapes
+    /// (in uppercase) instead of encoding them twice
+    Reencode,
+}
+
+impl QualifierEncoding {
+    pub fn is_verbatim(&self) -> bool {
+        *self == QualifierEncoding::Verbatim
+    }
+
+    /// The qualifier as it should be substituted into the route pattern
+    pub fn apply<'a>(&self, qualifier: &'a str) -> Cow<'a, str> {
+        match self {
+            QualifierEncoding::Verbatim => Cow::Borrowed(qualifier),
+            QualifierEncoding::Decode => Cow::Owned(decode(qualifier)),
+            QualifierEncoding::Reencode => Cow::Owned(reencode(qualifier)),
+        }
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/// The byte encoded by the escape starting at `bytes[i]`, if there is a valid one
+fn escaped_byte(bytes: &[u8], i: usize) -> Option<u8> {
+    if bytes.get(i) != Some(&b'%') {
+        return None;
+    }
+    let high = hex_value(*bytes.get(i + 1)?)?;
+    let low = hex_value(*bytes.get(i + 2)?)?;
+    Some(high << 4 | low)
+}
+
+/// Whether an escaped byte must stay escaped when decoding: the URL delimiters, `.` (which could
+/// form `..` segments), `%` itself and control characters
+fn stays_escaped(byte: u8) -> bool {
+    byte.is_ascii_control() || b":/?#[]@!$&'()*+,;=.%".contains(&byte)
+}
+
+fn decode(qualifier: &str) -> String {
+    let bytes = qualifier.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut in_query = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match escaped_byte(bytes, i) {
+            Some(byte) if !stays_escaped(byte) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            _ => {}
+        }
+        match bytes[i] {
+            b'?' => in_query = true,
+            b'+' if in_query => {
+                decoded.push(b' ');
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Characters kept as they are when re-encoding: unreserved characters and the delimiters
+/// that give a qualifier its structure, except `+`
+fn allowed(byte: u8, in_query: bool) -> bool {
+    byte.is_ascii_alphanumeric()
+        || b"-._~!$&'()*,;=:@/".contains(&byte)
+        || (in_query && byte == b'?')
+}
+
+fn reencode(qualifier: &str) -> String {
+    let bytes = qualifier.as_bytes();
+    let mut encoded = String::with_capacity(bytes.len());
+    let mut in_query = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(byte) = escaped_byte(bytes, i) {
+            encoded.push_str(&format!("%{:02X}", byte));
+            i += 3;
+            continue;
+        }
+        let byte = bytes[i];
+        if byte == b'?' && !in_query {
+            in_query = true;
+            encoded.push('?');
+        } else if allowed(byte, in_query) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+        i += 1;
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbatim() {
+        let qualifier = "a+b/c%20d?q=x+y%2b";
+        assert_eq!(QualifierEncoding::Verbatim.apply(qualifier), qualifier);
+    }
+
+    #[test]
+    fn test_decode() {
+        let decode = |q| QualifierEncoding::Decode.apply(q).into_owned();
+        // `+` is only a space in the query string
+        assert_eq!(decode("a+b/c%20d?q=x+y%2B"), "a+b/c d?q=x y%2B");
+        assert_eq!(decode("caf%C3%A9%7e"), "café~");
+        // Encoded control characters and broken escapes are left alone
+        assert_eq!(decode("a%0Ab%zz"), "a%0Ab%zz");
+        // As are encoded delimiters, dots and escapes, which would change the target's structure
+        assert_eq!(
+            decode("a%2fb%3Fc%23d/%2E%2e/%252F"),
+            "a%2fb%3Fc%23d/%2E%2e/%252F"
+        );
+    }
+
+    #[test]
+    fn test_reencode() {
+        let reencode = |q| QualifierEncoding::Reencode.apply(q).into_owned();
+        assert_eq!(reencode("a+b/c d?q=x+y"), "a%2Bb/c%20d?q=x%2By");
+        // Existing escapes are normalized, not encoded twice
+        assert_eq!(reencode("c%20d%2fe?q=%2b"), "c%20d%2Fe?q=%2B");
+        assert_eq!(reencode("café?a?b"), "caf%C3%A9?a?b");
+        assert_eq!(reencode("100%"), "100%25");
+    }
+}
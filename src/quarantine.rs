@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::RwLock;
+
+use crate::names::ShoulderName;
+
+/// A group of blocked redirects sharing a root cause: the same shoulder, route pattern, and
+/// validation error
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct QuarantineEntry {
+    pub id: u64,
+    pub shoulder: ShoulderName,
+    pub route_pattern: String,
+    pub reason: String,
+    /// The most recent ARK that was blocked
+    pub ark: String,
+    /// The URL computed for that ARK
+    pub target: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub occurrences: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A blocked redirect to record
+#[derive(Clone, Debug)]
+pub struct BlockedResolution {
+    pub shoulder: ShoulderName,
+    pub route_pattern: String,
+    pub reason: String,
+    pub ark: String,
+    pub target: String,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    next_id: u64,
+    entries: Vec<QuarantineEntry>,
+}
+
+/// Review queue of redirects blocked by security validation
+///
+/// Repeats of an open entry's root cause are counted on that entry, so the queue grows with
+/// the number of distinct problems rather than with traffic. Once an entry is resolved, the
+/// next occurrence opens a new one.
+#[derive(Debug, Default)]
+pub struct Quarantine {
+    entries: RwLock<Entries>,
+}
+
+impl Quarantine {
+    /// Records a blocked redirect, returning the entry it was counted on
+    pub fn record(&self, blocked: BlockedResolution) -> QuarantineEntry {
+        let mut entries = self.entries.write().expect("quarantine lock poisoned");
+        let now = Utc::now();
+
+        if let Some(entry) = entries.entries.iter_mut().find(|entry| {
+            entry.resolved_at.is_none()
+                && entry.shoulder == blocked.shoulder
+                && entry.route_pattern == blocked.route_pattern
+                && entry.reason == blocked.reason
+        }) {
+            entry.ark = blocked.ark;
+            entry.target = blocked.target;
+            entry.last_seen = now;
+            entry.occurrences += 1;
+            return entry.clone();
+        }
+
+        entries.next_id += 1;
+        let entry = QuarantineEntry {
+            id: entries.next_id,
+            shoulder: blocked.shoulder,
+            route_pattern: blocked.route_pattern,
+            reason: blocked.reason,
+            ark: blocked.ark,
+            target: blocked.target,
+            first_seen: now,
+            last_seen: now,
+            occurrences: 1,
+            resolved_at: None,
+            resolved_by: None,
+            note: None,
+        };
+        entries.entries.push(entry.clone());
+        entry
+    }
+
+    /// All entries, open ones first, most recently seen first
+    pub fn list(&self) -> Vec<QuarantineEntry> {
+        let mut entries = self
+            .entries
+            .read()
+            .expect("quarantine lock poisoned")
+            .entries
+            .clone();
+        entries.sort_by(|a, b| {
+            a.resolved_at
+                .is_some()
+                .cmp(&b.resolved_at.is_some())
+                .then(b.last_seen.cmp(&a.last_seen))
+        });
+        entries
+    }
+
+    /// Marks an entry as resolved, returning it if it exists
+    pub fn resolve(
+        &self,
+        id: u64,
+        resolved_by: String,
+        note: Option<String>,
+    ) -> Option<QuarantineEntry> {
+        let mut entries = self.entries.write().expect("quarantine lock poisoned");
+        let entry = entries.entries.iter_mut().find(|entry| entry.id == id)?;
+        if entry.resolved_at.is_none() {
+            entry.resolved_at = Some(Utc::now());
+            entry.resolved_by = Some(resolved_by);
+            entry.note = note;
+        }
+        Some(entry.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocked(ark: &str) -> BlockedResolution {
+        BlockedResolution {
+            shoulder: "x6".parse().unwrap(),
+            route_pattern: "https://example.org/${value}".to_string(),
+            reason: "Invalid redirect URL constructed: invalid port number".to_string(),
+            ark: ark.to_string(),
+            target: format!("https://example.org:99999/{}", ark),
+        }
+    }
+
+    #[test]
+    fn test_groups_repeats_until_resolved() {
+        let quarantine = Quarantine::default();
+        let first = quarantine.record(blocked("ark:12345/x6a"));
+        let second = quarantine.record(blocked("ark:12345/x6b"));
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.occurrences, 2);
+        assert_eq!(second.ark, "ark:12345/x6b");
+
+        let resolved = quarantine
+            .resolve(
+                first.id,
+                "alice".to_string(),
+                Some("Fixed pattern".to_string()),
+            )
+            .unwrap();
+        assert_eq!(resolved.resolved_by.as_deref(), Some("alice"));
+        assert!(quarantine.resolve(99, "alice".to_string(), None).is_none());
+
+        // A new occurrence after resolution opens a new entry, listed first
+        let third = quarantine.record(blocked("ark:12345/x6c"));
+        assert_ne!(third.id, first.id);
+        let ids: Vec<u64> = quarantine.list().iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![third.id, first.id]);
+    }
+}
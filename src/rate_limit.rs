@@ -0,0 +1,396 @@
+#[cfg(feature = "redis-rate-limit")]
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::notify::env_parse;
+use crate::secrets::Secrets;
+
+#[cfg(feature = "redis-rate-limit")]
+/// How long a Redis round trip may take before the local buckets are used instead
+pub const DEFAULT_REDIS_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[cfg(feature = "redis-rate-limit")]
+/// How long to stay on the local buckets after Redis failed
+const REDIS_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Local buckets kept at most; full ones are forgotten first, then the least recently used
+const MAX_LOCAL_BUCKETS: usize = 10_000;
+
+#[cfg(feature = "redis-rate-limit")]
+/// Token bucket shared through Redis: refills from the server clock, so every replica agrees
+///
+/// Returns `{allowed, milliseconds until a token is available}`.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local per_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'at')
+local tokens = tonumber(bucket[1]) or capacity
+local at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - at) * per_ms)
+local allowed, wait = 0, 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  wait = math.ceil((1 - tokens) / per_ms)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'at', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / per_ms) + 1000)
+return {allowed, wait}
+"#;
+
+/// Requests allowed per client
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitPolicy {
+    /// Sustained rate
+    pub requests_per_minute: u32,
+    /// Requests a client may make at once after being idle
+    pub burst: u32,
+    /// Identify anonymous clients by the last (proxy-appended) `X-Forwarded-For` address instead
+    /// of the peer address; only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitPolicy {
+    fn tokens_per_ms(&self) -> f64 {
+        f64::from(self.requests_per_minute) / 60_000.0
+    }
+}
+
+/// Whether a request may proceed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decision {
+    Allowed,
+    Limited { retry_after: Duration },
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    at: Instant,
+}
+
+/// Token buckets held by this replica alone
+#[derive(Debug, Default)]
+struct LocalBuckets {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl LocalBuckets {
+    fn take(&self, key: &str, policy: &RateLimitPolicy, now: Instant) -> Decision {
+        let capacity = f64::from(policy.burst);
+        let per_ms = policy.tokens_per_ms();
+        let mut buckets = self.buckets.lock().expect("rate limit lock poisoned");
+
+        if buckets.len() >= MAX_LOCAL_BUCKETS && !buckets.contains_key(key) {
+            // Buckets that have refilled completely behave like new ones
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.saturating_duration_since(bucket.at).as_millis() as f64 * per_ms
+                    < capacity
+            });
+            // Clients that keep draining their buckets must not grow the map without bound
+            if buckets.len() >= MAX_LOCAL_BUCKETS {
+                let mut used: Vec<Instant> = buckets.values().map(|bucket| bucket.at).collect();
+                let (_, &mut cutoff, _) = used.select_nth_unstable(MAX_LOCAL_BUCKETS / 10);
+                buckets.retain(|_, bucket| bucket.at > cutoff);
+            }
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.at).as_millis() as f64;
+        bucket.tokens = (bucket.tokens + elapsed * per_ms).min(capacity);
+        bucket.at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Decision::Allowed
+        } else {
+            let wait = ((1.0 - bucket.tokens) / per_ms).ceil() as u64;
+            Decision::Limited {
+                retry_after: Duration::from_millis(wait),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "redis-rate-limit")]
+/// Token buckets in Redis, shared by every replica
+struct SharedBuckets {
+    client: redis::Client,
+    connection: tokio::sync::Mutex<Option<ConnectionManager>>,
+    script: redis::Script,
+    timeout: Duration,
+    /// Until when Redis is skipped after a failure
+    unavailable_until: Mutex<Option<Instant>>,
+}
+
+#[cfg(feature = "redis-rate-limit")]
+impl std::fmt::Debug for SharedBuckets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The connection info may hold a password
+        f.debug_struct("SharedBuckets")
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "redis-rate-limit")]
+impl SharedBuckets {
+    async fn connection(&self) -> Result<ConnectionManager, String> {
+        let mut connection = self.connection.lock().await;
+        if let Some(connection) = connection.as_ref() {
+            return Ok(connection.clone());
+        }
+        let connected =
+            tokio::time::timeout(self.timeout, ConnectionManager::new(self.client.clone()))
+                .await
+                .map_err(|_| "timed out connecting to Redis".to_string())?
+                .map_err(|e| e.to_string())?;
+        *connection = Some(connected.clone());
+        Ok(connected)
+    }
+
+    async fn take(&self, key: &str, policy: &RateLimitPolicy) -> Result<Decision, String> {
+        let mut connection = self.connection().await?;
+        let mut invocation = self.script.key(format!("ark-service:rate-limit:{}", key));
+        invocation.arg(policy.burst).arg(policy.tokens_per_ms());
+        let (allowed, wait): (i64, u64) =
+            tokio::time::timeout(self.timeout, invocation.invoke_async(&mut connection))
+                .await
+                .map_err(|_| "timed out waiting for Redis".to_string())?
+                .map_err(|e| e.to_string())?;
+
+        Ok(if allowed == 1 {
+            Decision::Allowed
+        } else {
+            Decision::Limited {
+                retry_after: Duration::from_millis(wait),
+            }
+        })
+    }
+}
+
+/// Limits requests per client, sharing the counters through Redis when it is configured
+///
+/// While Redis is unreachable, each replica falls back to its own buckets, so limits are
+/// looser (one allowance per replica) but still enforced. Disabled without a policy.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    policy: Option<RateLimitPolicy>,
+    local: LocalBuckets,
+    #[cfg(feature = "redis-rate-limit")]
+    shared: Option<SharedBuckets>,
+}
+
+impl RateLimiter {
+    /// Limits on this replica alone
+    pub fn local(policy: RateLimitPolicy) -> Self {
+        Self {
+            policy: Some(policy),
+            ..Default::default()
+        }
+    }
+
+    /// Limits shared through the Redis server at `redis_url`; no connection is made until the
+    /// first request
+    #[cfg(feature = "redis-rate-limit")]
+    pub fn shared(
+        policy: RateLimitPolicy,
+        redis_url: &str,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let client = redis::Client::open(redis_url)
+            .map_err(|e| format!("Invalid REDIS_URL: {}", e.category()))?;
+        Ok(Self {
+            policy: Some(policy),
+            local: LocalBuckets::default(),
+            shared: Some(SharedBuckets {
+                client,
+                connection: tokio::sync::Mutex::new(None),
+                script: redis::Script::new(TOKEN_BUCKET_SCRIPT),
+                timeout,
+                unavailable_until: Mutex::new(None),
+            }),
+        })
+    }
+
+    pub fn policy(&self) -> Option<&RateLimitPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Takes a token from the bucket of the client identified by `key`
+    pub async fn check(&self, key: &str) -> Decision {
+        let Some(policy) = &self.policy else {
+            return Decision::Allowed;
+        };
+
+        #[cfg(feature = "redis-rate-limit")]
+        if let Some(shared) = &self.shared {
+            let skip = shared
+                .unavailable_until
+                .lock()
+                .expect("rate limit lock poisoned")
+                .is_some_and(|until| Instant::now() < until);
+            if !skip {
+                match shared.take(key, policy).await {
+                    Ok(decision) => return decision,
+                    Err(error) => {
+                        tracing::warn!(
+                            error = %error,
+                            retry_in_secs = REDIS_RETRY_DELAY.as_secs(),
+                            "Redis unavailable, rate limiting per replica"
+                        );
+                        *shared
+                            .unavailable_until
+                            .lock()
+                            .expect("rate limit lock poisoned") =
+                            Some(Instant::now() + REDIS_RETRY_DELAY);
+                    }
+                }
+            }
+        }
+
+        self.local.take(key, policy, Instant::now())
+    }
+}
+
+/// Build the rate limiter from environment variables
+///
+/// Disabled unless `RATE_LIMIT_PER_MINUTE` is set. Optional: `RATE_LIMIT_BURST` (default: the
+/// per-minute rate), `RATE_LIMIT_TRUST_FORWARDED_FOR` (default false), `REDIS_URL` (a secret),
+/// `RATE_LIMIT_REDIS_TIMEOUT_MS` (default 100).
+pub fn load_rate_limiter_from_env(secrets: &Secrets) -> Result<RateLimiter, String> {
+    let Some(requests_per_minute) = env_parse::<u32>("RATE_LIMIT_PER_MINUTE")? else {
+        return Ok(RateLimiter::default());
+    };
+    if requests_per_minute == 0 {
+        return Err("RATE_LIMIT_PER_MINUTE must be greater than 0".to_string());
+    }
+    let burst = env_parse("RATE_LIMIT_BURST")?.unwrap_or(requests_per_minute);
+    if burst == 0 {
+        return Err("RATE_LIMIT_BURST must be greater than 0".to_string());
+    }
+    let policy = RateLimitPolicy {
+        requests_per_minute,
+        burst,
+        trust_forwarded_for: env_parse("RATE_LIMIT_TRUST_FORWARDED_FOR")?.unwrap_or(false),
+    };
+
+    limiter_from_env(policy, secrets)
+}
+
+/// Limits shared through `REDIS_URL` when it is set, otherwise on this replica alone
+#[cfg(feature = "redis-rate-limit")]
+pub(crate) fn limiter_from_env(
+    policy: RateLimitPolicy,
+    secrets: &Secrets,
+) -> Result<RateLimiter, String> {
+    match secrets.get("REDIS_URL") {
+        Some(url) => {
+            let timeout = env_parse("RATE_LIMIT_REDIS_TIMEOUT_MS")?
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_REDIS_TIMEOUT);
+            RateLimiter::shared(policy, url.expose(), timeout)
+        }
+        None => Ok(RateLimiter::local(policy)),
+    }
+}
+
+/// Limits on this replica alone, as shared limits need the `redis-rate-limit` feature
+#[cfg(not(feature = "redis-rate-limit"))]
+pub(crate) fn limiter_from_env(
+    policy: RateLimitPolicy,
+    secrets: &Secrets,
+) -> Result<RateLimiter, String> {
+    if secrets.get("REDIS_URL").is_some() {
+        tracing::warn!(
+            "REDIS_URL is set, but the service was built without the redis-rate-limit feature; \
+             rate limiting per replica"
+        );
+    }
+    Ok(RateLimiter::local(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RateLimitPolicy {
+        RateLimitPolicy {
+            requests_per_minute: 60,
+            burst: 2,
+            trust_forwarded_for: false,
+        }
+    }
+
+    #[test]
+    fn test_local_token_bucket() {
+        let buckets = LocalBuckets::default();
+        let start = Instant::now();
+
+        assert_eq!(buckets.take("a", &policy(), start), Decision::Allowed);
+        assert_eq!(buckets.take("a", &policy(), start), Decision::Allowed);
+        assert_eq!(
+            buckets.take("a", &policy(), start),
+            Decision::Limited {
+                retry_after: Duration::from_secs(1)
+            }
+        );
+        // Other clients have their own bucket
+        assert_eq!(buckets.take("b", &policy(), start), Decision::Allowed);
+        // One token per second refills
+        let later = start + Duration::from_secs(1);
+        assert_eq!(buckets.take("a", &policy(), later), Decision::Allowed);
+        assert!(matches!(
+            buckets.take("a", &policy(), later),
+            Decision::Limited { .. }
+        ));
+    }
+
+    #[test]
+    fn test_caps_local_buckets() {
+        let buckets = LocalBuckets::default();
+        let start = Instant::now();
+        // Every client drains its bucket, so none is full when the map fills up
+        for client in 0..MAX_LOCAL_BUCKETS + 1 {
+            let now = start + Duration::from_millis(client as u64);
+            for _ in 0..2 {
+                buckets.take(&client.to_string(), &policy(), now);
+            }
+        }
+
+        let kept = buckets.buckets.lock().unwrap();
+        assert!(kept.len() <= MAX_LOCAL_BUCKETS);
+        // The least recently used went first
+        assert!(!kept.contains_key("0"));
+        assert!(kept.contains_key(&MAX_LOCAL_BUCKETS.to_string()));
+    }
+
+    #[cfg(feature = "redis-rate-limit")]
+    #[tokio::test]
+    async fn test_falls_back_to_local_buckets_without_redis() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("redis://{}/", closed.local_addr().unwrap());
+        drop(closed);
+        let limiter = RateLimiter::shared(policy(), &url, Duration::from_millis(200)).unwrap();
+
+        assert_eq!(limiter.check("a").await, Decision::Allowed);
+        assert_eq!(limiter.check("a").await, Decision::Allowed);
+        assert!(matches!(limiter.check("a").await, Decision::Limited { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_policy() {
+        let limiter = RateLimiter::default();
+        for _ in 0..100 {
+            assert_eq!(limiter.check("a").await, Decision::Allowed);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::notify::env_parse;
+
+/// Who turned read-only mode on when it comes from the environment
+pub const CONFIGURATION: &str = "configuration";
+
+/// Why, since when, and by whom the service is read-only
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReadOnlyStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub since: DateTime<Utc>,
+    pub changed_by: String,
+}
+
+/// Global switch pausing minting, binding and status or metadata changes, e.g. during a
+/// storage migration or a failover, while resolution and validation carry on
+#[derive(Debug, Default)]
+pub struct ReadOnly {
+    status: RwLock<Option<ReadOnlyStatus>>,
+    /// Writes started before read-only mode that may still be running
+    writes: AtomicUsize,
+}
+
+/// A write in progress, counted until dropped
+#[derive(Debug)]
+pub struct WriteGuard<'a>(&'a ReadOnly);
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.0.writes.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ReadOnly {
+    pub fn status(&self) -> Option<ReadOnlyStatus> {
+        self.status.read().expect("read-only lock poisoned").clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status
+            .read()
+            .expect("read-only lock poisoned")
+            .is_some()
+    }
+
+    /// Turns read-only mode on, or updates its reason if it already is
+    pub fn enable(&self, reason: Option<String>, changed_by: &str) -> ReadOnlyStatus {
+        let mut status = self.status.write().expect("read-only lock poisoned");
+        let since = status.as_ref().map_or_else(Utc::now, |status| status.since);
+        let enabled = ReadOnlyStatus {
+            reason,
+            since,
+            changed_by: changed_by.to_string(),
+        };
+        *status = Some(enabled.clone());
+        enabled
+    }
+
+    /// Turns read-only mode off, returning what it was
+    pub fn disable(&self) -> Option<ReadOnlyStatus> {
+        self.status.write().expect("read-only lock poisoned").take()
+    }
+
+    /// Turns read-only mode off unless someone changed it since it was turned on as `status`
+    pub fn disable_if(&self, status: &ReadOnlyStatus) -> bool {
+        let mut current = self.status.write().expect("read-only lock poisoned");
+        if current.as_ref() != Some(status) {
+            return false;
+        }
+        *current = None;
+        true
+    }
+
+    /// Starts a write that lasts until the guard is dropped, unless the service is read-only
+    pub fn begin_write(&self) -> Option<WriteGuard<'_>> {
+        // Counted while holding the lock, so no write starts once `enable` has returned
+        let status = self.status.read().expect("read-only lock poisoned");
+        if status.is_some() {
+            return None;
+        }
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        Some(WriteGuard(self))
+    }
+
+    /// Waits up to `timeout` for the writes started before read-only mode to finish, returning
+    /// whether they did
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.writes.load(Ordering::SeqCst) > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        true
+    }
+}
+
+/// Build the read-only switch from environment variables
+///
+/// Optional: `READ_ONLY` (default false), `READ_ONLY_REASON`.
+pub fn load_read_only_from_env() -> Result<ReadOnly, String> {
+    let read_only = ReadOnly::default();
+    if env_parse("READ_ONLY")?.unwrap_or(false) {
+        let reason = std::env::var("READ_ONLY_REASON")
+            .ok()
+            .filter(|reason| !reason.trim().is_empty());
+        read_only.enable(reason, CONFIGURATION);
+    }
+    Ok(read_only)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enable_and_disable() {
+        let read_only = ReadOnly::default();
+        assert!(!read_only.is_enabled());
+        assert_eq!(read_only.disable(), None);
+
+        let enabled = read_only.enable(Some("Storage migration".to_string()), "alice");
+        assert!(read_only.is_enabled());
+        assert_eq!(read_only.status(), Some(enabled.clone()));
+
+        // A new reason keeps the time it started
+        let updated = read_only.enable(Some("Failover to eu-west".to_string()), "bob");
+        assert_eq!(updated.since, enabled.since);
+        assert_eq!(updated.changed_by, "bob");
+
+        assert_eq!(read_only.disable(), Some(updated));
+        assert!(!read_only.is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_drains_writes_started_before_read_only() {
+        let read_only = ReadOnly::default();
+        let write = read_only.begin_write().unwrap();
+        let paused = read_only.enable(None, "snapshot");
+        assert!(read_only.begin_write().is_none());
+        assert!(!read_only.drain(Duration::from_millis(20)).await);
+        drop(write);
+        assert!(read_only.drain(Duration::from_millis(20)).await);
+
+        // Someone else's read-only mode is left on
+        read_only.enable(Some("Failover".to_string()), "alice");
+        assert!(!read_only.disable_if(&paused));
+        assert!(read_only.is_enabled());
+    }
+}
@@ -0,0 +1,389 @@
+use std::borrow::Cow;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, RwLock};
+
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::notify::env_parse;
+use crate::secrets::Secrets;
+
+/// What redacted values are replaced with in log output
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Query parameters that carry credentials in signed or tokenized URLs, redacted by default
+pub const DEFAULT_REDACTED_QUERY_PARAMS: [&str; 11] = [
+    "access_token",
+    "api_key",
+    "key",
+    "sig",
+    "signature",
+    "token",
+    "x-amz-credential",
+    "x-amz-security-token",
+    "x-amz-signature",
+    "x-goog-credential",
+    "x-goog-signature",
+];
+
+/// Shortest secret value redacted verbatim; shorter ones would mangle unrelated output
+const MIN_SECRET_LENGTH: usize = 4;
+
+/// Scrubs sensitive values from log lines before they are written
+///
+/// Secrets are registered once they are loaded, which is after logging starts, so everything
+/// logged from then on is covered whatever its level or field.
+#[derive(Debug)]
+pub struct Redactor {
+    secrets: RwLock<Vec<String>>,
+    query_params: Vec<String>,
+    client_ips: bool,
+}
+
+impl Default for Redactor {
+    /// Redacts the default query parameters, but not client addresses
+    fn default() -> Self {
+        Self::new(Vec::new(), false)
+    }
+}
+
+impl Redactor {
+    /// Redacts `query_params` on top of [`DEFAULT_REDACTED_QUERY_PARAMS`], and IP addresses
+    /// if `client_ips` is set
+    pub fn new(query_params: Vec<String>, client_ips: bool) -> Self {
+        let mut query_params: Vec<String> = DEFAULT_REDACTED_QUERY_PARAMS
+            .iter()
+            .map(|param| param.to_string())
+            .chain(query_params.into_iter().map(|param| param.to_lowercase()))
+            .collect();
+        query_params.sort();
+        query_params.dedup();
+        Self {
+            secrets: RwLock::new(Vec::new()),
+            query_params,
+            client_ips,
+        }
+    }
+
+    /// Redacts `value` wherever it appears from now on
+    pub fn add_secret(&self, value: &str) {
+        if value.len() < MIN_SECRET_LENGTH {
+            return;
+        }
+        let mut secrets = self.secrets.write().expect("redactor lock poisoned");
+        if !secrets.iter().any(|secret| secret == value) {
+            secrets.push(value.to_string());
+            // Longest first, so a secret containing another is redacted whole
+            secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        }
+    }
+
+    /// Redacts every credential loaded at startup
+    pub fn add_secrets(&self, secrets: &Secrets) {
+        for secret in secrets.iter() {
+            self.add_secret(secret.expose());
+        }
+    }
+
+    /// The line with secrets, credential query parameters, and (if enabled) IP addresses
+    /// replaced by [`REDACTED`]
+    pub fn redact<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        for secret in self.secrets.read().expect("redactor lock poisoned").iter() {
+            if line.contains(secret.as_str()) {
+                line = Cow::Owned(line.replace(secret.as_str(), REDACTED));
+            }
+        }
+        if let Some(redacted) = self.redact_query_params(&line) {
+            line = Cow::Owned(redacted);
+        }
+        if self.client_ips
+            && let Some(redacted) = redact_ip_addresses(&line)
+        {
+            line = Cow::Owned(redacted);
+        }
+        line
+    }
+
+    /// Replaces the values of `name=value` pairs whose name is a redacted query parameter
+    fn redact_query_params(&self, line: &str) -> Option<String> {
+        let mut redacted = String::with_capacity(line.len());
+        let mut changed = false;
+        let mut rest = line;
+        while let Some(equals) = rest.find('=') {
+            let (before, after) = rest.split_at(equals);
+            let name_start = before
+                .rfind(|c: char| !is_param_name_char(c))
+                .map_or(0, |i| {
+                    i + before[i..].chars().next().map_or(1, char::len_utf8)
+                });
+            let name = &before[name_start..];
+            let value_len = after[1..]
+                .find(|c: char| {
+                    matches!(c, '?' | '&' | '#' | '"' | '\'' | '>') || c.is_whitespace()
+                })
+                .unwrap_or(after.len() - 1);
+            // Only parameters of a query string, not fields such as `key=value` in a log line
+            let in_query = before[..name_start].ends_with(['?', '&']);
+            redacted.push_str(before);
+            redacted.push('=');
+            if in_query && value_len > 0 && self.query_params.contains(&name.to_lowercase()) {
+                redacted.push_str(REDACTED);
+                changed = true;
+            } else {
+                redacted.push_str(&after[1..1 + value_len]);
+            }
+            rest = &after[1 + value_len..];
+        }
+        redacted.push_str(rest);
+        changed.then_some(redacted)
+    }
+}
+
+fn is_param_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+/// Replaces IPv4 and IPv6 addresses, with or without a port
+fn redact_ip_addresses(line: &str) -> Option<String> {
+    let is_address_char = |c: char| c.is_ascii_hexdigit() || c == ':' || c == '.';
+    let mut redacted = String::with_capacity(line.len());
+    let mut changed = false;
+    let mut rest = line;
+    while let Some(start) = rest.find(is_address_char) {
+        let (before, candidate) = rest.split_at(start);
+        let end = candidate
+            .find(|c: char| !is_address_char(c))
+            .unwrap_or(candidate.len());
+        let (token, after) = candidate.split_at(end);
+        // Separators around the address, as in `ip:192.0.2.1` or a sentence's final period
+        let core = token.trim_matches([':', '.']);
+        // A digit tells `fe80::1` from Rust paths like `ce::e` that also parse as IPv6
+        let is_address = core.contains(|c: char| c.is_ascii_digit())
+            && (core.parse::<IpAddr>().is_ok() || core.parse::<SocketAddr>().is_ok());
+        redacted.push_str(before);
+        if is_address {
+            let leading = token.len() - token.trim_start_matches([':', '.']).len();
+            redacted.push_str(&token[..leading]);
+            redacted.push_str(REDACTED);
+            redacted.push_str(&token[leading + core.len()..]);
+            changed = true;
+        } else {
+            redacted.push_str(token);
+        }
+        rest = after;
+    }
+    redacted.push_str(rest);
+    changed.then_some(redacted)
+}
+
+/// Wraps a log writer so everything written through it is redacted first
+#[derive(Debug)]
+pub struct RedactingMakeWriter<M> {
+    redactor: Arc<Redactor>,
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(redactor: Arc<Redactor>, inner: M) -> Self {
+        Self { redactor, inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<'a, M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            redactor: &self.redactor,
+            inner: self.inner.make_writer(),
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+        RedactingWriter {
+            redactor: &self.redactor,
+            inner: self.inner.make_writer_for(meta),
+        }
+    }
+}
+
+/// Writer for one log event, see [`RedactingMakeWriter`]
+///
+/// The formatter writes each event in a single call, so values are never split across writes.
+#[derive(Debug)]
+pub struct RedactingWriter<'a, W> {
+    redactor: &'a Redactor,
+    inner: W,
+}
+
+impl<W: io::Write> io::Write for RedactingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.inner
+            .write_all(self.redactor.redact(&line).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Configure log redaction from `LOG_REDACT_QUERY_PARAMS` (comma-separated, added to the
+/// defaults) and `LOG_REDACT_CLIENT_IPS`
+pub fn load_redactor_from_env() -> Result<Redactor, String> {
+    let query_params = std::env::var("LOG_REDACT_QUERY_PARAMS")
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|param| !param.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let client_ips = env_parse("LOG_REDACT_CLIENT_IPS")?.unwrap_or(false);
+    Ok(Redactor::new(query_params, client_ips))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::{RateLimitPolicy, RateLimiter};
+    use crate::server::create_router;
+    use crate::testing::{ADMIN_KEY, fixture_state};
+    use axum::body::Body;
+    use axum::http::{Request, header};
+    use std::sync::Mutex;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_redacts_secrets_and_signed_urls() {
+        let redactor = Redactor::new(vec!["X-Session".to_string()], false);
+        redactor.add_secret("s3cr3t-admin-key");
+        redactor.add_secret("abc");
+
+        assert_eq!(
+            redactor.redact("key s3cr3t-admin-key rejected, abc kept"),
+            "key [REDACTED] rejected, abc kept"
+        );
+        assert_eq!(
+            redactor.redact(
+                "upstream=https://cdn.example.org/a.pdf?X-Amz-Signature=f00d&page=2&x-session=1 failed"
+            ),
+            "upstream=https://cdn.example.org/a.pdf?X-Amz-Signature=[REDACTED]&page=2&x-session=[REDACTED] failed"
+        );
+        // Log fields of the same name are not query parameters
+        assert_eq!(
+            redactor.redact("token=expired key=x6"),
+            "token=expired key=x6"
+        );
+        assert!(matches!(
+            redactor.redact("nothing to hide"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_redacts_client_ips_in_privacy_mode() {
+        let line =
+            "client=ip:203.0.113.7 peer=[2001:db8::1]:443 ark:12345/x6np1wh8k at 10:15:00.123.";
+        assert!(matches!(Redactor::default().redact(line), Cow::Borrowed(_)));
+        assert_eq!(
+            Redactor::new(Vec::new(), true).redact(line),
+            "client=ip:[REDACTED] peer=[[REDACTED]]:443 ark:12345/x6np1wh8k at 10:15:00.123."
+        );
+        assert_eq!(
+            Redactor::new(Vec::new(), true).redact("from 192.0.2.1:8080."),
+            "from [REDACTED]."
+        );
+    }
+
+    /// Collects log output in memory
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handlers_log_nothing_sensitive_at_trace_level() {
+        let redactor = Arc::new(Redactor::new(Vec::new(), true));
+        redactor.add_secret(ADMIN_KEY);
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(RedactingMakeWriter::new(redactor, move || writer.clone()))
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let authorized = [
+            ("GET", "/api/v1/info", ""),
+            ("GET", "/ark:12345/servicestatus", ""),
+            ("POST", "/api/v1/mint", r#"{"shoulder": "x6", "count": 2}"#),
+            ("POST", "/api/v1/mint", r#"{"shoulder": "z9", "count": 2}"#),
+            (
+                "POST",
+                "/api/v1/validate",
+                r#"{"arks": ["ark:12345/x6np1wh8k"]}"#,
+            ),
+            ("GET", "/api/v1/shoulders/x6/capacity", ""),
+            ("GET", "/ark:12345/x6np1wh8k?token=f00d", ""),
+            ("GET", "/ark:12345/x6np1wh8k.", ""),
+            ("GET", "/ark:12345/z9np1wh8k", ""),
+            ("GET", "/admin/api/mint-stats", ""),
+            ("GET", "/admin/api/quarantine", ""),
+        ];
+        // Anonymous clients go over the limit the key holder stays within, to log their address
+        let anonymous = [
+            ("GET", "/ark:12345/x6np1wh8k?sig=f00d", ""),
+            ("GET", "/ark:12345/b3np1wh8k", ""),
+        ]
+        .repeat(authorized.len());
+        let app = create_router(Arc::new(crate::config::AppState {
+            rate_limiter: RateLimiter::local(RateLimitPolicy {
+                requests_per_minute: 1,
+                burst: authorized.len() as u32,
+                trust_forwarded_for: true,
+            }),
+            ..fixture_state()
+        }));
+        for (authorization, requests) in [(true, &authorized[..]), (false, &anonymous[..])] {
+            for (method, uri, body) in requests {
+                let mut request = Request::builder()
+                    .method(*method)
+                    .uri(*uri)
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .header("x-forwarded-for", "203.0.113.7");
+                if authorization {
+                    request =
+                        request.header(header::AUTHORIZATION, format!("Bearer {}", ADMIN_KEY));
+                }
+                let request = request.body(Body::from(body.to_string())).unwrap();
+                app.clone().oneshot(request).await.unwrap();
+            }
+        }
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("Request rate limited"), "{}", output);
+        assert!(output.contains(REDACTED), "{}", output);
+        for sensitive in [ADMIN_KEY, "203.0.113.7", "f00d"] {
+            assert!(
+                !output.contains(sensitive),
+                "{} logged in {}",
+                sensitive,
+                output
+            );
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::api_keys::{ApiKey, KeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, StorageError};
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::idempotency::{MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerEntry, LedgerPage, MintLedger};
+use crate::notify::env_parse;
+use crate::store::ArkStore;
+
+/// Time a Redis round trip may take unless `ARK_STORE_REDIS_TIMEOUT_MS` says otherwise
+pub const DEFAULT_REDIS_STORE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Hash of minted ARKs, to their shoulder
+const MINTED_KEY: &str = "ark-service:store:minted";
+/// Hash of minted ARKs, to when they were recorded (RFC 3339)
+const MINTED_AT_KEY: &str = "ark-service:store:minted-at";
+/// Hash of minted ARKs, to the metadata kept with them as JSON
+const MINTED_METADATA_KEY: &str = "ark-service:store:minted-metadata";
+/// Hash of each shoulder's next sequential position
+const SEQUENCES_KEY: &str = "ark-service:store:sequences";
+/// Hash of bindings as JSON, by normalized ARK
+const BINDINGS_KEY: &str = "ark-service:store:bindings";
+/// Hash of webhook deliveries as JSON, by id
+const DELIVERIES_KEY: &str = "ark-service:store:deliveries";
+/// Hash of mint receipts as JSON, by idempotency key
+const RECEIPTS_KEY: &str = "ark-service:store:receipts";
+/// Hash of managed API keys as JSON, by id
+const API_KEYS_KEY: &str = "ark-service:store:api-keys";
+
+/// Records ARKs on a shoulder (`ARGV[1]`) at a time (`ARGV[2]`), with metadata (`ARGV[3]` as
+/// JSON, or empty for none), in one step, returning those already recorded
+const RECORD_SCRIPT: &str = r#"
+local taken = {}
+for i = 4, #ARGV do
+  if redis.call('HSETNX', KEYS[1], ARGV[i], ARGV[1]) == 0 then
+    table.insert(taken, ARGV[i])
+  else
+    redis.call('HSET', KEYS[2], ARGV[i], ARGV[2])
+    if ARGV[3] ~= '' then
+      redis.call('HSET', KEYS[3], ARGV[i], ARGV[3])
+    end
+  end
+end
+return taken
+"#;
+
+/// Keeps metadata (`ARGV[1]` as JSON, or empty to drop it) with the recorded ARKs among the
+/// others
+const ANNOTATE_SCRIPT: &str = r#"
+for i = 2, #ARGV do
+  if redis.call('HEXISTS', KEYS[1], ARGV[i]) == 1 then
+    if ARGV[1] == '' then
+      redis.call('HDEL', KEYS[2], ARGV[i])
+    else
+      redis.call('HSET', KEYS[2], ARGV[i], ARGV[1])
+    end
+  end
+end
+return 0
+"#;
+
+/// Mint ledger, binding store, webhook deliveries, mint receipts and API keys in Redis, which
+/// replicas of the service can share
+///
+/// Durability is the server's: without append-only persistence, a Redis restart forgets
+/// minted ARKs. Queries block the calling thread, like the SQLite store's.
+pub struct RedisStore {
+    client: redis::Client,
+    timeout: Duration,
+    /// Dropped after a connection failure, and opened again by the next query
+    connection: Mutex<Option<redis::Connection>>,
+    record_script: redis::Script,
+    annotate_script: redis::Script,
+}
+
+impl RedisStore {
+    /// Connects to `url` and checks that the server answers
+    pub fn connect(url: &str, timeout: Duration) -> Result<Self, StorageError> {
+        // The URL may hold a password, so only the kind of error is reported
+        let client = redis::Client::open(url)
+            .map_err(|e| StorageError(format!("redis store: invalid URL: {}", e.category())))?;
+        let store = Self {
+            client,
+            timeout,
+            connection: Mutex::new(None),
+            record_script: redis::Script::new(RECORD_SCRIPT),
+            annotate_script: redis::Script::new(ANNOTATE_SCRIPT),
+        };
+        store.run(|connection| redis::cmd("PING").query::<String>(connection))?;
+        Ok(store)
+    }
+
+    /// Runs a query on the store's connection, connecting first if needed
+    fn run<T>(
+        &self,
+        query: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<T>,
+    ) -> Result<T, StorageError> {
+        let mut connection = self.connection.lock().expect("redis store lock poisoned");
+        if connection.is_none() {
+            let connected = self
+                .client
+                .get_connection_with_timeout(self.timeout)
+                .map_err(redis_error)?;
+            connected
+                .set_read_timeout(Some(self.timeout))
+                .and_then(|()| connected.set_write_timeout(Some(self.timeout)))
+                .map_err(redis_error)?;
+            *connection = Some(connected);
+        }
+        let result = query(connection.as_mut().expect("connected above"));
+        if let Err(e) = &result
+            && (e.is_io_error() || e.is_connection_dropped() || e.is_unrecoverable_error())
+        {
+            *connection = None;
+        }
+        result.map_err(redis_error)
+    }
+
+    /// Values of a hash, parsed from JSON
+    fn values<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Vec<T>, StorageError> {
+        let values: Vec<String> =
+            self.run(|connection| redis::cmd("HVALS").arg(key).query(connection))?;
+        values.iter().map(|json| parse(json)).collect()
+    }
+
+    /// One value of a hash, parsed from JSON
+    fn value<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+        field: &str,
+    ) -> Result<Option<T>, StorageError> {
+        let value: Option<String> =
+            self.run(|connection| redis::cmd("HGET").arg(key).arg(field).query(connection))?;
+        value.as_deref().map(parse).transpose()
+    }
+
+    /// Removes fields of a hash, returning how many were there
+    fn remove(&self, key: &str, fields: &[String]) -> Result<usize, StorageError> {
+        if fields.is_empty() {
+            return Ok(0);
+        }
+        self.run(|connection| redis::cmd("HDEL").arg(key).arg(fields).query(connection))
+    }
+}
+
+impl MintLedger for RedisStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        if arks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let json = if metadata.is_empty() {
+            String::new()
+        } else {
+            serde_json::to_string(metadata).expect("metadata serializes")
+        };
+        self.run(|connection| {
+            self.record_script
+                .key(MINTED_KEY)
+                .key(MINTED_AT_KEY)
+                .key(MINTED_METADATA_KEY)
+                .arg(shoulder)
+                .arg(Utc::now().to_rfc3339())
+                .arg(&json)
+                .arg(arks)
+                .invoke(connection)
+        })
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        if arks.is_empty() {
+            return Ok(());
+        }
+        let json = if metadata.is_empty() {
+            String::new()
+        } else {
+            serde_json::to_string(metadata).expect("metadata serializes")
+        };
+        self.run(|connection| {
+            self.annotate_script
+                .key(MINTED_KEY)
+                .key(MINTED_METADATA_KEY)
+                .arg(&json)
+                .arg(arks)
+                .invoke::<()>(connection)
+        })
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        self.run(|connection| {
+            redis::cmd("HEXISTS")
+                .arg(MINTED_KEY)
+                .arg(ark)
+                .query(connection)
+        })
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.run(|connection| redis::cmd("HLEN").arg(MINTED_KEY).query(connection))
+    }
+
+    /// The hash of minted ARKs is not kept by shoulder, so counting them would take a scan
+    fn minted_on(&self, _shoulder: &str) -> Result<Option<usize>, StorageError> {
+        Ok(None)
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        let next: u64 = self.run(|connection| {
+            redis::cmd("HINCRBY")
+                .arg(SEQUENCES_KEY)
+                .arg(shoulder)
+                .arg(count)
+                .query(connection)
+        })?;
+        Ok(next - count)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        let next: Option<u64> = self.run(|connection| {
+            redis::cmd("HGET")
+                .arg(SEQUENCES_KEY)
+                .arg(shoulder)
+                .query(connection)
+        })?;
+        Ok(next.unwrap_or(0))
+    }
+
+    /// Pages follow Redis' `HSCAN` cursor, so they are in no particular order
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        let (next, fields): (String, Vec<String>) = self.run(|connection| {
+            redis::cmd("HSCAN")
+                .arg(MINTED_KEY)
+                .arg(cursor.unwrap_or("0"))
+                .arg("COUNT")
+                .arg(limit)
+                .query(connection)
+        })?;
+        let arks: Vec<(String, String)> = fields
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .filter(|(_, on)| shoulder.is_none_or(|shoulder| on == shoulder))
+            .collect();
+        let names: Vec<&str> = arks.iter().map(|(ark, _)| ark.as_str()).collect();
+        let values_of = |key: &str| -> Result<Vec<Option<String>>, StorageError> {
+            if names.is_empty() {
+                return Ok(Vec::new());
+            }
+            self.run(|connection| redis::cmd("HMGET").arg(key).arg(&names).query(connection))
+        };
+        let minted_at = values_of(MINTED_AT_KEY)?;
+        let metadata = values_of(MINTED_METADATA_KEY)?;
+        let entries = arks
+            .into_iter()
+            .zip(minted_at.into_iter().zip(metadata))
+            .map(|((ark, shoulder), (minted_at, metadata))| LedgerEntry {
+                ark,
+                shoulder,
+                minted_at: minted_at
+                    .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+                    .map(|at| at.with_timezone(&Utc)),
+                metadata: metadata
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Ok(LedgerPage {
+            entries,
+            next: (next != "0").then_some(next),
+        })
+    }
+}
+
+impl BindingStore for RedisStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.value(BINDINGS_KEY, canonical)
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        if bindings.is_empty() {
+            return Ok(());
+        }
+        let mut hset = redis::cmd("HSET");
+        hset.arg(BINDINGS_KEY);
+        for (canonical, binding) in &bindings {
+            let json = serde_json::to_string(binding).expect("binding serializes");
+            hset.arg(canonical).arg(json);
+        }
+        self.run(|connection| hset.query::<()>(connection))
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        let json = serde_json::to_string(binding).expect("binding serializes");
+        let inserted: bool = self.run(|connection| {
+            redis::cmd("HSETNX")
+                .arg(BINDINGS_KEY)
+                .arg(canonical)
+                .arg(json)
+                .query(connection)
+        })?;
+        if inserted {
+            return Ok(None);
+        }
+        self.get(canonical)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.run(|connection| redis::cmd("HLEN").arg(BINDINGS_KEY).query(connection))
+    }
+
+    fn count_prefixed(&self, _prefix: &str) -> Result<Option<usize>, StorageError> {
+        Ok(None)
+    }
+
+    /// Pages follow Redis' `HSCAN` cursor, so they are in no particular order
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        let (next, fields): (String, Vec<String>) = self.run(|connection| {
+            redis::cmd("HSCAN")
+                .arg(BINDINGS_KEY)
+                .arg(cursor.unwrap_or("0"))
+                .arg("COUNT")
+                .arg(limit)
+                .query(connection)
+        })?;
+        let bindings = fields
+            .chunks_exact(2)
+            .map(|pair| Ok((pair[0].clone(), parse(&pair[1])?)))
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        Ok(BindingPage {
+            bindings,
+            next: (next != "0").then_some(next),
+        })
+    }
+}
+
+impl DeliveryStore for RedisStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        let json = serde_json::to_string(delivery).expect("delivery serializes");
+        self.run(|connection| {
+            redis::cmd("HSET")
+                .arg(DELIVERIES_KEY)
+                .arg(&delivery.id)
+                .arg(json)
+                .query(connection)
+        })
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        self.value(DELIVERIES_KEY, id)
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        let mut deliveries: Vec<Delivery> = self
+            .values::<Delivery>(DELIVERIES_KEY)?
+            .into_iter()
+            .filter(|delivery| status.is_none_or(|status| delivery.status == status))
+            .collect();
+        deliveries.sort_by_key(|delivery| std::cmp::Reverse(delivery.created_at));
+        Ok(deliveries)
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        let pruned: Vec<String> = self
+            .values::<Delivery>(DELIVERIES_KEY)?
+            .into_iter()
+            .filter(|delivery| {
+                delivery.status == DeliveryStatus::Delivered && delivery.created_at < before
+            })
+            .map(|delivery| delivery.id)
+            .collect();
+        self.remove(DELIVERIES_KEY, &pruned)
+    }
+}
+
+impl ReceiptStore for RedisStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        let json = serde_json::to_string(receipt).expect("receipt serializes");
+        let inserted: bool = self.run(|connection| {
+            redis::cmd("HSETNX")
+                .arg(RECEIPTS_KEY)
+                .arg(&receipt.key)
+                .arg(json)
+                .query(connection)
+        })?;
+        if inserted {
+            return Ok(None);
+        }
+        self.receipt(&receipt.key)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        self.value(RECEIPTS_KEY, key)
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        let pruned: Vec<String> = self
+            .values::<MintReceipt>(RECEIPTS_KEY)?
+            .into_iter()
+            .filter(|receipt| receipt.created_at < before)
+            .map(|receipt| receipt.key)
+            .collect();
+        self.remove(RECEIPTS_KEY, &pruned)
+    }
+}
+
+impl KeyStore for RedisStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        let json = serde_json::to_string(key).expect("api key serializes");
+        self.run(|connection| {
+            redis::cmd("HSET")
+                .arg(API_KEYS_KEY)
+                .arg(&key.id)
+                .arg(json)
+                .query(connection)
+        })
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        self.values(API_KEYS_KEY)
+    }
+}
+
+impl ArkStore for RedisStore {
+    fn backend(&self) -> &'static str {
+        "redis"
+    }
+}
+
+fn redis_error(error: redis::RedisError) -> StorageError {
+    StorageError(format!("redis store: {}", error))
+}
+
+fn parse<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, StorageError> {
+    serde_json::from_str(json).map_err(|e| StorageError(format!("redis store: bad value: {}", e)))
+}
+
+/// Connect to Redis from environment variables; `None` unless `ARK_STORE_REDIS_URL` is set
+///
+/// Optional: `ARK_STORE_REDIS_TIMEOUT_MS` (default 1000).
+pub fn load_redis_store_from_env() -> Result<Option<Arc<RedisStore>>, String> {
+    let Ok(url) = std::env::var("ARK_STORE_REDIS_URL") else {
+        return Ok(None);
+    };
+    let timeout = env_parse("ARK_STORE_REDIS_TIMEOUT_MS")?
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_REDIS_STORE_TIMEOUT);
+    if timeout.is_zero() {
+        return Err("ARK_STORE_REDIS_TIMEOUT_MS must be greater than 0".to_string());
+    }
+    RedisStore::connect(&url, timeout)
+        .map(|store| Some(Arc::new(store)))
+        .map_err(|e| format!("Failed to connect to ARK_STORE_REDIS_URL: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::parse_ark;
+    use crate::bindings::Bindings;
+
+    /// Runs against the server at `TEST_REDIS_URL`, and is skipped when that is unset
+    #[test]
+    fn test_redis_store_shares_ledger_and_bindings() {
+        let Ok(url) = std::env::var("TEST_REDIS_URL") else {
+            return;
+        };
+        let store = Arc::new(RedisStore::connect(&url, DEFAULT_REDIS_STORE_TIMEOUT).unwrap());
+        let blade = format!("x6rd{}", Utc::now().timestamp_micros());
+        let ark = format!("ark:12345/{}", blade);
+
+        let minted = std::slice::from_ref(&ark);
+        assert!(
+            store
+                .record("x6", minted, &BTreeMap::new())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store.record("x6", minted, &BTreeMap::new()).unwrap(),
+            minted
+        );
+        assert_eq!(store.reserve_sequence(&blade, 3).unwrap(), 0);
+        let delivery = Delivery::new(
+            "https://hooks.example.org/x6",
+            "x6",
+            serde_json::json!({"shoulder": "x6"}),
+        );
+        store.save_delivery(&delivery).unwrap();
+        let receipt = MintReceipt {
+            key: blade.clone(),
+            shoulder: "x6".to_string(),
+            count: 1,
+            metadata: Default::default(),
+            detail: false,
+            arks: minted.to_vec(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(store.save_receipt(&receipt).unwrap(), None);
+        let parsed = parse_ark(&ark).unwrap();
+        Bindings::new(store.clone())
+            .bind_all([(
+                parsed.clone(),
+                Binding {
+                    target: "https://example.org/redis".to_string(),
+                    metadata: Default::default(),
+                    bound_at: Utc::now(),
+                    lifecycle: Default::default(),
+                },
+            )])
+            .unwrap();
+
+        // A second connection, as another replica would open, sees everything
+        let replica = RedisStore::connect(&url, DEFAULT_REDIS_STORE_TIMEOUT).unwrap();
+        assert!(replica.contains(&ark).unwrap());
+        assert_eq!(replica.sequence_position(&blade).unwrap(), 3);
+        assert_eq!(replica.delivery(&delivery.id).unwrap(), Some(delivery));
+        assert_eq!(
+            replica.save_receipt(&MintReceipt {
+                arks: Vec::new(),
+                ..receipt.clone()
+            }),
+            Ok(Some(receipt))
+        );
+        let binding = replica.get(parsed.canonical()).unwrap().unwrap();
+        assert_eq!(binding.target, "https://example.org/redis");
+    }
+
+    #[test]
+    fn test_connect_fails_without_server() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("redis://{}/", closed.local_addr().unwrap());
+        drop(closed);
+        assert!(RedisStore::connect(&url, Duration::from_millis(200)).is_err());
+    }
+}
@@ -0,0 +1,1016 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::api_keys::{ApiKey, KeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, StorageError};
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::http_client::{HttpClient, HttpClients};
+use crate::idempotency::{MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerPage, MintLedger};
+use crate::notify::env_parse;
+use crate::secrets::{Secret, Secrets};
+use crate::store::ArkStore;
+
+/// Where a standby accepts replicated changes, relative to its admin API path
+pub const REPLICATION_CHANGES_PATH: &str = "/replication/changes";
+
+/// Largest batch of changes, in bytes, that a standby accepts
+pub const MAX_REPLICATION_BATCH_BYTES: usize = 16 * 1024 * 1024;
+
+/// A change to the ARK store, as mirrored to the standby
+///
+/// Applying a change a second time leaves the store as it was, so batches the standby may
+/// already have applied can safely be sent again.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Change {
+    /// ARKs newly recorded in the mint ledger, with the metadata kept with them
+    Mint {
+        shoulder: String,
+        arks: Vec<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        metadata: BTreeMap<String, String>,
+    },
+    /// Bindings recorded together, keyed by canonical ARK
+    Bind { bindings: Vec<(String, Binding)> },
+    /// Metadata kept with recorded ARKs, replacing what they had
+    Annotate {
+        arks: Vec<String>,
+        metadata: BTreeMap<String, String>,
+    },
+    /// A shoulder's sequential counter moved on to `next`
+    Sequence { shoulder: String, next: u64 },
+    /// The answer to a mint request with an idempotency key
+    Receipt { receipt: MintReceipt },
+    /// A managed API key created, or its expiry changed
+    Key { key: ApiKey },
+}
+
+impl Change {
+    pub fn apply(&self, store: &dyn ArkStore) -> Result<(), StorageError> {
+        match self {
+            Change::Mint {
+                shoulder,
+                arks,
+                metadata,
+            } => store.record(shoulder, arks, metadata).map(|_| ()),
+            Change::Annotate { arks, metadata } => store.annotate(arks, metadata),
+            Change::Bind { bindings } => store.put_all(bindings.clone()),
+            Change::Sequence { shoulder, next } => {
+                let position = store.sequence_position(shoulder)?;
+                if *next > position {
+                    store.reserve_sequence(shoulder, next - position)?;
+                }
+                Ok(())
+            }
+            Change::Receipt { receipt } => store.save_receipt(receipt).map(|_| ()),
+            Change::Key { key } => store.put_key(key),
+        }
+    }
+}
+
+/// A change with its place in the replication stream
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ReplicatedChange {
+    pub id: u64,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// Changes sent to the standby in one request, oldest first
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ChangeBatch {
+    pub changes: Vec<ReplicatedChange>,
+}
+
+/// Delivers batches of changes to the standby
+pub trait StandbyTransport: Send + Sync {
+    fn send(&self, batch: String) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>>;
+}
+
+/// Posts changes to the admin API of a standby ark-service
+pub struct HttpStandby {
+    client: HttpClient,
+    url: String,
+    key: Secret,
+}
+
+impl HttpStandby {
+    /// `admin_api_url` is the standby's admin API, e.g. `https://standby.example.org/admin/api`
+    pub fn new(client: HttpClient, admin_api_url: &str, key: Secret) -> Self {
+        Self {
+            client,
+            url: format!(
+                "{}{}",
+                admin_api_url.trim_end_matches('/'),
+                REPLICATION_CHANGES_PATH
+            ),
+            key,
+        }
+    }
+}
+
+impl StandbyTransport for HttpStandby {
+    fn send(&self, batch: String) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+        let request = self
+            .client
+            .post(&self.url)
+            .bearer_auth(self.key.expose())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(batch);
+        Box::pin(async move {
+            let response = self.client.send(request).await?;
+            response
+                .error_for_status()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// How changes are shipped to the standby, and how far behind it may fall
+#[derive(Clone, Debug)]
+pub struct ReplicationPolicy {
+    /// How often queued changes are sent
+    pub flush_interval: Duration,
+    /// Maximum number of changes in one request
+    pub max_batch: usize,
+    /// Maximum size of one request, in bytes of JSON; a larger change is sent alone
+    pub max_batch_bytes: usize,
+    /// Changes kept while the standby is unreachable; the oldest are dropped beyond this
+    pub capacity: usize,
+    /// Lag beyond which replication is reported as failing
+    pub max_lag: Duration,
+}
+
+impl Default for ReplicationPolicy {
+    fn default() -> Self {
+        Self {
+            flush_interval: Duration::from_secs(1),
+            max_batch: 500,
+            max_batch_bytes: 1024 * 1024,
+            capacity: 100_000,
+            max_lag: Duration::from_secs(60),
+        }
+    }
+}
+
+/// How far the standby is behind, for the admin API and the status page
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReplicationStatus {
+    pub enabled: bool,
+    /// Changes not yet acknowledged by the standby
+    pub pending: usize,
+    /// Age of the oldest pending change, in seconds
+    pub lag_seconds: i64,
+    /// Whether the lag exceeds the allowed maximum or changes were dropped
+    pub lagging: bool,
+    /// Changes the standby acknowledged since the service started
+    pub replicated: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_replicated_at: Option<DateTime<Utc>>,
+    /// When sending started failing; cleared by the next successful batch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failing_since: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Changes dropped because the queue was full; the standby needs re-seeding if any were
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    changes: VecDeque<ReplicatedChange>,
+    next_id: u64,
+    replicated: u64,
+    last_replicated_at: Option<DateTime<Utc>>,
+    failing_since: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    dropped: u64,
+}
+
+/// A line of the queue file: a pending change, or how many changes were dropped
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum QueueLine {
+    Dropped { dropped: u64 },
+    Change(ReplicatedChange),
+}
+
+/// Mirrors changes to the ARK store to a warm standby, in the order they were made
+///
+/// Changes are queued as [`ReplicatedStore`] makes them and sent in batches by
+/// [`Replication::flush`]; a batch stays queued until the standby acknowledges it. The queue is
+/// kept in memory, and also in a file if one is given, so a restart does not lose it.
+#[derive(Default)]
+pub struct Replication {
+    transport: Option<Arc<dyn StandbyTransport>>,
+    policy: ReplicationPolicy,
+    queue: Mutex<Queue>,
+    file: Option<PathBuf>,
+}
+
+impl Replication {
+    pub fn new(transport: Arc<dyn StandbyTransport>, policy: ReplicationPolicy) -> Self {
+        Self {
+            transport: Some(transport),
+            policy,
+            queue: Mutex::new(Queue::default()),
+            file: None,
+        }
+    }
+
+    /// Replication whose queue is also kept in the file at `path`, picking up the changes and
+    /// drop count left there by a previous run
+    pub fn persisted(
+        transport: Arc<dyn StandbyTransport>,
+        policy: ReplicationPolicy,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let mut queue = Queue::default();
+        for line in read_queue_file(&path)? {
+            match line {
+                QueueLine::Dropped { dropped } => queue.dropped = dropped,
+                QueueLine::Change(change) => queue.changes.push_back(change),
+            }
+        }
+        queue.next_id = queue.changes.back().map_or(0, |change| change.id + 1);
+        if !queue.changes.is_empty() {
+            tracing::info!(
+                pending = queue.changes.len(),
+                "Replication queue picked up from the previous run"
+            );
+        }
+        Ok(Self {
+            transport: Some(transport),
+            policy,
+            queue: Mutex::new(queue),
+            file: Some(path),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    pub fn flush_interval(&self) -> Duration {
+        self.policy.flush_interval
+    }
+
+    /// Queues a change the store just made
+    pub fn push(&self, change: Change) {
+        if self.transport.is_none() {
+            return;
+        }
+
+        let mut queue = self.queue.lock().expect("replication lock poisoned");
+        let id = queue.next_id;
+        queue.next_id += 1;
+        queue.changes.push_back(ReplicatedChange {
+            id,
+            at: Utc::now(),
+            change,
+        });
+        let dropped = queue.dropped;
+        while queue.changes.len() > self.policy.capacity {
+            queue.changes.pop_front();
+            queue.dropped += 1;
+            if queue.dropped == 1 {
+                tracing::error!(
+                    capacity = self.policy.capacity,
+                    "Replication queue full, dropping changes; the standby must be re-seeded"
+                );
+            }
+        }
+        if queue.dropped > dropped {
+            self.persist(&queue);
+        } else if let Some(path) = &self.file {
+            let line = QueueLine::Change(queue.changes.back().expect("just queued").clone());
+            if let Err(e) = append_queue_line(path, &line) {
+                tracing::error!(error = %e, "Failed to persist replication queue");
+            }
+        }
+    }
+
+    /// Rewrites the queue file, if there is one, to hold what is left in `queue`
+    fn persist(&self, queue: &Queue) {
+        let Some(path) = &self.file else {
+            return;
+        };
+        if let Err(e) = write_queue_file(path, queue) {
+            tracing::error!(error = %e, "Failed to persist replication queue");
+        }
+    }
+
+    /// The oldest queued changes that fit in one request, as the request body, with the id of
+    /// the last of them and their count
+    ///
+    /// A change too large for any standby to accept is dropped, like changes beyond the queue's
+    /// capacity.
+    fn next_batch(&self) -> Option<(String, u64, usize)> {
+        let mut queue = self.queue.lock().expect("replication lock poisoned");
+        let mut lines: Vec<String> = Vec::new();
+        let mut bytes = 0;
+        let mut last = None;
+        while lines.len() < self.policy.max_batch {
+            let Some(change) = queue.changes.get(lines.len()) else {
+                break;
+            };
+            let (id, line) = (
+                change.id,
+                serde_json::to_string(change).expect("replicated change serializes"),
+            );
+            if lines.is_empty() && line.len() > MAX_REPLICATION_BATCH_BYTES {
+                tracing::error!(
+                    id = id,
+                    bytes = line.len(),
+                    "Replicated change too large for the standby, dropping it; the standby must be re-seeded"
+                );
+                queue.changes.pop_front();
+                queue.dropped += 1;
+                self.persist(&queue);
+                continue;
+            }
+            if !lines.is_empty() && bytes + line.len() > self.policy.max_batch_bytes {
+                break;
+            }
+            bytes += line.len() + 1;
+            lines.push(line);
+            last = Some(id);
+        }
+        let count = lines.len();
+        last.map(|last| {
+            (
+                format!(r#"{{"changes":[{}]}}"#, lines.join(",")),
+                last,
+                count,
+            )
+        })
+    }
+
+    /// Sends queued changes to the standby until none are left or a batch fails, returning
+    /// how many were acknowledged
+    pub async fn flush(&self) -> usize {
+        let Some(transport) = &self.transport else {
+            return 0;
+        };
+
+        let mut sent = 0;
+        loop {
+            let Some((batch, last, count)) = self.next_batch() else {
+                return sent;
+            };
+            let result = transport.send(batch).await;
+
+            let mut queue = self.queue.lock().expect("replication lock poisoned");
+            match result {
+                Ok(()) => {
+                    // Changes dropped meanwhile are no longer at the front
+                    while queue
+                        .changes
+                        .front()
+                        .is_some_and(|change| change.id <= last)
+                    {
+                        queue.changes.pop_front();
+                    }
+                    self.persist(&queue);
+                    queue.replicated += count as u64;
+                    queue.last_replicated_at = Some(Utc::now());
+                    queue.last_error = None;
+                    if let Some(since) = queue.failing_since.take() {
+                        tracing::info!(%since, "Standby replication recovered");
+                    }
+                    sent += count;
+                }
+                Err(e) => {
+                    if queue.failing_since.is_none() {
+                        tracing::warn!(error = %e, "Standby replication failing");
+                        queue.failing_since = Some(Utc::now());
+                    }
+                    queue.last_error = Some(e);
+                    return sent;
+                }
+            }
+        }
+    }
+
+    pub fn status(&self) -> ReplicationStatus {
+        let queue = self.queue.lock().expect("replication lock poisoned");
+        let lag = queue
+            .changes
+            .front()
+            .map(|oldest| (Utc::now() - oldest.at).max(chrono::Duration::zero()))
+            .unwrap_or_default();
+        let max_lag =
+            chrono::Duration::from_std(self.policy.max_lag).unwrap_or(chrono::Duration::MAX);
+        ReplicationStatus {
+            enabled: self.is_enabled(),
+            pending: queue.changes.len(),
+            lag_seconds: lag.num_seconds(),
+            lagging: lag > max_lag || queue.dropped > 0,
+            replicated: queue.replicated,
+            last_replicated_at: queue.last_replicated_at,
+            failing_since: queue.failing_since,
+            last_error: queue.last_error.clone(),
+            dropped: queue.dropped,
+        }
+    }
+}
+
+/// Reads the queue file; a missing file is an empty queue
+///
+/// A torn last line, left by a crash mid-write, is skipped: its change was never acknowledged
+/// to a client whose store write failed with it.
+fn read_queue_file(path: &Path) -> io::Result<Vec<QueueLine>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(line) => Some(line),
+            Err(e) => {
+                tracing::warn!(error = %e, "Skipping unreadable replication queue line");
+                None
+            }
+        })
+        .collect())
+}
+
+fn append_queue_line(path: &Path, line: &QueueLine) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(line).expect("queue line serializes") + "\n";
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+/// Replaces the queue file in one step, so a crash leaves either the old or the new queue
+fn write_queue_file(path: &Path, queue: &Queue) -> io::Result<()> {
+    let mut contents = String::new();
+    if queue.dropped > 0 {
+        let dropped = QueueLine::Dropped {
+            dropped: queue.dropped,
+        };
+        contents
+            .push_str(&(serde_json::to_string(&dropped).expect("queue line serializes") + "\n"));
+    }
+    for change in &queue.changes {
+        contents.push_str(
+            &(serde_json::to_string(change).expect("replicated change serializes") + "\n"),
+        );
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// [`ArkStore`] that queues every change it makes for the standby
+///
+/// Webhook deliveries and pruning stay local: each instance keeps its own.
+pub struct ReplicatedStore {
+    inner: Arc<dyn ArkStore>,
+    replication: Arc<Replication>,
+    /// Held from each write to the inner store until its change is queued, so changes are
+    /// queued in the order they took effect
+    writes: Mutex<()>,
+}
+
+impl ReplicatedStore {
+    pub fn new(inner: Arc<dyn ArkStore>, replication: Arc<Replication>) -> Self {
+        Self {
+            inner,
+            replication,
+            writes: Mutex::new(()),
+        }
+    }
+}
+
+impl MintLedger for ReplicatedStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        let already = self.inner.record(shoulder, arks, metadata)?;
+        let recorded: Vec<String> = arks
+            .iter()
+            .filter(|ark| !already.contains(ark))
+            .cloned()
+            .collect();
+        if !recorded.is_empty() {
+            self.replication.push(Change::Mint {
+                shoulder: shoulder.to_string(),
+                arks: recorded,
+                metadata: metadata.clone(),
+            });
+        }
+        Ok(already)
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        self.inner.annotate(arks, metadata)?;
+        self.replication.push(Change::Annotate {
+            arks: arks.to_vec(),
+            metadata: metadata.clone(),
+        });
+        Ok(())
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        self.inner.contains(ark)
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.inner.minted()
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        self.inner.minted_on(shoulder)
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        let first = self.inner.reserve_sequence(shoulder, count)?;
+        self.replication.push(Change::Sequence {
+            shoulder: shoulder.to_string(),
+            next: first + count,
+        });
+        Ok(first)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        self.inner.sequence_position(shoulder)
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        self.inner.entries(shoulder, cursor, limit)
+    }
+}
+
+impl BindingStore for ReplicatedStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.inner.get(canonical)
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        self.inner.put_all(bindings.clone())?;
+        self.replication.push(Change::Bind { bindings });
+        Ok(())
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        let existing = self.inner.put_new(canonical, binding)?;
+        if existing.is_none() {
+            self.replication.push(Change::Bind {
+                bindings: vec![(canonical.to_string(), binding.clone())],
+            });
+        }
+        Ok(existing)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.inner.count()
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        self.inner.count_prefixed(prefix)
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        self.inner.bindings(cursor, limit)
+    }
+}
+
+impl DeliveryStore for ReplicatedStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        self.inner.save_delivery(delivery)
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        self.inner.delivery(id)
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        self.inner.deliveries(status)
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.inner.prune_deliveries(before)
+    }
+}
+
+impl ReceiptStore for ReplicatedStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        let existing = self.inner.save_receipt(receipt)?;
+        if existing.is_none() {
+            self.replication.push(Change::Receipt {
+                receipt: receipt.clone(),
+            });
+        }
+        Ok(existing)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        self.inner.receipt(key)
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.inner.prune_receipts(before)
+    }
+}
+
+impl KeyStore for ReplicatedStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        let _writes = self.writes.lock().expect("replication write lock poisoned");
+        self.inner.put_key(key)?;
+        self.replication.push(Change::Key { key: key.clone() });
+        Ok(())
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        self.inner.api_keys()
+    }
+}
+
+impl ArkStore for ReplicatedStore {
+    fn backend(&self) -> &'static str {
+        self.inner.backend()
+    }
+}
+
+/// Build standby replication from environment variables; disabled unless
+/// `REPLICATION_STANDBY_URL` is set
+///
+/// `REPLICATION_STANDBY_URL` is the standby's admin API (e.g.
+/// `https://standby.example.org/admin/api`) and requires `REPLICATION_STANDBY_KEY`, one of the
+/// standby's admin keys. Optional: `REPLICATION_FLUSH_INTERVAL_MS` (default 1000),
+/// `REPLICATION_MAX_BATCH` (default 500), `REPLICATION_MAX_BATCH_BYTES` (default 1 MiB),
+/// `REPLICATION_QUEUE_CAPACITY` (default 100000), `REPLICATION_MAX_LAG_SECS` (default 60),
+/// `REPLICATION_TIMEOUT_SECS` (default 10), `REPLICATION_QUEUE_PATH` (the queue is kept in
+/// memory only without it).
+pub fn load_replication_from_env(
+    http: &HttpClients,
+    secrets: &Secrets,
+) -> Result<Replication, String> {
+    let Ok(url) = std::env::var("REPLICATION_STANDBY_URL") else {
+        return Ok(Replication::default());
+    };
+    url::Url::parse(&url)
+        .map_err(|e| format!("REPLICATION_STANDBY_URL is not a valid URL: {}", e))?;
+    let key = secrets
+        .get("REPLICATION_STANDBY_KEY")
+        .cloned()
+        .ok_or("REPLICATION_STANDBY_URL is set, but REPLICATION_STANDBY_KEY is not")?;
+
+    let defaults = ReplicationPolicy::default();
+    let policy = ReplicationPolicy {
+        flush_interval: env_parse("REPLICATION_FLUSH_INTERVAL_MS")?
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.flush_interval),
+        max_batch: env_parse("REPLICATION_MAX_BATCH")?.unwrap_or(defaults.max_batch),
+        max_batch_bytes: env_parse("REPLICATION_MAX_BATCH_BYTES")?
+            .unwrap_or(defaults.max_batch_bytes),
+        capacity: env_parse("REPLICATION_QUEUE_CAPACITY")?.unwrap_or(defaults.capacity),
+        max_lag: env_parse("REPLICATION_MAX_LAG_SECS")?
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.max_lag),
+    };
+    if policy.flush_interval.is_zero() {
+        return Err("REPLICATION_FLUSH_INTERVAL_MS must be greater than 0".to_string());
+    }
+    if policy.max_batch == 0 || policy.capacity == 0 {
+        return Err(
+            "REPLICATION_MAX_BATCH and REPLICATION_QUEUE_CAPACITY must be greater than 0"
+                .to_string(),
+        );
+    }
+    if !(1..=MAX_REPLICATION_BATCH_BYTES).contains(&policy.max_batch_bytes) {
+        return Err(format!(
+            "REPLICATION_MAX_BATCH_BYTES must be between 1 and {}",
+            MAX_REPLICATION_BATCH_BYTES
+        ));
+    }
+    let timeout = Duration::from_secs(env_parse("REPLICATION_TIMEOUT_SECS")?.unwrap_or(10));
+
+    let standby = Arc::new(HttpStandby::new(http.client(timeout), &url, key));
+    match std::env::var("REPLICATION_QUEUE_PATH") {
+        Ok(path) => Replication::persisted(standby, policy, &path)
+            .map_err(|e| format!("Failed to open REPLICATION_QUEUE_PATH {}: {}", path, e)),
+        Err(_) => {
+            tracing::warn!(
+                "REPLICATION_QUEUE_PATH is not set; changes not yet sent to the standby are lost on restart"
+            );
+            Ok(Replication::new(standby, policy))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::{ApiKeys, Scope};
+    use crate::store::MemoryArkStore;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct RecordingStandby {
+        batches: Mutex<Vec<ChangeBatch>>,
+        down: AtomicBool,
+    }
+
+    impl StandbyTransport for RecordingStandby {
+        fn send(
+            &self,
+            batch: String,
+        ) -> Pin<Box<dyn Future<Output = Result<(), String>> + Send + '_>> {
+            let result = if self.down.load(Ordering::Relaxed) {
+                Err("503 Service Unavailable".to_string())
+            } else {
+                self.batches
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::from_str(&batch).unwrap());
+                Ok(())
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn binding(target: &str) -> Binding {
+        Binding {
+            target: target.to_string(),
+            metadata: Default::default(),
+            bound_at: Utc::now(),
+            lifecycle: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replicates_store_changes_in_order() {
+        let standby = Arc::new(RecordingStandby::default());
+        let policy = ReplicationPolicy {
+            max_batch: 2,
+            ..Default::default()
+        };
+        let replication = Arc::new(Replication::new(standby.clone(), policy));
+        let primary =
+            ReplicatedStore::new(Arc::new(MemoryArkStore::default()), replication.clone());
+
+        let arks = ["ark:12345/x6a".to_string(), "ark:12345/x6b".to_string()];
+        primary.record("x6", &arks[..1], &BTreeMap::new()).unwrap();
+        // Only the newly recorded ARK is replicated
+        primary.record("x6", &arks, &BTreeMap::new()).unwrap();
+        let requested_by = BTreeMap::from([("creator".to_string(), "digitization".to_string())]);
+        primary.annotate(&arks[1..], &requested_by).unwrap();
+        primary.reserve_sequence("x6", 3).unwrap();
+        primary
+            .put_all(vec![(arks[0].clone(), binding("https://example.org/a"))])
+            .unwrap();
+        let issued = ApiKeys::default()
+            .create(&primary, "ingest", &[Scope::Admin], None, "tester")
+            .unwrap();
+        // Deliveries stay local
+        primary
+            .save_delivery(&Delivery::new(
+                "https://hooks.example.org/x6",
+                "x6",
+                serde_json::json!({}),
+            ))
+            .unwrap();
+        assert_eq!(replication.status().pending, 6);
+
+        assert_eq!(replication.flush().await, 6);
+        let changes: Vec<ReplicatedChange> = standby
+            .batches
+            .lock()
+            .unwrap()
+            .drain(..)
+            .flat_map(|batch| batch.changes)
+            .collect();
+        assert_eq!(
+            changes.iter().map(|change| change.id).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            changes[1].change,
+            Change::Mint {
+                shoulder: "x6".to_string(),
+                arks: arks[1..].to_vec(),
+                metadata: BTreeMap::new(),
+            }
+        );
+
+        // Applying the stream, even twice, brings a standby store up to date
+        let copy = MemoryArkStore::default();
+        for change in changes.iter().chain(&changes) {
+            change.change.apply(&copy).unwrap();
+        }
+        assert_eq!(copy.minted().unwrap(), 2);
+        assert_eq!(
+            copy.entries(None, None, 10).unwrap().entries[1].metadata,
+            requested_by
+        );
+        assert_eq!(copy.sequence_position("x6").unwrap(), 3);
+        assert_eq!(
+            copy.get(&arks[0]).unwrap().unwrap().target,
+            "https://example.org/a"
+        );
+        assert_eq!(copy.api_keys().unwrap(), vec![issued.record]);
+        assert!(copy.deliveries(None).unwrap().is_empty());
+
+        let status = replication.status();
+        assert_eq!((status.pending, status.replicated), (0, 6));
+        assert!(!status.lagging);
+    }
+
+    #[tokio::test]
+    async fn test_queues_concurrent_writes_in_the_order_they_took_effect() {
+        let standby = Arc::new(RecordingStandby::default());
+        let replication = Arc::new(Replication::new(standby.clone(), Default::default()));
+        let primary = Arc::new(ReplicatedStore::new(
+            Arc::new(MemoryArkStore::default()),
+            replication.clone(),
+        ));
+
+        let ark = "ark:12345/x6a";
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let primary = primary.clone();
+                std::thread::spawn(move || {
+                    for write in 0..50 {
+                        let target = format!("https://example.org/{}/{}", writer, write);
+                        primary
+                            .put_all(vec![(ark.to_string(), binding(&target))])
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(replication.flush().await, 400);
+        // The standby ends on the binding the primary ended on
+        let copy = MemoryArkStore::default();
+        for batch in standby.batches.lock().unwrap().iter() {
+            for change in &batch.changes {
+                change.change.apply(&copy).unwrap();
+            }
+        }
+        assert_eq!(
+            copy.get(ark).unwrap().unwrap().target,
+            primary.get(ark).unwrap().unwrap().target
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keeps_changes_until_the_standby_acknowledges_them() {
+        let standby = Arc::new(RecordingStandby::default());
+        standby.down.store(true, Ordering::Relaxed);
+        let policy = ReplicationPolicy {
+            capacity: 2,
+            max_lag: Duration::ZERO,
+            ..Default::default()
+        };
+        let replication = Replication::new(standby.clone(), policy);
+        for next in 1..=3 {
+            replication.push(Change::Sequence {
+                shoulder: "x6".to_string(),
+                next,
+            });
+        }
+
+        assert_eq!(replication.flush().await, 0);
+        let status = replication.status();
+        assert_eq!((status.pending, status.dropped), (2, 1));
+        assert!(status.lagging);
+        assert!(status.failing_since.is_some());
+        assert_eq!(
+            status.last_error.as_deref(),
+            Some("503 Service Unavailable")
+        );
+
+        standby.down.store(false, Ordering::Relaxed);
+        assert_eq!(replication.flush().await, 2);
+        let batches = standby.batches.lock().unwrap();
+        assert_eq!(batches[0].changes[0].id, 1);
+        let status = replication.status();
+        assert_eq!(status.failing_since, None);
+        // Dropped changes keep replication reported as lagging
+        assert!(status.lagging);
+    }
+
+    #[tokio::test]
+    async fn test_caps_batches_by_size() {
+        let standby = Arc::new(RecordingStandby::default());
+        let policy = ReplicationPolicy {
+            max_batch_bytes: 300,
+            ..Default::default()
+        };
+        let replication = Replication::new(standby.clone(), policy);
+        let arks: Vec<String> = (0..4).map(|n| format!("ark:12345/x6{:040}", n)).collect();
+        for ark in &arks {
+            replication.push(Change::Mint {
+                shoulder: "x6".to_string(),
+                arks: vec![ark.clone()],
+                metadata: BTreeMap::new(),
+            });
+        }
+        // A change larger than the cap still goes, alone
+        replication.push(Change::Mint {
+            shoulder: "x6".to_string(),
+            arks: arks.iter().cycle().take(8).cloned().collect(),
+            metadata: BTreeMap::new(),
+        });
+
+        assert_eq!(replication.flush().await, 5);
+        let batches = standby.batches.lock().unwrap();
+        let sizes: Vec<usize> = batches.iter().map(|batch| batch.changes.len()).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_keeps_the_queue_across_restarts() {
+        let path =
+            std::env::temp_dir().join(format!("ark-replication-{}.queue", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let standby = Arc::new(RecordingStandby::default());
+        standby.down.store(true, Ordering::Relaxed);
+        let policy = ReplicationPolicy {
+            capacity: 2,
+            ..Default::default()
+        };
+        let sequence = |next| Change::Sequence {
+            shoulder: "x6".to_string(),
+            next,
+        };
+
+        let replication = Replication::persisted(standby.clone(), policy.clone(), &path).unwrap();
+        for next in 1..=3 {
+            replication.push(sequence(next));
+        }
+        assert_eq!(replication.flush().await, 0);
+        drop(replication);
+
+        // The pending changes and the drop survive the restart
+        let replication = Replication::persisted(standby.clone(), policy.clone(), &path).unwrap();
+        let status = replication.status();
+        assert_eq!((status.pending, status.dropped), (2, 1));
+        replication.push(sequence(4));
+        standby.down.store(false, Ordering::Relaxed);
+        assert_eq!(replication.flush().await, 2);
+        drop(replication);
+
+        let replication = Replication::persisted(standby.clone(), policy, &path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(replication.status().pending, 0);
+        let changes: Vec<Change> = standby
+            .batches
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|batch| batch.changes.iter().map(|change| change.change.clone()))
+            .collect();
+        assert_eq!(changes, vec![sequence(3), sequence(4)]);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_transport() {
+        let replication = Replication::default();
+        replication.push(Change::Sequence {
+            shoulder: "x6".to_string(),
+            next: 1,
+        });
+        assert_eq!(replication.flush().await, 0);
+        assert_eq!(replication.status(), ReplicationStatus::default());
+    }
+}
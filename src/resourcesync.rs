@@ -0,0 +1,149 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::events::ArkEvent;
+
+/// Path of the Source Description, as defined by the ResourceSync well-known URI
+pub const DESCRIPTION_PATH: &str = "/.well-known/resourcesync";
+pub const CAPABILITY_LIST_PATH: &str = "/resourcesync/capabilitylist.xml";
+pub const CHANGE_LIST_PATH: &str = "/resourcesync/changelist.xml";
+
+/// Builds ResourceSync documents for a resolver reachable at `base_url`
+///
+/// `base_url` is prefixed to every link; when it is empty the links are relative.
+pub struct ResourceSync<'a> {
+    pub base_url: &'a str,
+}
+
+impl ResourceSync<'_> {
+    /// The Source Description, pointing at the capability list
+    pub fn description(&self) -> String {
+        urlset(
+            None,
+            "<rs:md capability=\"description\"/>",
+            &[url_entry(
+                &self.url(CAPABILITY_LIST_PATH),
+                "<rs:md capability=\"capabilitylist\"/>",
+            )],
+        )
+    }
+
+    /// The capability list; change lists are the only capability offered
+    pub fn capability_list(&self) -> String {
+        urlset(
+            Some(&self.url(DESCRIPTION_PATH)),
+            "<rs:md capability=\"capabilitylist\"/>",
+            &[url_entry(
+                &self.url(CHANGE_LIST_PATH),
+                "<rs:md capability=\"changelist\"/>",
+            )],
+        )
+    }
+
+    /// A change list of `events`, which must cover `from..until`
+    pub fn change_list(
+        &self,
+        events: &[ArkEvent],
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> String {
+        let entries: Vec<String> = events
+            .iter()
+            .map(|event| {
+                url_entry(
+                    &self.url(&format!("/{}", event.ark)),
+                    &format!(
+                        "<rs:md change=\"{}\" datetime=\"{}\"/>",
+                        event.change.as_str(),
+                        w3c_datetime(event.at)
+                    ),
+                )
+            })
+            .collect();
+
+        urlset(
+            Some(&self.url(CAPABILITY_LIST_PATH)),
+            &format!(
+                "<rs:md capability=\"changelist\" from=\"{}\" until=\"{}\"/>",
+                w3c_datetime(from),
+                w3c_datetime(until)
+            ),
+            &entries,
+        )
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+fn urlset(up: Option<&str>, metadata: &str, entries: &[String]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" \
+         xmlns:rs=\"http://www.openarchives.org/rs/terms/\">\n",
+    );
+    if let Some(up) = up {
+        xml.push_str(&format!("  <rs:ln rel=\"up\" href=\"{}\"/>\n", escape(up)));
+    }
+    xml.push_str(&format!("  {}\n", metadata));
+    for entry in entries {
+        xml.push_str(entry);
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn url_entry(loc: &str, metadata: &str) -> String {
+    format!(
+        "  <url>\n    <loc>{}</loc>\n    {}\n  </url>\n",
+        escape(loc),
+        metadata
+    )
+}
+
+pub(crate) fn w3c_datetime(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Escape text for XML element content and attribute values
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ArkChange;
+
+    #[test]
+    fn test_change_list() {
+        let sync = ResourceSync {
+            base_url: "https://ark.example.org",
+        };
+        let events = vec![ArkEvent {
+            ark: "ark:12345/x6np1wh8k".to_string(),
+            change: ArkChange::Created,
+            at: "2025-01-02T03:04:05Z".parse().unwrap(),
+        }];
+
+        let xml = sync.change_list(
+            &events,
+            "2025-01-01T00:00:00Z".parse().unwrap(),
+            "2025-01-03T00:00:00Z".parse().unwrap(),
+        );
+        assert!(xml.contains(
+            "<rs:ln rel=\"up\" href=\"https://ark.example.org/resourcesync/capabilitylist.xml\"/>"
+        ));
+        assert!(xml.contains(
+            "<rs:md capability=\"changelist\" from=\"2025-01-01T00:00:00Z\" until=\"2025-01-03T00:00:00Z\"/>"
+        ));
+        assert!(xml.contains("<loc>https://ark.example.org/ark:12345/x6np1wh8k</loc>"));
+        assert!(xml.contains("<rs:md change=\"created\" datetime=\"2025-01-02T03:04:05Z\"/>"));
+
+        let description = ResourceSync { base_url: "" }.description();
+        assert!(description.contains("<loc>/resourcesync/capabilitylist.xml</loc>"));
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::http_client::{HttpClient, HttpClients};
+
+/// Environment variables that hold credentials and may be given as `*_FILE` or `*_VAULT`
+pub const SECRET_VARS: &[&str] = &[
+    "ADMIN_API_KEYS",
+    "ADMIN_TOKEN_SECRET",
+    "RESOLUTION_API_KEYS",
+    "MINT_API_KEYS",
+    "REDIS_URL",
+    "REPLICATION_STANDBY_KEY",
+    "SMTP_USERNAME",
+    "SMTP_PASSWORD",
+];
+
+/// A credential whose value is never printed by `Debug` or `Display`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// The actual value, for handing to the code that needs it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// Where a secret is read from
+#[derive(Clone, Debug, PartialEq)]
+enum SecretSource {
+    /// `NAME`: the value itself
+    Inline(Secret),
+    /// `NAME_FILE`: a file holding the value, such as a Docker secret
+    File(String),
+    /// `NAME_VAULT`: a `path#key` reference into Vault's HTTP API
+    Vault { path: String, key: String },
+}
+
+/// A HashiCorp Vault server, configured with `VAULT_ADDR` and `VAULT_TOKEN`
+#[derive(Debug)]
+pub struct Vault {
+    addr: String,
+    token: Secret,
+    client: HttpClient,
+}
+
+impl Vault {
+    pub fn new(addr: String, token: Secret, client: HttpClient) -> Self {
+        Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            token,
+            client,
+        }
+    }
+
+    /// Reads `key` from the secret at `path` (e.g. `secret/data/ark-service`)
+    ///
+    /// Handles both KV version 2 (`data.data`) and version 1 (`data`) responses.
+    pub async fn read(&self, path: &str, key: &str) -> Result<Secret, String> {
+        let url = format!("{}/v1/{}", self.addr, path.trim_start_matches('/'));
+        let request = self
+            .client
+            .get(&url)
+            .header("X-Vault-Token", self.token.expose());
+        let response = self
+            .client
+            .send(request)
+            .await
+            .and_then(|response| response.error_for_status().map_err(|e| e.to_string()))
+            .map_err(|e| format!("Failed to read {} from Vault: {}", path, e))?;
+        let body: serde_json::Value = response
+            .text()
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Invalid Vault response for {}: {}", path, e))?;
+
+        let data = &body["data"];
+        data["data"][key]
+            .as_str()
+            .or_else(|| data[key].as_str())
+            .map(|value| Secret::new(value.to_string()))
+            .ok_or_else(|| format!("Vault secret {} has no string field '{}'", path, key))
+    }
+}
+
+/// Credentials resolved at startup from the environment, files, or Vault
+#[derive(Debug, Default)]
+pub struct Secrets {
+    values: HashMap<String, Secret>,
+}
+
+impl Secrets {
+    /// Resolves every secret in [`SECRET_VARS`] that is configured
+    ///
+    /// Each secret can be set as `NAME`, `NAME_FILE`, or `NAME_VAULT`; setting more than one is
+    /// an error. Vault references require `VAULT_ADDR` and `VAULT_TOKEN` (or `VAULT_TOKEN_FILE`).
+    pub async fn load_from_env(http: &HttpClients) -> Result<Self, String> {
+        let lookup = |name: &str| std::env::var(name).ok();
+        let vault = match lookup("VAULT_ADDR") {
+            Some(addr) => {
+                let token = match source(&lookup, "VAULT_TOKEN")? {
+                    Some(SecretSource::Inline(token)) => token,
+                    Some(SecretSource::File(path)) => read_file(&path)?,
+                    _ => return Err("VAULT_ADDR requires VAULT_TOKEN or VAULT_TOKEN_FILE".into()),
+                };
+                Some(Vault::new(
+                    addr,
+                    token,
+                    http.client(Duration::from_secs(10)),
+                ))
+            }
+            None => None,
+        };
+
+        let mut values = HashMap::new();
+        for name in SECRET_VARS {
+            let value = match source(&lookup, name)? {
+                None => continue,
+                Some(SecretSource::Inline(value)) => value,
+                Some(SecretSource::File(path)) => read_file(&path)?,
+                Some(SecretSource::Vault { path, key }) => {
+                    let vault = vault
+                        .as_ref()
+                        .ok_or_else(|| format!("{}_VAULT requires VAULT_ADDR", name))?;
+                    vault.read(&path, &key).await?
+                }
+            };
+            values.insert(name.to_string(), value);
+        }
+
+        Ok(Self { values })
+    }
+
+    /// The resolved value of a secret, if it was configured
+    pub fn get(&self, name: &str) -> Option<&Secret> {
+        self.values.get(name)
+    }
+
+    /// Every secret that was configured
+    pub fn iter(&self) -> impl Iterator<Item = &Secret> {
+        self.values.values()
+    }
+}
+
+/// Finds where `name` is configured, rejecting ambiguous configurations
+fn source(
+    lookup: &impl Fn(&str) -> Option<String>,
+    name: &str,
+) -> Result<Option<SecretSource>, String> {
+    let inline = lookup(name).map(|value| SecretSource::Inline(Secret::new(value)));
+    let file = lookup(&format!("{}_FILE", name)).map(SecretSource::File);
+    let vault = lookup(&format!("{}_VAULT", name))
+        .map(|reference| {
+            reference
+                .split_once('#')
+                .filter(|(path, key)| !path.is_empty() && !key.is_empty())
+                .map(|(path, key)| SecretSource::Vault {
+                    path: path.to_string(),
+                    key: key.to_string(),
+                })
+                .ok_or_else(|| format!("{}_VAULT must have the form path#key", name))
+        })
+        .transpose()?;
+
+    let mut sources = [inline, file, vault].into_iter().flatten();
+    let first = sources.next();
+    if sources.next().is_some() {
+        return Err(format!(
+            "Only one of {0}, {0}_FILE, and {0}_VAULT may be set",
+            name
+        ));
+    }
+    Ok(first)
+}
+
+/// Reads a secret file, ignoring the trailing newline most editors and tools add
+fn read_file(path: &str) -> Result<Secret, String> {
+    std::fs::read_to_string(path)
+        .map(|value| Secret::new(value.trim_end_matches(['\n', '\r']).to_string()))
+        .map_err(|e| format!("Failed to read secret file {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_source() {
+        assert_eq!(source(&lookup(&[]), "SMTP_PASSWORD").unwrap(), None);
+        assert_eq!(
+            source(
+                &lookup(&[("SMTP_PASSWORD_FILE", "/run/secrets/smtp")]),
+                "SMTP_PASSWORD"
+            )
+            .unwrap(),
+            Some(SecretSource::File("/run/secrets/smtp".to_string()))
+        );
+        assert_eq!(
+            source(
+                &lookup(&[("SMTP_PASSWORD_VAULT", "secret/data/ark#smtp")]),
+                "SMTP_PASSWORD"
+            )
+            .unwrap(),
+            Some(SecretSource::Vault {
+                path: "secret/data/ark".to_string(),
+                key: "smtp".to_string()
+            })
+        );
+
+        assert!(
+            source(
+                &lookup(&[("SMTP_PASSWORD_VAULT", "no-key")]),
+                "SMTP_PASSWORD"
+            )
+            .is_err()
+        );
+        assert!(
+            source(
+                &lookup(&[("SMTP_PASSWORD", "x"), ("SMTP_PASSWORD_FILE", "/x")]),
+                "SMTP_PASSWORD"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_file_and_redaction() {
+        let path = std::env::temp_dir().join(format!("ark-secret-{}", std::process::id()));
+        std::fs::write(&path, "hunter2\n").unwrap();
+        let secret = read_file(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let secret = secret.unwrap();
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(format!("{:?} {}", secret, secret), "[REDACTED] [REDACTED]");
+        assert!(read_file("/nonexistent/secret").is_err());
+    }
+}
@@ -1,6 +1,18 @@
+mod admin;
+mod auth;
+mod canonical_host;
+mod deadline;
+mod error_pages;
 mod handlers;
+mod key_usage;
+mod localize;
 mod models;
+mod read_only;
 mod router;
 mod run;
+mod throttle;
 
+pub use admin::DEFAULT_ADMIN_API_PATH;
+pub use canonical_host::CanonicalHost;
+pub(crate) use router::create_router;
 pub use run::run;
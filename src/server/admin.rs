@@ -0,0 +1,154 @@
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post},
+};
+use std::sync::Arc;
+
+use crate::ark::strip_label;
+use crate::config::AppState;
+use crate::notify::env_parse;
+use crate::replication::{MAX_REPLICATION_BATCH_BYTES, REPLICATION_CHANGES_PATH};
+use crate::server::{auth, handlers, read_only};
+
+/// Where the admin API is mounted unless `ADMIN_API_PATH` says otherwise
+pub const DEFAULT_ADMIN_API_PATH: &str = "/admin/api";
+
+/// The admin endpoints, guarded by the admin keys, relative to the admin API path
+pub fn router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    let writes = || middleware::from_fn_with_state(state.clone(), read_only::reject_writes);
+    let pausing_writes =
+        || middleware::from_fn_with_state(state.clone(), read_only::reject_when_read_only);
+    Router::new()
+        .route("/token", post(handlers::issue_admin_token_handler))
+        .route("/config", get(handlers::export_config_handler))
+        .route(
+            "/config/staged",
+            get(handlers::staged_config_handler)
+                .put(handlers::import_config_handler)
+                .delete(handlers::discard_staged_config_handler),
+        )
+        .route("/incidents", post(handlers::open_incident_handler))
+        .route(
+            "/incidents/{id}/resolve",
+            post(handlers::resolve_incident_handler),
+        )
+        .route("/quarantine", get(handlers::quarantine_handler))
+        .route(
+            "/quarantine/{id}/resolve",
+            post(handlers::resolve_quarantine_handler),
+        )
+        .route("/webhooks/deliveries", get(handlers::deliveries_handler))
+        .route(
+            "/webhooks/deliveries/{id}/retry",
+            post(handlers::retry_delivery_handler),
+        )
+        .route(
+            "/batch",
+            post(handlers::batch_handler).route_layer(writes()),
+        )
+        .route(
+            "/lifecycle/promote",
+            post(handlers::promote_handler).route_layer(writes()),
+        )
+        .route(
+            "/lifecycle/withdraw",
+            post(handlers::withdraw_handler).route_layer(writes()),
+        )
+        .route(
+            "/read-only",
+            get(handlers::read_only_handler)
+                .put(handlers::enable_read_only_handler)
+                .delete(handlers::disable_read_only_handler),
+        )
+        .route(
+            "/snapshots",
+            get(handlers::snapshots_handler).post(handlers::take_snapshot_handler),
+        )
+        .route(
+            "/restore",
+            post(handlers::restore_handler).route_layer(pausing_writes()),
+        )
+        .route("/replication", get(handlers::replication_handler))
+        .route(
+            REPLICATION_CHANGES_PATH,
+            post(handlers::apply_replicated_changes_handler)
+                .layer(DefaultBodyLimit::max(MAX_REPLICATION_BATCH_BYTES)),
+        )
+        .route("/mint-stats", get(handlers::mint_stats_handler))
+        .route("/mint-queue", get(handlers::mint_queue_handler))
+        .route("/mint-pools", get(handlers::mint_pools_handler))
+        .route("/blade-length", get(handlers::blade_length_handler))
+        .route("/http-metrics", get(handlers::http_metrics_handler))
+        .route(
+            "/keys",
+            get(handlers::api_keys_handler).post(handlers::create_api_key_handler),
+        )
+        .route("/keys/{id}/expire", post(handlers::expire_api_key_handler))
+        .route("/keys/{id}/rotate", post(handlers::rotate_api_key_handler))
+        .route("/keys/{id}/usage", get(handlers::key_usage_handler))
+        .route("/input-limits", get(handlers::input_limits_handler))
+        .route(
+            "/loglevel",
+            get(handlers::log_level_handler)
+                .put(handlers::set_log_level_handler)
+                .delete(handlers::reset_log_level_handler),
+        )
+        .route("/maintenance", post(handlers::schedule_maintenance_handler))
+        .route(
+            "/maintenance/{id}",
+            delete(handlers::cancel_maintenance_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(state, auth::require_admin))
+}
+
+/// Checks that `path` can be used as a mount path: absolute, not the root, no trailing slash,
+/// and clear of the public routes
+fn validate_admin_api_path(path: &str) -> Result<(), String> {
+    if !path.starts_with('/') || path == "/" || path.ends_with('/') {
+        return Err(format!(
+            "ADMIN_API_PATH must start with '/' and not end with one: {}",
+            path
+        ));
+    }
+    if path.strip_prefix('/').and_then(strip_label).is_some()
+        || path.starts_with("/.well-known")
+        || path == "/api/v1"
+    {
+        return Err(format!(
+            "ADMIN_API_PATH must not overlap the public API: {}",
+            path
+        ));
+    }
+    Ok(())
+}
+
+/// Where to mount the admin API, or `None` if it is disabled
+///
+/// Optional: `ADMIN_API_ENABLED` (default true), `ADMIN_API_PATH` (default `/admin/api`).
+pub fn load_admin_api_path_from_env() -> Result<Option<String>, String> {
+    if !env_parse("ADMIN_API_ENABLED")?.unwrap_or(true) {
+        return Ok(None);
+    }
+    let path =
+        std::env::var("ADMIN_API_PATH").unwrap_or_else(|_| DEFAULT_ADMIN_API_PATH.to_string());
+    validate_admin_api_path(&path)?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_admin_api_path() {
+        assert!(validate_admin_api_path(DEFAULT_ADMIN_API_PATH).is_ok());
+        assert!(validate_admin_api_path("/internal/ark-admin").is_ok());
+        assert!(validate_admin_api_path("admin").is_err());
+        assert!(validate_admin_api_path("/").is_err());
+        assert!(validate_admin_api_path("/admin/").is_err());
+        assert!(validate_admin_api_path("/ark:12345").is_err());
+        assert!(validate_admin_api_path("/api/v1").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api_keys::{Scope, constant_time_eq};
+use crate::config::AppState;
+use crate::error::AppError;
+use crate::key_usage::UsageKey;
+
+/// Realm announced to clients that need an admin key
+pub const ADMIN_REALM: &str = "admin";
+
+/// Realm announced to clients that need a key to resolve ARKs on a `require_auth` shoulder
+pub const RESOLVER_REALM: &str = "resolver";
+
+/// Realm announced to clients that need a key to add ARKs
+pub const MINT_REALM: &str = "mint";
+
+/// Cookie holding the admin UI token
+pub const TOKEN_COOKIE: &str = "ark_admin_token";
+
+/// Header carrying the CSRF token on state-changing requests made with an admin UI token
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// The name of the admin key holder that authenticated the current request
+#[derive(Clone, Debug)]
+pub struct AdminIdentity(pub String);
+
+/// Marks admin requests authenticated with an admin UI token rather than an API key
+#[derive(Clone, Copy, Debug)]
+pub struct AdminSession;
+
+/// Middleware guarding admin endpoints with a bearer API key from `ADMIN_API_KEYS` or a managed
+/// key with the admin scope, or an admin UI token sent as a bearer token or in the [`TOKEN_COOKIE`] cookie
+///
+/// A token issued for a managed key is only accepted while that key is active.
+///
+/// State-changing requests made with an admin UI token must repeat its CSRF token in the
+/// [`CSRF_HEADER`] header. On success the key holder's name is attached to the request as an
+/// [`AdminIdentity`] extension, so handlers can record who made a change.
+pub async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let headers = request.headers();
+    let name = if let Ok(name) = authenticate_admin(&state, headers) {
+        name
+    } else if let Some(verified) = bearer_token(headers)
+        .or_else(|| cookie(headers, TOKEN_COOKIE))
+        .and_then(|token| state.token_signer.verify(token))
+        // Tokens stop working with the managed key they were issued for
+        .filter(|verified| {
+            verified
+                .key_id
+                .as_deref()
+                .is_none_or(|id| state.api_keys.grants(id, Scope::Admin))
+        })
+    {
+        let csrf_token = headers
+            .get(CSRF_HEADER)
+            .and_then(|value| value.to_str().ok());
+        if !request.method().is_safe()
+            && !csrf_token.is_some_and(|csrf_token| {
+                constant_time_eq(csrf_token.as_bytes(), verified.csrf_token.as_bytes())
+            })
+        {
+            return Err(AppError::CsrfRejected);
+        }
+        request.extensions_mut().insert(AdminSession);
+        verified.holder
+    } else {
+        return Ok(challenge(AppError::Unauthorized, ADMIN_REALM));
+    };
+
+    tracing::debug!(admin = %name, path = %request.uri().path(), "Admin request authenticated");
+
+    request.extensions_mut().insert(AdminIdentity(name));
+    Ok(next.run(request).await)
+}
+
+/// Middleware guarding the endpoints that add ARKs with a bearer API key from `MINT_API_KEYS`
+/// or `ADMIN_API_KEYS`, or a managed key with the mint or admin scope
+pub async fn require_minter(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match authenticate_minter(&state, request.headers()) {
+        Ok(name) => {
+            tracing::debug!(minter = %name, path = %request.uri().path(), "Mint request authenticated");
+            next.run(request).await
+        }
+        Err(error) => challenge(error, MINT_REALM),
+    }
+}
+
+/// The bearer token sent in the `Authorization` header
+pub(super) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// The value of the cookie called `name`
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            pair.trim()
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+}
+
+/// The name of the holder of the admin key sent as a bearer token: one from `ADMIN_API_KEYS`,
+/// or a managed key with the admin scope
+pub fn authenticate_admin(state: &AppState, headers: &HeaderMap) -> Result<String, AppError> {
+    authenticate_scoped(state, &state.admin_keys, Scope::Admin, headers)
+}
+
+/// The name of the holder of the resolution key sent as a bearer token: one from
+/// `RESOLUTION_API_KEYS`, or a managed key with the resolve scope
+pub fn authenticate_resolver(state: &AppState, headers: &HeaderMap) -> Result<String, AppError> {
+    authenticate_scoped(state, &state.resolution_keys, Scope::Resolve, headers)
+}
+
+/// The name of the holder of a key that may add ARKs sent as a bearer token: an admin key, one
+/// from `MINT_API_KEYS`, or a managed key with the mint scope
+pub fn authenticate_minter(state: &AppState, headers: &HeaderMap) -> Result<String, AppError> {
+    authenticate_admin(state, headers)
+        .or_else(|_| authenticate_scoped(state, &state.mint_keys, Scope::Mint, headers))
+}
+
+fn authenticate_scoped(
+    state: &AppState,
+    keys: &HashMap<String, String>,
+    scope: Scope,
+    headers: &HeaderMap,
+) -> Result<String, AppError> {
+    authenticate(keys, headers).or_else(|error| {
+        bearer_token(headers)
+            .and_then(|token| state.api_keys.authenticate(token, scope))
+            .ok_or(error)
+    })
+}
+
+/// The name of the holder of the key among `keys` sent as a bearer token
+fn authenticate(keys: &HashMap<String, String>, headers: &HeaderMap) -> Result<String, AppError> {
+    let token = bearer_token(headers).ok_or(AppError::Unauthorized)?;
+
+    keys.iter()
+        .find(|(key, _)| constant_time_eq(key.as_bytes(), token.as_bytes()))
+        .map(|(_, name)| name.clone())
+        .ok_or(AppError::Unauthorized)
+}
+
+/// The admin, mint or resolution key sent as a bearer token, if it is a known one
+pub fn usage_key(state: &AppState, headers: &HeaderMap) -> Option<UsageKey> {
+    let token = bearer_token(headers)?;
+    [&state.admin_keys, &state.mint_keys, &state.resolution_keys]
+        .into_iter()
+        .find_map(|keys| authenticate(keys, headers).ok())
+        .map(UsageKey::Configured)
+        .or_else(|| {
+            state
+                .api_keys
+                .identify(token)
+                .map(|key| UsageKey::Managed(key.id))
+        })
+}
+
+/// The error response for a failed authentication, telling the client which keys it needs
+pub fn challenge(error: AppError, realm: &str) -> Response {
+    let mut response = error.into_response();
+    if let Ok(value) = header::HeaderValue::from_str(&format!("Bearer realm=\"{}\"", realm)) {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "theme=dark; ark_admin_token=abc.def".parse().unwrap(),
+        );
+        assert_eq!(cookie(&headers, TOKEN_COOKIE), Some("abc.def"));
+        assert_eq!(cookie(&headers, "ark_admin"), None);
+        assert_eq!(cookie(&headers, "missing"), None);
+    }
+}
@@ -0,0 +1,190 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::config::AppState;
+use crate::server::handlers::is_service_status_path;
+
+/// The one host the resolver should be cited under, with the scheme to redirect to
+#[derive(Clone, Debug, PartialEq)]
+pub struct CanonicalHost {
+    scheme: String,
+    /// Host, with the port if one was given
+    authority: String,
+}
+
+impl CanonicalHost {
+    /// Parses a bare host (`ark.example.org`, redirected to over `https`) or an origin
+    /// (`http://localhost:3000`)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let with_scheme = if value.contains("://") {
+            value.to_string()
+        } else {
+            format!("https://{}", value)
+        };
+        let url = url::Url::parse(&with_scheme)
+            .map_err(|e| format!("Invalid CANONICAL_HOST {}: {}", value, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("CANONICAL_HOST must use http or https: {}", value));
+        }
+        if url.path() != "/" || url.query().is_some() || !url.username().is_empty() {
+            return Err(format!(
+                "CANONICAL_HOST must be a host or origin, without a path: {}",
+                value
+            ));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("CANONICAL_HOST has no host: {}", value))?;
+        let authority = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        Ok(Self {
+            scheme: url.scheme().to_string(),
+            authority,
+        })
+    }
+
+    /// Whether a request's `Host` is this host; without a configured port, any port matches
+    pub fn matches(&self, host: &str) -> bool {
+        if host.eq_ignore_ascii_case(&self.authority) {
+            return true;
+        }
+        !self.authority.contains(':')
+            && host
+                .rsplit_once(':')
+                .is_some_and(|(name, _)| name.eq_ignore_ascii_case(&self.authority))
+    }
+
+    /// The canonical URL of `path_and_query`
+    pub fn url(&self, path_and_query: &str) -> String {
+        format!("{}://{}{}", self.scheme, self.authority, path_and_query)
+    }
+}
+
+/// Load the canonical host from `CANONICAL_HOST`; `None` when unset
+pub fn load_canonical_host_from_env() -> Result<Option<CanonicalHost>, String> {
+    std::env::var("CANONICAL_HOST")
+        .ok()
+        .map(|value| CanonicalHost::parse(&value))
+        .transpose()
+}
+
+/// Middleware sending `GET` and `HEAD` requests for other hosts (an old domain, a bare IP) to
+/// the canonical host with `301 Moved Permanently`, keeping the path and query
+///
+/// Other methods are served where they arrive, since clients would replay them as `GET`. The
+/// service status ARK is always answered locally, so health probes by IP keep working.
+pub async fn redirect_to_canonical_host(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(canonical) = &state.canonical_host else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return next.run(request).await;
+    }
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            request
+                .uri()
+                .authority()
+                .map(|authority| authority.as_str())
+        });
+    let Some(host) = host else {
+        return next.run(request).await;
+    };
+    if canonical.matches(host) || is_service_status_path(&state.naan, request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+    let location = canonical.url(path_and_query);
+    tracing::debug!(host = %host, location = %location, "Redirecting to canonical host");
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, location)],
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::router::create_router;
+    use crate::testing::fixture_state;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    async fn send(method: Method, host: &str, path: &str) -> Response {
+        let state = AppState {
+            canonical_host: Some(CanonicalHost::parse("ark.example.org").unwrap()),
+            ..fixture_state()
+        };
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::HOST, host)
+            .body(Body::empty())
+            .unwrap();
+        create_router(Arc::new(state))
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_redirects_other_hosts() {
+        let response = send(Method::GET, "old.example.org", "/ark:12345/x6np1wh8kq?info").await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://ark.example.org/ark:12345/x6np1wh8kq?info"
+        );
+
+        let response = send(Method::GET, "192.0.2.1:3000", "/api/v1/info").await;
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://ark.example.org/api/v1/info"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serves_canonical_host_and_exemptions() {
+        let response = send(Method::GET, "ARK.example.org:443", "/ark:12345/x6np1wh8kq").await;
+        assert_eq!(response.status(), StatusCode::FOUND);
+
+        let response = send(Method::GET, "10.0.0.7", "/ark:12345/servicestatus").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(Method::POST, "old.example.org", "/api/v1/info").await;
+        assert_ne!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    }
+
+    #[test]
+    fn test_parse_canonical_host() {
+        let origin = CanonicalHost::parse("http://localhost:3000").unwrap();
+        assert_eq!(
+            origin.url("/ark:12345/x6"),
+            "http://localhost:3000/ark:12345/x6"
+        );
+        assert!(origin.matches("localhost:3000"));
+        assert!(!origin.matches("localhost:8080"));
+
+        assert!(CanonicalHost::parse("ark.example.org/resolver").is_err());
+        assert!(CanonicalHost::parse("ftp://ark.example.org").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::ark::strip_label;
+use crate::config::AppState;
+use crate::error::AppError;
+
+/// Middleware giving each resolution request its deadline, answering `504 Gateway Timeout`
+/// once it passes
+///
+/// The deadline is left in the request extensions, so handlers can bound their own storage
+/// queries and upstream calls by it. Other requests, such as mints, run to completion.
+pub async fn enforce_deadline(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let path = request.uri().path().to_string();
+    if path.strip_prefix('/').and_then(strip_label).is_none() {
+        return Ok(next.run(request).await);
+    }
+    let deadline = state
+        .request_timeouts
+        .deadline_for(request.headers())
+        .map_err(AppError::InvalidRequest)?;
+    let Some(deadline) = deadline else {
+        return Ok(next.run(request).await);
+    };
+
+    request.extensions_mut().insert(deadline);
+    tokio::time::timeout_at(deadline.instant(), next.run(request))
+        .await
+        .map_err(|_| {
+            tracing::warn!(path = %path, "Request deadline exceeded");
+            AppError::DeadlineExceeded
+        })
+}
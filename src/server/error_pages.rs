@@ -0,0 +1,174 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use minijinja::context;
+use std::sync::Arc;
+
+use crate::ark::{Ark, strip_label};
+use crate::config::AppState;
+use crate::error::ErrorMessage;
+use crate::error_pages;
+use crate::i18n::Locale;
+
+/// Middleware replacing public error messages with the operator's HTML error pages
+///
+/// Only applies to clients that accept `text/html`, so API clients keep plain-text errors.
+pub async fn render_error_pages(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wants_html = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    let locale = Locale::from_headers(request.headers());
+    let requested_ark = request
+        .uri()
+        .path()
+        .strip_prefix('/')
+        .and_then(strip_label)
+        .map(|rest| format!("ark:{}", urlencoding::decode(rest).unwrap_or_default()));
+
+    let mut response = next.run(request).await;
+    let Some(ErrorMessage(key)) = response.extensions().get::<ErrorMessage>().copied() else {
+        return response;
+    };
+    if !wants_html {
+        return response;
+    }
+
+    let Some(template) = error_pages::template_name(key).filter(|name| state.views.has(name))
+    else {
+        return response;
+    };
+
+    let ark = requested_ark.unwrap_or_default();
+    let shoulder = Ark::try_from(ark.as_str())
+        .ok()
+        .and_then(|parsed| state.shoulders.get(&parsed.shoulder));
+    let context = context! {
+        lang => locale.tag(),
+        naan => &state.naan,
+        ark => ark,
+        status => response.status().as_u16(),
+        message => locale.message(key),
+        project_name => shoulder.map(|shoulder| &shoulder.project_name),
+        contact => shoulder.and_then(|shoulder| shoulder.contact.as_ref()),
+    };
+
+    match state.views.render(template, context) {
+        Ok(html) => {
+            *response.body_mut() = Body::from(html);
+            let headers = response.headers_mut();
+            headers.remove(header::CONTENT_LENGTH);
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            headers.append(header::VARY, HeaderValue::from_static("accept"));
+        }
+        Err(e) => tracing::error!(error = %e, "Failed to render error page"),
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_pages::ErrorPagesConfig;
+    use crate::server::router::create_router;
+    use crate::shoulder::{Contact, Shoulder};
+    use crate::views::Views;
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn create_test_state() -> Arc<AppState> {
+        let config: ErrorPagesConfig = serde_json::from_value(serde_json::json!({
+            "shoulder_not_found": {"inline": "<h1>{{ status }}</h1><p>{{ message }}: {{ ark }}</p>"},
+            "invalid_ark": {"inline": "<p>{{ message }} ({{ contact.email }})</p>"},
+        }))
+        .unwrap();
+
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Test Project".to_string(),
+                contact: Some(Contact {
+                    name: "Test Team".to_string(),
+                    email: Some("team@example.org".to_string()),
+                    organization: None,
+                }),
+                ..Default::default()
+            },
+        );
+
+        let mut views = Views::default();
+        config.load_into(&mut views).unwrap();
+
+        Arc::new(AppState {
+            shoulders,
+            views,
+            ..Default::default()
+        })
+    }
+
+    async fn get(path: &str, accept: Option<&str>, language: Option<&str>) -> (Response, String) {
+        let mut request = Request::get(path);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        if let Some(language) = language {
+            request = request.header(header::ACCEPT_LANGUAGE, language);
+        }
+        let response = create_router(create_test_state())
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        (
+            Response::from_parts(parts, Body::empty()),
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_renders_page_for_browsers() {
+        let (response, body) = get("/ark:12345/z9np1wh8k", Some("text/html"), Some("fr")).await;
+
+        assert_eq!(response.status(), 404);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(
+            body,
+            "<h1>404</h1><p>Préfixe introuvable: ark:12345&#x2f;z9np1wh8k</p>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_includes_shoulder_contact() {
+        let (response, body) = get("/ark:99999/x6np1wh8k", Some("text/html"), None).await;
+
+        assert_eq!(response.status(), 400);
+        assert_eq!(body, "<p>NAAN does not match (team@example.org)</p>");
+    }
+
+    #[tokio::test]
+    async fn test_keeps_plain_text_for_api_clients() {
+        let (response, body) = get("/ark:12345/z9np1wh8k", None, None).await;
+
+        assert_eq!(response.status(), 404);
+        assert_eq!(body, "Shoulder not found");
+    }
+}
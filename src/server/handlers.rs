@@ -1,56 +1,280 @@
 use axum::{
-    Json,
-    extract::{OriginalUri, State},
-    http::{StatusCode, header},
-    response::{IntoResponse, Response},
+    Extension, Json,
+    body::Body,
+    extract::{OriginalUri, Path, Query, State},
+    http::{HeaderMap, Method, StatusCode, header},
+    response::{Html, IntoResponse, Response},
 };
+use chrono::Utc;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::ReceiverStream;
 
+use super::auth::{self, AdminIdentity, AdminSession};
 use super::models::{
-    ArkValidationResult, InfoResponse, MintRequest, MintResponse, ShoulderInfo, ValidateRequest,
-    ValidateResponse,
+    ApiKeySummary, AppliedChangesResponse, ArkValidationResult, BatchRequest, BatchResponse,
+    BindingRequest, BladeLengthReport, BoundArk, CapacityQuery, ChangeListQuery, CompareRequest,
+    CompareResponse, CreateKeyRequest, DeliveriesQuery, EnableReadOnlyRequest, EquivalenceGroup,
+    ExampleArk, ExampleQuery, ExpireKeyRequest, ExportQuery, ImportError, ImportQuery,
+    ImportResponse, InfoResponse, IssuedKeyResponse, KeyUsageQuery, KeyUsageReport,
+    LogLevelResponse, MintAndBindRequest, MintAndBindResponse, MintRequest, MintResponse,
+    MintedArk, MintedArkDetail, NormalizedArk, OpenIncidentRequest, PairComparison,
+    ReadOnlyResponse, RegisterRequest, RegisterResponse, ResolutionResponse,
+    ResolveQuarantineRequest, RestoreRequest, RotateKeyRequest, ScheduleMaintenanceRequest,
+    ShortenRequest, ShortenResponse, ShoulderCapacity, ShoulderExamples, ShoulderInfo,
+    SitemapQuery, SnapshotsResponse, StatusChangeRequest, StatusChangeResponse,
+    TakeSnapshotRequest, UnknownNamespaceStub, ValidateRequest, ValidateResponse, ValidationTiming,
 };
+use crate::ark::{explain_normalization, parse_ark, strip_label};
+use crate::batch;
+use crate::bindings::{Binding, StorageError};
+use crate::blade_advisor::{self, BladeLengthAdvice};
+use crate::callbacks::{FailureKind, ResolutionFailure};
 use crate::config::AppState;
+use crate::config_document::{ConfigDocument, StagedConfig};
+use crate::deadline::{self, Deadline};
+use crate::deliveries::{Delivery, DeliveryStatus};
+use crate::dublin_core::DublinCore;
 use crate::error::AppError;
+use crate::events::{ArkChange, ArkEvent};
+use crate::export::EXPORT_PAGE_SIZE;
+use crate::http_client::DestinationStats;
+use crate::i18n::Locale;
+use crate::idempotency::{MintReceipt, receipt_key, validate_idempotency_key};
+use crate::import::{self, IMPORT_CHUNK_SIZE, ImportedIdentifier, MAX_IMPORT_ERRORS};
+use crate::inflection::{UnregisteredInflections, erc_record};
+use crate::key_usage::{USAGE_CSV_HEADER, UsageCounts, UsageFormat, UsageKey};
+use crate::ledger::LedgerPage;
+use crate::lifecycle::{ArkStatus, Lifecycle};
+use crate::limits::InputLimitsReport;
+use crate::log_level::LogFilter;
+use crate::mint_pool::PoolLevel;
+use crate::mint_queue::MintQueueStats;
 use crate::minting;
-use crate::validation;
+use crate::minting::ShoulderMintStats;
+use crate::notify::Alert;
+use crate::quarantine::{BlockedResolution, QuarantineEntry};
+use crate::read_only::ReadOnlyStatus;
+use crate::replication::{ChangeBatch, ReplicationStatus};
+use crate::resourcesync::ResourceSync;
+use crate::shorten;
+use crate::shoulder::{BlockedTarget, ResolutionOutcome, Shoulder};
+use crate::sitemap;
+use crate::snapshot::{self, RestoreReport, Snapshot, SnapshotDir, SnapshotSummary};
+use crate::status::{DependencyHealth, Incident, MaintenanceWindow};
+use crate::template::Generator;
+use crate::turtle::ArkResource;
+use crate::validation::{self, ValidationCode};
+use crate::well_known::ArkConfiguration;
 use crate::{ark::Ark, minting::mint_ark};
 
-pub async fn health_check_handler() -> &'static str {
-    "OK"
+/// Service status: plain `OK` for health probes, or a full status page when the client asks
+/// for JSON or HTML via the `Accept` header
+pub async fn health_check_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if accept.contains("application/json") {
+        Json(status_report(&state)).into_response()
+    } else if accept.contains("text/html") {
+        render_html(&state, "status.html", status_report(&state))
+    } else if state.bindings.unavailable_since().is_some() {
+        // Still ready: resolution carries on from route patterns, so probes must not pull
+        // the instance out of rotation
+        "DEGRADED".into_response()
+    } else {
+        "OK".into_response()
+    }
+}
+
+/// Renders an HTML view, or a 500 response if the template fails
+fn render_html(state: &AppState, name: &str, context: impl serde::Serialize) -> Response {
+    state
+        .views
+        .render(name, context)
+        .map(Html)
+        .map_err(AppError::Internal)
+        .into_response()
+}
+
+fn status_report(state: &AppState) -> crate::status::StatusReport {
+    let bindings_down_since = state.bindings.unavailable_since();
+    let read_only = state.read_only.status();
+    let dependencies = vec![
+        DependencyHealth {
+            name: "shoulders".to_string(),
+            status: if state.shoulders.is_empty() {
+                "failing"
+            } else {
+                "ok"
+            }
+            .to_string(),
+            detail: Some(format!("{} configured", state.shoulders.len())),
+        },
+        DependencyHealth {
+            name: "bindings".to_string(),
+            status: if bindings_down_since.is_some() {
+                "failing"
+            } else {
+                "ok"
+            }
+            .to_string(),
+            detail: bindings_down_since
+                .map(|since| format!("unavailable since {}", since.to_rfc3339())),
+        },
+        match state.store.minted() {
+            Ok(minted) => DependencyHealth {
+                name: "mint_ledger".to_string(),
+                status: "ok".to_string(),
+                detail: Some(format!("{}, {} recorded", state.store.backend(), minted)),
+            },
+            Err(e) => DependencyHealth {
+                name: "mint_ledger".to_string(),
+                status: "failing".to_string(),
+                detail: Some(e.to_string()),
+            },
+        },
+        DependencyHealth {
+            name: "mint_journal".to_string(),
+            status: if state.mint_journal.is_some() {
+                "ok"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            detail: state
+                .mint_journal
+                .as_ref()
+                .map(|journal| format!("{} pending", journal.pending())),
+        },
+        DependencyHealth {
+            name: "writes".to_string(),
+            status: if read_only.is_some() {
+                "disabled"
+            } else {
+                "ok"
+            }
+            .to_string(),
+            detail: read_only.map(|status| {
+                let since = format!("read-only since {}", status.since.to_rfc3339());
+                match status.reason {
+                    Some(reason) => format!("{}: {}", since, reason),
+                    None => since,
+                }
+            }),
+        },
+        {
+            let replication = state.replication.status();
+            DependencyHealth {
+                name: "replication".to_string(),
+                status: if !replication.enabled {
+                    "disabled"
+                } else if replication.lagging || replication.failing_since.is_some() {
+                    "failing"
+                } else {
+                    "ok"
+                }
+                .to_string(),
+                detail: replication.enabled.then(|| {
+                    let behind = format!(
+                        "{} pending, {}s behind",
+                        replication.pending, replication.lag_seconds
+                    );
+                    match replication.dropped {
+                        0 => behind,
+                        dropped => format!("{}, {} dropped", behind, dropped),
+                    }
+                }),
+            }
+        },
+        DependencyHealth {
+            name: "email".to_string(),
+            status: if state.notifier.is_enabled() {
+                "ok"
+            } else {
+                "disabled"
+            }
+            .to_string(),
+            detail: None,
+        },
+    ];
+
+    state.status_board.report(&state.naan, dependencies)
 }
 
-pub async fn info_handler(State(state): State<Arc<AppState>>) -> Json<InfoResponse> {
-    let shoulders: Vec<ShoulderInfo> = state
+/// Service info as JSON, or as an HTML page when the client asks for it via `Accept`
+pub async fn info_handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let mut shoulders: Vec<ShoulderInfo> = state
         .shoulders
         .iter()
+        .filter(|(_, config)| config.public)
         .map(|(shoulder, config)| {
-            let blade_length = config.blade_length.unwrap_or(state.default_blade_length);
+            let blade_length = state.blade_length_for(shoulder, config);
+            let mint_stats = state.mint_stats.shoulder(shoulder).unwrap_or_default();
+            let resolutions = state.resolution_stats.recent(shoulder);
+            let has_components = !config.components.is_empty();
+            // The ledger is shared, so it is measured against every instance's partition
+            let namespace_size = config.blade_template(blade_length).namespace_size();
+            let recorded = state.ledger_counts.get(shoulder);
             ShoulderInfo {
                 shoulder: shoulder.clone(),
                 project_name: config.project_name.clone(),
-                uses_check_character: config.uses_check_character,
+                uses_check_character: config.check_character(),
                 blade_length,
+                alphabet_size: config.alphabet.characters().len(),
+                namespace_size,
+                percent_consumed: recorded.map(|recorded| recorded as f64 / namespace_size * 100.0),
                 example_ark: mint_ark(
                     &state.naan,
                     shoulder,
-                    blade_length,
-                    config.uses_check_character,
+                    &state.blade_template_for(config, blade_length),
+                    state.ark_format_for(config),
                 ),
+                contact: config.contact.clone(),
+                minted: mint_stats.minted,
+                bound: state.binding_counts.get(shoulder),
+                last_minted_at: mint_stats.last_minted_at,
+                resolutions_24h: resolutions.last_24h,
+                resolutions_30d: resolutions.last_30d,
+                component_resolutions_24h: has_components.then_some(resolutions.components_24h),
+                component_resolutions_30d: has_components.then_some(resolutions.components_30d),
             }
         })
         .collect();
 
+    shoulders.sort_by(|a, b| a.shoulder.cmp(&b.shoulder));
+
     tracing::debug!(shoulder_count = shoulders.len(), "Info request");
 
-    Json(InfoResponse {
+    let info = InfoResponse {
         naan: state.naan.clone(),
         shoulders,
-    })
+    };
+    if accepts_html(&headers) {
+        render_html(&state, "info.html", info)
+    } else {
+        Json(info).into_response()
+    }
+}
+
+/// Whether the client prefers HTML, e.g. a browser
+fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
 }
 
 pub async fn mint_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(payload): Json<MintRequest>,
 ) -> Result<Json<MintResponse>, AppError> {
     tracing::info!(
@@ -59,7 +283,59 @@ pub async fn mint_handler(
         "Mint request received"
     );
 
-    let arks = minting::mint_arks(&state, &payload.shoulder, payload.count)?;
+    minting::validate_mint_metadata(&payload.metadata).map_err(AppError::InvalidRequest)?;
+    if payload.dry_run {
+        return mint_dry_run(&state, &headers, &payload);
+    }
+
+    let key = idempotency_key(&headers, payload.request_id.as_deref())?
+        .map(|key| {
+            // Receipts are kept per key holder, so callers without a key cannot share them
+            auth::authenticate_minter(&state, &headers)
+                .map(|holder| receipt_key(&holder, &key))
+                .map_err(|_| {
+                    AppError::InvalidRequest(
+                        "Idempotency keys can only be used with an API key".to_string(),
+                    )
+                })
+        })
+        .transpose()?;
+    if let Some(key) = &key
+        && let Some(receipt) = state.store.receipt(key)?
+        && receipt.is_current(Utc::now())
+    {
+        return replay_mint(&state, receipt, &payload);
+    }
+
+    let job_state = state.clone();
+    let shoulder = payload.shoulder.clone();
+    let count = payload.count;
+    let metadata = payload.metadata.clone();
+    let arks = state
+        .mint_queue
+        .run(move || minting::mint_with_metadata(&job_state, &shoulder, count, &metadata))
+        .await?;
+
+    // The receipt must be kept before the ARKs are handed out, or a retry would get others
+    if let Some(key) = key {
+        let receipt = MintReceipt {
+            key,
+            shoulder: payload.shoulder.clone(),
+            count: payload.count,
+            metadata: payload.metadata.clone(),
+            detail: payload.detail,
+            arks: arks.clone(),
+            created_at: Utc::now(),
+        };
+        if let Some(existing) = state.store.save_receipt(&receipt)? {
+            tracing::warn!(
+                shoulder = %payload.shoulder,
+                unused_count = arks.len(),
+                "Concurrent mint with the same idempotency key, ARKs left unused"
+            );
+            return replay_mint(&state, existing, &payload);
+        }
+    }
 
     tracing::info!(
         shoulder = %payload.shoulder,
@@ -68,184 +344,4259 @@ pub async fn mint_handler(
         "Mint request completed successfully"
     );
 
+    for ark in &arks {
+        state.event_log.record(ark.clone(), ArkChange::Created);
+    }
+
+    Ok(Json(mint_response(&state, arks, payload.detail, false)))
+}
+
+/// The request's idempotency key, from the `Idempotency-Key` header or else `request_id`
+fn idempotency_key(
+    headers: &HeaderMap,
+    request_id: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let header = headers
+        .get("idempotency-key")
+        .map(|value| {
+            value.to_str().map_err(|_| {
+                AppError::InvalidRequest("Idempotency key must be visible ASCII characters".into())
+            })
+        })
+        .transpose()?;
+    let key = match (header, request_id) {
+        (Some(header), Some(request_id)) if header != request_id => {
+            return Err(AppError::InvalidRequest(
+                "Idempotency-Key header and request_id differ".to_string(),
+            ));
+        }
+        (header, request_id) => header.or(request_id),
+    };
+    key.map(|key| {
+        validate_idempotency_key(key)
+            .map(|()| key.to_string())
+            .map_err(AppError::InvalidRequest)
+    })
+    .transpose()
+}
+
+/// Answer a dry run with ARKs shaped like the ones the shoulder mints, neither recorded nor
+/// reserved
+fn mint_dry_run(
+    state: &AppState,
+    headers: &HeaderMap,
+    payload: &MintRequest,
+) -> Result<Json<MintResponse>, AppError> {
+    if idempotency_key(headers, payload.request_id.as_deref())?.is_some() {
+        return Err(AppError::InvalidRequest(
+            "Dry runs cannot be retried with an idempotency key".to_string(),
+        ));
+    }
+    let count = payload.count.min(state.max_mint_count);
+    let arks = minting::synthesize_arks(state, &payload.shoulder, count)?;
+    tracing::info!(
+        shoulder = %payload.shoulder,
+        example_count = arks.len(),
+        requested_count = payload.count,
+        "Mint dry run completed"
+    );
     Ok(Json(MintResponse {
+        dry_run: true,
+        ..mint_response(state, arks, payload.detail, false)
+    }))
+}
+
+/// Answer a retried mint with the ARKs of its receipt
+fn replay_mint(
+    state: &AppState,
+    receipt: MintReceipt,
+    payload: &MintRequest,
+) -> Result<Json<MintResponse>, AppError> {
+    if receipt.shoulder != payload.shoulder
+        || receipt.count != payload.count
+        || receipt.metadata != payload.metadata
+        || receipt.detail != payload.detail
+    {
+        return Err(AppError::Unprocessable(
+            "Idempotency key was already used for a different mint request".to_string(),
+        ));
+    }
+    tracing::info!(
+        shoulder = %payload.shoulder,
+        replayed_count = receipt.arks.len(),
+        "Mint request replayed from its idempotency key"
+    );
+    Ok(Json(mint_response(
+        state,
+        receipt.arks,
+        payload.detail,
+        true,
+    )))
+}
+
+fn mint_response(
+    state: &AppState,
+    arks: Vec<String>,
+    detail: bool,
+    replayed: bool,
+) -> MintResponse {
+    let arks: Vec<MintedArk> = if detail {
+        arks.into_iter()
+            .map(|ark| MintedArk::Detailed(minted_ark_detail(state, ark)))
+            .collect()
+    } else {
+        arks.into_iter().map(MintedArk::Plain).collect()
+    };
+    MintResponse {
         count: arks.len(),
         arks,
-    }))
+        replayed,
+        dry_run: false,
+    }
 }
 
-pub async fn validate_handler(
+/// Mint ARKs and send them as newline-delimited JSON while they are generated
+///
+/// Takes the same request as [`mint_handler`], but counts up to `max_stream_mint_count`. ARKs
+/// are minted `max_mint_count` at a time, and the next batch only once the client has read the
+/// previous one, so neither side holds the whole list. A failure after the first line ends the
+/// stream with an `{"error": ...}` line.
+pub async fn mint_stream_handler(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<ValidateRequest>,
-) -> Json<ValidateResponse> {
-    let results: Vec<ArkValidationResult> = payload
-        .arks
-        .iter()
-        .map(|ark| {
-            let result = validation::validate_ark(&state, ark, payload.has_check_character);
+    headers: HeaderMap,
+    Json(payload): Json<MintRequest>,
+) -> Result<Response, AppError> {
+    if idempotency_key(&headers, payload.request_id.as_deref())?.is_some() {
+        return Err(AppError::InvalidRequest(
+            "Streamed mints cannot be retried with an idempotency key".to_string(),
+        ));
+    }
+    if payload.dry_run {
+        return Err(AppError::InvalidRequest(
+            "Streamed mints cannot be dry runs".to_string(),
+        ));
+    }
+    if !state.shoulders.contains_key(payload.shoulder.as_str()) {
+        return Err(AppError::ShoulderNotFound);
+    }
+    minting::validate_mint_metadata(&payload.metadata).map_err(AppError::InvalidRequest)?;
+    if payload.count > state.max_stream_mint_count {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} ARKs can be minted per stream",
+            state.max_stream_mint_count
+        )));
+    }
+    tracing::info!(
+        shoulder = %payload.shoulder,
+        requested_count = payload.count,
+        "Streamed mint request received"
+    );
 
-            ArkValidationResult {
-                ark: ark.clone(),
-                valid: result.valid,
-                naan: result.naan,
-                shoulder: result.shoulder,
-                blade: result.blade,
-                shoulder_registered: result.shoulder_registered,
-                has_check_character: result.has_check_character,
-                check_character_valid: result.check_character_valid,
-                error: result.error,
-                warnings: result.warnings,
+    // One batch waits while the client reads the previous one
+    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(stream_mint(state, payload, sender));
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(ReceiverStream::new(receiver)),
+    )
+        .into_response())
+}
+
+/// Mints the ARKs of a streamed request batch by batch, sending each as NDJSON lines
+async fn stream_mint(
+    state: Arc<AppState>,
+    payload: MintRequest,
+    sender: tokio::sync::mpsc::Sender<Result<String, Infallible>>,
+) {
+    // At least one per batch, or a zero `max_mint_count` would never finish, and no more than
+    // the shoulder's quota allows at once
+    let quota_size = state
+        .shoulders
+        .get(payload.shoulder.as_str())
+        .and_then(|shoulder| shoulder.mint_quota)
+        .map_or(usize::MAX, |quota| {
+            usize::try_from(quota.largest_mint()).unwrap_or(usize::MAX)
+        });
+    let chunk_size = state.max_mint_count.min(quota_size).max(1);
+    let mut remaining = payload.count;
+    let mut minted = 0;
+    while remaining > 0 {
+        let count = remaining.min(chunk_size);
+        let job_state = state.clone();
+        let shoulder = payload.shoulder.clone();
+        let metadata = payload.metadata.clone();
+        // Read-only mode switched on mid-stream stops it before the next batch
+        let minted_batch = match state.read_only.begin_write() {
+            None => Err(AppError::ReadOnly),
+            Some(_write) => {
+                state
+                    .mint_queue
+                    .run(move || {
+                        minting::mint_with_metadata(&job_state, &shoulder, count, &metadata)
+                    })
+                    .await
             }
-        })
-        .collect();
+        };
+        let lines = match minted_batch {
+            Ok(arks) => {
+                for ark in &arks {
+                    state.event_log.record(ark.clone(), ArkChange::Created);
+                }
+                minted += arks.len();
+                remaining -= count;
+                mint_response(&state, arks, payload.detail, false)
+                    .arks
+                    .iter()
+                    .map(|ark| serde_json::to_string(ark).expect("minted ARKs serialize") + "\n")
+                    .collect::<String>()
+            }
+            Err(error) => {
+                tracing::warn!(
+                    shoulder = %payload.shoulder,
+                    minted_count = minted,
+                    error = ?error,
+                    "Streamed mint stopped"
+                );
+                let line = serde_json::json!({ "error": stream_error_message(&error) });
+                let _ = sender.send(Ok(line.to_string() + "\n")).await;
+                return;
+            }
+        };
+        if sender.send(Ok(lines)).await.is_err() {
+            tracing::info!(
+                shoulder = %payload.shoulder,
+                minted_count = minted,
+                "Streamed mint client went away"
+            );
+            return;
+        }
+    }
+    tracing::info!(
+        shoulder = %payload.shoulder,
+        minted_count = minted,
+        "Streamed mint completed successfully"
+    );
+}
 
-    let valid_count = results.iter().filter(|r| r.valid).count();
-    let invalid_count = results.len() - valid_count;
+/// English message for an error that ended a streamed mint, once the status was already sent
+fn stream_error_message(error: &AppError) -> &'static str {
+    let key = match error {
+        AppError::MintExhausted => "mint_exhausted",
+        AppError::StorageUnavailable(_) => "storage_unavailable",
+        AppError::Overloaded { .. } => "overloaded",
+        AppError::MintQuotaExceeded { .. } => "mint_quota_exceeded",
+        AppError::DeadlineExceeded => "deadline_exceeded",
+        AppError::ReadOnly => "read_only",
+        _ => return "Internal server error",
+    };
+    Locale::En.message(key)
+}
 
-    if invalid_count > 0 {
-        tracing::info!(
-            total = results.len(),
-            valid = valid_count,
-            invalid = invalid_count,
-            "Validation completed with failures"
-        );
-    } else {
-        tracing::debug!(total = results.len(), "Validation completed - all valid");
+/// Mint a few ARKs on the public mint's test shoulder, for clients trying the API out
+///
+/// Takes the same request as [`mint_handler`], but only for the test shoulder, with the count
+/// capped at the public mint's `max_count`.
+pub async fn public_mint_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<MintRequest>,
+) -> Result<Json<MintResponse>, AppError> {
+    let public_mint = state
+        .public_mint
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Public minting is disabled".to_string()))?;
+    if payload.shoulder != public_mint.shoulder.as_str() {
+        return Err(AppError::InvalidRequest(format!(
+            "Only the test shoulder {} can be minted on without credentials",
+            public_mint.shoulder
+        )));
     }
+    let payload = MintRequest {
+        count: payload.count.min(public_mint.max_count),
+        ..payload
+    };
+    mint_handler(State(state), headers, Json(payload)).await
+}
 
-    Json(ValidateResponse { results })
+/// Derive the short ARK for a URL, or a content hash, binding it on first use
+///
+/// Answers 201 Created when the ARK was bound by this request and 200 OK when it already was.
+pub async fn shorten_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ShortenRequest>,
+) -> Result<(StatusCode, Json<ShortenResponse>), AppError> {
+    let job_state = state.clone();
+    let shortened = state
+        .mint_queue
+        .run(move || shorten::shorten(&job_state, &payload.url, payload.hash.as_deref()))
+        .await?;
+    let status = if shortened.created {
+        state
+            .event_log
+            .record(shortened.canonical.clone(), ArkChange::Created);
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    let short_url = state
+        .public_base_url
+        .as_ref()
+        .map(|base| format!("{}/{}", base, shortened.ark));
+    Ok((
+        status,
+        Json(ShortenResponse {
+            ark: shortened.ark,
+            short_url,
+            target: shortened.target,
+            status: shortened.status,
+            created: shortened.created,
+        }),
+    ))
 }
 
-pub async fn resolve_handler(
+/// Mint one ARK per binding and record the bindings, so no ARK is handed out unbound
+pub async fn mint_and_bind_handler(
     State(state): State<Arc<AppState>>,
-    OriginalUri(uri): OriginalUri,
-) -> Result<Response, AppError> {
-    // Extract path and query from URI: /ark:12345/x6test?info -> ark:12345/x6test?info
-    let path_and_query = uri.path_and_query().ok_or(AppError::InvalidArk)?.as_str();
+    Json(payload): Json<MintAndBindRequest>,
+) -> Result<Json<MintAndBindResponse>, AppError> {
+    let shoulder = state
+        .shoulders
+        .get(payload.shoulder.as_str())
+        .ok_or(AppError::ShoulderNotFound)?;
+    if payload.bindings.is_empty() {
+        return Err(AppError::InvalidRequest("No bindings given".to_string()));
+    }
+    if payload.bindings.len() > state.max_mint_count {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} ARKs can be minted per request",
+            state.max_mint_count
+        )));
+    }
+    let lifecycle = Lifecycle::starting(payload.status).map_err(AppError::InvalidRequest)?;
+    // Check every target before minting, so a bad one leaves nothing half done
+    for (index, binding) in payload.bindings.iter().enumerate() {
+        shoulder
+            .validate_target(&binding.target)
+            .map_err(|e| AppError::InvalidRequest(format!("bindings[{}]: {}", index, e)))?;
+    }
 
-    // Remove leading /ark: to get just the ARK identifier
-    let ark_string = path_and_query
-        .strip_prefix("/ark:")
-        .ok_or(AppError::InvalidArk)?;
+    let job_state = state.clone();
+    let shoulder = payload.shoulder.clone();
+    let (arks, journaled) = state
+        .mint_queue
+        .run(move || mint_and_bind(&job_state, &shoulder, payload.bindings, lifecycle))
+        .await?;
+    // Reserved ARKs are announced once they are promoted
+    if payload.status == ArkStatus::Active {
+        for bound in &arks {
+            state
+                .event_log
+                .record(bound.ark.clone(), ArkChange::Created);
+        }
+    }
 
-    let ark_string = format!("ark:{}", ark_string);
-    // Parse the full ARK string (e.g., "ark:12345/x6np1wh8k/page2.pdf?info")
-    let parsed_ark = Ark::try_from(ark_string.as_str())?;
+    tracing::info!(
+        shoulder = %payload.shoulder,
+        bound_count = arks.len(),
+        "Mint and bind completed successfully"
+    );
 
-    // Check NAAN matches
-    if parsed_ark.naan != state.naan {
-        return Err(AppError::InvalidNaan);
-    }
+    Ok(Json(MintAndBindResponse {
+        count: arks.len(),
+        arks,
+        journaled,
+    }))
+}
 
-    // Look up routing rule
+/// Mints the ARKs for `bindings` and records them, in the store or else in the journal
+fn mint_and_bind(
+    state: &AppState,
+    shoulder: &str,
+    bindings: Vec<BindingRequest>,
+    lifecycle: Lifecycle,
+) -> Result<(Vec<BoundArk>, bool), AppError> {
+    let arks = minting::mint_arks(state, shoulder, bindings.len())?;
     let shoulder_config = state
         .shoulders
-        .get(&parsed_ark.shoulder)
-        .ok_or(AppError::ShoulderNotFound)?;
+        .get(shoulder)
+        .expect("minting succeeded on a configured shoulder");
+    let bound_at = Utc::now();
+    let arks: Vec<BoundArk> = arks
+        .into_iter()
+        .zip(bindings)
+        .map(|(ark, binding)| BoundArk {
+            ark,
+            binding: Binding {
+                target: binding.target,
+                metadata: shoulder_config.metadata_with_defaults(binding.metadata),
+                bound_at,
+                lifecycle: lifecycle.clone(),
+            },
+        })
+        .collect();
+    let entries: Vec<(Ark, Binding)> = arks
+        .iter()
+        .map(|bound| {
+            let ark = Ark::try_from(bound.ark.as_str()).expect("minted ARKs always parse");
+            (ark, bound.binding.clone())
+        })
+        .collect();
+    // The ARKs are already minted, but an unbound ARK must not be handed out: without the store,
+    // the bindings must at least reach the journal
+    let journaled = match state.bindings.bind_all(entries.iter().cloned()) {
+        Ok(()) => false,
+        Err(e) => {
+            let journal = state
+                .mint_journal
+                .as_ref()
+                .ok_or_else(|| AppError::from(e))?;
+            journal.append(&entries).map_err(|io| {
+                AppError::StorageUnavailable(format!("mint journal write failed: {}", io))
+            })?;
+            tracing::warn!(
+                shoulder = %shoulder,
+                journaled = entries.len(),
+                "Binding storage unavailable, bindings journaled"
+            );
+            true
+        }
+    };
+    Ok((arks, journaled))
+}
+
+/// Record ARKs created outside the service in the mint ledger, so minting avoids them and they
+/// count in the shoulders' stats
+///
+/// Every ARK must be valid on a configured shoulder of this NAAN, without a qualifier, or nothing
+/// is recorded.
+pub async fn register_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<Json<RegisterResponse>, AppError> {
+    let mut inputs: Vec<(String, String)> = payload
+        .arks
+        .into_iter()
+        .enumerate()
+        .map(|(index, ark)| (format!("arks[{}]", index), ark))
+        .collect();
+    if !payload.blades.is_empty() {
+        let shoulder = payload.shoulder.as_deref().ok_or_else(|| {
+            AppError::InvalidRequest("blades need the shoulder they are on".to_string())
+        })?;
+        let config = state
+            .shoulders
+            .get(shoulder)
+            .ok_or(AppError::ShoulderNotFound)?;
+        let format = state.ark_format_for(config);
+        inputs.extend(payload.blades.iter().enumerate().map(|(index, blade)| {
+            (
+                format!("blades[{}]", index),
+                format.format(&state.naan, shoulder, blade, ""),
+            )
+        }));
+    }
+    if inputs.is_empty() {
+        return Err(AppError::InvalidRequest("No ARKs given".to_string()));
+    }
+    if inputs.len() > state.max_mint_count {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} ARKs can be registered per request",
+            state.max_mint_count
+        )));
+    }
+
+    // Check every ARK before recording any, so a bad one leaves nothing half done
+    let mut arks = Vec::with_capacity(inputs.len());
+    for (field, input) in &inputs {
+        let invalid = |reason: String| AppError::InvalidRequest(format!("{}: {}", field, reason));
+        let result = validation::validate_ark(&state, input, None);
+        if !result.valid {
+            return Err(invalid(invalid_reason(result)));
+        }
+        let ark = parse_ark(input).expect("valid ARKs parse");
+        if !ark.qualifier.is_empty() {
+            return Err(invalid("qualifiers cannot be registered".to_string()));
+        }
+        arks.push(ark);
+    }
 
-    // Resolve ARK using shoulder's routing configuration
-    let target_url = shoulder_config.resolve(&parsed_ark);
+    let job_state = state.clone();
+    let (registered, already_registered) = state
+        .mint_queue
+        .run(move || minting::register_arks(&job_state, &arks))
+        .await?;
+    for ark in &registered {
+        state.event_log.record(ark.clone(), ArkChange::Created);
+    }
 
-    tracing::debug!(
-        shoulder = %parsed_ark.shoulder,
-        "ARK resolved"
+    tracing::info!(
+        registered_count = registered.len(),
+        already_registered_count = already_registered.len(),
+        "Register request completed successfully"
     );
 
-    // Create a 302 Found redirect
-    Ok((StatusCode::FOUND, [(header::LOCATION, target_url)]).into_response())
+    Ok(Json(RegisterResponse {
+        count: registered.len(),
+        registered,
+        already_registered,
+    }))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::shoulder::Shoulder;
-    use std::collections::HashMap;
+/// Why an ARK failed validation, for requests that record ARKs
+fn invalid_reason(result: validation::ValidationResult) -> String {
+    result
+        .error
+        .or_else(|| result.warnings.and_then(|w| w.into_iter().next()))
+        .unwrap_or_else(|| "not a valid ARK on a configured shoulder".to_string())
+}
 
-    fn create_test_state() -> Arc<AppState> {
-        let mut shoulders = HashMap::new();
-        shoulders.insert(
-            "x6".to_string(),
-            Shoulder {
-                route_pattern: "https://example.org/${value}".to_string(),
-                project_name: "Test Project".to_string(),
-                ..Default::default()
-            },
-        );
-        shoulders.insert(
-            "b3".to_string(),
-            Shoulder {
-                route_pattern: "https://beta.org/items/${value}".to_string(),
-                project_name: "Beta Project".to_string(),
-                uses_check_character: false,
+/// Record the identifiers of a NOID database dump or an EZID CSV download in the mint ledger,
+/// so minting never issues them again
+///
+/// Unlike registration, identifiers that are not valid ARKs on a configured shoulder are
+/// skipped and reported instead of failing the import, and there is no `MAX_MINT_COUNT` limit.
+/// Identifiers are recorded in chunks, so a failed import may be partly done; importing again
+/// completes it. Imported ARKs are not published as changes, since they were not created now.
+pub async fn import_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<Json<ImportResponse>, AppError> {
+    let found = import::identifiers(query.format, &body, state.naan.as_str())
+        .map_err(AppError::InvalidRequest)?;
+    drop(body);
+
+    let job_state = state.clone();
+    let response = state
+        .mint_queue
+        .run(move || {
+            let state = job_state;
+            let mut response = ImportResponse {
+                found: found.len(),
                 ..Default::default()
-            },
-        );
+            };
+            let mut arks = Vec::with_capacity(found.len());
+            for ImportedIdentifier { line, identifier } in found {
+                let result = validation::validate_ark(&state, &identifier, None);
+                let reason = if result.valid {
+                    let ark = parse_ark(&identifier).expect("valid ARKs parse");
+                    if ark.qualifier.is_empty() {
+                        arks.push(ark);
+                        continue;
+                    }
+                    "qualifiers cannot be imported".to_string()
+                } else {
+                    invalid_reason(result)
+                };
+                response.skipped += 1;
+                if response.errors.len() < MAX_IMPORT_ERRORS {
+                    response.errors.push(ImportError {
+                        line,
+                        identifier,
+                        reason,
+                    });
+                }
+            }
 
-        Arc::new(AppState {
-            naan: "12345".to_string(),
-            default_blade_length: 8,
-            max_mint_count: 1000,
-            shoulders,
+            for chunk in arks.chunks(IMPORT_CHUNK_SIZE) {
+                let (registered, already_registered) = minting::register_arks(&state, chunk)?;
+                response.imported += registered.len();
+                response.already_registered += already_registered.len();
+            }
+            Ok(response)
         })
+        .await?;
+
+    tracing::info!(
+        format = ?query.format,
+        found = response.found,
+        imported = response.imported,
+        already_registered = response.already_registered,
+        skipped = response.skipped,
+        "Import completed"
+    );
+    Ok(Json(response))
+}
+
+/// Stream the ARKs recorded in the mint ledger, with their mint times, as CSV or JSON Lines
+///
+/// Pages are read from the store as the client reads, so exports of any size use little memory.
+/// A storage failure before the first page is an error response; after it, the body is cut
+/// short. ARKs of private shoulders are left out.
+pub async fn export_arks_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    if let Some(shoulder) = &query.shoulder
+        && !state
+            .shoulders
+            .get(shoulder.as_str())
+            .is_some_and(|config| config.public)
+    {
+        return Err(AppError::ShoulderNotFound);
+    }
+    let first = ledger_page(&state, query.shoulder.clone(), None).await?;
+    tracing::info!(
+        shoulder = query.shoulder.as_deref().unwrap_or("*"),
+        format = ?query.format,
+        "Mint ledger export started"
+    );
+
+    // One page waits while the client reads the previous one
+    let content_type = query.format.content_type();
+    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    tokio::spawn(stream_export(state, query, first, sender));
+    Ok((
+        [(header::CONTENT_TYPE, content_type)],
+        Body::from_stream(ReceiverStream::new(receiver)),
+    )
+        .into_response())
+}
+
+/// Sends the export page by page, starting with `page`
+async fn stream_export(
+    state: Arc<AppState>,
+    query: ExportQuery,
+    mut page: LedgerPage,
+    sender: tokio::sync::mpsc::Sender<Result<String, std::io::Error>>,
+) {
+    let mut exported = 0;
+    let mut chunk = query.format.header().to_string();
+    loop {
+        for entry in &page.entries {
+            if state.is_public(&entry.shoulder) {
+                chunk.push_str(&query.format.line(entry));
+                exported += 1;
+            }
+        }
+        if !chunk.is_empty() && sender.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+            tracing::info!(
+                exported_count = exported,
+                "Mint ledger export client went away"
+            );
+            return;
+        }
+        let Some(cursor) = page.next.take() else {
+            break;
+        };
+        page = match ledger_page(&state, query.shoulder.clone(), Some(cursor)).await {
+            Ok(page) => page,
+            Err(error) => {
+                tracing::warn!(exported_count = exported, error = ?error, "Mint ledger export stopped");
+                let _ = sender
+                    .send(Err(std::io::Error::other("mint ledger unavailable")))
+                    .await;
+                return;
+            }
+        };
+    }
+    tracing::info!(
+        exported_count = exported,
+        "Mint ledger export completed successfully"
+    );
+}
+
+/// Reads a page of the mint ledger off the request threads
+async fn ledger_page(
+    state: &Arc<AppState>,
+    shoulder: Option<String>,
+    cursor: Option<String>,
+) -> Result<LedgerPage, AppError> {
+    let state = state.clone();
+    let page = tokio::task::spawn_blocking(move || {
+        state
+            .store
+            .entries(shoulder.as_deref(), cursor.as_deref(), EXPORT_PAGE_SIZE)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(page)
+}
+
+/// Apply binds, rebinds, status and metadata changes together, or none of them
+///
+/// Answers 422 with every operation's result when one fails, so a migration script can fix the
+/// batch and send it again as a whole.
+pub async fn batch_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BatchRequest>,
+) -> Result<(StatusCode, Json<BatchResponse>), AppError> {
+    if payload.operations.is_empty() {
+        return Err(AppError::InvalidRequest("No operations given".to_string()));
+    }
+    if payload.operations.len() > state.max_mint_count {
+        return Err(AppError::InvalidRequest(format!(
+            "At most {} operations can be run per batch",
+            state.max_mint_count
+        )));
+    }
+
+    let job_state = state.clone();
+    let outcome = state
+        .mint_queue
+        .run(move || batch::run_batch(&job_state, &payload.operations))
+        .await?;
+    for (ark, change) in outcome.changes {
+        state.event_log.record(ark, change);
+    }
+
+    let failed = outcome.results.iter().filter(|result| !result.ok).count();
+    tracing::info!(
+        operation_count = outcome.results.len(),
+        failed_count = failed,
+        applied = outcome.applied,
+        "Batch completed"
+    );
+
+    let status = if outcome.applied {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((
+        status,
+        Json(BatchResponse {
+            applied: outcome.applied,
+            results: outcome.results,
+        }),
+    ))
+}
+
+/// Make a reserved ARK public, or reinstate a withdrawn one
+pub async fn promote_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<StatusChangeRequest>,
+) -> Result<Json<StatusChangeResponse>, AppError> {
+    change_status(state, payload, ArkStatus::Active).await
+}
+
+/// Take an ARK out of service, answering 410 Gone until it is promoted again
+pub async fn withdraw_handler(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<StatusChangeRequest>,
+) -> Result<Json<StatusChangeResponse>, AppError> {
+    change_status(state, payload, ArkStatus::Withdrawn).await
+}
+
+/// Moves one ARK to `status` as a single-operation batch, with the same checks and records
+async fn change_status(
+    state: Arc<AppState>,
+    payload: StatusChangeRequest,
+    status: ArkStatus,
+) -> Result<Json<StatusChangeResponse>, AppError> {
+    let operations = [batch::BatchOperation::SetStatus {
+        ark: payload.ark,
+        status,
+        alias_of: None,
+        reason: payload.reason,
+    }];
+    let job_state = state.clone();
+    let mut outcome = state
+        .mint_queue
+        .run(move || batch::run_batch(&job_state, &operations))
+        .await?;
+    let result = outcome
+        .results
+        .pop()
+        .expect("a batch has a result per operation");
+    if !outcome.applied {
+        return Err(AppError::InvalidRequest(result.error.unwrap_or_default()));
+    }
+    for (ark, change) in outcome.changes {
+        state.event_log.record(ark, change);
+    }
+
+    let ark = result.ark.expect("applied operations have a valid ARK");
+    tracing::info!(ark = %ark, status = %status, "ARK status changed");
+    Ok(Json(StatusChangeResponse { ark, status }))
+}
+
+/// Report the namespace size and collision outlook of a shoulder
+pub async fn shoulder_capacity_handler(
+    State(state): State<Arc<AppState>>,
+    Path(shoulder): Path<String>,
+    Query(query): Query<CapacityQuery>,
+) -> Result<Json<ShoulderCapacity>, AppError> {
+    let (name, config) = state
+        .shoulders
+        .get_key_value(shoulder.as_str())
+        .filter(|(_, config)| config.public)
+        .ok_or(AppError::ShoulderNotFound)?;
+    let blade_length = state.blade_length_for(name, config);
+    let stats = state.mint_stats.shoulder(name).unwrap_or_default();
+    let next = query.next.unwrap_or(state.blade_advisor.policy().horizon);
+    let template = state.blade_template_for(config, blade_length);
+    let namespace_size = template.namespace_size();
+    // Sequential blades never collide
+    let (collision_probability, sequential_position) = match template.generator() {
+        Generator::Random => (
+            blade_advisor::collision_probability_in(stats.issued(), next, namespace_size),
+            None,
+        ),
+        Generator::Sequential | Generator::Unbounded => {
+            let partition = state.node_partition;
+            let local = state
+                .store
+                .sequence_position(&partition.sequence_key(name))?;
+            (0.0, partition.position(local))
+        }
+    };
+
+    Ok(Json(ShoulderCapacity {
+        shoulder: name.clone(),
+        blade_length,
+        alphabet_size: config.alphabet.characters().len(),
+        namespace_size,
+        minted: stats.minted,
+        registered: stats.registered,
+        next,
+        collision_probability,
+        sequential_position,
+    }))
+}
+
+/// Synthetic ARKs generated unless the request asks for another count
+const DEFAULT_EXAMPLE_COUNT: usize = 5;
+/// Most synthetic ARKs generated for one request
+const MAX_EXAMPLE_COUNT: usize = 50;
+
+/// Generate synthetic, unminted ARKs for a shoulder, with the targets they would resolve to
+pub async fn shoulder_examples_handler(
+    State(state): State<Arc<AppState>>,
+    Path(shoulder): Path<String>,
+    Query(query): Query<ExampleQuery>,
+) -> Result<Json<ShoulderExamples>, AppError> {
+    let (name, config) = state
+        .shoulders
+        .get_key_value(shoulder.as_str())
+        .filter(|(_, config)| config.public)
+        .ok_or(AppError::ShoulderNotFound)?;
+    let count = query
+        .count
+        .unwrap_or(DEFAULT_EXAMPLE_COUNT)
+        .min(MAX_EXAMPLE_COUNT);
+
+    let examples = minting::synthesize_arks(&state, name, count)?
+        .into_iter()
+        .map(|ark| {
+            let parsed = Ark::try_from(ark.as_str()).expect("synthetic ARKs always parse");
+            let (target, blocked) = match config.try_resolve(&parsed) {
+                Ok(target) => (target, None),
+                Err(blocked) => (blocked.target, Some(blocked.reason)),
+            };
+            ExampleArk {
+                ark,
+                target,
+                blocked,
+            }
+        })
+        .collect();
+
+    Ok(Json(ShoulderExamples {
+        shoulder: name.clone(),
+        examples,
+    }))
+}
+
+/// JSON Schema of a shoulder's configuration, as the shoulders of the admin API's configuration
+/// documents and `SHOULDERS` accept it
+pub async fn shoulder_schema_handler() -> Json<schemars::Schema> {
+    Json(schemars::schema_for!(Shoulder))
+}
+
+/// Breaks a freshly minted ARK into its components
+fn minted_ark_detail(state: &AppState, ark: String) -> MintedArkDetail {
+    let parsed = Ark::try_from(ark.as_str()).expect("minted ARKs always parse");
+    let blade = parsed.blade.replace('-', "");
+    let uses_check_character = state
+        .shoulders
+        .get(&parsed.shoulder)
+        .is_some_and(|shoulder| shoulder.check_character());
+    let check_character = uses_check_character
+        .then(|| blade.chars().last())
+        .flatten()
+        .map(String::from);
+    let resolver_url = state
+        .public_base_url
+        .as_ref()
+        .map(|base| format!("{}/{}", base, ark));
+
+    MintedArkDetail {
+        naan: state.naan.clone(),
+        shoulder: parsed.shoulder,
+        blade,
+        check_character,
+        resolver_url,
+        ark,
+    }
+}
+
+/// Report which ARKs are equivalent under RFC normalization, with the normalized forms
+pub async fn compare_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CompareRequest>,
+) -> Json<CompareResponse> {
+    let locale = Locale::from_headers(&headers);
+    let normalize = |input: &str| -> (NormalizedArk, Option<Ark>) {
+        // Oversized input is turned away before it is parsed
+        let checked = match state.input_limits.check_raw(input) {
+            Err(_) => Err(ValidationCode::TooLong),
+            Ok(()) => match parse_ark(input) {
+                None => Err(ValidationCode::Parse),
+                Some(ark) => state
+                    .input_limits
+                    .check_parsed(&ark)
+                    .map(|_| ark)
+                    .map_err(|_| ValidationCode::TooLong),
+            },
+        };
+        match checked {
+            Ok(ark) => {
+                let (normalized, transformations) = explain_normalization(input);
+                let result = NormalizedArk {
+                    ark: input.to_string(),
+                    valid: true,
+                    normalized: Some(normalized),
+                    transformations,
+                    error: None,
+                    error_code: None,
+                };
+                (result, Some(ark))
+            }
+            Err(code) => {
+                let result = NormalizedArk {
+                    ark: input.to_string(),
+                    valid: false,
+                    normalized: None,
+                    transformations: Vec::new(),
+                    error: Some(code.message(locale)),
+                    error_code: Some(code),
+                };
+                (result, None)
+            }
+        }
+    };
+
+    let mut results = Vec::with_capacity(payload.arks.len());
+    let mut groups: Vec<EquivalenceGroup> = Vec::new();
+    let mut group_of: HashMap<String, usize> = HashMap::new();
+    for (index, input) in payload.arks.iter().enumerate() {
+        let (result, parsed) = normalize(input);
+        if let Some(ark) = parsed {
+            let group = *group_of
+                .entry(ark.canonical().to_string())
+                .or_insert_with(|| {
+                    groups.push(EquivalenceGroup {
+                        normalized: ark.canonical().to_string(),
+                        indices: Vec::new(),
+                        arks: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+            groups[group].indices.push(index);
+            groups[group].arks.push(input.clone());
+        }
+        results.push(result);
+    }
+
+    let pairs = payload
+        .pairs
+        .iter()
+        .map(|(left, right)| {
+            let (left, left_ark) = normalize(left);
+            let (right, right_ark) = normalize(right);
+            PairComparison {
+                equivalent: left_ark.is_some() && left_ark == right_ark,
+                left,
+                right,
+            }
+        })
+        .collect();
+
+    Json(CompareResponse {
+        results,
+        groups,
+        pairs,
+    })
+}
+
+pub async fn validate_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<ValidateRequest>,
+) -> Json<ValidateResponse> {
+    let locale = Locale::from_headers(&headers);
+    let truncated = payload.arks.len() > state.max_validate_count;
+    if truncated {
+        tracing::warn!(
+            requested_count = payload.arks.len(),
+            max_validate_count = state.max_validate_count,
+            "Validate request exceeded maximum, batch truncated"
+        );
+    }
+
+    let mut arks = payload.arks;
+    arks.truncate(state.max_validate_count);
+    let (has_check_character, debug) = (payload.has_check_character, payload.debug);
+    let parallel = state.validation_pool.is_parallel(arks.len());
+    let started = Instant::now();
+    let worker_state = state.clone();
+    let results: Vec<ArkValidationResult> = state
+        .validation_pool
+        .map(arks, move |ark| {
+            let started = Instant::now();
+            let result =
+                validation::validate_ark_localized(&worker_state, ark, has_check_character, locale);
+
+            ArkValidationResult {
+                ark: ark.clone(),
+                valid: result.valid,
+                naan: result.naan,
+                shoulder: result.shoulder,
+                blade: result.blade,
+                shoulder_registered: result.shoulder_registered,
+                has_check_character: result.has_check_character,
+                check_character_valid: result.check_character_valid,
+                error: result.error,
+                error_code: result.error_code,
+                warnings: result.warnings,
+                warning_codes: result.warning_codes,
+                component_of: result.component_of,
+                elapsed_us: debug.then(|| started.elapsed().as_micros() as u64),
+            }
+        })
+        .await;
+    let timing = debug.then(|| ValidationTiming {
+        elapsed_us: started.elapsed().as_micros() as u64,
+        parallel,
+        threads: state.validation_pool.threads(),
+    });
+
+    let valid_count = results.iter().filter(|r| r.valid).count();
+    let invalid_count = results.len() - valid_count;
+
+    if invalid_count > 0 {
+        tracing::info!(
+            total = results.len(),
+            valid = valid_count,
+            invalid = invalid_count,
+            "Validation completed with failures"
+        );
+    } else {
+        tracing::debug!(total = results.len(), "Validation completed - all valid");
+    }
+
+    Json(ValidateResponse {
+        results,
+        truncated,
+        timing,
+    })
+}
+
+/// Sends ARKs whose label is not lowercase (`/ARK:…`, `/Ark:…`) to the resolver, since routes
+/// match case-sensitively; any other unmatched path is not found
+pub async fn fallback_handler(
+    state: State<Arc<AppState>>,
+    method: Method,
+    headers: HeaderMap,
+    uri: OriginalUri,
+    deadline: Option<Extension<Deadline>>,
+) -> Result<Response, AppError> {
+    if uri.path().strip_prefix('/').and_then(strip_label).is_none() {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+    resolve_handler(state, method, headers, uri, deadline).await
+}
+
+pub async fn resolve_handler(
+    State(state): State<Arc<AppState>>,
+    method: Method,
+    headers: HeaderMap,
+    OriginalUri(uri): OriginalUri,
+    deadline: Option<Extension<Deadline>>,
+) -> Result<Response, AppError> {
+    let deadline = deadline.map(|Extension(deadline)| deadline);
+    // The service status ARK is matched here rather than in the router, so every spelling of
+    // the label reaches it
+    if is_service_status_path(&state.naan, uri.path()) {
+        if method != Method::GET && method != Method::HEAD {
+            return Err(AppError::MethodNotAllowed);
+        }
+        return Ok(health_check_handler(State(state), headers).await);
+    }
+
+    // Extract path and query from URI: /ark:12345/x6test?info -> ark:12345/x6test?info
+    let path_and_query = uri.path_and_query().ok_or(AppError::InvalidArk)?.as_str();
+    state.input_limits.check_raw(path_and_query)?;
+    let (path_and_query, view) = take_resolution_override(path_and_query);
+    let view = view.or_else(|| negotiated_view(&headers));
+
+    // Remove leading /ark: (in any case) to get just the ARK identifier
+    let ark_string = path_and_query
+        .strip_prefix('/')
+        .and_then(strip_label)
+        .ok_or(AppError::InvalidArk)?;
+
+    // Accept the old-style /ark:/NAAN/... shape, including when a proxy has
+    // percent-encoded the slash after the label
+    let ark_string = ["%2F", "%2f"]
+        .iter()
+        .find_map(|encoded| ark_string.strip_prefix(encoded))
+        .map_or_else(|| ark_string.to_string(), |rest| format!("/{}", rest));
+
+    let ark_string = format!("ark:{}", ark_string);
+    // ARKs copied from prose often carry the sentence's punctuation; unless the exact ARK has
+    // a binding of its own, resolve it without
+    let (ark_string, punctuation_stripped) = match strip_prose_punctuation(&ark_string) {
+        Some(stripped) if !has_own_binding(&state, deadline, &ark_string).await? => {
+            tracing::info!(requested = %ark_string, resolved = %stripped, "Stripped trailing punctuation from ARK");
+            (stripped.to_string(), true)
+        }
+        _ => (ark_string, false),
+    };
+    // Parse the full ARK string (e.g., "ark:12345/x6np1wh8k/page2.pdf?info")
+    let parsed_ark = Ark::try_from(ark_string.as_str())?;
+    state.input_limits.check_parsed(&parsed_ark)?;
+
+    // Check NAAN matches
+    if parsed_ark.naan != state.naan {
+        return Err(AppError::InvalidNaan);
+    }
+
+    // Look up routing rule
+    let Some(shoulder_config) = state.shoulders.get(&parsed_ark.shoulder) else {
+        if state.unregistered_inflections == UnregisteredInflections::Stub
+            && (method == Method::GET || method == Method::HEAD)
+            && parsed_ark.inflection().is_some()
+        {
+            return Ok(unknown_namespace_stub(&parsed_ark));
+        }
+        return Err(AppError::ShoulderNotFound);
+    };
+    if shoulder_config.require_auth
+        && let Err(error) = auth::authenticate_resolver(&state, &headers)
+    {
+        return Ok(auth::challenge(error, auth::RESOLVER_REALM));
+    }
+
+    // Components count towards their shoulder's resolutions, and separately as components
+    let component = shoulder_config.is_component(&parsed_ark.without_inflection());
+    // Stripped ARKs are counted once they resolve
+    let record_resolution = || {
+        if punctuation_stripped {
+            state.input_limits.record_punctuation_stripped();
+        }
+        if component {
+            state
+                .resolution_stats
+                .record_component(&parsed_ark.shoulder);
+        } else {
+            state.resolution_stats.record(&parsed_ark.shoulder);
+        }
+    };
+
+    // Resolve ARK using its binding or the shoulder's routing configuration; views describe
+    // the redirect. While the binding store is down, the routing configuration still answers.
+    // The status of the ARK applies to every request for it, qualified or not. Components may
+    // have a binding of their own; other qualified ARKs do not.
+    let (base_binding, degraded) = match lookup_binding(&state, deadline, &parsed_ark.base())
+        .await?
+    {
+        Ok(binding) => (binding, false),
+        Err(e) => {
+            tracing::warn!(error = %e, ark = %parsed_ark.original, "Resolving from route pattern");
+            (None, true)
+        }
+    };
+    let (component_binding, degraded) = if !degraded
+        && !parsed_ark.qualifier.is_empty()
+        && shoulder_config.is_component(&parsed_ark)
+    {
+        match lookup_binding(&state, deadline, &parsed_ark).await? {
+            Ok(binding) => (binding, false),
+            Err(e) => {
+                tracing::warn!(error = %e, ark = %parsed_ark.original, "Resolving from route pattern");
+                (None, true)
+            }
+        }
+    } else {
+        (None, degraded)
+    };
+    // An alias of the whole object keeps the qualifier; a component's own alias replaces it
+    let qualifier = if parsed_ark.qualifier.is_empty() || parsed_ark.qualifier.starts_with('?') {
+        parsed_ark.qualifier.clone()
+    } else {
+        format!("/{}", parsed_ark.qualifier)
+    };
+    let gates = [
+        (base_binding.as_ref(), qualifier.as_str()),
+        (component_binding.as_ref(), ""),
+    ];
+    for (binding, qualifier) in gates {
+        let Some(binding) = binding else { continue };
+        match binding.lifecycle.status {
+            ArkStatus::Active => {}
+            ArkStatus::Reserved => return Err(AppError::NotFound("ARK".to_string())),
+            ArkStatus::Withdrawn | ArkStatus::Tombstoned => return Err(AppError::Withdrawn),
+            ArkStatus::Alias => {
+                let alias_of = binding.lifecycle.alias_of.as_deref().unwrap_or_default();
+                record_resolution();
+                let response = with_shoulder_headers(
+                    alias_redirect(&format!("{}{}", alias_of, qualifier)),
+                    shoulder_config,
+                );
+                return Ok(mark_degraded(response, degraded));
+            }
+        }
+    }
+    let binding = if parsed_ark.qualifier.is_empty() {
+        base_binding
+    } else {
+        component_binding
+    };
+    let outcome = match (binding, view) {
+        (Some(binding), _) => shoulder_config.resolve_bound(&parsed_ark, &binding.target, &method),
+        (None, Some(_)) => shoulder_config.redirect(&parsed_ark, &method),
+        (None, None) => shoulder_config.resolve(&parsed_ark, &method),
+    };
+    let (target_url, status) = match outcome {
+        ResolutionOutcome::Redirect { url, status } => (url, status),
+        ResolutionOutcome::Blocked(blocked) => {
+            return Err(report_blocked_redirect(
+                &state,
+                shoulder_config,
+                &parsed_ark,
+                blocked,
+            ));
+        }
+        // Delegated NMA mode: answer inflections with the upstream's response
+        ResolutionOutcome::LandingPage { source, target } => {
+            record_resolution();
+            let response = forward_inflection(
+                &state,
+                shoulder_config,
+                &parsed_ark,
+                &source,
+                target,
+                deadline,
+            )
+            .await?;
+            let response = with_shoulder_headers(response, shoulder_config);
+            return Ok(mark_degraded(response, degraded));
+        }
+        ResolutionOutcome::MethodNotAllowed => return Err(AppError::MethodNotAllowed),
+        ResolutionOutcome::NotFound => return Err(AppError::ShoulderNotFound),
+    };
+
+    tracing::debug!(
+        shoulder = %parsed_ark.shoulder,
+        "ARK resolved"
+    );
+    record_resolution();
+
+    let response = resolved_response(
+        &state,
+        shoulder_config,
+        parsed_ark,
+        view,
+        target_url,
+        status,
+    );
+    let response = with_shoulder_headers(response, shoulder_config);
+    Ok(mark_degraded(response, degraded))
+}
+
+/// Redirect from an alias to the ARK it stands in for, on this resolver
+fn alias_redirect(alias_of: &str) -> Response {
+    (
+        StatusCode::FOUND,
+        [(header::LOCATION, format!("/{}", alias_of))],
+    )
+        .into_response()
+}
+
+/// Answer to an inflection on an ARK whose shoulder is not registered, from its structure alone
+fn unknown_namespace_stub(parsed_ark: &Ark) -> Response {
+    tracing::debug!(ark = %parsed_ark.original, "Inflection on unregistered shoulder answered with stub");
+    let ark = parsed_ark.without_inflection();
+    Json(UnknownNamespaceStub {
+        ark: ark.original,
+        naan: ark.naan,
+        shoulder: ark.shoulder,
+        blade: ark.blade,
+        qualifier: ark.qualifier,
+        status: "unknown_namespace",
+    })
+    .into_response()
+}
+
+/// Adds the shoulder's configured `response_headers` to a resolution response
+fn with_shoulder_headers(mut response: Response, shoulder: &Shoulder) -> Response {
+    for (name, value) in &shoulder.response_header_map() {
+        response.headers_mut().append(name.clone(), value.clone());
+    }
+    response
+}
+
+/// Header telling clients a resolution was answered without part of the service's storage
+const DEGRADED_HEADER: &str = "x-ark-degraded";
+
+/// Flags a response served from the route pattern because bindings were unavailable
+fn mark_degraded(mut response: Response, degraded: bool) -> Response {
+    if degraded {
+        response.headers_mut().insert(
+            header::HeaderName::from_static(DEGRADED_HEADER),
+            header::HeaderValue::from_static("bindings-unavailable"),
+        );
+    }
+    response
+}
+
+/// The redirect for a resolved ARK, or the view of it the client asked for
+fn resolved_response(
+    state: &AppState,
+    shoulder_config: &Shoulder,
+    parsed_ark: Ark,
+    view: Option<ResolutionView>,
+    target_url: String,
+    status: StatusCode,
+) -> Response {
+    if view == Some(ResolutionView::DublinCore) {
+        let record = DublinCore::describe(
+            &parsed_ark,
+            shoulder_config,
+            state.ark_format_for(shoulder_config),
+            state.public_base_url.as_deref(),
+            &target_url,
+        );
+        return (
+            [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+            record.to_oai_dc_xml(),
+        )
+            .into_response();
+    }
+
+    if view == Some(ResolutionView::Turtle) {
+        let format = state.ark_format_for(shoulder_config);
+        let identifier = format.format_canonical(&parsed_ark);
+        let resource = ArkResource {
+            subject: state.public_base_url.as_ref().map_or_else(
+                || identifier.clone(),
+                |base| format!("{}/{}", base, identifier),
+            ),
+            identifier,
+            target: target_url,
+            status: "active",
+            project_name: shoulder_config.project_name.clone(),
+            publisher: shoulder_config.contact.as_ref().map(|contact| {
+                contact
+                    .organization
+                    .clone()
+                    .unwrap_or_else(|| contact.name.clone())
+            }),
+            provenance: state.config_store.provenance(&parsed_ark.shoulder),
+        };
+        return (
+            [(header::CONTENT_TYPE, "text/turtle; charset=utf-8")],
+            resource.to_turtle(),
+        )
+            .into_response();
+    }
+
+    if view == Some(ResolutionView::Description) {
+        return Json(ResolutionResponse {
+            ark: parsed_ark.original,
+            naan: parsed_ark.naan,
+            shoulder: parsed_ark.shoulder,
+            blade: parsed_ark.blade,
+            qualifier: parsed_ark.qualifier,
+            project_name: shoulder_config.project_name.clone(),
+            target: target_url,
+            redirect_status: status.as_u16(),
+        })
+        .into_response();
+    }
+
+    let mut response = (status, [(header::LOCATION, target_url)]).into_response();
+
+    if let Some(link) = canonical_link(state, shoulder_config, &parsed_ark) {
+        response.headers_mut().insert(header::LINK, link);
+    }
+
+    // Point at the delegated metadata record, except when already redirecting there
+    if parsed_ark.inflection().is_none()
+        && let Some(link) = shoulder_config.metadata_url(&parsed_ark).and_then(|url| {
+            header::HeaderValue::from_str(&format!("<{}>; rel=\"describedby\"", url)).ok()
+        })
+    {
+        response.headers_mut().append(header::LINK, link);
+    }
+
+    if shoulder_config.noindex {
+        response.headers_mut().insert(
+            header::HeaderName::from_static("x-robots-tag"),
+            header::HeaderValue::from_static("noindex"),
+        );
+    }
+
+    response
+}
+
+/// What a resolution request asks for instead of a redirect
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ResolutionView {
+    /// JSON description of the resolution
+    Description,
+    /// `oai_dc` Dublin Core record
+    DublinCore,
+    /// RDF description in Turtle
+    Turtle,
+}
+
+/// Characters a sentence can leave at the end of an ARK copied from it
+const PROSE_PUNCTUATION: [char; 5] = ['.', ',', ')', ';', ']'];
+
+/// The ARK without the trailing punctuation of the sentence it was copied from, if it has any
+///
+/// A closing bracket that closes one opened within the ARK is kept, so qualifiers such as
+/// `page(2)` survive. ARKs with a query string are left alone.
+fn strip_prose_punctuation(ark: &str) -> Option<&str> {
+    if ark.contains('?') {
+        return None;
+    }
+    let mut stripped = ark;
+    while let Some(last) = stripped.chars().next_back() {
+        let rest = &stripped[..stripped.len() - last.len_utf8()];
+        let closes_own_bracket = match last {
+            ')' => rest.matches('(').count() > rest.matches(')').count(),
+            ']' => rest.matches('[').count() > rest.matches(']').count(),
+            _ => false,
+        };
+        if !PROSE_PUNCTUATION.contains(&last) || closes_own_bracket {
+            break;
+        }
+        stripped = rest;
+    }
+    (stripped.len() < ark.len()).then_some(stripped)
+}
+
+/// Whether `ark` parses and is bound to a target itself; storage errors count as unbound
+async fn has_own_binding(
+    state: &Arc<AppState>,
+    deadline: Option<Deadline>,
+    ark: &str,
+) -> Result<bool, AppError> {
+    let Ok(ark) = Ark::try_from(ark) else {
+        return Ok(false);
+    };
+    Ok(matches!(
+        lookup_binding(state, deadline, &ark).await?,
+        Ok(Some(_))
+    ))
+}
+
+/// The binding of an ARK, given up on with `504 Gateway Timeout` once the deadline passes
+async fn lookup_binding(
+    state: &Arc<AppState>,
+    deadline: Option<Deadline>,
+    ark: &Ark,
+) -> Result<Result<Option<Binding>, StorageError>, AppError> {
+    let original = ark.original.clone();
+    let (state, ark) = (state.clone(), ark.clone());
+    deadline::run_blocking(deadline, move || state.bindings.get(&ark))
+        .await
+        .map_err(|_| {
+            tracing::warn!(ark = %original, "Binding lookup missed the request deadline");
+            AppError::DeadlineExceeded
+        })
+}
+
+/// Whether `path` is the `/ark:{naan}/servicestatus` ARK for `naan`
+pub(super) fn is_service_status_path(naan: &str, path: &str) -> bool {
+    path.strip_prefix('/')
+        .and_then(strip_label)
+        .and_then(|rest| rest.strip_suffix("/servicestatus"))
+        .is_some_and(|requested| requested.eq_ignore_ascii_case(naan))
+}
+
+/// Query parameters that ask for a view instead of a redirect
+const VIEW_PARAMS: [(&str, ResolutionView); 3] = [
+    ("_format=json", ResolutionView::Description),
+    ("_redirect=false", ResolutionView::Description),
+    ("format=oai_dc", ResolutionView::DublinCore),
+];
+
+/// Splits the resolution override parameters off the query string
+///
+/// Returns the remaining path and query, whose query string is forwarded to the target as part
+/// of the qualifier, and the requested view, if any. Only the exact parameters in
+/// [`VIEW_PARAMS`] are consumed; anything else (including `_format=xml`) is left in place.
+fn take_resolution_override(path_and_query: &str) -> (String, Option<ResolutionView>) {
+    let Some((path, query)) = path_and_query.split_once('?') else {
+        return (path_and_query.to_string(), None);
+    };
+
+    let mut view = None;
+    let remaining: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let requested = VIEW_PARAMS
+                .iter()
+                .find(|(param, _)| param == pair)
+                .map(|(_, view)| *view);
+            view = view.or(requested);
+            requested.is_none()
+        })
+        .collect();
+
+    if view.is_none() {
+        // Leave ARK inflections such as a bare `?` or `??` untouched
+        return (path_and_query.to_string(), None);
+    }
+    match remaining.join("&") {
+        query if query.is_empty() => (path.to_string(), view),
+        query => (format!("{}?{}", path, query), view),
+    }
+}
+
+/// The view the client asks for via `Accept`, if it prefers one over HTML
+///
+/// Browsers list `application/xml` with a lower quality than `text/html`, so they keep
+/// getting redirects.
+fn negotiated_view(headers: &HeaderMap) -> Option<ResolutionView> {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())?;
+
+    let (mut xml, mut turtle, mut html) = (0.0f32, 0.0f32, 0.0f32);
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let media_type = params.next().unwrap_or_default().trim();
+        let quality = params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match media_type {
+            "application/xml" | "text/xml" => xml = xml.max(quality),
+            "text/turtle" => turtle = turtle.max(quality),
+            "text/html" | "application/xhtml+xml" => html = html.max(quality),
+            _ => {}
+        }
+    }
+
+    let (view, quality) = if turtle >= xml {
+        (ResolutionView::Turtle, turtle)
+    } else {
+        (ResolutionView::DublinCore, xml)
+    };
+    (quality > 0.0 && quality > html).then_some(view)
+}
+
+/// Builds the `Link` header pointing at the normalized form of the ARK on this resolver
+fn canonical_link(
+    state: &AppState,
+    shoulder: &Shoulder,
+    parsed_ark: &Ark,
+) -> Option<header::HeaderValue> {
+    let rel = state.canonical_link_rel.as_deref()?;
+    let base_url = state.public_base_url.as_deref()?;
+    let canonical = state.ark_format_for(shoulder).format_canonical(parsed_ark);
+
+    header::HeaderValue::from_str(&format!("<{}/{}>; rel=\"{}\"", base_url, canonical, rel)).ok()
+}
+
+/// Relays the upstream answer to an inflection request, or answers with a local ERC record
+/// if the upstream fails
+async fn forward_inflection(
+    state: &Arc<AppState>,
+    shoulder: &Shoulder,
+    parsed_ark: &Ark,
+    upstream: &str,
+    target: Option<String>,
+    deadline: Option<Deadline>,
+) -> Result<Response, AppError> {
+    match state.inflections.fetch(upstream, deadline).await {
+        Ok(record) => {
+            tracing::debug!(upstream = %upstream, "Inflection answered upstream");
+            Ok(([(header::CONTENT_TYPE, record.content_type)], record.body).into_response())
+        }
+        // With the request's time used up, a local answer would come too late as well
+        Err(e) if deadline.is_some_and(|deadline| deadline.is_expired()) => {
+            tracing::warn!(
+                shoulder = %parsed_ark.shoulder,
+                upstream = %upstream,
+                error = %e,
+                "Inflection upstream missed the request deadline"
+            );
+            Err(AppError::DeadlineExceeded)
+        }
+        Err(e) => {
+            tracing::warn!(
+                shoulder = %parsed_ark.shoulder,
+                upstream = %upstream,
+                error = %e,
+                "Inflection upstream failed, answering locally"
+            );
+            let who = shoulder
+                .contact
+                .as_ref()
+                .map(|contact| contact.organization.as_deref().unwrap_or(&contact.name));
+            let record = erc_record(who, &shoulder.project_name, target.as_deref());
+            Ok((
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                record,
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Quarantines a blocked redirect and tells the people responsible for the shoulder
+fn report_blocked_redirect(
+    state: &Arc<AppState>,
+    shoulder: &Shoulder,
+    parsed_ark: &Ark,
+    blocked: BlockedTarget,
+) -> AppError {
+    // Inflections are routed by the metadata pattern, when the shoulder has one
+    let route_pattern = shoulder
+        .metadata_route_pattern
+        .as_ref()
+        .filter(|_| parsed_ark.inflection().is_some())
+        .map_or_else(
+            || shoulder.effective_route_pattern().into_owned(),
+            Clone::clone,
+        );
+    let entry = state.quarantine.record(BlockedResolution {
+        shoulder: parsed_ark.shoulder.clone(),
+        route_pattern,
+        reason: blocked.reason.clone(),
+        ark: parsed_ark.original.clone(),
+        target: blocked.target,
+    });
+    tracing::debug!(
+        quarantine_id = entry.id,
+        occurrences = entry.occurrences,
+        "Blocked redirect quarantined"
+    );
+
+    alert_blocked_redirect(state, parsed_ark, &blocked.reason);
+    if let Some(callback) = &shoulder.failure_callback {
+        state.failure_callbacks.report(
+            &parsed_ark.shoulder,
+            callback,
+            ResolutionFailure {
+                ark: parsed_ark.original.clone(),
+                kind: FailureKind::BlockedRedirect,
+                reason: blocked.reason,
+                at: Utc::now(),
+            },
+        );
+    }
+
+    AppError::BlockedRedirect
+}
+
+/// Emails the shoulder's contact about a blocked redirect without delaying the response
+fn alert_blocked_redirect(state: &Arc<AppState>, parsed_ark: &Ark, reason: &str) {
+    let alert = Alert::BlockedRedirect {
+        naan: state.naan.clone(),
+        shoulder: parsed_ark.shoulder.clone(),
+        ark: parsed_ark.original.clone(),
+        reason: reason.to_string(),
+    };
+    let state = state.clone();
+    let shoulder = parsed_ark.shoulder.clone();
+
+    tokio::spawn(async move {
+        let contact = state
+            .shoulders
+            .get(&shoulder)
+            .and_then(|s| s.contact.as_ref())
+            .and_then(|c| c.email.as_deref());
+        state.notifier.notify(alert, contact).await;
+    });
+}
+
+/// Exports the live configuration as a canonical document, including provenance
+pub async fn export_config_handler(State(state): State<Arc<AppState>>) -> Response {
+    let document = live_config_document(&state);
+
+    tracing::debug!(
+        shoulder_count = document.shoulders.len(),
+        "Configuration exported"
+    );
+
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        document.to_canonical_json(),
+    )
+        .into_response()
+}
+
+/// Stages an imported configuration document for review without applying it
+pub async fn import_config_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(document): Json<ConfigDocument>,
+) -> Result<Json<StagedConfig>, AppError> {
+    document.validate().map_err(AppError::InvalidConfig)?;
+
+    let changes = live_config_document(&state).diff(&document);
+    let staged = StagedConfig {
+        document,
+        staged_by: admin,
+        staged_at: Utc::now(),
+        changes,
+    };
+
+    tracing::info!(
+        admin = %staged.staged_by,
+        change_count = staged.changes.len(),
+        "Configuration document staged"
+    );
+
+    state.config_store.stage(staged.clone());
+    Ok(Json(staged))
+}
+
+/// Returns the currently staged configuration document
+pub async fn staged_config_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<StagedConfig>, AppError> {
+    state
+        .config_store
+        .staged()
+        .map(Json)
+        .ok_or(AppError::NoStagedConfig)
+}
+
+/// Discards the currently staged configuration document
+pub async fn discard_staged_config_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+) -> Result<StatusCode, AppError> {
+    if !state.config_store.discard_staged() {
+        return Err(AppError::NoStagedConfig);
+    }
+
+    tracing::info!(admin = %admin, "Staged configuration discarded");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reports the log filter in effect
+pub async fn log_level_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<LogLevelResponse>, AppError> {
+    let filter = state
+        .log_level
+        .current()
+        .map_err(AppError::InvalidRequest)?;
+    Ok(Json(LogLevelResponse { filter }))
+}
+
+/// Replaces the log filter, e.g. to turn on debug logging for one module while diagnosing it
+pub async fn set_log_level_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(payload): Json<LogFilter>,
+) -> Result<Json<LogLevelResponse>, AppError> {
+    let filter = state
+        .log_level
+        .set(&payload)
+        .map_err(AppError::InvalidRequest)?;
+
+    tracing::warn!(admin = %admin, filter = %filter, "Log filter changed");
+    Ok(Json(LogLevelResponse { filter }))
+}
+
+/// Restores the log filter the service started with
+pub async fn reset_log_level_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+) -> Result<Json<LogLevelResponse>, AppError> {
+    let filter = state.log_level.reset().map_err(AppError::InvalidRequest)?;
+
+    tracing::warn!(admin = %admin, filter = %filter, "Log filter reset");
+    Ok(Json(LogLevelResponse { filter }))
+}
+
+/// Reports whether the service is read-only
+pub async fn read_only_handler(State(state): State<Arc<AppState>>) -> Json<ReadOnlyResponse> {
+    Json(read_only_response(state.read_only.status()))
+}
+
+/// Makes the service read-only, e.g. before a storage migration or failover: minting and
+/// changes to ARKs are refused until it is turned off, while resolution carries on
+pub async fn enable_read_only_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    payload: Option<Json<EnableReadOnlyRequest>>,
+) -> Json<ReadOnlyResponse> {
+    let Json(payload) = payload.unwrap_or_default();
+    let reason = payload.reason.filter(|reason| !reason.trim().is_empty());
+    let status = state.read_only.enable(reason, &admin);
+
+    tracing::warn!(
+        admin = %admin,
+        reason = status.reason.as_deref().unwrap_or_default(),
+        "Read-only mode enabled"
+    );
+    Json(read_only_response(Some(status)))
+}
+
+/// Ends read-only mode
+pub async fn disable_read_only_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+) -> Json<ReadOnlyResponse> {
+    if let Some(status) = state.read_only.disable() {
+        tracing::warn!(
+            admin = %admin,
+            since = %status.since,
+            "Read-only mode disabled"
+        );
+    }
+    Json(read_only_response(None))
+}
+
+fn read_only_response(status: Option<ReadOnlyStatus>) -> ReadOnlyResponse {
+    ReadOnlyResponse {
+        read_only: status.is_some(),
+        status,
+    }
+}
+
+/// The snapshot directory, or 404 while snapshots are not configured
+fn snapshot_dir(state: &AppState) -> Result<&SnapshotDir, AppError> {
+    state
+        .snapshots
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Snapshots are disabled".to_string()))
+}
+
+/// Runs `job` on a blocking thread with writes paused: read-only mode is turned on unless it
+/// already is, and writes in progress get the snapshot drain timeout to finish
+async fn with_writes_paused<T: Send + 'static>(
+    state: &Arc<AppState>,
+    admin: &str,
+    reason: String,
+    job: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    let timeout = snapshot_dir(state)?.drain_timeout();
+    let paused =
+        (!state.read_only.is_enabled()).then(|| state.read_only.enable(Some(reason), admin));
+    let result = if state.read_only.drain(timeout).await {
+        tokio::task::spawn_blocking(job)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+            .and_then(|result| result)
+    } else {
+        Err(AppError::StorageUnavailable(format!(
+            "writes in progress did not finish within {} seconds",
+            timeout.as_secs()
+        )))
+    };
+    // Left on if someone else changed it meanwhile
+    if let Some(paused) = paused {
+        state.read_only.disable_if(&paused);
+    }
+    result
+}
+
+/// Lists the snapshots taken so far, oldest first
+pub async fn snapshots_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SnapshotsResponse>, AppError> {
+    snapshot_dir(&state)?;
+    let snapshots = tokio::task::spawn_blocking(move || {
+        snapshot_dir(&state)?
+            .list()
+            .map_err(|e| AppError::Internal(e.to_string()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+    Ok(Json(SnapshotsResponse { snapshots }))
+}
+
+/// Takes a consistent snapshot of the ledger, bindings, sequential counters and configuration,
+/// with writes paused while it is read
+pub async fn take_snapshot_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    payload: Option<Json<TakeSnapshotRequest>>,
+) -> Result<(StatusCode, Json<SnapshotSummary>), AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let name = payload
+        .name
+        .unwrap_or_else(|| Utc::now().format("%Y%m%dT%H%M%SZ").to_string());
+    if !snapshot::is_valid_name(&name) {
+        return Err(AppError::InvalidRequest(
+            "Snapshot names are up to 64 letters, digits, '-', '_' and '.', not starting with '.'"
+                .to_string(),
+        ));
+    }
+    if snapshot_dir(&state)?.contains(&name) {
+        return Err(AppError::InvalidRequest(format!(
+            "Snapshot {} already exists",
+            name
+        )));
+    }
+
+    let config = live_config_document(&state);
+    let job_state = state.clone();
+    let taken_by = admin.clone();
+    let reason = format!("Taking snapshot {}", name);
+    let summary = with_writes_paused(&state, &admin, reason, move || {
+        let snapshot = Snapshot::capture(&job_state, name, &taken_by, config)?;
+        snapshot_dir(&job_state)?
+            .save(&snapshot)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        Ok(snapshot.summary().clone())
+    })
+    .await?;
+
+    tracing::info!(
+        admin = %admin,
+        snapshot = %summary.name,
+        minted = summary.minted,
+        bindings = summary.bindings,
+        "Snapshot taken"
+    );
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+/// Restores the registry to a snapshot, or to a later time using the event log, and stages the
+/// snapshot's configuration; only reports what would change unless `dry_run` is `false`
+pub async fn restore_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(payload): Json<RestoreRequest>,
+) -> Result<Json<RestoreReport>, AppError> {
+    snapshot_dir(&state)?;
+    if payload.at.is_some_and(|at| at > Utc::now()) {
+        return Err(AppError::InvalidRequest(
+            "Cannot restore to a time in the future".to_string(),
+        ));
+    }
+    let dry_run = payload.dry_run;
+    let job_state = state.clone();
+    let job = move || {
+        let dir = snapshot_dir(&job_state)?;
+        let name = match (payload.snapshot, payload.at) {
+            (Some(name), _) => name,
+            (None, Some(at)) => {
+                dir.latest_at(at)
+                    .map_err(|e| AppError::Internal(e.to_string()))?
+                    .ok_or_else(|| AppError::NotFound(format!("Snapshot taken by {}", at)))?
+                    .name
+            }
+            (None, None) => {
+                return Err(AppError::InvalidRequest(
+                    "Give a snapshot, a time to restore to, or both".to_string(),
+                ));
+            }
+        };
+        let snapshot = dir
+            .load(&name)
+            .map_err(|e| AppError::Internal(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Snapshot {}", name)))?;
+        let taken_at = snapshot.summary().taken_at;
+        let at = payload.at.unwrap_or(taken_at);
+        if at < taken_at {
+            return Err(AppError::InvalidRequest(format!(
+                "Snapshot {} was taken after {}",
+                name, at
+            )));
+        }
+        if at > taken_at && job_state.event_log.complete_since() > taken_at {
+            return Err(AppError::InvalidRequest(format!(
+                "The event log no longer reaches back to snapshot {}; restore to the snapshot itself",
+                name
+            )));
+        }
+        let events = job_state.event_log.between(Some(taken_at), None);
+        let plan = snapshot.plan_restore(&job_state, at, &events)?;
+        if !dry_run {
+            plan.apply(&job_state)?;
+        }
+        Ok((snapshot, plan, at))
+    };
+    let (snapshot, plan, at) = if dry_run {
+        tokio::task::spawn_blocking(job)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))??
+    } else {
+        with_writes_paused(&state, &admin, "Restoring a snapshot".to_string(), job).await?
+    };
+
+    let config_changes = live_config_document(&state).diff(snapshot.config());
+    let report = plan.report(snapshot.summary(), at, dry_run, config_changes.clone());
+    if dry_run {
+        return Ok(Json(report));
+    }
+    for ark in plan.rebound() {
+        state.event_log.record(ark.to_string(), ArkChange::Updated);
+    }
+    if !config_changes.is_empty() {
+        state.config_store.stage(StagedConfig {
+            document: snapshot.config().clone(),
+            staged_by: admin.clone(),
+            staged_at: Utc::now(),
+            changes: config_changes,
+        });
+    }
+    tracing::warn!(
+        admin = %admin,
+        snapshot = %report.snapshot,
+        restored_to = %report.restored_to,
+        rebound = report.rebound,
+        recorded = report.recorded,
+        conflicts = report.conflicts,
+        "Registry restored"
+    );
+    Ok(Json(report))
+}
+
+/// How far the standby is behind this instance
+pub async fn replication_handler(State(state): State<Arc<AppState>>) -> Json<ReplicationStatus> {
+    Json(state.replication.status())
+}
+
+/// Applies changes replicated from the primary, in order, on a standby
+///
+/// Accepted while read-only, as standbys usually are until they take over.
+pub async fn apply_replicated_changes_handler(
+    State(state): State<Arc<AppState>>,
+    Json(batch): Json<ChangeBatch>,
+) -> Result<Json<AppliedChangesResponse>, AppError> {
+    let count = batch.changes.len();
+    let last = batch.changes.last().map(|change| change.id);
+    let store = state.store.clone();
+    tokio::task::spawn_blocking(move || {
+        batch
+            .changes
+            .iter()
+            .try_for_each(|change| change.change.apply(store.as_ref()))
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    tracing::debug!(count = count, last = ?last, "Replicated changes applied");
+    Ok(Json(AppliedChangesResponse { applied: count }))
+}
+
+/// Records a new incident for the status page
+pub async fn open_incident_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(payload): Json<OpenIncidentRequest>,
+) -> (StatusCode, Json<Incident>) {
+    let incident = state.status_board.open_incident(
+        payload.title,
+        payload.description,
+        payload.severity,
+        admin,
+    );
+
+    tracing::info!(
+        incident_id = incident.id,
+        severity = ?incident.severity,
+        admin = %incident.recorded_by,
+        "Incident opened"
+    );
+
+    (StatusCode::CREATED, Json(incident))
+}
+
+/// Marks an incident as resolved
+pub async fn resolve_incident_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Incident>, AppError> {
+    let incident = state
+        .status_board
+        .resolve_incident(id)
+        .ok_or_else(|| AppError::NotFound(format!("Incident {}", id)))?;
+
+    tracing::info!(incident_id = id, "Incident resolved");
+    Ok(Json(incident))
+}
+
+/// Schedules a maintenance window for the status page
+pub async fn schedule_maintenance_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(payload): Json<ScheduleMaintenanceRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), AppError> {
+    if payload.ends_at <= payload.starts_at {
+        return Err(AppError::InvalidRequest(
+            "ends_at must be after starts_at".to_string(),
+        ));
+    }
+
+    let window = state.status_board.schedule_maintenance(
+        payload.description,
+        payload.starts_at,
+        payload.ends_at,
+        admin,
+    );
+
+    tracing::info!(
+        maintenance_id = window.id,
+        starts_at = %window.starts_at,
+        ends_at = %window.ends_at,
+        "Maintenance window scheduled"
+    );
+
+    Ok((StatusCode::CREATED, Json(window)))
+}
+
+/// Cancels a maintenance window
+pub async fn cancel_maintenance_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    if !state.status_board.cancel_maintenance(id) {
+        return Err(AppError::NotFound(format!("Maintenance window {}", id)));
+    }
+
+    tracing::info!(maintenance_id = id, "Maintenance window cancelled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Describes the resolver's NAAN, inflections, endpoints, and commitments
+pub async fn ark_configuration_handler(
+    State(state): State<Arc<AppState>>,
+) -> Json<ArkConfiguration> {
+    Json(ArkConfiguration::describe(&state))
+}
+
+/// ResourceSync Source Description
+pub async fn resourcesync_description_handler(State(state): State<Arc<AppState>>) -> Response {
+    xml_response(resource_sync(&state).description())
+}
+
+/// ResourceSync capability list
+pub async fn capability_list_handler(State(state): State<Arc<AppState>>) -> Response {
+    xml_response(resource_sync(&state).capability_list())
+}
+
+/// ResourceSync change list of ARKs created, updated, or tombstoned in a time window
+///
+/// `from` defaults to, and is never earlier than, the oldest time the event log still covers,
+/// so the document never claims completeness for changes that were dropped.
+pub async fn change_list_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ChangeListQuery>,
+) -> Result<Response, AppError> {
+    let complete_since = state.event_log.complete_since();
+    let from = query
+        .from
+        .map_or(complete_since, |from| from.max(complete_since));
+    let until = query.until.unwrap_or_else(Utc::now);
+    if until < from {
+        return Err(AppError::InvalidRequest(
+            "until must not be before from".to_string(),
+        ));
+    }
+
+    // Changes to ARKs on private shoulders are not advertised
+    let events: Vec<ArkEvent> = state
+        .event_log
+        .between(Some(from), Some(until))
+        .into_iter()
+        .filter(|event| {
+            Ark::try_from(event.ark.as_str()).map_or(true, |ark| state.is_public(&ark.shoulder))
+        })
+        .collect();
+    tracing::debug!(
+        from = %from,
+        until = %until,
+        event_count = events.len(),
+        "Change list request"
+    );
+
+    Ok(xml_response(
+        resource_sync(&state).change_list(&events, from, until),
+    ))
+}
+
+/// Sitemap index, listing the sitemaps of every public shoulder that may be indexed
+///
+/// Shoulders with more ARKs than fit in one sitemap are listed once per page, where the
+/// background walk of the ledger last found the pages to start.
+pub async fn sitemap_index_handler(State(state): State<Arc<AppState>>) -> Response {
+    let mut shoulders: Vec<&str> = state
+        .shoulders
+        .iter()
+        .filter(|(_, config)| config.public && !config.noindex)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    shoulders.sort();
+
+    let mut sitemaps = Vec::new();
+    for shoulder in shoulders {
+        let url = format!(
+            "{}{}/{}",
+            sitemap_base_url(&state),
+            sitemap::SHOULDER_SITEMAP_PATH,
+            shoulder
+        );
+        let starts = state.sitemap_pages.starts(shoulder);
+        sitemaps.push(url.clone());
+        sitemaps.extend(
+            starts
+                .iter()
+                .map(|start| format!("{}?after={}", url, urlencoding::encode(start))),
+        );
+    }
+    xml_response(sitemap::index(&sitemaps))
+}
+
+/// Sitemap of a shoulder's ARKs, from where `after` left off, without those that are reserved
+/// or gone
+///
+/// Private shoulders and those marked `noindex` have none.
+pub async fn shoulder_sitemap_handler(
+    State(state): State<Arc<AppState>>,
+    Path(shoulder): Path<String>,
+    Query(query): Query<SitemapQuery>,
+) -> Result<Response, AppError> {
+    state
+        .shoulders
+        .get(shoulder.as_str())
+        .filter(|config| config.public && !config.noindex)
+        .ok_or(AppError::ShoulderNotFound)?;
+
+    let blocking_state = state.clone();
+    let entries = tokio::task::spawn_blocking(move || {
+        let page = sitemap::page(
+            blocking_state.store.as_ref(),
+            &shoulder,
+            query.after.as_deref(),
+            sitemap::SITEMAP_PAGE_SIZE,
+        )?;
+        sitemap::listed(&blocking_state.bindings, page.entries)
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))??;
+
+    Ok(xml_response(sitemap::urlset(
+        sitemap_base_url(&state),
+        &entries,
+    )))
+}
+
+/// Prefix of sitemap URLs, which must be absolute; the sitemap routes exist only with
+/// `PUBLIC_BASE_URL`
+fn sitemap_base_url(state: &AppState) -> &str {
+    state.public_base_url.as_deref().unwrap_or_default()
+}
+
+fn resource_sync(state: &AppState) -> ResourceSync<'_> {
+    ResourceSync {
+        base_url: state.public_base_url.as_deref().unwrap_or_default(),
+    }
+}
+
+fn xml_response(xml: String) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        xml,
+    )
+        .into_response()
+}
+
+/// Lists quarantined redirects, open ones first
+pub async fn quarantine_handler(State(state): State<Arc<AppState>>) -> Json<Vec<QuarantineEntry>> {
+    Json(state.quarantine.list())
+}
+
+/// Lists webhook deliveries, newest first
+pub async fn deliveries_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeliveriesQuery>,
+) -> Result<Json<Vec<Delivery>>, AppError> {
+    Ok(Json(state.store.deliveries(query.status)?))
+}
+
+/// Attempts a pending or failed webhook delivery right away
+pub async fn retry_delivery_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Path(id): Path<String>,
+) -> Result<Json<Delivery>, AppError> {
+    let delivery = state
+        .store
+        .delivery(&id)?
+        .ok_or_else(|| AppError::NotFound(format!("Delivery {}", id)))?;
+    if delivery.status == DeliveryStatus::Delivered {
+        return Err(AppError::InvalidRequest(format!(
+            "Delivery {} was delivered already",
+            id
+        )));
+    }
+
+    tracing::info!(delivery = %id, admin = %admin, "Webhook delivery retried");
+    state
+        .failure_callbacks
+        .retry(state.store.as_ref(), delivery)
+        .await?
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::InvalidRequest(format!("Delivery {} is being attempted already", id))
+        })
+}
+
+/// Mint and collision counters, per shoulder
+pub async fn mint_stats_handler(
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<ShoulderMintStats>> {
+    Json(state.mint_stats.snapshot())
+}
+
+/// Exchanges an admin API key for a short-lived admin UI token
+///
+/// The token is returned together with its CSRF token and set as an `HttpOnly` cookie scoped
+/// to the admin API, so the browser never holds the API key. Tokens cannot renew themselves.
+pub async fn issue_admin_token_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    session: Option<Extension<AdminSession>>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    if session.is_some() {
+        return Err(AppError::Unauthorized);
+    }
+
+    // A token asked for with a managed key stops working when that key is expired or rotated
+    let key_id = auth::bearer_token(&headers)
+        .and_then(|token| state.api_keys.identify(token))
+        .map(|key| key.id);
+    let issued = state.token_signer.issue(&admin, key_id.as_deref());
+    tracing::info!(admin = %admin, expires_at = %issued.expires_at, "Admin UI token issued");
+
+    let cookie = format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; Secure; SameSite=Strict",
+        auth::TOKEN_COOKIE,
+        issued.token,
+        state.admin_api_path.as_deref().unwrap_or("/"),
+        state.token_signer.ttl().as_secs()
+    );
+    Ok(([(header::SET_COOKIE, cookie)], Json(issued)).into_response())
+}
+
+/// Blade length advice for every shoulder, by shoulder
+pub async fn blade_length_handler(State(state): State<Arc<AppState>>) -> Json<BladeLengthReport> {
+    let advisor = &state.blade_advisor;
+    let mut shoulders: Vec<BladeLengthAdvice> = state
+        .shoulders
+        .iter()
+        .map(|(name, config)| {
+            let stats = state
+                .mint_stats
+                .shoulder(name)
+                .unwrap_or_else(|| ShoulderMintStats {
+                    shoulder: name.to_string(),
+                    ..Default::default()
+                });
+            advisor.advise(
+                state.blade_length_for(name, config),
+                config.alphabet.characters().len(),
+                &stats,
+            )
+        })
+        .collect();
+    shoulders.sort_by(|a, b| a.shoulder.cmp(&b.shoulder));
+
+    let policy = advisor.policy();
+    Json(BladeLengthReport {
+        auto_escalate: policy.auto_escalate,
+        horizon: policy.horizon,
+        threshold: policy.threshold,
+        shoulders,
+        escalations: advisor.escalations(),
+    })
+}
+
+/// Outbound HTTP request counters, per destination
+/// Mint queue capacity and occupancy
+pub async fn mint_queue_handler(State(state): State<Arc<AppState>>) -> Json<MintQueueStats> {
+    Json(state.mint_queue.stats())
+}
+
+/// How many pre-minted ARKs each shoulder's pool holds
+pub async fn mint_pools_handler(State(state): State<Arc<AppState>>) -> Json<Vec<PoolLevel>> {
+    Json(
+        state
+            .mint_pools
+            .levels(state.shoulders.keys().map(|shoulder| shoulder.as_str())),
+    )
+}
+
+pub async fn http_metrics_handler(
+    State(state): State<Arc<AppState>>,
+) -> Json<Vec<DestinationStats>> {
+    Json(state.http_metrics.snapshot())
+}
+
+/// Every managed API key, oldest first, without the keys themselves
+pub async fn api_keys_handler(State(state): State<Arc<AppState>>) -> Json<Vec<ApiKeySummary>> {
+    Json(
+        state
+            .api_keys
+            .list()
+            .into_iter()
+            .map(ApiKeySummary::from)
+            .collect(),
+    )
+}
+
+/// Issues a managed API key, answering with the key this one time
+pub async fn create_api_key_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Json(payload): Json<CreateKeyRequest>,
+) -> Result<Response, AppError> {
+    let issued = state.api_keys.create(
+        state.store.as_ref(),
+        &payload.name,
+        &payload.scopes,
+        payload.expires_at,
+        &admin,
+    )?;
+    tracing::info!(
+        key = %issued.record.prefix(),
+        holder = %issued.record.name,
+        scopes = ?issued.record.scopes,
+        admin = %admin,
+        "API key created"
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(IssuedKeyResponse {
+            key: issued.key,
+            summary: issued.record.into(),
+        }),
+    )
+        .into_response())
+}
+
+/// Makes a managed API key stop working, now or at a given time
+pub async fn expire_api_key_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Path(id): Path<String>,
+    payload: Option<Json<ExpireKeyRequest>>,
+) -> Result<Json<ApiKeySummary>, AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let key = state
+        .api_keys
+        .expire(state.store.as_ref(), &id, payload.at)?;
+    tracing::info!(
+        key = %key.prefix(),
+        holder = %key.name,
+        expires_at = ?key.expires_at,
+        admin = %admin,
+        "API key expired"
+    );
+    Ok(Json(key.into()))
+}
+
+/// Replaces a managed API key with a new one, keeping the old one working for a grace period
+pub async fn rotate_api_key_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Path(id): Path<String>,
+    payload: Option<Json<RotateKeyRequest>>,
+) -> Result<Response, AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let issued = state.api_keys.rotate(
+        state.store.as_ref(),
+        &id,
+        Duration::from_secs(payload.grace_secs),
+        &admin,
+    )?;
+    tracing::info!(
+        key = %issued.record.prefix(),
+        rotated_from = %id,
+        holder = %issued.record.name,
+        grace_secs = payload.grace_secs,
+        admin = %admin,
+        "API key rotated"
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(IssuedKeyResponse {
+            key: issued.key,
+            summary: issued.record.into(),
+        }),
+    )
+        .into_response())
+}
+
+/// Calls made with API key `id`, an hour or a day at a time, as JSON or CSV
+///
+/// `id` is the id of a managed key, or the name a configured key is held under.
+pub async fn key_usage_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<KeyUsageQuery>,
+) -> Result<Response, AppError> {
+    let (key, name) = match state.api_keys.get(&id) {
+        Some(key) => (UsageKey::Managed(key.id), key.name),
+        None if state
+            .admin_keys
+            .values()
+            .chain(state.resolution_keys.values())
+            .chain(state.mint_keys.values())
+            .any(|holder| *holder == id) =>
+        {
+            (UsageKey::Configured(id.clone()), id.clone())
+        }
+        None => return Err(AppError::NotFound(format!("No API key {}", id))),
+    };
+    let buckets = state.key_usage.report(&key, query.granularity);
+
+    Ok(match query.format {
+        UsageFormat::Json => {
+            let mut total = UsageCounts::default();
+            for bucket in &buckets {
+                total.add(&bucket.calls);
+            }
+            Json(KeyUsageReport {
+                key: id,
+                name,
+                granularity: query.granularity,
+                buckets,
+                total,
+            })
+            .into_response()
+        }
+        UsageFormat::Csv => {
+            let csv: String = std::iter::once(USAGE_CSV_HEADER.to_string())
+                .chain(buckets.iter().map(|bucket| bucket.csv_line()))
+                .collect();
+            ([(header::CONTENT_TYPE, "text/csv; charset=utf-8")], csv).into_response()
+        }
+    })
+}
+
+/// Maximum ARK input lengths and how many inputs each rejected
+pub async fn input_limits_handler(State(state): State<Arc<AppState>>) -> Json<InputLimitsReport> {
+    Json(state.input_limits.report())
+}
+
+/// Marks a quarantined redirect as resolved once its root cause is fixed
+pub async fn resolve_quarantine_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(AdminIdentity(admin)): Extension<AdminIdentity>,
+    Path(id): Path<u64>,
+    payload: Option<Json<ResolveQuarantineRequest>>,
+) -> Result<Json<QuarantineEntry>, AppError> {
+    let note = payload.and_then(|Json(payload)| payload.note);
+    let entry = state
+        .quarantine
+        .resolve(id, admin, note)
+        .ok_or_else(|| AppError::NotFound(format!("Quarantine entry {}", id)))?;
+
+    tracing::info!(
+        quarantine_id = id,
+        admin = ?entry.resolved_by,
+        "Quarantine entry resolved"
+    );
+    Ok(Json(entry))
+}
+
+fn live_config_document(state: &AppState) -> ConfigDocument {
+    // Blade lengths escalated at runtime are part of the live configuration
+    let shoulders = state
+        .shoulders
+        .iter()
+        .map(|(name, config)| {
+            let mut config = config.clone();
+            let blade_length = state.blade_length_for(name, &config);
+            if blade_length != config.blade_length.unwrap_or(state.default_blade_length) {
+                config.blade_length = Some(blade_length);
+            }
+            (name.clone(), config)
+        })
+        .collect();
+    ConfigDocument::from_live(
+        &state.naan,
+        state.default_blade_length,
+        state.max_mint_count,
+        &shoulders,
+        &state.config_store,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ark::Normalization;
+    use crate::bindings::Bindings;
+    use crate::config_document::ConfigStore;
+    use crate::export::ExportFormat;
+    use crate::import::ImportFormat;
+    use crate::journal::MintJournal;
+    use crate::limits::InputLimits;
+    use crate::mint_quota::MintQuota;
+    use crate::notify::SuppressionPolicy;
+    use crate::notify::tests::{RecordingTransport, notifier};
+    use crate::shoulder::{Contact, MAX_BLADE_LENGTH, MethodRedirect};
+    use crate::testing::SwitchableBindingStore;
+    use crate::validation::ValidationPool;
+    use std::collections::BTreeMap;
+
+    fn create_test_state() -> Arc<AppState> {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Test Project".to_string(),
+                contact: Some(Contact {
+                    name: "Test Team".to_string(),
+                    email: Some("team@example.org".to_string()),
+                    organization: None,
+                }),
+                ..Default::default()
+            },
+        );
+        shoulders.insert(
+            "b3".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://beta.org/items/${value}".to_string(),
+                project_name: "Beta Project".to_string(),
+                uses_check_character: false,
+                noindex: true,
+                ..Default::default()
+            },
+        );
+
+        Arc::new(AppState {
+            config_store: ConfigStore::new(shoulders.keys(), "environment", "SHOULDERS"),
+            shoulders,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_health_check_handler() {
+        let state = create_test_state();
+        let response = health_check_handler(State(state), HeaderMap::new()).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"OK");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_handler_status_page() {
+        let state = create_test_state();
+        state.status_board.open_incident(
+            "Elevated error rate".to_string(),
+            None,
+            crate::status::Severity::Major,
+            "alice".to_string(),
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+        let response = health_check_handler(State(state.clone()), headers).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["status"], "degraded");
+        assert_eq!(report["incidents"][0]["title"], "Elevated error rate");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+        let response = health_check_handler(State(state), headers).await;
+        let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().starts_with("text/html"));
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_handler_rejects_inverted_window() {
+        let state = create_test_state();
+        let now = Utc::now();
+        let payload = ScheduleMaintenanceRequest {
+            description: "Upgrade".to_string(),
+            starts_at: now,
+            ends_at: now - chrono::Duration::hours(1),
+        };
+
+        let result = schedule_maintenance_handler(
+            State(state),
+            Extension(AdminIdentity("alice".to_string())),
+            Json(payload),
+        )
+        .await;
+        assert!(matches!(result.unwrap_err(), AppError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_info_handler_returns_shoulder_info() {
+        let state = create_test_state();
+        let response = info_handler(State(state.clone()), HeaderMap::new()).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(info["naan"], "12345");
+        let shoulders = info["shoulders"].as_array().unwrap();
+        assert_eq!(shoulders.len(), 2);
+
+        // Shoulders are listed in order
+        assert_eq!(shoulders[0]["shoulder"], "b3");
+        assert_eq!(shoulders[1]["shoulder"], "x6");
+
+        // Contact details are surfaced only for shoulders that have them
+        assert_eq!(shoulders[1]["contact"]["email"], "team@example.org");
+        assert!(shoulders[0].get("contact").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_info_handler_reports_shoulder_stats() {
+        let state = create_test_state();
+        let arks = minting::mint_arks(&state, "x6", 2).unwrap();
+        let uri: axum::http::Uri = format!("/{}", arks[0]).parse().unwrap();
+        resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let response = info_handler(State(state), HeaderMap::new()).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let (b3, x6) = (&info["shoulders"][0], &info["shoulders"][1]);
+
+        assert_eq!(x6["minted"], 2);
+        assert!(x6["last_minted_at"].is_string());
+        assert_eq!(x6["resolutions_24h"], 1);
+        assert_eq!(x6["resolutions_30d"], 1);
+        assert_eq!(b3["minted"], 0);
+        assert!(b3["last_minted_at"].is_null());
+        assert_eq!(b3["resolutions_24h"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_info_handler_reports_namespace_consumed() {
+        let state = create_test_state();
+        minting::mint_arks(&state, "x6", 4).unwrap();
+        state
+            .ledger_counts
+            .refresh(state.store.as_ref(), ["x6", "b3"]);
+
+        let response = info_handler(State(state), HeaderMap::new()).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let (b3, x6) = (&info["shoulders"][0], &info["shoulders"][1]);
+
+        let blade_length = x6["blade_length"].as_i64().unwrap() as i32;
+        let namespace_size = 29f64.powi(blade_length);
+        assert_eq!(x6["alphabet_size"], 29);
+        assert_eq!(x6["namespace_size"].as_f64().unwrap(), namespace_size);
+        let consumed = x6["percent_consumed"].as_f64().unwrap();
+        assert!((consumed - 400.0 / namespace_size).abs() < 1e-12);
+        assert_eq!(b3["percent_consumed"], 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_info_handler_reports_bound_arks() {
+        let state = create_test_state();
+        let binding = |target: &str| Binding {
+            target: target.to_string(),
+            metadata: Default::default(),
+            lifecycle: Default::default(),
+            bound_at: Utc::now(),
+        };
+        state
+            .bindings
+            .bind_all(
+                ["ark:12345/x6bbbb", "ark:12345/x6cccc", "ark:12345/b3dddd"].map(|ark| {
+                    (
+                        Ark::try_from(ark).unwrap(),
+                        binding("https://example.org/bound"),
+                    )
+                }),
+            )
+            .unwrap();
+        let info = || async {
+            let response = info_handler(State(state.clone()), HeaderMap::new()).await;
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+
+        // Left out until the bindings are first counted
+        assert!(info().await["shoulders"][1].get("bound").is_none());
+
+        state
+            .binding_counts
+            .refresh(&state.bindings, &state.naan, ["x6", "b3"]);
+        let info = info().await;
+        let (b3, x6) = (&info["shoulders"][0], &info["shoulders"][1]);
+        assert_eq!(x6["bound"], 2);
+        assert_eq!(b3["bound"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_info_handler_html() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+        let response = info_handler(State(create_test_state()), headers).await;
+
+        let content_type = response.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().starts_with("text/html"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8(body.to_vec()).unwrap();
+        assert!(html.contains("<td>Test Project</td>"));
+        assert!(html.contains("mailto:team@example.org"));
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_success() {
+        let state = create_test_state();
+        let payload = MintRequest {
+            shoulder: "x6".to_string(),
+            count: 3,
+            detail: false,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+
+        let result = mint_handler(State(state), HeaderMap::new(), Json(payload)).await;
+        assert!(result.is_ok());
+
+        let response = result.unwrap();
+        assert_eq!(response.0.count, 3);
+        assert_eq!(response.0.arks.len(), 3);
+
+        // Verify ARKs have correct format (starts with ark:12345/x6)
+        for ark in &response.0.arks {
+            let MintedArk::Plain(ark) = ark else {
+                panic!("expected plain ARK strings");
+            };
+            assert!(ark.starts_with("ark:12345/x6"));
+        }
+    }
+
+    /// A test state where `alice-key` and `bob-key` may mint
+    fn create_minter_state() -> Arc<AppState> {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.mint_keys = HashMap::from([
+            ("alice-key".to_string(), "alice".to_string()),
+            ("bob-key".to_string(), "bob".to_string()),
+        ]);
+        Arc::new(state)
+    }
+
+    /// Headers of a request made with `api_key`, and `idempotency_key` if given
+    fn minter_headers(api_key: &str, idempotency_key: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", api_key).parse().unwrap(),
+        );
+        if let Some(key) = idempotency_key {
+            headers.insert("idempotency-key", key.parse().unwrap());
+        }
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_replays_idempotent_requests() {
+        let state = create_minter_state();
+        let request = |count: usize, request_id: Option<&str>| MintRequest {
+            shoulder: "x6".to_string(),
+            count,
+            detail: false,
+            request_id: request_id.map(String::from),
+            dry_run: false,
+            metadata: Default::default(),
+        };
+        let keyed = |key: &str| minter_headers("alice-key", Some(key));
+
+        let first = mint_handler(
+            State(state.clone()),
+            keyed("batch-7"),
+            Json(request(3, None)),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(!first.replayed);
+        // The key may also come in the body
+        let retry = mint_handler(
+            State(state.clone()),
+            minter_headers("alice-key", None),
+            Json(request(3, Some("batch-7"))),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(retry.replayed);
+        assert_eq!(
+            serde_json::to_value(&retry.arks).unwrap(),
+            serde_json::to_value(&first.arks).unwrap()
+        );
+        assert_eq!(state.store.minted().unwrap(), 3);
+        assert_eq!(state.event_log.between(None, None).len(), 3);
+
+        // Two different keys, or a key without credentials, are rejected
+        let mut anonymous = HeaderMap::new();
+        anonymous.insert("idempotency-key", "batch-7".parse().unwrap());
+        for (headers, payload) in [
+            (keyed("batch-7"), request(3, Some("batch-8"))),
+            (keyed("batch 7"), request(3, None)),
+            (anonymous, request(3, None)),
+        ] {
+            let result = mint_handler(State(state.clone()), headers, Json(payload)).await;
+            assert!(matches!(result, Err(AppError::InvalidRequest(_))));
+        }
+        // The same key for another request is refused
+        let other_metadata = MintRequest {
+            metadata: [("batch".to_string(), "8".to_string())].into(),
+            ..request(3, None)
+        };
+        let detailed = MintRequest {
+            detail: true,
+            ..request(3, None)
+        };
+        for payload in [request(2, None), other_metadata, detailed] {
+            let result = mint_handler(State(state.clone()), keyed("batch-7"), Json(payload)).await;
+            assert!(matches!(result, Err(AppError::Unprocessable(_))));
+        }
+        assert_eq!(state.store.minted().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_keeps_idempotency_keys_per_holder() {
+        let state = create_minter_state();
+        let request = || MintRequest {
+            shoulder: "x6".to_string(),
+            count: 2,
+            detail: false,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+
+        let alice = mint_handler(
+            State(state.clone()),
+            minter_headers("alice-key", Some("batch-7")),
+            Json(request()),
+        )
+        .await
+        .unwrap()
+        .0;
+        // Bob's request with the same key is his own, and gets other ARKs
+        let bob = mint_handler(
+            State(state.clone()),
+            minter_headers("bob-key", Some("batch-7")),
+            Json(request()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(!bob.replayed);
+        assert_ne!(
+            serde_json::to_value(&bob.arks).unwrap(),
+            serde_json::to_value(&alice.arks).unwrap()
+        );
+        assert_eq!(state.store.minted().unwrap(), 4);
+
+        let bob_retry = mint_handler(
+            State(state.clone()),
+            minter_headers("bob-key", Some("batch-7")),
+            Json(request()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert!(bob_retry.replayed);
+        assert_eq!(
+            serde_json::to_value(&bob_retry.arks).unwrap(),
+            serde_json::to_value(&bob.arks).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_detail() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.public_base_url = Some("https://ark.example.org".to_string());
+        let state = Arc::new(state);
+
+        let payload = MintRequest {
+            shoulder: "x6".to_string(),
+            count: 2,
+            detail: true,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+        let response = mint_handler(State(state.clone()), HeaderMap::new(), Json(payload))
+            .await
+            .unwrap();
+        assert_eq!(response.0.count, 2);
+
+        for item in &response.0.arks {
+            let MintedArk::Detailed(detail) = item else {
+                panic!("expected detailed ARKs");
+            };
+            assert_eq!(detail.naan, "12345");
+            assert_eq!(detail.shoulder, "x6");
+            assert_eq!(detail.ark, format!("ark:12345/x6{}", detail.blade));
+            assert_eq!(detail.blade.len(), 9);
+            assert_eq!(detail.check_character.as_deref(), Some(&detail.blade[8..]));
+            assert_eq!(
+                detail.resolver_url.as_deref(),
+                Some(format!("https://ark.example.org/{}", detail.ark).as_str())
+            );
+        }
+
+        // Shoulders without check characters report none
+        let payload = MintRequest {
+            shoulder: "b3".to_string(),
+            count: 1,
+            detail: true,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+        let response = mint_handler(State(state), HeaderMap::new(), Json(payload))
+            .await
+            .unwrap();
+        let json = serde_json::to_value(&response.0).unwrap();
+        assert!(json["arks"][0].get("check_character").is_none());
+        assert_eq!(json["arks"][0]["blade"].as_str().unwrap().len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_mint_dry_run_records_nothing() {
+        let state = create_test_state();
+        let request = |request_id: Option<&str>| MintRequest {
+            shoulder: "x6".to_string(),
+            count: 3,
+            detail: true,
+            request_id: request_id.map(str::to_string),
+            dry_run: true,
+            metadata: Default::default(),
+        };
+        let response = mint_handler(State(state.clone()), HeaderMap::new(), Json(request(None)))
+            .await
+            .unwrap();
+        assert_eq!(response.0.count, 3);
+        let json = serde_json::to_value(&response.0).unwrap();
+        assert_eq!(json["dry_run"], true);
+        // Examples are shaped like minted ARKs, check character included
+        assert_eq!(json["arks"][0]["blade"].as_str().unwrap().len(), 9);
+        assert_eq!(state.store.minted().unwrap(), 0);
+        assert!(state.event_log.between(None, None).is_empty());
+
+        let keyed = mint_handler(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(request(Some("demo-1"))),
+        )
+        .await;
+        assert!(matches!(keyed, Err(AppError::InvalidRequest(_))));
+        let streamed =
+            mint_stream_handler(State(state.clone()), HeaderMap::new(), Json(request(None))).await;
+        assert!(matches!(streamed, Err(AppError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_change_list_handler_lists_minted_arks() {
+        let state = create_test_state();
+        let request = MintRequest {
+            shoulder: "x6".to_string(),
+            count: 2,
+            detail: false,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+        let minted = mint_handler(State(state.clone()), HeaderMap::new(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(minted.0.count, 2);
+
+        let query = ChangeListQuery {
+            from: None,
+            until: None,
+        };
+        let response = change_list_handler(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(xml.matches("change=\"created\"").count(), 2);
+        assert!(xml.contains("<loc>/ark:12345/x6"));
+
+        let query = ChangeListQuery {
+            from: Some("2025-01-02T00:00:00Z".parse().unwrap()),
+            until: Some("2025-01-01T00:00:00Z".parse().unwrap()),
+        };
+        assert!(matches!(
+            change_list_handler(State(state), Query(query)).await,
+            Err(AppError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sitemaps_list_indexable_shoulders_only() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.public_base_url = Some("https://ark.example.org".to_string());
+        let state = Arc::new(state);
+        let mut minted = Vec::new();
+        for shoulder in ["x6", "b3"] {
+            let request = MintRequest {
+                shoulder: shoulder.to_string(),
+                count: 3,
+                detail: false,
+                request_id: None,
+                dry_run: false,
+                metadata: Default::default(),
+            };
+            let response = mint_handler(State(state.clone()), HeaderMap::new(), Json(request))
+                .await
+                .unwrap();
+            minted.extend(response.0.arks.iter().map(|ark| match ark {
+                MintedArk::Plain(ark) => ark.clone(),
+                MintedArk::Detailed(detail) => detail.ark.clone(),
+            }));
+        }
+
+        // Reserved and withdrawn ARKs resolve to 404 and 410, so they are not listed
+        let mut withdrawn = Lifecycle::default();
+        withdrawn
+            .transition(ArkStatus::Withdrawn, None, None, Utc::now())
+            .unwrap();
+        let statuses = [Lifecycle::starting(ArkStatus::Reserved).unwrap(), withdrawn];
+        state
+            .bindings
+            .bind_all(minted.iter().zip(statuses).map(|(ark, lifecycle)| {
+                (
+                    Ark::try_from(ark.as_str()).unwrap(),
+                    Binding {
+                        target: "https://example.org/hidden".to_string(),
+                        metadata: Default::default(),
+                        lifecycle,
+                        bound_at: Utc::now(),
+                    },
+                )
+            }))
+            .unwrap();
+
+        let response = sitemap_index_handler(State(state.clone())).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+        assert!(xml.contains("<loc>https://ark.example.org/sitemaps/x6</loc>"));
+        assert!(!xml.contains("b3"));
+
+        let query = SitemapQuery { after: None };
+        let response =
+            shoulder_sitemap_handler(State(state.clone()), Path("x6".to_string()), Query(query))
+                .await
+                .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            xml.matches("<loc>https://ark.example.org/ark:12345/x6")
+                .count(),
+            1
+        );
+        assert!(xml.contains(&format!("<loc>https://ark.example.org/{}</loc>", minted[2])));
+
+        // Shoulders marked noindex have no sitemap
+        let query = SitemapQuery { after: None };
+        assert!(matches!(
+            shoulder_sitemap_handler(State(state), Path("b3".to_string()), Query(query)).await,
+            Err(AppError::ShoulderNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_private_shoulders_are_not_advertised() {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Public Project".to_string(),
+                ..Default::default()
+            },
+        );
+        shoulders.insert(
+            "s9".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://staging.example.org/${value}".to_string(),
+                project_name: "Staging".to_string(),
+                uses_check_character: false,
+                public: false,
+                require_auth: true,
+                ..Default::default()
+            },
+        );
+        let state = Arc::new(AppState {
+            shoulders,
+            resolution_keys: HashMap::from([("secret".to_string(), "alice".to_string())]),
+            ..Default::default()
+        });
+
+        let response = info_handler(State(state.clone()), HeaderMap::new()).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info["shoulders"].as_array().unwrap().len(), 1);
+        assert_eq!(info["shoulders"][0]["shoulder"], "x6");
+
+        let request = MintRequest {
+            shoulder: "s9".to_string(),
+            count: 1,
+            detail: false,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+        let minted = mint_handler(State(state.clone()), HeaderMap::new(), Json(request))
+            .await
+            .unwrap();
+        assert_eq!(minted.0.count, 1);
+        let query = ChangeListQuery {
+            from: None,
+            until: None,
+        };
+        let response = change_list_handler(State(state.clone()), Query(query))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8(body.to_vec()).unwrap().contains("s9"));
+
+        // Private shoulders still resolve, here only with a resolution key
+        let resolve = |headers| {
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                headers,
+                OriginalUri(axum::http::Uri::from_static("/ark:12345/s9test")),
+                None,
+            )
+        };
+        let anonymous = resolve(HeaderMap::new()).await.unwrap();
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            anonymous.headers()[header::WWW_AUTHENTICATE],
+            "Bearer realm=\"resolver\""
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert_eq!(resolve(headers).await.unwrap().status(), StatusCode::FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_invalid_shoulder() {
+        let state = create_test_state();
+        let payload = MintRequest {
+            shoulder: "z9".to_string(), // Unregistered shoulder
+            count: 1,
+            detail: false,
+            request_id: None,
+            dry_run: false,
+            metadata: Default::default(),
+        };
+
+        let result = mint_handler(State(state), HeaderMap::new(), Json(payload)).await;
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), AppError::ShoulderNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_mint_handler_enforces_shoulder_quota() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.shoulders.get_mut("x6").unwrap().mint_quota = Some(MintQuota {
+            per_hour: Some(5),
+            per_day: None,
+        });
+        let transport = Arc::new(RecordingTransport::default());
+        state.notifier = Arc::new(notifier(transport.clone(), SuppressionPolicy::default()));
+        let state = Arc::new(state);
+        let mint = |shoulder: &str, count: usize| {
+            mint_handler(
+                State(state.clone()),
+                HeaderMap::new(),
+                Json(MintRequest {
+                    shoulder: shoulder.to_string(),
+                    count,
+                    detail: false,
+                    request_id: None,
+                    dry_run: false,
+                    metadata: Default::default(),
+                }),
+            )
+        };
+
+        assert!(matches!(
+            mint("x6", 6).await,
+            Err(AppError::InvalidRequest(_))
+        ));
+        assert_eq!(mint("x6", 4).await.unwrap().0.count, 4);
+        let refused = mint("x6", 2).await.unwrap_err().into_response();
+        assert_eq!(refused.status(), StatusCode::TOO_MANY_REQUESTS);
+        let retry_after: u64 = refused.headers()[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=3600).contains(&retry_after));
+        // The operator hears about it once, however often minters are refused
+        assert!(mint("x6", 2).await.is_err());
+        tokio::task::yield_now().await;
+        let sent = transport.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].subject,
+            "[ark:12345] Mint quota used up on shoulder x6"
+        );
+        // What is left of the hour, and other shoulders, can still be minted
+        assert_eq!(mint("x6", 1).await.unwrap().0.count, 1);
+        assert_eq!(mint("b3", 10).await.unwrap().0.count, 10);
+        assert_eq!(state.store.minted().unwrap(), 15);
+    }
+
+    #[tokio::test]
+    async fn test_shoulder_capacity_handler() {
+        let state = create_test_state();
+        minting::mint_arks(&state, "x6", 2).unwrap();
+
+        let capacity = shoulder_capacity_handler(
+            State(state.clone()),
+            Path("x6".to_string()),
+            Query(CapacityQuery { next: Some(1000) }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(capacity.minted, 2);
+        assert_eq!(capacity.alphabet_size, 29);
+        assert_eq!(
+            capacity.namespace_size,
+            29f64.powi(capacity.blade_length as i32)
+        );
+        assert_eq!(
+            capacity.collision_probability,
+            blade_advisor::collision_probability(2, 1000, capacity.blade_length)
+        );
+        assert_eq!(capacity.sequential_position, None);
+
+        let unknown = shoulder_capacity_handler(
+            State(state),
+            Path("z9".to_string()),
+            Query(CapacityQuery { next: None }),
+        )
+        .await;
+        assert!(matches!(unknown, Err(AppError::ShoulderNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_shoulder_schema_handler() {
+        let Json(schema) = shoulder_schema_handler().await;
+        let schema = serde_json::to_value(schema).unwrap();
+
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["route_pattern", "project_name"])
+        );
+        let properties = &schema["properties"];
+        assert_eq!(properties["blade_length"]["maximum"], MAX_BLADE_LENGTH);
+        assert_eq!(properties["public"]["default"], true);
+        assert!(properties["template"]["pattern"].is_string());
+        for field in ["commitment", "metadata_route_pattern", "qualifier_encoding"] {
+            assert!(properties.get(field).is_some(), "{} missing", field);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shoulder_examples_handler() {
+        let state = create_test_state();
+
+        let examples = shoulder_examples_handler(
+            State(state.clone()),
+            Path("x6".to_string()),
+            Query(ExampleQuery { count: Some(3) }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(examples.examples.len(), 3);
+        for example in &examples.examples {
+            let parsed = parse_ark(&example.ark).unwrap();
+            assert_eq!(
+                example.target,
+                format!("https://example.org/{}{}", parsed.shoulder, parsed.blade)
+            );
+            assert!(example.blocked.is_none());
+        }
+        // Nothing was minted
+        assert!(state.mint_stats.shoulder("x6").is_none());
+        assert_eq!(state.store.minted().unwrap(), 0);
+
+        let capped = shoulder_examples_handler(
+            State(state),
+            Path("x6".to_string()),
+            Query(ExampleQuery { count: Some(1000) }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(capped.examples.len(), MAX_EXAMPLE_COUNT);
+    }
+
+    #[tokio::test]
+    async fn test_export_arks_handler_streams_the_ledger() {
+        let shoulder = |public| Shoulder {
+            route_pattern: "https://example.org/${value}".to_string(),
+            project_name: "Test Project".to_string(),
+            public,
+            ..Default::default()
+        };
+        let state = Arc::new(AppState {
+            shoulders: HashMap::from([
+                ("x6".parse().unwrap(), shoulder(true)),
+                ("b3".parse().unwrap(), shoulder(true)),
+                ("q9".parse().unwrap(), shoulder(false)),
+            ]),
+            ..Default::default()
+        });
+        let x6: Vec<String> = (0..EXPORT_PAGE_SIZE + 1)
+            .map(|n| format!("ark:12345/x6{:05}", n))
+            .collect();
+        state.store.record("x6", &x6, &BTreeMap::new()).unwrap();
+        state
+            .store
+            .record("b3", &["ark:12345/b3abc".to_string()], &BTreeMap::new())
+            .unwrap();
+        state
+            .store
+            .record("q9", &["ark:12345/q9abc".to_string()], &BTreeMap::new())
+            .unwrap();
+
+        let export = |shoulder: Option<&str>, format| {
+            export_arks_handler(
+                State(state.clone()),
+                Query(ExportQuery {
+                    shoulder: shoulder.map(String::from),
+                    format,
+                }),
+            )
+        };
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        // Every page of the shoulder, after the header row
+        let response = export(Some("x6"), ExportFormat::Csv).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        let csv = body(response).await;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), EXPORT_PAGE_SIZE + 2);
+        assert_eq!(lines[0], "ark,shoulder,minted_at");
+        assert!(
+            lines[1].starts_with("ark:12345/x600000,x6,20"),
+            "{}",
+            lines[1]
+        );
+        assert!(lines.last().unwrap().starts_with(&x6[EXPORT_PAGE_SIZE]));
+
+        // Private shoulders are left out of full exports and cannot be asked for
+        let response = export(None, ExportFormat::Jsonl).await.unwrap();
+        let jsonl = body(response).await;
+        let arks: Vec<String> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["ark"].to_string())
+            .collect();
+        assert_eq!(arks.len(), EXPORT_PAGE_SIZE + 2);
+        assert!(arks.contains(&"\"ark:12345/b3abc\"".to_string()));
+        assert!(!jsonl.contains("q9abc"));
+        for hidden in ["q9", "zz"] {
+            assert!(matches!(
+                export(Some(hidden), ExportFormat::Csv).await,
+                Err(AppError::ShoulderNotFound)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_handler_records_external_arks() {
+        let state = create_test_state();
+        let check = crate::check_character::calculate_check_character("x6k7t01");
+        let external = format!("ark:/{}/x6-k7t01{}", state.naan, check);
+        let register = |arks: Vec<String>, blades: Vec<&str>| {
+            register_handler(
+                State(state.clone()),
+                Json(RegisterRequest {
+                    arks,
+                    shoulder: (!blades.is_empty()).then(|| "b3".to_string()),
+                    blades: blades.into_iter().map(String::from).collect(),
+                }),
+            )
+        };
+
+        // One invalid ARK fails the whole request before anything is recorded
+        for invalid in [
+            format!(
+                "ark:{}/x6k7t01{}",
+                state.naan,
+                if check == 'b' { 'c' } else { 'b' }
+            ),
+            format!("ark:{}/q9k7t01", state.naan),
+            "ark:99999/b3k7t01".to_string(),
+            format!("ark:{}/b3k7t01/page2", state.naan),
+        ] {
+            let result = register(vec![external.clone(), invalid.clone()], vec![]).await;
+            assert!(
+                matches!(&result, Err(AppError::InvalidRequest(m)) if m.starts_with("arks[1]")),
+                "{}",
+                invalid
+            );
+        }
+        assert_eq!(state.store.minted().unwrap(), 0);
+
+        let response = register(vec![external.clone()], vec!["k7t01", "k7t01"])
+            .await
+            .unwrap()
+            .0;
+        let canonical = format!("ark:{}/x6k7t01{}", state.naan, check);
+        let blade = format!("ark:{}/b3k7t01", state.naan);
+        assert_eq!(response.registered, [canonical.clone(), blade.clone()]);
+        assert_eq!(response.already_registered, [blade]);
+        assert_eq!(state.mint_stats.shoulder("x6").unwrap().registered, 1);
+        assert!(state.store.contains(&canonical).unwrap());
+
+        let again = register(vec![external], vec![]).await.unwrap().0;
+        assert_eq!(again.count, 0);
+        assert_eq!(again.already_registered, [canonical]);
+        assert_eq!(state.mint_stats.shoulder("x6").unwrap().registered, 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_handler_records_noid_and_ezid_exports() {
+        let state = create_test_state();
+        let check = crate::check_character::calculate_check_character("x6k7t01");
+        let import = |format: ImportFormat, body: String| {
+            import_handler(State(state.clone()), Query(ImportQuery { format }), body)
+        };
+
+        let dump = format!(
+            "HEADER=END\n :/template\n b3{{eedk}}\n {naan}/b3k7t01\\09:/c\n i|20190301|noid|0\n \
+             b3k7t02\\09:/c\n i|20190302|noid|0\n 99999/b3k7t03\\09:/c\n q|20190303|noid|0\n\
+             DATA=END\n",
+            naan = state.naan
+        );
+        let noid = import(ImportFormat::Noid, dump).await.unwrap().0;
+        assert_eq!((noid.found, noid.imported, noid.skipped), (3, 2, 1));
+        assert_eq!(noid.errors[0].line, 8);
+        assert_eq!(noid.errors[0].identifier, "ark:99999/b3k7t03");
+        assert!(
+            state
+                .store
+                .contains(&format!("ark:{}/b3k7t02", state.naan))
+                .unwrap()
+        );
+
+        let csv = format!(
+            "_id,_target\nark:/{naan}/x6k7t01{check},https://example.org/a\n\
+             ark:/{naan}/b3k7t01,https://example.org/b\ndoi:10.5072/FK2,https://example.org/c\n",
+            naan = state.naan
+        );
+        let ezid = import(ImportFormat::Ezid, csv).await.unwrap().0;
+        assert_eq!(
+            (
+                ezid.found,
+                ezid.imported,
+                ezid.already_registered,
+                ezid.skipped
+            ),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(ezid.errors[0].line, 4);
+        assert_eq!(state.mint_stats.shoulder("b3").unwrap().registered, 2);
+
+        let unreadable = import(ImportFormat::Ezid, "target\nx\n".to_string()).await;
+        assert!(matches!(unreadable, Err(AppError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mint_and_bind_handler_binds_targets() {
+        let state = create_test_state();
+        let binding = |target: &str| BindingRequest {
+            target: target.to_string(),
+            metadata: Default::default(),
+        };
+
+        // A bad target fails the whole request before anything is minted
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![
+                binding("https://example.org/a"),
+                binding("javascript:alert(1)"),
+            ],
+            status: ArkStatus::Active,
+        };
+        let result = mint_and_bind_handler(State(state.clone()), Json(payload)).await;
+        assert!(matches!(result, Err(AppError::InvalidRequest(_))));
+        assert!(state.mint_stats.shoulder("x6").is_none());
+
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![
+                binding("https://example.org/a"),
+                binding("https://example.org/b"),
+            ],
+            status: ArkStatus::Active,
+        };
+        let response = mint_and_bind_handler(State(state.clone()), Json(payload))
+            .await
+            .unwrap();
+        assert_eq!(response.0.count, 2);
+        assert_eq!(state.bindings.len(), Ok(2));
+
+        let resolve = |ark: String| {
+            let uri: axum::http::Uri = format!("/{}", ark).parse().unwrap();
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+        };
+        let bound = &response.0.arks[1];
+        let redirect = resolve(bound.ark.clone()).await.unwrap();
+        assert_eq!(
+            redirect.headers()[header::LOCATION],
+            "https://example.org/b"
+        );
+        // Qualified ARKs keep using the route pattern
+        let redirect = resolve(format!("{}/page2", bound.ark)).await.unwrap();
+        assert!(
+            redirect.headers()[header::LOCATION]
+                .to_str()
+                .unwrap()
+                .ends_with("/page2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mint_and_bind_handler_applies_default_metadata() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.shoulders.get_mut("x6").unwrap().default_metadata =
+            [("publisher", "Example Library"), ("rights", "CC BY 4.0")]
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+        let state = Arc::new(state);
+
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![
+                BindingRequest {
+                    target: "https://example.org/a".to_string(),
+                    metadata: Default::default(),
+                },
+                BindingRequest {
+                    target: "https://example.org/b".to_string(),
+                    metadata: [("rights", "CC0"), ("title", "B")]
+                        .into_iter()
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                },
+            ],
+            status: ArkStatus::Active,
+        };
+        let response = mint_and_bind_handler(State(state.clone()), Json(payload))
+            .await
+            .unwrap();
+        let metadata: Vec<_> = response
+            .0
+            .arks
+            .iter()
+            .map(|bound| serde_json::to_value(&bound.binding.metadata).unwrap())
+            .collect();
+        assert_eq!(
+            metadata,
+            [
+                serde_json::json!({"publisher": "Example Library", "rights": "CC BY 4.0"}),
+                serde_json::json!({"publisher": "Example Library", "rights": "CC0", "title": "B"}),
+            ]
+        );
+        let stored = state
+            .bindings
+            .get(&parse_ark(&response.0.arks[1].ark).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.metadata["publisher"], "Example Library");
+    }
+
+    #[tokio::test]
+    async fn test_reserved_arks_are_promoted_and_withdrawn() {
+        let state = create_test_state();
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![BindingRequest {
+                target: "https://example.org/draft".to_string(),
+                metadata: Default::default(),
+            }],
+            status: ArkStatus::Reserved,
+        };
+        let response = mint_and_bind_handler(State(state.clone()), Json(payload))
+            .await
+            .unwrap();
+        let ark = response.0.arks[0].ark.clone();
+        let resolve = || {
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(format!("/{}", ark).parse().unwrap()),
+                None,
+            )
+        };
+        let change = |status: ArkStatus| {
+            let payload = StatusChangeRequest {
+                ark: ark.clone(),
+                reason: Some("Curated".to_string()),
+            };
+            let state = State(state.clone());
+            async move {
+                match status {
+                    ArkStatus::Active => promote_handler(state, Json(payload)).await,
+                    _ => withdraw_handler(state, Json(payload)).await,
+                }
+            }
+        };
+
+        assert_eq!(
+            resolve().await.into_response().status(),
+            StatusCode::NOT_FOUND
+        );
+        // Reserved ARKs cannot be withdrawn, only promoted
+        assert!(matches!(
+            change(ArkStatus::Withdrawn).await,
+            Err(AppError::InvalidRequest(_))
+        ));
+
+        let promoted = change(ArkStatus::Active).await.unwrap();
+        assert_eq!(promoted.0.status, ArkStatus::Active);
+        assert_eq!(resolve().await.into_response().status(), StatusCode::FOUND);
+        assert!(matches!(
+            change(ArkStatus::Active).await,
+            Err(AppError::InvalidRequest(_))
+        ));
+
+        let withdrawn = change(ArkStatus::Withdrawn).await.unwrap();
+        assert_eq!(withdrawn.0.status, ArkStatus::Withdrawn);
+        assert_eq!(resolve().await.into_response().status(), StatusCode::GONE);
+        let binding = state
+            .bindings
+            .get(&parse_ark(&ark).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(binding.lifecycle.history.len(), 2);
+        assert_eq!(
+            binding.lifecycle.history[1].reason.as_deref(),
+            Some("Curated")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mint_and_bind_handler_rejects_non_initial_status() {
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![BindingRequest {
+                target: "https://example.org/a".to_string(),
+                metadata: Default::default(),
+            }],
+            status: ArkStatus::Withdrawn,
+        };
+        let result = mint_and_bind_handler(State(create_test_state()), Json(payload)).await;
+        assert!(matches!(result, Err(AppError::InvalidRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn test_batch_handler_applies_all_or_nothing() {
+        let state = create_test_state();
+        let a = format!("ark:{}/b3k7t01", state.naan);
+        let b = format!("ark:{}/b3k7t02", state.naan);
+        let batch = |operations: serde_json::Value| {
+            let payload: BatchRequest =
+                serde_json::from_value(serde_json::json!({ "operations": operations })).unwrap();
+            batch_handler(State(state.clone()), Json(payload))
+        };
+
+        // Failing operations are all reported, and nothing is written
+        let (status, response) = batch(serde_json::json!([
+            {"op": "bind", "ark": a, "target": "https://example.org/a"},
+            {"op": "set_metadata", "ark": a, "metadata": {"title": "A"}},
+            {"op": "bind", "ark": b, "target": "javascript:alert(1)"},
+            {"op": "rebind", "ark": b, "target": "https://example.org/b"},
+            {"op": "tombstone", "ark": "ark:99999/b3k7t03"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(!response.applied);
+        let failed: Vec<usize> = response
+            .results
+            .iter()
+            .filter(|result| !result.ok)
+            .map(|result| result.index)
+            .collect();
+        assert_eq!(failed, [2, 3, 4]);
+        assert_eq!(state.bindings.len(), Ok(0));
+        assert!(state.event_log.between(None, None).is_empty());
+
+        let (status, response) = batch(serde_json::json!([
+            {"op": "bind", "ark": format!("ark:/{}/b3-k7t01", state.naan), "target": "https://example.org/a"},
+            {"op": "set_metadata", "ark": a, "metadata": {"title": "A"}},
+            {"op": "bind", "ark": b, "target": "https://example.org/b"},
+            {"op": "tombstone", "ark": b, "reason": "Duplicate of A"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert!(response.applied);
+        assert_eq!(state.event_log.between(None, None).len(), 4);
+
+        let resolve = |ark: &str| {
+            let uri: axum::http::Uri = format!("/{}", ark).parse().unwrap();
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+        };
+        let redirect = resolve(&a).await.unwrap();
+        assert_eq!(
+            redirect.headers()[header::LOCATION],
+            "https://example.org/a"
+        );
+        assert!(matches!(resolve(&b).await, Err(AppError::Withdrawn)));
+        let parsed = parse_ark(&a).unwrap();
+        assert_eq!(
+            state.bindings.get(&parsed).unwrap().unwrap().metadata["title"],
+            "A"
+        );
+
+        // Null metadata values remove keys
+        let (status, _) = batch(serde_json::json!([
+            {"op": "set_metadata", "ark": a, "metadata": {"title": null}},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            state
+                .bindings
+                .get(&parsed)
+                .unwrap()
+                .unwrap()
+                .metadata
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_components_have_bindings_of_their_own() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state
+            .shoulders
+            .get_mut("b3")
+            .unwrap()
+            .components
+            .push("/page*".to_string());
+        let state = Arc::new(state);
+        let base = format!("ark:{}/b3k7t01", state.naan);
+        let page = format!("{}/page2", base);
+        let batch = |operations: serde_json::Value| {
+            let payload: BatchRequest =
+                serde_json::from_value(serde_json::json!({ "operations": operations })).unwrap();
+            batch_handler(State(state.clone()), Json(payload))
+        };
+
+        // Components start with the metadata of the ARK they are part of
+        let (status, _) = batch(serde_json::json!([
+            {"op": "bind", "ark": base, "target": "https://example.org/book",
+             "metadata": {"title": "Book", "rights": "CC0"}},
+            {"op": "bind", "ark": page, "target": "https://example.org/book/2",
+             "metadata": {"title": "Page 2"}},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let metadata = state
+            .bindings
+            .get(&parse_ark(&page).unwrap())
+            .unwrap()
+            .unwrap()
+            .metadata;
+        assert_eq!(metadata["title"], "Page 2");
+        assert_eq!(metadata["rights"], "CC0");
+
+        // Other qualifiers still have no binding of their own
+        let (status, _) = batch(serde_json::json!([
+            {"op": "bind", "ark": format!("{}/cover.jpg", base), "target": "https://example.org/c"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let resolve = |ark: &str| {
+            let uri: axum::http::Uri = format!("/{}", ark).parse().unwrap();
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+        };
+        let redirect = resolve(&page).await.unwrap();
+        assert_eq!(
+            redirect.headers()[header::LOCATION],
+            "https://example.org/book/2"
+        );
+        resolve(&base).await.unwrap();
+        let resolutions = state.resolution_stats.recent("b3");
+        assert_eq!((resolutions.last_24h, resolutions.components_24h), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_batch_handler_enforces_lifecycle() {
+        let state = create_test_state();
+        let [a, b, c] =
+            ["b3k7t01", "b3k7t02", "b3k7t03"].map(|blade| format!("ark:{}/{}", state.naan, blade));
+        let batch = |operations: serde_json::Value| {
+            let payload: BatchRequest =
+                serde_json::from_value(serde_json::json!({ "operations": operations })).unwrap();
+            batch_handler(State(state.clone()), Json(payload))
+        };
+        let resolve = |ark: &str| {
+            let uri: axum::http::Uri = format!("/{}", ark).parse().unwrap();
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+        };
+
+        // Tombstoned ARKs stay tombstoned
+        let (status, _) = batch(serde_json::json!([
+            {"op": "bind", "ark": a, "target": "https://example.org/a"},
+            {"op": "tombstone", "ark": a, "reason": "Duplicate"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let (status, response) = batch(serde_json::json!([
+            {"op": "rebind", "ark": a, "target": "https://example.org/a2"},
+            {"op": "set_status", "ark": a, "status": "active"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            response.results[1].error.as_deref(),
+            Some("cannot change status from tombstoned to active")
+        );
+        assert!(matches!(resolve(&a).await, Err(AppError::Withdrawn)));
+
+        // Reserved ARKs are unknown to the resolver until activated
+        let (status, response) = batch(serde_json::json!([
+            {"op": "bind", "ark": b, "target": "https://example.org/b", "status": "reserved"},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.results[0].status, Some(ArkStatus::Reserved));
+        assert!(matches!(resolve(&b).await, Err(AppError::NotFound(_))));
+
+        // Aliases stand in for active ARKs only
+        let (status, response) = batch(serde_json::json!([
+            {"op": "set_status", "ark": c, "status": "alias", "alias_of": b},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            response.results[0].error,
+            Some(format!(
+                "alias_of: {} is reserved; an alias must stand in for an active ARK",
+                b
+            ))
+        );
+
+        let (status, _) = batch(serde_json::json!([
+            {"op": "set_status", "ark": b, "status": "active", "reason": "Published"},
+            {"op": "set_status", "ark": c, "status": "alias", "alias_of": b},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let redirect = resolve(&c).await.unwrap();
+        assert_eq!(redirect.headers()[header::LOCATION], format!("/{}", b));
+
+        // An alias cannot be aliased in turn, so aliases never loop
+        let (status, response) = batch(serde_json::json!([
+            {"op": "set_status", "ark": b, "status": "alias", "alias_of": c},
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(
+            response.results[0]
+                .error
+                .as_ref()
+                .unwrap()
+                .contains("is alias")
+        );
+
+        let redirect = resolve(&b).await.unwrap();
+        assert_eq!(
+            redirect.headers()[header::LOCATION],
+            "https://example.org/b"
+        );
+        let lifecycle = state
+            .bindings
+            .get(&parse_ark(&b).unwrap())
+            .unwrap()
+            .unwrap()
+            .lifecycle;
+        assert_eq!(lifecycle.history.len(), 1);
+        assert_eq!(lifecycle.history[0].reason.as_deref(), Some("Published"));
     }
 
     #[tokio::test]
-    async fn test_health_check_handler() {
-        let result = health_check_handler().await;
-        assert_eq!(result, "OK");
-    }
+    async fn test_resolution_survives_binding_storage_outage() {
+        let store = Arc::new(SwitchableBindingStore::default());
+        store.set_down(true);
+        let state = Arc::new(AppState {
+            shoulders: [(
+                "x6".parse().unwrap(),
+                Shoulder {
+                    route_pattern: "https://example.org/${value}".to_string(),
+                    project_name: "Test Project".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            bindings: Bindings::new(store),
+            ..Default::default()
+        });
 
-    #[tokio::test]
-    async fn test_info_handler_returns_shoulder_info() {
-        let state = create_test_state();
-        let response = info_handler(State(state.clone())).await;
+        let uri: axum::http::Uri = "/ark:12345/x6np1wh8kq".parse().unwrap();
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://example.org/x6np1wh8kq"
+        );
+        assert_eq!(response.headers()[DEGRADED_HEADER], "bindings-unavailable");
 
-        assert_eq!(response.0.naan, "12345");
-        assert_eq!(response.0.shoulders.len(), 2);
+        // Probes keep the instance in rotation, while the status page shows the outage
+        let response = health_check_handler(State(state.clone()), HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"DEGRADED");
+        let report = status_report(&state);
+        assert_eq!(report.status, crate::status::OverallStatus::Degraded);
+        assert!(
+            report
+                .dependencies
+                .iter()
+                .any(|d| d.name == "bindings" && d.status == "failing")
+        );
 
-        // Check that shoulders are present
-        let shoulder_names: Vec<&str> = response
-            .0
-            .shoulders
-            .iter()
-            .map(|s| s.shoulder.as_str())
-            .collect();
-        assert!(shoulder_names.contains(&"x6"));
-        assert!(shoulder_names.contains(&"b3"));
+        // Binding needs the store, so it fails instead of handing out unbound ARKs
+        let payload = MintAndBindRequest {
+            shoulder: "x6".to_string(),
+            bindings: vec![BindingRequest {
+                target: "https://example.org/a".to_string(),
+                metadata: Default::default(),
+            }],
+            status: ArkStatus::Active,
+        };
+        let result = mint_and_bind_handler(State(state), Json(payload)).await;
+        assert_eq!(
+            result.unwrap_err().into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
     }
 
     #[tokio::test]
-    async fn test_mint_handler_success() {
-        let state = create_test_state();
-        let payload = MintRequest {
+    async fn test_mint_and_bind_journals_during_storage_outage() {
+        let path =
+            std::env::temp_dir().join(format!("ark-handler-journal-{}.jsonl", std::process::id()));
+        let store = Arc::new(SwitchableBindingStore::default());
+        store.set_down(true);
+        let state = Arc::new(AppState {
+            shoulders: [(
+                "x6".parse().unwrap(),
+                Shoulder {
+                    route_pattern: "https://example.org/${value}".to_string(),
+                    project_name: "Test Project".to_string(),
+                    ..Default::default()
+                },
+            )]
+            .into(),
+            bindings: Bindings::new(store.clone()),
+            mint_journal: Some(
+                MintJournal::open(&path, crate::journal::DEFAULT_RECONCILE_INTERVAL).unwrap(),
+            ),
+            ..Default::default()
+        });
+
+        let payload = MintAndBindRequest {
             shoulder: "x6".to_string(),
-            count: 3,
+            bindings: vec![BindingRequest {
+                target: "https://example.org/a".to_string(),
+                metadata: Default::default(),
+            }],
+            status: ArkStatus::Active,
         };
+        let response = mint_and_bind_handler(State(state.clone()), Json(payload))
+            .await
+            .unwrap();
+        assert!(response.0.journaled);
+        let journal = state.mint_journal.as_ref().unwrap();
+        assert_eq!(journal.pending(), 1);
 
-        let result = mint_handler(State(state), Json(payload)).await;
-        assert!(result.is_ok());
-
-        let response = result.unwrap();
-        assert_eq!(response.0.count, 3);
-        assert_eq!(response.0.arks.len(), 3);
+        store.set_down(false);
+        let report = journal.reconcile(&state.bindings).unwrap();
+        assert_eq!(report.applied, 1);
+        let ark = Ark::try_from(response.0.arks[0].ark.as_str()).unwrap();
+        assert_eq!(
+            state.bindings.get(&ark).unwrap().unwrap().target,
+            "https://example.org/a"
+        );
+        assert!(!path.exists());
+    }
 
-        // Verify ARKs have correct format (starts with ark:12345/x6)
-        for ark in &response.0.arks {
-            assert!(ark.starts_with("ark:12345/x6"));
-        }
+    #[test]
+    fn test_strip_prose_punctuation() {
+        assert_eq!(
+            strip_prose_punctuation("ark:12345/x6np1wh8kq)."),
+            Some("ark:12345/x6np1wh8kq")
+        );
+        assert_eq!(
+            strip_prose_punctuation("ark:12345/x6np1wh8kq/page2];"),
+            Some("ark:12345/x6np1wh8kq/page2")
+        );
+        assert_eq!(
+            strip_prose_punctuation("ark:12345/x6np1wh8kq/page(2))"),
+            Some("ark:12345/x6np1wh8kq/page(2)")
+        );
+        assert_eq!(strip_prose_punctuation("ark:12345/x6np1wh8kq"), None);
+        assert_eq!(strip_prose_punctuation("ark:12345/x6np1wh8kq?info."), None);
     }
 
     #[tokio::test]
-    async fn test_mint_handler_invalid_shoulder() {
+    async fn test_resolve_strips_trailing_punctuation() {
         let state = create_test_state();
-        let payload = MintRequest {
-            shoulder: "z9".to_string(), // Unregistered shoulder
-            count: 1,
+        let resolve = |path: &str| {
+            let uri: axum::http::Uri = path.parse().unwrap();
+            resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
         };
 
-        let result = mint_handler(State(state), Json(payload)).await;
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), AppError::ShoulderNotFound));
+        let response = resolve("/ark:12345/x6np1wh8kq),").await.unwrap();
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://example.org/x6np1wh8kq"
+        );
+        assert_eq!(state.input_limits.report().punctuation_stripped, 1);
+
+        // An ARK bound with its punctuation keeps it
+        let exact = Ark::try_from("ark:12345/x6np1wh8kq.").unwrap();
+        state
+            .bindings
+            .bind_all([(
+                exact,
+                Binding {
+                    target: "https://example.org/exact".to_string(),
+                    metadata: Default::default(),
+                    lifecycle: Default::default(),
+                    bound_at: Utc::now(),
+                },
+            )])
+            .unwrap();
+        let response = resolve("/ark:12345/x6np1wh8kq.").await.unwrap();
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://example.org/exact"
+        );
+        assert_eq!(state.input_limits.report().punctuation_stripped, 1);
+
+        // Stripping that still finds nothing is not counted
+        assert!(resolve("/ark:12345/zz9np1wh8kq.").await.is_err());
+        assert_eq!(state.input_limits.report().punctuation_stripped, 1);
     }
 
     #[tokio::test]
@@ -257,9 +4608,10 @@ mod tests {
                 "ark:12345/b3data456".to_string(),
             ],
             has_check_character: None,
+            debug: false,
         };
 
-        let response = validate_handler(State(state), Json(payload)).await;
+        let response = validate_handler(State(state), HeaderMap::new(), Json(payload)).await;
         assert_eq!(response.0.results.len(), 2);
 
         // Verify handler returns results for each ARK
@@ -267,12 +4619,176 @@ mod tests {
         assert_eq!(response.0.results[1].ark, "ark:12345/b3data456");
     }
 
+    #[tokio::test]
+    async fn test_validate_handler_truncates_oversized_batches() {
+        let state = Arc::new(AppState {
+            max_validate_count: 2,
+            ..Default::default()
+        });
+        let payload = ValidateRequest {
+            arks: vec!["ark:12345/x6test123".to_string(); 3],
+            has_check_character: Some(false),
+            debug: false,
+        };
+        let response = validate_handler(State(state), HeaderMap::new(), Json(payload)).await;
+        assert_eq!(response.0.results.len(), 2);
+        assert!(response.0.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_validate_handler_parallel_batches_keep_order() {
+        let state = Arc::new(AppState {
+            validation_pool: ValidationPool::new(2, 4).unwrap(),
+            ..Default::default()
+        });
+        let arks: Vec<String> = (0..20)
+            .map(|i| format!("ark:12345/x6test{}", i))
+            .chain(["not an ark".to_string()])
+            .collect();
+        let payload = ValidateRequest {
+            arks: arks.clone(),
+            has_check_character: Some(false),
+            debug: true,
+        };
+        let response = validate_handler(State(state), HeaderMap::new(), Json(payload)).await;
+
+        let returned: Vec<&str> = response.0.results.iter().map(|r| r.ark.as_str()).collect();
+        assert_eq!(returned, arks);
+        assert!(!response.0.results[20].valid);
+        assert!(response.0.results.iter().all(|r| r.elapsed_us.is_some()));
+        let timing = response.0.timing.unwrap();
+        assert!(timing.parallel);
+        assert_eq!(timing.threads, 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_handler_localizes_messages() {
+        let state = create_test_state();
+        let payload = ValidateRequest {
+            arks: vec!["not-an-ark".to_string()],
+            has_check_character: None,
+            debug: false,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_LANGUAGE, "fr".parse().unwrap());
+
+        let response = validate_handler(State(state), headers, Json(payload)).await;
+        let result = &response.0.results[0];
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Impossible d'analyser la structure de l'ARK")
+        );
+        assert_eq!(result.error_code, Some(ValidationCode::Parse));
+    }
+
+    #[tokio::test]
+    async fn test_compare_handler_groups_equivalent_arks() {
+        let payload = CompareRequest {
+            arks: vec![
+                "ark:12345/x6np1wh8k".to_string(),
+                "not-an-ark".to_string(),
+                "ark:/12345/x6-np1wh8k?foo=bar".to_string(),
+                "ark:12345/b3data456".to_string(),
+            ],
+            pairs: vec![
+                (
+                    "ark:12345/x6np1wh8k".to_string(),
+                    "ark:12345/x6np1wh8k/".to_string(),
+                ),
+                (
+                    "ark:12345/x6np1wh8k".to_string(),
+                    "ark:12345/b3data456".to_string(),
+                ),
+                ("not-an-ark".to_string(), "not-an-ark".to_string()),
+            ],
+        };
+
+        let response = compare_handler(State(create_test_state()), HeaderMap::new(), Json(payload))
+            .await
+            .0;
+
+        assert_eq!(
+            response.results[2].normalized.as_deref(),
+            Some("ark:12345/x6np1wh8k")
+        );
+        assert_eq!(
+            response.results[2].transformations,
+            vec![
+                Normalization::QueryRemoved,
+                Normalization::LabelSlashRemoved,
+                Normalization::HyphensRemoved,
+            ]
+        );
+        assert_eq!(response.results[1].error_code, Some(ValidationCode::Parse));
+
+        assert_eq!(response.groups.len(), 2);
+        assert_eq!(response.groups[0].indices, vec![0, 2]);
+        assert_eq!(response.groups[1].arks, vec!["ark:12345/b3data456"]);
+
+        let equivalent: Vec<bool> = response.pairs.iter().map(|p| p.equivalent).collect();
+        assert_eq!(equivalent, vec![true, false, false]);
+        assert_eq!(
+            response.pairs[0].right.transformations,
+            vec![Normalization::TrailingPunctuationRemoved]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_arks_are_rejected() {
+        let mut state = Arc::into_inner(create_test_state()).unwrap();
+        state.input_limits = InputLimits::new(64, 12, 16);
+        let state = Arc::new(state);
+        let resolve = |path: String| {
+            let state = state.clone();
+            async move {
+                resolve_handler(
+                    State(state),
+                    Method::GET,
+                    HeaderMap::new(),
+                    OriginalUri(path.parse().unwrap()),
+                    None,
+                )
+                .await
+            }
+        };
+
+        let long = format!("/ark:12345/x6{}", "b".repeat(80));
+        assert!(matches!(resolve(long).await, Err(AppError::UriTooLong)));
+        let blade = format!("/ark:12345/x6{}", "b".repeat(13));
+        assert!(matches!(resolve(blade).await, Err(AppError::InputTooLong)));
+        let qualifier = "/ark:12345/x6np1wh8k/chapter1/page2.pdf".to_string();
+        assert!(matches!(
+            resolve(qualifier).await,
+            Err(AppError::InputTooLong)
+        ));
+
+        let payload = ValidateRequest {
+            arks: vec![format!("ark:12345/x6{}", "b".repeat(13))],
+            has_check_character: None,
+            debug: false,
+        };
+        let response =
+            validate_handler(State(state.clone()), HeaderMap::new(), Json(payload)).await;
+        assert_eq!(
+            response.0.results[0].error_code,
+            Some(ValidationCode::TooLong)
+        );
+        assert_eq!(state.input_limits.report().rejected.blade, 2);
+    }
+
     #[tokio::test]
     async fn test_resolve_handler_success() {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_ok());
 
         // Handler returns a redirect - verify it produces a response
@@ -284,12 +4800,85 @@ mod tests {
         assert_eq!(location, "https://example.org/x6np1wh8k");
     }
 
+    #[tokio::test]
+    async fn test_resolve_handler_service_status() {
+        let state = create_test_state();
+        let status = |state: Arc<AppState>, method: Method, path: &'static str| {
+            resolve_handler(
+                State(state),
+                method,
+                HeaderMap::new(),
+                OriginalUri(axum::http::Uri::from_static(path)),
+                None,
+            )
+        };
+
+        let response = status(state.clone(), Method::GET, "/ark:12345/servicestatus")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(matches!(
+            status(state.clone(), Method::POST, "/ark:12345/servicestatus").await,
+            Err(AppError::MethodNotAllowed)
+        ));
+
+        // Only the NAAN of the state answers
+        let state = Arc::new(AppState {
+            naan: "99999".parse().unwrap(),
+            ..Default::default()
+        });
+        let response = status(state.clone(), Method::GET, "/ark:99999/servicestatus")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            status(state, Method::GET, "/ark:12345/servicestatus")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_old_style_path() {
+        for path in [
+            "/ark:/12345/x6np1wh8k",
+            "/ark:%2F12345/x6np1wh8k",
+            "/ark:%2f12345/x6np1wh8k",
+        ] {
+            let state = create_test_state();
+            let uri = axum::http::Uri::from_static(path);
+
+            let response = resolve_handler(
+                State(state),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+            .await
+            .unwrap()
+            .into_response();
+            assert_eq!(response.status(), StatusCode::FOUND, "{}", path);
+            assert_eq!(
+                response.headers().get(header::LOCATION).unwrap(),
+                "https://example.org/x6np1wh8k"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_resolve_handler_with_qualifier() {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k/page2.pdf");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_ok());
 
         // Handler returns a redirect - verify it produces a response
@@ -306,7 +4895,14 @@ mod tests {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:99999/x6np1wh8k");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::InvalidNaan));
     }
@@ -316,17 +4912,67 @@ mod tests {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:12345/z9unknown");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::ShoulderNotFound));
     }
 
+    #[tokio::test]
+    async fn test_resolve_handler_unregistered_inflection_stub() {
+        let resolve = |state: Arc<AppState>, uri: &'static str| {
+            resolve_handler(
+                State(state),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(axum::http::Uri::from_static(uri)),
+                None,
+            )
+        };
+        let state = Arc::new(AppState {
+            unregistered_inflections: UnregisteredInflections::Stub,
+            ..Arc::into_inner(create_test_state()).unwrap()
+        });
+
+        let response = resolve(state.clone(), "/ark:12345/z9unknown/page2?info")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stub: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(stub["ark"], "ark:12345/z9unknown/page2");
+        assert_eq!(stub["naan"], "12345");
+        assert_eq!(stub["shoulder"], "z9");
+        assert_eq!(stub["blade"], "unknown");
+        assert_eq!(stub["qualifier"], "page2");
+        assert_eq!(stub["status"], "unknown_namespace");
+
+        // Only inflections get the stub
+        let result = resolve(state, "/ark:12345/z9unknown").await;
+        assert!(matches!(result, Err(AppError::ShoulderNotFound)));
+    }
+
     #[tokio::test]
     async fn test_resolve_handler_invalid_ark_format() {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:invalid");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AppError::InvalidArk));
     }
@@ -336,7 +4982,14 @@ mod tests {
         let state = create_test_state();
         let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k?info");
 
-        let result = resolve_handler(State(state), OriginalUri(uri)).await;
+        let result = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
         assert!(result.is_ok());
 
         let response = result.unwrap().into_response();
@@ -346,4 +4999,680 @@ mod tests {
         let location = response.headers().get(header::LOCATION).unwrap();
         assert_eq!(location, "https://example.org/x6np1wh8k?info");
     }
+
+    #[tokio::test]
+    async fn test_resolve_handler_canonical_link_header() {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Test Project".to_string(),
+                ..Default::default()
+            },
+        );
+        let state = Arc::new(AppState {
+            shoulders,
+            public_base_url: Some("https://ark.example.org".to_string()),
+            canonical_link_rel: Some("cite-as".to_string()),
+            ..Default::default()
+        });
+
+        // Hyphens, query strings, and trailing punctuation don't appear in the canonical form
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np-1wh8k/page2.pdf/?foo=bar");
+        let response = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(
+            response.headers().get(header::LINK).unwrap(),
+            "<https://ark.example.org/ark:12345/x6np1wh8k/page2.pdf>; rel=\"cite-as\""
+        );
+
+        // Disabled by default
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
+        let response = resolve_handler(
+            State(create_test_state()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert!(response.headers().get(header::LINK).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_noindex_header() {
+        let state = create_test_state();
+
+        // b3 opts out of indexing
+        let uri = axum::http::Uri::from_static("/ark:12345/b3np1wh8k");
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.headers().get("x-robots-tag").unwrap(), "noindex");
+
+        // x6 keeps the default
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
+        let response = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert!(response.headers().get("x-robots-tag").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_adds_shoulder_response_headers() {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder::builder()
+                .route_pattern("https://viewer.example.org/${value}")
+                .project_name("Viewer")
+                .uses_check_character(false)
+                .response_header("Access-Control-Allow-Origin", "*")
+                .response_header("X-Collection", "maps")
+                .build()
+                .unwrap(),
+        );
+        let state = Arc::new(AppState {
+            shoulders,
+            ..Default::default()
+        });
+
+        for path in ["/ark:12345/x6np1wh8k", "/ark:12345/x6np1wh8k?info"] {
+            let response = resolve_handler(
+                State(state.clone()),
+                Method::GET,
+                HeaderMap::new(),
+                OriginalUri(path.parse().unwrap()),
+                None,
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::FOUND, "{}", path);
+            let headers = response.headers();
+            assert_eq!(headers.get("access-control-allow-origin").unwrap(), "*");
+            assert_eq!(headers.get("x-collection").unwrap(), "maps");
+        }
+
+        // Errors are answered without them
+        let response = resolve_handler(
+            State(state),
+            Method::POST,
+            HeaderMap::new(),
+            OriginalUri("/ark:12345/x6np1wh8k".parse().unwrap()),
+            None,
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert!(response.headers().get("x-collection").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_method_redirects() {
+        let mut shoulders = HashMap::new();
+        for (name, method_redirect) in [
+            ("x6", Some(MethodRedirect::Temporary)),
+            ("b3", Some(MethodRedirect::Permanent)),
+            ("c4", None),
+        ] {
+            shoulders.insert(
+                name.parse().unwrap(),
+                Shoulder {
+                    route_pattern: "https://api.example.org/${value}".to_string(),
+                    project_name: "API".to_string(),
+                    uses_check_character: false,
+                    method_redirect,
+                    ..Default::default()
+                },
+            );
+        }
+        let state = Arc::new(AppState {
+            shoulders,
+            ..Default::default()
+        });
+
+        let cases = [
+            (Method::GET, "x6", StatusCode::FOUND),
+            (Method::HEAD, "x6", StatusCode::FOUND),
+            (Method::POST, "x6", StatusCode::TEMPORARY_REDIRECT),
+            (Method::PUT, "x6", StatusCode::TEMPORARY_REDIRECT),
+            (Method::PATCH, "x6", StatusCode::TEMPORARY_REDIRECT),
+            (Method::DELETE, "x6", StatusCode::TEMPORARY_REDIRECT),
+            (Method::POST, "b3", StatusCode::PERMANENT_REDIRECT),
+            (Method::PUT, "b3", StatusCode::PERMANENT_REDIRECT),
+            (Method::GET, "c4", StatusCode::FOUND),
+            (Method::POST, "c4", StatusCode::METHOD_NOT_ALLOWED),
+            (Method::PUT, "c4", StatusCode::METHOD_NOT_ALLOWED),
+        ];
+        for (method, shoulder, expected) in cases {
+            let uri: axum::http::Uri = format!("/ark:12345/{}np1wh8k", shoulder).parse().unwrap();
+            let response = resolve_handler(
+                State(state.clone()),
+                method.clone(),
+                HeaderMap::new(),
+                OriginalUri(uri),
+                None,
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), expected, "{} {}", method, shoulder);
+
+            if expected == StatusCode::METHOD_NOT_ALLOWED {
+                assert_eq!(response.headers().get(header::ALLOW).unwrap(), "GET, HEAD");
+            } else {
+                assert_eq!(
+                    response
+                        .headers()
+                        .get(header::LOCATION)
+                        .unwrap()
+                        .to_str()
+                        .unwrap(),
+                    format!("https://api.example.org/{}np1wh8k", shoulder)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_take_resolution_override() {
+        assert_eq!(
+            take_resolution_override("/ark:12345/x6abc?_format=json"),
+            (
+                "/ark:12345/x6abc".to_string(),
+                Some(ResolutionView::Description)
+            )
+        );
+        assert_eq!(
+            take_resolution_override("/ark:12345/x6abc/p.pdf?page=2&_redirect=false"),
+            (
+                "/ark:12345/x6abc/p.pdf?page=2".to_string(),
+                Some(ResolutionView::Description)
+            )
+        );
+        assert_eq!(
+            take_resolution_override("/ark:12345/x6abc?_format=xml"),
+            ("/ark:12345/x6abc?_format=xml".to_string(), None)
+        );
+        assert_eq!(
+            take_resolution_override("/ark:12345/x6abc?"),
+            ("/ark:12345/x6abc?".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_negotiated_view() {
+        let accept = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ACCEPT, value.parse().unwrap());
+            negotiated_view(&headers)
+        };
+        assert_eq!(accept("application/xml"), Some(ResolutionView::DublinCore));
+        assert_eq!(
+            accept("text/xml, */*;q=0.1"),
+            Some(ResolutionView::DublinCore)
+        );
+        assert_eq!(accept("text/turtle"), Some(ResolutionView::Turtle));
+        assert_eq!(
+            accept("application/xml;q=0.5, text/turtle"),
+            Some(ResolutionView::Turtle)
+        );
+        assert_eq!(
+            accept("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+            None
+        );
+        assert_eq!(accept("*/*"), None);
+        assert_eq!(negotiated_view(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_turtle() {
+        let state = create_test_state();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "text/turtle".parse().unwrap());
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
+
+        let response = resolve_handler(State(state), Method::GET, headers, OriginalUri(uri), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("text/turtle")
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let turtle = String::from_utf8(body.to_vec()).unwrap();
+        assert!(turtle.contains("\n<ark:12345/x6np1wh8k>\n"));
+        assert!(turtle.contains("schema:url <https://example.org/x6np1wh8k>"));
+        assert!(turtle.contains("schema:creativeWorkStatus \"active\""));
+        assert!(turtle.contains("changed by environment via SHOULDERS"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_delegated_metadata() {
+        let state = Arc::new(AppState {
+            naan: "12345".parse().unwrap(),
+            shoulders: HashMap::from([(
+                "m5".parse().unwrap(),
+                Shoulder {
+                    route_pattern: "https://example.org/${value}".to_string(),
+                    metadata_route_pattern: Some("https://meta.example.org/${value}".to_string()),
+                    project_name: "Delegated".to_string(),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        });
+
+        let uri = axum::http::Uri::from_static("/ark:12345/m5item");
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://example.org/m5item"
+        );
+        assert_eq!(
+            response.headers()[header::LINK],
+            "<https://meta.example.org/m5item>; rel=\"describedby\""
+        );
+
+        let uri = axum::http::Uri::from_static("/ark:12345/m5item?info");
+        let response = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://meta.example.org/m5item"
+        );
+        assert!(response.headers().get(header::LINK).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_forwards_inflections() {
+        // An upstream NMA that answers inflections on the query string it receives
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream = listener.local_addr().unwrap();
+        let app = axum::Router::new()
+            .route(
+                "/items/{id}",
+                axum::routing::get(|uri: axum::http::Uri| async move {
+                    format!("erc:\nwhat: upstream {}\n", uri.query().unwrap_or_default())
+                }),
+            )
+            .route(
+                "/slow/{id}",
+                axum::routing::get(|| async {
+                    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                    "erc:\n"
+                }),
+            );
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // Nothing listens on a port once its listener is dropped
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let shoulder = |host: std::net::SocketAddr| Shoulder {
+            route_pattern: format!("http://{}/items/${{value}}", host),
+            project_name: "Delegated".to_string(),
+            forward_inflections: true,
+            ..Default::default()
+        };
+        let state = Arc::new(AppState {
+            naan: "12345".parse().unwrap(),
+            shoulders: HashMap::from([
+                ("u5".parse().unwrap(), shoulder(upstream)),
+                ("d5".parse().unwrap(), shoulder(closed)),
+                (
+                    "s5".parse().unwrap(),
+                    Shoulder {
+                        route_pattern: format!("http://{}/slow/${{value}}", upstream),
+                        ..shoulder(upstream)
+                    },
+                ),
+            ]),
+            ..Default::default()
+        });
+
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        let uri = axum::http::Uri::from_static("/ark:12345/u5item?info");
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, "erc:\nwhat: upstream info\n");
+
+        let uri = axum::http::Uri::from_static("/ark:12345/d5item??");
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let record = body(response).await;
+        assert!(record.contains("what: Delegated\n"), "{}", record);
+        assert!(record.contains(&format!("where: http://{}/items/d5item\n", closed)));
+
+        // An upstream slower than the request's deadline gets no local answer either
+        let uri = axum::http::Uri::from_static("/ark:12345/s5item?info");
+        let deadline = Deadline::after(std::time::Duration::from_millis(50));
+        let result = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            Some(Extension(deadline)),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::DeadlineExceeded)));
+
+        // Requests without an inflection are still redirected
+        let uri = axum::http::Uri::from_static("/ark:12345/u5item?page=2");
+        let response = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_dublin_core() {
+        let state = create_test_state();
+
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k?format=oai_dc");
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("application/xml")
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let xml = String::from_utf8(body.to_vec()).unwrap();
+        assert!(xml.contains("<dc:identifier>ark:12345/x6np1wh8k</dc:identifier>"));
+        assert!(xml.contains("<dc:identifier>https://example.org/x6np1wh8k</dc:identifier>"));
+        assert!(xml.contains("<dc:publisher>Test Team</dc:publisher>"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "application/xml".parse().unwrap());
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
+        let response = resolve_handler(State(state), Method::GET, headers, OriginalUri(uri), None)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_describes_resolution() {
+        let state = create_test_state();
+        let uri =
+            axum::http::Uri::from_static("/ark:12345/x6np1wh8k/page2.pdf?lang=fr&_format=json");
+
+        let response = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::LOCATION).is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["shoulder"], "x6");
+        assert_eq!(json["qualifier"], "page2.pdf?lang=fr");
+        assert_eq!(json["project_name"], "Test Project");
+        // Other query parameters are still forwarded to the target
+        assert_eq!(
+            json["target"],
+            "https://example.org/x6np1wh8k/page2.pdf?lang=fr"
+        );
+        assert_eq!(json["redirect_status"], 302);
+
+        // Unrelated parameters keep the redirect
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k?_format=xml");
+        let response = resolve_handler(
+            State(state),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://example.org/x6np1wh8k?_format=xml"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_handler_blocks_invalid_target() {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                // Not validated here, and resolves to a relative (invalid) redirect target
+                route_pattern: "${value}".to_string(),
+                project_name: "Broken".to_string(),
+                ..Default::default()
+            },
+        );
+        let state = Arc::new(AppState {
+            shoulders,
+            ..Default::default()
+        });
+        let uri = axum::http::Uri::from_static("/ark:12345/x6np1wh8k");
+
+        let result = resolve_handler(
+            State(state.clone()),
+            Method::GET,
+            HeaderMap::new(),
+            OriginalUri(uri),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::BlockedRedirect)));
+        assert_eq!(
+            result.unwrap_err().into_response().status(),
+            StatusCode::BAD_GATEWAY
+        );
+
+        let quarantined = state.quarantine.list();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].ark, "ark:12345/x6np1wh8k");
+        assert_eq!(quarantined[0].target, "x6np1wh8k");
+        assert_eq!(quarantined[0].route_pattern, "${value}");
+    }
+
+    #[tokio::test]
+    async fn test_export_config_handler_includes_provenance() {
+        let state = create_test_state();
+        let response = export_config_handler(State(state)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(document["naan"], "12345");
+        assert_eq!(
+            document["shoulders"]["x6"]["provenance"]["changed_by"],
+            "environment"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_config_handler_stages_document() {
+        let state = create_test_state();
+        let mut document = live_config_document(&state);
+        document.shoulders.remove("b3");
+
+        let result = import_config_handler(
+            State(state.clone()),
+            Extension(AdminIdentity("alice".to_string())),
+            Json(document),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.0.staged_by, "alice");
+        assert_eq!(result.0.changes.len(), 1);
+
+        // Live configuration is untouched until the staged document is applied
+        assert!(state.shoulders.contains_key("b3"));
+        assert!(staged_config_handler(State(state.clone())).await.is_ok());
+
+        let discarded = discard_staged_config_handler(
+            State(state.clone()),
+            Extension(AdminIdentity("alice".to_string())),
+        )
+        .await;
+        assert_eq!(discarded.unwrap(), StatusCode::NO_CONTENT);
+        assert!(matches!(
+            staged_config_handler(State(state)).await.unwrap_err(),
+            AppError::NoStagedConfig
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_handlers() {
+        let state = create_test_state();
+        let mut delivery = Delivery::new(
+            "https://hooks.example.org/x6",
+            "x6",
+            serde_json::json!({"shoulder": "x6"}),
+        );
+        delivery.status = DeliveryStatus::Delivered;
+        state.store.save_delivery(&delivery).unwrap();
+
+        let listed =
+            |status| deliveries_handler(State(state.clone()), Query(DeliveriesQuery { status }));
+        assert_eq!(listed(None).await.unwrap().0, [delivery.clone()]);
+        assert!(
+            listed(Some(DeliveryStatus::Failed))
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+
+        let retry = |id: &str| {
+            retry_delivery_handler(
+                State(state.clone()),
+                Extension(AdminIdentity("alice".to_string())),
+                Path(id.to_string()),
+            )
+        };
+        assert!(matches!(
+            retry("unknown").await.unwrap_err(),
+            AppError::NotFound(_)
+        ));
+        assert!(matches!(
+            retry(&delivery.id).await.unwrap_err(),
+            AppError::InvalidRequest(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_config_handler_rejects_invalid_pattern() {
+        let state = create_test_state();
+        let mut document = live_config_document(&state);
+        document
+            .shoulders
+            .get_mut("x6")
+            .unwrap()
+            .config
+            .route_pattern = "javascript:alert(1)".to_string();
+
+        let result = import_config_handler(
+            State(state),
+            Extension(AdminIdentity("alice".to_string())),
+            Json(document),
+        )
+        .await;
+        assert!(matches!(result.unwrap_err(), AppError::InvalidConfig(_)));
+    }
 }
@@ -0,0 +1,26 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::config::AppState;
+use crate::key_usage::Endpoint;
+use crate::server::auth;
+
+/// Middleware counting the calls made with each API key
+///
+/// Calls are counted whether or not they succeed, rate-limited ones included, so the counts
+/// show the load each integration puts on the service.
+pub async fn count_key_usage(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(key) = auth::usage_key(&state, request.headers()) {
+        let endpoint = Endpoint::of(request.uri().path(), state.admin_api_path.as_deref());
+        state.key_usage.record(&key, endpoint);
+    }
+    next.run(request).await
+}
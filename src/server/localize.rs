@@ -0,0 +1,90 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::ErrorMessage;
+use crate::i18n::Locale;
+
+/// Middleware rewriting public error messages in the language negotiated from `Accept-Language`
+pub async fn localize_errors(request: Request, next: Next) -> Response {
+    let locale = Locale::from_headers(request.headers());
+    let mut response = next.run(request).await;
+
+    let Some(ErrorMessage(key)) = response.extensions().get::<ErrorMessage>().copied() else {
+        return response;
+    };
+
+    if locale != Locale::En {
+        *response.body_mut() = Body::from(locale.message(key));
+        response.headers_mut().remove(header::CONTENT_LENGTH);
+    }
+    response.headers_mut().insert(
+        header::CONTENT_LANGUAGE,
+        HeaderValue::from_static(locale.tag()),
+    );
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-language"));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use axum::{Router, middleware, routing::get};
+    use tower::ServiceExt;
+
+    async fn fail() -> Result<&'static str, AppError> {
+        Err(AppError::ShoulderNotFound)
+    }
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/fail", get(fail))
+            .route("/ok", get(|| async { "fine" }))
+            .layer(middleware::from_fn(localize_errors))
+    }
+
+    #[tokio::test]
+    async fn test_localizes_error_messages() {
+        let request = Request::get("/fail")
+            .header(header::ACCEPT_LANGUAGE, "fr-FR,fr;q=0.9")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "fr");
+        assert_eq!(body_text(response).await, "Préfixe introuvable");
+
+        let request = Request::get("/fail").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "en");
+        assert_eq!(body_text(response).await, "Shoulder not found");
+    }
+
+    #[tokio::test]
+    async fn test_leaves_other_responses_alone() {
+        let request = Request::get("/ok")
+            .header(header::ACCEPT_LANGUAGE, "fr")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert!(response.headers().get(header::CONTENT_LANGUAGE).is_none());
+        assert_eq!(body_text(response).await, "fine");
+    }
+}
@@ -1,40 +1,470 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::api_keys::{ApiKey, Scope};
+use crate::ark::Normalization;
+use crate::batch::{BatchOperation, OperationResult};
+use crate::bindings::Binding;
+use crate::blade_advisor::{BladeLengthAdvice, Escalation};
+use crate::deliveries::DeliveryStatus;
+use crate::export::ExportFormat;
+use crate::import::ImportFormat;
+use crate::key_usage::{Granularity, UsageBucket, UsageCounts, UsageFormat};
+use crate::lifecycle::ArkStatus;
+use crate::names::{Naan, ShoulderName};
+use crate::read_only::ReadOnlyStatus;
+use crate::shoulder::Contact;
+use crate::snapshot::SnapshotSummary;
+use crate::status::Severity;
+use crate::validation::ValidationCode;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MintRequest {
     pub shoulder: String,
     #[serde(default = "default_count")]
     pub count: usize,
+    /// Return each ARK as a [`MintedArkDetail`] instead of a bare string
+    #[serde(default)]
+    pub detail: bool,
+    /// Idempotency key, for clients that cannot set the `Idempotency-Key` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Generate example ARKs without recording or reserving them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    /// Kept in the mint ledger with every ARK minted, such as who asked for them and why
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
 fn default_count() -> usize {
     1
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MintAndBindRequest {
+    pub shoulder: String,
+    /// One ARK is minted per binding
+    pub bindings: Vec<BindingRequest>,
+    /// `reserved` to keep the ARKs from resolving until they are promoted (default: `active`)
+    #[serde(default)]
+    pub status: ArkStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BindingRequest {
+    pub target: String,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintAndBindResponse {
+    pub arks: Vec<BoundArk>,
+    pub count: usize,
+    /// The binding store was down, so the bindings were journaled and take effect once it is back
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub journaled: bool,
+}
+
+/// A minted ARK with the binding recorded for it
+#[derive(Debug, Serialize)]
+pub struct BoundArk {
+    pub ark: String,
+    #[serde(flatten)]
+    pub binding: Binding,
+}
+
+/// A URL to derive a short ARK for, on the URL shortener's shoulder
+#[derive(Debug, Deserialize)]
+pub struct ShortenRequest {
+    pub url: String,
+    /// Content hash, like `sha256:9f86d0...`, to derive the ARK from instead of the URL, so
+    /// the ARK follows the content when it moves
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShortenResponse {
+    pub ark: String,
+    /// Where the ARK resolves on this service; requires `PUBLIC_BASE_URL`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_url: Option<String>,
+    pub target: String,
+    pub status: ArkStatus,
+    /// The ARK was bound by this request rather than derived from the same URL or hash before
+    pub created: bool,
+}
+
+/// ARKs created outside the service, given whole or as blades on one shoulder
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    #[serde(default)]
+    pub arks: Vec<String>,
+    /// Shoulder of `blades`
+    pub shoulder: Option<String>,
+    #[serde(default)]
+    pub blades: Vec<String>,
+}
+
+/// Normalized forms of the ARKs a register request recorded, and of those already recorded
+#[derive(Debug, Serialize)]
+pub struct RegisterResponse {
+    pub registered: Vec<String>,
+    pub already_registered: Vec<String>,
+    pub count: usize,
+}
+
+/// Kind of export an import request body holds
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    pub format: ImportFormat,
+}
+
+/// What an import recorded, and a sample of the identifiers it skipped
+#[derive(Debug, Default, Serialize)]
+pub struct ImportResponse {
+    /// Identifiers found in the export
+    pub found: usize,
+    pub imported: usize,
+    /// ARKs the ledger held before, or repeated within the export
+    pub already_registered: usize,
+    /// Identifiers that are not valid ARKs on a configured shoulder
+    pub skipped: usize,
+    /// The first skipped identifiers, with why
+    pub errors: Vec<ImportError>,
+}
+
+/// An identifier an import skipped
+#[derive(Debug, Serialize)]
+pub struct ImportError {
+    pub line: usize,
+    pub identifier: String,
+    pub reason: String,
+}
+
+/// Binding changes to apply together, in order
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOperation>,
+}
+
+/// Result of every operation of a batch; when one failed, `applied` is false and nothing changed
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub applied: bool,
+    pub results: Vec<OperationResult>,
+}
+
+/// An ARK to promote or withdraw, with the reason kept in its lifecycle history
+#[derive(Debug, Deserialize)]
+pub struct StatusChangeRequest {
+    pub ark: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// An ARK's status after a promotion or withdrawal
+#[derive(Debug, Serialize)]
+pub struct StatusChangeResponse {
+    pub ark: String,
+    pub status: ArkStatus,
+}
+
+/// The log filter in effect, as `EnvFilter` directives
+#[derive(Debug, Serialize)]
+pub struct LogLevelResponse {
+    pub filter: String,
+}
+
+/// Whether the service is read-only, and why, since when, and by whom if it is
+#[derive(Debug, Serialize)]
+pub struct ReadOnlyResponse {
+    pub read_only: bool,
+    #[serde(flatten)]
+    pub status: Option<ReadOnlyStatus>,
+}
+
+/// Number of replicated changes a standby applied
+#[derive(Debug, Serialize)]
+pub struct AppliedChangesResponse {
+    pub applied: usize,
+}
+
+/// Why the service is made read-only, shown to admins and on the status page
+#[derive(Debug, Default, Deserialize)]
+pub struct EnableReadOnlyRequest {
+    pub reason: Option<String>,
+}
+
+/// Name of a snapshot to take; named after the time it is taken if not given
+#[derive(Debug, Default, Deserialize)]
+pub struct TakeSnapshotRequest {
+    pub name: Option<String>,
+}
+
+/// Snapshots in the snapshot directory, oldest first
+#[derive(Debug, Serialize)]
+pub struct SnapshotsResponse {
+    pub snapshots: Vec<SnapshotSummary>,
+}
+
+/// What to restore the registry to
+#[derive(Debug, Deserialize)]
+pub struct RestoreRequest {
+    /// Snapshot to restore; the newest one taken at or before `at` if not given
+    pub snapshot: Option<String>,
+    /// Time to restore to, no earlier than the snapshot; the snapshot's own if not given
+    pub at: Option<DateTime<Utc>>,
+    /// Only report what would change (default: true)
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+/// Optional explanation recorded when resolving a quarantine entry
+#[derive(Debug, Deserialize)]
+pub struct ResolveQuarantineRequest {
+    pub note: Option<String>,
+}
+
+/// Time window of a ResourceSync change list; both ends are optional
+#[derive(Debug, Deserialize)]
+pub struct ChangeListQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Where a shoulder's sitemap starts, as listed in the sitemap index; at its first ARK when unset
+#[derive(Debug, Deserialize)]
+pub struct SitemapQuery {
+    pub after: Option<String>,
+}
+
+/// Status to filter webhook deliveries by; all of them when unset
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    pub status: Option<DeliveryStatus>,
+}
+
+/// Number of upcoming mints to project the collision probability for
+#[derive(Debug, Deserialize)]
+pub struct CapacityQuery {
+    pub next: Option<u64>,
+}
+
+/// How much room a shoulder's namespace has, for sizing blade lengths
+#[derive(Debug, Serialize)]
+pub struct ShoulderCapacity {
+    pub shoulder: ShoulderName,
+    /// Blade length currently used for minting, excluding the check character
+    pub blade_length: usize,
+    pub alphabet_size: usize,
+    /// `alphabet_size` to the power of `blade_length`
+    pub namespace_size: f64,
+    /// Identifiers minted since the service started
+    pub minted: u64,
+    /// Identifiers created elsewhere and registered since the service started
+    pub registered: u64,
+    pub next: u64,
+    /// Chance that minting the next `next` identifiers draws at least one already issued
+    pub collision_probability: f64,
+    /// Next position of the shoulder's sequential counter this instance issues; `None` while
+    /// blades are random
+    pub sequential_position: Option<u64>,
+}
+
+/// ARKs to export from the mint ledger: those of one shoulder, or all, in the given format
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub shoulder: Option<String>,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// A managed API key to create, for `name`, with `scopes`, until `expires_at` if given
+#[derive(Debug, Deserialize)]
+pub struct CreateKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// When a managed key stops working; right away if not given
+#[derive(Debug, Default, Deserialize)]
+pub struct ExpireKeyRequest {
+    pub at: Option<DateTime<Utc>>,
+}
+
+/// How long the key being rotated keeps working, in seconds; it stops right away if not given
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeyRequest {
+    #[serde(default)]
+    pub grace_secs: u64,
+}
+
+/// A managed API key as listed, without its hash
+#[derive(Debug, Serialize)]
+pub struct ApiKeySummary {
+    pub id: String,
+    /// What the key starts with
+    pub prefix: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub created_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotated_from: Option<String>,
+    pub active: bool,
+}
+
+impl From<ApiKey> for ApiKeySummary {
+    fn from(key: ApiKey) -> Self {
+        ApiKeySummary {
+            prefix: key.prefix(),
+            active: key.is_active(Utc::now()),
+            id: key.id,
+            name: key.name,
+            scopes: key.scopes,
+            created_at: key.created_at,
+            created_by: key.created_by,
+            expires_at: key.expires_at,
+            rotated_from: key.rotated_from,
+        }
+    }
+}
+
+/// A newly issued API key; `key` is only ever shown here
+#[derive(Debug, Serialize)]
+pub struct IssuedKeyResponse {
+    pub key: String,
+    #[serde(flatten)]
+    pub summary: ApiKeySummary,
+}
+
+/// How to report the calls made with an API key
+#[derive(Debug, Default, Deserialize)]
+pub struct KeyUsageQuery {
+    #[serde(default)]
+    pub granularity: Granularity,
+    #[serde(default)]
+    pub format: UsageFormat,
+}
+
+/// Calls made with an API key, a bucket at a time, and over the whole report
+#[derive(Debug, Serialize)]
+pub struct KeyUsageReport {
+    /// Id of the managed key, or name of the configured key, reported on
+    pub key: String,
+    /// Name of the key holder
+    pub name: String,
+    pub granularity: Granularity,
+    pub buckets: Vec<UsageBucket>,
+    pub total: UsageCounts,
+}
+
+/// Number of synthetic ARKs to generate
+#[derive(Debug, Deserialize)]
+pub struct ExampleQuery {
+    pub count: Option<usize>,
+}
+
+/// A synthetic ARK and where the shoulder's route pattern would send it
+#[derive(Debug, Serialize)]
+pub struct ExampleArk {
+    pub ark: String,
+    pub target: String,
+    /// Why the target would be blocked instead of redirected to, if it would be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<String>,
+}
+
+/// ARKs shaped like a shoulder's minted ones, which have not been issued
+#[derive(Debug, Serialize)]
+pub struct ShoulderExamples {
+    pub shoulder: ShoulderName,
+    pub examples: Vec<ExampleArk>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ValidateRequest {
     pub arks: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub has_check_character: Option<bool>,
+    /// Report how long validation took, overall and per ARK
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub debug: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct MintResponse {
-    pub arks: Vec<String>,
+    pub arks: Vec<MintedArk>,
     pub count: usize,
+    /// The idempotency key was seen before, so these are the ARKs minted for it then
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replayed: bool,
+    /// The ARKs are examples from a dry run: not minted, and free to be minted by anyone later
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+}
+
+/// A minted ARK, as a plain string or broken into components when `detail` was requested
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum MintedArk {
+    Plain(String),
+    Detailed(MintedArkDetail),
+}
+
+#[derive(Debug, Serialize)]
+pub struct MintedArkDetail {
+    pub ark: String,
+    pub naan: Naan,
+    pub shoulder: ShoulderName,
+    /// The blade, including the check character if the shoulder uses one
+    pub blade: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_character: Option<String>,
+    /// Where the ARK resolves on this service; requires `PUBLIC_BASE_URL`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolver_url: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ValidateResponse {
     pub results: Vec<ArkValidationResult>,
+    /// Set when the request had more ARKs than `MAX_VALIDATE_COUNT`; only the first ones
+    /// were validated
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Only with `debug`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<ValidationTiming>,
+}
+
+/// How a validation batch was processed, for requests with `debug`
+#[derive(Debug, Serialize)]
+pub struct ValidationTiming {
+    /// Wall-clock time for the whole batch, in microseconds
+    pub elapsed_us: u64,
+    /// Whether the batch was spread over the validation threads
+    pub parallel: bool,
+    pub threads: usize,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ArkValidationResult {
     pub ark: String,
     pub valid: bool,
-    pub naan: Option<String>,
-    pub shoulder: Option<String>,
+    pub naan: Option<Naan>,
+    pub shoulder: Option<ShoulderName>,
     pub blade: Option<String>,
     pub shoulder_registered: Option<bool>,
     pub has_check_character: Option<bool>,
@@ -42,20 +472,158 @@ pub struct ArkValidationResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ValidationCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning_codes: Option<Vec<ValidationCode>>,
+    /// The ARK this one is a component of, when its qualifier names one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_of: Option<String>,
+    /// Time spent validating this ARK, in microseconds; only with `debug`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_us: Option<u64>,
+}
+
+/// ARKs to compare: a list to group by equivalence, and/or explicit pairs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompareRequest {
+    #[serde(default)]
+    pub arks: Vec<String>,
+    #[serde(default)]
+    pub pairs: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareResponse {
+    /// One entry per ARK in `arks`, in request order
+    pub results: Vec<NormalizedArk>,
+    /// The valid ARKs in `arks` grouped by equivalence, in order of first appearance
+    pub groups: Vec<EquivalenceGroup>,
+    /// One entry per pair in `pairs`, in request order
+    pub pairs: Vec<PairComparison>,
+}
+
+/// An ARK as given and its normalized form, which decides equivalence
+#[derive(Debug, Serialize)]
+pub struct NormalizedArk {
+    pub ark: String,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
+    /// Normalization steps that changed the ARK, in the order they were applied
+    pub transformations: Vec<Normalization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ValidationCode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EquivalenceGroup {
+    pub normalized: String,
+    /// Positions in `arks` of the members
+    pub indices: Vec<usize>,
+    pub arks: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairComparison {
+    /// Whether both ARKs are valid and identify the same object
+    pub equivalent: bool,
+    pub left: NormalizedArk,
+    pub right: NormalizedArk,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ShoulderInfo {
-    pub shoulder: String,
+    pub shoulder: ShoulderName,
     pub project_name: String,
     pub uses_check_character: bool,
     pub blade_length: usize,
+    pub alphabet_size: usize,
+    /// Number of distinct blades of `blade_length` the shoulder can mint, across all instances
+    pub namespace_size: f64,
+    /// Share of `namespace_size` taken by ARKs in the mint ledger, as last counted in the
+    /// background; omitted until then, or where the store cannot count them by shoulder. ARKs of
+    /// every blade length count, so it overstates the share after the blade length grew.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent_consumed: Option<f64>,
     pub example_ark: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    /// Identifiers minted since the service started
+    pub minted: u64,
+    /// ARKs of the shoulder with a binding, as last counted in the background; omitted until
+    /// then, or where the store cannot count them by shoulder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bound: Option<usize>,
+    pub last_minted_at: Option<DateTime<Utc>>,
+    /// Successful resolutions, counted in whole hours
+    pub resolutions_24h: u64,
+    pub resolutions_30d: u64,
+    /// Of the resolutions, those of components; only for shoulders that declare components
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_resolutions_24h: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_resolutions_30d: Option<u64>,
+}
+
+/// Machine-readable description of how an ARK resolves, returned instead of a redirect
+/// when the client asks with `?_format=json` or `?_redirect=false`
+#[derive(Debug, Serialize)]
+pub struct ResolutionResponse {
+    pub ark: String,
+    pub naan: Naan,
+    pub shoulder: ShoulderName,
+    pub blade: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub qualifier: String,
+    pub project_name: String,
+    pub target: String,
+    pub redirect_status: u16,
+}
+
+/// What an ARK of an unregistered shoulder tells by its structure alone, answered to
+/// inflections when `UNREGISTERED_INFLECTIONS` is `stub`
+#[derive(Debug, Serialize)]
+pub struct UnknownNamespaceStub {
+    pub ark: String,
+    pub naan: Naan,
+    pub shoulder: ShoulderName,
+    pub blade: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub qualifier: String,
+    /// Always `unknown_namespace`: the NAAN is served here, but not the shoulder
+    pub status: &'static str,
+}
+
+/// Blade length advice for every shoulder, and the escalations applied automatically
+#[derive(Debug, Serialize)]
+pub struct BladeLengthReport {
+    pub auto_escalate: bool,
+    pub horizon: u64,
+    pub threshold: f64,
+    pub shoulders: Vec<BladeLengthAdvice>,
+    pub escalations: Vec<Escalation>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct InfoResponse {
-    pub naan: String,
+    pub naan: Naan,
     pub shoulders: Vec<ShoulderInfo>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct OpenIncidentRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMaintenanceRequest {
+    pub description: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
@@ -0,0 +1,40 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::config::AppState;
+use crate::error::AppError;
+
+/// Middleware turning away requests that would mint or change ARKs while the service is
+/// read-only, with `503 Service Unavailable`
+pub async fn reject_writes(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(_write) = state.read_only.begin_write() else {
+        tracing::debug!(path = %request.uri().path(), "Write rejected in read-only mode");
+        return Err(AppError::ReadOnly);
+    };
+    Ok(next.run(request).await)
+}
+
+/// Middleware turning away requests that pause writes themselves, such as restores, while the
+/// service is read-only
+///
+/// Unlike [`reject_writes`] the request is not counted as a write in progress, since it waits
+/// for those to finish.
+pub async fn reject_when_read_only(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if state.read_only.is_enabled() {
+        tracing::debug!(path = %request.uri().path(), "Write rejected in read-only mode");
+        return Err(AppError::ReadOnly);
+    }
+    Ok(next.run(request).await)
+}
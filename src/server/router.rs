@@ -1,18 +1,158 @@
-use axum::{Router, routing::get, routing::post};
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{any, get, post},
+};
 use std::sync::Arc;
 
-use crate::{AppState, server::handlers};
+use crate::{
+    AppState, public_mint, resourcesync,
+    server::{
+        admin, auth, canonical_host, deadline, error_pages, handlers, key_usage, localize,
+        read_only, throttle,
+    },
+    shorten, sitemap, well_known,
+};
 
 /// Creates and configures the application router with all routes
 pub fn create_router(state: Arc<AppState>) -> Router {
-    Router::new()
+    let writes = || middleware::from_fn_with_state(state.clone(), read_only::reject_writes);
+    let minters = || middleware::from_fn_with_state(state.clone(), auth::require_minter);
+    let router = Router::new()
         .route("/api/v1/info", get(handlers::info_handler))
-        .route("/api/v1/mint", post(handlers::mint_handler))
+        .route(
+            "/api/v1/mint",
+            post(handlers::mint_handler)
+                .route_layer(writes())
+                .route_layer(minters()),
+        )
+        .route(
+            "/api/v1/mint/stream",
+            post(handlers::mint_stream_handler)
+                .route_layer(writes())
+                .route_layer(minters()),
+        )
+        .route(
+            "/api/v1/mint-and-bind",
+            post(handlers::mint_and_bind_handler)
+                .route_layer(writes())
+                .route_layer(minters()),
+        )
+        .route(
+            "/api/v1/register",
+            post(handlers::register_handler)
+                .route_layer(writes())
+                .route_layer(minters()),
+        )
+        .route(
+            "/api/v1/import",
+            post(handlers::import_handler)
+                .layer(DefaultBodyLimit::max(state.max_import_bytes))
+                .route_layer(writes())
+                .route_layer(minters()),
+        )
+        .route(
+            "/api/v1/export/arks",
+            get(handlers::export_arks_handler).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                auth::require_admin,
+            )),
+        )
         .route("/api/v1/validate", post(handlers::validate_handler))
         .route(
-            &format!("/ark:{}/servicestatus", state.naan),
-            get(handlers::health_check_handler),
+            "/api/v1/shoulders/schema",
+            get(handlers::shoulder_schema_handler),
         )
-        .route("/ark:{*ark_fragment}", get(handlers::resolve_handler))
+        .route(
+            "/api/v1/shoulders/{shoulder}/capacity",
+            get(handlers::shoulder_capacity_handler),
+        )
+        .route(
+            "/api/v1/shoulders/{shoulder}/example",
+            get(handlers::shoulder_examples_handler),
+        )
+        .route("/api/v1/compare", post(handlers::compare_handler))
+        .route(
+            well_known::ARK_CONFIGURATION_PATH,
+            get(handlers::ark_configuration_handler),
+        )
+        .route(
+            resourcesync::DESCRIPTION_PATH,
+            get(handlers::resourcesync_description_handler),
+        )
+        .route(
+            resourcesync::CAPABILITY_LIST_PATH,
+            get(handlers::capability_list_handler),
+        )
+        .route(
+            resourcesync::CHANGE_LIST_PATH,
+            get(handlers::change_list_handler),
+        )
+        .route("/ark:{*ark_fragment}", any(handlers::resolve_handler))
+        .fallback(handlers::fallback_handler);
+
+    let router = match &state.public_mint {
+        Some(_) => router.route(
+            public_mint::PUBLIC_MINT_PATH,
+            post(handlers::public_mint_handler)
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    throttle::limit_public_mint,
+                ))
+                .route_layer(writes()),
+        ),
+        None => router,
+    };
+
+    let router = match &state.shortener {
+        Some(_) => router.route(
+            shorten::SHORTEN_PATH,
+            post(handlers::shorten_handler)
+                .route_layer(writes())
+                .route_layer(minters()),
+        ),
+        None => router,
+    };
+
+    // Sitemaps must list absolute URLs, so they need the public base URL
+    let router = match &state.public_base_url {
+        Some(_) => router
+            .route(sitemap::SITEMAP_PATH, get(handlers::sitemap_index_handler))
+            .route(
+                &format!("{}/{{shoulder}}", sitemap::SHOULDER_SITEMAP_PATH),
+                get(handlers::shoulder_sitemap_handler),
+            ),
+        None => router,
+    };
+
+    // The admin API is its own surface, left out entirely when disabled
+    let router = match &state.admin_api_path {
+        Some(path) => router.nest(path, admin::router(state.clone())),
+        None => router,
+    };
+
+    router
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            canonical_host::redirect_to_canonical_host,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            deadline::enforce_deadline,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            throttle::limit_requests,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            key_usage::count_key_usage,
+        ))
+        .layer(middleware::from_fn(localize::localize_errors))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            error_pages::render_error_pages,
+        ))
         .with_state(state)
 }
@@ -1,20 +1,82 @@
+use std::future::Future;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use crate::config::AppState;
+use crate::admin_token::load_token_signer_from_env;
+use crate::api_keys::{ApiKeys, KEY_RELOAD_INTERVAL, Scope};
+use crate::ark::ArkFormat;
+use crate::bindings::{BindingCounts, Bindings};
+use crate::blade_advisor::load_blade_advisor_from_env;
+use crate::callbacks::load_failure_callbacks_from_env;
+use crate::config::{AppState, parse_admin_keys};
+use crate::config_document::ConfigStore;
+use crate::deadline::load_request_timeouts_from_env;
+use crate::error_pages::ErrorPagesConfig;
+use crate::events::{DEFAULT_EVENT_LOG_CAPACITY, EventLog};
+use crate::heartbeat::load_heartbeat_from_env;
+use crate::http_client::load_http_clients_from_env;
+use crate::idempotency::{self, RECEIPT_PRUNE_INTERVAL};
+use crate::import::DEFAULT_MAX_IMPORT_BYTES;
+use crate::inflection::{
+    load_inflection_forwarder_from_env, load_unregistered_inflections_from_env,
+};
+use crate::journal::load_mint_journal_from_env;
+use crate::key_usage::KeyUsage;
+use crate::ledger::{LEDGER_COUNT_INTERVAL, LedgerCounts};
+use crate::limits::load_input_limits_from_env;
+use crate::log_level::LogLevel;
+use crate::mint_pool::load_mint_pools_from_env;
+use crate::mint_queue::load_mint_queue_from_env;
+use crate::mint_quota::MintQuotas;
+use crate::minting::{self, MintStats, load_blade_pool_from_env, load_collision_policy_from_env};
+use crate::names::Naan;
+use crate::notify::{env_parse, load_notifier_from_env};
+use crate::partition::load_node_partition_from_env;
+use crate::profile::apply_profile_from_env;
+use crate::public_mint::load_public_mint_from_env;
+use crate::quarantine::Quarantine;
+use crate::rate_limit::load_rate_limiter_from_env;
+use crate::read_only::load_read_only_from_env;
+use crate::redaction::{RedactingMakeWriter, Redactor, load_redactor_from_env};
+use crate::replication::{ReplicatedStore, load_replication_from_env};
+use crate::secrets::Secrets;
+use crate::server::admin::load_admin_api_path_from_env;
+use crate::server::canonical_host::load_canonical_host_from_env;
 use crate::server::router::create_router;
+use crate::shorten::load_shortener_from_env;
 use crate::shoulder::load_shoulders_from_env;
+use crate::sitemap::SitemapPages;
+use crate::snapshot::load_snapshots_from_env;
+use crate::stats::ResolutionStats;
+use crate::status::StatusBoard;
+use crate::store::{ArkStore, load_ark_store_from_env};
+use crate::validation::load_validation_pool_from_env;
+use crate::views::Views;
 
 /// Runs the server with configuration loaded from environment variables
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Profile defaults go beneath everything else, including the log filter
+    let profile = apply_profile_from_env();
+
     // Initialize tracing to stdout
-    use tracing_subscriber::{EnvFilter, fmt};
+    use tracing_subscriber::{EnvFilter, fmt, prelude::*, reload};
 
     // Set up env filter
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
+    // Scrub credentials (and client addresses in privacy mode) from every log line
+    let (redactor, redaction_error) = match load_redactor_from_env() {
+        Ok(redactor) => (Arc::new(redactor), None),
+        Err(e) => (Arc::new(Redactor::default()), Some(e)),
+    };
+
+    // The filter can be swapped at runtime through the admin API
+    let initial_filter = env_filter.to_string();
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+
     // Configure formatter for Apache-like structured text logs
-    fmt()
-        .with_env_filter(env_filter)
+    let fmt_layer = fmt::layer()
         .with_target(false) // No Rust module paths
         .with_ansi(true) // Colors
         .with_level(true) // Show log level
@@ -23,13 +85,39 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         .with_file(false) // No file names
         .with_line_number(false) // No line numbers
         .compact() // Compact format
+        .with_writer(RedactingMakeWriter::new(redactor.clone(), std::io::stdout));
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer)
         .init();
 
+    if let Some(e) = redaction_error {
+        tracing::error!(error = %e, "Invalid log redaction configuration");
+        std::process::exit(1);
+    }
+
+    match profile {
+        Ok(Some((profile, applied))) => tracing::info!(
+            profile = profile.name(),
+            applied = ?applied,
+            "Configuration profile applied"
+        ),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!(error = %e, "Invalid configuration profile");
+            std::process::exit(1);
+        }
+    }
+
     // Load configuration from environment
     let naan = std::env::var("NAAN").unwrap_or_else(|_| {
         tracing::warn!("NAAN not set, using default: 12345");
         "12345".to_string()
     });
+    let naan = Naan::new(naan).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Invalid NAAN");
+        std::process::exit(1);
+    });
 
     let default_blade_length = std::env::var("DEFAULT_BLADE_LENGTH")
         .ok()
@@ -47,6 +135,49 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             1000
         });
 
+    let max_stream_mint_count = std::env::var("MAX_STREAM_MINT_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| {
+            tracing::warn!("MAX_STREAM_MINT_COUNT not set or invalid, using default: 1000000");
+            1_000_000
+        });
+
+    let max_validate_count = std::env::var("MAX_VALIDATE_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| {
+            tracing::warn!("MAX_VALIDATE_COUNT not set or invalid, using default: 10000");
+            10000
+        });
+
+    let max_import_bytes = env_parse("IMPORT_MAX_BYTES")
+        .unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Invalid IMPORT_MAX_BYTES");
+            std::process::exit(1);
+        })
+        .unwrap_or(DEFAULT_MAX_IMPORT_BYTES);
+
+    let validation_pool = load_validation_pool_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load validation pool configuration");
+        std::process::exit(1);
+    });
+
+    let collision_policy = load_collision_policy_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load mint collision policy");
+        std::process::exit(1);
+    });
+
+    let blade_pool = load_blade_pool_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load blade pool configuration");
+        std::process::exit(1);
+    });
+
+    let blade_advisor = load_blade_advisor_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load blade length advisor");
+        std::process::exit(1);
+    });
+
     // Load shoulders from environment
     let shoulders = load_shoulders_from_env().unwrap_or_else(|e| {
         tracing::error!(
@@ -56,11 +187,364 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     });
 
+    let node_partition = load_node_partition_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load node partition");
+        std::process::exit(1);
+    });
+    for (shoulder, config) in &shoulders {
+        if let Err(e) = node_partition.check(&config.blade_template(default_blade_length)) {
+            tracing::error!(shoulder = %shoulder, error = %e, "Shoulder cannot be partitioned");
+            std::process::exit(1);
+        }
+    }
+    if node_partition.nodes > 1 {
+        tracing::info!(
+            node = node_partition.node,
+            nodes = node_partition.nodes,
+            "Minting within this node's partition of every shoulder"
+        );
+    }
+
+    // Outbound HTTP shares one connection pool, retry policy, and set of metrics
+    let http = load_http_clients_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to set up outbound HTTP");
+        std::process::exit(1);
+    });
+
+    // Resolve credentials given inline, as *_FILE, or as *_VAULT references
+    let secrets = Secrets::load_from_env(&http).await.unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load secrets");
+        std::process::exit(1);
+    });
+
+    redactor.add_secrets(&secrets);
+
+    let admin_keys = secrets
+        .get("ADMIN_API_KEYS")
+        .map(|keys| {
+            parse_admin_keys(keys.expose()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to parse ADMIN_API_KEYS");
+                std::process::exit(1);
+            })
+        })
+        .unwrap_or_default();
+
+    let admin_api_path = load_admin_api_path_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure the admin API");
+        std::process::exit(1);
+    });
+
+    let resolution_keys = secrets
+        .get("RESOLUTION_API_KEYS")
+        .map(|keys| {
+            parse_admin_keys(keys.expose()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to parse RESOLUTION_API_KEYS");
+                std::process::exit(1);
+            })
+        })
+        .unwrap_or_default();
+
+    let mint_keys = secrets
+        .get("MINT_API_KEYS")
+        .map(|keys| {
+            parse_admin_keys(keys.expose()).unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to parse MINT_API_KEYS");
+                std::process::exit(1);
+            })
+        })
+        .unwrap_or_default();
+
+    for key in admin_keys
+        .keys()
+        .chain(resolution_keys.keys())
+        .chain(mint_keys.keys())
+    {
+        redactor.add_secret(key);
+    }
+
+    let token_signer = load_token_signer_from_env(&secrets).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure admin UI tokens");
+        std::process::exit(1);
+    });
+
+    let rate_limiter = load_rate_limiter_from_env(&secrets).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure rate limiting");
+        std::process::exit(1);
+    });
+
+    let public_mint = load_public_mint_from_env(&secrets, &shoulders).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure public minting");
+        std::process::exit(1);
+    });
+    if let Some(public_mint) = &public_mint {
+        tracing::info!(
+            shoulder = %public_mint.shoulder,
+            max_count = public_mint.max_count,
+            "Public minting enabled"
+        );
+    }
+
+    let shortener = load_shortener_from_env(&shoulders).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure the URL shortener");
+        std::process::exit(1);
+    });
+    if let Some(shortener) = &shortener {
+        tracing::info!(shoulder = %shortener.shoulder, "URL shortener enabled");
+    }
+
+    let input_limits = load_input_limits_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load input limits");
+        std::process::exit(1);
+    });
+
+    let read_only = load_read_only_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load read-only mode");
+        std::process::exit(1);
+    });
+    if let Some(status) = read_only.status() {
+        tracing::warn!(
+            reason = status.reason.as_deref().unwrap_or_default(),
+            "Starting read-only: minting and changes to ARKs are paused"
+        );
+    }
+
+    let request_timeouts = load_request_timeouts_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load request timeouts");
+        std::process::exit(1);
+    });
+    if let Some(timeout) = request_timeouts.default {
+        tracing::info!(
+            timeout_ms = timeout.as_millis() as u64,
+            "Resolution requests have a deadline"
+        );
+    }
+
+    let notifier = load_notifier_from_env(&secrets).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load SMTP configuration");
+        std::process::exit(1);
+    });
+
+    if !notifier.is_enabled() {
+        tracing::info!("SMTP_HOST not set, alert emails disabled");
+    }
+
+    let failure_callbacks = load_failure_callbacks_from_env(&http).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to set up failure callbacks");
+        std::process::exit(1);
+    });
+
+    let inflections = load_inflection_forwarder_from_env(&http).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to set up inflection forwarding");
+        std::process::exit(1);
+    });
+
+    let unregistered_inflections = load_unregistered_inflections_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load UNREGISTERED_INFLECTIONS");
+        std::process::exit(1);
+    });
+
+    let mint_queue = load_mint_queue_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to set up mint queue");
+        std::process::exit(1);
+    });
+    let queue_stats = mint_queue.stats();
+    tracing::info!(
+        workers = queue_stats.workers,
+        capacity = queue_stats.capacity,
+        "Mint queue configured"
+    );
+
+    let mint_pools = load_mint_pools_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to configure mint pools");
+        std::process::exit(1);
+    });
+    if mint_pools.is_enabled() {
+        tracing::info!(
+            size = mint_pools.size(),
+            refill_interval_secs = mint_pools.refill_interval().as_secs(),
+            "Mint pools enabled"
+        );
+    }
+
+    let store = load_ark_store_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to open ARK store");
+        std::process::exit(1);
+    });
+    match store.minted() {
+        Ok(_) if store.backend() == "memory" => tracing::warn!(
+            "Neither DATABASE_URL nor ARK_STORE_PATH set, minted ARKs and bindings are only kept until the service stops"
+        ),
+        Ok(minted) => tracing::info!(
+            backend = store.backend(),
+            minted = minted,
+            "ARK store opened"
+        ),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to read ARK store");
+            std::process::exit(1);
+        }
+    }
+
+    // Keys created through the admin API, accepted alongside the configured ones
+    let api_keys = ApiKeys::default();
+    match api_keys.reload(store.as_ref()) {
+        Ok(count) => tracing::info!(count, "Managed API keys loaded"),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to read managed API keys");
+            std::process::exit(1);
+        }
+    }
+
+    match &admin_api_path {
+        None => tracing::info!("Admin API disabled"),
+        Some(_) if admin_keys.is_empty() && !api_keys.has_scope(Scope::Admin) => {
+            tracing::warn!(
+                "ADMIN_API_KEYS not set and no managed admin keys, admin endpoints will reject all requests"
+            )
+        }
+        Some(path) => tracing::info!(path = %path, "Admin API enabled"),
+    }
+
+    if resolution_keys.is_empty()
+        && !api_keys.has_scope(Scope::Resolve)
+        && shoulders.values().any(|config| config.require_auth)
+    {
+        tracing::warn!(
+            "RESOLUTION_API_KEYS not set and no managed resolve keys, ARKs on shoulders with require_auth will not resolve"
+        );
+    }
+
+    if mint_keys.is_empty()
+        && admin_keys.is_empty()
+        && !api_keys.has_scope(Scope::Mint)
+        && !api_keys.has_scope(Scope::Admin)
+    {
+        tracing::warn!(
+            "MINT_API_KEYS and ADMIN_API_KEYS not set and no managed mint or admin keys, only public minting will add ARKs"
+        );
+    }
+
+    // Mirror every change to the store to the standby, if there is one
+    let replication = Arc::new(
+        load_replication_from_env(&http, &secrets).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Failed to set up standby replication");
+            std::process::exit(1);
+        }),
+    );
+    let store = if replication.is_enabled() {
+        tracing::info!(
+            flush_interval_ms = replication.flush_interval().as_millis() as u64,
+            "Replicating changes to the standby"
+        );
+        Arc::new(ReplicatedStore::new(store, replication.clone())) as Arc<dyn ArkStore>
+    } else {
+        store
+    };
+
+    let mint_journal = load_mint_journal_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to open mint journal");
+        std::process::exit(1);
+    });
+    match &mint_journal {
+        Some(journal) => tracing::info!(
+            pending = journal.pending(),
+            reconcile_interval_secs = journal.reconcile_interval().as_secs(),
+            "Mint journal enabled"
+        ),
+        None => tracing::info!("MINT_JOURNAL_PATH not set, mint journal disabled"),
+    }
+
+    let snapshots = load_snapshots_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to open snapshot directory");
+        std::process::exit(1);
+    });
+    if snapshots.is_none() {
+        tracing::info!("SNAPSHOT_DIR not set, snapshots disabled");
+    }
+
+    let heartbeat = load_heartbeat_from_env(&http).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to set up heartbeat");
+        std::process::exit(1);
+    });
+
+    let public_base_url = std::env::var("PUBLIC_BASE_URL").ok().map(|url| {
+        if url::Url::parse(&url).is_err() {
+            tracing::error!(url = %url, "PUBLIC_BASE_URL is not a valid URL");
+            std::process::exit(1);
+        }
+        url.trim_end_matches('/').to_string()
+    });
+
+    let canonical_host = load_canonical_host_from_env().unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load canonical host");
+        std::process::exit(1);
+    });
+    if let Some(canonical_host) = &canonical_host {
+        tracing::info!(canonical_url = %canonical_host.url("/"), "Redirecting other hosts to the canonical host");
+    }
+
+    let canonical_link_rel = std::env::var("CANONICAL_LINK").ok().map(|rel| {
+        if rel != "canonical" && rel != "cite-as" {
+            tracing::error!(rel = %rel, "CANONICAL_LINK must be 'canonical' or 'cite-as'");
+            std::process::exit(1);
+        }
+        if public_base_url.is_none() {
+            tracing::warn!(
+                "CANONICAL_LINK is set but PUBLIC_BASE_URL is not, no Link header will be sent"
+            );
+        }
+        rel
+    });
+
+    let ark_format = std::env::var("ARK_FORMAT")
+        .ok()
+        .map(|json| {
+            serde_json::from_str::<ArkFormat>(&json)
+                .map_err(|e| e.to_string())
+                .and_then(|format| format.validate().map(|_| format))
+                .unwrap_or_else(|e| {
+                    tracing::error!(error = %e, "Failed to parse ARK_FORMAT");
+                    std::process::exit(1);
+                })
+        })
+        .unwrap_or_default();
+
+    let event_log_capacity = std::env::var("EVENT_LOG_CAPACITY")
+        .ok()
+        .map(|s| {
+            s.parse().unwrap_or_else(|_| {
+                tracing::error!(value = %s, "EVENT_LOG_CAPACITY must be a number");
+                std::process::exit(1);
+            })
+        })
+        .unwrap_or(DEFAULT_EVENT_LOG_CAPACITY);
+
+    let template_dir = std::env::var("TEMPLATE_DIR")
+        .ok()
+        .map(std::path::PathBuf::from);
+    let mut views = Views::new(template_dir.as_deref()).unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load templates");
+        std::process::exit(1);
+    });
+
+    if let Ok(json) = std::env::var("ERROR_PAGES") {
+        serde_json::from_str::<ErrorPagesConfig>(&json)
+            .map_err(|e| e.to_string())
+            .and_then(|config| config.load_into(&mut views))
+            .unwrap_or_else(|e| {
+                tracing::error!(error = %e, "Failed to load ERROR_PAGES");
+                std::process::exit(1);
+            });
+    }
+
     tracing::info!(
         naan = %naan,
         default_blade_length = default_blade_length,
         max_mint_count = max_mint_count,
+        max_stream_mint_count = max_stream_mint_count,
+        max_validate_count = max_validate_count,
         shoulder_count = shoulders.len(),
+        admin_key_count = admin_keys.len(),
         "Server configuration loaded"
     );
 
@@ -68,26 +552,233 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         tracing::debug!(
             shoulder = %shoulder,
             project_name = %config.project_name,
-            route_pattern = %config.route_pattern,
-            uses_check_character = config.uses_check_character,
+            route_pattern = %config.effective_route_pattern(),
+            uses_check_character = config.check_character(),
             blade_length = ?config.blade_length,
+            template = ?config.template.as_ref().map(ToString::to_string),
             "Shoulder configuration"
         );
     }
 
+    let config_store = ConfigStore::new(shoulders.keys(), "environment", "SHOULDERS");
+
     let state = Arc::new(AppState {
         naan,
         default_blade_length,
         max_mint_count,
+        max_stream_mint_count,
+        max_validate_count,
+        max_import_bytes,
+        validation_pool,
         shoulders,
+        admin_keys,
+        admin_api_path,
+        token_signer,
+        resolution_keys,
+        mint_keys,
+        api_keys,
+        config_store,
+        notifier: Arc::new(notifier),
+        status_board: StatusBoard::default(),
+        public_base_url,
+        canonical_host,
+        canonical_link_rel,
+        commitment: std::env::var("ARK_COMMITMENT").ok(),
+        ark_format,
+        views,
+        event_log: EventLog::new(event_log_capacity),
+        failure_callbacks,
+        quarantine: Quarantine::default(),
+        inflections,
+        unregistered_inflections,
+        http_metrics: http.metrics(),
+        collision_policy,
+        blade_pool,
+        node_partition,
+        mint_stats: MintStats::default(),
+        mint_quotas: MintQuotas::default(),
+        mint_pools,
+        log_level: LogLevel::new(filter_handle, initial_filter),
+        blade_advisor,
+        resolution_stats: ResolutionStats::default(),
+        ledger_counts: LedgerCounts::default(),
+        binding_counts: BindingCounts::default(),
+        sitemap_pages: SitemapPages::default(),
+        key_usage: KeyUsage::default(),
+        rate_limiter,
+        public_mint,
+        shortener,
+        read_only,
+        request_timeouts,
+        input_limits,
+        mint_observer: None,
+        store: store.clone(),
+        bindings: Bindings::new(store),
+        mint_journal,
+        replication,
+        snapshots,
+        mint_queue,
     });
 
+    // Post batched resolution failures to shoulder callbacks
+    let flusher_state = state.clone();
+    spawn_periodic(state.failure_callbacks.flush_interval(), move || {
+        let state = flusher_state.clone();
+        async move {
+            state.failure_callbacks.flush(state.store.as_ref()).await;
+        }
+    });
+
+    // Ship queued changes to the standby
+    if state.replication.is_enabled() {
+        let replication = state.replication.clone();
+        spawn_periodic(replication.flush_interval(), move || {
+            let replication = replication.clone();
+            async move {
+                replication.flush().await;
+            }
+        });
+    }
+
+    // Pick up keys created, rotated or expired on other instances
+    let keys_state = state.clone();
+    spawn_periodic(KEY_RELOAD_INTERVAL, move || {
+        let state = keys_state.clone();
+        async move {
+            if let Err(e) = state.api_keys.reload(state.store.as_ref()) {
+                tracing::warn!(error = %e, "Failed to reload managed API keys");
+            }
+        }
+    });
+
+    // Count recorded and bound ARKs for the info page, and page the sitemaps, off the threads
+    // serving requests
+    let counts_state = state.clone();
+    spawn_periodic(LEDGER_COUNT_INTERVAL, move || {
+        let state = counts_state.clone();
+        async move {
+            let refresh = tokio::task::spawn_blocking(move || {
+                let public = || {
+                    state
+                        .shoulders
+                        .iter()
+                        .filter(|(_, config)| config.public)
+                        .map(|(shoulder, _)| shoulder.as_str())
+                };
+                state.ledger_counts.refresh(state.store.as_ref(), public());
+                state
+                    .binding_counts
+                    .refresh(&state.bindings, &state.naan, public());
+                // Sitemaps are only served with a base URL to make their URLs absolute
+                if state.public_base_url.is_some() {
+                    let indexed = state
+                        .shoulders
+                        .iter()
+                        .filter(|(_, config)| config.public && !config.noindex)
+                        .map(|(shoulder, _)| shoulder.as_str());
+                    state.sitemap_pages.refresh(state.store.as_ref(), indexed);
+                }
+            });
+            if let Err(e) = refresh.await {
+                tracing::error!(error = %e, "Counting recorded and bound ARKs panicked");
+            }
+        }
+    });
+
+    // Forget idempotency keys once retries are no longer expected
+    let receipts_state = state.clone();
+    spawn_periodic(RECEIPT_PRUNE_INTERVAL, move || {
+        let state = receipts_state.clone();
+        async move {
+            let before = idempotency::retention_cutoff(chrono::Utc::now());
+            if let Err(e) = state.store.prune_receipts(before) {
+                tracing::warn!(error = %e, "Failed to prune mint receipts");
+            }
+        }
+    });
+
+    // Replay journaled bindings once the binding store answers again
+    if let Some(interval) = state
+        .mint_journal
+        .as_ref()
+        .map(|journal| journal.reconcile_interval())
+    {
+        let journal_state = state.clone();
+        spawn_periodic(interval, move || {
+            let state = journal_state.clone();
+            async move {
+                let journal = state
+                    .mint_journal
+                    .as_ref()
+                    .expect("mint journal configured");
+                // Replay waits for the end of read-only mode, like any other binding
+                if journal.pending() > 0
+                    && let Some(_write) = state.read_only.begin_write()
+                    && let Err(e) = journal.reconcile(&state.bindings)
+                {
+                    tracing::error!(error = %e, "Failed to replay mint journal");
+                }
+            }
+        });
+    }
+
+    // Mint ahead of requests, off the threads serving them
+    if state.mint_pools.is_enabled() {
+        let pool_state = state.clone();
+        spawn_periodic(state.mint_pools.refill_interval(), move || {
+            let state = pool_state.clone();
+            async move {
+                if let Err(e) =
+                    tokio::task::spawn_blocking(move || minting::refill_pools(&state)).await
+                {
+                    tracing::error!(error = %e, "Mint pool refill panicked");
+                }
+            }
+        });
+    }
+
+    // Tell the uptime monitor the service is alive
+    match heartbeat {
+        Some(heartbeat) => {
+            tracing::info!(
+                interval_secs = heartbeat.interval().as_secs(),
+                "Heartbeat enabled"
+            );
+            let heartbeat = Arc::new(heartbeat);
+            let heartbeat_state = state.clone();
+            spawn_periodic(heartbeat.interval(), move || {
+                let (heartbeat, state) = (heartbeat.clone(), heartbeat_state.clone());
+                async move { heartbeat.ping(&state).await }
+            });
+        }
+        None => tracing::info!("HEARTBEAT_URL not set, heartbeat disabled"),
+    }
+
     let app = create_router(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
     tracing::info!("Server listening on {}", listener.local_addr()?);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
+
+/// Runs `task` every `period` in the background, starting right away
+fn spawn_periodic<F, Fut>(period: Duration, mut task: F)
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            task().await;
+        }
+    });
+}
@@ -0,0 +1,90 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::config::AppState;
+use crate::error::AppError;
+use crate::public_mint::PublicMint;
+use crate::rate_limit::Decision;
+use crate::server::auth;
+
+/// Middleware rejecting clients that exceed the rate limit with `429 Too Many Requests`
+pub async fn limit_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(policy) = state.rate_limiter.policy() else {
+        return Ok(next.run(request).await);
+    };
+
+    let key = client_key(&state, &request, policy.trust_forwarded_for);
+    match state.rate_limiter.check(&key).await {
+        Decision::Allowed => Ok(next.run(request).await),
+        Decision::Limited { retry_after } => {
+            tracing::info!(client = %key, path = %request.uri().path(), "Request rate limited");
+            Err(AppError::RateLimited { retry_after })
+        }
+    }
+}
+
+/// Middleware holding requests to the public mint endpoint to its own, stricter rate limit
+pub async fn limit_public_mint(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(public_mint) = &state.public_mint else {
+        return Ok(next.run(request).await);
+    };
+    let Some(policy) = public_mint.rate_limiter.policy() else {
+        return Ok(next.run(request).await);
+    };
+
+    let key = PublicMint::client_key(&client_key(&state, &request, policy.trust_forwarded_for));
+    match public_mint.rate_limiter.check(&key).await {
+        Decision::Allowed => Ok(next.run(request).await),
+        Decision::Limited { retry_after } => {
+            tracing::info!(client = %key, "Public mint request rate limited");
+            Err(AppError::RateLimited { retry_after })
+        }
+    }
+}
+
+/// Identifies the client: by key holder when it sends a known API key, else by address
+fn client_key(state: &AppState, request: &Request, trust_forwarded_for: bool) -> String {
+    let headers = request.headers();
+    if let Ok(name) = auth::authenticate_admin(state, headers) {
+        return format!("admin:{}", name);
+    }
+    if let Ok(name) = auth::authenticate_minter(state, headers) {
+        return format!("mint:{}", name);
+    }
+    if let Ok(name) = auth::authenticate_resolver(state, headers) {
+        return format!("resolution:{}", name);
+    }
+
+    // The last address is the one the proxy saw; those before it are the client's to choose
+    let forwarded = trust_forwarded_for
+        .then(|| headers.get("x-forwarded-for"))
+        .flatten()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .map(|address| address.trim().to_string());
+    let peer = || {
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string())
+    };
+    format!(
+        "ip:{}",
+        forwarded
+            .or_else(peer)
+            .unwrap_or_else(|| "unknown".to_string())
+    )
+}
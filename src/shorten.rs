@@ -0,0 +1,320 @@
+use chrono::Utc;
+use ring::digest::{SHA256, digest};
+use std::collections::{BTreeMap, HashMap};
+use url::Url;
+
+use crate::ark::parse_ark;
+use crate::bindings::Binding;
+use crate::config::AppState;
+use crate::error::AppError;
+use crate::lifecycle::ArkStatus;
+use crate::minting::with_check_character;
+use crate::names::ShoulderName;
+use crate::shoulder::Shoulder;
+
+/// Path of the shorten endpoint
+pub const SHORTEN_PATH: &str = "/api/v1/shorten";
+
+/// Binding metadata key holding what a shortened ARK was derived from
+pub const SOURCE_METADATA_KEY: &str = "shortened_from";
+
+/// Longest content hash accepted, including its algorithm
+const MAX_HASH_LENGTH: usize = 160;
+
+/// A service-managed shoulder whose ARKs are derived from the URLs, or content hashes, they
+/// stand for, making it a persistent short-link generator under the service's NAAN
+///
+/// Nothing is minted on the shoulder: the same URL or hash always derives the same ARK.
+#[derive(Clone, Debug)]
+pub struct Shortener {
+    pub shoulder: ShoulderName,
+}
+
+/// An ARK the shortener derived, and whether this request bound it
+#[derive(Clone, Debug)]
+pub struct Shortened {
+    /// The ARK as emitted, following the shoulder's ARK format
+    pub ark: String,
+    pub canonical: String,
+    pub target: String,
+    pub status: ArkStatus,
+    pub created: bool,
+}
+
+/// What an ARK is derived from: the URL, normalized by parsing it, or a content hash given as
+/// `algorithm:hex`, lowercased
+pub fn source_of(target: &str, hash: Option<&str>) -> Result<String, String> {
+    let Some(hash) = hash else {
+        return Ok(format!("url:{}", target));
+    };
+    let valid = hash.len() <= MAX_HASH_LENGTH
+        && hash.split_once(':').is_some_and(|(algorithm, digest)| {
+            !algorithm.is_empty()
+                && algorithm
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !digest.is_empty()
+                && digest.chars().all(|c| c.is_ascii_hexdigit())
+        });
+    if !valid {
+        return Err(format!(
+            "hash must be an algorithm and a hex digest, like sha256:9f86d0..., of at most {} characters",
+            MAX_HASH_LENGTH
+        ));
+    }
+    Ok(format!("hash:{}", hash.to_ascii_lowercase()))
+}
+
+/// The blade derived from `source` on the `attempt`-th try, with its check character if the
+/// shoulder uses one
+///
+/// Later attempts, for when an earlier blade is taken by another source, hash the source with
+/// the attempt number. The blade keeps the shoulder's configured length even when the blade
+/// advisor lengthens minted blades, so the derivation never changes.
+fn derived_blade(
+    name: &str,
+    shoulder: &Shoulder,
+    default_blade_length: usize,
+    source: &str,
+    attempt: u32,
+) -> String {
+    let template = shoulder.blade_template(shoulder.blade_length.unwrap_or(default_blade_length));
+    let input = match attempt {
+        0 => source.to_string(),
+        attempt => format!("{}\n{}", source, attempt),
+    };
+    let blade = template.derived_blade(digest(&SHA256, input.as_bytes()).as_ref());
+    with_check_character(name, blade, template.check_character())
+}
+
+/// Derives the ARK for `url`, or for `hash` when given, and binds it to `url` unless it
+/// already is
+///
+/// An ARK derived from the same source is answered as it is, including when it was rebound
+/// or tombstoned since. A content hash already shortened to another URL is rejected instead:
+/// the ARK must be rebound through the batch API.
+pub fn shorten(state: &AppState, url: &str, hash: Option<&str>) -> Result<Shortened, AppError> {
+    let shortener = state
+        .shortener
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Shortening is disabled".to_string()))?;
+    let name = shortener.shoulder.as_str();
+    let shoulder = state
+        .shoulders
+        .get(name)
+        .ok_or(AppError::ShoulderNotFound)?;
+    let target = Url::parse(url)
+        .map_err(|e| AppError::InvalidRequest(format!("Invalid url: {}", e)))?
+        .to_string();
+    shoulder
+        .validate_target(&target)
+        .map_err(AppError::InvalidRequest)?;
+    let source = source_of(&target, hash).map_err(AppError::InvalidRequest)?;
+    let format = state.ark_format_for(shoulder);
+
+    for attempt in 0..=state.collision_policy.max_retries {
+        let blade = derived_blade(name, shoulder, state.default_blade_length, &source, attempt);
+        let emitted = format.format(&state.naan, name, &blade, "");
+        let ark = parse_ark(&emitted).expect("derived ARKs parse");
+        let canonical = ark.canonical().to_string();
+        let existing = match state.bindings.get(&ark)? {
+            // ARKs registered or imported onto the shoulder are taken even without a binding
+            None if state.store.contains(&canonical)? => continue,
+            None => {
+                let binding = Binding {
+                    target: target.clone(),
+                    metadata: shoulder.metadata_with_defaults(BTreeMap::from([(
+                        SOURCE_METADATA_KEY.to_string(),
+                        source.clone(),
+                    )])),
+                    bound_at: Utc::now(),
+                    lifecycle: Default::default(),
+                };
+                // The binding store settles concurrent requests, on this instance or another:
+                // only the first binds, the others find its binding
+                state.bindings.bind_new(&ark, &binding)?
+            }
+            existing => existing,
+        };
+        let Some(binding) = existing else {
+            state
+                .store
+                .record(name, std::slice::from_ref(&canonical), &BTreeMap::new())?;
+            tracing::info!(shoulder = %name, ark = %canonical, attempt, "URL shortened");
+            return Ok(Shortened {
+                ark: emitted,
+                canonical,
+                target,
+                status: ArkStatus::Active,
+                created: true,
+            });
+        };
+        if binding.metadata.get(SOURCE_METADATA_KEY) != Some(&source) {
+            continue;
+        }
+        // A request that bound the ARK may have failed before recording it
+        if !state.store.contains(&canonical)? {
+            state
+                .store
+                .record(name, std::slice::from_ref(&canonical), &BTreeMap::new())?;
+        }
+        if let Some(hash) = hash
+            && binding.target != target
+            && binding.lifecycle.status == ArkStatus::Active
+        {
+            return Err(AppError::InvalidRequest(format!(
+                "{} was already shortened to {}; rebind {} to change it",
+                hash, binding.target, emitted
+            )));
+        }
+        return Ok(Shortened {
+            ark: emitted,
+            canonical,
+            target: binding.target,
+            status: binding.lifecycle.status,
+            created: false,
+        });
+    }
+    tracing::error!(
+        shoulder = %name,
+        "Shortening failed: every derived ARK is taken"
+    );
+    Err(AppError::MintExhausted)
+}
+
+/// Set up the shortener from environment variables; `None` unless `SHORTEN_SHOULDER` names
+/// one of `shoulders`
+pub fn load_shortener_from_env(
+    shoulders: &HashMap<ShoulderName, Shoulder>,
+) -> Result<Option<Shortener>, String> {
+    let Ok(shoulder) = std::env::var("SHORTEN_SHOULDER") else {
+        return Ok(None);
+    };
+    let shoulder: ShoulderName = shoulder
+        .parse()
+        .map_err(|e| format!("Invalid SHORTEN_SHOULDER: {}", e))?;
+    if !shoulders.contains_key(&shoulder) {
+        return Err(format!(
+            "SHORTEN_SHOULDER {} is not a configured shoulder",
+            shoulder
+        ));
+    }
+    Ok(Some(Shortener { shoulder }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixture_state;
+
+    fn shortening_state() -> AppState {
+        let mut state = fixture_state();
+        state.shortener = Some(Shortener {
+            shoulder: "b3".parse().unwrap(),
+        });
+        state
+    }
+
+    #[test]
+    fn test_same_url_gets_same_ark() {
+        let state = shortening_state();
+        let first = shorten(&state, "https://example.org/a/long/path?q=1", None).unwrap();
+        assert!(first.created);
+        assert!(first.ark.starts_with("ark:12345/b3"));
+        assert!(state.store.contains(&first.canonical).unwrap());
+
+        let again = shorten(&state, "https://example.org/a/long/path?q=1", None).unwrap();
+        assert_eq!(
+            (again.ark.as_str(), again.created),
+            (first.ark.as_str(), false)
+        );
+        let other = shorten(&state, "https://example.org/b", None).unwrap();
+        assert_ne!(other.ark, first.ark);
+
+        // A fresh service derives the same ARK
+        let fresh = shortening_state();
+        let derived = shorten(&fresh, "https://example.org/a/long/path?q=1", None).unwrap();
+        assert_eq!(derived.ark, first.ark);
+    }
+
+    #[test]
+    fn test_content_hash_keeps_its_ark() {
+        let state = shortening_state();
+        let hash = "SHA256:9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        let first = shorten(&state, "https://example.org/v1", Some(hash)).unwrap();
+        let same = shorten(
+            &state,
+            "https://example.org/v1",
+            Some(&hash.to_ascii_lowercase()),
+        )
+        .unwrap();
+        assert_eq!((same.ark, same.created), (first.ark, false));
+        assert!(matches!(
+            shorten(&state, "https://example.org/v2", Some(hash)),
+            Err(AppError::InvalidRequest(_))
+        ));
+
+        for hash in ["9f86d081", "sha256:", "sha256:xyz", "sha 256:9f86"] {
+            assert!(
+                source_of("https://example.org/", Some(hash)).is_err(),
+                "{}",
+                hash
+            );
+        }
+    }
+
+    #[test]
+    fn test_concurrent_shortening_binds_one_ark() {
+        let state = shortening_state();
+        let store = state.store.clone();
+        let instances: Vec<AppState> = (0..4)
+            .map(|_| AppState {
+                store: store.clone(),
+                bindings: crate::bindings::Bindings::new(store.clone()),
+                ..shortening_state()
+            })
+            .collect();
+
+        let shortened: Vec<Shortened> = std::thread::scope(|scope| {
+            let handles: Vec<_> = instances
+                .iter()
+                .map(|instance| {
+                    scope.spawn(|| shorten(instance, "https://example.org/raced", None).unwrap())
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert!(shortened.iter().all(|s| s.ark == shortened[0].ark));
+        assert_eq!(shortened.iter().filter(|s| s.created).count(), 1);
+        assert_eq!(store.minted().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_taken_arks_are_skipped() {
+        let state = shortening_state();
+        let b3 = &state.shoulders["b3"];
+        let source = source_of("https://example.org/a", None).unwrap();
+        let taken = format!(
+            "ark:12345/b3{}",
+            derived_blade("b3", b3, state.default_blade_length, &source, 0)
+        );
+        state
+            .store
+            .record("b3", std::slice::from_ref(&taken), &BTreeMap::new())
+            .unwrap();
+
+        let shortened = shorten(&state, "https://example.org/a", None).unwrap();
+        assert_ne!(shortened.canonical, taken);
+        assert!(shortened.created);
+    }
+
+    #[test]
+    fn test_shortener_shoulder_is_not_minted_on() {
+        let state = shortening_state();
+        assert!(matches!(
+            crate::minting::mint_arks(&state, "b3", 1),
+            Err(AppError::InvalidRequest(_))
+        ));
+        assert_eq!(crate::minting::mint_arks(&state, "x6", 1).unwrap().len(), 1);
+    }
+}
@@ -1,8 +1,17 @@
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use url::Url;
 
-use crate::ark::Ark;
+use crate::ark::{Ark, ArkFormat};
+use crate::config::BETANUMERIC;
+use crate::mint_quota::MintQuota;
+use crate::names::{Naan, ShoulderName};
+use crate::platform::Platform;
+use crate::qualifier::QualifierEncoding;
+use crate::template::{Alphabet, Generator, NoidTemplate};
 
 /// Represents a shoulder configuration in the ARK system
 ///
@@ -36,6 +45,7 @@ use crate::ark::Ark;
 /// - `${content}` - Everything after "ark:": `12345/x8rd9/page2.pdf`
 /// - `${prefix}` - NAAN: `12345`
 /// - `${value}` - Everything after NAAN/: `x8rd9/page2.pdf`
+/// - `${metadata_url}` - The ARK's URL under `metadata_route_pattern`
 ///
 /// ## Template Examples
 ///
@@ -82,11 +92,30 @@ use crate::ark::Ark;
 /// }
 /// ```
 /// `ark:12345/z9item/file.txt` → `https://storage.example.org/12345/items/z9item/file.txt`
-#[derive(Clone, Debug, Deserialize, Serialize)]
+///
+/// ## Delegated metadata
+///
+/// With a `metadata_route_pattern`, inflection requests (`?`, `??`, `?info`) are sent to the
+/// project's own metadata service instead of the object's target:
+///
+/// ```json
+/// {
+///   "m5": {
+///     "route_pattern": "https://viewer.example.org/?manifest=${metadata_url}",
+///     "metadata_route_pattern": "https://meta.example.org/records/${value}",
+///     "project_name": "Delegated Metadata"
+///   }
+/// }
+/// ```
+/// `ark:12345/m5item?info` → `https://meta.example.org/records/m5item`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[schemars(description = "Configuration of one shoulder of the ARK service")]
 pub struct Shoulder {
     /// The routing pattern/template for this shoulder
+    #[schemars(length(min = 1))]
     pub route_pattern: String,
     /// The human-readable project name associated with this shoulder
+    #[schemars(length(min = 1))]
     pub project_name: String,
     /// Whether this shoulder uses a check character (default: true)
     #[serde(default = "default_uses_check_character")]
@@ -94,13 +123,421 @@ pub struct Shoulder {
     /// Optional blade length for this shoulder, excluding the check character.
     /// If not specified, defaults to the global DEFAULT_BLADE_LENGTH.
     /// When uses_check_character is true, the final blade will be one character longer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = MIN_BLADE_LENGTH, max = MAX_BLADE_LENGTH))]
     pub blade_length: Option<usize>,
+    /// Optional free-form note kept alongside the configuration (e.g. why a pattern changed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Who is responsible for this shoulder's namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<Contact>,
+    /// Ask search engines not to index resolver URLs for this shoulder (`X-Robots-Tag: noindex`),
+    /// so only the target pages get indexed, and leave it out of the sitemaps (default: false)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub noindex: bool,
+    /// How ARKs on this shoulder are spelled when emitted; overrides the global `ARK_FORMAT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ark_format: Option<ArkFormat>,
+    /// Redirect used for non-GET requests (POST, PUT, ...) so the target receives the original
+    /// method and body. Non-GET requests are rejected with 405 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method_redirect: Option<MethodRedirect>,
+    /// URL that receives batched reports of failed resolutions on this shoulder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(url)]
+    pub failure_callback: Option<String>,
+    /// Hosts redirects may point to, as exact names or `*.example.org` for any subdomain.
+    /// Any host is allowed when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_target_hosts: Option<Vec<String>>,
+    /// Routing pattern for inflection requests (`?`, `??`, `?info`), which otherwise go to the
+    /// `route_pattern` target. Also available to `route_pattern` as `${metadata_url}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_route_pattern: Option<String>,
+    /// Answer inflection requests with the response of the target's Name Mapping Authority,
+    /// fetched from the computed target with the inflection appended (or from the
+    /// `metadata_route_pattern` URL), instead of redirecting (default: false)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forward_inflections: bool,
+    /// Persistence commitment for this shoulder's ARKs, published in
+    /// `/.well-known/ark-configuration`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+    /// Whether the shoulder is advertised in `/api/v1/info`, `/.well-known/ark-configuration`,
+    /// and ResourceSync change lists (default: true). Private shoulders still resolve.
+    #[serde(default = "default_public")]
+    pub public: bool,
+    /// Only resolve ARKs on this shoulder for requests with an admin API key (default: false)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_auth: bool,
+    /// How the qualifier is forwarded into the route pattern: `verbatim`, `decode`, or
+    /// `reencode` (default: verbatim)
+    #[serde(default, skip_serializing_if = "QualifierEncoding::is_verbatim")]
+    pub qualifier_encoding: QualifierEncoding,
+    /// How blades are generated: `random`, or `sequential` from a counter kept in the ARK
+    /// store, like NOID's sequential templates (default: random)
+    #[serde(default, skip_serializing_if = "Minter::is_random")]
+    pub minter: Minter,
+    /// NOID mask template describing the blades, e.g. `.reedeedk`: generator `r`, `s` or `z`,
+    /// then `d` (digit) or `e` (betanumeric) per position, and `k` for a check character. Takes
+    /// the place of `blade_length`, `uses_check_character` and `minter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>", regex(pattern = r"^[^.]*\.[rsz][de]+k?$"))]
+    pub template: Option<NoidTemplate>,
+    /// Most ARKs that may be minted on this shoulder per hour and per UTC day, counted on
+    /// each instance; mints beyond it answer 429 Too Many Requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint_quota: Option<MintQuota>,
+    /// Metadata given to every ARK minted and bound on this shoulder, e.g. `publisher` or
+    /// `rights`; metadata sent with the binding overrides it key by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub default_metadata: BTreeMap<String, String>,
+    /// Repository platform (`dspace`, `omeka`, `fedora` or `islandora`, `samvera`) whose URL
+    /// layout ARKs resolve into; `route_pattern` is then the repository's base URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+    /// Characters blades are drawn from: `betanumeric`, `digits`, `alphanumeric`, or
+    /// `{"custom": "..."}` (default: betanumeric). Alphabets with other characters than
+    /// betanumeric ones need `uses_check_character` set to false.
+    #[serde(default, skip_serializing_if = "Alphabet::is_betanumeric")]
+    pub alphabet: Alphabet,
+    /// Extra headers sent with this shoulder's redirects and inflection responses, e.g.
+    /// `Access-Control-Allow-Origin` for viewers that need CORS on the redirect hop itself
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub response_headers: BTreeMap<String, String>,
+    /// Qualifiers naming component objects that are citable on their own, e.g. `/page*` for
+    /// `/page2`; `*` stands for one or more characters of a path segment. Components can have a
+    /// binding of their own, which starts with the metadata of the ARK they are part of.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+}
+
+/// A redirect target that failed security validation
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockedTarget {
+    /// The URL computed from the route pattern
+    pub target: String,
+    /// Why it was rejected
+    pub reason: String,
+}
+
+/// What resolving an ARK leads to, for the HTTP resolver and library callers alike
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResolutionOutcome {
+    /// Redirect to the target with this status (302, or 307/308 for other methods than GET
+    /// and HEAD)
+    Redirect { url: String, status: StatusCode },
+    /// The computed target failed security validation
+    Blocked(BlockedTarget),
+    /// Answer the inflection request with a page about the ARK instead of redirecting
+    ///
+    /// The page is fetched from `source` (see `forward_inflections`); `target` is where the ARK
+    /// itself resolves, for describing it locally if the source fails.
+    LandingPage {
+        source: String,
+        target: Option<String>,
+    },
+    /// The request method is not redirected on this shoulder
+    MethodNotAllowed,
+    /// The ARK does not belong to this NAAN or a registered shoulder
+    NotFound,
+}
+
+/// Resolve an ARK against a set of shoulders, as the resolver does for a request with `method`
+///
+/// Authentication of shoulders with `require_auth` is left to the caller.
+pub fn resolve_ark(
+    naan: &Naan,
+    shoulders: &HashMap<ShoulderName, Shoulder>,
+    parsed_ark: &Ark,
+    method: &Method,
+) -> ResolutionOutcome {
+    if parsed_ark.naan != *naan {
+        return ResolutionOutcome::NotFound;
+    }
+    shoulders
+        .get(&parsed_ark.shoulder)
+        .map_or(ResolutionOutcome::NotFound, |shoulder| {
+            shoulder.resolve(parsed_ark, method)
+        })
+}
+
+/// Method-preserving redirect status for non-GET resolution requests
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodRedirect {
+    /// 307 Temporary Redirect
+    Temporary,
+    /// 308 Permanent Redirect
+    Permanent,
+}
+
+/// How a shoulder generates blades
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Minter {
+    /// Random betanumeric blades (default)
+    #[default]
+    Random,
+    /// Consecutive positions of a counter, written in betanumeric and padded with `0` to the
+    /// blade length, so blades are issued in order and without gaps
+    Sequential,
+}
+
+impl Minter {
+    pub fn is_random(&self) -> bool {
+        *self == Minter::Random
+    }
+}
+
+/// Contact and ownership details for the project behind a shoulder
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct Contact {
+    /// Name of the responsible person or team
+    pub name: String,
+    /// Email address used for alerts about this shoulder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Owning organization
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+}
+
+/// Shortest blade a shoulder may configure, excluding the check character
+pub const MIN_BLADE_LENGTH: usize = 1;
+
+/// Longest blade a shoulder may configure, excluding the check character
+pub const MAX_BLADE_LENGTH: usize = 64;
+
+/// Template variables accepted as both `${var}` and `{var}`
+const TEMPLATE_VARIABLES: [&str; 6] = [
+    "pid",
+    "scheme",
+    "content",
+    "prefix",
+    "value",
+    "metadata_url",
+];
+
+/// Headers the resolver sets itself, which `response_headers` may not override
+const RESERVED_RESPONSE_HEADERS: [&str; 8] = [
+    "location",
+    "content-type",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "set-cookie",
+    "x-robots-tag",
+    "x-ark-degraded",
+];
+
+/// Why a shoulder configuration is unusable
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShoulderError {
+    MissingRoutePattern,
+    MissingProjectName,
+    /// `route_pattern` or `metadata_route_pattern` failed security validation
+    InvalidRoutePattern(String),
+    /// A `${var}` or `{var}` that is never substituted
+    UnknownTemplateVariable(String),
+    BladeLengthOutOfRange(usize),
+    InvalidFailureCallback(String),
+    InvalidAllowedTargetHosts(String),
+    InvalidArkFormat(String),
+    InvalidTemplate(String),
+    InvalidMintQuota(String),
+    InvalidAlphabet(String),
+    InvalidResponseHeaders(String),
+    InvalidComponents(String),
+}
+
+impl std::fmt::Display for ShoulderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShoulderError::MissingRoutePattern => f.write_str("route_pattern is required"),
+            ShoulderError::MissingProjectName => f.write_str("project_name is required"),
+            ShoulderError::InvalidRoutePattern(reason) => f.write_str(reason),
+            ShoulderError::UnknownTemplateVariable(variable) => {
+                write!(
+                    f,
+                    "Unknown template variable in route pattern: {}",
+                    variable
+                )
+            }
+            ShoulderError::BladeLengthOutOfRange(length) => write!(
+                f,
+                "blade_length must be between {} and {}, found: {}",
+                MIN_BLADE_LENGTH, MAX_BLADE_LENGTH, length
+            ),
+            ShoulderError::InvalidFailureCallback(reason)
+            | ShoulderError::InvalidAllowedTargetHosts(reason) => f.write_str(reason),
+            ShoulderError::InvalidArkFormat(reason) => write!(f, "Invalid ark_format: {}", reason),
+            ShoulderError::InvalidTemplate(reason) => write!(f, "Invalid template: {}", reason),
+            ShoulderError::InvalidMintQuota(reason) => write!(f, "Invalid mint_quota: {}", reason),
+            ShoulderError::InvalidAlphabet(reason) => write!(f, "Invalid alphabet: {}", reason),
+            ShoulderError::InvalidResponseHeaders(reason) => {
+                write!(f, "Invalid response_headers: {}", reason)
+            }
+            ShoulderError::InvalidComponents(reason) => {
+                write!(f, "Invalid components: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShoulderError {}
+
+/// Builds a [`Shoulder`] that is checked for everything that would otherwise only fail at
+/// resolve or mint time
+#[derive(Clone, Debug, Default)]
+pub struct ShoulderBuilder {
+    shoulder: Shoulder,
+}
+
+impl ShoulderBuilder {
+    pub fn route_pattern(mut self, route_pattern: impl Into<String>) -> Self {
+        self.shoulder.route_pattern = route_pattern.into();
+        self
+    }
+
+    pub fn project_name(mut self, project_name: impl Into<String>) -> Self {
+        self.shoulder.project_name = project_name.into();
+        self
+    }
+
+    pub fn uses_check_character(mut self, uses_check_character: bool) -> Self {
+        self.shoulder.uses_check_character = uses_check_character;
+        self
+    }
+
+    pub fn blade_length(mut self, blade_length: usize) -> Self {
+        self.shoulder.blade_length = Some(blade_length);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.shoulder.comment = Some(comment.into());
+        self
+    }
+
+    pub fn contact(mut self, contact: Contact) -> Self {
+        self.shoulder.contact = Some(contact);
+        self
+    }
+
+    pub fn noindex(mut self, noindex: bool) -> Self {
+        self.shoulder.noindex = noindex;
+        self
+    }
+
+    pub fn ark_format(mut self, ark_format: ArkFormat) -> Self {
+        self.shoulder.ark_format = Some(ark_format);
+        self
+    }
+
+    pub fn method_redirect(mut self, method_redirect: MethodRedirect) -> Self {
+        self.shoulder.method_redirect = Some(method_redirect);
+        self
+    }
+
+    pub fn failure_callback(mut self, failure_callback: impl Into<String>) -> Self {
+        self.shoulder.failure_callback = Some(failure_callback.into());
+        self
+    }
+
+    pub fn allowed_target_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.shoulder.allowed_target_hosts = Some(hosts);
+        self
+    }
+
+    pub fn metadata_route_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.shoulder.metadata_route_pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn forward_inflections(mut self, forward_inflections: bool) -> Self {
+        self.shoulder.forward_inflections = forward_inflections;
+        self
+    }
+
+    pub fn commitment(mut self, commitment: impl Into<String>) -> Self {
+        self.shoulder.commitment = Some(commitment.into());
+        self
+    }
+
+    pub fn public(mut self, public: bool) -> Self {
+        self.shoulder.public = public;
+        self
+    }
+
+    pub fn require_auth(mut self, require_auth: bool) -> Self {
+        self.shoulder.require_auth = require_auth;
+        self
+    }
+
+    pub fn qualifier_encoding(mut self, qualifier_encoding: QualifierEncoding) -> Self {
+        self.shoulder.qualifier_encoding = qualifier_encoding;
+        self
+    }
+
+    pub fn minter(mut self, minter: Minter) -> Self {
+        self.shoulder.minter = minter;
+        self
+    }
+
+    pub fn template(mut self, template: NoidTemplate) -> Self {
+        self.shoulder.template = Some(template);
+        self
+    }
+
+    pub fn mint_quota(mut self, mint_quota: MintQuota) -> Self {
+        self.shoulder.mint_quota = Some(mint_quota);
+        self
+    }
+
+    pub fn default_metadata(mut self, default_metadata: BTreeMap<String, String>) -> Self {
+        self.shoulder.default_metadata = default_metadata;
+        self
+    }
+
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.shoulder.platform = Some(platform);
+        self
+    }
+
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.shoulder.alphabet = alphabet;
+        self
+    }
+
+    pub fn response_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.shoulder
+            .response_headers
+            .insert(name.into(), value.into());
+        self
+    }
+
+    pub fn component(mut self, pattern: impl Into<String>) -> Self {
+        self.shoulder.components.push(pattern.into());
+        self
+    }
+
+    /// Validates the configuration, see [`Shoulder::validate`]
+    pub fn build(self) -> Result<Shoulder, ShoulderError> {
+        self.shoulder.validate()?;
+        Ok(self.shoulder)
+    }
 }
 
 fn default_uses_check_character() -> bool {
     true
 }
 
+fn default_public() -> bool {
+    true
+}
+
 impl Default for Shoulder {
     fn default() -> Self {
         Self {
@@ -108,41 +545,225 @@ impl Default for Shoulder {
             project_name: String::new(),
             uses_check_character: true,
             blade_length: None,
+            comment: None,
+            contact: None,
+            noindex: false,
+            ark_format: None,
+            method_redirect: None,
+            failure_callback: None,
+            allowed_target_hosts: None,
+            metadata_route_pattern: None,
+            forward_inflections: false,
+            commitment: None,
+            public: true,
+            require_auth: false,
+            qualifier_encoding: QualifierEncoding::Verbatim,
+            minter: Minter::Random,
+            template: None,
+            mint_quota: None,
+            default_metadata: BTreeMap::new(),
+            platform: None,
+            alphabet: Alphabet::Betanumeric,
+            response_headers: BTreeMap::new(),
+            components: Vec::new(),
         }
     }
 }
 
 impl Shoulder {
-    /// Validate the route_pattern for security issues
+    /// Starts a shoulder that is validated when built
+    pub fn builder() -> ShoulderBuilder {
+        ShoulderBuilder::default()
+    }
+
+    /// Runs every configuration check: required fields, route pattern security, template
+    /// variables, blade length bounds, failure callback, target host allowlist, ARK format,
+    /// template, mint quota, alphabet, response headers, and component patterns
+    pub fn validate(&self) -> Result<(), ShoulderError> {
+        if self.route_pattern.trim().is_empty() {
+            return Err(ShoulderError::MissingRoutePattern);
+        }
+        if self.project_name.trim().is_empty() {
+            return Err(ShoulderError::MissingProjectName);
+        }
+        self.validate_route_pattern()
+            .map_err(ShoulderError::InvalidRoutePattern)?;
+        let route_pattern = self.effective_route_pattern();
+        for pattern in
+            std::iter::once(&*route_pattern).chain(self.metadata_route_pattern.as_deref())
+        {
+            if let Some(variable) = unknown_template_variable(pattern) {
+                return Err(ShoulderError::UnknownTemplateVariable(variable));
+            }
+        }
+        if let Some(length) = self.blade_length
+            && !(MIN_BLADE_LENGTH..=MAX_BLADE_LENGTH).contains(&length)
+        {
+            return Err(ShoulderError::BladeLengthOutOfRange(length));
+        }
+        self.validate_failure_callback()
+            .map_err(ShoulderError::InvalidFailureCallback)?;
+        self.validate_allowed_target_hosts()
+            .map_err(ShoulderError::InvalidAllowedTargetHosts)?;
+        if let Some(format) = &self.ark_format {
+            format.validate().map_err(ShoulderError::InvalidArkFormat)?;
+        }
+        self.validate_template()
+            .map_err(ShoulderError::InvalidTemplate)?;
+        if let Some(quota) = &self.mint_quota {
+            quota.validate().map_err(ShoulderError::InvalidMintQuota)?;
+        }
+        self.alphabet
+            .validate()
+            .map_err(ShoulderError::InvalidAlphabet)?;
+        if self.check_character() && !self.alphabet.supports_check_character() {
+            return Err(ShoulderError::InvalidAlphabet(
+                "check characters need betanumeric blades; set uses_check_character to false"
+                    .to_string(),
+            ));
+        }
+        self.validate_response_headers()
+            .map_err(ShoulderError::InvalidResponseHeaders)?;
+        self.validate_components()
+            .map_err(ShoulderError::InvalidComponents)?;
+        Ok(())
+    }
+
+    /// Runs [`Shoulder::validate`] and the checks that need the shoulder's name
+    pub fn validate_as(&self, name: &str) -> Result<(), ShoulderError> {
+        self.validate()?;
+        match &self.template {
+            Some(template) if !template.prefix().is_empty() && template.prefix() != name => {
+                Err(ShoulderError::InvalidTemplate(format!(
+                    "prefix {} is not the shoulder {}",
+                    template.prefix(),
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the template does not contradict the settings it replaces
+    fn validate_template(&self) -> Result<(), String> {
+        let Some(template) = &self.template else {
+            return Ok(());
+        };
+        if self.blade_length.is_some() {
+            return Err(
+                "blade_length cannot be set with a template, whose mask sets it".to_string(),
+            );
+        }
+        if self.minter == Minter::Sequential && template.generator() == Generator::Random {
+            return Err("minter is sequential, but the template's generator is r".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether minted blades end in a check character: the template's `k` if there is a
+    /// template, otherwise `uses_check_character`
+    pub fn check_character(&self) -> bool {
+        self.template
+            .as_ref()
+            .map_or(self.uses_check_character, NoidTemplate::check_character)
+    }
+
+    /// The shape of the blades minted on this shoulder: its template, or `blade_length`
+    /// characters of its alphabet drawn as `minter` says
+    pub fn blade_template(&self, blade_length: usize) -> NoidTemplate {
+        let template = self.template.clone().unwrap_or_else(|| {
+            let generator = match self.minter {
+                Minter::Random => Generator::Random,
+                Minter::Sequential => Generator::Sequential,
+            };
+            NoidTemplate::flat(generator, blade_length, self.uses_check_character)
+        });
+        template.with_alphabet(self.alphabet.clone())
+    }
+
+    /// Whether a blade, without hyphens, only has characters this shoulder mints: its
+    /// alphabet and the digits of `d` template positions, then a betanumeric check character
+    pub fn accepts_blade(&self, blade: &str) -> bool {
+        let (body, check) = match blade.len() {
+            len if self.check_character() && len > 1 => blade.split_at(len - 1),
+            _ => (blade, ""),
+        };
+        body.bytes()
+            .all(|c| c.is_ascii_digit() || self.alphabet.characters().contains(&c))
+            && check.bytes().all(|c| BETANUMERIC.contains(&c))
+    }
+
+    /// Validate the route_pattern and metadata_route_pattern for security issues
     ///
     /// Ensures:
     /// - Pattern is a valid URL
     /// - Scheme is http or https only
     /// - Template variables appear only in path or query components
     /// - No control characters (CR, LF, null bytes)
+    /// - `${metadata_url}` is only used when there is a metadata_route_pattern to fill it
     pub fn validate_route_pattern(&self) -> Result<(), String> {
+        if self.platform.is_some() {
+            self.validate_platform_base_url()?;
+        }
+        let route_pattern = self.effective_route_pattern();
+        self.validate_pattern(&route_pattern)?;
+
+        let uses_metadata_url = route_pattern.contains("{metadata_url}");
+        match &self.metadata_route_pattern {
+            None if uses_metadata_url => Err(
+                "route_pattern uses ${metadata_url} but metadata_route_pattern is not set"
+                    .to_string(),
+            ),
+            None => Ok(()),
+            Some(pattern) if pattern.contains("{metadata_url}") => {
+                Err("metadata_route_pattern cannot use ${metadata_url}".to_string())
+            }
+            Some(pattern) => self
+                .validate_pattern(pattern)
+                .map_err(|e| format!("metadata_route_pattern: {}", e)),
+        }
+    }
+
+    /// The route pattern ARKs resolve by: `route_pattern`, expanded for the `platform` if set
+    pub fn effective_route_pattern(&self) -> Cow<'_, str> {
+        match self.platform {
+            Some(platform) => Cow::Owned(platform.route_pattern(&self.route_pattern)),
+            None => Cow::Borrowed(&self.route_pattern),
+        }
+    }
+
+    /// With a platform, the route pattern must be a plain base URL for the platform to extend
+    fn validate_platform_base_url(&self) -> Result<(), String> {
+        let invalid = || {
+            "route_pattern must be the repository's base URL, without template variables, query \
+             or fragment, when platform is set"
+                .to_string()
+        };
+        if has_template_vars(&self.route_pattern) {
+            return Err(invalid());
+        }
+        let url = Url::parse(&self.route_pattern).map_err(|_| invalid())?;
+        if url.query().is_some() || url.fragment().is_some() {
+            return Err(invalid());
+        }
+        Ok(())
+    }
+
+    /// Validate one routing pattern
+    fn validate_pattern(&self, pattern: &str) -> Result<(), String> {
         // Check for control characters
-        if self.route_pattern.chars().any(|c| c.is_control()) {
+        if pattern.chars().any(|c| c.is_control()) {
             return Err("route_pattern contains control characters".to_string());
         }
 
-        // Check if pattern has template variables
-        let has_template_vars = self.route_pattern.contains("${")
-            || self.route_pattern.contains("{pid}")
-            || self.route_pattern.contains("{scheme}")
-            || self.route_pattern.contains("{content}")
-            || self.route_pattern.contains("{prefix}")
-            || self.route_pattern.contains("{value}")
-            || self.route_pattern.contains("{naan}");
-
         // If no template variables, just validate the base URL
-        if !has_template_vars {
-            return self.validate_base_url(&self.route_pattern);
+        if !has_template_vars(pattern) {
+            return self.validate_base_url(pattern);
         }
 
         // For templates, replace variables with safe placeholders to check structure
-        let test_url = self
-            .route_pattern
+        let test_url = pattern
+            .replace("${metadata_url}", "placeholder")
             .replace("${pid}", "placeholder")
             .replace("${scheme}", "placeholder")
             .replace("${content}", "placeholder")
@@ -153,6 +774,7 @@ impl Shoulder {
             .replace("{content}", "placeholder")
             .replace("{prefix}", "placeholder")
             .replace("{value}", "placeholder")
+            .replace("{metadata_url}", "placeholder")
             .replace("{naan}", "placeholder");
 
         self.validate_base_url(&test_url)?;
@@ -161,9 +783,9 @@ impl Shoulder {
         // Parse the original pattern to find where variables are
         if let Ok(parsed) = Url::parse(&test_url) {
             // Check if scheme contains template markers in original
-            let scheme_end = self.route_pattern.find("://").unwrap_or(0);
+            let scheme_end = pattern.find("://").unwrap_or(0);
             if scheme_end > 0 {
-                let scheme_part = &self.route_pattern[..scheme_end];
+                let scheme_part = &pattern[..scheme_end];
                 if scheme_part.contains('$') || scheme_part.contains('{') {
                     return Err("Template variables not allowed in URL scheme position".to_string());
                 }
@@ -172,7 +794,7 @@ impl Shoulder {
             // Check if host contains template markers
             if parsed.host_str().is_some() {
                 // Find the host section in original pattern
-                if let Some(after_scheme) = self.route_pattern.split("://").nth(1) {
+                if let Some(after_scheme) = pattern.split("://").nth(1) {
                     // Host is before the first '/' or '?' or end of string
                     let host_end = after_scheme
                         .find('/')
@@ -192,6 +814,22 @@ impl Shoulder {
         Ok(())
     }
 
+    /// Validate the failure_callback URL, if one is configured
+    pub fn validate_failure_callback(&self) -> Result<(), String> {
+        let Some(callback) = &self.failure_callback else {
+            return Ok(());
+        };
+        let parsed =
+            Url::parse(callback).map_err(|e| format!("Invalid failure_callback URL: {}", e))?;
+        match parsed.scheme() {
+            "http" | "https" => Ok(()),
+            other => Err(format!(
+                "failure_callback must use http or https, found: {}",
+                other
+            )),
+        }
+    }
+
     /// Validate a URL string
     fn validate_base_url(&self, url_str: &str) -> Result<(), String> {
         let parsed =
@@ -214,15 +852,192 @@ impl Shoulder {
 
         // Only allow http and https schemes
         match parsed.scheme() {
-            "http" | "https" => Ok(parsed),
-            other => Err(format!(
-                "Redirect URL has invalid scheme (expected http/https): {}",
-                other
-            )),
+            "http" | "https" => {}
+            other => {
+                return Err(format!(
+                    "Redirect URL has invalid scheme (expected http/https): {}",
+                    other
+                ));
+            }
+        }
+
+        // Only allow hosts on the shoulder's allowlist, if it has one
+        if let Some(allowed) = &self.allowed_target_hosts {
+            let host = parsed.host_str().unwrap_or_default();
+            if !allowed.iter().any(|pattern| host_matches(pattern, host)) {
+                return Err(format!(
+                    "Redirect URL host is not in allowed_target_hosts: {}",
+                    host
+                ));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Validate the allowed_target_hosts entries, if an allowlist is configured
+    pub fn validate_allowed_target_hosts(&self) -> Result<(), String> {
+        let Some(allowed) = &self.allowed_target_hosts else {
+            return Ok(());
+        };
+        if allowed.is_empty() {
+            return Err("allowed_target_hosts must not be empty".to_string());
+        }
+        for pattern in allowed {
+            let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+            if host.is_empty()
+                || host.contains(['*', '/', ':'])
+                || host.chars().any(char::is_whitespace)
+            {
+                return Err(format!(
+                    "Invalid entry in allowed_target_hosts: {}",
+                    pattern
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the response_headers names and values, leaving the resolver's own headers alone
+    pub fn validate_response_headers(&self) -> Result<(), String> {
+        for (name, value) in &self.response_headers {
+            let parsed = HeaderName::try_from(name.as_str())
+                .map_err(|_| format!("Invalid header name: {}", name))?;
+            if RESERVED_RESPONSE_HEADERS.contains(&parsed.as_str()) {
+                return Err(format!("{} is set by the resolver itself", name));
+            }
+            HeaderValue::try_from(value.as_str())
+                .map_err(|_| format!("Invalid value for header {}", name))?;
+        }
+        Ok(())
+    }
+
+    /// Validate the component patterns: paths starting with `/`, without query string or
+    /// fragment, and with at most one `*` per path segment
+    pub fn validate_components(&self) -> Result<(), String> {
+        for pattern in &self.components {
+            if !pattern.starts_with('/')
+                || pattern.len() == 1
+                || pattern.ends_with(['/', '.'])
+                || pattern.contains(['?', '#'])
+                || pattern.chars().any(char::is_whitespace)
+                || pattern
+                    .split('/')
+                    .any(|segment| segment.matches('*').count() > 1)
+            {
+                return Err(format!("{} is not a qualifier path pattern", pattern));
+            }
         }
+        Ok(())
+    }
+
+    /// Whether `ark`'s qualifier names a component of the ARK, per `components`
+    ///
+    /// Qualifiers with a query string or inflection are not components themselves; trailing
+    /// `/` and `.` are ignored, as in the ARK's normalized form.
+    pub fn is_component(&self, ark: &Ark) -> bool {
+        let qualifier = ark.qualifier.trim_end_matches(['/', '.']);
+        !qualifier.is_empty()
+            && !qualifier.contains('?')
+            && self
+                .components
+                .iter()
+                .filter_map(|pattern| pattern.strip_prefix('/'))
+                .any(|pattern| matches_component(pattern, qualifier))
+    }
+
+    /// The configured response headers; entries that fail validation are left out
+    pub fn response_header_map(&self) -> HeaderMap {
+        self.response_headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::try_from(name.as_str()).ok()?,
+                    HeaderValue::try_from(value.as_str()).ok()?,
+                ))
+            })
+            .collect()
     }
 
-    /// Resolve an ARK identifier using this shoulder's routing pattern
+    /// Resolve an ARK identifier requested with `method` using this shoulder's configuration
+    ///
+    /// Inflection requests on shoulders with `forward_inflections` lead to a landing page;
+    /// everything else is a redirect, unless the method is not redirected or the target is
+    /// blocked (see [`Shoulder::try_resolve`]).
+    pub fn resolve(&self, parsed_ark: &Ark, method: &Method) -> ResolutionOutcome {
+        if self.forward_inflections
+            && (method == Method::GET || method == Method::HEAD)
+            && parsed_ark.inflection().is_some()
+        {
+            return match self.try_resolve_inflection(parsed_ark) {
+                Ok(source) => ResolutionOutcome::LandingPage {
+                    source,
+                    target: self.try_resolve(&parsed_ark.without_inflection()).ok(),
+                },
+                Err(blocked) => ResolutionOutcome::Blocked(blocked),
+            };
+        }
+        self.redirect(parsed_ark, method)
+    }
+
+    /// Resolve an ARK identifier to a redirect, even for inflections that
+    /// [`Shoulder::resolve`] would answer with a landing page
+    pub fn redirect(&self, parsed_ark: &Ark, method: &Method) -> ResolutionOutcome {
+        let Some(status) = self.redirect_status(method) else {
+            return ResolutionOutcome::MethodNotAllowed;
+        };
+        match self.try_resolve(parsed_ark) {
+            Ok(url) => ResolutionOutcome::Redirect { url, status },
+            Err(blocked) => ResolutionOutcome::Blocked(blocked),
+        }
+    }
+
+    /// Resolve an ARK bound to `target` (see [`crate::bindings`]) instead of the route pattern
+    ///
+    /// The target goes through the same security validation as computed targets, so bindings
+    /// follow later changes to `allowed_target_hosts`.
+    pub fn resolve_bound(
+        &self,
+        parsed_ark: &Ark,
+        target: &str,
+        method: &Method,
+    ) -> ResolutionOutcome {
+        let Some(status) = self.redirect_status(method) else {
+            return ResolutionOutcome::MethodNotAllowed;
+        };
+        match self.check_target(target.to_string(), parsed_ark) {
+            Ok(url) => ResolutionOutcome::Redirect { url, status },
+            Err(blocked) => ResolutionOutcome::Blocked(blocked),
+        }
+    }
+
+    /// The metadata of a new binding: the shoulder's defaults, overridden by `supplied`
+    pub fn metadata_with_defaults(
+        &self,
+        supplied: BTreeMap<String, String>,
+    ) -> BTreeMap<String, String> {
+        let mut metadata = self.default_metadata.clone();
+        metadata.extend(supplied);
+        metadata
+    }
+
+    /// Checks a target given for an ARK on this shoulder, as [`Shoulder::resolve_bound`] will
+    pub fn validate_target(&self, target: &str) -> Result<(), String> {
+        self.validate_redirect_url(target).map(|_| ())
+    }
+
+    /// Redirect status for `method`: 302 for GET and HEAD, otherwise `method_redirect`
+    fn redirect_status(&self, method: &Method) -> Option<StatusCode> {
+        if method == Method::GET || method == Method::HEAD {
+            return Some(StatusCode::FOUND);
+        }
+        match self.method_redirect? {
+            MethodRedirect::Temporary => Some(StatusCode::TEMPORARY_REDIRECT),
+            MethodRedirect::Permanent => Some(StatusCode::PERMANENT_REDIRECT),
+        }
+    }
+
+    /// Resolve an ARK identifier to its target URL using this shoulder's routing pattern
     ///
     /// This applies the N2T.net/ARK Alliance template substitution to generate
     /// the target URL for the given ARK.
@@ -233,12 +1048,41 @@ impl Shoulder {
     /// - It parses as a valid URL
     /// - It uses http or https scheme only
     /// - No injection of malicious schemes (javascript:, data:, etc.)
+    /// - Its host is in `allowed_target_hosts`, if set
     ///
-    /// If validation fails, returns the error message as the redirect target
-    /// (which will cause the redirect to fail safely).
-    pub fn resolve(&self, parsed_ark: &Ark) -> String {
-        let target = self.apply_template(parsed_ark);
+    /// If validation fails, the rejected target is returned with the reason, so callers can
+    /// react (e.g. by alerting the shoulder's contact).
+    pub fn try_resolve(&self, parsed_ark: &Ark) -> Result<String, BlockedTarget> {
+        let target = match (&self.metadata_route_pattern, parsed_ark.inflection()) {
+            (Some(pattern), Some(_)) => {
+                self.apply_template(pattern, &parsed_ark.without_inflection())
+            }
+            _ => self.apply_template(&self.effective_route_pattern(), parsed_ark),
+        };
+        self.check_target(target, parsed_ark)
+    }
+
+    /// URL an inflection request is forwarded to when `forward_inflections` is set
+    ///
+    /// This is the `metadata_route_pattern` URL if there is one, and otherwise the target of
+    /// the ARK without its inflection, with the inflection appended.
+    pub fn try_resolve_inflection(&self, parsed_ark: &Ark) -> Result<String, BlockedTarget> {
+        if self.metadata_route_pattern.is_some() {
+            return self.try_resolve(parsed_ark);
+        }
+        let target = format!(
+            "{}{}",
+            self.apply_template(
+                &self.effective_route_pattern(),
+                &parsed_ark.without_inflection()
+            ),
+            parsed_ark.inflection().unwrap_or_default()
+        );
+        self.check_target(target, parsed_ark)
+    }
 
+    /// Validates a computed target, logging it either way
+    fn check_target(&self, target: String, parsed_ark: &Ark) -> Result<String, BlockedTarget> {
         // Validate the constructed URL
         match self.validate_redirect_url(&target) {
             Ok(validated_url) => {
@@ -247,7 +1091,7 @@ impl Shoulder {
                     target = %validated_url.as_str(),
                     "ARK redirect target validated"
                 );
-                validated_url.to_string()
+                Ok(validated_url.to_string())
             }
             Err(e) => {
                 tracing::error!(
@@ -257,13 +1101,23 @@ impl Shoulder {
                     error = %e,
                     "SECURITY: Invalid redirect URL blocked"
                 );
-                // Return an error URL that will fail safely
-                format!("about:blank#error={}", urlencoding::encode(&e))
+                Err(BlockedTarget { target, reason: e })
             }
         }
     }
 
-    /// Apply N2T.net/ARK Alliance template substitution
+    /// URL of the ARK's metadata under `metadata_route_pattern`, if set and valid
+    ///
+    /// Any inflection on the ARK is dropped first, so `x6abc` and `x6abc?info` share a URL.
+    pub fn metadata_url(&self, parsed_ark: &Ark) -> Option<String> {
+        let pattern = self.metadata_route_pattern.as_deref()?;
+        let target = self.apply_template(pattern, &parsed_ark.without_inflection());
+        self.validate_redirect_url(&target)
+            .ok()
+            .map(|url| url.to_string())
+    }
+
+    /// Apply N2T.net/ARK Alliance template substitution to `pattern`
     ///
     /// Supported variables (both {var} and ${var} formats):
     /// - {pid} or ${pid} - Full ARK identifier (e.g., "ark:12345/x8rd9")
@@ -271,13 +1125,14 @@ impl Shoulder {
     /// - {content} or ${content} - Content without scheme (e.g., "12345/x8rd9")
     /// - {prefix} or ${prefix} or {naan} - NAAN/prefix (e.g., "12345")
     /// - {value} or ${value} - Identifier value (e.g., "x8rd9")
+    /// - {metadata_url} or ${metadata_url} - The ARK's URL under `metadata_route_pattern`
     ///
-    /// If no template variables are present in the route_pattern, the full ARK
+    /// If no template variables are present in the pattern, the full ARK
     /// identifier is appended to the base URL (N2T.net standard behavior).
-    fn apply_template(&self, parsed_ark: &Ark) -> String {
-        let pid = &parsed_ark.original;
+    fn apply_template(&self, pattern: &str, parsed_ark: &Ark) -> String {
+        let qualifier = self.qualifier_encoding.apply(&parsed_ark.qualifier);
         let scheme = "ark";
-        let content = if parsed_ark.qualifier.is_empty() {
+        let content = if qualifier.is_empty() {
             format!(
                 "{}/{}{}",
                 parsed_ark.naan, parsed_ark.shoulder, parsed_ark.blade
@@ -285,43 +1140,48 @@ impl Shoulder {
         } else {
             format!(
                 "{}/{}{}/{}",
-                parsed_ark.naan, parsed_ark.shoulder, parsed_ark.blade, parsed_ark.qualifier
+                parsed_ark.naan, parsed_ark.shoulder, parsed_ark.blade, qualifier
             )
         };
         let prefix = &parsed_ark.naan;
-        let value = if parsed_ark.qualifier.is_empty() {
+        let value = if qualifier.is_empty() {
             format!("{}{}", parsed_ark.shoulder, parsed_ark.blade)
-        } else if parsed_ark.qualifier.starts_with('?') {
+        } else if qualifier.starts_with('?') {
             // Query string without path qualifier - no slash needed
-            format!(
-                "{}{}{}",
-                parsed_ark.shoulder, parsed_ark.blade, parsed_ark.qualifier
-            )
+            format!("{}{}{}", parsed_ark.shoulder, parsed_ark.blade, qualifier)
         } else {
             // Path qualifier - include slash
-            format!(
-                "{}{}/{}",
-                parsed_ark.shoulder, parsed_ark.blade, parsed_ark.qualifier
-            )
+            format!("{}{}/{}", parsed_ark.shoulder, parsed_ark.blade, qualifier)
         };
-
-        // Check if route_pattern contains any template variables
-        let has_template_vars = self.route_pattern.contains("${")
-            || self.route_pattern.contains("{pid}")
-            || self.route_pattern.contains("{scheme}")
-            || self.route_pattern.contains("{content}")
-            || self.route_pattern.contains("{prefix}")
-            || self.route_pattern.contains("{value}")
-            || self.route_pattern.contains("{naan}");
+        // The ARK as received, unless its qualifier is re-encoded
+        let pid = if self.qualifier_encoding.is_verbatim() {
+            parsed_ark.original.clone()
+        } else {
+            format!("ark:{}/{}", parsed_ark.naan, value)
+        };
+        let pid = &pid;
 
         // If no template variables, append the full ARK (N2T.net standard behavior)
-        if !has_template_vars {
-            return format!("{}{}", self.route_pattern, pid);
+        if !has_template_vars(pattern) {
+            return format!("{}{}", pattern, pid);
         }
 
+        // Only computed when used, since it is a substitution of its own
+        let metadata_url = if pattern.contains("{metadata_url}") {
+            self.metadata_route_pattern
+                .as_deref()
+                .filter(|metadata_pattern| !metadata_pattern.contains("{metadata_url}"))
+                .map(|metadata_pattern| {
+                    self.apply_template(metadata_pattern, &parsed_ark.without_inflection())
+                })
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+
         // Normalize template: convert ${var} to {var} format, and also support {naan}
-        let normalized = self
-            .route_pattern
+        let normalized = pattern
+            .replace("${metadata_url}", "{metadata_url}")
             .replace("${pid}", "{pid}")
             .replace("${scheme}", "{scheme}")
             .replace("${content}", "{content}")
@@ -336,9 +1196,45 @@ impl Shoulder {
             .replace("{content}", &content)
             .replace("{prefix}", prefix)
             .replace("{value}", &value)
+            .replace("{metadata_url}", &metadata_url)
     }
 }
 
+/// Whether a routing pattern contains template variables
+fn has_template_vars(pattern: &str) -> bool {
+    pattern.contains("${")
+        || pattern.contains("{pid}")
+        || pattern.contains("{scheme}")
+        || pattern.contains("{content}")
+        || pattern.contains("{prefix}")
+        || pattern.contains("{value}")
+        || pattern.contains("{metadata_url}")
+        || pattern.contains("{naan}")
+}
+
+/// The first `${var}` or `{var}` in `pattern` that template substitution leaves in place
+fn unknown_template_variable(pattern: &str) -> Option<String> {
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let dollar = rest[..start].ends_with('$');
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            // Only `${` without a closing brace is clearly a broken variable
+            return dollar.then(|| format!("${{{}", after));
+        };
+        let name = &after[..end];
+        let known = TEMPLATE_VARIABLES.contains(&name) || (!dollar && name == "naan");
+        let variable_like =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !known && (dollar || variable_like) {
+            let marker = if dollar { "$" } else { "" };
+            return Some(format!("{}{{{}}}", marker, name));
+        }
+        rest = &after[end + 1..];
+    }
+    None
+}
+
 /// Load shoulders configuration from environment variable
 ///
 /// Supports two formats:
@@ -366,7 +1262,7 @@ impl Shoulder {
 /// - Only http/https schemes
 /// - Template variables only in path/query positions
 /// - No control characters
-pub fn load_shoulders_from_env() -> Result<HashMap<String, Shoulder>, String> {
+pub fn load_shoulders_from_env() -> Result<HashMap<ShoulderName, Shoulder>, String> {
     let shoulders_config =
         std::env::var("SHOULDERS").map_err(|_| "SHOULDERS environment variable not set")?;
 
@@ -378,16 +1274,68 @@ pub fn load_shoulders_from_env() -> Result<HashMap<String, Shoulder>, String> {
         parse_shoulders_simple(&shoulders_config)?
     };
 
-    // Validate all route patterns
+    // Validate all shoulders, route patterns first
     for (name, shoulder) in &shoulders {
         shoulder
-            .validate_route_pattern()
-            .map_err(|e| format!("Security validation failed for shoulder '{}': {}", name, e))?;
+            .validate_as(name)
+            .map_err(|e| describe_shoulder_error(name, &e))?;
     }
 
     Ok(shoulders)
 }
 
+/// Error message for an invalid shoulder in the configuration
+pub(crate) fn describe_shoulder_error(name: &str, error: &ShoulderError) -> String {
+    match error {
+        ShoulderError::InvalidRoutePattern(_) | ShoulderError::UnknownTemplateVariable(_) => {
+            format!(
+                "Security validation failed for shoulder '{}': {}",
+                name, error
+            )
+        }
+        _ => format!("Invalid shoulder '{}': {}", name, error),
+    }
+}
+
+/// Whether `host` matches an allowlist entry (`example.org` or `*.example.org`)
+fn host_matches(pattern: &str, host: &str) -> bool {
+    // Parsed URLs have lowercase hosts
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .and_then(|rest| rest.strip_suffix('.'))
+            .is_some_and(|subdomain| !subdomain.is_empty()),
+        None => host == pattern,
+    }
+}
+
+/// Whether `qualifier` matches a component pattern, segment by segment; a `*` takes at least
+/// one character
+fn matches_component(pattern: &str, qualifier: &str) -> bool {
+    let mut patterns = pattern.split('/');
+    let mut segments = qualifier.split('/');
+    loop {
+        match (patterns.next(), segments.next()) {
+            (None, None) => return true,
+            (Some(pattern), Some(segment)) => {
+                let matched = match pattern.split_once('*') {
+                    Some((prefix, suffix)) => {
+                        segment.len() > prefix.len() + suffix.len()
+                            && segment.starts_with(prefix)
+                            && segment.ends_with(suffix)
+                    }
+                    None => segment == pattern,
+                };
+                if !matched {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
 /// Parse shoulders from JSON format
 ///
 /// Expects a JSON object with shoulder names as keys and Shoulder objects as values:
@@ -400,8 +1348,10 @@ pub fn load_shoulders_from_env() -> Result<HashMap<String, Shoulder>, String> {
 ///   }
 /// }
 /// ```
-fn parse_shoulders_json(json_str: &str) -> Result<HashMap<String, Shoulder>, String> {
-    serde_json::from_str::<HashMap<String, Shoulder>>(json_str)
+pub(crate) fn parse_shoulders_json(
+    json_str: &str,
+) -> Result<HashMap<ShoulderName, Shoulder>, String> {
+    serde_json::from_str::<HashMap<ShoulderName, Shoulder>>(json_str)
         .map_err(|e| format!("Failed to parse JSON: {}", e))
 }
 
@@ -413,7 +1363,7 @@ fn parse_shoulders_json(json_str: &str) -> Result<HashMap<String, Shoulder>, Str
 /// Supports both literal tab characters and escaped \t sequences.
 ///
 /// Returns an error if no valid shoulders are found.
-fn parse_shoulders_simple(simple_str: &str) -> Result<HashMap<String, Shoulder>, String> {
+fn parse_shoulders_simple(simple_str: &str) -> Result<HashMap<ShoulderName, Shoulder>, String> {
     let mut shoulders = HashMap::new();
 
     // Replace escaped \t with actual tab characters
@@ -425,7 +1375,7 @@ fn parse_shoulders_simple(simple_str: &str) -> Result<HashMap<String, Shoulder>,
             continue;
         }
 
-        let shoulder = parts[0].trim().to_string();
+        let shoulder = ShoulderName::new(parts[0].trim())?;
         let route_pattern = parts[1].trim().to_string();
         let project_name = parts[2].trim().to_string();
 
@@ -451,6 +1401,112 @@ mod tests {
     use super::*;
     use crate::ark::parse_ark;
 
+    #[test]
+    fn test_builder_validates_at_construction() {
+        let shoulder = Shoulder::builder()
+            .route_pattern("https://example.org/${value}")
+            .project_name("Built")
+            .blade_length(10)
+            .uses_check_character(false)
+            .build()
+            .unwrap();
+        assert_eq!(shoulder.blade_length, Some(10));
+        assert!(!shoulder.uses_check_character);
+
+        let base = || {
+            Shoulder::builder()
+                .route_pattern("https://example.org/${value}")
+                .project_name("Built")
+        };
+        assert_eq!(
+            Shoulder::builder().project_name("Built").build(),
+            Err(ShoulderError::MissingRoutePattern)
+        );
+        assert!(matches!(
+            base().route_pattern("javascript:alert(${value})").build(),
+            Err(ShoulderError::InvalidRoutePattern(_))
+        ));
+        assert_eq!(
+            base().blade_length(0).build(),
+            Err(ShoulderError::BladeLengthOutOfRange(0))
+        );
+        assert_eq!(
+            base().blade_length(MAX_BLADE_LENGTH + 1).build(),
+            Err(ShoulderError::BladeLengthOutOfRange(MAX_BLADE_LENGTH + 1))
+        );
+        assert!(matches!(
+            base().failure_callback("ftp://example.org/").build(),
+            Err(ShoulderError::InvalidFailureCallback(_))
+        ));
+        assert!(matches!(
+            base().alphabet(Alphabet::Alphanumeric).build(),
+            Err(ShoulderError::InvalidAlphabet(_))
+        ));
+        assert!(
+            base()
+                .alphabet(Alphabet::Alphanumeric)
+                .uses_check_character(false)
+                .build()
+                .is_ok()
+        );
+        assert!(
+            base()
+                .response_header("Access-Control-Allow-Origin", "*")
+                .build()
+                .is_ok()
+        );
+        for (name, value) in [
+            ("Location", "https://example.org/"),
+            ("Set-Cookie", "session=1"),
+            ("X Collection", "maps"),
+            ("X-Collection", "line\nbreak"),
+        ] {
+            assert!(matches!(
+                base().response_header(name, value).build(),
+                Err(ShoulderError::InvalidResponseHeaders(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_template_variables() {
+        assert_eq!(
+            unknown_template_variable("https://example.org/${value}"),
+            None
+        );
+        assert_eq!(
+            unknown_template_variable("https://example.org/{naan}/{value}"),
+            None
+        );
+        assert_eq!(
+            unknown_template_variable("https://example.org/${vaule}"),
+            Some("${vaule}".to_string())
+        );
+        assert_eq!(
+            unknown_template_variable("https://example.org/{id}"),
+            Some("{id}".to_string())
+        );
+        // {naan} is only substituted without the dollar sign
+        assert_eq!(
+            unknown_template_variable("https://example.org/${naan}"),
+            Some("${naan}".to_string())
+        );
+        assert_eq!(
+            unknown_template_variable("https://example.org/${value"),
+            Some("${value".to_string())
+        );
+
+        let error = Shoulder::builder()
+            .route_pattern("https://example.org/${vaule}")
+            .project_name("Typo")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            ShoulderError::UnknownTemplateVariable("${vaule}".to_string())
+        );
+    }
+
     // Security validation tests
 
     #[test]
@@ -601,10 +1657,8 @@ mod tests {
 
         for (ark_str, expected) in test_cases {
             if let Some(parsed) = parse_ark(ark_str) {
-                let result = shoulder.resolve(&parsed);
                 // If it's a valid redirect, check it matches expected
-                // If it's blocked, it will be about:blank#error=...
-                if !result.starts_with("about:blank") {
+                if let Ok(result) = shoulder.try_resolve(&parsed) {
                     assert_eq!(result, expected);
                 }
             }
@@ -621,14 +1675,149 @@ mod tests {
         };
 
         let ark = parse_ark("ark:12345/x6test").unwrap();
-        let result = shoulder.resolve(&ark);
+        let result = shoulder.try_resolve(&ark).unwrap();
 
         // Should be a valid URL
         assert!(Url::parse(&result).is_ok());
 
         // Should be https
         let parsed = Url::parse(&result).unwrap();
-        assert!(parsed.scheme() == "https" || result.starts_with("about:blank"));
+        assert_eq!(parsed.scheme(), "https");
+    }
+
+    #[test]
+    fn test_resolve_enforces_allowed_target_hosts() {
+        let ark = parse_ark("ark:12345/x6test").unwrap();
+        let resolve = |route_pattern: &str| {
+            Shoulder {
+                route_pattern: route_pattern.to_string(),
+                allowed_target_hosts: Some(vec![
+                    "example.org".to_string(),
+                    "*.cdn.example.org".to_string(),
+                ]),
+                ..Default::default()
+            }
+            .try_resolve(&ark)
+        };
+
+        assert!(resolve("https://example.org/${value}").is_ok());
+        assert!(resolve("https://EU.cdn.example.org/${value}").is_ok());
+        // The wildcard covers subdomains only, and suffix matches need a dot boundary
+        assert!(resolve("https://cdn.example.org/${value}").is_err());
+        assert!(resolve("https://evilexample.org/${value}").is_err());
+        assert!(resolve("https://example.org@attacker.net/${value}").is_err());
+        let blocked = resolve("https://attacker.net/${value}").unwrap_err();
+        assert!(blocked.reason.contains("attacker.net"));
+    }
+
+    #[test]
+    fn test_resolution_outcomes() {
+        let shoulder = Shoulder {
+            route_pattern: "https://example.org/${value}".to_string(),
+            project_name: "Test".to_string(),
+            ..Default::default()
+        };
+        let ark = parse_ark("ark:12345/x6test").unwrap();
+        assert_eq!(
+            shoulder.resolve(&ark, &Method::GET),
+            ResolutionOutcome::Redirect {
+                url: "https://example.org/x6test".to_string(),
+                status: StatusCode::FOUND,
+            }
+        );
+        assert_eq!(
+            shoulder.resolve(&ark, &Method::POST),
+            ResolutionOutcome::MethodNotAllowed
+        );
+        let permanent = Shoulder {
+            method_redirect: Some(MethodRedirect::Permanent),
+            ..shoulder.clone()
+        };
+        assert!(matches!(
+            permanent.resolve(&ark, &Method::POST),
+            ResolutionOutcome::Redirect {
+                status: StatusCode::PERMANENT_REDIRECT,
+                ..
+            }
+        ));
+
+        let blocked = Shoulder {
+            route_pattern: "https://example.org/${value}".to_string(),
+            allowed_target_hosts: Some(vec!["example.net".to_string()]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            blocked.resolve(&ark, &Method::GET),
+            ResolutionOutcome::Blocked(BlockedTarget { ref target, .. })
+                if target == "https://example.org/x6test"
+        ));
+
+        // Forwarded inflections are answered with a landing page unless a redirect is asked for
+        let forwarding = Shoulder {
+            forward_inflections: true,
+            ..shoulder.clone()
+        };
+        let info = parse_ark("ark:12345/x6test?info").unwrap();
+        assert_eq!(
+            forwarding.resolve(&info, &Method::GET),
+            ResolutionOutcome::LandingPage {
+                source: "https://example.org/x6test?info".to_string(),
+                target: Some("https://example.org/x6test".to_string()),
+            }
+        );
+        assert!(matches!(
+            forwarding.redirect(&info, &Method::GET),
+            ResolutionOutcome::Redirect { .. }
+        ));
+
+        let naan: Naan = "12345".parse().unwrap();
+        let shoulders = HashMap::from([("x6".parse().unwrap(), shoulder)]);
+        assert!(matches!(
+            resolve_ark(&naan, &shoulders, &ark, &Method::GET),
+            ResolutionOutcome::Redirect { .. }
+        ));
+        for other in ["ark:12345/b3test", "ark:99999/x6test"] {
+            assert_eq!(
+                resolve_ark(&naan, &shoulders, &parse_ark(other).unwrap(), &Method::GET),
+                ResolutionOutcome::NotFound
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_allowed_target_hosts() {
+        let mut shoulder = Shoulder {
+            allowed_target_hosts: Some(vec![
+                "example.org".to_string(),
+                "*.example.org".to_string(),
+            ]),
+            ..Default::default()
+        };
+        assert!(shoulder.validate_allowed_target_hosts().is_ok());
+
+        for invalid in [
+            vec![],
+            vec!["*".to_string()],
+            vec!["https://example.org".to_string()],
+        ] {
+            shoulder.allowed_target_hosts = Some(invalid);
+            assert!(shoulder.validate_allowed_target_hosts().is_err());
+        }
+    }
+
+    #[test]
+    fn test_validate_failure_callback() {
+        let mut shoulder = Shoulder {
+            route_pattern: "https://example.org/${value}".to_string(),
+            failure_callback: Some("https://hooks.example.org/ark".to_string()),
+            ..Default::default()
+        };
+        assert!(shoulder.validate_failure_callback().is_ok());
+
+        shoulder.failure_callback = Some("file:///etc/passwd".to_string());
+        assert!(shoulder.validate_failure_callback().is_err());
+        shoulder.failure_callback = Some("not a url".to_string());
+        assert!(shoulder.validate_failure_callback().is_err());
     }
 
     #[test]
@@ -740,6 +1929,46 @@ mod tests {
         assert_eq!(b3.blade_length, None); // Not specified, should be None
     }
 
+    #[test]
+    fn test_parse_shoulders_with_contact() {
+        let json = r#"
+        {
+            "x6": {
+                "route_pattern": "https://alpha.tm.org/${value}",
+                "project_name": "Project Alpha",
+                "contact": {
+                    "name": "Digital Collections Team",
+                    "email": "collections@example.org",
+                    "organization": "Example Library"
+                }
+            },
+            "b3": {
+                "route_pattern": "https://beta.tm.org/${value}",
+                "project_name": "Project Beta",
+                "contact": {"name": "Jane Doe"}
+            }
+        }
+        "#;
+
+        let shoulders = parse_shoulders_json(json).unwrap();
+
+        let x6 = shoulders["x6"].contact.as_ref().unwrap();
+        assert_eq!(x6.name, "Digital Collections Team");
+        assert_eq!(x6.email.as_deref(), Some("collections@example.org"));
+        assert_eq!(x6.organization.as_deref(), Some("Example Library"));
+
+        let b3 = shoulders["b3"].contact.as_ref().unwrap();
+        assert_eq!(b3.email, None);
+
+        // A contact without a name is rejected
+        assert!(
+            parse_shoulders_json(
+                r#"{"x6": {"route_pattern": "https://a.org/", "project_name": "A", "contact": {}}}"#
+            )
+            .is_err()
+        );
+    }
+
     #[test]
     fn test_parse_shoulders_simple() {
         // Valid: single and multiple shoulders with complex URLs and special chars in names
@@ -803,7 +2032,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder_pid.resolve(&parsed),
+            shoulder_pid.try_resolve(&parsed).unwrap(),
             "https://example.org/resolve?id=ark:12345/x6np1wh8k/page2.pdf"
         );
 
@@ -813,7 +2042,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder_content.resolve(&parsed),
+            shoulder_content.try_resolve(&parsed).unwrap(),
             "https://example.org/12345/x6np1wh8k/page2.pdf"
         );
 
@@ -823,7 +2052,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder_prefix.resolve(&parsed),
+            shoulder_prefix.try_resolve(&parsed).unwrap(),
             "https://example.org/12345/items"
         );
 
@@ -833,7 +2062,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder_value.resolve(&parsed),
+            shoulder_value.try_resolve(&parsed).unwrap(),
             "https://example.org/objects/x6np1wh8k/page2.pdf"
         );
 
@@ -845,7 +2074,7 @@ mod tests {
             ..Default::default()
         };
         let expected = "https://example.org/view?ark=ark:12345/x6np1wh8k/page2.pdf&naan=12345&id=x6np1wh8k/page2.pdf";
-        assert_eq!(shoulder_complex.resolve(&parsed), expected);
+        assert_eq!(shoulder_complex.try_resolve(&parsed).unwrap(), expected);
     }
 
     #[test]
@@ -860,7 +2089,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder.resolve(&parsed),
+            shoulder.try_resolve(&parsed).unwrap(),
             "https://example.org/items/x6np1wh8k"
         );
     }
@@ -878,7 +2107,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder.resolve(&parsed),
+            shoulder.try_resolve(&parsed).unwrap(),
             "https://example.org/items/x6np1wh8k?info"
         );
 
@@ -889,7 +2118,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder2.resolve(&parsed),
+            shoulder2.try_resolve(&parsed).unwrap(),
             "https://example.org/resolve?id=ark:12345/x6np1wh8k?info"
         );
 
@@ -900,11 +2129,161 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder3.resolve(&parsed),
+            shoulder3.try_resolve(&parsed).unwrap(),
             "https://example.org/ark:12345/x6np1wh8k?info"
         );
     }
 
+    #[test]
+    fn test_resolve_qualifier_encoding() {
+        let parsed = parse_ark("ark:12345/x6np1wh8k/a+b/c%20d%2fe?q=x+y").unwrap();
+        let resolve = |qualifier_encoding, route_pattern: &str| {
+            Shoulder {
+                route_pattern: route_pattern.to_string(),
+                project_name: "Test".to_string(),
+                qualifier_encoding,
+                ..Default::default()
+            }
+            .try_resolve(&parsed)
+            .unwrap()
+        };
+
+        assert_eq!(
+            resolve(QualifierEncoding::Verbatim, "https://example.org/${value}"),
+            "https://example.org/x6np1wh8k/a+b/c%20d%2fe?q=x+y"
+        );
+        assert_eq!(
+            resolve(QualifierEncoding::Reencode, "https://example.org/${value}"),
+            "https://example.org/x6np1wh8k/a%2Bb/c%20d%2Fe?q=x%2By"
+        );
+        assert_eq!(
+            resolve(
+                QualifierEncoding::Reencode,
+                "https://example.org/?id=${pid}"
+            ),
+            "https://example.org/?id=ark:12345/x6np1wh8k/a%2Bb/c%20d%2Fe?q=x%2By"
+        );
+        // Decoded spaces are escaped again when the target is checked as a URL; an encoded `/`
+        // stays encoded
+        assert_eq!(
+            resolve(QualifierEncoding::Decode, "https://example.org/${value}"),
+            "https://example.org/x6np1wh8k/a+b/c%20d%2fe?q=x%20y"
+        );
+    }
+
+    #[test]
+    fn test_resolve_delegated_metadata() {
+        let shoulder = Shoulder {
+            route_pattern: "https://viewer.example.org/?manifest=${metadata_url}".to_string(),
+            metadata_route_pattern: Some("https://meta.example.org/records/${value}".to_string()),
+            project_name: "Test".to_string(),
+            ..Default::default()
+        };
+        assert!(shoulder.validate_route_pattern().is_ok());
+
+        // Inflections go to the metadata service, without the inflection itself
+        for ark in [
+            "ark:12345/m5item?",
+            "ark:12345/m5item??",
+            "ark:12345/m5item?info",
+        ] {
+            assert_eq!(
+                shoulder.try_resolve(&parse_ark(ark).unwrap()).unwrap(),
+                "https://meta.example.org/records/m5item",
+                "{}",
+                ark
+            );
+        }
+
+        // Other requests go to the target, which can embed the metadata URL
+        let parsed = parse_ark("ark:12345/m5item/page2").unwrap();
+        assert_eq!(
+            shoulder.try_resolve(&parsed).unwrap(),
+            "https://viewer.example.org/?manifest=https://meta.example.org/records/m5item/page2"
+        );
+        assert_eq!(
+            shoulder.metadata_url(&parsed).as_deref(),
+            Some("https://meta.example.org/records/m5item/page2")
+        );
+    }
+
+    #[test]
+    fn test_validate_metadata_route_pattern() {
+        let mut shoulder = Shoulder {
+            route_pattern: "https://example.org/${metadata_url}".to_string(),
+            project_name: "Test".to_string(),
+            ..Default::default()
+        };
+        // ${metadata_url} needs a metadata_route_pattern
+        assert!(shoulder.validate_route_pattern().is_err());
+
+        shoulder.metadata_route_pattern = Some("https://${value}.example.org/".to_string());
+        assert!(shoulder.validate_route_pattern().is_err());
+
+        shoulder.metadata_route_pattern =
+            Some("https://meta.example.org/${metadata_url}".to_string());
+        assert!(shoulder.validate_route_pattern().is_err());
+
+        shoulder.metadata_route_pattern = Some("https://meta.example.org/${value}".to_string());
+        assert!(shoulder.validate_route_pattern().is_ok());
+    }
+
+    #[test]
+    fn test_validate_template() {
+        let json = r#"{"route_pattern": "https://example.org/${value}",
+            "project_name": "Test", "template": "x6.sddk"}"#;
+        let mut shoulder: Shoulder = serde_json::from_str(json).unwrap();
+        assert!(shoulder.check_character());
+        assert_eq!(shoulder.blade_template(8).blade_length(), 2);
+        assert!(shoulder.validate_as("x6").is_ok());
+        assert!(shoulder.validate_as("b3").is_err());
+
+        shoulder.blade_length = Some(8);
+        assert!(shoulder.validate().is_err());
+        shoulder.blade_length = None;
+        shoulder.template = Some(".reek".parse().unwrap());
+        shoulder.minter = Minter::Sequential;
+        assert!(shoulder.validate().is_err());
+
+        let json = json.replace("x6.sddk", ".sdq");
+        assert!(serde_json::from_str::<Shoulder>(&json).is_err());
+    }
+
+    #[test]
+    fn test_components() {
+        let shoulder = Shoulder::builder()
+            .route_pattern("https://example.org/${value}")
+            .project_name("Test")
+            .component("/page*")
+            .component("/v*/side-*")
+            .build()
+            .unwrap();
+        let is_component =
+            |ark: &str| shoulder.is_component(&Ark::try_from(ark).expect("test ARKs parse"));
+
+        assert!(is_component("ark:12345/x6np1wh8k/page2"));
+        assert!(is_component("ark:12345/x6np1wh8k/page12/"));
+        assert!(is_component("ark:12345/x6np1wh8k/v3/side-a"));
+        assert!(!is_component("ark:12345/x6np1wh8k"));
+        assert!(!is_component("ark:12345/x6np1wh8k/page"));
+        assert!(!is_component("ark:12345/x6np1wh8k/page2/image.jpg"));
+        assert!(!is_component("ark:12345/x6np1wh8k/page2?info"));
+        assert!(!is_component("ark:12345/x6np1wh8k/v3"));
+
+        for pattern in ["page*", "/", "/page*/", "/p**", "/page?n=*"] {
+            let result = Shoulder::builder()
+                .route_pattern("https://example.org/${value}")
+                .project_name("Test")
+                .component(pattern)
+                .build();
+            assert!(
+                matches!(result, Err(ShoulderError::InvalidComponents(_))),
+                "{}",
+                pattern
+            );
+        }
+    }
+
     #[test]
     fn test_resolve_real_world_examples() {
         let ark = "ark:99999/fk4test123/metadata.xml";
@@ -918,7 +2297,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder1.resolve(&parsed),
+            shoulder1.try_resolve(&parsed).unwrap(),
             "https://example.org/ark:99999/fk4test123/metadata.xml"
         );
 
@@ -929,7 +2308,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder2.resolve(&parsed),
+            shoulder2.try_resolve(&parsed).unwrap(),
             "https://ark.example.org/mycontent/fk4test123/metadata.xml"
         );
 
@@ -940,7 +2319,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder3.resolve(&parsed),
+            shoulder3.try_resolve(&parsed).unwrap(),
             "https://resolver.example.org/resolve?id=ark:99999/fk4test123/metadata.xml"
         );
 
@@ -951,7 +2330,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder4.resolve(&parsed),
+            shoulder4.try_resolve(&parsed).unwrap(),
             "https://api.example.org/v1/objects/99999/fk4test123/metadata.xml"
         );
 
@@ -962,7 +2341,7 @@ mod tests {
             ..Default::default()
         };
         assert_eq!(
-            shoulder5.resolve(&parsed),
+            shoulder5.try_resolve(&parsed).unwrap(),
             "https://storage.example.org/99999/items/fk4test123/metadata.xml"
         );
     }
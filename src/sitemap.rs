@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::ark::parse_ark;
+use crate::bindings::{Bindings, StorageError};
+use crate::ledger::{LedgerEntry, MintLedger};
+use crate::lifecycle::ArkStatus;
+use crate::resourcesync::{escape, w3c_datetime};
+
+/// Path of the sitemap index, which lists a sitemap per indexed shoulder
+pub const SITEMAP_PATH: &str = "/sitemap.xml";
+/// Path of a shoulder's sitemap, below which its name follows
+pub const SHOULDER_SITEMAP_PATH: &str = "/sitemaps";
+
+/// ARKs per sitemap, below the protocol's 50,000 because ledger pages are only about the size
+/// asked for
+pub const SITEMAP_PAGE_SIZE: usize = 40_000;
+
+/// One sitemap's worth of a shoulder's recorded ARKs
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SitemapPage {
+    pub entries: Vec<LedgerEntry>,
+    /// Where the next sitemap of the shoulder starts, none after the last one
+    pub next: Option<String>,
+}
+
+/// About `size` ARKs recorded on `shoulder`, from where `after` (the `next` of the previous
+/// page) left off
+pub fn page(
+    ledger: &dyn MintLedger,
+    shoulder: &str,
+    after: Option<&str>,
+    size: usize,
+) -> Result<SitemapPage, StorageError> {
+    let mut page = SitemapPage {
+        entries: Vec::new(),
+        next: after.map(str::to_string),
+    };
+    loop {
+        let remaining = size.saturating_sub(page.entries.len());
+        let ledger_page = ledger.entries(Some(shoulder), page.next.as_deref(), remaining)?;
+        page.entries.extend(ledger_page.entries);
+        page.next = ledger_page.next;
+        if page.next.is_none() || page.entries.len() >= size {
+            return Ok(page);
+        }
+    }
+}
+
+/// Where each sitemap of `shoulder` after the first starts, found by walking its ARKs
+///
+/// A page that would come out empty is not listed.
+pub fn page_starts(
+    ledger: &dyn MintLedger,
+    shoulder: &str,
+    size: usize,
+) -> Result<Vec<String>, StorageError> {
+    let mut starts = Vec::new();
+    let mut after = None;
+    loop {
+        let page = page(ledger, shoulder, after.as_deref(), size)?;
+        if page.entries.is_empty() {
+            starts.pop();
+            return Ok(starts);
+        }
+        match page.next {
+            Some(next) => {
+                starts.push(next.clone());
+                after = Some(next);
+            }
+            None => return Ok(starts),
+        }
+    }
+}
+
+/// The entries search engines may crawl: unbound, active or alias ARKs, leaving out those the
+/// resolver answers `404 Not Found` or `410 Gone` for
+pub fn listed(
+    bindings: &Bindings,
+    entries: Vec<LedgerEntry>,
+) -> Result<Vec<LedgerEntry>, StorageError> {
+    let mut listed = Vec::new();
+    for entry in entries {
+        let binding = match parse_ark(&entry.ark) {
+            Some(ark) => bindings.get(&ark)?,
+            None => None,
+        };
+        if binding.is_none_or(|binding| {
+            matches!(
+                binding.lifecycle.status,
+                ArkStatus::Active | ArkStatus::Alias
+            )
+        }) {
+            listed.push(entry);
+        }
+    }
+    Ok(listed)
+}
+
+/// Where the sitemaps of each indexed shoulder start, as last found in the background, so that
+/// requests for the index never walk the ledger
+#[derive(Debug, Default)]
+pub struct SitemapPages {
+    starts: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl SitemapPages {
+    /// Where the sitemaps of `shoulder` after the first start; none before the first walk
+    pub fn starts(&self, shoulder: &str) -> Vec<String> {
+        self.starts
+            .read()
+            .expect("sitemap pages lock poisoned")
+            .get(shoulder)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Walks the ARKs of each shoulder again; a shoulder whose walk fails keeps its last pages
+    pub fn refresh<'a>(
+        &self,
+        ledger: &dyn MintLedger,
+        shoulders: impl IntoIterator<Item = &'a str>,
+    ) {
+        for shoulder in shoulders {
+            match page_starts(ledger, shoulder, SITEMAP_PAGE_SIZE) {
+                Ok(starts) => {
+                    self.starts
+                        .write()
+                        .expect("sitemap pages lock poisoned")
+                        .insert(shoulder.to_string(), starts);
+                }
+                Err(error) => {
+                    tracing::warn!(shoulder = %shoulder, error = %error, "Could not page the sitemap");
+                }
+            }
+        }
+    }
+}
+
+/// A sitemap index pointing at `sitemaps`
+pub fn index(sitemaps: &[String]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for sitemap in sitemaps {
+        xml.push_str(&format!(
+            "  <sitemap>\n    <loc>{}</loc>\n  </sitemap>\n",
+            escape(sitemap)
+        ));
+    }
+    xml.push_str("</sitemapindex>\n");
+    xml
+}
+
+/// A sitemap of the resolver URLs of `entries`, with when each was minted where known
+///
+/// `base_url` is prefixed to every URL; when it is empty the URLs are relative.
+pub fn urlset(base_url: &str, entries: &[LedgerEntry]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for entry in entries {
+        xml.push_str(&format!(
+            "  <url>\n    <loc>{}</loc>\n",
+            escape(&format!("{}/{}", base_url, entry.ark))
+        ));
+        if let Some(minted_at) = entry.minted_at {
+            xml.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                w3c_datetime(minted_at)
+            ));
+        }
+        xml.push_str("  </url>\n");
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::MemoryMintLedger;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_pages_cover_every_ark_once() {
+        let ledger = MemoryMintLedger::default();
+        let arks: Vec<String> = (0..5).map(|i| format!("ark:12345/x6bc{}", i)).collect();
+        ledger.record("x6", &arks, &BTreeMap::new()).unwrap();
+        ledger
+            .record("b3", &["ark:12345/b3bc0".to_string()], &BTreeMap::new())
+            .unwrap();
+
+        let starts = page_starts(&ledger, "x6", 2).unwrap();
+        assert_eq!(starts, ["ark:12345/x6bc1", "ark:12345/x6bc3"]);
+        let mut listed = Vec::new();
+        for after in std::iter::once(None).chain(starts.iter().map(|start| Some(start.as_str()))) {
+            let page = page(&ledger, "x6", after, 2).unwrap();
+            assert!(page.entries.len() <= 2);
+            listed.extend(page.entries.into_iter().map(|entry| entry.ark));
+        }
+        assert_eq!(listed, arks);
+
+        // A last page that is exactly full leaves no empty sitemap behind it
+        let starts = page_starts(&ledger, "x6", 5).unwrap();
+        assert!(starts.is_empty());
+    }
+
+    #[test]
+    fn test_sitemap_documents() {
+        let entries = [LedgerEntry {
+            ark: "ark:12345/x6np1wh8k".to_string(),
+            shoulder: "x6".to_string(),
+            minted_at: Some("2025-01-02T03:04:05Z".parse().unwrap()),
+            metadata: BTreeMap::new(),
+        }];
+        let xml = urlset("https://ark.example.org", &entries);
+        assert!(xml.contains("<loc>https://ark.example.org/ark:12345/x6np1wh8k</loc>"));
+        assert!(xml.contains("<lastmod>2025-01-02T03:04:05Z</lastmod>"));
+
+        let xml = index(&["https://ark.example.org/sitemaps/x6?after=a&b".to_string()]);
+        assert!(xml.contains("<loc>https://ark.example.org/sitemaps/x6?after=a&amp;b</loc>"));
+    }
+}
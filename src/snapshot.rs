@@ -0,0 +1,729 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::bindings::{Binding, StorageError};
+use crate::config::AppState;
+use crate::config_document::{ConfigChange, ConfigDocument};
+use crate::events::ArkEvent;
+use crate::notify::env_parse;
+
+/// Ledger entries and bindings read from the store at once while taking a snapshot, and bindings
+/// written at once while restoring one
+pub const SNAPSHOT_PAGE_SIZE: usize = 1000;
+
+/// Most ARKs listed in a restore report; the rest are only counted
+pub const MAX_REPORTED_ARKS: usize = 100;
+
+/// Time a snapshot waits for writes in progress unless `SNAPSHOT_DRAIN_TIMEOUT_SECS` says
+/// otherwise
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What a snapshot holds, without its ARKs
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub taken_at: DateTime<Utc>,
+    pub taken_by: String,
+    /// ARKs in the mint ledger
+    pub minted: usize,
+    pub bindings: usize,
+}
+
+/// First line of a snapshot file
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct SnapshotHeader {
+    #[serde(flatten)]
+    summary: SnapshotSummary,
+    /// Next position of each sequential counter in use
+    counters: BTreeMap<String, u64>,
+    config: ConfigDocument,
+}
+
+/// An ARK in a snapshot: its ledger entry, its binding, or both
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SnapshotEntry {
+    pub ark: String,
+    /// Shoulder of ARKs in the ledger; none for ARKs only bound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shoulder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minted_at: Option<DateTime<Utc>>,
+    /// Kept in the ledger with the ARK when it was minted
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding: Option<Binding>,
+}
+
+/// The registry, sequential counters and configuration as they were at one moment
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    header: SnapshotHeader,
+    /// By canonical ARK
+    entries: Vec<SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// Reads the store's ledger, bindings and counters; writes must be paused for the snapshot
+    /// to be consistent
+    pub fn capture(
+        state: &AppState,
+        name: String,
+        taken_by: &str,
+        config: ConfigDocument,
+    ) -> Result<Self, StorageError> {
+        let taken_at = Utc::now();
+        let mut entries: BTreeMap<String, SnapshotEntry> = BTreeMap::new();
+        let mut cursor = None;
+        loop {
+            let page = state
+                .store
+                .entries(None, cursor.as_deref(), SNAPSHOT_PAGE_SIZE)?;
+            for entry in page.entries {
+                entries.insert(
+                    entry.ark.clone(),
+                    SnapshotEntry {
+                        ark: entry.ark,
+                        shoulder: Some(entry.shoulder),
+                        minted_at: entry.minted_at,
+                        metadata: entry.metadata,
+                        binding: None,
+                    },
+                );
+            }
+            cursor = page.next;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        let minted = entries.len();
+
+        let mut bindings = 0;
+        loop {
+            let page = state
+                .store
+                .bindings(cursor.as_deref(), SNAPSHOT_PAGE_SIZE)?;
+            for (ark, binding) in page.bindings {
+                bindings += 1;
+                entries
+                    .entry(ark.clone())
+                    .or_insert_with(|| SnapshotEntry {
+                        ark,
+                        shoulder: None,
+                        minted_at: None,
+                        metadata: BTreeMap::new(),
+                        binding: None,
+                    })
+                    .binding = Some(binding);
+            }
+            cursor = page.next;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let mut counters = BTreeMap::new();
+        for shoulder in state.shoulders.keys() {
+            let counter = state.node_partition.sequence_key(shoulder);
+            let next = state.store.sequence_position(&counter)?;
+            if next > 0 {
+                counters.insert(counter, next);
+            }
+        }
+
+        Ok(Self {
+            header: SnapshotHeader {
+                summary: SnapshotSummary {
+                    name,
+                    taken_at,
+                    taken_by: taken_by.to_string(),
+                    minted,
+                    bindings,
+                },
+                counters,
+                config,
+            },
+            entries: entries.into_values().collect(),
+        })
+    }
+
+    pub fn summary(&self) -> &SnapshotSummary {
+        &self.header.summary
+    }
+
+    pub fn config(&self) -> &ConfigDocument {
+        &self.header.config
+    }
+
+    /// Works out how to bring the store back to its state at `at`, which must not be before
+    /// the snapshot
+    ///
+    /// `events` are the ARK changes since the snapshot. Changes up to `at` are kept; so are
+    /// bindings made after it, since bindings cannot be removed, and ARKs changed both before
+    /// and after it, whose state at `at` is unknown. Ledger entries are never removed and
+    /// counters never moved back, so nothing restored can be minted again.
+    pub fn plan_restore(
+        &self,
+        state: &AppState,
+        at: DateTime<Utc>,
+        events: &[ArkEvent],
+    ) -> Result<RestorePlan, StorageError> {
+        let taken_at = self.summary().taken_at;
+        let changed_until: HashSet<&str> = events
+            .iter()
+            .filter(|event| event.at > taken_at && event.at <= at)
+            .map(|event| event.ark.as_str())
+            .collect();
+        let changed_after: HashSet<&str> = events
+            .iter()
+            .filter(|event| event.at > at)
+            .map(|event| event.ark.as_str())
+            .collect();
+        let mut plan = RestorePlan::default();
+
+        let snapshot: BTreeMap<&str, &SnapshotEntry> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.ark.as_str(), entry))
+            .collect();
+        let mut cursor = None;
+        let mut seen = HashSet::new();
+        loop {
+            let page = state
+                .store
+                .bindings(cursor.as_deref(), SNAPSHOT_PAGE_SIZE)?;
+            for (ark, current) in page.bindings {
+                let restored = snapshot.get(ark.as_str()).and_then(|e| e.binding.as_ref());
+                if restored != Some(&current) {
+                    plan.compare(
+                        &ark,
+                        restored,
+                        changed_until.contains(ark.as_str()),
+                        changed_after.contains(ark.as_str()),
+                    );
+                }
+                seen.insert(ark);
+            }
+            cursor = page.next;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        for entry in &self.entries {
+            if let Some(binding) = &entry.binding
+                && !seen.contains(&entry.ark)
+            {
+                plan.compare(
+                    &entry.ark,
+                    Some(binding),
+                    changed_until.contains(entry.ark.as_str()),
+                    changed_after.contains(entry.ark.as_str()),
+                );
+            }
+            if let Some(shoulder) = &entry.shoulder
+                && !state.store.contains(&entry.ark)?
+            {
+                plan.note(&entry.ark, RestoreAction::Record, None);
+                plan.record
+                    .entry(shoulder.clone())
+                    .or_default()
+                    .push(entry.ark.clone());
+                if !entry.metadata.is_empty() {
+                    plan.annotate
+                        .push((entry.ark.clone(), entry.metadata.clone()));
+                }
+            }
+        }
+
+        for (counter, &next) in &self.header.counters {
+            let current = state.store.sequence_position(counter)?;
+            if current < next {
+                plan.counters.push(CounterChange {
+                    counter: counter.clone(),
+                    from: current,
+                    to: next,
+                });
+            }
+        }
+        Ok(plan)
+    }
+}
+
+/// What restoring does to an ARK
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreAction {
+    /// Its binding is set back
+    Rebind,
+    /// It is added back to the ledger
+    Record,
+    /// It was bound after the restore time and is left as it is
+    Keep,
+    /// It changed both before and after the restore time, so its state then is unknown; left
+    /// as it is
+    Conflict,
+}
+
+/// An ARK a restore affects
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RestoredArk {
+    pub ark: String,
+    pub action: RestoreAction,
+    /// Target it resolves to after the restore, for rebound ARKs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// A sequential counter moved forward to where it stood
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CounterChange {
+    pub counter: String,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// The changes a restore makes, worked out before anything is written
+#[derive(Debug, Default)]
+pub struct RestorePlan {
+    rebind: Vec<(String, Binding)>,
+    /// ARKs missing from the ledger, by shoulder
+    record: BTreeMap<String, Vec<String>>,
+    /// Mint metadata of the ARKs in `record` that had any
+    annotate: Vec<(String, BTreeMap<String, String>)>,
+    counters: Vec<CounterChange>,
+    kept: usize,
+    conflicts: usize,
+    arks: Vec<RestoredArk>,
+}
+
+impl RestorePlan {
+    /// Decides what happens to an ARK whose binding differs from the snapshot's
+    fn compare(
+        &mut self,
+        ark: &str,
+        restored: Option<&Binding>,
+        changed_until: bool,
+        changed_after: bool,
+    ) {
+        match (changed_until, changed_after, restored) {
+            // Its latest change is the one it had at the restore time
+            (true, false, _) => {}
+            (true, true, _) => {
+                self.conflicts += 1;
+                self.note(ark, RestoreAction::Conflict, None);
+            }
+            (false, _, Some(restored)) => {
+                self.note(ark, RestoreAction::Rebind, Some(restored.target.clone()));
+                self.rebind.push((ark.to_string(), restored.clone()));
+            }
+            (false, _, None) => {
+                self.kept += 1;
+                self.note(ark, RestoreAction::Keep, None);
+            }
+        }
+    }
+
+    fn note(&mut self, ark: &str, action: RestoreAction, target: Option<String>) {
+        if self.arks.len() < MAX_REPORTED_ARKS {
+            self.arks.push(RestoredArk {
+                ark: ark.to_string(),
+                action,
+                target,
+            });
+        }
+    }
+
+    /// The plan's changes, as reported to the operator
+    pub fn report(
+        &self,
+        snapshot: &SnapshotSummary,
+        restored_to: DateTime<Utc>,
+        dry_run: bool,
+        config_changes: Vec<ConfigChange>,
+    ) -> RestoreReport {
+        RestoreReport {
+            snapshot: snapshot.name.clone(),
+            taken_at: snapshot.taken_at,
+            restored_to,
+            dry_run,
+            rebound: self.rebind.len(),
+            recorded: self.record.values().map(Vec::len).sum(),
+            counters: self.counters.clone(),
+            kept: self.kept,
+            conflicts: self.conflicts,
+            config_changes,
+            arks: self.arks.clone(),
+        }
+    }
+
+    /// Canonical forms of the ARKs whose binding is set back
+    pub fn rebound(&self) -> impl Iterator<Item = &str> {
+        self.rebind.iter().map(|(ark, _)| ark.as_str())
+    }
+
+    /// Writes the plan to the store: missing ledger entries first, then counters, then
+    /// bindings, in chunks; applying it again after a failure finishes the restore
+    pub fn apply(&self, state: &AppState) -> Result<(), StorageError> {
+        for (shoulder, arks) in &self.record {
+            for chunk in arks.chunks(SNAPSHOT_PAGE_SIZE) {
+                state.store.record(shoulder, chunk, &BTreeMap::new())?;
+            }
+        }
+        for (ark, metadata) in &self.annotate {
+            state.store.annotate(std::slice::from_ref(ark), metadata)?;
+        }
+        for change in &self.counters {
+            let current = state.store.sequence_position(&change.counter)?;
+            if current < change.to {
+                state
+                    .store
+                    .reserve_sequence(&change.counter, change.to - current)?;
+            }
+        }
+        for chunk in self.rebind.chunks(SNAPSHOT_PAGE_SIZE) {
+            state.store.put_all(chunk.to_vec())?;
+        }
+        Ok(())
+    }
+}
+
+/// What a restore changed, or would change in a dry run
+#[derive(Clone, Debug, Serialize)]
+pub struct RestoreReport {
+    pub snapshot: String,
+    pub taken_at: DateTime<Utc>,
+    pub restored_to: DateTime<Utc>,
+    pub dry_run: bool,
+    /// ARKs whose binding is set back
+    pub rebound: usize,
+    /// ARKs added back to the ledger
+    pub recorded: usize,
+    /// Sequential counters moved forward to where they stood
+    pub counters: Vec<CounterChange>,
+    /// ARKs bound after the restore time, left as they are
+    pub kept: usize,
+    /// ARKs changed both before and after the restore time, left as they are
+    pub conflicts: usize,
+    /// Changes from the live configuration to the snapshot's, staged for review
+    pub config_changes: Vec<ConfigChange>,
+    /// The first ARKs affected, by canonical ARK
+    pub arks: Vec<RestoredArk>,
+}
+
+/// Whether `name` can name a snapshot: up to 64 ASCII letters, digits, `-`, `_` and `.`, not
+/// starting with `.`
+pub fn is_valid_name(name: &str) -> bool {
+    (1..=64).contains(&name.len())
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Directory of snapshot files, one JSON Lines file per snapshot: its summary, counters and
+/// configuration on the first line, then one line per ARK
+#[derive(Debug)]
+pub struct SnapshotDir {
+    path: PathBuf,
+    drain_timeout: Duration,
+}
+
+impl SnapshotDir {
+    /// Opens the directory at `path`, creating it if needed
+    pub fn open(path: impl Into<PathBuf>, drain_timeout: Duration) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(Self {
+            path,
+            drain_timeout,
+        })
+    }
+
+    /// How long taking a snapshot waits for writes in progress to finish
+    pub fn drain_timeout(&self) -> Duration {
+        self.drain_timeout
+    }
+
+    fn file(&self, name: &str) -> PathBuf {
+        self.path.join(format!("{}.jsonl", name))
+    }
+
+    /// Whether a snapshot called `name` exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.file(name).exists()
+    }
+
+    /// Writes a new snapshot file, all or nothing; snapshots are never overwritten
+    pub fn save(&self, snapshot: &Snapshot) -> io::Result<()> {
+        let file = self.file(&snapshot.summary().name);
+        if file.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("snapshot {} already exists", snapshot.summary().name),
+            ));
+        }
+        let partial = self
+            .path
+            .join(format!(".{}.partial", snapshot.summary().name));
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer(&mut writer, &snapshot.header)?;
+        writer.write_all(b"\n")?;
+        for entry in &snapshot.entries {
+            serde_json::to_writer(&mut writer, entry)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.into_inner()?.sync_all()?;
+        fs::rename(partial, file)
+    }
+
+    /// Every snapshot, oldest first
+    pub fn list(&self) -> io::Result<Vec<SnapshotSummary>> {
+        let mut summaries = Vec::new();
+        for file in fs::read_dir(&self.path)? {
+            let path = file?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".jsonl"))
+                .filter(|name| is_valid_name(name))
+            else {
+                continue;
+            };
+            let mut first = String::new();
+            BufReader::new(File::open(&path)?).read_line(&mut first)?;
+            match serde_json::from_str::<SnapshotHeader>(&first) {
+                Ok(header) => summaries.push(header.summary),
+                Err(e) => tracing::warn!(snapshot = %name, error = %e, "Unreadable snapshot"),
+            }
+        }
+        summaries.sort_by_key(|summary| summary.taken_at);
+        Ok(summaries)
+    }
+
+    /// The snapshot called `name`, if there is one
+    pub fn load(&self, name: &str) -> io::Result<Option<Snapshot>> {
+        if !is_valid_name(name) {
+            return Ok(None);
+        }
+        let file = match File::open(self.file(name)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut lines = BufReader::new(file).lines();
+        let header: SnapshotHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "empty snapshot")),
+        };
+        let entries = lines
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<io::Result<Vec<SnapshotEntry>>>()?;
+        Ok(Some(Snapshot { header, entries }))
+    }
+
+    /// The newest snapshot taken at or before `at`
+    pub fn latest_at(&self, at: DateTime<Utc>) -> io::Result<Option<SnapshotSummary>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .rev()
+            .find(|summary| summary.taken_at <= at))
+    }
+}
+
+/// Open the snapshot directory from environment variables; `None` unless `SNAPSHOT_DIR` is set
+///
+/// Optional: `SNAPSHOT_DRAIN_TIMEOUT_SECS` (default 30).
+pub fn load_snapshots_from_env() -> Result<Option<SnapshotDir>, String> {
+    let Ok(path) = std::env::var("SNAPSHOT_DIR") else {
+        return Ok(None);
+    };
+    let drain_timeout = env_parse("SNAPSHOT_DRAIN_TIMEOUT_SECS")?
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_DRAIN_TIMEOUT);
+    SnapshotDir::open(&path, drain_timeout)
+        .map(Some)
+        .map_err(|e| format!("Failed to open SNAPSHOT_DIR {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ArkChange;
+    use crate::testing::fixture_state;
+    use chrono::TimeDelta;
+
+    fn binding(target: &str) -> Binding {
+        Binding {
+            target: target.to_string(),
+            metadata: BTreeMap::new(),
+            bound_at: Utc::now(),
+            lifecycle: Default::default(),
+        }
+    }
+
+    fn changed(ark: &str, at: DateTime<Utc>) -> ArkEvent {
+        ArkEvent {
+            ark: ark.to_string(),
+            change: ArkChange::Updated,
+            at,
+        }
+    }
+
+    fn snapshot_of(state: &AppState) -> Snapshot {
+        let config = ConfigDocument::from_live(
+            &state.naan,
+            state.default_blade_length,
+            state.max_mint_count,
+            &state.shoulders,
+            &state.config_store,
+        );
+        Snapshot::capture(state, "before".to_string(), "tester", config).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let state = fixture_state();
+        state
+            .store
+            .record("x6", &["ark:12345/x6a".to_string()], &BTreeMap::new())
+            .unwrap();
+        state
+            .store
+            .put_all(vec![
+                (
+                    "ark:12345/x6a".to_string(),
+                    binding("https://example.org/a"),
+                ),
+                (
+                    "ark:12345/x6b".to_string(),
+                    binding("https://example.org/b"),
+                ),
+            ])
+            .unwrap();
+        let snapshot = snapshot_of(&state);
+        assert_eq!(
+            (snapshot.summary().minted, snapshot.summary().bindings),
+            (1, 2)
+        );
+
+        let path = std::env::temp_dir().join(format!("ark-snapshots-{}", std::process::id()));
+        let dir = SnapshotDir::open(&path, DEFAULT_DRAIN_TIMEOUT).unwrap();
+        dir.save(&snapshot).unwrap();
+        // Snapshots are never overwritten
+        assert!(dir.save(&snapshot).is_err());
+        assert!(dir.contains("before"));
+        assert_eq!(dir.list().unwrap(), [snapshot.summary().clone()]);
+        let loaded = dir.load("before").unwrap().unwrap();
+        assert_eq!(loaded.summary(), snapshot.summary());
+        assert_eq!(loaded.entries, snapshot.entries);
+        assert!(dir.load("../before").unwrap().is_none());
+        assert!(
+            dir.latest_at(snapshot.summary().taken_at - TimeDelta::seconds(1))
+                .unwrap()
+                .is_none()
+        );
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_plan_keeps_changes_up_to_the_restore_time() {
+        let state = fixture_state();
+        let arks = ["ark:12345/x6a", "ark:12345/x6b", "ark:12345/x6c"];
+        state
+            .store
+            .put_all(
+                arks.iter()
+                    .map(|ark| (ark.to_string(), binding("https://example.org/old")))
+                    .collect(),
+            )
+            .unwrap();
+        let snapshot = snapshot_of(&state);
+        let taken_at = snapshot.summary().taken_at;
+        let at = taken_at + TimeDelta::seconds(10);
+
+        state
+            .store
+            .put_all(
+                arks.iter()
+                    .chain(["ark:12345/x6d"].iter())
+                    .map(|ark| (ark.to_string(), binding("https://example.org/new")))
+                    .collect(),
+            )
+            .unwrap();
+        let before = taken_at + TimeDelta::seconds(5);
+        let after = taken_at + TimeDelta::seconds(20);
+        let events = [
+            changed(arks[0], before),
+            changed(arks[1], after),
+            changed(arks[2], before),
+            changed(arks[2], after),
+            changed("ark:12345/x6d", after),
+        ];
+
+        let plan = snapshot.plan_restore(&state, at, &events).unwrap();
+        let report = plan.report(snapshot.summary(), at, true, Vec::new());
+        assert_eq!(
+            (
+                report.rebound,
+                report.kept,
+                report.conflicts,
+                report.recorded
+            ),
+            (1, 1, 1, 0)
+        );
+        assert_eq!(plan.rebound().collect::<Vec<_>>(), [arks[1]]);
+    }
+
+    #[test]
+    fn test_restore_brings_back_ledger_and_counters() {
+        let state = fixture_state();
+        let arks = vec!["ark:12345/x6a".to_string(), "ark:12345/x6b".to_string()];
+        state.store.record("x6", &arks, &BTreeMap::new()).unwrap();
+        let requested_by = BTreeMap::from([("creator".to_string(), "digitization".to_string())]);
+        state.store.annotate(&arks[1..], &requested_by).unwrap();
+        state
+            .store
+            .put_all(vec![(arks[0].clone(), binding("https://example.org/a"))])
+            .unwrap();
+        state.store.reserve_sequence("x6", 5).unwrap();
+        let snapshot = snapshot_of(&state);
+        let taken_at = snapshot.summary().taken_at;
+
+        let fresh = fixture_state();
+        let plan = snapshot.plan_restore(&fresh, taken_at, &[]).unwrap();
+        let report = plan.report(snapshot.summary(), taken_at, false, Vec::new());
+        assert_eq!((report.rebound, report.recorded), (1, 2));
+        assert_eq!(
+            report.counters,
+            [CounterChange {
+                counter: "x6".to_string(),
+                from: 0,
+                to: 5
+            }]
+        );
+        plan.apply(&fresh).unwrap();
+        assert!(fresh.store.contains(&arks[1]).unwrap());
+        let restored = fresh.store.entries(None, None, 10).unwrap().entries;
+        assert_eq!(restored[1].metadata, requested_by);
+        assert_eq!(fresh.store.sequence_position("x6").unwrap(), 5);
+        assert_eq!(
+            fresh.store.get(&arks[0]).unwrap().unwrap().target,
+            "https://example.org/a"
+        );
+
+        // Nothing is left to restore
+        let again = snapshot
+            .plan_restore(&fresh, taken_at, &[])
+            .unwrap()
+            .report(snapshot.summary(), taken_at, true, Vec::new());
+        assert_eq!((again.rebound, again.recorded), (0, 0));
+        assert!(again.counters.is_empty());
+    }
+}
@@ -0,0 +1,792 @@
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::api_keys::{ApiKey, KeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, LegacyTombstone, StorageError};
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::idempotency::{MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerEntry, LedgerPage, MintLedger, next_page_by_ark};
+use crate::store::ArkStore;
+
+/// Store in an SQLite database file, which survives restarts of a single instance
+pub struct SqliteArkStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteArkStore {
+    /// Opens the database at `path`, creating it and its tables if needed
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+        // Full sync: a mint is only acknowledged once its ARKs are on disk
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 PRAGMA synchronous = FULL;
+                 CREATE TABLE IF NOT EXISTS minted (
+                     ark TEXT PRIMARY KEY,
+                     shoulder TEXT NOT NULL,
+                     minted_at TEXT NOT NULL,
+                     metadata TEXT
+                 ) WITHOUT ROWID;
+                 CREATE INDEX IF NOT EXISTS minted_shoulder ON minted (shoulder);
+                 CREATE TABLE IF NOT EXISTS sequences (
+                     shoulder TEXT PRIMARY KEY,
+                     next_position INTEGER NOT NULL
+                 ) WITHOUT ROWID;
+                 CREATE TABLE IF NOT EXISTS bindings (
+                     ark TEXT PRIMARY KEY,
+                     target TEXT NOT NULL,
+                     metadata TEXT NOT NULL,
+                     bound_at TEXT NOT NULL,
+                     lifecycle TEXT
+                 ) WITHOUT ROWID;
+                 CREATE TABLE IF NOT EXISTS deliveries (
+                     id TEXT PRIMARY KEY,
+                     status TEXT NOT NULL,
+                     created_at TEXT NOT NULL,
+                     delivery TEXT NOT NULL
+                 ) WITHOUT ROWID;
+                 CREATE TABLE IF NOT EXISTS mint_receipts (
+                     key TEXT PRIMARY KEY,
+                     created_at TEXT NOT NULL,
+                     receipt TEXT NOT NULL
+                 ) WITHOUT ROWID;
+                 CREATE TABLE IF NOT EXISTS api_keys (
+                     id TEXT PRIMARY KEY,
+                     api_key TEXT NOT NULL
+                 ) WITHOUT ROWID;",
+            )
+            .map_err(sqlite_error)?;
+        add_column(&connection, "bindings", "lifecycle")?;
+        add_column(&connection, "minted", "metadata")?;
+        migrate_tombstones(&connection)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection.lock().expect("sqlite store lock poisoned")
+    }
+}
+
+/// Adds a nullable text column to a table created before it had it, such as `lifecycle` to
+/// `bindings` from before ARKs had a status
+fn add_column(connection: &Connection, table: &str, column: &str) -> Result<(), StorageError> {
+    let present: bool = connection
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            [table, column],
+            |row| row.get(0),
+        )
+        .map_err(sqlite_error)?;
+    if !present {
+        connection
+            .execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} TEXT", table, column),
+                [],
+            )
+            .map_err(sqlite_error)?;
+    }
+    Ok(())
+}
+
+/// Turns the `tombstone` of bindings withdrawn before ARKs had a lifecycle into a `tombstoned`
+/// lifecycle, clearing it so this runs once
+fn migrate_tombstones(connection: &Connection) -> Result<(), StorageError> {
+    let present: bool = connection
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('bindings') WHERE name = 'tombstone'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(sqlite_error)?;
+    if !present {
+        return Ok(());
+    }
+    let tombstones: Vec<(String, String, Option<String>)> = connection
+        .prepare("SELECT ark, tombstone, lifecycle FROM bindings WHERE tombstone IS NOT NULL")
+        .and_then(|mut select| {
+            select
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect()
+        })
+        .map_err(sqlite_error)?;
+    for (ark, tombstone, lifecycle) in tombstones {
+        // A lifecycle written since the upgrade is newer than the tombstone
+        let lifecycle = match lifecycle {
+            Some(lifecycle) => lifecycle,
+            None => {
+                let tombstone: LegacyTombstone = serde_json::from_str(&tombstone)
+                    .map_err(|e| StorageError(format!("sqlite store: bad tombstone: {}", e)))?;
+                serde_json::to_string(&tombstone.into_lifecycle()).expect("lifecycle serializes")
+            }
+        };
+        connection
+            .execute(
+                "UPDATE bindings SET lifecycle = ?2, tombstone = NULL WHERE ark = ?1",
+                params![ark, lifecycle],
+            )
+            .map_err(sqlite_error)?;
+    }
+    Ok(())
+}
+
+impl MintLedger for SqliteArkStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        let metadata = (!metadata.is_empty())
+            .then(|| serde_json::to_string(metadata).expect("metadata serializes"));
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        let mut taken = Vec::new();
+        {
+            let mut insert = transaction
+                .prepare_cached(
+                    "INSERT OR IGNORE INTO minted (ark, shoulder, minted_at, metadata)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(sqlite_error)?;
+            let minted_at = Utc::now().to_rfc3339();
+            for ark in arks {
+                let inserted = insert
+                    .execute(params![ark, shoulder, minted_at, metadata])
+                    .map_err(sqlite_error)?;
+                if inserted == 0 {
+                    taken.push(ark.clone());
+                }
+            }
+        }
+        transaction.commit().map_err(sqlite_error)?;
+        Ok(taken)
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        let json = (!metadata.is_empty())
+            .then(|| serde_json::to_string(metadata).expect("metadata serializes"));
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        {
+            let mut update = transaction
+                .prepare_cached("UPDATE minted SET metadata = ?2 WHERE ark = ?1")
+                .map_err(sqlite_error)?;
+            for ark in arks {
+                update.execute(params![ark, json]).map_err(sqlite_error)?;
+            }
+        }
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        self.connection()
+            .query_row("SELECT 1 FROM minted WHERE ark = ?1", [ark], |_| Ok(()))
+            .optional()
+            .map(|found| found.is_some())
+            .map_err(sqlite_error)
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.connection()
+            .query_row("SELECT COUNT(*) FROM minted", [], |row| row.get(0))
+            .map_err(sqlite_error)
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        self.connection()
+            .query_row(
+                "SELECT COUNT(*) FROM minted WHERE shoulder = ?1",
+                [shoulder],
+                |row| row.get(0),
+            )
+            .map(Some)
+            .map_err(sqlite_error)
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        let next: u64 = self
+            .connection()
+            .query_row(
+                "INSERT INTO sequences (shoulder, next_position) VALUES (?1, ?2)
+                 ON CONFLICT (shoulder) DO UPDATE SET next_position = next_position + ?2
+                 RETURNING next_position",
+                params![shoulder, count],
+                |row| row.get(0),
+            )
+            .map_err(sqlite_error)?;
+        Ok(next - count)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        self.connection()
+            .query_row(
+                "SELECT next_position FROM sequences WHERE shoulder = ?1",
+                [shoulder],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(|next| next.unwrap_or(0))
+            .map_err(sqlite_error)
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        let connection = self.connection();
+        let mut select = connection
+            .prepare_cached(
+                "SELECT ark, shoulder, minted_at, metadata FROM minted
+                 WHERE ark > ?1 AND (?2 IS NULL OR shoulder = ?2)
+                 ORDER BY ark LIMIT ?3",
+            )
+            .map_err(sqlite_error)?;
+        let rows = select
+            .query_map(params![cursor.unwrap_or(""), shoulder, limit], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .map_err(sqlite_error)?;
+        let entries = rows
+            .map(|row| {
+                let (ark, shoulder, minted_at, metadata) = row.map_err(sqlite_error)?;
+                let minted_at = DateTime::parse_from_rfc3339(&minted_at)
+                    .map_err(|e| StorageError(format!("sqlite store: bad minted_at: {}", e)))?
+                    .with_timezone(&Utc);
+                let metadata = metadata
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .map_err(|e| StorageError(format!("sqlite store: bad metadata: {}", e)))?
+                    .unwrap_or_default();
+                Ok(LedgerEntry {
+                    ark,
+                    shoulder,
+                    minted_at: Some(minted_at),
+                    metadata,
+                })
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        Ok(next_page_by_ark(entries, limit))
+    }
+}
+
+impl BindingStore for SqliteArkStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.connection()
+            .query_row(
+                "SELECT target, metadata, bound_at, lifecycle FROM bindings WHERE ark = ?1",
+                [canonical],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .map(binding_from_row)
+            .transpose()
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(sqlite_error)?;
+        {
+            let mut upsert = transaction
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO bindings (ark, target, metadata, bound_at, lifecycle)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(sqlite_error)?;
+            for (canonical, binding) in bindings {
+                let metadata =
+                    serde_json::to_string(&binding.metadata).expect("metadata serializes");
+                // Untouched lifecycles are left out, as most ARKs never change status
+                let lifecycle = (!binding.lifecycle.is_default()).then(|| {
+                    serde_json::to_string(&binding.lifecycle).expect("lifecycle serializes")
+                });
+                upsert
+                    .execute(params![
+                        canonical,
+                        binding.target,
+                        metadata,
+                        binding.bound_at.to_rfc3339(),
+                        lifecycle
+                    ])
+                    .map_err(sqlite_error)?;
+            }
+        }
+        transaction.commit().map_err(sqlite_error)
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        let metadata = serde_json::to_string(&binding.metadata).expect("metadata serializes");
+        let lifecycle = (!binding.lifecycle.is_default())
+            .then(|| serde_json::to_string(&binding.lifecycle).expect("lifecycle serializes"));
+        let connection = self.connection();
+        let inserted = connection
+            .execute(
+                "INSERT OR IGNORE INTO bindings (ark, target, metadata, bound_at, lifecycle)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    canonical,
+                    binding.target,
+                    metadata,
+                    binding.bound_at.to_rfc3339(),
+                    lifecycle
+                ],
+            )
+            .map_err(sqlite_error)?;
+        if inserted > 0 {
+            return Ok(None);
+        }
+        drop(connection);
+        self.get(canonical)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.connection()
+            .query_row("SELECT COUNT(*) FROM bindings", [], |row| row.get(0))
+            .map_err(sqlite_error)
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        self.connection()
+            .query_row(
+                "SELECT COUNT(*) FROM bindings WHERE substr(ark, 1, length(?1)) = ?1",
+                [prefix],
+                |row| row.get(0),
+            )
+            .map(Some)
+            .map_err(sqlite_error)
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        let connection = self.connection();
+        let mut select = connection
+            .prepare_cached(
+                "SELECT ark, target, metadata, bound_at, lifecycle FROM bindings
+                 WHERE ark > ?1 ORDER BY ark LIMIT ?2",
+            )
+            .map_err(sqlite_error)?;
+        let rows = select
+            .query_map(params![cursor.unwrap_or(""), limit], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?),
+                ))
+            })
+            .map_err(sqlite_error)?;
+        let bindings = rows
+            .map(|row| {
+                let (ark, binding) = row.map_err(sqlite_error)?;
+                Ok((ark, binding_from_row(binding)?))
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        Ok(BindingPage::by_ark(bindings, limit))
+    }
+}
+
+/// A binding from its `target`, `metadata`, `bound_at` and `lifecycle` columns
+fn binding_from_row(
+    (target, metadata, bound_at, lifecycle): (String, String, String, Option<String>),
+) -> Result<Binding, StorageError> {
+    let metadata: BTreeMap<String, String> = serde_json::from_str(&metadata)
+        .map_err(|e| StorageError(format!("sqlite store: bad metadata: {}", e)))?;
+    let bound_at = DateTime::parse_from_rfc3339(&bound_at)
+        .map_err(|e| StorageError(format!("sqlite store: bad bound_at: {}", e)))?
+        .with_timezone(&Utc);
+    let lifecycle = lifecycle
+        .map(|lifecycle| serde_json::from_str(&lifecycle))
+        .transpose()
+        .map_err(|e| StorageError(format!("sqlite store: bad lifecycle: {}", e)))?
+        .unwrap_or_default();
+    Ok(Binding {
+        target,
+        metadata,
+        bound_at,
+        lifecycle,
+    })
+}
+
+impl DeliveryStore for SqliteArkStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        let json = serde_json::to_string(delivery).expect("delivery serializes");
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO deliveries (id, status, created_at, delivery)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    delivery.id,
+                    delivery.status.as_str(),
+                    delivery.created_at.to_rfc3339(),
+                    json
+                ],
+            )
+            .map(|_| ())
+            .map_err(sqlite_error)
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        let json: Option<String> = self
+            .connection()
+            .query_row(
+                "SELECT delivery FROM deliveries WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        json.as_deref().map(parse_delivery).transpose()
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        let connection = self.connection();
+        let mut select = connection
+            .prepare_cached(
+                "SELECT delivery FROM deliveries WHERE ?1 IS NULL OR status = ?1
+                 ORDER BY created_at DESC",
+            )
+            .map_err(sqlite_error)?;
+        let rows = select
+            .query_map([status.map(|status| status.as_str())], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(sqlite_error)?;
+        rows.map(|json| parse_delivery(&json.map_err(sqlite_error)?))
+            .collect()
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.connection()
+            .execute(
+                "DELETE FROM deliveries WHERE status = ?1 AND created_at < ?2",
+                params![DeliveryStatus::Delivered.as_str(), before.to_rfc3339()],
+            )
+            .map_err(sqlite_error)
+    }
+}
+
+fn parse_delivery(json: &str) -> Result<Delivery, StorageError> {
+    serde_json::from_str(json)
+        .map_err(|e| StorageError(format!("sqlite store: bad delivery: {}", e)))
+}
+
+impl ReceiptStore for SqliteArkStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        let json = serde_json::to_string(receipt).expect("receipt serializes");
+        let connection = self.connection();
+        let inserted = connection
+            .execute(
+                "INSERT OR IGNORE INTO mint_receipts (key, created_at, receipt) VALUES (?1, ?2, ?3)",
+                params![receipt.key, receipt.created_at.to_rfc3339(), json],
+            )
+            .map_err(sqlite_error)?;
+        if inserted > 0 {
+            return Ok(None);
+        }
+        drop(connection);
+        self.receipt(&receipt.key)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        let json: Option<String> = self
+            .connection()
+            .query_row(
+                "SELECT receipt FROM mint_receipts WHERE key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)?;
+        json.map(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| StorageError(format!("sqlite store: bad receipt: {}", e)))
+        })
+        .transpose()
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.connection()
+            .execute(
+                "DELETE FROM mint_receipts WHERE created_at < ?1",
+                [before.to_rfc3339()],
+            )
+            .map_err(sqlite_error)
+    }
+}
+
+impl KeyStore for SqliteArkStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        let json = serde_json::to_string(key).expect("api key serializes");
+        self.connection()
+            .execute(
+                "INSERT OR REPLACE INTO api_keys (id, api_key) VALUES (?1, ?2)",
+                params![key.id, json],
+            )
+            .map(|_| ())
+            .map_err(sqlite_error)
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT api_key FROM api_keys ORDER BY id")
+            .map_err(sqlite_error)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(sqlite_error)?;
+        rows.map(|json| {
+            serde_json::from_str(&json.map_err(sqlite_error)?)
+                .map_err(|e| StorageError(format!("sqlite store: bad api key: {}", e)))
+        })
+        .collect()
+    }
+}
+
+impl ArkStore for SqliteArkStore {
+    fn backend(&self) -> &'static str {
+        "sqlite"
+    }
+}
+
+fn sqlite_error(error: rusqlite::Error) -> StorageError {
+    StorageError(format!("sqlite store: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::{ApiKeys, Scope};
+    use crate::ark::parse_ark;
+    use crate::bindings::Bindings;
+    use crate::lifecycle::{ArkStatus, Lifecycle};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sqlite_store_survives_reopening() {
+        let path = std::env::temp_dir().join(format!("ark-store-{}.db", std::process::id()));
+        let arks = [
+            "ark:12345/x6aaaa".to_string(),
+            "ark:12345/x6bbbb".to_string(),
+        ];
+        let ark = parse_ark("ark:12345/x6aaaa").unwrap();
+
+        let store = Arc::new(SqliteArkStore::open(&path).unwrap());
+        assert!(
+            store
+                .record("x6", &arks, &BTreeMap::new())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store.record("x6", &arks[..1], &BTreeMap::new()).unwrap(),
+            &arks[..1]
+        );
+        let requested_by = BTreeMap::from([("creator".to_string(), "digitization".to_string())]);
+        store
+            .annotate(
+                &[arks[1].clone(), "ark:12345/x6cccc".to_string()],
+                &requested_by,
+            )
+            .unwrap();
+        assert_eq!(store.reserve_sequence("x6", 3).unwrap(), 0);
+        let delivery = Delivery::new(
+            "https://hooks.example.org/x6",
+            "x6",
+            serde_json::json!({"shoulder": "x6"}),
+        );
+        store.save_delivery(&delivery).unwrap();
+        let receipt = MintReceipt {
+            key: "batch-7".to_string(),
+            shoulder: "x6".to_string(),
+            count: 2,
+            metadata: Default::default(),
+            detail: false,
+            arks: arks.to_vec(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(store.save_receipt(&receipt).unwrap(), None);
+        let issued = ApiKeys::default()
+            .create(store.as_ref(), "ingest", &[Scope::Admin], None, "tester")
+            .unwrap();
+        let mut lifecycle = Lifecycle::default();
+        lifecycle
+            .transition(
+                ArkStatus::Withdrawn,
+                None,
+                Some("Duplicate".to_string()),
+                Utc::now(),
+            )
+            .unwrap();
+        Bindings::new(store.clone())
+            .bind_all([(
+                ark.clone(),
+                Binding {
+                    target: "https://example.org/a".to_string(),
+                    metadata: BTreeMap::from([("title".to_string(), "A".to_string())]),
+                    bound_at: Utc::now(),
+                    lifecycle,
+                },
+            )])
+            .unwrap();
+        drop(store);
+
+        let store = Arc::new(SqliteArkStore::open(&path).unwrap());
+        let contains_b = store.contains("ark:12345/x6bbbb").unwrap();
+        let contains_c = store.contains("ark:12345/x6cccc").unwrap();
+        let minted = store.minted().unwrap();
+        let minted_on = (
+            store.minted_on("x6").unwrap(),
+            store.minted_on("b3").unwrap(),
+        );
+        let first_page = store.entries(Some("x6"), None, 1).unwrap();
+        let last_page = store
+            .entries(Some("x6"), first_page.next.as_deref(), 1)
+            .unwrap();
+        let other_shoulder = store.entries(Some("b3"), None, 10).unwrap();
+        let sequence = store.reserve_sequence("x6", 2).unwrap();
+        let position = store.sequence_position("x6").unwrap();
+        let binding = Bindings::new(store.clone()).get(&ark).unwrap();
+        let listed = store.bindings(None, 10).unwrap();
+        let bound_on = (
+            store.count_prefixed("ark:12345/x6").unwrap(),
+            store.count_prefixed("ark:12345/b3").unwrap(),
+        );
+        let unbound = Binding {
+            target: "https://example.org/b".to_string(),
+            metadata: BTreeMap::new(),
+            bound_at: Utc::now(),
+            lifecycle: Lifecycle::default(),
+        };
+        let kept_binding = store.put_new(ark.canonical(), &unbound).unwrap();
+        let new_binding = store.put_new("ark:12345/x6bbbb", &unbound).unwrap();
+        let pending = store.deliveries(Some(DeliveryStatus::Pending)).unwrap();
+        let pruned = store.prune_deliveries(Utc::now()).unwrap();
+        let retried = MintReceipt {
+            arks: Vec::new(),
+            ..receipt.clone()
+        };
+        let kept = store.save_receipt(&retried).unwrap();
+        let pruned_receipts = store
+            .prune_receipts(receipt.created_at + chrono::Duration::seconds(1))
+            .unwrap();
+        let api_keys = store.api_keys().unwrap();
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+
+        assert!(contains_b);
+        assert!(!contains_c);
+        assert_eq!(minted, 2);
+        assert_eq!(minted_on, (Some(2), Some(0)));
+        assert_eq!(bound_on, (Some(1), Some(0)));
+        assert_eq!(first_page.entries[0].ark, "ark:12345/x6aaaa");
+        assert!(first_page.entries[0].minted_at.is_some());
+        assert_eq!(first_page.next.as_deref(), Some("ark:12345/x6aaaa"));
+        assert_eq!(last_page.entries[0].ark, "ark:12345/x6bbbb");
+        assert!(first_page.entries[0].metadata.is_empty());
+        assert_eq!(last_page.entries[0].metadata, requested_by);
+        assert_eq!(other_shoulder, LedgerPage::default());
+        assert_eq!((sequence, position), (3, 5));
+        assert_eq!(pending, [delivery]);
+        // Only delivered deliveries are pruned
+        assert_eq!(pruned, 0);
+        // The first receipt for a key is kept
+        assert_eq!(kept, Some(receipt));
+        assert_eq!(api_keys, [issued.record]);
+        assert_eq!(pruned_receipts, 1);
+        let binding = binding.unwrap();
+        assert_eq!(
+            listed.bindings,
+            [(ark.canonical().to_string(), binding.clone())]
+        );
+        assert_eq!(listed.next, None);
+        // A new binding only takes an ARK without one
+        assert_eq!(kept_binding, Some(binding.clone()));
+        assert_eq!(new_binding, None);
+        assert_eq!(binding.target, "https://example.org/a");
+        assert_eq!(binding.metadata["title"], "A");
+        assert_eq!(binding.lifecycle.status, ArkStatus::Withdrawn);
+        assert_eq!(
+            binding.lifecycle.history[0].reason.as_deref(),
+            Some("Duplicate")
+        );
+    }
+
+    #[test]
+    fn test_sqlite_store_adds_lifecycle_column() {
+        let path = std::env::temp_dir().join(format!("ark-store-old-{}.db", std::process::id()));
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE bindings (
+                     ark TEXT PRIMARY KEY,
+                     target TEXT NOT NULL,
+                     metadata TEXT NOT NULL,
+                     bound_at TEXT NOT NULL
+                 ) WITHOUT ROWID;
+                 INSERT INTO bindings VALUES
+                     ('ark:12345/x6bbbb', 'https://example.org/b', '{}', '2025-01-15T00:00:00Z');",
+            )
+            .unwrap();
+
+        let binding = SqliteArkStore::open(&path).and_then(|store| store.get("ark:12345/x6bbbb"));
+        let _ = std::fs::remove_file(&path);
+
+        let binding = binding.unwrap().unwrap();
+        assert_eq!(binding.target, "https://example.org/b");
+        assert!(binding.lifecycle.is_default());
+    }
+
+    #[test]
+    fn test_sqlite_store_migrates_tombstones() {
+        let path = std::env::temp_dir().join(format!("ark-store-tomb-{}.db", std::process::id()));
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                r#"CREATE TABLE bindings (
+                     ark TEXT PRIMARY KEY,
+                     target TEXT NOT NULL,
+                     metadata TEXT NOT NULL,
+                     bound_at TEXT NOT NULL,
+                     tombstone TEXT
+                 ) WITHOUT ROWID;
+                 INSERT INTO bindings VALUES
+                     ('ark:12345/x6bbbb', 'https://example.org/b', '{}', '2025-01-15T00:00:00Z',
+                      '{"reason":"Duplicate","withdrawn_at":"2025-02-01T00:00:00Z"}'),
+                     ('ark:12345/x6cccc', 'https://example.org/c', '{}', '2025-01-15T00:00:00Z',
+                      NULL);"#,
+            )
+            .unwrap();
+
+        let bindings = SqliteArkStore::open(&path).and_then(|store| {
+            Ok((
+                store.get("ark:12345/x6bbbb")?,
+                store.get("ark:12345/x6cccc")?,
+            ))
+        });
+        let _ = std::fs::remove_file(&path);
+
+        let (withdrawn, bound) = bindings.unwrap();
+        let lifecycle = withdrawn.unwrap().lifecycle;
+        assert_eq!(lifecycle.status, ArkStatus::Tombstoned);
+        assert_eq!(lifecycle.history[0].reason.as_deref(), Some("Duplicate"));
+        assert!(bound.unwrap().lifecycle.is_default());
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+
+/// Hours of resolution counts kept per shoulder: 30 days
+const RETAINED_HOURS: i64 = 30 * 24;
+
+/// `(hour since the epoch, resolutions)` of each shoulder, oldest first
+type Buckets = RwLock<HashMap<String, VecDeque<(i64, u64)>>>;
+
+/// Successful resolutions per shoulder, counted in hourly buckets over the last 30 days
+#[derive(Debug, Default)]
+pub struct ResolutionStats {
+    shoulders: Buckets,
+    /// Resolutions of components (see `Shoulder::components`), which `shoulders` counts too
+    components: Buckets,
+}
+
+/// Resolutions of one shoulder over the recent past
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RecentResolutions {
+    pub last_24h: u64,
+    pub last_30d: u64,
+    /// Of `last_24h`, resolutions of components
+    pub components_24h: u64,
+    /// Of `last_30d`, resolutions of components
+    pub components_30d: u64,
+}
+
+fn hour_of(at: DateTime<Utc>) -> i64 {
+    at.timestamp().div_euclid(SECONDS_PER_HOUR)
+}
+
+/// Counts one resolution in `shoulder`'s bucket for `hour`, dropping buckets past retention
+fn count(buckets: &Buckets, shoulder: &str, hour: i64) {
+    let mut shoulders = buckets.write().expect("resolution stats lock poisoned");
+    let buckets = shoulders.entry(shoulder.to_string()).or_default();
+    match buckets.back_mut() {
+        Some((last, count)) if *last == hour => *count += 1,
+        _ => buckets.push_back((hour, 1)),
+    }
+    while buckets
+        .front()
+        .is_some_and(|(first, _)| *first <= hour - RETAINED_HOURS)
+    {
+        buckets.pop_front();
+    }
+}
+
+/// Resolutions of `shoulder` in the last 24 hours and 30 days up to `hour`
+fn recent(buckets: &Buckets, shoulder: &str, hour: i64) -> (u64, u64) {
+    let shoulders = buckets.read().expect("resolution stats lock poisoned");
+    let Some(buckets) = shoulders.get(shoulder) else {
+        return (0, 0);
+    };
+    let since = |hours: i64| -> u64 {
+        buckets
+            .iter()
+            .filter(|(bucket, _)| *bucket > hour - hours && *bucket <= hour)
+            .map(|(_, count)| count)
+            .sum()
+    };
+    (since(24), since(RETAINED_HOURS))
+}
+
+impl ResolutionStats {
+    /// Counts a resolution of an ARK on `shoulder` now
+    pub fn record(&self, shoulder: &str) {
+        self.record_at(shoulder, false, Utc::now());
+    }
+
+    /// Counts a resolution of a component of an ARK on `shoulder` now, which also counts
+    /// towards the shoulder's resolutions
+    pub fn record_component(&self, shoulder: &str) {
+        self.record_at(shoulder, true, Utc::now());
+    }
+
+    fn record_at(&self, shoulder: &str, component: bool, at: DateTime<Utc>) {
+        let hour = hour_of(at);
+        count(&self.shoulders, shoulder, hour);
+        if component {
+            count(&self.components, shoulder, hour);
+        }
+    }
+
+    /// Resolutions of `shoulder` in the last 24 hours and 30 days, in whole hours including
+    /// the current one
+    pub fn recent(&self, shoulder: &str) -> RecentResolutions {
+        self.recent_at(shoulder, Utc::now())
+    }
+
+    fn recent_at(&self, shoulder: &str, now: DateTime<Utc>) -> RecentResolutions {
+        let hour = hour_of(now);
+        let (last_24h, last_30d) = recent(&self.shoulders, shoulder, hour);
+        let (components_24h, components_30d) = recent(&self.components, shoulder, hour);
+        RecentResolutions {
+            last_24h,
+            last_30d,
+            components_24h,
+            components_30d,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_recent_resolutions() {
+        let stats = ResolutionStats::default();
+        let now: DateTime<Utc> = "2025-03-31T12:30:00Z".parse().unwrap();
+
+        stats.record_at("x6", false, now - TimeDelta::days(40));
+        stats.record_at("x6", false, now - TimeDelta::days(10));
+        stats.record_at("x6", false, now - TimeDelta::hours(2));
+        stats.record_at("x6", true, now - TimeDelta::minutes(10));
+        stats.record_at("x6", false, now);
+        stats.record_at("b3", false, now);
+
+        assert_eq!(
+            stats.recent_at("x6", now),
+            RecentResolutions {
+                last_24h: 3,
+                last_30d: 4,
+                components_24h: 1,
+                components_30d: 1,
+            }
+        );
+        assert_eq!(stats.recent_at("zz", now), RecentResolutions::default());
+        // Buckets older than 30 days are dropped
+        assert_eq!(stats.shoulders.read().unwrap()["x6"].len(), 3);
+    }
+}
@@ -0,0 +1,292 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// How long resolved incidents stay on the status page
+const RECENT_INCIDENT_DAYS: i64 = 30;
+
+/// How badly an incident affects the service
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Minor,
+    Major,
+    Critical,
+}
+
+/// An incident recorded by an operator through the admin API
+#[derive(Clone, Debug, Serialize)]
+pub struct Incident {
+    pub id: u64,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub severity: Severity,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub recorded_by: String,
+}
+
+/// A scheduled maintenance window
+#[derive(Clone, Debug, Serialize)]
+pub struct MaintenanceWindow {
+    pub id: u64,
+    pub description: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub recorded_by: String,
+}
+
+impl MaintenanceWindow {
+    /// Whether the window covers the given instant
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+/// Health of something the service depends on
+#[derive(Clone, Debug, Serialize)]
+pub struct DependencyHealth {
+    pub name: String,
+    /// `ok`, `disabled`, or `failing`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Overall state shown at the top of the status page
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverallStatus {
+    Operational,
+    Maintenance,
+    Degraded,
+}
+
+/// Everything shown on the status page
+#[derive(Clone, Debug, Serialize)]
+pub struct StatusReport {
+    pub status: OverallStatus,
+    pub naan: String,
+    pub started_at: DateTime<Utc>,
+    pub uptime_seconds: i64,
+    pub dependencies: Vec<DependencyHealth>,
+    /// Current and upcoming maintenance windows
+    pub maintenance: Vec<MaintenanceWindow>,
+    /// Open incidents and incidents resolved within the last 30 days, newest first
+    pub incidents: Vec<Incident>,
+}
+
+#[derive(Debug, Default)]
+struct Records {
+    next_id: u64,
+    incidents: Vec<Incident>,
+    maintenance: Vec<MaintenanceWindow>,
+}
+
+/// Tracks uptime, incidents, and maintenance windows for the status page
+#[derive(Debug)]
+pub struct StatusBoard {
+    started_at: DateTime<Utc>,
+    records: RwLock<Records>,
+}
+
+impl Default for StatusBoard {
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+            records: RwLock::new(Records::default()),
+        }
+    }
+}
+
+impl StatusBoard {
+    /// Records a new open incident
+    pub fn open_incident(
+        &self,
+        title: String,
+        description: Option<String>,
+        severity: Severity,
+        recorded_by: String,
+    ) -> Incident {
+        let mut records = self.records.write().expect("status lock poisoned");
+        records.next_id += 1;
+        let incident = Incident {
+            id: records.next_id,
+            title,
+            description,
+            severity,
+            started_at: Utc::now(),
+            resolved_at: None,
+            recorded_by,
+        };
+        records.incidents.push(incident.clone());
+        incident
+    }
+
+    /// Marks an incident as resolved, returning it if it exists
+    pub fn resolve_incident(&self, id: u64) -> Option<Incident> {
+        let mut records = self.records.write().expect("status lock poisoned");
+        let incident = records.incidents.iter_mut().find(|i| i.id == id)?;
+        incident.resolved_at.get_or_insert_with(Utc::now);
+        Some(incident.clone())
+    }
+
+    /// Schedules a maintenance window
+    pub fn schedule_maintenance(
+        &self,
+        description: String,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        recorded_by: String,
+    ) -> MaintenanceWindow {
+        let mut records = self.records.write().expect("status lock poisoned");
+        records.next_id += 1;
+        let window = MaintenanceWindow {
+            id: records.next_id,
+            description,
+            starts_at,
+            ends_at,
+            recorded_by,
+        };
+        records.maintenance.push(window.clone());
+        window
+    }
+
+    /// Cancels a maintenance window, returning whether it existed
+    pub fn cancel_maintenance(&self, id: u64) -> bool {
+        let mut records = self.records.write().expect("status lock poisoned");
+        let before = records.maintenance.len();
+        records.maintenance.retain(|w| w.id != id);
+        records.maintenance.len() != before
+    }
+
+    /// Builds the status report for the given NAAN and dependency health
+    pub fn report(&self, naan: &str, dependencies: Vec<DependencyHealth>) -> StatusReport {
+        let now = Utc::now();
+        let records = self.records.read().expect("status lock poisoned");
+
+        let maintenance: Vec<MaintenanceWindow> = records
+            .maintenance
+            .iter()
+            .filter(|w| w.ends_at > now)
+            .cloned()
+            .collect();
+
+        let cutoff = now - Duration::days(RECENT_INCIDENT_DAYS);
+        let mut incidents: Vec<Incident> = records
+            .incidents
+            .iter()
+            .filter(|i| i.resolved_at.is_none_or(|resolved| resolved > cutoff))
+            .cloned()
+            .collect();
+        incidents.sort_by_key(|i| std::cmp::Reverse(i.started_at));
+
+        let status = if maintenance.iter().any(|w| w.is_active_at(now)) {
+            OverallStatus::Maintenance
+        } else if incidents.iter().any(|i| i.resolved_at.is_none())
+            || dependencies.iter().any(|d| d.status == "failing")
+        {
+            OverallStatus::Degraded
+        } else {
+            OverallStatus::Operational
+        };
+
+        StatusReport {
+            status,
+            naan: naan.to_string(),
+            started_at: self.started_at,
+            uptime_seconds: (now - self.started_at).num_seconds(),
+            dependencies,
+            maintenance,
+            incidents,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::Views;
+
+    #[test]
+    fn test_report_is_operational_by_default() {
+        let board = StatusBoard::default();
+        let report = board.report("12345", Vec::new());
+
+        assert_eq!(report.status, OverallStatus::Operational);
+        assert!(report.incidents.is_empty());
+        assert!(report.uptime_seconds >= 0);
+    }
+
+    #[test]
+    fn test_open_incident_degrades_until_resolved() {
+        let board = StatusBoard::default();
+        let incident = board.open_incident(
+            "Resolver latency".to_string(),
+            None,
+            Severity::Minor,
+            "alice".to_string(),
+        );
+        assert_eq!(
+            board.report("12345", Vec::new()).status,
+            OverallStatus::Degraded
+        );
+
+        let resolved = board.resolve_incident(incident.id).unwrap();
+        assert!(resolved.resolved_at.is_some());
+
+        let report = board.report("12345", Vec::new());
+        assert_eq!(report.status, OverallStatus::Operational);
+        // Recently resolved incidents stay visible
+        assert_eq!(report.incidents.len(), 1);
+        assert!(board.resolve_incident(999).is_none());
+    }
+
+    #[test]
+    fn test_active_maintenance_window() {
+        let board = StatusBoard::default();
+        let now = Utc::now();
+        let window = board.schedule_maintenance(
+            "Database upgrade".to_string(),
+            now - Duration::minutes(5),
+            now + Duration::minutes(55),
+            "alice".to_string(),
+        );
+        board.schedule_maintenance(
+            "Already over".to_string(),
+            now - Duration::hours(2),
+            now - Duration::hours(1),
+            "alice".to_string(),
+        );
+
+        let report = board.report("12345", Vec::new());
+        assert_eq!(report.status, OverallStatus::Maintenance);
+        assert_eq!(report.maintenance.len(), 1);
+
+        assert!(board.cancel_maintenance(window.id));
+        assert!(!board.cancel_maintenance(window.id));
+        assert_eq!(
+            board.report("12345", Vec::new()).status,
+            OverallStatus::Operational
+        );
+    }
+
+    #[test]
+    fn test_html_escapes_operator_input() {
+        let board = StatusBoard::default();
+        board.open_incident(
+            "<script>alert(1)</script>".to_string(),
+            None,
+            Severity::Critical,
+            "alice".to_string(),
+        );
+
+        let html = Views::default()
+            .render("status.html", board.report("12345", Vec::new()))
+            .unwrap();
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::api_keys::{ApiKey, KeyStore, MemoryKeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, MemoryBindingStore, StorageError};
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore, MemoryDeliveryStore};
+use crate::idempotency::{MemoryReceiptStore, MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerPage, MemoryMintLedger, MintLedger};
+
+/// Persistence for everything the service records: the mint ledger, the bindings of individual
+/// ARKs, webhook deliveries, the receipts of idempotent mints, and managed API keys
+///
+/// Deployments pick a backend through configuration (see [`load_ark_store_from_env`]); the
+/// handlers only see this trait.
+pub trait ArkStore: MintLedger + BindingStore + DeliveryStore + ReceiptStore + KeyStore {
+    /// Short name of the backend, for logs and the status page
+    fn backend(&self) -> &'static str;
+}
+
+/// Store kept in memory, lost when the service restarts
+#[derive(Debug, Default)]
+pub struct MemoryArkStore {
+    ledger: MemoryMintLedger,
+    bindings: MemoryBindingStore,
+    deliveries: MemoryDeliveryStore,
+    receipts: MemoryReceiptStore,
+    keys: MemoryKeyStore,
+}
+
+impl MintLedger for MemoryArkStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        self.ledger.record(shoulder, arks, metadata)
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        self.ledger.annotate(arks, metadata)
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        self.ledger.contains(ark)
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.ledger.minted()
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        self.ledger.minted_on(shoulder)
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        self.ledger.reserve_sequence(shoulder, count)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        self.ledger.sequence_position(shoulder)
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        self.ledger.entries(shoulder, cursor, limit)
+    }
+}
+
+impl BindingStore for MemoryArkStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.bindings.get(canonical)
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        self.bindings.put_all(bindings)
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        self.bindings.put_new(canonical, binding)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.bindings.count()
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        self.bindings.count_prefixed(prefix)
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        self.bindings.bindings(cursor, limit)
+    }
+}
+
+impl DeliveryStore for MemoryArkStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        self.deliveries.save_delivery(delivery)
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        self.deliveries.delivery(id)
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        self.deliveries.deliveries(status)
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.deliveries.prune_deliveries(before)
+    }
+}
+
+impl ReceiptStore for MemoryArkStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        self.receipts.save_receipt(receipt)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        self.receipts.receipt(key)
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.receipts.prune_receipts(before)
+    }
+}
+
+impl KeyStore for MemoryArkStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        self.keys.put_key(key)
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        self.keys.api_keys()
+    }
+}
+
+impl ArkStore for MemoryArkStore {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// Open the ARK store from environment variables
+///
+/// PostgreSQL at `DATABASE_URL` (with the `postgres` feature), otherwise Redis at
+/// `ARK_STORE_REDIS_URL` (with the `redis-store` feature), otherwise the SQLite database at
+/// `ARK_STORE_PATH` (with the `sqlite` feature, on by default), otherwise memory.
+/// `MINT_LEDGER_PATH` is read as an older name of `ARK_STORE_PATH`. Selecting a backend the
+/// service was built without is an error.
+pub fn load_ark_store_from_env() -> Result<Arc<dyn ArkStore>, String> {
+    let path = std::env::var("ARK_STORE_PATH").ok().or_else(|| {
+        let path = std::env::var("MINT_LEDGER_PATH").ok()?;
+        tracing::warn!("MINT_LEDGER_PATH is deprecated, use ARK_STORE_PATH");
+        Some(path)
+    });
+    #[cfg(any(feature = "postgres", not(feature = "redis-store")))]
+    let redis = std::env::var_os("ARK_STORE_REDIS_URL").is_some();
+
+    #[cfg(feature = "postgres")]
+    if let Some(store) = crate::postgres::load_postgres_store_from_env()? {
+        if path.is_some() || redis {
+            tracing::warn!("DATABASE_URL is set, ignoring ARK_STORE_PATH and ARK_STORE_REDIS_URL");
+        }
+        return Ok(store as Arc<dyn ArkStore>);
+    }
+    #[cfg(not(feature = "postgres"))]
+    if std::env::var("DATABASE_URL").is_ok() {
+        return Err(
+            "DATABASE_URL is set, but the service was built without the postgres feature"
+                .to_string(),
+        );
+    }
+
+    #[cfg(feature = "redis-store")]
+    if let Some(store) = crate::redis_store::load_redis_store_from_env()? {
+        if path.is_some() {
+            tracing::warn!("ARK_STORE_REDIS_URL is set, ignoring ARK_STORE_PATH");
+        }
+        return Ok(store as Arc<dyn ArkStore>);
+    }
+    #[cfg(not(feature = "redis-store"))]
+    if redis {
+        return Err(
+            "ARK_STORE_REDIS_URL is set, but the service was built without the redis-store \
+             feature"
+                .to_string(),
+        );
+    }
+
+    match path {
+        #[cfg(feature = "sqlite")]
+        Some(path) => crate::sqlite::SqliteArkStore::open(&path)
+            .map(|store| Arc::new(store) as Arc<dyn ArkStore>)
+            .map_err(|e| format!("Failed to open ARK_STORE_PATH {}: {}", path, e)),
+        #[cfg(not(feature = "sqlite"))]
+        Some(_) => Err(
+            "ARK_STORE_PATH is set, but the service was built without the sqlite feature"
+                .to_string(),
+        ),
+        None => Ok(Arc::new(MemoryArkStore::default())),
+    }
+}
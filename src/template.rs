@@ -0,0 +1,497 @@
+use rand::{Rng, RngCore};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::BETANUMERIC;
+use crate::partition::NodePartition;
+
+/// How a template's blades are drawn, the first character of its mask
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generator {
+    /// `r`: at random
+    Random,
+    /// `s`: in order from a counter, until the mask is used up
+    Sequential,
+    /// `z`: in order from a counter, growing the mask by its first character when it is used up
+    Unbounded,
+}
+
+/// Characters a shoulder's blades are drawn from
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Alphabet {
+    /// `0-9` and `b-z` without vowels, `l` and `y`, as NOID and NCDA check characters expect
+    #[default]
+    Betanumeric,
+    /// `0-9` only
+    Digits,
+    /// `0-9`, `a-z` and `A-Z`; case matters, so blades carry no check character
+    Alphanumeric,
+    /// The given ASCII letters and digits, in counting order
+    Custom(String),
+}
+
+impl Alphabet {
+    pub fn is_betanumeric(&self) -> bool {
+        *self == Alphabet::Betanumeric
+    }
+
+    /// The characters, in the order sequential blades count in
+    pub fn characters(&self) -> &[u8] {
+        match self {
+            Alphabet::Betanumeric => BETANUMERIC,
+            Alphabet::Digits => &BETANUMERIC[..10],
+            Alphabet::Alphanumeric => {
+                b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
+            }
+            Alphabet::Custom(characters) => characters.as_bytes(),
+        }
+    }
+
+    /// Whether NCDA check characters can be computed for blades in this alphabet, which needs
+    /// every character to be betanumeric
+    pub fn supports_check_character(&self) -> bool {
+        self.characters().iter().all(|c| BETANUMERIC.contains(c))
+    }
+
+    /// Checks that a custom alphabet has at least two distinct ASCII letters or digits
+    pub fn validate(&self) -> Result<(), String> {
+        let Alphabet::Custom(characters) = self else {
+            return Ok(());
+        };
+        if let Some(c) = characters.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(format!("{:?} is not an ASCII letter or digit", c));
+        }
+        let mut seen = std::collections::HashSet::new();
+        if let Some(c) = characters.chars().find(|&c| !seen.insert(c)) {
+            return Err(format!("{:?} appears more than once", c));
+        }
+        if characters.len() < 2 {
+            return Err("a custom alphabet needs at least two characters".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Characters one blade position may take, from a template's mask
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaskChar {
+    /// `d`: a digit
+    Digit,
+    /// `e`: an extended digit, any character of the shoulder's alphabet
+    Extended,
+}
+
+impl MaskChar {
+    fn alphabet<'a>(&self, extended: &'a Alphabet) -> &'a [u8] {
+        match self {
+            MaskChar::Digit => &BETANUMERIC[..10],
+            MaskChar::Extended => extended.characters(),
+        }
+    }
+}
+
+/// A NOID mask template describing a shoulder's blades, e.g. `.reedeedk`
+///
+/// After the optional prefix and the `.` come the generator (`r`, `s` or `z`), one mask
+/// character per blade position (`d` or `e`), and an optional final `k` for a check character.
+/// The prefix, if given, is the shoulder.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NoidTemplate {
+    prefix: String,
+    generator: Generator,
+    mask: Vec<MaskChar>,
+    check_character: bool,
+    /// Characters of `e` positions, set by the shoulder rather than the template string
+    alphabet: Alphabet,
+    /// This instance's share of the first position, set by the deployment
+    partition: NodePartition,
+}
+
+impl NoidTemplate {
+    /// Blades of `blade_length` extended digits, the shape of shoulders without a template
+    pub fn flat(generator: Generator, blade_length: usize, check_character: bool) -> Self {
+        Self {
+            prefix: String::new(),
+            generator,
+            mask: vec![MaskChar::Extended; blade_length],
+            check_character,
+            alphabet: Alphabet::Betanumeric,
+            partition: NodePartition::default(),
+        }
+    }
+
+    /// The template with its `e` positions drawn from `alphabet`
+    pub fn with_alphabet(self, alphabet: Alphabet) -> Self {
+        Self { alphabet, ..self }
+    }
+
+    /// The template with random blades starting with `partition`'s share of the first
+    /// position's characters
+    pub fn with_partition(self, partition: NodePartition) -> Self {
+        Self { partition, ..self }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn generator(&self) -> Generator {
+        self.generator
+    }
+
+    /// Whether blades get a check character
+    pub fn check_character(&self) -> bool {
+        self.check_character
+    }
+
+    /// Number of blade positions, excluding the check character
+    pub fn blade_length(&self) -> usize {
+        self.mask.len()
+    }
+
+    /// Number of characters the first blade position is drawn from, before partitioning
+    pub fn first_alphabet_size(&self) -> Option<usize> {
+        self.mask
+            .first()
+            .map(|position| position.alphabet(&self.alphabet).len())
+    }
+
+    /// Number of distinct blades the mask describes, within this instance's partition
+    pub fn namespace_size(&self) -> f64 {
+        self.mask
+            .iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let size = position.alphabet(&self.alphabet).len();
+                if index == 0 {
+                    self.partition.share(size) as f64
+                } else {
+                    size as f64
+                }
+            })
+            .product()
+    }
+
+    /// The template with one more position in front, a repeat of its first mask character
+    pub fn lengthened(&self) -> Self {
+        let mut mask = self.mask.clone();
+        mask.insert(0, mask.first().copied().unwrap_or(MaskChar::Extended));
+        Self {
+            mask,
+            ..self.clone()
+        }
+    }
+
+    /// A random blade, without its check character
+    pub fn random_blade(&self) -> String {
+        self.random_blade_with(&mut rand::rng())
+    }
+
+    /// A blade drawn from `rng`, without its check character; a seeded `rng` draws the same
+    /// blades every time
+    pub fn random_blade_with(&self, rng: &mut impl Rng) -> String {
+        self.mask
+            .iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let alphabet = position.alphabet(&self.alphabet);
+                if index == 0 {
+                    self.partition.first_character(alphabet, rng) as char
+                } else {
+                    alphabet[rng.random_range(0..alphabet.len())] as char
+                }
+            })
+            .collect()
+    }
+
+    /// A blade derived from `digest`, the same for the same digest, without its check
+    /// character: the digest read as a number and written in the mask's digits, least
+    /// significant last
+    ///
+    /// The node partition does not apply, so every node derives the same blade.
+    pub fn derived_blade(&self, digest: &[u8]) -> String {
+        let mut number = digest.to_vec();
+        let mut blade: Vec<char> = self
+            .mask
+            .iter()
+            .rev()
+            .map(|position| {
+                let alphabet = position.alphabet(&self.alphabet);
+                alphabet[divide(&mut number, alphabet.len() as u32) as usize] as char
+            })
+            .collect();
+        blade.reverse();
+        blade.into_iter().collect()
+    }
+
+    /// The blade for a position of a sequential counter, without its check character: the
+    /// position written in the mask's digits, most significant first
+    ///
+    /// `None` once the position no longer fits, unless the generator is `z`, whose mask grows.
+    pub fn sequential_blade(&self, position: u64) -> Option<String> {
+        let mut mask = self.mask.clone();
+        loop {
+            let mut digits = Vec::with_capacity(mask.len());
+            let mut rest = position;
+            for position in mask.iter().rev() {
+                let alphabet = position.alphabet(&self.alphabet);
+                let base = alphabet.len() as u64;
+                digits.push(alphabet[(rest % base) as usize]);
+                rest /= base;
+            }
+            if rest == 0 {
+                digits.reverse();
+                return Some(String::from_utf8(digits).expect("alphabets are ASCII"));
+            }
+            if self.generator != Generator::Unbounded || mask.is_empty() {
+                return None;
+            }
+            mask.insert(0, mask[0]);
+        }
+    }
+}
+
+/// Random bytes drawn from the thread's generator a buffer at a time, for drawing many blades
+/// without going back to the generator for every character
+pub struct BufferedRng {
+    buffer: [u8; 256],
+    position: usize,
+}
+
+impl Default for BufferedRng {
+    fn default() -> Self {
+        Self {
+            buffer: [0; 256],
+            position: 256,
+        }
+    }
+}
+
+impl RngCore for BufferedRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        let mut filled = 0;
+        while filled < dst.len() {
+            if self.position == self.buffer.len() {
+                rand::rng().fill_bytes(&mut self.buffer);
+                self.position = 0;
+            }
+            let taken = (dst.len() - filled).min(self.buffer.len() - self.position);
+            dst[filled..filled + taken]
+                .copy_from_slice(&self.buffer[self.position..self.position + taken]);
+            filled += taken;
+            self.position += taken;
+        }
+    }
+}
+
+/// Divides the big-endian number in `number` by `divisor` in place, returning the remainder
+fn divide(number: &mut [u8], divisor: u32) -> u32 {
+    let mut remainder = 0;
+    for byte in number {
+        let value = remainder << 8 | u32::from(*byte);
+        *byte = (value / divisor) as u8;
+        remainder = value % divisor;
+    }
+    remainder
+}
+
+impl std::str::FromStr for NoidTemplate {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!("Invalid template {:?}: {}", template, reason);
+        let (prefix, mask) = template
+            .rsplit_once('.')
+            .ok_or_else(|| invalid("expected a mask after '.', e.g. .reedeedk"))?;
+        let mut chars = mask.chars();
+        let generator = match chars.next() {
+            Some('r') => Generator::Random,
+            Some('s') => Generator::Sequential,
+            Some('z') => Generator::Unbounded,
+            _ => return Err(invalid("the mask must start with r, s or z")),
+        };
+        let mask_chars = chars.as_str();
+        let (positions, check_character) = match mask_chars.strip_suffix('k') {
+            Some(positions) => (positions, true),
+            None => (mask_chars, false),
+        };
+        let mask = positions
+            .chars()
+            .map(|c| match c {
+                'd' => Ok(MaskChar::Digit),
+                'e' => Ok(MaskChar::Extended),
+                _ => Err(invalid(
+                    "blade positions must be d or e, with k only at the end",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if mask.is_empty() {
+            return Err(invalid("the mask needs at least one d or e"));
+        }
+        Ok(Self {
+            prefix: prefix.to_string(),
+            generator,
+            mask,
+            check_character,
+            alphabet: Alphabet::Betanumeric,
+            partition: NodePartition::default(),
+        })
+    }
+}
+
+impl fmt::Display for NoidTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let generator = match self.generator {
+            Generator::Random => 'r',
+            Generator::Sequential => 's',
+            Generator::Unbounded => 'z',
+        };
+        write!(f, "{}.{}", self.prefix, generator)?;
+        for position in &self.mask {
+            f.write_str(match position {
+                MaskChar::Digit => "d",
+                MaskChar::Extended => "e",
+            })?;
+        }
+        if self.check_character {
+            f.write_str("k")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for NoidTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        template.parse()
+    }
+}
+
+impl From<NoidTemplate> for String {
+    fn from(template: NoidTemplate) -> Self {
+        template.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template() {
+        let template: NoidTemplate = ".reedeedk".parse().unwrap();
+        assert_eq!(template.generator(), Generator::Random);
+        assert_eq!(template.blade_length(), 6);
+        assert!(template.check_character());
+        assert_eq!(template.namespace_size(), 29f64.powi(4) * 100.0);
+        assert_eq!(template.to_string(), ".reedeedk");
+
+        let template: NoidTemplate = "x6.sdd".parse().unwrap();
+        assert_eq!(template.prefix(), "x6");
+        assert!(!template.check_character());
+
+        for invalid in ["reedeedk", ".qeed", ".r", ".rk", ".redkd", ".reex"] {
+            assert!(invalid.parse::<NoidTemplate>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_random_blade_follows_mask() {
+        let template: NoidTemplate = ".rdedk".parse().unwrap();
+        for _ in 0..100 {
+            let blade: Vec<char> = template.random_blade().chars().collect();
+            assert_eq!(blade.len(), 3);
+            assert!(blade[0].is_ascii_digit() && blade[2].is_ascii_digit());
+            assert!(BETANUMERIC.contains(&(blade[1] as u8)));
+        }
+    }
+
+    #[test]
+    fn test_partition_splits_first_position() {
+        let template: NoidTemplate = ".rde".parse().unwrap();
+        let template = template.with_partition(NodePartition::new(1, 4).unwrap());
+        assert_eq!(template.namespace_size(), 3.0 * 29.0);
+        for _ in 0..100 {
+            let blade = template.random_blade();
+            assert!(
+                matches!(blade.as_bytes()[0], b'1' | b'5' | b'9'),
+                "{}",
+                blade
+            );
+        }
+    }
+
+    #[test]
+    fn test_derived_blade_is_stable() {
+        let template: NoidTemplate = ".reedd".parse().unwrap();
+        // 0x0104 = 260 = 2 * 100 + 6 * 10 + 0, then 0 for the betanumeric positions
+        assert_eq!(template.derived_blade(&[0x01, 0x04]), "0260");
+        let blade = template.derived_blade(&[0xff; 32]);
+        assert_eq!(blade, template.derived_blade(&[0xff; 32]));
+        assert_ne!(blade, template.derived_blade(&[0xfe; 32]));
+    }
+
+    #[test]
+    fn test_sequential_blade_counts_in_mask_digits() {
+        let template: NoidTemplate = ".sed".parse().unwrap();
+        assert_eq!(template.sequential_blade(0).unwrap(), "00");
+        assert_eq!(template.sequential_blade(9).unwrap(), "09");
+        assert_eq!(template.sequential_blade(10).unwrap(), "10");
+        assert_eq!(template.sequential_blade(100).unwrap(), "b0");
+        assert_eq!(template.sequential_blade(289).unwrap(), "z9");
+        assert_eq!(template.sequential_blade(290), None);
+
+        let unbounded: NoidTemplate = ".zd".parse().unwrap();
+        assert_eq!(unbounded.sequential_blade(9).unwrap(), "9");
+        assert_eq!(unbounded.sequential_blade(10).unwrap(), "10");
+        assert_eq!(unbounded.sequential_blade(1234).unwrap(), "1234");
+    }
+
+    #[test]
+    fn test_alphabet_replaces_extended_digits() {
+        let template: NoidTemplate = ".sde".parse().unwrap();
+        let template = template.with_alphabet(Alphabet::Custom("xyz".to_string()));
+        assert_eq!(template.namespace_size(), 30.0);
+        assert_eq!(template.sequential_blade(0).unwrap(), "0x");
+        assert_eq!(template.sequential_blade(4).unwrap(), "1y");
+        // The alphabet is not part of the template string
+        assert_eq!(template.to_string(), ".sde");
+
+        let digits =
+            NoidTemplate::flat(Generator::Random, 6, false).with_alphabet(Alphabet::Digits);
+        assert!(digits.random_blade().bytes().all(|c| c.is_ascii_digit()));
+        assert_eq!(digits.namespace_size(), 1e6);
+
+        assert!(Alphabet::Digits.supports_check_character());
+        assert!(!Alphabet::Alphanumeric.supports_check_character());
+        assert_eq!(Alphabet::Alphanumeric.characters().len(), 62);
+        for invalid in ["a", "ab-", "abca", ""] {
+            assert!(
+                Alphabet::Custom(invalid.to_string()).validate().is_err(),
+                "{}",
+                invalid
+            );
+        }
+        assert!(
+            Alphabet::Custom("0123456789ABCDEF".to_string())
+                .validate()
+                .is_ok()
+        );
+    }
+}
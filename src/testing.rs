@@ -0,0 +1,337 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::api_keys::{ApiKey, KeyStore};
+use crate::bindings::{Binding, BindingPage, BindingStore, MemoryBindingStore, StorageError};
+use crate::config::AppState;
+use crate::config_document::ConfigStore;
+use crate::deliveries::{Delivery, DeliveryStatus, DeliveryStore};
+use crate::idempotency::{MintReceipt, ReceiptStore};
+use crate::ledger::{LedgerPage, MintLedger};
+use crate::names::ShoulderName;
+use crate::server::create_router;
+use crate::shoulder::Shoulder;
+use crate::store::{ArkStore, MemoryArkStore};
+
+/// Admin API key accepted by [`fixture_state`]
+pub const ADMIN_KEY: &str = "test-admin-key";
+
+/// Mint API key accepted by [`fixture_state`]
+pub const MINT_KEY: &str = "test-mint-key";
+
+/// State with NAAN `12345`, the admin key [`ADMIN_KEY`] (held by `tester`), the mint key
+/// [`MINT_KEY`] (held by `minter`), and two shoulders:
+/// `x6` (with check characters, redirecting to `https://example.org/${value}`) and `b3`
+/// (without, redirecting to `https://beta.example.org/items/${value}`)
+pub fn fixture_state() -> AppState {
+    let shoulders = HashMap::from([
+        (
+            ShoulderName::new("x6").expect("valid shoulder"),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Example Project".to_string(),
+                ..Default::default()
+            },
+        ),
+        (
+            ShoulderName::new("b3").expect("valid shoulder"),
+            Shoulder {
+                route_pattern: "https://beta.example.org/items/${value}".to_string(),
+                project_name: "Beta Project".to_string(),
+                uses_check_character: false,
+                ..Default::default()
+            },
+        ),
+    ]);
+
+    AppState {
+        config_store: ConfigStore::new(shoulders.keys(), "fixture", "testing"),
+        shoulders,
+        admin_keys: HashMap::from([(ADMIN_KEY.to_string(), "tester".to_string())]),
+        mint_keys: HashMap::from([(MINT_KEY.to_string(), "minter".to_string())]),
+        ..Default::default()
+    }
+}
+
+/// In-memory binding store that can be taken down, slowed, and brought back, to exercise
+/// outages
+#[derive(Debug, Default)]
+pub struct SwitchableBindingStore {
+    down: AtomicBool,
+    latency_ms: AtomicU64,
+    inner: MemoryBindingStore,
+}
+
+impl SwitchableBindingStore {
+    /// Makes every call fail (`true`) or succeed again (`false`)
+    pub fn set_down(&self, down: bool) {
+        self.down.store(down, Ordering::Relaxed);
+    }
+
+    /// Makes every call take `latency` before answering
+    pub fn set_latency(&self, latency: Duration) {
+        self.latency_ms
+            .store(latency.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn check(&self) -> Result<(), StorageError> {
+        let latency = self.latency_ms.load(Ordering::Relaxed);
+        if latency > 0 {
+            std::thread::sleep(Duration::from_millis(latency));
+        }
+        if self.down.load(Ordering::Relaxed) {
+            Err(StorageError("connection refused".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl BindingStore for SwitchableBindingStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.check()?;
+        self.inner.get(canonical)
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        self.check()?;
+        self.inner.put_all(bindings)
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        self.check()?;
+        self.inner.put_new(canonical, binding)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.check()?;
+        self.inner.count()
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        self.check()?;
+        self.inner.count_prefixed(prefix)
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        self.check()?;
+        self.inner.bindings(cursor, limit)
+    }
+}
+
+/// What [`InterceptedStore`] runs instead of recording ARKs in its ledger
+type RecordHook = dyn Fn(
+        &MemoryArkStore,
+        &str,
+        &[String],
+        &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError>
+    + Send
+    + Sync;
+
+/// Memory store whose ledger writes go through a hook, to exercise races and failures; every
+/// other call is passed on to the memory store as it is
+#[derive(Default)]
+pub struct InterceptedStore {
+    inner: MemoryArkStore,
+    on_record: Option<Box<RecordHook>>,
+}
+
+impl InterceptedStore {
+    /// A store that lets `hook` record ARKs, given the memory store underneath to write to
+    pub fn on_record(
+        hook: impl Fn(
+            &MemoryArkStore,
+            &str,
+            &[String],
+            &BTreeMap<String, String>,
+        ) -> Result<Vec<String>, StorageError>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self {
+            on_record: Some(Box::new(hook)),
+            ..Default::default()
+        }
+    }
+}
+
+impl MintLedger for InterceptedStore {
+    fn record(
+        &self,
+        shoulder: &str,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, StorageError> {
+        match &self.on_record {
+            Some(hook) => hook(&self.inner, shoulder, arks, metadata),
+            None => self.inner.record(shoulder, arks, metadata),
+        }
+    }
+
+    fn annotate(
+        &self,
+        arks: &[String],
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), StorageError> {
+        self.inner.annotate(arks, metadata)
+    }
+
+    fn contains(&self, ark: &str) -> Result<bool, StorageError> {
+        self.inner.contains(ark)
+    }
+
+    fn minted(&self) -> Result<usize, StorageError> {
+        self.inner.minted()
+    }
+
+    fn minted_on(&self, shoulder: &str) -> Result<Option<usize>, StorageError> {
+        self.inner.minted_on(shoulder)
+    }
+
+    fn reserve_sequence(&self, shoulder: &str, count: u64) -> Result<u64, StorageError> {
+        self.inner.reserve_sequence(shoulder, count)
+    }
+
+    fn sequence_position(&self, shoulder: &str) -> Result<u64, StorageError> {
+        self.inner.sequence_position(shoulder)
+    }
+
+    fn entries(
+        &self,
+        shoulder: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<LedgerPage, StorageError> {
+        self.inner.entries(shoulder, cursor, limit)
+    }
+}
+
+impl BindingStore for InterceptedStore {
+    fn get(&self, canonical: &str) -> Result<Option<Binding>, StorageError> {
+        self.inner.get(canonical)
+    }
+
+    fn put_all(&self, bindings: Vec<(String, Binding)>) -> Result<(), StorageError> {
+        self.inner.put_all(bindings)
+    }
+
+    fn put_new(&self, canonical: &str, binding: &Binding) -> Result<Option<Binding>, StorageError> {
+        self.inner.put_new(canonical, binding)
+    }
+
+    fn count(&self) -> Result<usize, StorageError> {
+        self.inner.count()
+    }
+
+    fn count_prefixed(&self, prefix: &str) -> Result<Option<usize>, StorageError> {
+        self.inner.count_prefixed(prefix)
+    }
+
+    fn bindings(&self, cursor: Option<&str>, limit: usize) -> Result<BindingPage, StorageError> {
+        self.inner.bindings(cursor, limit)
+    }
+}
+
+impl DeliveryStore for InterceptedStore {
+    fn save_delivery(&self, delivery: &Delivery) -> Result<(), StorageError> {
+        self.inner.save_delivery(delivery)
+    }
+
+    fn delivery(&self, id: &str) -> Result<Option<Delivery>, StorageError> {
+        self.inner.delivery(id)
+    }
+
+    fn deliveries(&self, status: Option<DeliveryStatus>) -> Result<Vec<Delivery>, StorageError> {
+        self.inner.deliveries(status)
+    }
+
+    fn prune_deliveries(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.inner.prune_deliveries(before)
+    }
+}
+
+impl ReceiptStore for InterceptedStore {
+    fn save_receipt(&self, receipt: &MintReceipt) -> Result<Option<MintReceipt>, StorageError> {
+        self.inner.save_receipt(receipt)
+    }
+
+    fn receipt(&self, key: &str) -> Result<Option<MintReceipt>, StorageError> {
+        self.inner.receipt(key)
+    }
+
+    fn prune_receipts(&self, before: DateTime<Utc>) -> Result<usize, StorageError> {
+        self.inner.prune_receipts(before)
+    }
+}
+
+impl KeyStore for InterceptedStore {
+    fn put_key(&self, key: &ApiKey) -> Result<(), StorageError> {
+        self.inner.put_key(key)
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKey>, StorageError> {
+        self.inner.api_keys()
+    }
+}
+
+impl ArkStore for InterceptedStore {
+    fn backend(&self) -> &'static str {
+        "intercepted"
+    }
+}
+
+/// The full router served on an ephemeral local port, stopped when dropped
+pub struct TestServer {
+    addr: SocketAddr,
+    client: reqwest::Client,
+    task: JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Serves `state` on `127.0.0.1` with a port picked by the OS
+    pub async fn start(state: AppState) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test listener");
+        let addr = listener.local_addr().expect("test listener address");
+        let app = create_router(Arc::new(state));
+        let task = tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .expect("test server");
+        });
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("build test client");
+
+        Self { addr, client, task }
+    }
+
+    /// Absolute URL of `path` on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// HTTP client that does not follow redirects, so they can be asserted on
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
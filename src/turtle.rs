@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+
+use crate::config_document::Provenance;
+
+/// Linked-data description of an ARK, serialized as Turtle
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArkResource {
+    /// IRI of the resource: the ARK on this resolver, or the bare ARK if the public URL is unknown
+    pub subject: String,
+    /// The canonical ARK
+    pub identifier: String,
+    /// Where the ARK currently resolves
+    pub target: String,
+    /// Lifecycle status of the ARK, e.g. `active`
+    pub status: &'static str,
+    pub project_name: String,
+    pub publisher: Option<String>,
+    /// Who last changed the shoulder's routing, and when
+    pub provenance: Option<Provenance>,
+}
+
+impl ArkResource {
+    /// Serializes the description using the DCMI Terms, schema.org, and PREMIS vocabularies
+    pub fn to_turtle(&self) -> String {
+        let mut statements = vec![
+            "a premis:IntellectualEntity".to_string(),
+            format!("dct:identifier {}", literal(&self.identifier)),
+            format!("schema:url {}", iri(&self.target)),
+            format!("schema:creativeWorkStatus {}", literal(self.status)),
+            format!(
+                "dct:isPartOf [ schema:name {} ]",
+                literal(&self.project_name)
+            ),
+        ];
+        if let Some(publisher) = &self.publisher {
+            statements.push(format!(
+                "dct:publisher [ schema:name {} ]",
+                literal(publisher)
+            ));
+        }
+        if let Some(provenance) = &self.provenance {
+            statements.push(format!("dct:modified {}", date_time(provenance.changed_at)));
+            statements.push(format!(
+                "dct:provenance [ a dct:ProvenanceStatement ; schema:description {} ]",
+                literal(&format!(
+                    "Routing last changed by {} via {}",
+                    provenance.changed_by, provenance.source
+                ))
+            ));
+        }
+
+        format!(
+            "@prefix dct: <http://purl.org/dc/terms/> .\n\
+             @prefix schema: <http://schema.org/> .\n\
+             @prefix premis: <http://www.loc.gov/premis/rdf/v3/> .\n\
+             @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\
+             \n\
+             {}\n    {} .\n",
+            iri(&self.subject),
+            statements.join(" ;\n    ")
+        )
+    }
+}
+
+/// A Turtle IRI reference, percent-encoding characters not allowed in one
+fn iri(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('<');
+    for c in value.chars() {
+        if c.is_control() || c == ' ' || "<>\"{}|^`\\".contains(c) {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded.push('>');
+    encoded
+}
+
+/// A Turtle string literal
+fn literal(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{}\"", escaped)
+}
+
+fn date_time(value: DateTime<Utc>) -> String {
+    format!("\"{}\"^^xsd:dateTime", value.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_turtle() {
+        let resource = ArkResource {
+            subject: "https://ark.example.org/ark:12345/x6np1wh8k".to_string(),
+            identifier: "ark:12345/x6np1wh8k".to_string(),
+            target: "https://example.org/x6np1wh8k?a=1 2".to_string(),
+            status: "active",
+            project_name: "The \"Letters\" Project".to_string(),
+            publisher: Some("Example Library".to_string()),
+            provenance: Some(Provenance {
+                changed_by: "alice".to_string(),
+                changed_at: "2025-01-02T03:04:05Z".parse().unwrap(),
+                source: "admin_api".to_string(),
+            }),
+        };
+
+        let turtle = resource.to_turtle();
+        assert!(turtle.contains("\n<https://ark.example.org/ark:12345/x6np1wh8k>\n"));
+        assert!(turtle.contains("schema:url <https://example.org/x6np1wh8k?a=1%202>"));
+        assert!(turtle.contains(r#"schema:name "The \"Letters\" Project""#));
+        assert!(turtle.contains(r#"dct:modified "2025-01-02T03:04:05+00:00"^^xsd:dateTime"#));
+        assert!(turtle.contains("Routing last changed by alice via admin_api"));
+        assert!(turtle.trim_end().ends_with('.'));
+    }
+}
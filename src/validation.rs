@@ -1,24 +1,99 @@
+use rayon::prelude::*;
+use serde::{Serialize, Serializer};
+
 use crate::ark::parse_ark;
 use crate::check_character::validate_check_character;
 use crate::config::{AppState, BETANUMERIC};
+use crate::i18n::Locale;
+use crate::names::{Naan, ShoulderName};
+use crate::notify::env_parse;
+
+/// Smallest batch validated in parallel unless `VALIDATE_PARALLEL_THRESHOLD` says otherwise;
+/// below it, handing the work to the pool costs more than it saves
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 256;
+
+/// Stable, machine-readable identifier for a validation error or warning
+///
+/// Errors are prefixed `E_`, warnings `W_`. Clients can branch on these instead of matching
+/// the human-readable messages, which may change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationCode {
+    /// The input does not have the `ark:NAAN/shoulder+blade` structure
+    Parse,
+    /// The ARK, its blade or its qualifier is longer than the configured maximum
+    TooLong,
+    /// The shoulder or blade contains non-betanumeric characters
+    Betanumeric,
+    /// The blade contains characters outside its shoulder's configured alphabet
+    Alphabet,
+    /// The NAAN is not the one this service manages
+    NaanMismatch,
+    /// The shoulder is unregistered and no `has_check_character` hint was given
+    ShoulderUnknown,
+    /// The check character does not match the rest of the identifier
+    CheckCharMismatch,
+    /// The shoulder is not registered in this service
+    ShoulderUnregistered,
+    /// The blade is too short to carry a check character
+    BladeTooShort,
+}
+
+impl ValidationCode {
+    /// The code as sent to clients, which is also its message catalog key
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ValidationCode::Parse => "E_PARSE",
+            ValidationCode::TooLong => "E_TOO_LONG",
+            ValidationCode::Betanumeric => "E_BETANUMERIC",
+            ValidationCode::Alphabet => "E_ALPHABET",
+            ValidationCode::NaanMismatch => "E_NAAN_MISMATCH",
+            ValidationCode::ShoulderUnknown => "E_SHOULDER_UNKNOWN",
+            ValidationCode::CheckCharMismatch => "W_CHECKCHAR_MISMATCH",
+            ValidationCode::ShoulderUnregistered => "W_SHOULDER_UNREGISTERED",
+            ValidationCode::BladeTooShort => "W_BLADE_TOO_SHORT",
+        }
+    }
+
+    /// The human-readable message for this code in the given locale
+    pub fn message(self, locale: Locale) -> String {
+        locale.message(self.as_str()).to_string()
+    }
+}
+
+impl Serialize for ValidationCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
 
 /// Result of ARK validation
 #[derive(Debug, Clone, PartialEq)]
 pub struct ValidationResult {
     pub valid: bool,
-    pub naan: Option<String>,
-    pub shoulder: Option<String>,
+    pub naan: Option<Naan>,
+    pub shoulder: Option<ShoulderName>,
     pub blade: Option<String>,
     pub shoulder_registered: Option<bool>,
     pub has_check_character: Option<bool>,
     pub check_character_valid: Option<bool>,
     pub error: Option<String>,
+    pub error_code: Option<ValidationCode>,
     pub warnings: Option<Vec<String>>,
+    /// Codes for `warnings`, in the same order
+    pub warning_codes: Option<Vec<ValidationCode>>,
+    /// The ARK this one is a component of, when its qualifier names one (see
+    /// `Shoulder::components`)
+    pub component_of: Option<String>,
 }
 
 impl ValidationResult {
     /// Creates a validation result for a parsing error
-    pub fn parse_error() -> Self {
+    pub fn parse_error(locale: Locale) -> Self {
+        Self::rejected(ValidationCode::Parse, locale)
+    }
+
+    /// Creates a validation result for an input rejected before its parts were examined
+    pub fn rejected(code: ValidationCode, locale: Locale) -> Self {
         Self {
             valid: false,
             naan: None,
@@ -27,34 +102,72 @@ impl ValidationResult {
             shoulder_registered: None,
             has_check_character: None,
             check_character_valid: None,
-            error: Some("Failed to parse ARK structure".to_string()),
+            error: Some(code.message(locale)),
+            error_code: Some(code),
             warnings: None,
+            warning_codes: None,
+            component_of: None,
         }
     }
 }
 
-/// Validates an ARK identifier
+/// Validates an ARK identifier, with messages in English
 pub fn validate_ark(
     state: &AppState,
     ark: &str,
     has_check_character: Option<bool>,
 ) -> ValidationResult {
+    validate_ark_localized(state, ark, has_check_character, Locale::default())
+}
+
+/// Validates an ARK identifier, with messages in the given locale
+pub fn validate_ark_localized(
+    state: &AppState,
+    ark: &str,
+    has_check_character: Option<bool>,
+    locale: Locale,
+) -> ValidationResult {
+    // Oversized input is turned away before it is parsed or logged
+    if state.input_limits.check_raw(ark).is_err() {
+        return ValidationResult::rejected(ValidationCode::TooLong, locale);
+    }
+
     // Parse ARK
     let Some(parsed) = parse_ark(ark) else {
         tracing::debug!(
             ark = %ark,
             "Validation failed: invalid ARK format"
         );
-        return ValidationResult::parse_error();
+        return ValidationResult::parse_error(locale);
     };
+    if state.input_limits.check_parsed(&parsed).is_err() {
+        return ValidationResult::rejected(ValidationCode::TooLong, locale);
+    }
+
+    // Hyphens are identity-inert (and may be emitted for readability via ARK_FORMAT),
+    // so they are ignored for character and check character validation
+    let blade = parsed.blade.replace('-', "");
 
-    // Validate betanumeric characters in shoulder and blade
-    if !is_betanumeric(&parsed.shoulder) || !is_betanumeric(&parsed.blade) {
+    // Validate betanumeric characters in the shoulder, and the blade against the shoulder's
+    // alphabet, or betanumeric for unregistered shoulders
+    let shoulder_config = state.shoulders.get(&parsed.shoulder);
+    let character_error = if !is_betanumeric(&parsed.shoulder) {
+        Some(ValidationCode::Betanumeric)
+    } else {
+        match shoulder_config {
+            Some(config) if config.alphabet.is_betanumeric() => {
+                (!config.accepts_blade(&blade)).then_some(ValidationCode::Betanumeric)
+            }
+            Some(config) => (!config.accepts_blade(&blade)).then_some(ValidationCode::Alphabet),
+            None => (!is_betanumeric(&blade)).then_some(ValidationCode::Betanumeric),
+        }
+    };
+    if let Some(code) = character_error {
         tracing::debug!(
             ark = %ark,
             shoulder = %parsed.shoulder,
             blade = %parsed.blade,
-            "Validation failed: non-betanumeric characters"
+            "Validation failed: characters outside the alphabet"
         );
         return ValidationResult {
             valid: false,
@@ -64,26 +177,26 @@ pub fn validate_ark(
             shoulder_registered: None,
             has_check_character: None,
             check_character_valid: None,
-            error: Some(
-                "Shoulder and blade must contain only betanumeric characters (0-9, b-z excluding vowels)".to_string()
-            ),
+            error: Some(code.message(locale)),
+            error_code: Some(code),
             warnings: None,
+            warning_codes: None,
+            component_of: None,
         };
     }
 
     // Check if NAAN matches
     let naan_matches = parsed.naan == state.naan;
     let naan_error = if !naan_matches {
-        Some(format!(
-            "NAAN {} does not match configured NAAN {}",
-            parsed.naan, state.naan
+        Some(locale.format(
+            ValidationCode::NaanMismatch.as_str(),
+            &[("naan", &parsed.naan), ("configured", &state.naan)],
         ))
     } else {
         None
     };
 
     // Check if shoulder is registered
-    let shoulder_config = state.shoulders.get(&parsed.shoulder);
     let shoulder_registered = shoulder_config.is_some();
 
     // Determine if check character should be validated
@@ -91,7 +204,7 @@ pub fn validate_ark(
         Some(has_check) => Some(has_check),
         None => {
             // Check shoulder configuration
-            shoulder_config.map(|c| c.uses_check_character)
+            shoulder_config.map(|c| c.check_character())
         }
     };
 
@@ -110,10 +223,11 @@ pub fn validate_ark(
             shoulder_registered: Some(false),
             has_check_character: None,
             check_character_valid: None,
-            error: Some(
-                "Unknown shoulder. Please specify has_check_character parameter to validate unregistered shoulders.".to_string()
-            ),
+            error: Some(ValidationCode::ShoulderUnknown.message(locale)),
+            error_code: Some(ValidationCode::ShoulderUnknown),
             warnings: None,
+            warning_codes: None,
+            component_of: None,
         };
     };
 
@@ -121,41 +235,33 @@ pub fn validate_ark(
     // - At least 1 character is needed for the base identifier
     // - The last character is the check character to validate
     // Example: blade "ab" -> base "a" + check char "b"
-    let (check_character_valid, warnings) = if should_validate_check && parsed.blade.len() > 1 {
-        let identifier_for_check = format!("{}{}", parsed.shoulder, parsed.blade);
+    let (check_character_valid, warning_codes) = if should_validate_check && blade.len() > 1 {
+        let identifier_for_check = format!("{}{}", parsed.shoulder, blade);
         let is_valid = validate_check_character(&identifier_for_check);
 
         let mut warnings_list = Vec::new();
         if !is_valid {
-            warnings_list.push(
-                "Check character validation failed. Either there's an error or this ARK has no check character."
-                    .to_string(),
-            );
+            warnings_list.push(ValidationCode::CheckCharMismatch);
         }
         if !shoulder_registered {
-            warnings_list.push("Shoulder is not registered in the system.".to_string());
+            warnings_list.push(ValidationCode::ShoulderUnregistered);
         }
 
-        (
-            Some(is_valid),
-            if warnings_list.is_empty() {
-                None
-            } else {
-                Some(warnings_list)
-            },
-        )
+        (Some(is_valid), warnings_list)
     } else if !should_validate_check {
-        (Some(true), None)
+        (Some(true), Vec::new())
     } else {
-        (
-            None,
-            Some(vec![
-                "Blade too short for check character validation".to_string(),
-            ]),
-        )
+        (None, vec![ValidationCode::BladeTooShort])
     };
+    let warnings: Vec<String> = warning_codes
+        .iter()
+        .map(|code| code.message(locale))
+        .collect();
 
     let valid = naan_matches && check_character_valid.unwrap_or(true) && shoulder_registered;
+    let component_of = shoulder_config
+        .filter(|config| config.is_component(&parsed.without_inflection()))
+        .map(|_| parsed.base().original);
 
     ValidationResult {
         valid,
@@ -165,11 +271,96 @@ pub fn validate_ark(
         shoulder_registered: Some(shoulder_registered),
         has_check_character: Some(should_validate_check),
         check_character_valid,
+        error_code: naan_error.as_ref().map(|_| ValidationCode::NaanMismatch),
         error: naan_error,
-        warnings,
+        warnings: (!warnings.is_empty()).then_some(warnings),
+        warning_codes: (!warning_codes.is_empty()).then_some(warning_codes),
+        component_of,
+    }
+}
+
+/// Worker threads shared by all requests for validating large batches
+///
+/// Validation is CPU-bound, so large batches are spread over a fixed number of threads instead
+/// of running on (and holding up) the async runtime. Requests beyond the pool's capacity queue.
+pub struct ValidationPool {
+    pool: Option<rayon::ThreadPool>,
+    threshold: usize,
+}
+
+impl Default for ValidationPool {
+    /// Validates every batch serially
+    fn default() -> Self {
+        Self {
+            pool: None,
+            threshold: DEFAULT_PARALLEL_THRESHOLD,
+        }
+    }
+}
+
+impl ValidationPool {
+    /// A pool of `threads` workers for batches of at least `threshold` ARKs; with fewer than
+    /// two threads, batches are validated serially
+    pub fn new(threads: usize, threshold: usize) -> Result<Self, String> {
+        let pool = (threads > 1)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("validate-{}", i))
+                    .build()
+                    .map_err(|e| format!("Failed to start validation threads: {}", e))
+            })
+            .transpose()?;
+        Ok(Self { pool, threshold })
+    }
+
+    /// Number of worker threads, 0 when validating serially
+    pub fn threads(&self) -> usize {
+        self.pool
+            .as_ref()
+            .map_or(0, |pool| pool.current_num_threads())
+    }
+
+    /// Whether a batch of `len` items is spread over the pool
+    pub fn is_parallel(&self, len: usize) -> bool {
+        self.pool.is_some() && len >= self.threshold
+    }
+
+    /// Applies `f` to every item, keeping their order
+    pub async fn map<T, R, F>(&self, items: Vec<T>, f: F) -> Vec<R>
+    where
+        T: Sync + Send + 'static,
+        R: Send + 'static,
+        F: Fn(&T) -> R + Sync + Send + 'static,
+    {
+        match &self.pool {
+            Some(pool) if items.len() >= self.threshold => {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                pool.spawn(move || {
+                    let results = items.par_iter().map(f).collect();
+                    // The request may have been dropped in the meantime
+                    let _ = sender.send(results);
+                });
+                receiver.await.expect("validation worker panicked")
+            }
+            _ => items.iter().map(f).collect(),
+        }
     }
 }
 
+/// Build the validation pool from environment variables
+///
+/// Optional: `VALIDATE_THREADS` (default: the number of CPUs; 0 or 1 validates serially),
+/// `VALIDATE_PARALLEL_THRESHOLD` (default 256).
+pub fn load_validation_pool_from_env() -> Result<ValidationPool, String> {
+    let threads = match env_parse("VALIDATE_THREADS")? {
+        Some(threads) => threads,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let threshold = env_parse("VALIDATE_PARALLEL_THRESHOLD")?.unwrap_or(DEFAULT_PARALLEL_THRESHOLD);
+    ValidationPool::new(threads, threshold)
+}
+
 /// Checks if a string contains only valid betanumeric characters
 fn is_betanumeric(s: &str) -> bool {
     s.bytes().all(|b| BETANUMERIC.contains(&b))
@@ -179,12 +370,13 @@ fn is_betanumeric(s: &str) -> bool {
 mod tests {
     use super::*;
     use crate::shoulder::Shoulder;
+    use crate::template::Alphabet;
     use std::collections::HashMap;
 
     fn create_test_state() -> AppState {
         let mut shoulders = HashMap::new();
         shoulders.insert(
-            "x6".to_string(),
+            "x6".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://example.org/${value}".to_string(),
                 project_name: "Test Project".to_string(),
@@ -192,7 +384,7 @@ mod tests {
             },
         );
         shoulders.insert(
-            "b3".to_string(),
+            "b3".parse().unwrap(),
             Shoulder {
                 route_pattern: "https://beta.org/items/${value}".to_string(),
                 project_name: "Beta Project".to_string(),
@@ -202,10 +394,11 @@ mod tests {
         );
 
         AppState {
-            naan: "12345".to_string(),
+            naan: "12345".parse().unwrap(),
             default_blade_length: 8,
             max_mint_count: 1000,
             shoulders,
+            ..Default::default()
         }
     }
 
@@ -216,14 +409,43 @@ mod tests {
         let result = validate_ark(&state, "ark:/12345/x6np1wh8f", Some(true));
 
         assert!(result.valid);
-        assert_eq!(result.naan, Some("12345".to_string()));
-        assert_eq!(result.shoulder, Some("x6".to_string()));
+        assert_eq!(result.naan.as_deref(), Some("12345"));
+        assert_eq!(result.shoulder.as_deref(), Some("x6"));
         assert_eq!(result.blade, Some("np1wh8f".to_string()));
         assert_eq!(result.shoulder_registered, Some(true));
         assert_eq!(result.check_character_valid, Some(true));
         assert!(result.error.is_none());
     }
 
+    #[test]
+    fn test_validate_reports_components() {
+        let mut state = create_test_state();
+        state
+            .shoulders
+            .get_mut("x6")
+            .unwrap()
+            .components
+            .push("/page*".to_string());
+
+        let result = validate_ark(&state, "ark:/12345/x6np1wh8f/page2", None);
+        assert!(result.valid);
+        assert_eq!(result.component_of.as_deref(), Some("ark:12345/x6np1wh8f"));
+        let result = validate_ark(&state, "ark:/12345/x6np1wh8f/cover.jpg", None);
+        assert!(result.valid);
+        assert!(result.component_of.is_none());
+    }
+
+    #[test]
+    fn test_validate_ignores_hyphens() {
+        let state = create_test_state();
+        // Same ARK as above, hyphenated for readability
+        let result = validate_ark(&state, "ark:/12345/x6np1w-h8f", Some(true));
+
+        assert!(result.valid);
+        assert_eq!(result.blade, Some("np1w-h8f".to_string()));
+        assert_eq!(result.check_character_valid, Some(true));
+    }
+
     #[test]
     fn test_validate_invalid_check_char() {
         let state = create_test_state();
@@ -233,6 +455,10 @@ mod tests {
         assert!(!result.valid);
         assert_eq!(result.check_character_valid, Some(false));
         assert!(result.warnings.is_some());
+        assert_eq!(
+            result.warning_codes,
+            Some(vec![ValidationCode::CheckCharMismatch])
+        );
     }
 
     #[test]
@@ -241,14 +467,15 @@ mod tests {
         let result = validate_ark(&state, "ark:/99999/x6nmkd123", None);
 
         assert!(!result.valid);
-        assert_eq!(result.naan, Some("99999".to_string()));
-        assert_eq!(result.shoulder, Some("x6".to_string()));
+        assert_eq!(result.naan.as_deref(), Some("99999"));
+        assert_eq!(result.shoulder.as_deref(), Some("x6"));
         assert_eq!(result.blade, Some("nmkd123".to_string()));
         assert_eq!(result.shoulder_registered, Some(true)); // x6 is registered
         assert!(result.has_check_character.is_some());
         assert!(result.check_character_valid.is_some());
         assert!(result.error.is_some());
         assert!(result.error.unwrap().contains("does not match"));
+        assert_eq!(result.error_code, Some(ValidationCode::NaanMismatch));
     }
 
     #[test]
@@ -258,6 +485,19 @@ mod tests {
 
         assert!(!result.valid);
         assert_eq!(result.shoulder_registered, Some(false));
+
+        // Without a hint the shoulder's check character policy is unknown
+        let result = validate_ark(&state, "ark:/12345/z9nmkd123", None);
+        assert_eq!(result.error_code, Some(ValidationCode::ShoulderUnknown));
+
+        // With a check character, the unregistered shoulder is reported as a warning
+        let result = validate_ark(&state, "ark:/12345/z9np1wh8k", Some(true));
+        assert!(
+            result
+                .warning_codes
+                .unwrap()
+                .contains(&ValidationCode::ShoulderUnregistered)
+        );
     }
 
     #[test]
@@ -268,6 +508,7 @@ mod tests {
         assert!(!result.valid);
         assert!(result.error.is_some());
         assert_eq!(result.error.unwrap(), "Failed to parse ARK structure");
+        assert_eq!(result.error_code, Some(ValidationCode::Parse));
     }
 
     #[test]
@@ -294,6 +535,30 @@ mod tests {
         assert!(result.warnings.is_some());
         let warnings = result.warnings.unwrap();
         assert!(warnings.iter().any(|w| w.contains("too short")));
+        assert_eq!(
+            result.warning_codes,
+            Some(vec![ValidationCode::BladeTooShort])
+        );
+    }
+
+    #[test]
+    fn test_validate_localized_messages() {
+        let state = create_test_state();
+        let result = validate_ark_localized(&state, "ark:/99999/x6np1wh8x", Some(true), Locale::Fr);
+
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Le NAAN 99999 ne correspond pas au NAAN configuré 12345")
+        );
+        assert_eq!(result.error_code, Some(ValidationCode::NaanMismatch));
+        assert_eq!(
+            result.warnings.unwrap()[0],
+            ValidationCode::CheckCharMismatch.message(Locale::Fr)
+        );
+        assert_eq!(
+            serde_json::to_string(&result.warning_codes).unwrap(),
+            r#"["W_CHECKCHAR_MISMATCH"]"#
+        );
     }
 
     #[test]
@@ -303,6 +568,7 @@ mod tests {
         let result = validate_ark(&state, "ark:/12345/a6nmkd123", None);
 
         assert!(!result.valid);
+        assert_eq!(result.error_code, Some(ValidationCode::Betanumeric));
         assert!(result.error.is_some());
         assert!(result.error.unwrap().contains("betanumeric"));
     }
@@ -339,4 +605,42 @@ mod tests {
         assert!(result.error.is_some());
         assert!(result.error.unwrap().contains("betanumeric"));
     }
+
+    #[test]
+    fn test_validate_uses_shoulder_alphabet() {
+        let mut state = create_test_state();
+        state.shoulders.insert(
+            "n4".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Numbers".to_string(),
+                alphabet: Alphabet::Digits,
+                ..Default::default()
+            },
+        );
+        state.shoulders.insert(
+            "m5".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Mixed Case".to_string(),
+                uses_check_character: false,
+                alphabet: Alphabet::Alphanumeric,
+                ..Default::default()
+            },
+        );
+
+        // Digits, then a betanumeric check character
+        let blade = "20250115";
+        let check = crate::check_character::calculate_check_character(&format!("n4{}", blade));
+        let ark = format!("ark:12345/n4{}{}", blade, check);
+        assert!(validate_ark(&state, &ark, None).valid);
+        let result = validate_ark(&state, "ark:12345/n4202b01159", None);
+        assert!(!result.valid);
+        assert_eq!(result.error_code, Some(ValidationCode::Alphabet));
+
+        assert!(validate_ark(&state, "ark:12345/m5Qa9Ze1", None).valid);
+        // Unregistered shoulders still need betanumeric blades
+        let result = validate_ark(&state, "ark:12345/k7Qa9Ze1", Some(false));
+        assert_eq!(result.error_code, Some(ValidationCode::Betanumeric));
+    }
 }
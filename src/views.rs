@@ -0,0 +1,146 @@
+use minijinja::Environment;
+use serde::Serialize;
+use std::path::Path;
+
+/// Templates built into the binary, each of which can be overridden from `TEMPLATE_DIR`
+const EMBEDDED: &[(&str, &str)] = &[
+    ("layout.html", include_str!("../templates/layout.html")),
+    ("status.html", include_str!("../templates/status.html")),
+    ("info.html", include_str!("../templates/info.html")),
+];
+
+/// Templates without a built-in default, used only when an operator provides them
+const OPTIONAL: &[&str] = &[
+    "error/shoulder_not_found.html",
+    "error/tombstone.html",
+    "error/maintenance.html",
+    "error/invalid_ark.html",
+];
+
+/// HTML templates for every browser-facing page
+///
+/// Values are HTML-escaped automatically. Built-in templates live in `templates/`; a file with
+/// the same relative path in the override directory replaces the built-in one.
+#[derive(Debug)]
+pub struct Views {
+    env: Environment<'static>,
+}
+
+impl Default for Views {
+    fn default() -> Self {
+        Self::new(None).expect("embedded templates are valid")
+    }
+}
+
+impl Views {
+    /// Loads the embedded templates, replaced by any matching file in `override_dir`
+    pub fn new(override_dir: Option<&Path>) -> Result<Self, String> {
+        let mut views = Self {
+            env: Environment::new(),
+        };
+
+        for (name, source) in EMBEDDED {
+            let source = match override_dir.and_then(|dir| read_override(dir, name)) {
+                Some(source) => source?,
+                None => source.to_string(),
+            };
+            views.add_template(name, source)?;
+        }
+        for name in OPTIONAL {
+            if let Some(source) = override_dir.and_then(|dir| read_override(dir, name)) {
+                views.add_template(name, source?)?;
+            }
+        }
+
+        Ok(views)
+    }
+
+    /// Adds or replaces a template
+    pub fn add_template(&mut self, name: &'static str, source: String) -> Result<(), String> {
+        self.env
+            .add_template_owned(name, source)
+            .map_err(|e| format!("Invalid template {}: {}", name, e))
+    }
+
+    /// Whether a template with this name exists
+    pub fn has(&self, name: &str) -> bool {
+        self.env.get_template(name).is_ok()
+    }
+
+    /// Renders a template with the given context
+    pub fn render(&self, name: &str, context: impl Serialize) -> Result<String, String> {
+        self.env
+            .get_template(name)
+            .and_then(|template| template.render(context))
+            .map_err(|e| format!("Failed to render {}: {}", name, e))
+    }
+}
+
+fn read_override(dir: &Path, name: &str) -> Option<Result<String, String>> {
+    let path = dir.join(name);
+    path.exists().then(|| {
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read template {}: {}", path.display(), e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+
+    #[test]
+    fn test_embedded_templates_escape_values() {
+        let views = Views::default();
+        let html = views
+            .render(
+                "info.html",
+                context! {
+                    naan => "12345",
+                    shoulders => vec![context! {
+                        shoulder => "x6",
+                        project_name => "<script>alert(1)</script>",
+                        example_ark => "ark:12345/x6np1wh8k",
+                    }],
+                },
+            )
+            .unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(!views.has("error/tombstone.html"));
+    }
+
+    #[test]
+    fn test_templates_overridable_from_disk() {
+        let dir = std::env::temp_dir().join(format!("ark-views-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("error")).unwrap();
+        std::fs::write(dir.join("info.html"), "<p>{{ naan }}</p>").unwrap();
+        std::fs::write(dir.join("error/tombstone.html"), "gone").unwrap();
+
+        let views = Views::new(Some(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let views = views.unwrap();
+
+        assert_eq!(
+            views
+                .render("info.html", context! { naan => "12345" })
+                .unwrap(),
+            "<p>12345</p>"
+        );
+        assert!(views.has("error/tombstone.html"));
+        // Templates that weren't overridden keep the built-in version
+        assert!(views.has("status.html"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_templates() {
+        let mut views = Views::default();
+        assert!(
+            views
+                .add_template("info.html", "{% if %}".to_string())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::AppState;
+use crate::names::{Naan, ShoulderName};
+use crate::resourcesync;
+use crate::shoulder::Shoulder;
+
+pub const ARK_CONFIGURATION_PATH: &str = "/.well-known/ark-configuration";
+
+/// Inflections every shoulder answers
+pub const INFLECTIONS: [&str; 3] = ["?", "??", "?info"];
+
+/// Ways to ask for a description of a resolution instead of a redirect
+const RESOLUTION_VIEWS: [&str; 5] = [
+    "?_format=json",
+    "?_redirect=false",
+    "?format=oai_dc",
+    "Accept: application/xml",
+    "Accept: text/turtle",
+];
+
+/// Machine-readable description of this resolver, for clients to discover its capabilities
+#[derive(Debug, Serialize)]
+pub struct ArkConfiguration {
+    pub naans: Vec<Naan>,
+    /// Public base URL of the resolver, when configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolver: Option<String>,
+    /// The service's persistence commitment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+    pub inflections: Vec<&'static str>,
+    pub resolution_views: Vec<&'static str>,
+    /// Endpoints by name, absolute when the public base URL is known
+    pub endpoints: BTreeMap<&'static str, String>,
+    pub shoulders: Vec<ShoulderConfiguration>,
+}
+
+/// How a single shoulder resolves, as published in [`ArkConfiguration`]
+#[derive(Debug, Serialize)]
+pub struct ShoulderConfiguration {
+    pub shoulder: ShoulderName,
+    pub project_name: String,
+    /// Where inflections are answered: `target`, `metadata` (the metadata route), or
+    /// `forwarded` (relayed from the upstream Name Mapping Authority)
+    pub inflections: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+}
+
+impl ShoulderConfiguration {
+    fn describe(name: &ShoulderName, shoulder: &Shoulder) -> Self {
+        let inflections = if shoulder.forward_inflections {
+            "forwarded"
+        } else if shoulder.metadata_route_pattern.is_some() {
+            "metadata"
+        } else {
+            "target"
+        };
+        Self {
+            shoulder: name.clone(),
+            project_name: shoulder.project_name.clone(),
+            inflections,
+            commitment: shoulder.commitment.clone(),
+        }
+    }
+}
+
+impl ArkConfiguration {
+    pub fn describe(state: &AppState) -> Self {
+        let base = state.public_base_url.as_deref().unwrap_or_default();
+        let public_mint = state.public_mint.as_ref().map(|_| {
+            (
+                "public_mint",
+                crate::public_mint::PUBLIC_MINT_PATH.to_string(),
+            )
+        });
+        let shorten = state
+            .shortener
+            .as_ref()
+            .map(|_| ("shorten", crate::shorten::SHORTEN_PATH.to_string()));
+        let endpoints = [
+            ("info", "/api/v1/info".to_string()),
+            ("mint", "/api/v1/mint".to_string()),
+            ("mint_stream", "/api/v1/mint/stream".to_string()),
+            ("mint_and_bind", "/api/v1/mint-and-bind".to_string()),
+            ("register", "/api/v1/register".to_string()),
+            ("import", "/api/v1/import".to_string()),
+            ("export_arks", "/api/v1/export/arks".to_string()),
+            ("shoulder_schema", "/api/v1/shoulders/schema".to_string()),
+            (
+                "shoulder_capacity",
+                "/api/v1/shoulders/{shoulder}/capacity".to_string(),
+            ),
+            (
+                "shoulder_examples",
+                "/api/v1/shoulders/{shoulder}/example".to_string(),
+            ),
+            ("validate", "/api/v1/validate".to_string()),
+            ("compare", "/api/v1/compare".to_string()),
+            (
+                "resolve",
+                format!("/ark:{}/{{shoulder}}{{blade}}", state.naan),
+            ),
+            ("status", format!("/ark:{}/servicestatus", state.naan)),
+            ("resourcesync", resourcesync::DESCRIPTION_PATH.to_string()),
+        ]
+        .into_iter()
+        .chain(public_mint)
+        .chain(shorten)
+        .map(|(name, path)| (name, format!("{}{}", base, path)))
+        .collect();
+
+        let mut shoulders: Vec<ShoulderConfiguration> = state
+            .shoulders
+            .iter()
+            .filter(|(_, shoulder)| shoulder.public)
+            .map(|(name, shoulder)| ShoulderConfiguration::describe(name, shoulder))
+            .collect();
+        shoulders.sort_by(|a, b| a.shoulder.cmp(&b.shoulder));
+
+        Self {
+            naans: vec![state.naan.clone()],
+            resolver: state.public_base_url.clone(),
+            commitment: state.commitment.clone(),
+            inflections: INFLECTIONS.to_vec(),
+            resolution_views: RESOLUTION_VIEWS.to_vec(),
+            endpoints,
+            shoulders,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_describe() {
+        let mut shoulders = HashMap::new();
+        shoulders.insert(
+            "x6".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Test Project".to_string(),
+                forward_inflections: true,
+                commitment: Some("Kept for 25 years".to_string()),
+                ..Default::default()
+            },
+        );
+        shoulders.insert(
+            "b3".parse().unwrap(),
+            Shoulder {
+                route_pattern: "https://example.org/${value}".to_string(),
+                project_name: "Other Project".to_string(),
+                ..Default::default()
+            },
+        );
+        let state = AppState {
+            shoulders,
+            public_base_url: Some("https://ark.example.org".to_string()),
+            commitment: Some("Identifiers are never reassigned".to_string()),
+            ..Default::default()
+        };
+
+        let configuration = ArkConfiguration::describe(&state);
+        assert_eq!(configuration.naans, vec!["12345"]);
+        assert_eq!(
+            configuration.endpoints["status"],
+            "https://ark.example.org/ark:12345/servicestatus"
+        );
+        assert_eq!(configuration.shoulders[0].shoulder, "b3");
+        assert_eq!(configuration.shoulders[0].inflections, "target");
+        assert_eq!(configuration.shoulders[1].inflections, "forwarded");
+        assert_eq!(
+            configuration.shoulders[1].commitment.as_deref(),
+            Some("Kept for 25 years")
+        );
+    }
+}
@@ -0,0 +1,1357 @@
+use ark_service::bindings::Bindings;
+use ark_service::config::AppState;
+use ark_service::http_client::HttpClients;
+use ark_service::public_mint::{PUBLIC_MINT_PATH, PublicMint};
+use ark_service::rate_limit::{RateLimitPolicy, RateLimiter};
+use ark_service::read_only::CONFIGURATION;
+use ark_service::replication::{HttpStandby, ReplicatedStore, Replication, ReplicationPolicy};
+use ark_service::secrets::Secret;
+use ark_service::shorten::{SHORTEN_PATH, Shortener};
+use ark_service::snapshot::SnapshotDir;
+use ark_service::testing::{
+    ADMIN_KEY, MINT_KEY, SwitchableBindingStore, TestServer, fixture_state,
+};
+use reqwest::{StatusCode, header};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+async fn json_body(response: reqwest::Response) -> Value {
+    serde_json::from_str(&response.text().await.unwrap()).unwrap()
+}
+
+async fn post_json(server: &TestServer, path: &str, body: Value) -> reqwest::Response {
+    server
+        .client()
+        .post(server.url(path))
+        .bearer_auth(MINT_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn mint_validate_resolve() {
+    let server = TestServer::start(fixture_state()).await;
+
+    let minted = post_json(
+        &server,
+        "/api/v1/mint",
+        json!({"shoulder": "x6", "count": 3}),
+    )
+    .await;
+    assert_eq!(minted.status(), StatusCode::OK);
+    let minted = json_body(minted).await;
+    let arks: Vec<String> = minted["arks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ark| ark.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(arks.len(), 3);
+
+    let validated = post_json(&server, "/api/v1/validate", json!({ "arks": arks })).await;
+    let validated = json_body(validated).await;
+    for result in validated["results"].as_array().unwrap() {
+        assert_eq!(result["valid"], true, "{}", result);
+        assert_eq!(result["check_character_valid"], true);
+    }
+
+    for ark in &arks {
+        let response = server
+            .client()
+            .get(server.url(&format!("/{}", ark)))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        let blade = ark.strip_prefix("ark:12345/").unwrap();
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            format!("https://example.org/{}", blade).as_str()
+        );
+    }
+}
+
+#[tokio::test]
+async fn resolution_views() {
+    let server = TestServer::start(fixture_state()).await;
+
+    let description = server
+        .client()
+        .get(server.url("/ark:12345/b3abc/page2?_format=json"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(description.status(), StatusCode::OK);
+    let description = json_body(description).await;
+    assert_eq!(
+        description["target"],
+        "https://beta.example.org/items/b3abc/page2"
+    );
+    assert_eq!(description["redirect_status"], 302);
+
+    let turtle = server
+        .client()
+        .get(server.url("/ark:12345/b3abc"))
+        .header(header::ACCEPT, "text/turtle")
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        turtle.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/turtle")
+    );
+    assert!(
+        turtle
+            .text()
+            .await
+            .unwrap()
+            .contains("a premis:IntellectualEntity")
+    );
+
+    let status = server
+        .client()
+        .get(server.url("/ark:12345/servicestatus"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(status.text().await.unwrap(), "OK");
+}
+
+#[tokio::test]
+async fn uppercase_labels_resolve() {
+    let server = TestServer::start(fixture_state()).await;
+
+    for path in [
+        "/ARK:12345/b3abc",
+        "/Ark:/12345/b3abc",
+        "/ARK:%2F12345/b3abc",
+    ] {
+        let response = server.client().get(server.url(path)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND, "{}", path);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://beta.example.org/items/b3abc"
+        );
+    }
+
+    let status = server
+        .client()
+        .get(server.url("/ARK:12345/servicestatus"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(status.text().await.unwrap(), "OK");
+
+    let missing = server
+        .client()
+        .get(server.url("/nothing-here"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn localized_errors() {
+    let server = TestServer::start(fixture_state()).await;
+
+    let not_found = server
+        .client()
+        .get(server.url("/ark:12345/zz9test"))
+        .header(header::ACCEPT_LANGUAGE, "fr-CA, en;q=0.5")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+    assert_eq!(not_found.headers()[header::CONTENT_LANGUAGE], "fr");
+    assert_eq!(not_found.text().await.unwrap(), "Préfixe introuvable");
+
+    let not_allowed = server
+        .client()
+        .post(server.url("/ark:12345/x6abc"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(not_allowed.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(not_allowed.headers()[header::ALLOW], "GET, HEAD");
+}
+
+#[tokio::test]
+async fn slow_storage_answers_gateway_timeout_within_the_deadline() {
+    let store = Arc::new(SwitchableBindingStore::default());
+    store.set_latency(Duration::from_millis(500));
+    let server = TestServer::start(AppState {
+        bindings: Bindings::new(store),
+        ..fixture_state()
+    })
+    .await;
+
+    let started = Instant::now();
+    let timed_out = server
+        .client()
+        .get(server.url("/ark:12345/b3abc"))
+        .header("X-Request-Timeout", "50")
+        .header(header::ACCEPT_LANGUAGE, "fr")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(timed_out.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(started.elapsed() < Duration::from_millis(400));
+    assert_eq!(
+        timed_out.text().await.unwrap(),
+        "La requête n'a pas pu aboutir à temps, veuillez réessayer plus tard"
+    );
+
+    // Without a deadline, the request waits for the store
+    let resolved = server
+        .client()
+        .get(server.url("/ark:12345/b3abc"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resolved.status(), StatusCode::FOUND);
+
+    let invalid = server
+        .client()
+        .get(server.url("/ark:12345/b3abc"))
+        .header("X-Request-Timeout", "soon")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn standby_receives_replicated_mints_and_bindings() {
+    // Standbys stay read-only until they take over
+    let standby_state = fixture_state();
+    standby_state.read_only.enable(None, CONFIGURATION);
+    let standby = TestServer::start(standby_state).await;
+
+    let replication = Arc::new(Replication::new(
+        Arc::new(HttpStandby::new(
+            HttpClients::default().client(Duration::from_secs(5)),
+            &standby.url("/admin/api"),
+            Secret::new(ADMIN_KEY.to_string()),
+        )),
+        ReplicationPolicy::default(),
+    ));
+    let mut primary_state = fixture_state();
+    primary_state.store = Arc::new(ReplicatedStore::new(
+        primary_state.store.clone(),
+        replication.clone(),
+    ));
+    primary_state.bindings = Bindings::new(primary_state.store.clone());
+    primary_state.replication = replication.clone();
+    let primary = TestServer::start(primary_state).await;
+
+    let bound = post_json(
+        &primary,
+        "/api/v1/mint-and-bind",
+        json!({"shoulder": "x6", "bindings": [{"target": "https://example.org/report"}]}),
+    )
+    .await;
+    assert_eq!(bound.status(), StatusCode::OK);
+    let ark = json_body(bound).await["arks"][0]["ark"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let status_of = |server: &TestServer| {
+        server
+            .client()
+            .get(server.url("/admin/api/replication"))
+            .bearer_auth(ADMIN_KEY)
+            .send()
+    };
+    let before = json_body(status_of(&primary).await.unwrap()).await;
+    assert_eq!(before["enabled"], true);
+    assert_eq!(before["pending"], 2);
+
+    assert_eq!(replication.flush().await, 2);
+    let after = json_body(status_of(&primary).await.unwrap()).await;
+    assert_eq!(after["pending"], 0);
+    assert_eq!(after["replicated"], 2);
+    assert_eq!(after["lagging"], false);
+
+    let resolved = standby
+        .client()
+        .get(standby.url(&format!("/{}", ark)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resolved.status(), StatusCode::FOUND);
+    assert_eq!(
+        resolved.headers()[header::LOCATION],
+        "https://example.org/report"
+    );
+    let standby_status = json_body(status_of(&standby).await.unwrap()).await;
+    assert_eq!(standby_status["enabled"], false);
+
+    // Changes are only accepted with an admin key of the standby
+    let refused = post_json(
+        &standby,
+        "/admin/api/replication/changes",
+        json!({"changes": []}),
+    )
+    .await;
+    assert_eq!(refused.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn read_only_mode_pauses_writes() {
+    let server = TestServer::start(fixture_state()).await;
+    let read_only = server.url("/admin/api/read-only");
+
+    let enabled = server
+        .client()
+        .put(&read_only)
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"reason": "Storage migration"}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(enabled.status(), StatusCode::OK);
+    let enabled = json_body(enabled).await;
+    assert_eq!(enabled["read_only"], true);
+    assert_eq!(enabled["reason"], "Storage migration");
+    assert_eq!(enabled["changed_by"], "tester");
+
+    let mint = json!({"shoulder": "b3", "count": 1});
+    let refused = post_json(&server, "/api/v1/mint", mint.clone()).await;
+    assert_eq!(refused.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(refused.text().await.unwrap().contains("read-only"));
+    let batch = server
+        .client()
+        .post(server.url("/admin/api/batch"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"operations": []}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(batch.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let restore = server
+        .client()
+        .post(server.url("/admin/api/restore"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"snapshot": "before-move"}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(restore.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Resolution, validation and the status page carry on
+    let resolved = server
+        .client()
+        .get(server.url("/ark:12345/b3abc"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resolved.status(), StatusCode::FOUND);
+    let validated = post_json(
+        &server,
+        "/api/v1/validate",
+        json!({"arks": ["ark:12345/b3abc"]}),
+    )
+    .await;
+    assert_eq!(validated.status(), StatusCode::OK);
+    let status = server
+        .client()
+        .get(server.url("/ark:12345/servicestatus"))
+        .header(header::ACCEPT, "application/json")
+        .send()
+        .await
+        .unwrap();
+    let status = json_body(status).await;
+    let writes = status["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|dependency| dependency["name"] == "writes")
+        .unwrap()
+        .clone();
+    assert_eq!(writes["status"], "disabled");
+    assert!(
+        writes["detail"]
+            .as_str()
+            .unwrap()
+            .ends_with(": Storage migration")
+    );
+
+    let disabled = server
+        .client()
+        .delete(&read_only)
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(json_body(disabled).await, json!({"read_only": false}));
+    let minted = post_json(&server, "/api/v1/mint", mint).await;
+    assert_eq!(minted.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn admin_requires_key() {
+    let server = TestServer::start(fixture_state()).await;
+    let url = server.url("/admin/api/config");
+
+    let anonymous = server.client().get(&url).send().await.unwrap();
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        anonymous.headers()[header::WWW_AUTHENTICATE],
+        "Bearer realm=\"admin\""
+    );
+
+    let exported = server
+        .client()
+        .get(&url)
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(exported.status(), StatusCode::OK);
+    let exported = json_body(exported).await;
+    assert_eq!(
+        exported["shoulders"]["x6"]["provenance"]["changed_by"],
+        "fixture"
+    );
+}
+
+#[tokio::test]
+async fn minting_requires_a_mint_or_admin_key() {
+    let server = TestServer::start(fixture_state()).await;
+    let mint = |key: Option<&'static str>| {
+        let mut request = server
+            .client()
+            .post(server.url("/api/v1/mint"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(json!({"shoulder": "x6"}).to_string());
+        if let Some(key) = key {
+            request = request.bearer_auth(key);
+        }
+        request.send()
+    };
+
+    let anonymous = mint(None).await.unwrap();
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        anonymous.headers()[header::WWW_AUTHENTICATE],
+        "Bearer realm=\"mint\""
+    );
+    assert_eq!(
+        mint(Some("wrong")).await.unwrap().status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(mint(Some(MINT_KEY)).await.unwrap().status(), StatusCode::OK);
+    assert_eq!(
+        mint(Some(ADMIN_KEY)).await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    let bind = server
+        .client()
+        .post(server.url("/api/v1/mint-and-bind"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(
+            json!({"shoulder": "x6", "bindings": [{"target": "https://example.org/a"}]})
+                .to_string(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bind.status(), StatusCode::UNAUTHORIZED);
+
+    let import = server
+        .client()
+        .post(server.url("/api/v1/import?format=ezid"))
+        .header(header::CONTENT_TYPE, "text/csv")
+        .body("_id\nark:12345/x6np1wh8k\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(import.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn managed_keys_can_be_rotated_and_expired() {
+    let server = TestServer::start(fixture_state()).await;
+    let admin = |key: &str, method: reqwest::Method, path: &str, body: Option<Value>| {
+        let mut request = server
+            .client()
+            .request(method, server.url(&format!("/admin/api{}", path)))
+            .bearer_auth(key);
+        if let Some(body) = body {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+        request.send()
+    };
+
+    let created = admin(
+        ADMIN_KEY,
+        reqwest::Method::POST,
+        "/keys",
+        Some(json!({"name": "deploy-bot", "scopes": ["admin"]})),
+    )
+    .await
+    .unwrap();
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created = json_body(created).await;
+    let key = created["key"].as_str().unwrap().to_string();
+    let id = created["id"].as_str().unwrap().to_string();
+    assert!(key.starts_with(created["prefix"].as_str().unwrap()));
+    assert_eq!(created["created_by"], "tester");
+
+    // The new key works on the admin API, and is listed without its secret
+    let listed = admin(&key, reqwest::Method::GET, "/keys", None)
+        .await
+        .unwrap();
+    assert_eq!(listed.status(), StatusCode::OK);
+    let listed = listed.text().await.unwrap();
+    assert!(!listed.contains(&key));
+    assert!(!listed.contains("hash"));
+    let listed: Value = serde_json::from_str(&listed).unwrap();
+    assert_eq!(listed[0]["name"], "deploy-bot");
+    assert_eq!(listed[0]["active"], true);
+
+    // A key without the admin scope is refused
+    let reader = admin(
+        &key,
+        reqwest::Method::POST,
+        "/keys",
+        Some(json!({"name": "reader", "scopes": ["resolve"]})),
+    )
+    .await
+    .unwrap();
+    let reader = json_body(reader).await;
+    let refused = admin(
+        reader["key"].as_str().unwrap(),
+        reqwest::Method::GET,
+        "/keys",
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(refused.status(), StatusCode::UNAUTHORIZED);
+
+    // Rotating without a grace period retires the old key at once
+    let rotated = admin(
+        ADMIN_KEY,
+        reqwest::Method::POST,
+        &format!("/keys/{}/rotate", id),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(rotated.status(), StatusCode::CREATED);
+    let rotated = json_body(rotated).await;
+    assert_eq!(rotated["rotated_from"], id.as_str());
+    let new_key = rotated["key"].as_str().unwrap().to_string();
+    let old = admin(&key, reqwest::Method::GET, "/keys", None)
+        .await
+        .unwrap();
+    assert_eq!(old.status(), StatusCode::UNAUTHORIZED);
+    let new = admin(&new_key, reqwest::Method::GET, "/keys", None)
+        .await
+        .unwrap();
+    assert_eq!(new.status(), StatusCode::OK);
+
+    let expired = admin(
+        ADMIN_KEY,
+        reqwest::Method::POST,
+        &format!("/keys/{}/expire", rotated["id"].as_str().unwrap()),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(json_body(expired).await["active"], false);
+    let new = admin(&new_key, reqwest::Method::GET, "/keys", None)
+        .await
+        .unwrap();
+    assert_eq!(new.status(), StatusCode::UNAUTHORIZED);
+
+    let missing = admin(ADMIN_KEY, reqwest::Method::POST, "/keys/nope/expire", None)
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn calls_are_counted_per_api_key() {
+    let server = TestServer::start(fixture_state()).await;
+    let call = |method: reqwest::Method, path: &str, body: Option<Value>| {
+        let mut request = server
+            .client()
+            .request(method, server.url(path))
+            .bearer_auth(ADMIN_KEY);
+        if let Some(body) = body {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+        request.send()
+    };
+
+    for _ in 0..2 {
+        let minted = call(
+            reqwest::Method::POST,
+            "/api/v1/mint",
+            Some(json!({"shoulder": "x6"})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(minted.status(), StatusCode::OK);
+    }
+    call(
+        reqwest::Method::POST,
+        "/api/v1/validate",
+        Some(json!({"arks": ["ark:12345/x6np1wh8k"]})),
+    )
+    .await
+    .unwrap();
+    // Calls with other keys are not counted
+    post_json(&server, "/api/v1/mint", json!({"shoulder": "x6"})).await;
+
+    let usage = call(reqwest::Method::GET, "/admin/api/keys/tester/usage", None)
+        .await
+        .unwrap();
+    assert_eq!(usage.status(), StatusCode::OK);
+    let usage = json_body(usage).await;
+    assert_eq!(usage["granularity"], "hour");
+    assert_eq!(usage["buckets"].as_array().unwrap().len(), 24);
+    assert_eq!(usage["total"]["mint"], 2);
+    assert_eq!(usage["total"]["validate"], 1);
+    // This call itself is counted as well
+    assert_eq!(usage["total"]["admin"], 1);
+
+    let csv = call(
+        reqwest::Method::GET,
+        "/admin/api/keys/tester/usage?granularity=day&format=csv",
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        csv.headers()[header::CONTENT_TYPE],
+        "text/csv; charset=utf-8"
+    );
+    let csv = csv.text().await.unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "start,mint,validate,resolve,admin,other,total");
+    assert_eq!(rows.len(), 31);
+    assert!(rows[30].ends_with(",2,1,0,2,0,5"), "{}", rows[30]);
+
+    let unknown = call(reqwest::Method::GET, "/admin/api/keys/nobody/usage", None)
+        .await
+        .unwrap();
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rotated_keys_are_counted_apart() {
+    let server = TestServer::start(fixture_state()).await;
+    let call = |key: &str, method: reqwest::Method, path: &str, body: Option<Value>| {
+        let mut request = server
+            .client()
+            .request(method, server.url(path))
+            .bearer_auth(key);
+        if let Some(body) = body {
+            request = request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(body.to_string());
+        }
+        request.send()
+    };
+    let mint = |key: String| async move {
+        let minted = call(
+            &key,
+            reqwest::Method::POST,
+            "/api/v1/mint",
+            Some(json!({"shoulder": "x6"})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(minted.status(), StatusCode::OK);
+    };
+    let minted_with = |id: String| async move {
+        let usage = call(
+            ADMIN_KEY,
+            reqwest::Method::GET,
+            &format!("/admin/api/keys/{}/usage", id),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(usage.status(), StatusCode::OK);
+        let usage = json_body(usage).await;
+        assert_eq!(usage["key"], id.as_str());
+        assert_eq!(usage["name"], "tester");
+        usage["total"]["mint"].as_u64().unwrap()
+    };
+
+    // A managed key held under the same name as the configured admin key
+    let created = call(
+        ADMIN_KEY,
+        reqwest::Method::POST,
+        "/admin/api/keys",
+        Some(json!({"name": "tester", "scopes": ["mint"]})),
+    )
+    .await
+    .unwrap();
+    let created = json_body(created).await;
+    let old_id = created["id"].as_str().unwrap().to_string();
+    mint(created["key"].as_str().unwrap().to_string()).await;
+
+    let rotated = call(
+        ADMIN_KEY,
+        reqwest::Method::POST,
+        &format!("/admin/api/keys/{}/rotate", old_id),
+        Some(json!({"grace_secs": 3600})),
+    )
+    .await
+    .unwrap();
+    let rotated = json_body(rotated).await;
+    let new_id = rotated["id"].as_str().unwrap().to_string();
+    for _ in 0..2 {
+        mint(rotated["key"].as_str().unwrap().to_string()).await;
+    }
+    mint(ADMIN_KEY.to_string()).await;
+
+    assert_eq!(minted_with(old_id).await, 1);
+    assert_eq!(minted_with(new_id).await, 2);
+    assert_eq!(minted_with("tester".to_string()).await, 1);
+}
+
+#[tokio::test]
+async fn admin_ui_tokens_replace_the_api_key() {
+    let server = TestServer::start(fixture_state()).await;
+    let issued = server
+        .client()
+        .post(server.url("/admin/api/token"))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(issued.status(), StatusCode::OK);
+    let set_cookie = issued.headers()[header::SET_COOKIE].to_str().unwrap();
+    assert!(set_cookie.contains("HttpOnly"));
+    assert!(set_cookie.contains("Path=/admin/api"));
+    let issued = json_body(issued).await;
+    let token = issued["token"].as_str().unwrap();
+    let csrf_token = issued["csrf_token"].as_str().unwrap();
+    let cookie = format!("ark_admin_token={}", token);
+    let staged = server.url("/admin/api/config/staged");
+
+    let exported = server
+        .client()
+        .get(server.url("/admin/api/config"))
+        .header(header::COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(exported.status(), StatusCode::OK);
+
+    // State-changing calls need the CSRF token
+    let forged = server
+        .client()
+        .delete(&staged)
+        .header(header::COOKIE, &cookie)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(forged.status(), StatusCode::FORBIDDEN);
+    let discarded = server
+        .client()
+        .delete(&staged)
+        .header(header::COOKIE, &cookie)
+        .header("x-csrf-token", csrf_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(discarded.status(), StatusCode::NOT_FOUND);
+
+    // Tokens cannot renew themselves
+    let renewed = server
+        .client()
+        .post(server.url("/admin/api/token"))
+        .bearer_auth(token)
+        .header("x-csrf-token", csrf_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(renewed.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admin_ui_tokens_end_with_their_managed_key() {
+    let server = TestServer::start(fixture_state()).await;
+    let created = server
+        .client()
+        .post(server.url("/admin/api/keys"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"name": "deploy-bot", "scopes": ["admin"]}).to_string())
+        .send()
+        .await
+        .unwrap();
+    let created = json_body(created).await;
+    let issued = server
+        .client()
+        .post(server.url("/admin/api/token"))
+        .bearer_auth(created["key"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap();
+    let issued = json_body(issued).await;
+    let token = issued["token"].as_str().unwrap();
+    let config = || {
+        server
+            .client()
+            .get(server.url("/admin/api/config"))
+            .bearer_auth(token)
+            .send()
+    };
+    assert_eq!(config().await.unwrap().status(), StatusCode::OK);
+
+    let expired = server
+        .client()
+        .post(server.url(&format!(
+            "/admin/api/keys/{}/expire",
+            created["id"].as_str().unwrap()
+        )))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(expired.status(), StatusCode::OK);
+    assert_eq!(config().await.unwrap().status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn admin_api_can_be_moved_or_disabled() {
+    let server = TestServer::start(AppState {
+        admin_api_path: Some("/internal/admin".to_string()),
+        ..fixture_state()
+    })
+    .await;
+    let moved = server
+        .client()
+        .get(server.url("/internal/admin/config"))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(moved.status(), StatusCode::OK);
+
+    let server = TestServer::start(AppState {
+        admin_api_path: None,
+        ..fixture_state()
+    })
+    .await;
+    let disabled = server
+        .client()
+        .get(server.url("/admin/api/config"))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(disabled.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn clients_over_the_rate_limit_are_rejected() {
+    let server = TestServer::start(AppState {
+        rate_limiter: RateLimiter::local(RateLimitPolicy {
+            requests_per_minute: 1,
+            burst: 2,
+            trust_forwarded_for: false,
+        }),
+        ..fixture_state()
+    })
+    .await;
+    let info = || server.client().get(server.url("/api/v1/info")).send();
+
+    assert_eq!(info().await.unwrap().status(), StatusCode::OK);
+    assert_eq!(info().await.unwrap().status(), StatusCode::OK);
+    let limited = info().await.unwrap();
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers()[header::RETRY_AFTER], "60");
+
+    // Clients sending a key have their own allowance
+    let admin = server
+        .client()
+        .get(server.url("/admin/api/config"))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(admin.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn minted_arks_appear_in_change_list() {
+    let server = TestServer::start(fixture_state()).await;
+
+    let minted = post_json(
+        &server,
+        "/api/v1/mint",
+        json!({"shoulder": "b3", "count": 2}),
+    )
+    .await;
+    let minted = json_body(minted).await;
+
+    let change_list = server
+        .client()
+        .get(server.url("/resourcesync/changelist.xml"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    for ark in minted["arks"].as_array().unwrap() {
+        assert!(change_list.contains(&format!("<loc>/{}</loc>", ark.as_str().unwrap())));
+    }
+    assert_eq!(change_list.matches("change=\"created\"").count(), 2);
+}
+
+#[tokio::test]
+async fn sitemaps_need_a_public_base_url() {
+    let server = TestServer::start(fixture_state()).await;
+    let index = server
+        .client()
+        .get(server.url("/sitemap.xml"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(index.status(), StatusCode::NOT_FOUND);
+
+    let server = TestServer::start(AppState {
+        public_base_url: Some("https://ark.example.org".to_string()),
+        ..fixture_state()
+    })
+    .await;
+    let index = server
+        .client()
+        .get(server.url("/sitemap.xml"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(index.status(), StatusCode::OK);
+    assert!(
+        index
+            .text()
+            .await
+            .unwrap()
+            .contains("<loc>https://ark.example.org/sitemaps/x6</loc>")
+    );
+}
+
+#[tokio::test]
+async fn mint_metadata_is_exported_with_the_arks() {
+    let server = TestServer::start(fixture_state()).await;
+
+    let minted = post_json(
+        &server,
+        "/api/v1/mint",
+        json!({"shoulder": "b3", "count": 2, "metadata": {"requested_by": "catalog-ingest"}}),
+    )
+    .await;
+    assert_eq!(minted.status(), StatusCode::OK);
+    let minted = json_body(minted).await;
+    let plain = post_json(&server, "/api/v1/mint", json!({"shoulder": "b3"})).await;
+    let plain = json_body(plain).await;
+
+    let anonymous = server
+        .client()
+        .get(server.url("/api/v1/export/arks?shoulder=b3&format=jsonl"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+    let export = server
+        .client()
+        .get(server.url("/api/v1/export/arks?shoulder=b3&format=jsonl"))
+        .bearer_auth(ADMIN_KEY)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let entries: Vec<Value> = export
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 3);
+    for entry in entries {
+        if entry["ark"] == plain["arks"][0] {
+            assert!(entry.get("metadata").is_none());
+        } else {
+            assert!(minted["arks"].as_array().unwrap().contains(&entry["ark"]));
+            assert_eq!(entry["metadata"]["requested_by"], "catalog-ingest");
+        }
+    }
+
+    let oversized = post_json(
+        &server,
+        "/api/v1/mint",
+        json!({"shoulder": "b3", "metadata": {"note": "x".repeat(5000)}}),
+    )
+    .await;
+    assert_eq!(oversized.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn streamed_mint_sends_one_ark_per_line() {
+    let server = TestServer::start(AppState {
+        max_mint_count: 4,
+        ..fixture_state()
+    })
+    .await;
+
+    let streamed = post_json(
+        &server,
+        "/api/v1/mint/stream",
+        json!({"shoulder": "x6", "count": 10}),
+    )
+    .await;
+    assert_eq!(streamed.status(), StatusCode::OK);
+    assert_eq!(
+        streamed.headers()[header::CONTENT_TYPE],
+        "application/x-ndjson"
+    );
+    let body = streamed.text().await.unwrap();
+    let mut arks: Vec<String> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(arks.len(), 10);
+    arks.sort();
+    arks.dedup();
+    assert_eq!(arks.len(), 10);
+
+    let keyed = post_json(
+        &server,
+        "/api/v1/mint/stream",
+        json!({"shoulder": "x6", "count": 10, "request_id": "batch-7"}),
+    )
+    .await;
+    assert_eq!(keyed.status(), StatusCode::BAD_REQUEST);
+    let unknown = post_json(&server, "/api/v1/mint/stream", json!({"shoulder": "q9"})).await;
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn public_mint_is_limited_to_the_test_shoulder() {
+    let server = TestServer::start(AppState {
+        public_mint: Some(PublicMint {
+            shoulder: "b3".parse().unwrap(),
+            max_count: 2,
+            rate_limiter: RateLimiter::local(RateLimitPolicy {
+                requests_per_minute: 1,
+                burst: 2,
+                trust_forwarded_for: false,
+            }),
+        }),
+        ..fixture_state()
+    })
+    .await;
+
+    let minted = post_json(
+        &server,
+        PUBLIC_MINT_PATH,
+        json!({"shoulder": "b3", "count": 10}),
+    )
+    .await;
+    assert_eq!(minted.status(), StatusCode::OK);
+    assert_eq!(json_body(minted).await["count"], 2);
+
+    let other = post_json(&server, PUBLIC_MINT_PATH, json!({"shoulder": "x6"})).await;
+    assert_eq!(other.status(), StatusCode::BAD_REQUEST);
+
+    // Two requests used up the burst
+    let limited = post_json(&server, PUBLIC_MINT_PATH, json!({"shoulder": "b3"})).await;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    // The rest of the API is not held to the public mint's limit
+    let info = server
+        .client()
+        .get(server.url("/api/v1/info"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(info.status(), StatusCode::OK);
+}
+
+async fn target_of(server: &TestServer, ark: &str) -> String {
+    let resolved = server
+        .client()
+        .get(server.url(&format!("/{}", ark)))
+        .send()
+        .await
+        .unwrap();
+    resolved.headers()[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn lifecycle_status_applies_to_qualified_and_inflected_requests() {
+    let server = TestServer::start(fixture_state()).await;
+    let status_of = |path: String| {
+        let request = server.client().get(server.url(&path)).send();
+        async move { request.await.unwrap().status() }
+    };
+    let requests = |ark: &str| {
+        [
+            format!("/{}", ark),
+            format!("/{}/page2.pdf", ark),
+            format!("/{}?info", ark),
+            format!("/{}?", ark),
+        ]
+    };
+
+    let reserved = post_json(
+        &server,
+        "/api/v1/mint-and-bind",
+        json!({
+            "shoulder": "x6",
+            "status": "reserved",
+            "bindings": [{"target": "https://example.org/draft"}]
+        }),
+    )
+    .await;
+    assert_eq!(reserved.status(), StatusCode::OK);
+    let reserved = json_body(reserved).await["arks"][0]["ark"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    for path in requests(&reserved) {
+        assert_eq!(
+            status_of(path.clone()).await,
+            StatusCode::NOT_FOUND,
+            "{}",
+            path
+        );
+    }
+
+    let withdrawn = post_json(
+        &server,
+        "/api/v1/mint-and-bind",
+        json!({"shoulder": "x6", "bindings": [{"target": "https://example.org/retracted"}]}),
+    )
+    .await;
+    let withdrawn = json_body(withdrawn).await["arks"][0]["ark"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    for path in requests(&withdrawn) {
+        assert_eq!(status_of(path.clone()).await, StatusCode::FOUND, "{}", path);
+    }
+    let withdraw = server
+        .client()
+        .post(server.url("/admin/api/lifecycle/withdraw"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"ark": withdrawn}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(withdraw.status(), StatusCode::OK);
+    for path in requests(&withdrawn) {
+        assert_eq!(status_of(path.clone()).await, StatusCode::GONE, "{}", path);
+    }
+
+    // An alias passes the qualifier on to the ARK it stands in for
+    let bound = post_json(
+        &server,
+        "/api/v1/mint-and-bind",
+        json!({"shoulder": "x6", "bindings": [{"target": "https://example.org/a"}, {"target": "https://example.org/b"}]}),
+    )
+    .await;
+    let bound = json_body(bound).await;
+    let (alias, target) = (
+        bound["arks"][0]["ark"].as_str().unwrap(),
+        bound["arks"][1]["ark"].as_str().unwrap(),
+    );
+    let aliased = server
+        .client()
+        .post(server.url("/admin/api/batch"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(
+            json!({"operations": [
+                {"op": "set_status", "ark": alias, "status": "alias", "alias_of": target}
+            ]})
+            .to_string(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(aliased.status(), StatusCode::OK);
+    assert_eq!(
+        target_of(&server, &format!("{}/page2.pdf", alias)).await,
+        format!("/{}/page2.pdf", target)
+    );
+    assert_eq!(
+        target_of(&server, &format!("{}?info", alias)).await,
+        format!("/{}?info", target)
+    );
+}
+
+#[tokio::test]
+async fn restore_sets_bindings_back_to_a_snapshot() {
+    let path = std::env::temp_dir().join(format!("ark-http-snapshots-{}", std::process::id()));
+    let mut state = fixture_state();
+    state.snapshots = Some(SnapshotDir::open(&path, Duration::from_secs(1)).unwrap());
+    let server = TestServer::start(state).await;
+    let admin_post = |path: &str, body: Value| {
+        server
+            .client()
+            .post(server.url(path))
+            .bearer_auth(ADMIN_KEY)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+    };
+
+    let bound = post_json(
+        &server,
+        "/api/v1/mint-and-bind",
+        json!({"shoulder": "x6", "bindings": [{"target": "https://example.org/report"}]}),
+    )
+    .await;
+    let ark = json_body(bound).await["arks"][0]["ark"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let taken = admin_post("/admin/api/snapshots", json!({"name": "before-move"}))
+        .await
+        .unwrap();
+    assert_eq!(taken.status(), StatusCode::CREATED);
+    let taken = json_body(taken).await;
+    assert_eq!(taken["taken_by"], "tester");
+    assert_eq!(taken["bindings"], 1);
+    let again = admin_post("/admin/api/snapshots", json!({"name": "before-move"}))
+        .await
+        .unwrap();
+    assert_eq!(again.status(), StatusCode::BAD_REQUEST);
+
+    let moved = admin_post(
+        "/admin/api/batch",
+        json!({"operations": [{"op": "rebind", "ark": ark, "target": "https://example.org/moved"}]}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(moved.status(), StatusCode::OK);
+    assert_eq!(target_of(&server, &ark).await, "https://example.org/moved");
+
+    // Restores are dry runs unless asked otherwise
+    let planned = admin_post("/admin/api/restore", json!({"snapshot": "before-move"}))
+        .await
+        .unwrap();
+    assert_eq!(planned.status(), StatusCode::OK);
+    let planned = json_body(planned).await;
+    assert_eq!(planned["dry_run"], true);
+    assert_eq!(planned["rebound"], 1);
+    assert_eq!(planned["arks"][0]["action"], "rebind");
+    assert_eq!(target_of(&server, &ark).await, "https://example.org/moved");
+
+    let restored = admin_post(
+        "/admin/api/restore",
+        json!({"snapshot": "before-move", "dry_run": false}),
+    )
+    .await
+    .unwrap();
+    assert_eq!(restored.status(), StatusCode::OK);
+    assert_eq!(json_body(restored).await["rebound"], 1);
+    assert_eq!(target_of(&server, &ark).await, "https://example.org/report");
+
+    // Writes resume once the restore is done
+    let minted = post_json(
+        &server,
+        "/api/v1/mint",
+        json!({"shoulder": "x6", "count": 1}),
+    )
+    .await;
+    assert_eq!(minted.status(), StatusCode::OK);
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[tokio::test]
+async fn shortened_urls_resolve_and_keep_their_arks() {
+    let server = TestServer::start(AppState {
+        shortener: Some(Shortener {
+            shoulder: "b3".parse().unwrap(),
+        }),
+        public_base_url: Some("https://n2t.example.org".to_string()),
+        ..fixture_state()
+    })
+    .await;
+    let url = "https://example.org/reports/2026/annual.pdf";
+
+    let anonymous = server
+        .client()
+        .post(server.url(SHORTEN_PATH))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"url": url}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+    let created = post_json(&server, SHORTEN_PATH, json!({"url": url})).await;
+    assert_eq!(created.status(), StatusCode::CREATED);
+    let created = json_body(created).await;
+    let ark = created["ark"].as_str().unwrap().to_string();
+    assert!(ark.starts_with("ark:12345/b3"));
+    assert_eq!(
+        created["short_url"],
+        format!("https://n2t.example.org/{}", ark)
+    );
+    assert_eq!(target_of(&server, &ark).await, url);
+
+    let again = post_json(&server, SHORTEN_PATH, json!({"url": url})).await;
+    assert_eq!(again.status(), StatusCode::OK);
+    assert_eq!(json_body(again).await["ark"], ark.as_str());
+
+    let tombstoned = server
+        .client()
+        .post(server.url("/admin/api/batch"))
+        .bearer_auth(ADMIN_KEY)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json!({"operations": [{"op": "tombstone", "ark": ark}]}).to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(tombstoned.status(), StatusCode::OK);
+    let resolved = server
+        .client()
+        .get(server.url(&format!("/{}", ark)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resolved.status(), StatusCode::GONE);
+    // The URL keeps its tombstoned ARK rather than getting a new one
+    let after = json_body(post_json(&server, SHORTEN_PATH, json!({"url": url})).await).await;
+    assert_eq!(
+        (&after["ark"], &after["status"]),
+        (&json!(ark), &json!("tombstoned"))
+    );
+
+    let minted = post_json(&server, "/api/v1/mint", json!({"shoulder": "b3"})).await;
+    assert_eq!(minted.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,207 @@
+use ark_service::compat::{DIVERGENCES, divergence};
+use ark_service::shoulder::Shoulder;
+use ark_service::testing::{TestServer, fixture_state};
+use reqwest::{StatusCode, header};
+
+/// How N2T.net answered a resolution request
+#[derive(Clone, Copy, Debug)]
+enum Outcome {
+    Redirect(&'static str),
+    Status(u16),
+}
+
+/// A resolution recorded from N2T.net, replayed against a shoulder `x6` with the same rule
+struct Case {
+    name: &'static str,
+    route_pattern: &'static str,
+    path: &'static str,
+    n2t: Outcome,
+    /// What this service does instead, for cases listed in `compat::DIVERGENCES`
+    divergence: Option<(&'static str, Outcome)>,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "value substitution",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k"),
+        divergence: None,
+    },
+    Case {
+        name: "classic ark:/ label",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:/12345/x6np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k"),
+        divergence: None,
+    },
+    Case {
+        name: "qualifier passes through value",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k/c3/page2.pdf",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k/c3/page2.pdf"),
+        divergence: None,
+    },
+    Case {
+        name: "content substitution",
+        route_pattern: "https://example.org/objects/${content}",
+        path: "/ark:12345/x6np1wh8k/page2",
+        n2t: Outcome::Redirect("https://example.org/objects/12345/x6np1wh8k/page2"),
+        divergence: None,
+    },
+    Case {
+        name: "pid substitution",
+        route_pattern: "https://example.org/resolve?id=${pid}",
+        path: "/ark:12345/x6np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/resolve?id=ark:12345/x6np1wh8k"),
+        divergence: None,
+    },
+    Case {
+        name: "prefix and value together",
+        route_pattern: "https://example.org/${prefix}/items/${value}",
+        path: "/ark:12345/x6np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/12345/items/x6np1wh8k"),
+        divergence: None,
+    },
+    Case {
+        name: "rule without variables gets the ARK appended",
+        route_pattern: "https://example.org/resolver/",
+        path: "/ark:12345/x6np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/resolver/ark:12345/x6np1wh8k"),
+        divergence: None,
+    },
+    Case {
+        name: "query string is forwarded",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k?page=2&lang=fr",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k?page=2&lang=fr"),
+        divergence: None,
+    },
+    Case {
+        name: "qualifier with query string",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k/page2?zoom=1",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k/page2?zoom=1"),
+        divergence: None,
+    },
+    Case {
+        name: "info inflection",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k?info",
+        n2t: Outcome::Status(200),
+        divergence: Some((
+            "inflections",
+            Outcome::Redirect("https://example.org/x6np1wh8k?info"),
+        )),
+    },
+    Case {
+        name: "double question mark inflection",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6np1wh8k??",
+        n2t: Outcome::Status(200),
+        divergence: Some((
+            "inflections",
+            Outcome::Redirect("https://example.org/x6np1wh8k??"),
+        )),
+    },
+    Case {
+        name: "hyphens in the blade",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/x6-np1-wh8k",
+        n2t: Outcome::Redirect("https://example.org/x6np1wh8k"),
+        divergence: Some((
+            "hyphens",
+            Outcome::Redirect("https://example.org/x6-np1-wh8k"),
+        )),
+    },
+    Case {
+        name: "shoulder without a rule",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:12345/q9np1wh8k",
+        n2t: Outcome::Redirect("https://example.org/q9np1wh8k"),
+        divergence: Some(("unknown-shoulder", Outcome::Status(404))),
+    },
+    Case {
+        name: "another NAAN",
+        route_pattern: "https://example.org/${value}",
+        path: "/ark:99999/x6np1wh8k",
+        n2t: Outcome::Redirect("https://other-naan.example.net/ark:99999/x6np1wh8k"),
+        divergence: Some(("other-naans", Outcome::Status(400))),
+    },
+];
+
+impl Outcome {
+    fn matches(self, status: StatusCode, location: Option<&str>) -> bool {
+        match self {
+            Outcome::Redirect(target) => status == StatusCode::FOUND && location == Some(target),
+            Outcome::Status(code) => status.as_u16() == code,
+        }
+    }
+}
+
+/// Requests the case's path from a server whose `x6` shoulder has the case's rule
+async fn replay(case: &Case) -> (StatusCode, Option<String>) {
+    let mut state = fixture_state();
+    state.shoulders.insert(
+        "x6".parse().unwrap(),
+        Shoulder {
+            route_pattern: case.route_pattern.to_string(),
+            project_name: "N2T Contract".to_string(),
+            ..Default::default()
+        },
+    );
+    let server = TestServer::start(state).await;
+
+    let response = server
+        .client()
+        .get(server.url(case.path))
+        .send()
+        .await
+        .unwrap();
+    let location = response
+        .headers()
+        .get(header::LOCATION)
+        .map(|value| value.to_str().unwrap().to_string());
+    (response.status(), location)
+}
+
+#[tokio::test]
+async fn matches_recorded_n2t_resolutions() {
+    for case in CASES {
+        let expected = match case.divergence {
+            Some((id, outcome)) => {
+                assert!(
+                    divergence(id).is_some(),
+                    "{}: undocumented divergence {}",
+                    case.name,
+                    id
+                );
+                outcome
+            }
+            None => case.n2t,
+        };
+        let (status, location) = replay(case).await;
+        assert!(
+            expected.matches(status, location.as_deref()),
+            "{}: expected {:?}, got {} {:?} (N2T: {:?})",
+            case.name,
+            expected,
+            status,
+            location,
+            case.n2t
+        );
+    }
+}
+
+#[test]
+fn every_divergence_is_exercised() {
+    for documented in DIVERGENCES {
+        assert!(
+            CASES
+                .iter()
+                .any(|case| matches!(case.divergence, Some((id, _)) if id == documented.id)),
+            "divergence {} has no contract case",
+            documented.id
+        );
+    }
+}